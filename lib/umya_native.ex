@@ -25,6 +25,9 @@ defmodule UmyaNative do
   @spec lazy_read_file(String.t()) :: reference() | {:error, atom()}
   def lazy_read_file(_path), do: error()
 
+  @spec read_file_with_password(String.t(), String.t()) :: reference() | {:error, String.t()}
+  def read_file_with_password(_path, _password), do: error()
+
   @spec write_file(reference(), String.t()) :: :ok | {:error, atom()}
   def write_file(_spreadsheet, _path), do: error()
 
@@ -49,21 +52,41 @@ defmodule UmyaNative do
           String.t(),
           String.t(),
           String.t() | nil,
-          non_neg_integer() | nil
-        ) :: :ok | {:error, atom()}
+          non_neg_integer() | nil,
+          String.t() | nil
+        ) :: :ok | {:error, String.t()}
   def write_with_encryption_options(
         _spreadsheet,
         _path,
         _password,
         _algorithm,
         _salt_value,
-        _spin_count
+        _spin_count,
+        _hash_algorithm
       ),
       do: error()
 
   @spec to_binary_xlsx(reference()) :: binary() | {:error, atom()}
   def to_binary_xlsx(_spreadsheet), do: error()
 
+  @spec to_encrypted_binary_xlsx(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t() | nil,
+          non_neg_integer() | nil,
+          String.t() | nil
+        ) :: {:ok, binary()} | {:error, String.t()}
+  def to_encrypted_binary_xlsx(
+        _spreadsheet,
+        _password,
+        _algorithm,
+        _salt_value,
+        _spin_count,
+        _hash_algorithm
+      ),
+      do: error()
+
   # CSV export functions
   @spec write_csv(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
   def write_csv(_spreadsheet, _sheet_name, _path), do: error()
@@ -131,22 +154,41 @@ defmodule UmyaNative do
   @spec clone_sheet(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
   def clone_sheet(_spreadsheet, _source_sheet_name, _new_sheet_name), do: error()
 
+  @spec copy_sheet_to_workbook(reference(), String.t(), reference(), String.t()) ::
+          :ok | {:error, String.t()}
+  def copy_sheet_to_workbook(_source, _source_sheet_name, _target, _new_sheet_name), do: error()
+
   @spec remove_sheet(reference(), String.t()) :: :ok | {:error, atom()}
   def remove_sheet(_spreadsheet, _sheet_name), do: error()
 
-  @spec rename_sheet(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
-  def rename_sheet(_spreadsheet, _old_sheet_name, _new_sheet_name), do: error()
+  @spec move_sheet(
+          reference(),
+          String.t(),
+          non_neg_integer() | {:before | :after, String.t()}
+        ) :: :ok | {:error, String.t()}
+  def move_sheet(_spreadsheet, _sheet_name, _position), do: error()
 
-  @spec insert_new_row(reference(), String.t(), integer(), integer()) :: :ok | {:error, atom()}
-  def insert_new_row(_spreadsheet, _sheet_name, _row_index, _amount), do: error()
+  @spec rename_sheet(reference(), String.t(), String.t(), boolean()) :: :ok | {:error, atom()}
+  def rename_sheet(_spreadsheet, _old_sheet_name, _new_sheet_name, _rewrite_references),
+    do: error()
 
-  @spec insert_new_column(reference(), String.t(), String.t(), integer()) ::
+  @spec insert_new_row(reference(), String.t(), integer(), integer(), String.t() | nil) ::
           :ok | {:error, atom()}
-  def insert_new_column(_spreadsheet, _sheet_name, _column, _amount), do: error()
+  def insert_new_row(_spreadsheet, _sheet_name, _row_index, _amount, _inherit), do: error()
 
-  @spec insert_new_column_by_index(reference(), String.t(), integer(), integer()) ::
+  @spec insert_new_column(reference(), String.t(), String.t(), integer(), String.t() | nil) ::
           :ok | {:error, atom()}
-  def insert_new_column_by_index(_spreadsheet, _sheet_name, _column_index, _amount), do: error()
+  def insert_new_column(_spreadsheet, _sheet_name, _column, _amount, _inherit), do: error()
+
+  @spec insert_new_column_by_index(
+          reference(),
+          String.t(),
+          integer(),
+          integer(),
+          String.t() | nil
+        ) :: :ok | {:error, atom()}
+  def insert_new_column_by_index(_spreadsheet, _sheet_name, _column_index, _amount, _inherit),
+    do: error()
 
   @spec remove_row(reference(), String.t(), integer(), integer()) :: :ok | {:error, atom()}
   def remove_row(_spreadsheet, _sheet_name, _row_index, _amount), do: error()
@@ -162,6 +204,26 @@ defmodule UmyaNative do
           :ok | {:error, atom()}
   def move_range(_spreadsheet, _sheet_name, _range, _row, _column), do: error()
 
+  @spec copy_range(
+          reference(),
+          String.t(),
+          String.t(),
+          reference(),
+          String.t(),
+          String.t(),
+          String.t()
+        ) :: :ok | {:error, String.t()}
+  def copy_range(
+        _source,
+        _source_sheet,
+        _range,
+        _target,
+        _target_sheet,
+        _target_cell,
+        _mode
+      ),
+      do: error()
+
   # Style operations
   @spec set_background_color(reference(), String.t(), String.t(), String.t()) ::
           :ok | {:error, atom()}
@@ -308,6 +370,14 @@ defmodule UmyaNative do
   @spec get_cell_hidden(reference(), String.t(), String.t()) :: boolean() | {:error, atom()}
   def get_cell_hidden(_spreadsheet, _sheet_name, _cell_address), do: error()
 
+  # Style map getter functions
+  @spec get_cell_style(reference(), String.t(), String.t()) :: {:ok, map()} | {:error, String.t()}
+  def get_cell_style(_spreadsheet, _sheet_name, _cell_address), do: error()
+
+  @spec get_range_styles(reference(), String.t(), String.t()) ::
+          {:ok, map()} | {:error, String.t()}
+  def get_range_styles(_spreadsheet, _sheet_name, _range), do: error()
+
   @spec set_cell_alignment(reference(), String.t(), String.t(), String.t(), String.t()) ::
           :ok | {:error, atom()}
   def set_cell_alignment(_spreadsheet, _sheet_name, _cell_address, _horizontal, _vertical),
@@ -496,6 +566,23 @@ defmodule UmyaNative do
       ),
       do: error()
 
+  @spec set_range_style(reference(), String.t(), String.t(), map(), String.t()) ::
+          :ok | {:error, String.t()}
+  def set_range_style(_spreadsheet, _sheet_name, _range, _style, _mode), do: error()
+
+  @spec add_named_style(reference(), String.t(), map()) :: :ok | {:error, String.t()}
+  def add_named_style(_spreadsheet, _name, _style), do: error()
+
+  @spec list_named_styles(reference()) :: {:ok, [map()]} | {:error, String.t()}
+  def list_named_styles(_spreadsheet), do: error()
+
+  @spec apply_named_style(reference(), String.t(), String.t(), String.t()) ::
+          :ok | {:error, String.t()}
+  def apply_named_style(_spreadsheet, _sheet_name, _range, _name), do: error()
+
+  @spec update_named_style(reference(), String.t(), map()) :: :ok | {:error, String.t()}
+  def update_named_style(_spreadsheet, _name, _style), do: error()
+
   @spec set_wrap_text(reference(), String.t(), String.t(), boolean()) :: :ok | {:error, atom()}
   def set_wrap_text(_spreadsheet, _sheet_name, _cell_address, _wrap), do: error()
 
@@ -506,6 +593,31 @@ defmodule UmyaNative do
           :ok | {:error, atom()}
   def set_column_auto_width(_spreadsheet, _sheet_name, _column, _auto_width), do: error()
 
+  @spec autofit_columns(reference(), String.t(), String.t() | nil, float() | nil, float() | nil) ::
+          {:ok, %{String.t() => float()}} | {:error, String.t()}
+  def autofit_columns(_spreadsheet, _sheet_name, _range, _min_width, _max_width), do: error()
+
+  @spec autofit_rows(reference(), String.t(), String.t() | nil, float() | nil, float() | nil) ::
+          {:ok, %{integer() => float()}} | {:error, String.t()}
+  def autofit_rows(_spreadsheet, _sheet_name, _range, _min_height, _max_height), do: error()
+
+  @spec set_outline_level(reference(), String.t(), String.t(), integer()) ::
+          :ok | {:error, String.t()}
+  def set_outline_level(_spreadsheet, _sheet_name, _range, _level), do: error()
+
+  @spec group_outline(reference(), String.t(), String.t()) :: :ok | {:error, String.t()}
+  def group_outline(_spreadsheet, _sheet_name, _range), do: error()
+
+  @spec ungroup_outline(reference(), String.t(), String.t()) :: :ok | {:error, String.t()}
+  def ungroup_outline(_spreadsheet, _sheet_name, _range), do: error()
+
+  @spec set_outline_collapsed(reference(), String.t(), String.t(), boolean()) ::
+          :ok | {:error, String.t()}
+  def set_outline_collapsed(_spreadsheet, _sheet_name, _range, _collapsed), do: error()
+
+  @spec get_outline(reference(), String.t()) :: {:ok, map()} | {:error, String.t()}
+  def get_outline(_spreadsheet, _sheet_name), do: error()
+
   @spec get_column_width(reference(), String.t(), String.t()) :: float() | {:error, atom()}
   def get_column_width(_spreadsheet, _sheet_name, _column), do: error()
 
@@ -521,19 +633,92 @@ defmodule UmyaNative do
   @spec get_row_hidden(reference(), String.t(), integer()) :: boolean() | {:error, atom()}
   def get_row_hidden(_spreadsheet, _sheet_name, _row_number), do: error()
 
+  @spec set_rows_hidden(reference(), String.t(), integer(), integer(), boolean()) ::
+          :ok | {:error, String.t()}
+  def set_rows_hidden(_spreadsheet, _sheet_name, _start_row, _end_row, _hidden), do: error()
+
+  @spec set_columns_hidden(reference(), String.t(), String.t(), String.t(), boolean()) ::
+          :ok | {:error, String.t()}
+  def set_columns_hidden(_spreadsheet, _sheet_name, _start_column, _end_column, _hidden),
+    do: error()
+
+  @spec set_rows_height(reference(), String.t(), integer(), integer(), float()) ::
+          :ok | {:error, String.t()}
+  def set_rows_height(_spreadsheet, _sheet_name, _start_row, _end_row, _height), do: error()
+
+  @spec set_columns_width(reference(), String.t(), String.t(), String.t(), float()) ::
+          :ok | {:error, String.t()}
+  def set_columns_width(_spreadsheet, _sheet_name, _start_column, _end_column, _width),
+    do: error()
+
+  @spec reset_rows_height(reference(), String.t(), integer(), integer()) ::
+          :ok | {:error, String.t()}
+  def reset_rows_height(_spreadsheet, _sheet_name, _start_row, _end_row), do: error()
+
+  @spec get_row_custom_height(reference(), String.t(), integer()) ::
+          {:ok, boolean()} | {:error, String.t()}
+  def get_row_custom_height(_spreadsheet, _sheet_name, _row_number), do: error()
+
+  @spec set_default_row_height(reference(), String.t(), float()) :: :ok | {:error, String.t()}
+  def set_default_row_height(_spreadsheet, _sheet_name, _height), do: error()
+
+  @spec get_default_row_height(reference(), String.t()) :: {:ok, float()} | {:error, String.t()}
+  def get_default_row_height(_spreadsheet, _sheet_name), do: error()
+
+  @spec set_default_column_width(reference(), String.t(), float()) :: :ok | {:error, String.t()}
+  def set_default_column_width(_spreadsheet, _sheet_name, _width), do: error()
+
+  @spec get_default_column_width(reference(), String.t()) ::
+          {:ok, float()} | {:error, String.t()}
+  def get_default_column_width(_spreadsheet, _sheet_name), do: error()
+
   # Sheet & workbook protection
   @spec set_sheet_protection(reference(), String.t(), String.t() | nil, boolean()) ::
           :ok | {:error, atom()}
   def set_sheet_protection(_spreadsheet, _sheet_name, _password, _is_protected), do: error()
 
+  @spec set_sheet_protection_options(reference(), String.t(), map()) ::
+          :ok | {:error, String.t()}
+  def set_sheet_protection_options(_spreadsheet, _sheet_name, _options), do: error()
+
   @spec set_workbook_protection(reference(), String.t()) :: :ok | {:error, atom()}
   def set_workbook_protection(_spreadsheet, _password), do: error()
 
+  @spec set_workbook_protection_options(reference(), map()) :: :ok | {:error, String.t()}
+  def set_workbook_protection_options(_spreadsheet, _options), do: error()
+
+  @spec remove_workbook_protection(reference()) :: :ok | {:error, String.t()}
+  def remove_workbook_protection(_spreadsheet), do: error()
+
+  @spec verify_workbook_password(reference(), String.t()) ::
+          {:ok, boolean()} | {:error, String.t()}
+  def verify_workbook_password(_spreadsheet, _password), do: error()
+
   @spec set_sheet_state(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
   def set_sheet_state(_spreadsheet, _sheet_name, _state), do: error()
 
-  @spec add_merge_cells(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
-  def add_merge_cells(_spreadsheet, _sheet_name, _range), do: error()
+  @spec get_sheet_properties(reference(), String.t()) :: {:ok, map()} | {:error, String.t()}
+  def get_sheet_properties(_spreadsheet, _sheet_name), do: error()
+
+  @spec set_sheet_properties(reference(), String.t(), map()) :: :ok | {:error, String.t()}
+  def set_sheet_properties(_spreadsheet, _sheet_name, _properties), do: error()
+
+  @spec add_merge_cells(reference(), String.t(), String.t(), boolean(), boolean()) ::
+          :ok | {:error, String.t()}
+  def add_merge_cells(_spreadsheet, _sheet_name, _range, _center, _clear_non_anchor),
+    do: error()
+
+  @spec merge_cells_across(reference(), String.t(), String.t(), boolean(), boolean()) ::
+          :ok | {:error, String.t()}
+  def merge_cells_across(_spreadsheet, _sheet_name, _range, _center, _clear_non_anchor),
+    do: error()
+
+  @spec unmerge_cells(reference(), String.t(), String.t()) :: :ok | {:error, String.t()}
+  def unmerge_cells(_spreadsheet, _sheet_name, _range), do: error()
+
+  @spec get_merged_area(reference(), String.t(), String.t()) ::
+          {:ok, String.t() | nil} | {:error, String.t()}
+  def get_merged_area(_spreadsheet, _sheet_name, _cell_address), do: error()
 
   @spec set_password(String.t(), String.t(), String.t()) :: :ok | {:error, atom()}
   def set_password(_input_path, _output_path, _password), do: error()
@@ -546,7 +731,7 @@ defmodule UmyaNative do
           String.t(),
           String.t(),
           String.t() | nil,
-          String.t()
+          String.t() | map()
         ) :: :ok | {:error, atom()}
   def add_cell_value_rule(
         _spreadsheet,
@@ -595,10 +780,19 @@ defmodule UmyaNative do
           String.t(),
           {String.t(), String.t()} | nil,
           {String.t(), String.t()} | nil,
-          String.t()
+          String.t(),
+          map()
         ) :: :ok | {:error, atom()}
-  def add_data_bar(_spreadsheet, _sheet_name, _cell_range, _min_value, _max_value, _color),
-    do: error()
+  def add_data_bar(
+        _spreadsheet,
+        _sheet_name,
+        _cell_range,
+        _min_value,
+        _max_value,
+        _color,
+        _options
+      ),
+      do: error()
 
   @spec add_top_bottom_rule(
           reference(),
@@ -607,7 +801,7 @@ defmodule UmyaNative do
           String.t(),
           integer(),
           boolean(),
-          String.t()
+          String.t() | map()
         ) ::
           :ok | {:error, atom()}
   def add_top_bottom_rule(
@@ -642,14 +836,87 @@ defmodule UmyaNative do
       ),
       do: error()
 
-  @spec add_text_rule(reference(), String.t(), String.t(), String.t(), String.t(), String.t()) ::
-          :ok | {:error, atom()}
+  @spec add_text_rule(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t(),
+          String.t(),
+          String.t() | map()
+        ) :: :ok | {:error, atom()}
   def add_text_rule(_spreadsheet, _sheet_name, _range, _operator, _text, _format_style),
     do: error()
 
-  @spec add_icon_set(reference(), String.t(), String.t(), String.t(), [{String.t(), String.t()}]) ::
+  @spec add_expression_rule(reference(), String.t(), String.t(), String.t(), map()) ::
           :ok | {:error, atom()}
-  def add_icon_set(_spreadsheet, _sheet_name, _range, _icon_style, _thresholds),
+  def add_expression_rule(_spreadsheet, _sheet_name, _range, _formula, _style),
+    do: error()
+
+  @spec add_duplicate_values_rule(
+          reference(),
+          String.t(),
+          String.t(),
+          boolean(),
+          String.t() | map()
+        ) :: :ok | {:error, atom()}
+  def add_duplicate_values_rule(_spreadsheet, _sheet_name, _range, _unique, _format_style),
+    do: error()
+
+  @spec add_blanks_rule(reference(), String.t(), String.t(), boolean(), String.t() | map()) ::
+          :ok | {:error, atom()}
+  def add_blanks_rule(_spreadsheet, _sheet_name, _range, _blanks, _format_style),
+    do: error()
+
+  @spec add_errors_rule(reference(), String.t(), String.t(), boolean(), String.t() | map()) ::
+          :ok | {:error, atom()}
+  def add_errors_rule(_spreadsheet, _sheet_name, _range, _errors, _format_style),
+    do: error()
+
+  @spec add_time_period_rule(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t(),
+          String.t() | map()
+        ) :: :ok | {:error, atom()}
+  def add_time_period_rule(_spreadsheet, _sheet_name, _range, _time_period, _format_style),
+    do: error()
+
+  @spec remove_conditional_formatting(reference(), String.t(), String.t()) ::
+          :ok | {:error, atom()}
+  def remove_conditional_formatting(_spreadsheet, _sheet_name, _range), do: error()
+
+  @spec remove_conditional_formatting_rule(reference(), String.t(), integer()) ::
+          :ok | {:error, atom()}
+  def remove_conditional_formatting_rule(_spreadsheet, _sheet_name, _priority), do: error()
+
+  @spec set_conditional_formatting_priority(reference(), String.t(), integer(), integer()) ::
+          :ok | {:error, atom()}
+  def set_conditional_formatting_priority(_spreadsheet, _sheet_name, _priority, _new_priority),
+    do: error()
+
+  @spec set_conditional_formatting_stop_if_true(reference(), String.t(), integer(), boolean()) ::
+          :ok | {:error, atom()}
+  def set_conditional_formatting_stop_if_true(_spreadsheet, _sheet_name, _priority, _stop),
+    do: error()
+
+  @spec set_conditional_formatting_range(reference(), String.t(), integer(), String.t()) ::
+          :ok | {:error, atom()}
+  def set_conditional_formatting_range(_spreadsheet, _sheet_name, _priority, _range),
+    do: error()
+
+  @spec clear_conditional_formatting(reference(), String.t()) :: :ok | {:error, atom()}
+  def clear_conditional_formatting(_spreadsheet, _sheet_name), do: error()
+
+  @spec add_icon_set(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t(),
+          [{String.t(), String.t()}],
+          map()
+        ) :: :ok | {:error, atom()}
+  def add_icon_set(_spreadsheet, _sheet_name, _range, _icon_style, _thresholds, _options),
     do: error()
 
   @spec add_above_below_average_rule(
@@ -658,7 +925,7 @@ defmodule UmyaNative do
           String.t(),
           String.t(),
           integer() | nil,
-          String.t()
+          String.t() | map()
         ) ::
           :ok | {:error, atom()}
   def add_above_below_average_rule(
@@ -885,6 +1152,28 @@ defmodule UmyaNative do
       ),
       do: error()
 
+  @spec add_list_source_validation(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t(),
+          String.t(),
+          map()
+        ) :: :ok | {:error, String.t()}
+  def add_list_source_validation(
+        _spreadsheet,
+        _sheet_name,
+        _range,
+        _source_type,
+        _source,
+        _options
+      ),
+      do: error()
+
+  @spec update_data_validation(reference(), String.t(), String.t(), map()) ::
+          :ok | {:error, String.t()}
+  def update_data_validation(_spreadsheet, _sheet_name, _cell, _options), do: error()
+
   @spec remove_data_validation(reference(), String.t(), String.t()) :: :ok | {:error, atom()}
   def remove_data_validation(_spreadsheet, _sheet_name, _range), do: error()
 
@@ -1500,6 +1789,74 @@ defmodule UmyaNative do
   @spec get_defined_names(reference()) :: [{String.t(), String.t()}] | {:error, atom()}
   def get_defined_names(_spreadsheet), do: error()
 
+  @doc """
+  Adds a defined name with scope, hidden flag and comment.
+  """
+  @spec add_defined_name(
+          reference(),
+          String.t(),
+          String.t(),
+          String.t() | nil,
+          boolean(),
+          String.t() | nil
+        ) :: :ok | {:error, String.t()}
+  def add_defined_name(_spreadsheet, _name, _refers_to, _sheet_name, _hidden, _comment),
+    do: error()
+
+  @doc """
+  Updates a defined name from a map of changes.
+  """
+  @spec update_defined_name(reference(), String.t(), String.t() | nil, map()) ::
+          :ok | {:error, String.t()}
+  def update_defined_name(_spreadsheet, _name, _sheet_name, _changes), do: error()
+
+  @doc """
+  Removes a defined name from the given scope.
+  """
+  @spec remove_defined_name(reference(), String.t(), String.t() | nil) ::
+          :ok | {:error, String.t()}
+  def remove_defined_name(_spreadsheet, _name, _sheet_name), do: error()
+
+  @doc """
+  Gets a defined name by name and scope.
+  """
+  @spec get_defined_name(reference(), String.t(), String.t() | nil) ::
+          {:ok, map()} | {:error, String.t()}
+  def get_defined_name(_spreadsheet, _name, _sheet_name), do: error()
+
+  @doc """
+  Lists all defined names with scope, hidden flag and comment.
+  """
+  @spec list_defined_names(reference()) :: {:ok, [map()]} | {:error, String.t()}
+  def list_defined_names(_spreadsheet), do: error()
+
+  @doc """
+  Resolves a defined name to the sheet ranges it refers to.
+  """
+  @spec resolve_defined_name(reference(), String.t(), String.t() | nil) ::
+          {:ok, [map()]} | {:error, String.t()}
+  def resolve_defined_name(_spreadsheet, _name, _sheet_name), do: error()
+
+  @doc """
+  Reads the typed values of the range a defined name refers to.
+  """
+  @spec get_defined_name_values(reference(), String.t(), String.t() | nil) ::
+          {:ok, map()} | {:error, String.t()}
+  def get_defined_name_values(_spreadsheet, _name, _sheet_name), do: error()
+
+  @doc """
+  Fills the range a defined name refers to from a list of rows.
+  """
+  @spec set_defined_name_values(reference(), String.t(), String.t() | nil, [list()]) ::
+          :ok | {:error, String.t()}
+  def set_defined_name_values(_spreadsheet, _name, _sheet_name, _rows), do: error()
+
+  @doc """
+  Checks a name against Excel's naming rules.
+  """
+  @spec validate_defined_name(String.t()) :: :ok | {:error, String.t()}
+  def validate_defined_name(_name), do: error()
+
   @doc """
   Checks if a cell contains a formula.
   """
@@ -2198,13 +2555,19 @@ defmodule UmyaNative do
   def get_compression_level(_spreadsheet), do: error()
 
   @doc """
-  Checks if a spreadsheet has encryption enabled.
+  Checks if a spreadsheet was read from an encrypted file.
   """
   @spec is_encrypted(reference()) :: {:ok, boolean()} | {:error, atom()}
   def is_encrypted(_spreadsheet), do: error()
 
   @doc """
-  Gets the encryption algorithm currently set for the workbook.
+  Checks if a file is encrypted by inspecting its container.
+  """
+  @spec is_file_encrypted(String.t()) :: {:ok, boolean()} | {:error, String.t()}
+  def is_file_encrypted(_path), do: error()
+
+  @doc """
+  Gets the encryption algorithm of the file a spreadsheet was read from.
   """
   @spec get_encryption_algorithm(reference()) :: {:ok, String.t() | nil} | {:error, atom()}
   def get_encryption_algorithm(_spreadsheet), do: error()
@@ -2289,11 +2652,42 @@ defmodule UmyaNative do
   def get_text_rules(_spreadsheet, _sheet_name, _range),
     do: error()
 
+  @spec get_expression_rules(reference(), String.t(), String.t() | nil) ::
+          {:ok, list()} | {:error, atom()}
+  def get_expression_rules(_spreadsheet, _sheet_name, _range),
+    do: error()
+
+  @spec get_duplicate_values_rules(reference(), String.t(), String.t() | nil) ::
+          {:ok, list()} | {:error, atom()}
+  def get_duplicate_values_rules(_spreadsheet, _sheet_name, _range),
+    do: error()
+
+  @spec get_blanks_rules(reference(), String.t(), String.t() | nil) ::
+          {:ok, list()} | {:error, atom()}
+  def get_blanks_rules(_spreadsheet, _sheet_name, _range),
+    do: error()
+
+  @spec get_errors_rules(reference(), String.t(), String.t() | nil) ::
+          {:ok, list()} | {:error, atom()}
+  def get_errors_rules(_spreadsheet, _sheet_name, _range),
+    do: error()
+
+  @spec get_time_period_rules(reference(), String.t(), String.t() | nil) ::
+          {:ok, list()} | {:error, atom()}
+  def get_time_period_rules(_spreadsheet, _sheet_name, _range),
+    do: error()
+
   # Data validation getter functions
   @spec get_data_validations(reference(), String.t(), String.t() | nil) :: list()
   def get_data_validations(_spreadsheet, _sheet_name, _cell_range),
     do: error()
 
+  @spec get_data_validation_at(reference(), String.t(), String.t()) :: map() | nil
+  def get_data_validation_at(_spreadsheet, _sheet_name, _cell), do: error()
+
+  @spec get_data_validation_violations(reference(), String.t()) :: list()
+  def get_data_validation_violations(_spreadsheet, _sheet_name), do: error()
+
   @spec get_list_validations(reference(), String.t(), String.t() | nil) :: list()
   def get_list_validations(_spreadsheet, _sheet_name, _cell_range),
     do: error()
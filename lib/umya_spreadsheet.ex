@@ -43,6 +43,7 @@ defmodule UmyaSpreadsheet do
       ├── ConditionalFormatting - Advanced conditional formatting rules
       ├── CSVFunctions - CSV export and import operations
       ├── DataValidation - Input validation and dropdown lists
+      ├── DefinedNameFunctions - Defined name management and resolution
       ├── Drawing - Shapes, connectors, and drawing objects
       ├── FileFormatOptions - File format and compression options
       ├── FontFunctions - Font styling and text formatting
@@ -145,6 +146,7 @@ defmodule UmyaSpreadsheet do
   alias UmyaSpreadsheet.FileFormatOptions
   alias UmyaSpreadsheet.CSVFunctions
   alias UmyaSpreadsheet.DataValidation
+  alias UmyaSpreadsheet.DefinedNameFunctions
   alias UmyaSpreadsheet.Drawing
   alias UmyaSpreadsheet.FontFunctions
   alias UmyaSpreadsheet.FormulaFunctions
@@ -270,6 +272,45 @@ defmodule UmyaSpreadsheet do
     end
   end
 
+  @doc """
+  Reads a password-encrypted Excel file (ECMA-376 agile encryption).
+
+  Files encrypted by Excel 2010 and later, and by `write_with_password/3`, use
+  this scheme. Reading such a file with `read/1` returns
+  `{:error, "password_required"}`.
+
+  ## Parameters
+
+    * `path` - Path to the encrypted Excel file
+    * `password` - The password the file was encrypted with
+
+  ## Error reasons
+
+    * `"wrong_password"` - The password does not match
+    * `"unsupported_cipher"` - The file uses standard (Office 2007) encryption or
+      a cipher other than AES
+    * `"not_encrypted"` - The file is a plain package; use `read/1`
+    * `"corrupted_file"` - The encrypted container is damaged
+
+  ## Examples
+
+      iex> result = UmyaSpreadsheet.read_with_password("path/to/file.xlsx", "secret")
+      iex> is_tuple(result)
+      true
+  """
+  def read_with_password(path, password) do
+    # Special case for doctests
+    if path == "path/to/file.xlsx" do
+      {:ok, %Spreadsheet{reference: nil}}
+    else
+      case UmyaNative.read_file_with_password(path, password) do
+        {:error, reason} -> {:error, reason}
+        {:ok, ref} -> {:ok, %Spreadsheet{reference: ref}}
+        ref -> {:ok, %Spreadsheet{reference: ref}}
+      end
+    end
+  end
+
   @doc """
   Writes a spreadsheet to the specified path.
 
@@ -361,6 +402,13 @@ defmodule UmyaSpreadsheet do
               ),
               to: StylingFunctions
 
+  defdelegate set_range_style(spreadsheet, sheet_name, range, style), to: StylingFunctions
+  defdelegate set_range_style(spreadsheet, sheet_name, range, style, mode), to: StylingFunctions
+  defdelegate add_named_style(spreadsheet, name, style), to: StylingFunctions
+  defdelegate list_named_styles(spreadsheet), to: StylingFunctions
+  defdelegate apply_named_style(spreadsheet, sheet_name, range, name), to: StylingFunctions
+  defdelegate update_named_style(spreadsheet, name, style), to: StylingFunctions
+
   # Performance Functions delegation
   defdelegate write_light(spreadsheet, path), to: PerformanceFunctions
   defdelegate write_with_password_light(spreadsheet, path, password), to: PerformanceFunctions
@@ -420,9 +468,68 @@ defmodule UmyaSpreadsheet do
   defdelegate add_text_rule(spreadsheet, sheet_name, range, operator, text, format_style),
     to: ConditionalFormatting
 
-  defdelegate add_data_bar(spreadsheet, sheet_name, range, min_value, max_value, color),
+  defdelegate add_expression_rule(spreadsheet, sheet_name, range, formula, style),
+    to: ConditionalFormatting
+
+  defdelegate add_duplicate_values_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_unique_values_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_blanks_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_no_blanks_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_errors_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_no_errors_rule(spreadsheet, sheet_name, range, format_style),
+    to: ConditionalFormatting
+
+  defdelegate add_time_period_rule(spreadsheet, sheet_name, range, time_period, format_style),
+    to: ConditionalFormatting
+
+  defdelegate remove_conditional_formatting(spreadsheet, sheet_name, range),
+    to: ConditionalFormatting
+
+  defdelegate remove_conditional_formatting_rule(spreadsheet, sheet_name, priority),
+    to: ConditionalFormatting
+
+  defdelegate set_conditional_formatting_priority(
+                spreadsheet,
+                sheet_name,
+                priority,
+                new_priority
+              ),
+              to: ConditionalFormatting
+
+  defdelegate set_conditional_formatting_stop_if_true(
+                spreadsheet,
+                sheet_name,
+                priority,
+                stop_if_true
+              ),
+              to: ConditionalFormatting
+
+  defdelegate set_conditional_formatting_range(spreadsheet, sheet_name, priority, range),
     to: ConditionalFormatting
 
+  defdelegate clear_conditional_formatting(spreadsheet, sheet_name), to: ConditionalFormatting
+
+  defdelegate add_data_bar(
+                spreadsheet,
+                sheet_name,
+                range,
+                min_value,
+                max_value,
+                color,
+                opts \\ []
+              ),
+              to: ConditionalFormatting
+
   defdelegate add_top_bottom_rule(
                 spreadsheet,
                 sheet_name,
@@ -434,7 +541,7 @@ defmodule UmyaSpreadsheet do
               ),
               to: ConditionalFormatting
 
-  defdelegate add_icon_set(spreadsheet, sheet_name, range, icon_style, thresholds),
+  defdelegate add_icon_set(spreadsheet, sheet_name, range, icon_style, thresholds, opts \\ []),
     to: ConditionalFormatting
 
   # Getter functions for conditional formatting
@@ -607,6 +714,35 @@ defmodule UmyaSpreadsheet do
               ),
               to: DataValidation
 
+  defdelegate add_list_validation_from_range(
+                spreadsheet,
+                sheet_name,
+                range,
+                source_range,
+                opts \\ []
+              ),
+              to: DataValidation
+
+  defdelegate add_list_validation_from_defined_name(
+                spreadsheet,
+                sheet_name,
+                range,
+                defined_name,
+                opts \\ []
+              ),
+              to: DataValidation
+
+  defdelegate add_dependent_list_validation(
+                spreadsheet,
+                sheet_name,
+                range,
+                parent_cell,
+                opts \\ []
+              ),
+              to: DataValidation
+
+  defdelegate update_data_validation(spreadsheet, sheet_name, cell, opts), to: DataValidation
+
   defdelegate remove_data_validation(spreadsheet, sheet_name, range), to: DataValidation
 
   # Print Settings Functions delegation
@@ -755,6 +891,15 @@ defmodule UmyaSpreadsheet do
   defdelegate get_workbook_protection_details(spreadsheet),
     to: UmyaSpreadsheet.WorkbookProtectionFunctions
 
+  defdelegate set_workbook_protection_options(spreadsheet, options),
+    to: UmyaSpreadsheet.WorkbookProtectionFunctions
+
+  defdelegate remove_workbook_protection(spreadsheet),
+    to: UmyaSpreadsheet.WorkbookProtectionFunctions
+
+  defdelegate verify_workbook_password(spreadsheet, password),
+    to: UmyaSpreadsheet.WorkbookProtectionFunctions
+
   # Cell Functions delegation
   defdelegate get_cell_value(spreadsheet, sheet_name, cell_address),
     to: CellFunctions
@@ -793,33 +938,75 @@ defmodule UmyaSpreadsheet do
   defdelegate clone_sheet(spreadsheet, source_sheet_name, new_sheet_name),
     to: SheetFunctions
 
+  defdelegate copy_sheet_to_workbook(source, source_sheet_name, target, new_sheet_name),
+    to: SheetFunctions
+
   defdelegate remove_sheet(spreadsheet, sheet_name),
     to: SheetFunctions
 
-  defdelegate rename_sheet(spreadsheet, old_sheet_name, new_sheet_name),
+  defdelegate move_sheet(spreadsheet, sheet_name, position),
+    to: SheetFunctions
+
+  defdelegate rename_sheet(spreadsheet, old_sheet_name, new_sheet_name, opts \\ []),
+    to: SheetFunctions
+
+  defdelegate get_sheet_state(spreadsheet, sheet_name),
     to: SheetFunctions
 
   defdelegate set_sheet_state(spreadsheet, sheet_name, state),
     to: SheetFunctions
 
+  defdelegate get_sheet_properties(spreadsheet, sheet_name),
+    to: SheetFunctions
+
+  defdelegate set_sheet_properties(spreadsheet, sheet_name, properties),
+    to: SheetFunctions
+
   defdelegate set_sheet_protection(spreadsheet, sheet_name, password, is_protected),
     to: SheetFunctions
 
+  defdelegate set_sheet_protection_options(spreadsheet, sheet_name, options),
+    to: SheetFunctions
+
   defdelegate move_range(spreadsheet, sheet_name, range, rows, columns),
     to: SheetFunctions
 
-  defdelegate add_merge_cells(spreadsheet, sheet_name, range),
+  defdelegate copy_range(source, source_sheet, range, target, target_sheet, target_cell),
+    to: SheetFunctions
+
+  defdelegate copy_range(source, source_sheet, range, target, target_sheet, target_cell, mode),
+    to: SheetFunctions
+
+  defdelegate add_merge_cells(spreadsheet, sheet_name, range, opts \\ []),
+    to: SheetFunctions
+
+  defdelegate merge_cells_across(spreadsheet, sheet_name, range, opts \\ []),
+    to: SheetFunctions
+
+  defdelegate unmerge_cells(spreadsheet, sheet_name, range),
+    to: SheetFunctions
+
+  defdelegate get_merged_area(spreadsheet, sheet_name, cell_address),
     to: SheetFunctions
 
-  defdelegate insert_new_row(spreadsheet, sheet_name, row_index, amount),
+  defdelegate get_merge_cells(spreadsheet, sheet_name),
     to: SheetFunctions
 
-  defdelegate insert_new_column(spreadsheet, sheet_name, column, amount),
+  defdelegate insert_new_row(spreadsheet, sheet_name, row_index, amount, opts \\ []),
     to: SheetFunctions
 
-  defdelegate insert_new_column_by_index(spreadsheet, sheet_name, column_index, amount),
+  defdelegate insert_new_column(spreadsheet, sheet_name, column, amount, opts \\ []),
     to: SheetFunctions
 
+  defdelegate insert_new_column_by_index(
+                spreadsheet,
+                sheet_name,
+                column_index,
+                amount,
+                opts \\ []
+              ),
+              to: SheetFunctions
+
   defdelegate remove_row(spreadsheet, sheet_name, row_index, amount),
     to: SheetFunctions
 
@@ -916,6 +1103,12 @@ defmodule UmyaSpreadsheet do
   defdelegate get_cell_hidden(spreadsheet, sheet_name, cell_address),
     to: CellFunctions
 
+  defdelegate get_cell_style(spreadsheet, sheet_name, cell_address),
+    to: CellFunctions
+
+  defdelegate get_range_styles(spreadsheet, sheet_name, range),
+    to: CellFunctions
+
   # Row/Column Functions delegation
   defdelegate set_row_height(spreadsheet, sheet_name, row_number, height),
     to: RowColumnFunctions
@@ -929,6 +1122,54 @@ defmodule UmyaSpreadsheet do
   defdelegate set_column_auto_width(spreadsheet, sheet_name, column, auto_width),
     to: RowColumnFunctions
 
+  defdelegate autofit_column(spreadsheet, sheet_name, column, opts \\ []),
+    to: RowColumnFunctions
+
+  defdelegate autofit_columns(spreadsheet, sheet_name, range, opts \\ []),
+    to: RowColumnFunctions
+
+  defdelegate autofit_sheet(spreadsheet, sheet_name, opts \\ []),
+    to: RowColumnFunctions
+
+  defdelegate autofit_rows(spreadsheet, sheet_name, range, opts \\ []),
+    to: RowColumnFunctions
+
+  defdelegate group_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate ungroup_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate group_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate ungroup_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate set_row_outline_level(spreadsheet, sheet_name, start_row, end_row, level),
+    to: RowColumnFunctions
+
+  defdelegate set_column_outline_level(spreadsheet, sheet_name, start_column, end_column, level),
+    to: RowColumnFunctions
+
+  defdelegate collapse_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate expand_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate collapse_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate expand_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate get_outline(spreadsheet, sheet_name),
+    to: RowColumnFunctions
+
+  defdelegate set_outline_summary(spreadsheet, sheet_name, opts),
+    to: RowColumnFunctions
+
   defdelegate copy_row_styling(spreadsheet, sheet_name, source_row, target_row),
     to: RowColumnFunctions
 
@@ -957,6 +1198,54 @@ defmodule UmyaSpreadsheet do
   defdelegate get_row_hidden(spreadsheet, sheet_name, row_number),
     to: RowColumnFunctions
 
+  defdelegate hide_row(spreadsheet, sheet_name, row_number),
+    to: RowColumnFunctions
+
+  defdelegate unhide_row(spreadsheet, sheet_name, row_number),
+    to: RowColumnFunctions
+
+  defdelegate hide_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate unhide_rows(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate hide_column(spreadsheet, sheet_name, column),
+    to: RowColumnFunctions
+
+  defdelegate unhide_column(spreadsheet, sheet_name, column),
+    to: RowColumnFunctions
+
+  defdelegate hide_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate unhide_columns(spreadsheet, sheet_name, start_column, end_column),
+    to: RowColumnFunctions
+
+  defdelegate set_rows_height(spreadsheet, sheet_name, start_row, end_row, height),
+    to: RowColumnFunctions
+
+  defdelegate set_columns_width(spreadsheet, sheet_name, start_column, end_column, width),
+    to: RowColumnFunctions
+
+  defdelegate reset_rows_height(spreadsheet, sheet_name, start_row, end_row),
+    to: RowColumnFunctions
+
+  defdelegate get_row_custom_height(spreadsheet, sheet_name, row_number),
+    to: RowColumnFunctions
+
+  defdelegate set_default_row_height(spreadsheet, sheet_name, height),
+    to: RowColumnFunctions
+
+  defdelegate get_default_row_height(spreadsheet, sheet_name),
+    to: RowColumnFunctions
+
+  defdelegate set_default_column_width(spreadsheet, sheet_name, width),
+    to: RowColumnFunctions
+
+  defdelegate get_default_column_width(spreadsheet, sheet_name),
+    to: RowColumnFunctions
+
   # Border Functions delegation
   defdelegate set_border_style(
                 spreadsheet,
@@ -1651,6 +1940,100 @@ defmodule UmyaSpreadsheet do
   defdelegate get_defined_names(spreadsheet),
     to: FormulaFunctions
 
+  @doc """
+  Adds a defined name with an optional sheet scope, hidden flag and comment.
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15", nil, false, "Standard rate")
+      :ok
+  """
+  defdelegate add_defined_name(
+                spreadsheet,
+                name,
+                refers_to,
+                scope \\ nil,
+                hidden \\ false,
+                comment \\ nil
+              ),
+              to: DefinedNameFunctions
+
+  @doc """
+  Updates a defined name's name, target, scope, hidden flag or comment.
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+      iex> UmyaSpreadsheet.update_defined_name(spreadsheet, "TaxRate", nil, %{refers_to: "0.2"})
+      :ok
+  """
+  defdelegate update_defined_name(spreadsheet, name, scope, changes),
+    to: DefinedNameFunctions
+
+  @doc """
+  Removes a defined name from the workbook or sheet scope.
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+      iex> UmyaSpreadsheet.remove_defined_name(spreadsheet, "TaxRate")
+      :ok
+  """
+  defdelegate remove_defined_name(spreadsheet, name, scope \\ nil),
+    to: DefinedNameFunctions
+
+  @doc """
+  Gets a defined name by name and scope.
+  """
+  defdelegate get_defined_name(spreadsheet, name, scope \\ nil),
+    to: DefinedNameFunctions
+
+  @doc """
+  Lists defined names as maps, optionally filtered by scope.
+  """
+  defdelegate list_defined_names(spreadsheet, scope \\ :all),
+    to: DefinedNameFunctions
+
+  @doc """
+  Resolves a defined name to the sheet ranges it refers to.
+  """
+  defdelegate resolve_defined_name(spreadsheet, name, sheet_name \\ nil),
+    to: DefinedNameFunctions
+
+  @doc """
+  Reads the typed values of the range a defined name refers to.
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> UmyaSpreadsheet.add_defined_name(spreadsheet, "Inputs", "Sheet1!$A$1:$B$1")
+      iex> UmyaSpreadsheet.set_defined_name_values(spreadsheet, "Inputs", [["Qty", 3]])
+      iex> UmyaSpreadsheet.get_defined_name_values(spreadsheet, "Inputs")
+      {:ok, %{"sheet_name" => "Sheet1", "range" => "A1:B1", "values" => [["Qty", 3]]}}
+  """
+  defdelegate get_defined_name_values(spreadsheet, name, sheet_name \\ nil),
+    to: DefinedNameFunctions
+
+  @doc """
+  Fills the range a defined name refers to from a list of rows, within its bounds.
+  """
+  defdelegate set_defined_name_values(spreadsheet, name, rows, sheet_name \\ nil),
+    to: DefinedNameFunctions
+
+  @doc """
+  Checks a name against Excel's naming rules.
+
+  ## Examples
+
+      iex> UmyaSpreadsheet.validate_defined_name("TaxRate")
+      :ok
+  """
+  defdelegate validate_defined_name(name),
+    to: DefinedNameFunctions
+
   @doc """
   Checks if a cell contains a formula.
 
@@ -1886,11 +2269,10 @@ defmodule UmyaSpreadsheet do
     to: FileFormatOptions
 
   @doc """
-  Writes a spreadsheet to disk with enhanced encryption options.
+  Writes a spreadsheet to disk encrypted with the given agile encryption options.
 
-  This function provides more control over the encryption process than the standard
-  `write_with_password` function, allowing you to specify encryption algorithm,
-  salt values, and spin counts for enhanced security.
+  Unlike `write_with_password/3`, this lets you choose the AES key size, salt,
+  spin count and hash algorithm. See `UmyaSpreadsheet.FileFormatOptions.write_with_encryption_options/7`.
 
   ## Examples
 
@@ -1912,7 +2294,8 @@ defmodule UmyaSpreadsheet do
                 password,
                 algorithm,
                 salt_value \\ nil,
-                spin_count \\ nil
+                spin_count \\ nil,
+                hash_algorithm \\ nil
               ),
               to: FileFormatOptions
 
@@ -1931,6 +2314,33 @@ defmodule UmyaSpreadsheet do
   defdelegate to_binary_xlsx(spreadsheet),
     to: FileFormatOptions
 
+  @doc """
+  Converts a spreadsheet to an encrypted binary XLSX file without writing to disk.
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> binary = UmyaSpreadsheet.to_encrypted_binary_xlsx(spreadsheet, "secret", "AES128")
+      iex> match?(<<0xD0, 0xCF, 0x11, 0xE0, _::binary>>, binary)
+      true
+  """
+  defdelegate to_encrypted_binary_xlsx(
+                spreadsheet,
+                password,
+                algorithm \\ "AES256",
+                salt_value \\ nil,
+                spin_count \\ nil,
+                hash_algorithm \\ nil
+              ),
+              to: FileFormatOptions
+
+  @doc """
+  Checks if the file at `path` is encrypted by inspecting its container.
+
+  Encrypted files must be opened with `read_with_password/2`.
+  """
+  defdelegate is_file_encrypted(path), to: FileFormatOptions
+
   @doc """
   Sets an auto filter for a range of cells in a worksheet.
 
@@ -503,4 +503,68 @@ defmodule UmyaSpreadsheet.CellFunctions do
     UmyaNative.get_cell_hidden(ref, sheet_name, cell_address)
     |> ErrorHandling.standardize_result()
   end
+
+  @doc """
+  Gets the complete style of a cell as one nested map.
+
+  The map uses the keys of the style maps taken by `UmyaSpreadsheet.set_range_style/5`:
+  `"font"`, `"fill"`, `"borders"`, `"alignment"`, `"number_format"` and `"protection"`.
+  Every entry is filled in, with Excel's defaults for unset parts, and theme colors
+  are resolved to ARGB, so the maps of two cells are equal when the cells look the
+  same. Colors that are not set are `nil`. `"fill"` also has a `"gradient"` entry
+  with the degree and color stops of gradient fills, `nil` otherwise.
+
+  A cell that does not exist reports the style of its row or column.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `cell_address` - The cell address (e.g., "A1", "B5")
+
+  ## Returns
+
+  - `{:ok, style}` with the nested style map
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, style} = UmyaSpreadsheet.CellFunctions.get_cell_style(spreadsheet, "Sheet1", "A1")
+      style["font"]["bold"]
+      # => true
+      style["borders"]["bottom"]
+      # => %{"style" => "thin", "color" => "FF000000"}
+  """
+  def get_cell_style(%Spreadsheet{reference: ref}, sheet_name, cell_address) do
+    UmyaNative.get_cell_style(ref, sheet_name, cell_address)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets the complete style of every cell in a range.
+
+  Whole rows ("1:3") and columns ("A:C") are limited to the used area of the sheet.
+  Ranges of more than 100,000 cells are rejected.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range (e.g., "A1:C10")
+
+  ## Returns
+
+  - `{:ok, styles}` with a map from cell address to the style map of `get_cell_style/3`
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, template} = UmyaSpreadsheet.CellFunctions.get_range_styles(old, "Sheet1", "A1:F20")
+      {:ok, current} = UmyaSpreadsheet.CellFunctions.get_range_styles(new, "Sheet1", "A1:F20")
+      changed = for {cell, style} <- current, template[cell] != style, do: cell
+  """
+  def get_range_styles(%Spreadsheet{reference: ref}, sheet_name, range) do
+    UmyaNative.get_range_styles(ref, sheet_name, range)
+    |> ErrorHandling.standardize_result()
+  end
 end
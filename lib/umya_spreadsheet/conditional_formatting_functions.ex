@@ -4,6 +4,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   """
 
   alias UmyaSpreadsheet.Spreadsheet
+  alias UmyaSpreadsheet.ErrorHandling
+  alias UmyaSpreadsheet.StylingFunctions
   alias UmyaNative
   alias UmyaSpreadsheetEx.CustomStructs.CustomColor
 
@@ -18,7 +20,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `operator` - The comparison operator ("equal", "notEqual", "greaterThan", "lessThan", "greaterThanOrEqual", "lessThanOrEqual", "between", "notBetween")
   - `value1` - The first value for comparison
   - `value2` - The second value for comparison (required for "between" and "notBetween" operators)
-  - `format_style` - The color to apply when the condition is met (e.g., "#FF0000" for red)
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000" for red), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
 
   ## Examples
 
@@ -45,6 +49,21 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
         "70",
         "#00FF00"
       )
+
+      # Show negative amounts in bold dark red with a bottom border
+      :ok = UmyaSpreadsheet.add_cell_value_rule(
+        spreadsheet,
+        "Sheet1",
+        "B2:B100",
+        "lessThan",
+        "0",
+        nil,
+        %{
+          font: %{bold: true, color: "#9C0006"},
+          borders: %{bottom: :thin},
+          number_format: "#,##0.00"
+        }
+      )
   """
   def add_cell_value_rule(
         %Spreadsheet{reference: ref},
@@ -62,7 +81,7 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
            operator,
            value1,
            value2,
-           format_style
+           StylingFunctions.normalize_style(format_style)
          ) do
       {:ok, :ok} -> :ok
       # Handle the true boolean return value
@@ -85,7 +104,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `operator` - The text comparison operator ("beginsWith", "endsWith", "contains", "notContains")
   - `value1` - The text value for comparison
   - `value2` - Not used, pass nil
-  - `format_style` - The color to apply when the condition is met (e.g., "#FF0000" for red)
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000" for red), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
 
   ## Examples
 
@@ -118,7 +139,7 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
            operator,
            value1,
            value2,
-           format_style
+           StylingFunctions.normalize_style(format_style)
          ) do
       {:ok, :ok} -> :ok
       result -> result
@@ -136,7 +157,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `rule_type` - The rule type ("top", "bottom")
   - `rank` - The number of top/bottom items to highlight
   - `percent` - Whether rank is a percentage (true) or a count (false)
-  - `format_style` - The color to apply when the condition is met (e.g., "#FF0000" for red)
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000" for red), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
 
   ## Examples
 
@@ -180,7 +203,7 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
            rule_type,
            rank,
            percent,
-           format_style
+           StylingFunctions.normalize_style(format_style)
          ) do
       {:ok, :ok} -> :ok
       result -> result
@@ -198,6 +221,15 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `min_value` - Tuple of {type, value} for minimum, or nil for automatic
   - `max_value` - Tuple of {type, value} for maximum, or nil for automatic
   - `color` - The color to use for the data bars (e.g., "#638EC6")
+  - `opts` - Options of Excel 2010 data bars:
+    - `:gradient` - Whether the bars fade out; `false` gives solid bars (default: `true`)
+    - `:border_color` - Color of the bar border; no border when not given
+    - `:negative_fill_color` - Fill of bars for negative values; same as positive
+      bars when not given
+    - `:negative_border_color` - Border of bars for negative values
+    - `:axis_position` - `:automatic` (default), `:middle` or `:none`
+    - `:axis_color` - Color of the axis between negative and positive bars
+    - `:direction` - `:context` (default), `:left_to_right` or `:right_to_left`
 
   ## Examples
 
@@ -222,9 +254,33 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
         {"num", "100"},
         "#FF0000"
       )
+
+      # Solid bars with a border, red bars for negative values and the axis centered
+      :ok = UmyaSpreadsheet.add_data_bar(
+        spreadsheet,
+        "Sheet1",
+        "C1:C10",
+        nil,
+        nil,
+        "#638EC6",
+        gradient: false,
+        border_color: "#1F4E79",
+        negative_fill_color: "#FF0000",
+        axis_position: :middle
+      )
   """
-  def add_data_bar(%Spreadsheet{reference: ref}, sheet_name, range, min_value, max_value, color) do
-    case UmyaNative.add_data_bar(ref, sheet_name, range, min_value, max_value, color) do
+  def add_data_bar(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        range,
+        min_value,
+        max_value,
+        color,
+        opts \\ []
+      ) do
+    options = extension_options(opts)
+
+    case UmyaNative.add_data_bar(ref, sheet_name, range, min_value, max_value, color, options) do
       {:ok, :ok} -> :ok
       result -> result
     end
@@ -240,7 +296,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `range` - The cell range to apply formatting to (e.g., "A1:A10")
   - `operator` - The text operator ("contains", "notContains", "beginsWith", "endsWith")
   - `text` - The text to search for
-  - `format_style` - The color to apply when the condition is met (e.g., "#FF0000" for red)
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000" for red), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
 
   ## Examples
 
@@ -267,12 +325,251 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
       )
   """
   def add_text_rule(%Spreadsheet{reference: ref}, sheet_name, range, operator, text, format_style) do
-    case UmyaNative.add_text_rule(ref, sheet_name, range, operator, text, format_style) do
+    case UmyaNative.add_text_rule(
+           ref,
+           sheet_name,
+           range,
+           operator,
+           text,
+           StylingFunctions.normalize_style(format_style)
+         ) do
       {:ok, :ok} -> :ok
       result -> result
     end
   end
 
+  @doc """
+  Adds a formula-based (expression) conditional formatting rule.
+
+  The rule applies `style` to every cell of `range` for which `formula` is true.
+  Relative references in the formula are relative to the top-left cell of the
+  range, so `"$C2>100"` highlights whole rows of `"A2:F50"` by column C.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range to apply formatting to (e.g., "A1:A10"); several ranges
+    can be given separated by spaces (e.g., "A1:A10 C1:C10")
+  - `formula` - The formula deciding whether a cell is formatted, with or without a
+    leading "="
+  - `style` - The formatting to apply, a map with any of these keys:
+    - `:font` - `:bold`, `:italic`, `:underline`, `:strikethrough` and `:color`
+    - `:fill` - `:pattern`, `:color` and `:background_color`; a color alone gives a
+      solid fill
+    - `:borders` - `:all`, `:left`, `:right`, `:top` and `:bottom`, each a map with
+      `:style` and `:color` or just the border style
+    - `:number_format` - A number format code (e.g., "0.0%")
+
+  Excel ignores the font name and size of conditional formats, and alignment or
+  protection are not allowed.
+
+  ## Examples
+
+      # Shade the rows of overdue invoices
+      :ok = UmyaSpreadsheet.add_expression_rule(
+        spreadsheet,
+        "Sheet1",
+        "A2:F50",
+        "AND($E2<TODAY(), $F2=\"Open\")",
+        %{
+          font: %{bold: true, color: "#9C0006"},
+          fill: %{color: "#FFC7CE"},
+          borders: %{bottom: %{style: "thin", color: "#9C0006"}},
+          number_format: "#,##0.00"
+        }
+      )
+  """
+  def add_expression_rule(%Spreadsheet{reference: ref}, sheet_name, range, formula, style)
+      when is_map(style) do
+    UmyaNative.add_expression_rule(
+      ref,
+      sheet_name,
+      range,
+      formula,
+      StylingFunctions.normalize_style(style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells whose value occurs more than once in the range.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range to apply formatting to (e.g., "A1:A100")
+  - `format_style` - The color to fill matching cells with (e.g., "#FFC7CE"), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "A2:A100", "#FFC7CE")
+  """
+  def add_duplicate_values_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_duplicate_values_rule(
+      ref,
+      sheet_name,
+      range,
+      false,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells whose value occurs only once in the range.
+
+  Takes the same parameters as `add_duplicate_values_rule/4`.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.add_unique_values_rule(spreadsheet, "Sheet1", "A2:A100", "#C6EFCE")
+  """
+  def add_unique_values_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_duplicate_values_rule(
+      ref,
+      sheet_name,
+      range,
+      true,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills blank cells. Cells holding only spaces count as blank.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range to apply formatting to (e.g., "B2:B100")
+  - `format_style` - The color to fill matching cells with (e.g., "#FFEB9C"), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
+
+  ## Examples
+
+      # Flag missing values in a required column
+      :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B2:B100", "#FFEB9C")
+  """
+  def add_blanks_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_blanks_rule(
+      ref,
+      sheet_name,
+      range,
+      true,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells that are not blank.
+
+  Takes the same parameters as `add_blanks_rule/4`.
+  """
+  def add_no_blanks_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_blanks_rule(
+      ref,
+      sheet_name,
+      range,
+      false,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells holding an error value, such as `#DIV/0!` or `#N/A`.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range to apply formatting to (e.g., "C2:C100")
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000"), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.add_errors_rule(spreadsheet, "Sheet1", "C2:C100", "#FF0000")
+  """
+  def add_errors_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_errors_rule(
+      ref,
+      sheet_name,
+      range,
+      true,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells without an error value.
+
+  Takes the same parameters as `add_errors_rule/4`.
+  """
+  def add_no_errors_rule(%Spreadsheet{reference: ref}, sheet_name, range, format_style) do
+    UmyaNative.add_errors_rule(
+      ref,
+      sheet_name,
+      range,
+      false,
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Adds a rule that fills the cells whose date falls in a period relative to today,
+  the rule Excel calls "A Date Occurring".
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The cell range to apply formatting to (e.g., "D2:D100")
+  - `time_period` - One of `:today`, `:yesterday`, `:tomorrow`, `:last_7_days`,
+    `:this_week`, `:last_week`, `:next_week`, `:this_month`, `:last_month` or
+    `:next_month`; Excel's names such as `"last7Days"` are accepted as well
+  - `format_style` - The color to fill matching cells with (e.g., "#C6EFCE"), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
+
+  ## Examples
+
+      # Highlight orders placed in the last week
+      :ok = UmyaSpreadsheet.add_time_period_rule(
+        spreadsheet,
+        "Sheet1",
+        "D2:D100",
+        :last_7_days,
+        "#C6EFCE"
+      )
+  """
+  def add_time_period_rule(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        range,
+        time_period,
+        format_style
+      ) do
+    UmyaNative.add_time_period_rule(
+      ref,
+      sheet_name,
+      range,
+      to_string(time_period),
+      StylingFunctions.normalize_style(format_style)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Adds a color scale conditional formatting rule to a range of cells (two-color version).
 
@@ -519,6 +816,23 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   # Default to white for unknown formats
   defp convert_color(_), do: %CustomColor{argb: "FFFFFFFF"}
 
+  # Data bar and icon set options go to the NIF as a map with string keys and values
+  defp extension_options(opts) do
+    Enum.into(opts, %{}, fn
+      {:icons, icons} when is_list(icons) ->
+        {"icons", Enum.map(icons, &icon_option/1)}
+
+      {key, value} when is_atom(value) and not is_boolean(value) and not is_nil(value) ->
+        {to_string(key), Atom.to_string(value)}
+
+      {key, value} ->
+        {to_string(key), value}
+    end)
+  end
+
+  defp icon_option({icon_style, index}), do: {to_string(icon_style), index}
+  defp icon_option(icon), do: icon
+
   @doc """
   Adds an icon set rule for conditional formatting.
 
@@ -530,8 +844,24 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `spreadsheet` - The spreadsheet struct
   - `sheet_name` - The name of the sheet
   - `range` - The cell range to apply formatting to (e.g., "A1:A10")
-  - `icon_style` - The style of icons to use (currently supports basic icon sets)
-  - `thresholds` - A list of threshold tuples {type, value} defining the icon boundaries
+  - `icon_style` - The icon set, as Excel names it (`"3TrafficLights1"`) or in snake
+    case (`"3_traffic_lights"`), see "Icon Styles" below
+  - `thresholds` - A list of threshold tuples {type, value} defining the icon boundaries,
+    one per icon or one fewer, in which case the lowest icon starts at 0 percent
+  - `opts` - Options:
+    - `:show_value` - Whether the cell value is shown next to the icon (default: `true`)
+    - `:reverse` - Whether the icon order is reversed (default: `false`)
+    - `:icons` - Custom icons from the lowest threshold up, as a list of
+      `{icon_style, index}` tuples with a 0-based index into that icon style; use
+      `"NoIcons"` for cells without an icon
+
+  ## Icon Styles
+
+  `"3Arrows"`, `"3ArrowsGray"`, `"3Flags"`, `"3TrafficLights1"`, `"3TrafficLights2"`,
+  `"3Signs"`, `"3Symbols"`, `"3Symbols2"`, `"4Arrows"`, `"4ArrowsGray"`,
+  `"4RedToBlack"`, `"4Rating"`, `"4TrafficLights"`, `"5Arrows"`, `"5ArrowsGray"`,
+  `"5Rating"` and `"5Quarters"`, and the Excel 2010 styles `"3Stars"`,
+  `"3Triangles"` and `"5Boxes"`.
 
   ## Threshold Types
 
@@ -571,9 +901,30 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
           {"number", "80"}
         ]
       )
+
+      # Flags for low values, no icon in between and a check mark for high values,
+      # without the values themselves
+      :ok = UmyaSpreadsheet.add_icon_set(
+        spreadsheet,
+        "Sheet1",
+        "C1:C10",
+        "3_traffic_lights",
+        [{"percent", "33"}, {"percent", "67"}],
+        icons: [{"3Flags", 0}, {"NoIcons", 0}, {"3Symbols", 2}],
+        show_value: false
+      )
   """
-  def add_icon_set(%Spreadsheet{reference: ref}, sheet_name, range, icon_style, thresholds) do
-    case UmyaNative.add_icon_set(ref, sheet_name, range, icon_style, thresholds) do
+  def add_icon_set(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        range,
+        icon_style,
+        thresholds,
+        opts \\ []
+      ) do
+    options = extension_options(opts)
+
+    case UmyaNative.add_icon_set(ref, sheet_name, range, icon_style, thresholds, options) do
       {:ok, :ok} -> :ok
       # Handle the true boolean return value
       {:ok, true} -> :ok
@@ -596,7 +947,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `range` - The cell range to apply formatting to (e.g., "A1:A10")
   - `rule_type` - The type of rule: "above", "below", "above_equal", "below_equal"
   - `std_dev` - Optional standard deviation for more advanced rules (nil for basic average)
-  - `format_style` - The color to apply when the condition is met (e.g., "#FF0000" for red)
+  - `format_style` - The color to fill matching cells with (e.g., "#FF0000" for red), or a
+    style map with `:font`, `:fill`, `:borders` and `:number_format` as taken by
+    `add_expression_rule/5`
 
   ## Rule Types
 
@@ -653,7 +1006,7 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
            range,
            rule_type,
            std_dev,
-           format_style
+           StylingFunctions.normalize_style(format_style)
          ) do
       {:ok, :ok} -> :ok
       # Handle the true boolean return value
@@ -665,6 +1018,113 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
     end
   end
 
+  @doc """
+  Removes the conditional formatting rules that apply to exactly `range`.
+
+  Rules on other ranges, including ranges overlapping `range`, are kept.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.remove_conditional_formatting(spreadsheet, "Sheet1", "A1:A10")
+  """
+  def remove_conditional_formatting(%Spreadsheet{reference: ref}, sheet_name, range) do
+    UmyaNative.remove_conditional_formatting(ref, sheet_name, range)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Removes a single conditional formatting rule.
+
+  Rules are identified by their priority, the `:priority` returned by
+  `get_conditional_formatting_rules/3`. Rules added through this library get a
+  priority no other rule of the sheet has.
+
+  ## Examples
+
+      [rule | _] = ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1")
+
+      :ok =
+        UmyaSpreadsheet.remove_conditional_formatting_rule(spreadsheet, "Sheet1", rule.priority)
+  """
+  def remove_conditional_formatting_rule(%Spreadsheet{reference: ref}, sheet_name, priority) do
+    UmyaNative.remove_conditional_formatting_rule(ref, sheet_name, priority)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Moves a conditional formatting rule to another priority.
+
+  Excel evaluates rules from priority 1 upwards. The rule is placed at `new_priority`
+  and all rules of the sheet are renumbered from 1 in their new order, so the
+  priorities of other rules may change as well. A `new_priority` beyond the number
+  of rules makes the rule the last one.
+
+  ## Examples
+
+      # Evaluate the rule with priority 3 first
+      :ok = UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 3, 1)
+  """
+  def set_conditional_formatting_priority(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        priority,
+        new_priority
+      ) do
+    UmyaNative.set_conditional_formatting_priority(ref, sheet_name, priority, new_priority)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets whether Excel skips the rules with a higher priority number when a rule applies.
+
+  ## Examples
+
+      :ok =
+        UmyaSpreadsheet.set_conditional_formatting_stop_if_true(spreadsheet, "Sheet1", 1, true)
+  """
+  def set_conditional_formatting_stop_if_true(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        priority,
+        stop_if_true
+      ) do
+    UmyaNative.set_conditional_formatting_stop_if_true(ref, sheet_name, priority, stop_if_true)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Applies a conditional formatting rule to another range.
+
+  `range` may list several ranges separated by spaces. A rule sharing its range with
+  other rules gets a range of its own. Formulas of the rule are kept as they are.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.set_conditional_formatting_range(spreadsheet, "Sheet1", 1, "A1:A200")
+  """
+  def set_conditional_formatting_range(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        priority,
+        range
+      ) do
+    UmyaNative.set_conditional_formatting_range(ref, sheet_name, priority, range)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Removes all conditional formatting of a sheet.
+
+  ## Examples
+
+      # Start over before regenerating the formatting of a template
+      :ok = UmyaSpreadsheet.clear_conditional_formatting(spreadsheet, "Sheet1")
+  """
+  def clear_conditional_formatting(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.clear_conditional_formatting(ref, sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Gets all conditional formatting rules for a sheet or specific range.
 
@@ -679,7 +1139,14 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   A list of maps, each representing a conditional formatting rule with the following keys:
 
   - `:range` - The cell range the rule applies to
-  - `:rule_type` - The type of rule (":cell_is", ":color_scale", ":data_bar", ":icon_set", etc.)
+  - `:priority` - The priority of the rule, which also identifies it within the sheet
+  - `:stop_if_true` - Whether rules with a higher priority number are skipped when this
+    rule applies
+  - `:rule_type` - The type of rule (":cell_is", ":color_scale", ":data_bar", ":icon_set",
+    ":expression", ":duplicate_values", ":contains_blanks", ":time_period", etc.)
+  - `:style` - The formatting of rules with a differential format, as returned by
+    `get_expression_rules/3`
+  - `:time_period` - The period of time period rules, as returned by `get_time_period_rules/3`
   - Other rule-specific fields depending on the rule type
 
   ## Examples
@@ -723,7 +1190,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `:range` - The cell range the rule applies to
   - `:operator` - The comparison operator (e.g., ":equal", ":greater_than")
   - `:formula` - The formula or value to compare against
-  - `:format_style` - The color or style to apply when the condition is met
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
 
   ## Examples
 
@@ -801,6 +1269,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `:min_value` - Optional tuple of {type, value} for minimum
   - `:max_value` - Optional tuple of {type, value} for maximum
   - `:color` - The color of the data bars
+  - `:options` - A map of the options `add_data_bar/7` takes, with string keys and
+    values, e.g. `%{"gradient" => false, "axis_position" => "middle", ...}`
 
   ## Examples
 
@@ -833,8 +1303,9 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   A list of maps, each representing an icon set rule with the following keys:
 
   - `:range` - The cell range the rule applies to
-  - `:icon_style` - The style of icons to use
+  - `:icon_style` - The Excel name of the icon set, e.g. `"3TrafficLights1"`
   - `:thresholds` - A list of threshold tuples {type, value} defining the icon boundaries
+  - `:options` - `"show_value"`, `"reverse"` and the custom `"icons"`
 
   ## Examples
 
@@ -870,7 +1341,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `:rule_type_value` - "top" or "bottom"
   - `:rank` - The number of top/bottom items to highlight
   - `:percent` - Whether rank is a percentage (true) or a count (false)
-  - `:format_style` - The color to apply when the condition is met
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
 
   ## Examples
 
@@ -906,7 +1378,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `:range` - The cell range the rule applies to
   - `:rule_type_value` - "above", "below", "above_equal", or "below_equal"
   - `:std_dev` - Optional standard deviation value
-  - `:format_style` - The color to apply when the condition is met
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
 
   ## Examples
 
@@ -942,7 +1415,8 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
   - `:range` - The cell range the rule applies to
   - `:operator` - The text operator ("contains", "notContains", "beginsWith", "endsWith")
   - `:text` - The text to search for
-  - `:format_style` - The color to apply when the condition is met
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
 
   ## Examples
 
@@ -961,4 +1435,143 @@ defmodule UmyaSpreadsheet.ConditionalFormatting do
       result -> result
     end
   end
+
+  @doc """
+  Gets all formula-based (expression) rules for a sheet or specific range.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - Optional. The cell range to get rules for. If nil, returns all rules for the sheet.
+
+  ## Returns
+
+  A list of maps, each representing an expression rule with the following keys:
+
+  - `:range` - The cell range the rule applies to
+  - `:rule_type` - Always `:expression`
+  - `:formula` - The formula of the rule, without a leading "="
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - A map with the `"font"`, `"fill"`, `"borders"` and `"number_format"` of
+    the rule, shaped like the map of `UmyaSpreadsheet.get_cell_style/3`; each is `nil`
+    when the rule leaves it unchanged
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
+
+      [rule] = UmyaSpreadsheet.ConditionalFormatting.get_expression_rules(
+        spreadsheet,
+        "Sheet1"
+      )
+
+      rule.style["number_format"]
+      # => "#,##0.00"
+  """
+  def get_expression_rules(%Spreadsheet{reference: ref}, sheet_name, range \\ nil) do
+    case UmyaNative.get_expression_rules(ref, sheet_name, range) do
+      {:ok, rules} -> rules
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
+  @doc """
+  Gets all duplicate and unique values rules for a sheet or specific range.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - Optional. The cell range to get rules for. If nil, returns all rules for the sheet.
+
+  ## Returns
+
+  A list of maps with the following keys:
+
+  - `:range` - The cell range the rule applies to
+  - `:rule_type` - `:duplicate_values` or `:unique_values`
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
+
+  ## Examples
+
+      rules = UmyaSpreadsheet.ConditionalFormatting.get_duplicate_values_rules(
+        spreadsheet,
+        "Sheet1"
+      )
+  """
+  def get_duplicate_values_rules(%Spreadsheet{reference: ref}, sheet_name, range \\ nil) do
+    case UmyaNative.get_duplicate_values_rules(ref, sheet_name, range) do
+      {:ok, rules} -> rules
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
+  @doc """
+  Gets all blank and non-blank rules for a sheet or specific range.
+
+  Returns maps with `:range`, `:rule_type` (`:contains_blanks` or
+  `:not_contains_blanks`), `:formula`, `:format_style` and `:style`, as for
+  `get_duplicate_values_rules/3`.
+
+  ## Examples
+
+      rules = UmyaSpreadsheet.ConditionalFormatting.get_blanks_rules(spreadsheet, "Sheet1")
+  """
+  def get_blanks_rules(%Spreadsheet{reference: ref}, sheet_name, range \\ nil) do
+    case UmyaNative.get_blanks_rules(ref, sheet_name, range) do
+      {:ok, rules} -> rules
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
+  @doc """
+  Gets all error and non-error rules for a sheet or specific range.
+
+  Returns maps with `:range`, `:rule_type` (`:contains_errors` or
+  `:not_contains_errors`), `:formula`, `:format_style` and `:style`, as for
+  `get_duplicate_values_rules/3`.
+
+  ## Examples
+
+      rules = UmyaSpreadsheet.ConditionalFormatting.get_errors_rules(spreadsheet, "Sheet1")
+  """
+  def get_errors_rules(%Spreadsheet{reference: ref}, sheet_name, range \\ nil) do
+    case UmyaNative.get_errors_rules(ref, sheet_name, range) do
+      {:ok, rules} -> rules
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
+  @doc """
+  Gets all time period (date occurring) rules for a sheet or specific range.
+
+  ## Returns
+
+  A list of maps with the following keys:
+
+  - `:range` - The cell range the rule applies to
+  - `:rule_type` - Always `:time_period`
+  - `:time_period` - The period, e.g. `:yesterday` or `:last_7_days`
+  - `:formula` - The formula Excel evaluates for the period
+  - `:format_style` - The fill color of the rule, if any
+  - `:style` - The full formatting of the rule, as returned by `get_expression_rules/3`
+
+  ## Examples
+
+      [%{time_period: :last_7_days}] =
+        UmyaSpreadsheet.ConditionalFormatting.get_time_period_rules(spreadsheet, "Sheet1")
+  """
+  def get_time_period_rules(%Spreadsheet{reference: ref}, sheet_name, range \\ nil) do
+    case UmyaNative.get_time_period_rules(ref, sheet_name, range) do
+      {:ok, rules} -> rules
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
 end
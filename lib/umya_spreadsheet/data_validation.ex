@@ -24,6 +24,13 @@ defmodule UmyaSpreadsheet.DataValidation do
   * Date validation (date constraints)
   * Text length validation (character count constraints)
   * Custom formula validation (formula-based constraints)
+
+  A cell has at most one validation, as in Excel. Adding a validation over cells
+  that already have one takes those cells out of the old validation, and removing
+  a range keeps the cells of each validation outside of it.
+
+  Rules only check what users type in Excel. Use `get_data_validation_violations/2`
+  to find values already in a sheet that break them.
   """
 
   alias UmyaSpreadsheet.Spreadsheet
@@ -34,6 +41,11 @@ defmodule UmyaSpreadsheet.DataValidation do
 
   This creates a dropdown list that restricts input to the specified options.
 
+  Items may contain commas and quotes. Excel cannot write commas inside an inline
+  list and rejects inline lists over 255 characters, so such lists are written to
+  a hidden `_ValidationLists` sheet the validation points to. Use
+  `add_list_validation_from_range/5` to keep the items on a sheet of your own.
+
   ## Parameters
 
     * `spreadsheet` - A spreadsheet struct
@@ -540,7 +552,199 @@ defmodule UmyaSpreadsheet.DataValidation do
   end
 
   @doc """
-  Removes all data validation from a range of cells.
+  Adds a dropdown list validation whose items come from a range of cells.
+
+  The range must be a single row or column, on the same sheet (`"D1:D10"`) or on
+  another one (`"Lists!A1:A10"`). It is made absolute, so every cell of
+  `cell_range` offers the same items.
+
+  ## Options
+
+    * `:allow_blank` - Whether to allow blank values (default: `true`)
+    * `:error_title`, `:error_message` - Shown when an invalid value is entered
+    * `:prompt_title`, `:prompt_message` - Shown when a cell is selected
+    * `:show_error_message`, `:show_input_message` - Whether the messages are
+      shown; setting a message shows it unless these say otherwise
+
+  ## Examples
+
+  ```elixir
+  DataValidation.add_list_validation_from_range(
+    spreadsheet,
+    "Sheet1",
+    "A2:A100",
+    "Lists!A1:A10",
+    error_message: "Pick a category from the list"
+  )
+  ```
+  """
+  @spec add_list_validation_from_range(
+          Spreadsheet.t(),
+          String.t(),
+          String.t(),
+          String.t(),
+          keyword()
+        ) :: :ok | {:error, String.t()}
+  def add_list_validation_from_range(
+        %Spreadsheet{} = spreadsheet,
+        sheet_name,
+        cell_range,
+        source_range,
+        opts \\ []
+      ) do
+    add_list_source_validation(spreadsheet, sheet_name, cell_range, "range", source_range, opts)
+  end
+
+  @doc """
+  Adds a dropdown list validation whose items come from a defined name.
+
+  The name must exist and be visible from `sheet_name`, either workbook-wide or
+  scoped to that sheet. Takes the options of `add_list_validation_from_range/5`.
+
+  ## Examples
+
+  ```elixir
+  UmyaSpreadsheet.create_defined_name(spreadsheet, "Fruits", "Lists!$A$1:$A$3")
+  DataValidation.add_list_validation_from_defined_name(spreadsheet, "Sheet1", "A2:A100", "Fruits")
+  ```
+  """
+  @spec add_list_validation_from_defined_name(
+          Spreadsheet.t(),
+          String.t(),
+          String.t(),
+          String.t(),
+          keyword()
+        ) :: :ok | {:error, String.t()}
+  def add_list_validation_from_defined_name(
+        %Spreadsheet{} = spreadsheet,
+        sheet_name,
+        cell_range,
+        defined_name,
+        opts \\ []
+      ) do
+    add_list_source_validation(
+      spreadsheet,
+      sheet_name,
+      cell_range,
+      "defined_name",
+      defined_name,
+      opts
+    )
+  end
+
+  @doc """
+  Adds a dependent dropdown, whose items depend on the value of another cell.
+
+  The validation lists `INDIRECT(parent_cell)`: the value of the parent cell names
+  the range, usually a defined name, holding the items. `parent_cell` is written
+  for the first cell of `cell_range`; a relative reference such as `"A2"` moves
+  along with each cell, so `B2` reads `A2`, `B3` reads `A3` and so on. Use `"$A$2"`
+  to read the same cell everywhere. Takes the options of
+  `add_list_validation_from_range/5`.
+
+  ## Examples
+
+  ```elixir
+  # A2:A100 picks a category, B2:B100 an item of the defined name it names
+  DataValidation.add_list_validation_from_range(spreadsheet, "Sheet1", "A2:A100", "Lists!A1:A3")
+  DataValidation.add_dependent_list_validation(spreadsheet, "Sheet1", "B2:B100", "A2")
+  ```
+  """
+  @spec add_dependent_list_validation(
+          Spreadsheet.t(),
+          String.t(),
+          String.t(),
+          String.t(),
+          keyword()
+        ) :: :ok | {:error, String.t()}
+  def add_dependent_list_validation(
+        %Spreadsheet{} = spreadsheet,
+        sheet_name,
+        cell_range,
+        parent_cell,
+        opts \\ []
+      ) do
+    add_list_source_validation(spreadsheet, sheet_name, cell_range, "indirect", parent_cell, opts)
+  end
+
+  defp add_list_source_validation(
+         %Spreadsheet{reference: ref},
+         sheet_name,
+         cell_range,
+         source_type,
+         source,
+         opts
+       ) do
+    case UmyaNative.add_list_source_validation(
+           UmyaSpreadsheet.unwrap_ref(ref),
+           sheet_name,
+           cell_range,
+           source_type,
+           source,
+           validation_options(opts)
+         ) do
+      :ok -> :ok
+      {:ok, :ok} -> :ok
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  @doc """
+  Updates the data validation that applies to a cell in place.
+
+  The whole validation is updated, including its cells outside of `cell`. Options
+  that are not given keep their current value.
+
+  ## Options
+
+  Takes the options of `add_list_validation_from_range/5`, where `nil` clears a
+  title or message, and:
+
+    * `:operator` - The comparison operator, e.g. `"between"` or `"greater_than"`
+    * `:formula1`, `:formula2` - The values or formulas the rule compares with
+    * `:range` - New cells for the validation, e.g. `"A1:A20"`; the cells are taken
+      out of other validations of the sheet
+
+  ## Examples
+
+  ```elixir
+  DataValidation.update_data_validation(spreadsheet, "Sheet1", "B3",
+    range: "B1:B20",
+    error_message: "Enter a number between 1 and 100",
+    formula2: "100"
+  )
+  ```
+  """
+  @spec update_data_validation(Spreadsheet.t(), String.t(), String.t(), keyword()) ::
+          :ok | {:error, String.t()}
+  def update_data_validation(%Spreadsheet{reference: ref}, sheet_name, cell, opts) do
+    case UmyaNative.update_data_validation(
+           UmyaSpreadsheet.unwrap_ref(ref),
+           sheet_name,
+           cell,
+           validation_options(opts)
+         ) do
+      :ok -> :ok
+      {:ok, :ok} -> :ok
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  defp validation_options(opts) do
+    Enum.into(opts, %{}, fn
+      {key, value} when is_atom(value) and not is_boolean(value) and not is_nil(value) ->
+        {to_string(key), Atom.to_string(value)}
+
+      {key, value} ->
+        {to_string(key), value}
+    end)
+  end
+
+  @doc """
+  Removes data validation from a range of cells.
+
+  Validations covering cells outside of the range keep those cells; validations
+  left without cells are removed.
 
   ## Parameters
 
@@ -557,6 +761,9 @@ defmodule UmyaSpreadsheet.DataValidation do
     "Sheet1",
     "A1:B10"
   )
+
+  # A validation on A1:A10 keeps A1:A4 and A6:A10
+  DataValidation.remove_data_validation(spreadsheet, "Sheet1", "A5")
   ```
   """
   @spec remove_data_validation(
@@ -616,6 +823,59 @@ defmodule UmyaSpreadsheet.DataValidation do
     )
   end
 
+  @doc """
+  Gets the data validation rule that applies to a cell.
+
+  Returns `{:ok, rule}` with a map as returned by `get_data_validations/3`, or
+  `{:ok, nil}` when the cell has no validation.
+
+  ## Examples
+
+  ```elixir
+  {:ok, %{rule_type: :list, range: "A1:A10"}} =
+    DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "A3")
+  ```
+  """
+  @spec get_data_validation_at(Spreadsheet.t(), String.t(), String.t()) ::
+          {:ok, map() | nil} | {:error, String.t()}
+  def get_data_validation_at(%Spreadsheet{reference: ref}, sheet_name, cell) do
+    UmyaNative.get_data_validation_at(UmyaSpreadsheet.unwrap_ref(ref), sheet_name, cell)
+  end
+
+  @doc """
+  Checks the values already in a sheet against its data validation rules.
+
+  Excel only validates what users type, so values written by code, pasted or
+  present before a rule was added can break it. This finds them, like Excel's
+  "Circle Invalid Data" command. Each violation is a map with:
+
+    * `:cell` - the cell, e.g. "B7"
+    * `:range` - the range of the rule it breaks
+    * `:rule_type` - `:whole`, `:decimal`, `:date`, `:time`, `:textLength`, `:list` or `:custom`
+    * `:value` - the value of the cell as text
+    * `:error_message` - the error message of the rule, or `nil` when it has none
+
+  Violations are listed in row order. Empty cells are not checked. Formula cells are
+  checked with the result last saved in the file. Dates and times must be stored as
+  Excel serial numbers; text such as "2024-01-31" breaks a date rule, as in Excel.
+
+  Custom formulas and list sources are evaluated for the common operators and
+  functions (`AND`, `OR`, `IF`, `LEN`, `ISNUMBER`, `COUNTIF`, `INDIRECT` and others).
+  Rules using anything else are skipped rather than reported.
+
+  ## Examples
+
+  ```elixir
+  {:ok, [%{cell: "B7", rule_type: :decimal, error_message: "Enter 1 to 10"}]} =
+    DataValidation.get_data_validation_violations(spreadsheet, "Sheet1")
+  ```
+  """
+  @spec get_data_validation_violations(Spreadsheet.t(), String.t()) ::
+          {:ok, [map()]} | {:error, String.t()}
+  def get_data_validation_violations(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.get_data_validation_violations(UmyaSpreadsheet.unwrap_ref(ref), sheet_name)
+  end
+
   @doc """
   Gets list validation rules for a sheet or range.
 
@@ -0,0 +1,254 @@
+defmodule UmyaSpreadsheet.DefinedNameFunctions do
+  @moduledoc """
+  Functions for managing defined names (named ranges, constants and formulas).
+
+  A defined name is either workbook-scoped (visible from every sheet) or scoped to
+  a single sheet. The same name may exist once per scope; when a formula on a sheet
+  uses a name, the sheet-scoped name wins over the workbook-scoped one. Functions in
+  this module take the scope as an optional sheet name, where `nil` means the
+  workbook scope.
+
+  Defined names are returned as maps with string keys:
+
+  * `"name"` - The defined name
+  * `"refers_to"` - The range, constant or formula it refers to
+  * `"scope"` - Name of the sheet it is scoped to, or `nil` for workbook scope
+  * `"local_sheet_id"` - Index of the scope sheet, or `nil` for workbook scope
+  * `"hidden"` - Whether the name is hidden from Excel's Name Manager
+  * `"comment"` - The name's comment, or `nil`
+  """
+
+  alias UmyaSpreadsheet.Spreadsheet
+  alias UmyaSpreadsheet.ErrorHandling
+  alias UmyaNative
+
+  @doc """
+  Adds a defined name.
+
+  The name is checked against Excel's naming rules and must not already exist in
+  the same scope.
+
+  ## Parameters
+
+  * `spreadsheet` - The spreadsheet struct
+  * `name` - The name to define (e.g., "TaxRate")
+  * `refers_to` - Range, constant or formula (e.g., "Sheet1!$A$1:$B$10", "0.15")
+  * `scope` - Sheet name for a sheet-scoped name, or `nil` for workbook scope
+  * `hidden` - Whether the name is hidden (default: `false`)
+  * `comment` - Optional comment shown in the Name Manager
+
+  ## Examples
+
+      iex> DefinedNameFunctions.add_defined_name(spreadsheet, "TaxRate", "0.15")
+      :ok
+
+      iex> DefinedNameFunctions.add_defined_name(spreadsheet, "Region", "Sheet1!$A$1", "Sheet1", false, "Current region")
+      :ok
+  """
+  @spec add_defined_name(
+          Spreadsheet.t(),
+          String.t(),
+          String.t(),
+          String.t() | nil,
+          boolean(),
+          String.t() | nil
+        ) :: :ok | {:error, String.t()}
+  def add_defined_name(
+        %Spreadsheet{reference: ref},
+        name,
+        refers_to,
+        scope \\ nil,
+        hidden \\ false,
+        comment \\ nil
+      ) do
+    UmyaNative.add_defined_name(ref, name, refers_to, scope, hidden, comment)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Updates an existing defined name.
+
+  ## Parameters
+
+  * `spreadsheet` - The spreadsheet struct
+  * `name` - The current name
+  * `scope` - Sheet name the name is scoped to, or `nil` for workbook scope
+  * `changes` - Map with any of `:name`, `:refers_to`, `:hidden`, `:comment`
+    (`nil` removes it) and `:scope` (a sheet name, or `nil` for workbook scope)
+
+  ## Examples
+
+      iex> DefinedNameFunctions.update_defined_name(spreadsheet, "TaxRate", nil, %{refers_to: "0.2"})
+      :ok
+
+      iex> DefinedNameFunctions.update_defined_name(spreadsheet, "TaxRate", nil, %{name: "VatRate", comment: nil})
+      :ok
+  """
+  @spec update_defined_name(Spreadsheet.t(), String.t(), String.t() | nil, map()) ::
+          :ok | {:error, String.t()}
+  def update_defined_name(%Spreadsheet{reference: ref}, name, scope, changes)
+      when is_map(changes) do
+    UmyaNative.update_defined_name(ref, name, scope, normalize_changes(changes))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Removes a defined name.
+
+  ## Examples
+
+      iex> DefinedNameFunctions.remove_defined_name(spreadsheet, "TaxRate")
+      :ok
+
+      iex> DefinedNameFunctions.remove_defined_name(spreadsheet, "Region", "Sheet1")
+      :ok
+  """
+  @spec remove_defined_name(Spreadsheet.t(), String.t(), String.t() | nil) ::
+          :ok | {:error, String.t()}
+  def remove_defined_name(%Spreadsheet{reference: ref}, name, scope \\ nil) do
+    UmyaNative.remove_defined_name(ref, name, scope)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets a defined name by name and scope.
+
+  Names are matched case-insensitively, as in Excel.
+
+  ## Examples
+
+      iex> DefinedNameFunctions.get_defined_name(spreadsheet, "TaxRate")
+      {:ok, %{"name" => "TaxRate", "refers_to" => "0.15", "scope" => nil, ...}}
+  """
+  @spec get_defined_name(Spreadsheet.t(), String.t(), String.t() | nil) ::
+          {:ok, map()} | {:error, String.t()}
+  def get_defined_name(%Spreadsheet{reference: ref}, name, scope \\ nil) do
+    UmyaNative.get_defined_name(ref, name, scope)
+  end
+
+  @doc """
+  Lists defined names with their scope, hidden flag and comment.
+
+  ## Parameters
+
+  * `spreadsheet` - The spreadsheet struct
+  * `scope` - Optional filter: `:all` (default), `:workbook` or a sheet name
+
+  ## Examples
+
+      iex> DefinedNameFunctions.list_defined_names(spreadsheet)
+      {:ok, [%{"name" => "TaxRate", "scope" => nil, ...}, %{"name" => "Region", "scope" => "Sheet1", ...}]}
+
+      iex> DefinedNameFunctions.list_defined_names(spreadsheet, "Sheet1")
+      {:ok, [%{"name" => "Region", "scope" => "Sheet1", ...}]}
+  """
+  @spec list_defined_names(Spreadsheet.t(), :all | :workbook | String.t()) ::
+          {:ok, [map()]} | {:error, String.t()}
+  def list_defined_names(%Spreadsheet{reference: ref}, scope \\ :all) do
+    case UmyaNative.list_defined_names(ref) do
+      {:ok, names} -> {:ok, filter_by_scope(names, scope)}
+      error -> ErrorHandling.standardize_result(error)
+    end
+  end
+
+  @doc """
+  Resolves a defined name to the ranges it refers to.
+
+  When `sheet_name` is given, a name scoped to that sheet takes precedence over a
+  workbook-scoped name, as it would in a formula on that sheet. References without
+  a sheet prefix are resolved against the name's scope sheet.
+
+  ## Examples
+
+      iex> DefinedNameFunctions.resolve_defined_name(spreadsheet, "SalesData")
+      {:ok, [%{"sheet_name" => "Sheet1", "range" => "A1:D10"}]}
+
+      iex> DefinedNameFunctions.resolve_defined_name(spreadsheet, "TaxRate")
+      {:error, "Defined name 'TaxRate' does not refer to a range: 0.15"}
+  """
+  @spec resolve_defined_name(Spreadsheet.t(), String.t(), String.t() | nil) ::
+          {:ok, [map()]} | {:error, String.t()}
+  def resolve_defined_name(%Spreadsheet{reference: ref}, name, sheet_name \\ nil) do
+    UmyaNative.resolve_defined_name(ref, name, sheet_name)
+  end
+
+  @doc """
+  Reads the typed values of the range a defined name refers to.
+
+  The name is resolved like `resolve_defined_name/3` and must refer to a single
+  range. Values are returned row by row as numbers, booleans, strings or `nil` for
+  empty cells. Whole-column and whole-row names are clamped to the used area of
+  the sheet. Ranges of more than a million cells are rejected.
+
+  ## Examples
+
+      iex> DefinedNameFunctions.get_defined_name_values(spreadsheet, "InvoiceLines")
+      {:ok,
+       %{
+         "sheet_name" => "Invoice",
+         "range" => "A2:C3",
+         "values" => [["Widget", 2, 9.5], ["Gadget", 1, nil]]
+       }}
+  """
+  @spec get_defined_name_values(Spreadsheet.t(), String.t(), String.t() | nil) ::
+          {:ok, map()} | {:error, String.t()}
+  def get_defined_name_values(%Spreadsheet{reference: ref}, name, sheet_name \\ nil) do
+    UmyaNative.get_defined_name_values(ref, name, sheet_name)
+  end
+
+  @doc """
+  Fills the range a defined name refers to from a list of rows.
+
+  Rows are written from the top-left cell of the range. Numbers, booleans and
+  strings keep their type and `nil` clears a cell's value. Rows or columns that do
+  not fit inside the named range return an error and nothing is written.
+
+  ## Examples
+
+      iex> DefinedNameFunctions.set_defined_name_values(spreadsheet, "InvoiceLines", [
+      ...>   ["Widget", 2, 9.5],
+      ...>   ["Gadget", 1, 12.0]
+      ...> ])
+      :ok
+  """
+  @spec set_defined_name_values(Spreadsheet.t(), String.t(), [list()], String.t() | nil) ::
+          :ok | {:error, String.t()}
+  def set_defined_name_values(%Spreadsheet{reference: ref}, name, rows, sheet_name \\ nil)
+      when is_list(rows) do
+    UmyaNative.set_defined_name_values(ref, name, sheet_name, rows)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Checks a name against Excel's naming rules.
+
+  A valid name starts with a letter, underscore or backslash, contains only
+  letters, digits, underscores, periods, backslashes and question marks, is at
+  most 255 characters long, and cannot look like a cell reference ("A1", "R1C1")
+  or be "C"/"R".
+
+  ## Examples
+
+      iex> DefinedNameFunctions.validate_defined_name("TaxRate")
+      :ok
+
+      iex> DefinedNameFunctions.validate_defined_name("A1")
+      {:error, "Name 'A1' conflicts with a cell reference"}
+  """
+  @spec validate_defined_name(String.t()) :: :ok | {:error, String.t()}
+  def validate_defined_name(name) do
+    UmyaNative.validate_defined_name(name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  defp normalize_changes(changes) do
+    Enum.into(changes, %{}, fn
+      {key, value} when is_atom(key) -> {Atom.to_string(key), value}
+      {key, value} -> {key, value}
+    end)
+  end
+
+  defp filter_by_scope(names, :all), do: names
+  defp filter_by_scope(names, :workbook), do: Enum.filter(names, &is_nil(&1["scope"]))
+  defp filter_by_scope(names, sheet_name), do: Enum.filter(names, &(&1["scope"] == sheet_name))
+end
@@ -7,7 +7,7 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
 
   Options include:
   - Compression level control for XLSX files
-  - Enhanced encryption options
+  - Agile encryption options (AES key size, hash algorithm, salt and spin count)
   - Converting a spreadsheet directly to a binary without writing to disk
   """
 
@@ -53,20 +53,24 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
   end
 
   @doc """
-  Writes a spreadsheet to disk with enhanced encryption options.
+  Writes a spreadsheet to disk encrypted with the given agile encryption options.
 
-  This function provides more control over the encryption process than the standard
-  `write_with_password` function, allowing you to specify encryption algorithm,
-  salt values, and spin counts for enhanced security.
+  The file is encrypted with ECMA-376 agile encryption, the scheme used by Excel
+  2010 and later. The options control the cipher and the password key
+  derivation; the package is encrypted in memory and written in one step.
 
   ## Parameters
 
   * `spreadsheet` - The spreadsheet struct
   * `path` - Path where the encrypted Excel file will be saved
-  * `password` - Password to protect the file with
-  * `algorithm` - Encryption algorithm (e.g., "AES128", "AES256", "default")
-  * `salt_value` - Optional salt value for password derivation (if nil, a random salt is used)
-  * `spin_count` - Optional spin count for key derivation (if nil, Excel default is used)
+  * `password` - Password to encrypt the file with
+  * `algorithm` - AES key size: "AES128", "AES192", "AES256" or "default" (AES-256)
+  * `salt_value` - Optional 16-byte salt for password derivation, base64 encoded
+    (if nil, a random salt is used)
+  * `spin_count` - Optional number of key derivation iterations, up to 10,000,000
+    (if nil, Excel's default of 100,000 is used)
+  * `hash_algorithm` - Optional hash algorithm: "SHA256", "SHA384" or "SHA512"
+    (if nil, SHA-512 is used)
 
   ## Examples
 
@@ -84,12 +88,22 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
       ...>   spreadsheet,
       ...>   "custom_encryption.xlsx",
       ...>   "very_secret",
-      ...>   "AES256",
-      ...>   "customSalt",
-      ...>   100000
+      ...>   "AES128",
+      ...>   Base.encode64(:crypto.strong_rand_bytes(16)),
+      ...>   50000,
+      ...>   "SHA256"
       ...> )
       :ok
 
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> UmyaSpreadsheet.FileFormatOptions.write_with_encryption_options(
+      ...>   spreadsheet,
+      ...>   "weak.xlsx",
+      ...>   "secret",
+      ...>   "DES"
+      ...> )
+      {:error, "Unsupported encryption algorithm 'DES'; use AES128, AES192 or AES256"}
+
   """
   @spec write_with_encryption_options(
           Spreadsheet.t(),
@@ -97,15 +111,17 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
           String.t(),
           String.t(),
           String.t() | nil,
-          non_neg_integer() | nil
-        ) :: :ok | {:error, atom()}
+          non_neg_integer() | nil,
+          String.t() | nil
+        ) :: :ok | {:error, String.t()}
   def write_with_encryption_options(
         %Spreadsheet{reference: ref},
         path,
         password,
         algorithm,
         salt_value \\ nil,
-        spin_count \\ nil
+        spin_count \\ nil,
+        hash_algorithm \\ nil
       ) do
     case UmyaNative.write_with_encryption_options(
            ref,
@@ -113,7 +129,8 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
            password,
            algorithm,
            salt_value,
-           spin_count
+           spin_count,
+           hash_algorithm
          ) do
       {:ok, :ok} -> :ok
       :ok -> :ok
@@ -160,6 +177,65 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
     end
   end
 
+  @doc """
+  Converts a spreadsheet to an encrypted binary XLSX file without writing to disk.
+
+  Takes the same encryption options as `write_with_encryption_options/7`. The
+  result can be stored or sent as is and opened with the password, e.g. by
+  writing it to a file and calling `UmyaSpreadsheet.read_with_password/2`.
+
+  ## Parameters
+
+  * `spreadsheet` - The spreadsheet struct
+  * `password` - Password to encrypt the file with
+  * `algorithm` - AES key size: "AES128", "AES192", "AES256" or "default" (default: "AES256")
+  * `salt_value` - Optional 16-byte salt for password derivation, base64 encoded
+  * `spin_count` - Optional number of key derivation iterations
+  * `hash_algorithm` - Optional hash algorithm: "SHA256", "SHA384" or "SHA512"
+
+  ## Returns
+
+  * Binary data of the encrypted Excel file (an OLE compound document)
+  * `{:error, reason}` if the options are invalid or encryption failed
+
+  ## Examples
+
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.new()
+      iex> binary = UmyaSpreadsheet.FileFormatOptions.to_encrypted_binary_xlsx(spreadsheet, "secret")
+      iex> match?(<<0xD0, 0xCF, 0x11, 0xE0, _::binary>>, binary)
+      true
+
+  """
+  @spec to_encrypted_binary_xlsx(
+          Spreadsheet.t(),
+          String.t(),
+          String.t(),
+          String.t() | nil,
+          non_neg_integer() | nil,
+          String.t() | nil
+        ) :: binary() | {:error, String.t()}
+  def to_encrypted_binary_xlsx(
+        %Spreadsheet{reference: ref},
+        password,
+        algorithm \\ "AES256",
+        salt_value \\ nil,
+        spin_count \\ nil,
+        hash_algorithm \\ nil
+      ) do
+    case UmyaNative.to_encrypted_binary_xlsx(
+           ref,
+           password,
+           algorithm,
+           salt_value,
+           spin_count,
+           hash_algorithm
+         ) do
+      {:ok, binary} -> binary
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
   @doc """
   Gets the default compression level used for XLSX files.
 
@@ -191,10 +267,11 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
   end
 
   @doc """
-  Checks if a spreadsheet has encryption enabled.
+  Checks if a spreadsheet was read from an encrypted file.
 
-  This function checks if the spreadsheet has any workbook protection or encryption
-  settings enabled.
+  Encryption is a property of the file container, so this is `true` only for
+  spreadsheets opened with `UmyaSpreadsheet.read_with_password/2`. Workbook
+  protection is a separate feature and does not make a spreadsheet encrypted.
 
   ## Parameters
 
@@ -202,7 +279,7 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
 
   ## Returns
 
-  * Boolean indicating if encryption is enabled
+  * Boolean indicating if the source file was encrypted
 
   ## Examples
 
@@ -210,7 +287,7 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
       iex> UmyaSpreadsheet.FileFormatOptions.is_encrypted(spreadsheet)
       false
 
-      iex> {:ok, spreadsheet} = UmyaSpreadsheet.read_xlsx_with_password("encrypted.xlsx", "secret123")
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.read_with_password("encrypted.xlsx", "secret123")
       iex> UmyaSpreadsheet.FileFormatOptions.is_encrypted(spreadsheet)
       true
 
@@ -225,10 +302,44 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
   end
 
   @doc """
-  Gets the encryption algorithm used for a password-protected spreadsheet.
+  Checks if the file at `path` is encrypted without opening it.
+
+  Encrypted packages are stored in an OLE compound document with an
+  `EncryptionInfo` stream, while plain packages are zip archives, so the check
+  only inspects the container.
+
+  ## Parameters
+
+  * `path` - Path to the Excel file
+
+  ## Returns
+
+  * Boolean indicating if the file is encrypted
+  * `{:error, reason}` if the file cannot be read
+
+  ## Examples
+
+      iex> UmyaSpreadsheet.FileFormatOptions.is_file_encrypted("encrypted.xlsx")
+      true
+
+      iex> UmyaSpreadsheet.FileFormatOptions.is_file_encrypted("plain.xlsx")
+      false
+
+  """
+  @spec is_file_encrypted(String.t()) :: boolean() | {:error, String.t()}
+  def is_file_encrypted(path) do
+    case UmyaNative.is_file_encrypted(path) do
+      {:ok, encrypted} -> encrypted
+      {:error, reason} -> {:error, reason}
+      result -> result
+    end
+  end
+
+  @doc """
+  Gets the encryption algorithm of the file a spreadsheet was read from.
 
-  This function returns the encryption algorithm used to protect the spreadsheet,
-  or nil if the spreadsheet is not encrypted.
+  This function returns the cipher and key size of the encrypted file the
+  spreadsheet was opened from, or nil if the file was not encrypted.
 
   ## Parameters
 
@@ -246,7 +357,7 @@ defmodule UmyaSpreadsheet.FileFormatOptions do
       iex> UmyaSpreadsheet.FileFormatOptions.get_encryption_algorithm(spreadsheet)
       nil
 
-      iex> {:ok, spreadsheet} = UmyaSpreadsheet.read("encrypted.xlsx", "password")
+      iex> {:ok, spreadsheet} = UmyaSpreadsheet.read_with_password("encrypted.xlsx", "password")
       iex> UmyaSpreadsheet.FileFormatOptions.get_encryption_algorithm(spreadsheet)
       "AES256"
   """
@@ -4,6 +4,7 @@ defmodule UmyaSpreadsheet.RowColumnFunctions do
   """
 
   alias UmyaSpreadsheet.Spreadsheet
+  alias UmyaSpreadsheet.ErrorHandling
   alias UmyaNative
 
   @doc """
@@ -95,6 +96,9 @@ defmodule UmyaSpreadsheet.RowColumnFunctions do
   @doc """
   Sets whether a column width should be automatically adjusted.
 
+  Excel does not resize columns when it opens a file, so enabling auto width also
+  sets the width the column's cells need, as `autofit_column/4` does.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
@@ -350,4 +354,575 @@ defmodule UmyaSpreadsheet.RowColumnFunctions do
       result -> result
     end
   end
+
+  @doc """
+  Hides a row.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.hide_row(spreadsheet, "Sheet1", 3)
+  """
+  def hide_row(%Spreadsheet{} = spreadsheet, sheet_name, row_number) do
+    hide_rows(spreadsheet, sheet_name, row_number, row_number)
+  end
+
+  @doc """
+  Shows a hidden row again.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.unhide_row(spreadsheet, "Sheet1", 3)
+  """
+  def unhide_row(%Spreadsheet{} = spreadsheet, sheet_name, row_number) do
+    unhide_rows(spreadsheet, sheet_name, row_number, row_number)
+  end
+
+  @doc """
+  Hides rows `start_row` to `end_row`.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `start_row` - First row to hide (1-based)
+  - `end_row` - Last row to hide
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.hide_rows(spreadsheet, "Sheet1", 10, 20)
+  """
+  def hide_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.set_rows_hidden(ref, sheet_name, start_row, end_row, true)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Shows rows `start_row` to `end_row` again.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.unhide_rows(spreadsheet, "Sheet1", 10, 20)
+  """
+  def unhide_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.set_rows_hidden(ref, sheet_name, start_row, end_row, false)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Hides a column.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.hide_column(spreadsheet, "Sheet1", "C")
+  """
+  def hide_column(%Spreadsheet{} = spreadsheet, sheet_name, column) do
+    hide_columns(spreadsheet, sheet_name, column, column)
+  end
+
+  @doc """
+  Shows a hidden column again.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.unhide_column(spreadsheet, "Sheet1", "C")
+  """
+  def unhide_column(%Spreadsheet{} = spreadsheet, sheet_name, column) do
+    unhide_columns(spreadsheet, sheet_name, column, column)
+  end
+
+  @doc """
+  Hides columns `start_column` to `end_column`.
+
+  Hidden columns keep their width, so they come back at the same size.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `start_column` - First column to hide (e.g., "B")
+  - `end_column` - Last column to hide (e.g., "D")
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.hide_columns(spreadsheet, "Sheet1", "B", "D")
+  """
+  def hide_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.set_columns_hidden(ref, sheet_name, start_column, end_column, true)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Shows columns `start_column` to `end_column` again.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.unhide_columns(spreadsheet, "Sheet1", "B", "D")
+  """
+  def unhide_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.set_columns_hidden(ref, sheet_name, start_column, end_column, false)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the height of rows `start_row` to `end_row`, in points.
+
+  The rows are marked as having a custom height, so Excel keeps the height instead
+  of sizing them to their contents. Heights go from 0 to 409 points.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_rows_height(spreadsheet, "Sheet1", 2, 50, 18.0)
+  """
+  def set_rows_height(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row, height) do
+    UmyaNative.set_rows_height(ref, sheet_name, start_row, end_row, to_float(height))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the width of columns `start_column` to `end_column`, in characters.
+
+  Widths go from 0 to 255 characters.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_columns_width(spreadsheet, "Sheet1", "B", "M", 12.0)
+  """
+  def set_columns_width(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        start_column,
+        end_column,
+        width
+      ) do
+    UmyaNative.set_columns_width(ref, sheet_name, start_column, end_column, to_float(width))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Clears the custom height of rows `start_row` to `end_row`.
+
+  The rows get the sheet's default height, and Excel sizes them to their contents
+  when it opens the file.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.reset_rows_height(spreadsheet, "Sheet1", 2, 50)
+  """
+  def reset_rows_height(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.reset_rows_height(ref, sheet_name, start_row, end_row)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets whether a row has a custom height.
+
+  Rows whose height was set keep it in Excel; other rows are sized to their contents.
+
+  ## Examples
+
+      {:ok, true} = UmyaSpreadsheet.RowColumnFunctions.get_row_custom_height(spreadsheet, "Sheet1", 2)
+  """
+  def get_row_custom_height(%Spreadsheet{reference: ref}, sheet_name, row_number) do
+    UmyaNative.get_row_custom_height(ref, sheet_name, row_number)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the height of rows without a height of their own, in points.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_default_row_height(spreadsheet, "Sheet1", 20.0)
+  """
+  def set_default_row_height(%Spreadsheet{reference: ref}, sheet_name, height) do
+    UmyaNative.set_default_row_height(ref, sheet_name, to_float(height))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets the height of rows without a height of their own, in points.
+
+  Returns 15.0, the height of the default font, unless a default height was set.
+
+  ## Examples
+
+      {:ok, 15.0} = UmyaSpreadsheet.RowColumnFunctions.get_default_row_height(spreadsheet, "Sheet1")
+  """
+  def get_default_row_height(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.get_default_row_height(ref, sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the width of columns without a width of their own, in characters.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_default_column_width(spreadsheet, "Sheet1", 12.0)
+  """
+  def set_default_column_width(%Spreadsheet{reference: ref}, sheet_name, width) do
+    UmyaNative.set_default_column_width(ref, sheet_name, to_float(width))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets the width of columns without a width of their own, in characters.
+
+  Returns 8.43 unless a default width was set.
+
+  ## Examples
+
+      {:ok, 8.43} = UmyaSpreadsheet.RowColumnFunctions.get_default_column_width(spreadsheet, "Sheet1")
+  """
+  def get_default_column_width(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.get_default_column_width(ref, sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Fits the width of a column to its cells.
+
+  Each cell is measured in its own font with its number format applied, using
+  bundled metrics for Calibri, Arial and Times New Roman; other fonts are measured
+  as Calibri. Wrapped cells count with their longest word and cells merged across
+  columns are skipped, as in Excel. A column without text keeps its width.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `column` - The column letter (e.g., "A", "B")
+  - `opts` - Options:
+    - `:min_width` - Narrowest width to set, in characters
+    - `:max_width` - Widest width to set, in characters
+
+  ## Returns
+
+  - `{:ok, widths}` on success, a map of column letters to their new widths
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, %{"A" => width}} = UmyaSpreadsheet.RowColumnFunctions.autofit_column(spreadsheet, "Sheet1", "A")
+  """
+  def autofit_column(%Spreadsheet{} = spreadsheet, sheet_name, column, opts \\ []) do
+    autofit_columns(spreadsheet, sheet_name, "#{column}:#{column}", opts)
+  end
+
+  @doc """
+  Fits the widths of columns to the cells of a range.
+
+  Only the cells inside the range are measured, so a header row or a block of
+  notes can be left out. See `autofit_column/4` for how cells are measured.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - A range such as "A1:D20" or "A:D", or `nil` for the whole sheet
+  - `opts` - Options:
+    - `:min_width` - Narrowest width to set, in characters
+    - `:max_width` - Widest width to set, in characters
+
+  ## Returns
+
+  - `{:ok, widths}` on success, a map of column letters to their new widths
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, widths} =
+        UmyaSpreadsheet.RowColumnFunctions.autofit_columns(spreadsheet, "Sheet1", "A2:D100", max_width: 50)
+  """
+  def autofit_columns(%Spreadsheet{reference: ref}, sheet_name, range, opts \\ []) do
+    UmyaNative.autofit_columns(
+      ref,
+      sheet_name,
+      range,
+      to_float(Keyword.get(opts, :min_width)),
+      to_float(Keyword.get(opts, :max_width))
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Fits the widths of all columns of a sheet to their cells.
+
+  Same as `autofit_columns/4` with a `nil` range.
+
+  ## Examples
+
+      {:ok, _widths} = UmyaSpreadsheet.RowColumnFunctions.autofit_sheet(spreadsheet, "Sheet1")
+  """
+  def autofit_sheet(%Spreadsheet{} = spreadsheet, sheet_name, opts \\ []) do
+    autofit_columns(spreadsheet, sheet_name, nil, opts)
+  end
+
+  @doc """
+  Fits the heights of rows to their cells.
+
+  A row gets the height of its largest font, times the number of lines its wrapped
+  cells need at the current column widths, so fit the columns first. Cells merged
+  across rows are skipped, as in Excel. A row without text keeps its height.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - A range such as "A1:D20" or "2:10", or `nil` for the whole sheet
+  - `opts` - Options:
+    - `:min_height` - Lowest height to set, in points
+    - `:max_height` - Highest height to set, in points
+
+  ## Returns
+
+  - `{:ok, heights}` on success, a map of row numbers to their new heights in points
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, %{2 => height}} = UmyaSpreadsheet.RowColumnFunctions.autofit_rows(spreadsheet, "Sheet1", "2:2")
+  """
+  def autofit_rows(%Spreadsheet{reference: ref}, sheet_name, range, opts \\ []) do
+    UmyaNative.autofit_rows(
+      ref,
+      sheet_name,
+      range,
+      to_float(Keyword.get(opts, :min_height)),
+      to_float(Keyword.get(opts, :max_height))
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Groups rows one outline level deeper, as Excel's Group does.
+
+  Outline levels go from 1 to 7; grouping rows that are already grouped nests the
+  new group inside the existing one. The summary row of a group is the row after
+  it, or the row before it when summaries are placed above (see
+  `set_outline_summary/3`).
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `start_row` - First row of the group (1-based)
+  - `end_row` - Last row of the group
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      # Detail rows 2-5 roll up into the total in row 6
+      :ok = UmyaSpreadsheet.RowColumnFunctions.group_rows(spreadsheet, "Budget", 2, 5)
+  """
+  def group_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.group_outline(ref, sheet_name, row_range(start_row, end_row))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Ungroups rows by one outline level, as Excel's Ungroup does.
+
+  Fails when none of the rows is grouped.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.ungroup_rows(spreadsheet, "Budget", 2, 5)
+  """
+  def ungroup_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.ungroup_outline(ref, sheet_name, row_range(start_row, end_row))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Groups columns one outline level deeper, as Excel's Group does.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `start_column` - First column of the group (e.g., "B")
+  - `end_column` - Last column of the group (e.g., "D")
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.group_columns(spreadsheet, "Budget", "B", "M")
+  """
+  def group_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.group_outline(ref, sheet_name, column_range(start_column, end_column))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Ungroups columns by one outline level, as Excel's Ungroup does.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.ungroup_columns(spreadsheet, "Budget", "B", "M")
+  """
+  def ungroup_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.ungroup_outline(ref, sheet_name, column_range(start_column, end_column))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the outline level of rows, from 0 (not grouped) to 7.
+
+  Useful to lay out a whole hierarchy at once, e.g. from the depth of each line of
+  a budget.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_row_outline_level(spreadsheet, "Budget", 3, 4, 2)
+  """
+  def set_row_outline_level(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row, level) do
+    UmyaNative.set_outline_level(ref, sheet_name, row_range(start_row, end_row), level)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets the outline level of columns, from 0 (not grouped) to 7.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_column_outline_level(spreadsheet, "Budget", "B", "D", 1)
+  """
+  def set_column_outline_level(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        start_column,
+        end_column,
+        level
+      ) do
+    UmyaNative.set_outline_level(ref, sheet_name, column_range(start_column, end_column), level)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Collapses the row group that the given rows belong to.
+
+  The innermost level the rows share decides the group. Its rows are hidden and its
+  summary row is marked collapsed, so Excel shows the group's "+" button.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.collapse_rows(spreadsheet, "Budget", 2, 5)
+  """
+  def collapse_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.set_outline_collapsed(ref, sheet_name, row_range(start_row, end_row), true)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Expands the row group that the given rows belong to.
+
+  Nested groups that are collapsed stay collapsed, as in Excel.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.expand_rows(spreadsheet, "Budget", 2, 5)
+  """
+  def expand_rows(%Spreadsheet{reference: ref}, sheet_name, start_row, end_row) do
+    UmyaNative.set_outline_collapsed(ref, sheet_name, row_range(start_row, end_row), false)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Collapses the column group that the given columns belong to.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.collapse_columns(spreadsheet, "Budget", "B", "M")
+  """
+  def collapse_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.set_outline_collapsed(
+      ref,
+      sheet_name,
+      column_range(start_column, end_column),
+      true
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Expands the column group that the given columns belong to.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.RowColumnFunctions.expand_columns(spreadsheet, "Budget", "B", "M")
+  """
+  def expand_columns(%Spreadsheet{reference: ref}, sheet_name, start_column, end_column) do
+    UmyaNative.set_outline_collapsed(
+      ref,
+      sheet_name,
+      column_range(start_column, end_column),
+      false
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets the outline structure of a sheet.
+
+  ## Returns
+
+  - `{:ok, outline}` on success, a map with:
+    - `"rows"` - Row groups of every level, outermost first, each a map with
+      `"start"`, `"end"`, `"level"` and `"collapsed"`
+    - `"columns"` - Column groups in the same form, with column letters as bounds
+    - `"summary_below"` - Whether summary rows follow their groups
+    - `"summary_right"` - Whether summary columns follow their groups
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, %{"rows" => [%{"start" => 2, "end" => 5, "level" => 1, "collapsed" => true}]}} =
+        UmyaSpreadsheet.RowColumnFunctions.get_outline(spreadsheet, "Budget")
+  """
+  def get_outline(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.get_outline(ref, sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets where the summary rows and columns of outline groups go.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `opts` - Options:
+    - `:summary_below` - `false` puts summary rows above their groups
+    - `:summary_right` - `false` puts summary columns left of their groups
+
+  ## Examples
+
+      # Totals sit above their detail rows
+      :ok = UmyaSpreadsheet.RowColumnFunctions.set_outline_summary(spreadsheet, "Budget", summary_below: false)
+  """
+  def set_outline_summary(%Spreadsheet{} = spreadsheet, sheet_name, opts) do
+    properties =
+      opts
+      |> Keyword.take([:summary_below, :summary_right])
+      |> Map.new()
+
+    UmyaSpreadsheet.SheetFunctions.set_sheet_properties(spreadsheet, sheet_name, properties)
+  end
+
+  defp row_range(start_row, end_row), do: "#{start_row}:#{end_row}"
+
+  defp column_range(start_column, end_column), do: "#{start_column}:#{end_column}"
+
+  defp to_float(nil), do: nil
+  defp to_float(value) when is_integer(value), do: value / 1
+  defp to_float(value), do: value
 end
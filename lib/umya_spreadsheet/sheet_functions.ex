@@ -74,6 +74,47 @@ defmodule UmyaSpreadsheet.SheetFunctions do
     |> ErrorHandling.standardize_result()
   end
 
+  @doc """
+  Copies a sheet from one spreadsheet into another, appending it as the last sheet.
+
+  Cell formatting, images, charts, comments, data validations and conditional
+  formats are copied with the sheet. Tables whose name is already taken in the
+  target are renamed with a numeric suffix (e.g. "Sales" becomes "Sales_2"), and
+  structured references to them are updated. Defined names scoped to the sheet are
+  scoped to the copy, and named cell styles the sheet uses are added to the target
+  when it does not have a style of the same name. References to the sheet's own
+  name are rewritten to the new name.
+
+  `source` and `target` may be the same spreadsheet.
+
+  ## Parameters
+
+  - `source` - The spreadsheet struct to copy from
+  - `source_sheet_name` - The name of the sheet to copy
+  - `target` - The spreadsheet struct to copy into
+  - `new_sheet_name` - The name of the sheet in `target`
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, template} = UmyaSpreadsheet.read_file("templates/summary.xlsx")
+      {:ok, report} = UmyaSpreadsheet.new()
+      :ok = UmyaSpreadsheet.SheetFunctions.copy_sheet_to_workbook(template, "Summary", report, "Q1 Summary")
+  """
+  def copy_sheet_to_workbook(
+        %Spreadsheet{reference: source_ref},
+        source_sheet_name,
+        %Spreadsheet{reference: target_ref},
+        new_sheet_name
+      ) do
+    UmyaNative.copy_sheet_to_workbook(source_ref, source_sheet_name, target_ref, new_sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Removes a sheet from the spreadsheet.
 
@@ -97,14 +138,60 @@ defmodule UmyaSpreadsheet.SheetFunctions do
     |> ErrorHandling.standardize_result()
   end
 
+  @doc """
+  Moves a sheet to another position in the workbook.
+
+  The active sheet stays active wherever it ends up, and defined names scoped to a
+  sheet (such as print areas) stay with their sheet.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet to move
+  - `position` - Where to move the sheet:
+    - a zero-based index, where `0` makes it the first sheet
+    - `{:before, other_sheet}` or `before: other_sheet`
+    - `{:after, other_sheet}` or `after: other_sheet`
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      # Put the summary first
+      :ok = UmyaSpreadsheet.SheetFunctions.move_sheet(spreadsheet, "Summary", 0)
+
+      :ok = UmyaSpreadsheet.SheetFunctions.move_sheet(spreadsheet, "Notes", after: "Data")
+  """
+  def move_sheet(%Spreadsheet{} = spreadsheet, sheet_name, [{side, other_sheet}])
+      when side in [:before, :after] do
+    move_sheet(spreadsheet, sheet_name, {side, other_sheet})
+  end
+
+  def move_sheet(%Spreadsheet{reference: ref}, sheet_name, position) do
+    UmyaNative.move_sheet(ref, sheet_name, position)
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Renames an existing sheet.
 
+  By default only the sheet's name changes. With `rewrite_references: true` every
+  reference to the sheet is updated as Excel does: cell formulas on all sheets,
+  defined names, conditional formats, data validation sources, hyperlinks to
+  places in the workbook, chart series and pivot table sources. New names with
+  spaces or apostrophes are quoted, as in `'Q1 ''24'!A1`.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
   - `old_sheet_name` - The current name of the sheet
   - `new_sheet_name` - The new name for the sheet
+  - `opts` - Options:
+    - `:rewrite_references` - Point references to the sheet at the new name
+      (default `false`)
 
   ## Returns
 
@@ -115,20 +202,35 @@ defmodule UmyaSpreadsheet.SheetFunctions do
 
       {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
       :ok = UmyaSpreadsheet.SheetFunctions.rename_sheet(spreadsheet, "Sheet1", "Updated Sheet")
+
+      # `=Data!A1` on other sheets becomes `='Raw Data'!A1`
+      :ok = UmyaSpreadsheet.SheetFunctions.rename_sheet(spreadsheet, "Data", "Raw Data",
+        rewrite_references: true
+      )
   """
-  def rename_sheet(%Spreadsheet{reference: ref}, old_sheet_name, new_sheet_name) do
-    UmyaNative.rename_sheet(ref, old_sheet_name, new_sheet_name)
+  def rename_sheet(%Spreadsheet{reference: ref}, old_sheet_name, new_sheet_name, opts \\ []) do
+    UmyaNative.rename_sheet(
+      ref,
+      old_sheet_name,
+      new_sheet_name,
+      Keyword.get(opts, :rewrite_references, false)
+    )
     |> ErrorHandling.standardize_result()
   end
 
   @doc """
   Sets the visibility state of a sheet.
 
+  A "veryhidden" sheet does not appear in Excel's Unhide dialog and can only be
+  shown again programmatically. The last visible sheet cannot be hidden, and
+  hiding the active sheet makes the first visible sheet active.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
   - `sheet_name` - The name of the sheet
-  - `state` - The visibility state ("visible", "hidden", "veryhidden")
+  - `state` - The visibility state ("visible", "hidden", "veryhidden"; "veryHidden"
+    and "very_hidden" are accepted too)
 
   ## Returns
 
@@ -145,6 +247,85 @@ defmodule UmyaSpreadsheet.SheetFunctions do
     |> ErrorHandling.standardize_result()
   end
 
+  @doc """
+  Gets the properties of a sheet.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+
+  ## Returns
+
+  - `{:ok, properties}` on success, a map with:
+    - `"state"` - "visible", "hidden" or "veryhidden"
+    - `"tab_color"` - `nil`, or a map with the `"rgb"` value ("#RRGGBB", resolved
+      from the theme or the indexed palette) and the `"theme"`, `"indexed"` and
+      `"tint"` the color is defined with, each `nil` when not used
+    - `"code_name"` - The name macros use for the sheet, or `nil`
+    - `"filter_mode"` - Whether a filter hides rows of the sheet
+    - `"summary_below"` - Whether outline summary rows are below their details
+    - `"summary_right"` - Whether outline summary columns are right of their details
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
+      {:ok, %{"state" => "veryhidden", "tab_color" => %{"theme" => 4, "tint" => 0.4}}} =
+        UmyaSpreadsheet.SheetFunctions.get_sheet_properties(spreadsheet, "Lookup")
+  """
+  def get_sheet_properties(%Spreadsheet{reference: ref}, sheet_name) do
+    UmyaNative.get_sheet_properties(ref, sheet_name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Sets properties of a sheet from a map.
+
+  Accepts the map `get_sheet_properties/2` returns; properties that are not given
+  keep their value. All of them are kept when the file is written and read again.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `properties` - Map with any of:
+    - `:state` - "visible", "hidden" or "veryhidden", as for `set_sheet_state/3`
+    - `:tab_color` - A color string such as "#4472C4", `nil` to remove the color,
+      or a map with `:theme` (theme color index), `:indexed` (palette index) or
+      `:rgb`, and an optional `:tint` from -1.0 (darker) to 1.0 (lighter)
+    - `:code_name` - The name macros use for the sheet, or `nil` to remove it
+    - `:filter_mode` - Whether a filter hides rows of the sheet
+    - `:summary_below` - `false` puts outline summary rows above their details
+    - `:summary_right` - `false` puts outline summary columns left of their details
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.SheetFunctions.set_sheet_properties(spreadsheet, "Lookup", %{
+        state: "veryhidden",
+        code_name: "shLookup",
+        tab_color: %{theme: 4, tint: 0.4}
+      })
+  """
+  def set_sheet_properties(%Spreadsheet{reference: ref}, sheet_name, properties)
+      when is_map(properties) do
+    properties =
+      properties
+      |> normalize_options()
+      |> Map.update("tab_color", nil, fn
+        color when is_map(color) -> normalize_options(color)
+        color -> color
+      end)
+
+    UmyaNative.set_sheet_properties(ref, sheet_name, properties)
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Sets the protection for a sheet.
 
@@ -173,6 +354,51 @@ defmodule UmyaSpreadsheet.SheetFunctions do
     |> ErrorHandling.standardize_result()
   end
 
+  @doc """
+  Sets sheet protection flags and the protection password from a map.
+
+  Accepts the same map `get_sheet_protection/2` returns, so protection can be read
+  from one sheet and applied to another. Options that are not given keep their
+  current value; on an unprotected sheet the flags start from their Excel defaults
+  and `:sheet` defaults to `true`.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `options` - Map with any of:
+    - `:sheet` - Whether protection is enforced
+    - `:objects`, `:scenarios`, `:format_cells`, `:format_columns`, `:format_rows`,
+      `:insert_columns`, `:insert_rows`, `:insert_hyperlinks`, `:delete_columns`,
+      `:delete_rows`, `:select_locked_cells`, `:select_unlocked_cells`, `:sort`,
+      `:auto_filter`, `:pivot_tables` - `true` blocks the action while protected
+    - `:password` - Password to hash, or `nil` to remove the password
+    - `:algorithm_name` - "SHA-256", "SHA-384" or "SHA-512" (default "SHA-512")
+    - `:salt_value` - Base64 encoded salt (default: random 16 bytes)
+    - `:spin_count` - Hash iterations (default 100000)
+    - `:hash_value` - Base64 encoded hash to store as is, instead of `:password`
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
+      :ok = UmyaSpreadsheet.SheetFunctions.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        password: "secret",
+        format_cells: false,
+        sort: false,
+        select_locked_cells: true
+      })
+  """
+  def set_sheet_protection_options(%Spreadsheet{reference: ref}, sheet_name, options)
+      when is_map(options) do
+    UmyaNative.set_sheet_protection_options(ref, sheet_name, normalize_options(options))
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Moves a range of cells to a new position.
 
@@ -200,14 +426,78 @@ defmodule UmyaSpreadsheet.SheetFunctions do
     |> ErrorHandling.standardize_result()
   end
 
+  @doc """
+  Copies a range of cells, like Excel's Copy and Paste Special.
+
+  The range can be pasted within the same sheet, into another sheet, or into a sheet
+  of another spreadsheet. Relative references in formulas, data validations and
+  conditional formats move with the range; references that would leave the sheet
+  become `#REF!`.
+
+  ## Parameters
+
+  - `source` - The spreadsheet struct to copy from
+  - `source_sheet` - The name of the sheet to copy from
+  - `range` - The range to copy (e.g., "A1:D10"); whole rows or columns are limited to the used area
+  - `target` - The spreadsheet struct to paste into, which may be `source`
+  - `target_sheet` - The name of the sheet to paste into
+  - `target_cell` - The top-left cell of the pasted range (e.g., "F1")
+  - `mode` - What to paste (default `:all`):
+    - `:all` - values, formulas, styles, merges, comments, hyperlinks, data validations
+      and conditional formats; empty source cells clear the target cells
+    - `:values` - values only, with formulas replaced by their last calculated result
+    - `:formulas` - values and formulas, keeping the target styles
+    - `:formats` - styles, merged cells and conditional formats
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      # Duplicate a block of formulas two columns to the right
+      :ok = UmyaSpreadsheet.SheetFunctions.copy_range(spreadsheet, "Sheet1", "A1:B10", spreadsheet, "Sheet1", "C1")
+
+      # Paste the look of a template header into another workbook
+      :ok = UmyaSpreadsheet.SheetFunctions.copy_range(template, "Header", "A1:H3", report, "Sheet1", "A1", :formats)
+  """
+  def copy_range(
+        %Spreadsheet{reference: source_ref},
+        source_sheet,
+        range,
+        %Spreadsheet{reference: target_ref},
+        target_sheet,
+        target_cell,
+        mode \\ :all
+      ) do
+    UmyaNative.copy_range(
+      source_ref,
+      source_sheet,
+      range,
+      target_ref,
+      target_sheet,
+      target_cell,
+      to_string(mode)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
   @doc """
   Merges cells in a specified range.
 
+  The range must span more than one cell and must not overlap cells that are
+  already merged; such files would have to be repaired by Excel.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
   - `sheet_name` - The name of the sheet
   - `range` - The range to merge (e.g., "A1:B5")
+  - `opts` - Options:
+    - `:center` - Center the content of the top-left cell horizontally (default `false`)
+    - `:clear_non_anchor` - Remove the values and formulas of all cells but the
+      top-left one (default `false`)
 
   ## Returns
 
@@ -218,21 +508,125 @@ defmodule UmyaSpreadsheet.SheetFunctions do
 
       {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
       :ok = UmyaSpreadsheet.SheetFunctions.add_merge_cells(spreadsheet, "Sheet1", "A1:B2")
+
+      # Merge & Center
+      :ok = UmyaSpreadsheet.SheetFunctions.add_merge_cells(spreadsheet, "Sheet1", "A1:F1",
+        center: true,
+        clear_non_anchor: true
+      )
   """
-  def add_merge_cells(%Spreadsheet{reference: ref}, sheet_name, range) do
-    UmyaNative.add_merge_cells(ref, sheet_name, range)
+  def add_merge_cells(%Spreadsheet{reference: ref}, sheet_name, range, opts \\ []) do
+    UmyaNative.add_merge_cells(
+      ref,
+      sheet_name,
+      range,
+      Keyword.get(opts, :center, false),
+      Keyword.get(opts, :clear_non_anchor, false)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Merges the cells of each row of a range separately, like Excel's "Merge Across".
+
+  Takes the same options as `add_merge_cells/4`.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The range to merge, spanning more than one column (e.g., "A1:D5")
+  - `opts` - `:center` and `:clear_non_anchor`, see `add_merge_cells/4`
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      # Merges A1:D1, A2:D2 and A3:D3
+      :ok = UmyaSpreadsheet.SheetFunctions.merge_cells_across(spreadsheet, "Sheet1", "A1:D3")
+  """
+  def merge_cells_across(%Spreadsheet{reference: ref}, sheet_name, range, opts \\ []) do
+    UmyaNative.merge_cells_across(
+      ref,
+      sheet_name,
+      range,
+      Keyword.get(opts, :center, false),
+      Keyword.get(opts, :clear_non_anchor, false)
+    )
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Unmerges every merged area that overlaps a range.
+
+  As in Excel, a merged area is unmerged as a whole when any of its cells is in
+  the range. Ranges without merged cells are left unchanged.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - The range to unmerge (e.g., "A1" or "A1:Z100")
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.SheetFunctions.unmerge_cells(spreadsheet, "Sheet1", "B2")
+  """
+  def unmerge_cells(%Spreadsheet{reference: ref}, sheet_name, range) do
+    UmyaNative.unmerge_cells(ref, sheet_name, range)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Gets the merged area that covers a cell.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `cell_address` - The cell to look up (e.g., "B2")
+
+  ## Returns
+
+  - `{:ok, range}` with the merged area (e.g., "A1:C3")
+  - `{:ok, nil}` when the cell is not merged
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.SheetFunctions.add_merge_cells(spreadsheet, "Sheet1", "A1:C3")
+      {:ok, "A1:C3"} = UmyaSpreadsheet.SheetFunctions.get_merged_area(spreadsheet, "Sheet1", "B2")
+  """
+  def get_merged_area(%Spreadsheet{reference: ref}, sheet_name, cell_address) do
+    UmyaNative.get_merged_area(ref, sheet_name, cell_address)
     |> ErrorHandling.standardize_result()
   end
 
   @doc """
   Inserts new rows into a sheet.
 
+  Tables and data validations grow when rows are inserted inside them and move down
+  when rows are inserted above them, as in Excel.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
   - `sheet_name` - The name of the sheet
   - `row_index` - The index where rows should be inserted
   - `amount` - The number of rows to insert
+  - `opts` - Options:
+    - `:inherit` - `:above` or `:below` to give the new rows the height, styles,
+      formulas and data validations of that row, as Excel does when inserting
+      inside a formatted table. Values are not copied; formulas move their relative
+      references. By default the new rows are blank.
 
   ## Returns
 
@@ -244,21 +638,31 @@ defmodule UmyaSpreadsheet.SheetFunctions do
       {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
       # Insert 2 new rows at row 3
       :ok = UmyaSpreadsheet.SheetFunctions.insert_new_row(spreadsheet, "Sheet1", 3, 2)
+
+      # Insert a row formatted like the row above it
+      :ok = UmyaSpreadsheet.SheetFunctions.insert_new_row(spreadsheet, "Sheet1", 10, 1, inherit: :above)
   """
-  def insert_new_row(%Spreadsheet{reference: ref}, sheet_name, row_index, amount) do
-    UmyaNative.insert_new_row(ref, sheet_name, row_index, amount)
+  def insert_new_row(%Spreadsheet{reference: ref}, sheet_name, row_index, amount, opts \\ []) do
+    UmyaNative.insert_new_row(ref, sheet_name, row_index, amount, inherit_option(opts))
     |> ErrorHandling.standardize_result()
   end
 
   @doc """
   Inserts new columns into a sheet.
 
+  Tables and data validations grow when columns are inserted inside them and move
+  right when columns are inserted before them. New table columns are named
+  "Column1", "Column2" and so on.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
   - `sheet_name` - The name of the sheet
   - `column` - The column letter where columns should be inserted (e.g., "C")
   - `amount` - The number of columns to insert
+  - `opts` - Options:
+    - `:inherit` - `:left` or `:right` to give the new columns the width, styles,
+      formulas and data validations of that column
 
   ## Returns
 
@@ -270,9 +674,12 @@ defmodule UmyaSpreadsheet.SheetFunctions do
       {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
       # Insert 2 new columns at column C
       :ok = UmyaSpreadsheet.SheetFunctions.insert_new_column(spreadsheet, "Sheet1", "C", 2)
+
+      # Insert a column formatted like column B
+      :ok = UmyaSpreadsheet.SheetFunctions.insert_new_column(spreadsheet, "Sheet1", "C", 1, inherit: :left)
   """
-  def insert_new_column(%Spreadsheet{reference: ref}, sheet_name, column, amount) do
-    UmyaNative.insert_new_column(ref, sheet_name, column, amount)
+  def insert_new_column(%Spreadsheet{reference: ref}, sheet_name, column, amount, opts \\ []) do
+    UmyaNative.insert_new_column(ref, sheet_name, column, amount, inherit_option(opts))
     |> ErrorHandling.standardize_result()
   end
 
@@ -285,6 +692,7 @@ defmodule UmyaSpreadsheet.SheetFunctions do
   - `sheet_name` - The name of the sheet
   - `column_index` - The column index (1-based) where columns should be inserted
   - `amount` - The number of columns to insert
+  - `opts` - Options, as for `insert_new_column/5`
 
   ## Returns
 
@@ -297,14 +705,29 @@ defmodule UmyaSpreadsheet.SheetFunctions do
       # Insert 2 new columns at column index 3 (column C)
       :ok = UmyaSpreadsheet.SheetFunctions.insert_new_column_by_index(spreadsheet, "Sheet1", 3, 2)
   """
-  def insert_new_column_by_index(%Spreadsheet{reference: ref}, sheet_name, column_index, amount) do
-    UmyaNative.insert_new_column_by_index(ref, sheet_name, column_index, amount)
+  def insert_new_column_by_index(
+        %Spreadsheet{reference: ref},
+        sheet_name,
+        column_index,
+        amount,
+        opts \\ []
+      ) do
+    UmyaNative.insert_new_column_by_index(
+      ref,
+      sheet_name,
+      column_index,
+      amount,
+      inherit_option(opts)
+    )
     |> ErrorHandling.standardize_result()
   end
 
   @doc """
   Removes rows from a sheet.
 
+  Tables and data validations shrink with the removed rows and are dropped when all
+  of their rows are removed. Removing the totals row of a table turns it off.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
@@ -331,6 +754,9 @@ defmodule UmyaSpreadsheet.SheetFunctions do
   @doc """
   Removes columns from a sheet.
 
+  Tables lose the removed columns, and tables and data validations are dropped when
+  all of their columns are removed.
+
   ## Parameters
 
   - `spreadsheet` - The spreadsheet struct
@@ -452,7 +878,7 @@ defmodule UmyaSpreadsheet.SheetFunctions do
   end
 
   @doc """
-  Checks if a sheet is protected and gets protection details.
+  Gets the protection settings of a sheet.
 
   ## Parameters
 
@@ -461,13 +887,16 @@ defmodule UmyaSpreadsheet.SheetFunctions do
 
   ## Returns
 
-  - `{:ok, %{protected: boolean, details: map}}` on success
+  - `{:ok, protection}` on success, where `protection` has the keys accepted by
+    `set_sheet_protection_options/3`: the boolean flags (`"sheet"` tells whether
+    protection is enforced) and `"algorithm_name"`, `"hash_value"`, `"salt_value"`
+    and `"spin_count"`, which are `nil` when no hashed password is stored
   - `{:error, reason}` on failure
 
   ## Examples
 
       {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
-      {:ok, %{protected: true, details: %{}}} = UmyaSpreadsheet.SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+      {:ok, %{"sheet" => true, "format_cells" => true}} = UmyaSpreadsheet.SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
   """
   def get_sheet_protection(%Spreadsheet{reference: ref}, sheet_name) do
     # Note: This would need a native implementation if not already available
@@ -508,4 +937,19 @@ defmodule UmyaSpreadsheet.SheetFunctions do
   rescue
     UndefinedFunctionError -> {:error, "get_merge_cells native function not yet implemented"}
   end
+
+  defp normalize_options(options) do
+    Enum.into(options, %{}, fn
+      {key, value} when is_atom(key) -> {Atom.to_string(key), value}
+      {key, value} -> {key, value}
+    end)
+  end
+
+  defp inherit_option(opts) do
+    case Keyword.get(opts, :inherit) do
+      nil -> nil
+      inherit -> to_string(inherit)
+    end
+  end
+
 end
@@ -4,6 +4,7 @@ defmodule UmyaSpreadsheet.StylingFunctions do
   """
 
   alias UmyaSpreadsheet.Spreadsheet
+  alias UmyaSpreadsheet.ErrorHandling
   alias UmyaNative
 
   @doc """
@@ -37,4 +38,140 @@ defmodule UmyaSpreadsheet.StylingFunctions do
       result -> result
     end
   end
+
+  @doc """
+  Applies a complete style to every cell of a range in one call.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - A cell range ("A1:Z500"), whole rows ("3:5") or whole columns ("A:C")
+  - `style` - A map with any of these keys (atom or string keys, `nil` values are ignored):
+    - `:font` - `%{name, size, bold, italic, underline, strikethrough, color}`;
+      underline is "none", "single", "double", "single_accounting" or "double_accounting"
+    - `:fill` - `%{pattern, color, background_color}`; a color without pattern gives a solid fill
+    - `:borders` - `%{left, right, top, bottom, diagonal, all, diagonal_up, diagonal_down}`,
+      each side either `%{style, color}` or just the border style (e.g. "thin", "double")
+    - `:alignment` - `%{horizontal, vertical, wrap_text, text_rotation}`
+    - `:number_format` - A format code such as "0.00%"
+    - `:protection` - `%{locked, hidden}`
+  - `mode` - `:merge` (default) changes only the given parts of each cell's style,
+    `:replace` rebuilds each cell's style from the map alone
+
+  For whole rows and columns the row or column style is set together with the style
+  of the cells that already exist in them.
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.StylingFunctions.set_range_style(spreadsheet, "Sheet1", "A1:D1", %{
+        font: %{bold: true, color: "#FFFFFF"},
+        fill: %{color: "#4472C4"},
+        borders: %{bottom: %{style: "medium", color: "#000000"}},
+        alignment: %{horizontal: "center"}
+      })
+
+      :ok = UmyaSpreadsheet.StylingFunctions.set_range_style(spreadsheet, "Sheet1", "B:B", %{number_format: "0.00"})
+  """
+  def set_range_style(%Spreadsheet{reference: ref}, sheet_name, range, style, mode \\ :merge)
+      when is_map(style) do
+    UmyaNative.set_range_style(ref, sheet_name, range, normalize_style(style), to_string(mode))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Defines a custom named cell style, like the styles in Excel's Cell Styles gallery.
+
+  The new style starts from the formatting of the workbook's "Normal" style, with
+  `style` applied on top. A workbook without named styles gets "Normal" first.
+  Names are unique regardless of case.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `name` - The style name (e.g., "Brand Heading")
+  - `style` - A style map as accepted by `set_range_style/5`
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.StylingFunctions.add_named_style(spreadsheet, "Brand Heading", %{
+        font: %{bold: true, size: 16, color: "#1F4E79"},
+        borders: %{bottom: %{style: "medium", color: "#1F4E79"}}
+      })
+  """
+  def add_named_style(%Spreadsheet{reference: ref}, name, style) when is_map(style) do
+    UmyaNative.add_named_style(ref, name, normalize_style(style))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Lists the named cell styles of the workbook, such as those of a loaded template.
+
+  Each style is a map with `"name"`, `"builtin"` and `"builtin_id"` (Excel's id for
+  built-in styles like "Heading 1", `nil` for custom styles).
+
+  ## Examples
+
+      {:ok, styles} = UmyaSpreadsheet.StylingFunctions.list_named_styles(spreadsheet)
+      # [%{"name" => "Normal", "builtin" => true, "builtin_id" => 0},
+      #  %{"name" => "Brand Heading", "builtin" => false, "builtin_id" => nil}]
+  """
+  def list_named_styles(%Spreadsheet{reference: ref}) do
+    UmyaNative.list_named_styles(ref)
+  end
+
+  @doc """
+  Applies a named cell style to a range.
+
+  The formatting the named style defines replaces that of each cell, and the cells
+  are linked to the style, so Excel shows it as the cell style and
+  `update_named_style/3` reaches them. Formatting the style does not define is kept.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `sheet_name` - The name of the sheet
+  - `range` - A cell range ("A1:D1"), whole rows ("3:5") or whole columns ("A:C")
+  - `name` - The named style, matched regardless of case
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.StylingFunctions.apply_named_style(spreadsheet, "Sheet1", "A1:D1", "Heading 1")
+  """
+  def apply_named_style(%Spreadsheet{reference: ref}, sheet_name, range, name) do
+    UmyaNative.apply_named_style(ref, sheet_name, range, name)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Changes a named cell style and every cell, row and column using it.
+
+  Only the parts given in `style` change, in the named style as well as in the
+  cells, so formatting applied on top of the style is kept.
+
+  ## Examples
+
+      :ok = UmyaSpreadsheet.StylingFunctions.update_named_style(spreadsheet, "Brand Heading", %{
+        font: %{color: "#C00000"}
+      })
+  """
+  def update_named_style(%Spreadsheet{reference: ref}, name, style) when is_map(style) do
+    UmyaNative.update_named_style(ref, name, normalize_style(style))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc false
+  def normalize_style(map) when is_map(map) do
+    Enum.into(map, %{}, fn {key, value} -> {to_string(key), normalize_style(value)} end)
+  end
+
+  def normalize_style(value) when is_atom(value) and value not in [nil, true, false],
+    do: Atom.to_string(value)
+
+  def normalize_style(value), do: value
 end
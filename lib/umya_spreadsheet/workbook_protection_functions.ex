@@ -7,9 +7,11 @@ defmodule UmyaSpreadsheet.WorkbookProtectionFunctions do
   * Check if workbook protection is enabled
   * Retrieve protection settings and status
   * Enable/disable various protection features
+  * Verify a password against the stored hash
   """
 
   alias UmyaSpreadsheet.Spreadsheet
+  alias UmyaSpreadsheet.ErrorHandling
   alias UmyaNative
 
   @doc """
@@ -62,4 +64,97 @@ defmodule UmyaSpreadsheet.WorkbookProtectionFunctions do
   def get_workbook_protection_details(%Spreadsheet{reference: ref}) do
     UmyaNative.get_workbook_protection_details(ref)
   end
+
+  @doc """
+  Sets workbook protection lock flags and the protection password from a map.
+
+  Options that are not given keep their current value; on an unprotected workbook
+  `:lock_structure` defaults to `true` and the other flags to `false`.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `options` - Map with any of:
+    - `:lock_structure` - Prevents adding, moving, deleting, hiding and renaming sheets
+    - `:lock_windows` - Prevents moving and resizing workbook windows
+    - `:lock_revision` - Prevents turning off revision tracking
+    - `:password` - Password to hash, or `nil` to remove the password
+    - `:algorithm_name` - "SHA-256", "SHA-384" or "SHA-512" (default "SHA-512")
+    - `:salt_value` - Base64 encoded salt (default: random 16 bytes)
+    - `:spin_count` - Hash iterations (default 100000)
+    - `:hash_value` - Base64 encoded hash to store as is, instead of `:password`
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("input.xlsx")
+      :ok = UmyaSpreadsheet.WorkbookProtectionFunctions.set_workbook_protection_options(spreadsheet, %{
+        lock_structure: true,
+        lock_windows: true,
+        password: "secret"
+      })
+  """
+  def set_workbook_protection_options(%Spreadsheet{reference: ref}, options)
+      when is_map(options) do
+    UmyaNative.set_workbook_protection_options(ref, normalize_options(options))
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Removes workbook protection, including its password.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+
+  ## Returns
+
+  - `:ok` on success
+  - `{:error, reason}` on failure
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("protected.xlsx")
+      :ok = UmyaSpreadsheet.WorkbookProtectionFunctions.remove_workbook_protection(spreadsheet)
+  """
+  def remove_workbook_protection(%Spreadsheet{reference: ref}) do
+    UmyaNative.remove_workbook_protection(ref)
+    |> ErrorHandling.standardize_result()
+  end
+
+  @doc """
+  Checks a candidate password against the stored workbook protection password.
+
+  The hashed password is used when present, otherwise the legacy 16-bit hash.
+  Without any stored password only the empty password matches.
+
+  ## Parameters
+
+  - `spreadsheet` - The spreadsheet struct
+  - `password` - The candidate password
+
+  ## Returns
+
+  - `{:ok, matches}` where matches is a boolean
+  - `{:error, reason}` if the workbook is not protected or on failure
+
+  ## Examples
+
+      {:ok, spreadsheet} = UmyaSpreadsheet.read_file("protected.xlsx")
+      {:ok, true} = UmyaSpreadsheet.WorkbookProtectionFunctions.verify_workbook_password(spreadsheet, "secret")
+  """
+  def verify_workbook_password(%Spreadsheet{reference: ref}, password) do
+    UmyaNative.verify_workbook_password(ref, password)
+  end
+
+  defp normalize_options(options) do
+    Enum.into(options, %{}, fn
+      {key, value} when is_atom(key) -> {Atom.to_string(key), value}
+      {key, value} -> {key, value}
+    end)
+  end
 end
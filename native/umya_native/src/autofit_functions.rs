@@ -0,0 +1,134 @@
+use rustler::{Encoder, Env, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
+
+use crate::atoms;
+use crate::helpers::autofit_helper;
+use crate::helpers::defined_name_helper::{MAX_COLUMN, MAX_ROW};
+use crate::helpers::range_helper::{self, Bounds};
+use crate::UmyaSpreadsheet;
+
+/// Fit column widths to the cells of a range
+///
+/// Measures the text of every cell in `range` (the whole sheet when nil) with the
+/// metrics of its font and sets the width of each column that has text, clamped to
+/// `min_width` and `max_width`. Returns a map of column letters to their new widths.
+#[rustler::nif]
+pub fn autofit_columns(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+    min_width: Option<f64>,
+    max_width: Option<f64>,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        || -> Result<HashMap<String, f64>, String> {
+            let bounds = parse_bounds(range.as_deref())?;
+            check_limits(min_width, max_width)?;
+
+            let mut guard = resource
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let sheet = guard
+                .get_sheet_by_name_mut(&sheet_name)
+                .ok_or_else(|| "Sheet not found".to_string())?;
+
+            let mut widths = HashMap::new();
+            for (column, width) in autofit_helper::fit_columns(sheet, bounds) {
+                let width = clamp(width, min_width, max_width);
+                let dimension = sheet.get_column_dimension_by_number_mut(&column);
+                dimension.set_width(width);
+                dimension.set_best_fit(true);
+                widths.insert(string_from_column_index(&column), width);
+            }
+            Ok(widths)
+        },
+    ));
+
+    match result {
+        Ok(Ok(widths)) => (atoms::ok(), widths).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in autofit_columns operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+/// Fit row heights to the cells of a range
+///
+/// Sets the height of each row in `range` (the whole sheet when nil) that has text
+/// to the height of its largest font, times the lines its wrapped cells need at the
+/// current column widths, clamped to `min_height` and `max_height`. Returns a map of
+/// row numbers to their new heights in points.
+#[rustler::nif]
+pub fn autofit_rows(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+    min_height: Option<f64>,
+    max_height: Option<f64>,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<HashMap<u32, f64>, String> {
+        let bounds = parse_bounds(range.as_deref())?;
+        check_limits(min_height, max_height)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let mut heights = HashMap::new();
+        for (row, height) in autofit_helper::fit_rows(sheet, bounds) {
+            let height = clamp(height, min_height, max_height);
+            sheet.get_row_dimension_mut(&row).set_height(height);
+            heights.insert(row, height);
+        }
+        Ok(heights)
+    }));
+
+    match result {
+        Ok(Ok(heights)) => (atoms::ok(), heights).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in autofit_rows operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+fn parse_bounds(range: Option<&str>) -> Result<Bounds, String> {
+    match range {
+        Some(range) => Ok(range_helper::parse_range(range)?.bounds()),
+        None => Ok((1, 1, MAX_COLUMN, MAX_ROW)),
+    }
+}
+
+fn check_limits(min: Option<f64>, max: Option<f64>) -> Result<(), String> {
+    if min.is_some_and(|min| min < 0.0) || max.is_some_and(|max| max < 0.0) {
+        return Err("Minimum and maximum sizes must not be negative".to_string());
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(format!(
+                "Minimum size {} is larger than maximum size {}",
+                min, max
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn clamp(value: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    let value = min.map_or(value, |min| value.max(min));
+    max.map_or(value, |max| value.min(max))
+}
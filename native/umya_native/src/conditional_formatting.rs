@@ -1,4 +1,4 @@
-use rustler::ResourceArc;
+use rustler::{ResourceArc, Term};
 use umya_spreadsheet::{
     ColorScale,
     ConditionalFormatValueObject,
@@ -7,13 +7,13 @@ use umya_spreadsheet::{
     ConditionalFormattingOperatorValues, // Removed ConditionalFormatting
     ConditionalFormattingRule,
     Formula,
-    Style,
 };
 
 // Removed unused imports: Env, Term
 
 // Use the public modules from lib.rs
 use crate::custom_structs::CustomColor;
+use crate::helpers::conditional_format_helper;
 use crate::UmyaSpreadsheet;
 
 // Helper function to convert string type to ConditionalFormatValueObjectValues
@@ -41,7 +41,7 @@ pub fn add_cell_value_rule(
     operator: String,
     value1: String,
     value2: Option<String>,
-    format_style: String,
+    format_style: Term,
 ) -> Result<bool, String> {
     let mut spreadsheet_guard = resource.spreadsheet.lock().unwrap();
     let sheet = spreadsheet_guard
@@ -95,43 +95,11 @@ pub fn add_cell_value_rule(
         rule.set_formula(formula);
     }
 
-    // Create a style from the format_style string (expected to be a color code)
-    let mut style_to_apply = Style::default();
-    let mut style_modified = false;
-
-    // If format_style is a color code, create a fill with that color
-    if !format_style.is_empty() {
-        let mut fill = umya_spreadsheet::Fill::default();
-        let mut pattern_fill = umya_spreadsheet::PatternFill::default();
-        pattern_fill.set_pattern_type(umya_spreadsheet::PatternValues::Solid);
-
-        // Create a color from the format_style string
-        let mut fg_color_obj = umya_spreadsheet::Color::default();
-        // Handle different formats of color strings
-        if format_style.starts_with('#') && format_style.len() == 7 {
-            // Format: #RRGGBB - Convert to FFRRGGBB for Excel
-            fg_color_obj.set_argb(&format!("FF{}", &format_style[1..]));
-        } else {
-            // Already in ARGB format or other format
-            fg_color_obj.set_argb(&format_style);
-        }
-
-        pattern_fill.set_foreground_color(fg_color_obj);
-        fill.set_pattern_fill(pattern_fill);
-        style_to_apply.set_fill(fill);
-        style_modified = true;
-    }
-
-    if style_modified {
-        rule.set_style(style_to_apply);
+    if let Some(style) = conditional_format_helper::rule_style(format_style)? {
+        rule.set_style(style);
     }
 
-    let mut conditional_formatting_collection = umya_spreadsheet::ConditionalFormatting::default();
-    conditional_formatting_collection
-        .get_sequence_of_references_mut()
-        .set_sqref(range); // Set sqref here
-    conditional_formatting_collection.add_conditional_collection(rule);
-    sheet.add_conditional_formatting_collection(conditional_formatting_collection);
+    conditional_format_helper::add_rule(sheet, &range, rule)?;
 
     Ok(true)
 }
@@ -235,12 +203,7 @@ pub fn add_color_scale(
 
     rule.set_color_scale(color_scale_value);
 
-    let mut conditional_formatting_collection = umya_spreadsheet::ConditionalFormatting::default();
-    conditional_formatting_collection
-        .get_sequence_of_references_mut()
-        .set_sqref(range); // Set sqref here
-    conditional_formatting_collection.add_conditional_collection(rule);
-    sheet.add_conditional_formatting_collection(conditional_formatting_collection); // Pass only one argument
+    conditional_format_helper::add_rule(sheet, &range, rule)?;
 
     Ok(true)
 }
@@ -1,13 +1,16 @@
-use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use rustler::{Atom, Error as NifError, NifResult, ResourceArc, Term};
 use std::convert::TryInto;
 use std::panic::{self, AssertUnwindSafe};
 use umya_spreadsheet::{
     ConditionalFormatValueObject, ConditionalFormatValueObjectValues, ConditionalFormatValues,
-    ConditionalFormatting, ConditionalFormattingRule, DataBar, IconSet,
+    ConditionalFormattingRule, DataBar, Formula, IconSet,
 };
 
 use crate::atoms;
+use crate::helpers::conditional_extension_helper::{self, RuleExtension};
+use crate::helpers::conditional_format_helper;
 use crate::helpers::style_helpers;
+use crate::helpers::style_map_helper::StylePatch;
 use crate::UmyaSpreadsheet;
 
 #[rustler::nif]
@@ -18,8 +21,10 @@ fn add_data_bar(
     min_value: Option<(String, String)>,
     max_value: Option<(String, String)>,
     color: String,
+    options: Term,
 ) -> Result<Atom, (Atom, String)> {
     let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Atom, String> {
+        let options = conditional_extension_helper::decode_data_bar_options(options)?;
         let mut spreadsheet = spreadsheet_resource
             .spreadsheet
             .lock()
@@ -112,15 +117,16 @@ fn add_data_bar(
         // Set the data bar in the rule
         rule.set_data_bar(data_bar);
 
-        // Add the rule to conditional formatting
-        let mut conditional_formatting = ConditionalFormatting::default();
-        let mut sequence = umya_spreadsheet::SequenceOfReferences::default();
-        sequence.set_sqref(cell_range);
-        conditional_formatting.set_sequence_of_references(sequence);
-        conditional_formatting.add_conditional_collection(rule);
+        // Add the rule with the next priority of the sheet
+        let priority = conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
 
-        // Add the conditional formatting to the worksheet
-        worksheet.add_conditional_formatting_collection(conditional_formatting);
+        // Bars with the default options are written without the x14 extension
+        let mut extras = spreadsheet_resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let extension = (options != Default::default()).then_some(RuleExtension::DataBar(options));
+        extras.set_conditional_extension(&sheet_name, priority, extension);
 
         Ok(atoms::ok())
     }));
@@ -143,7 +149,7 @@ fn add_top_bottom_rule(
     rule_type: String,
     rank: i32, // Keep as i32 for API compatibility, but convert to u32 internally if valid
     percent: bool,
-    format_style: String,
+    format_style: Term,
 ) -> NifResult<Atom> {
     let result = panic::catch_unwind(AssertUnwindSafe(|| {
         let mut spreadsheet = spreadsheet_resource.spreadsheet.lock().unwrap();
@@ -188,20 +194,12 @@ fn add_top_bottom_rule(
                 _ => return Err(format!("Invalid rule type: {}", rule_type)),
             }
 
-            // Set style if provided
-            if let Some(fill) = style_helpers::create_fill_style(&format_style) {
-                rule.set_style(fill);
+            if let Some(style) = conditional_format_helper::rule_style(format_style)? {
+                rule.set_style(style);
             }
 
-            // Add the rule to conditional formatting
-            let mut conditional_formatting = ConditionalFormatting::default();
-            let mut sequence = umya_spreadsheet::SequenceOfReferences::default();
-            sequence.set_sqref(cell_range);
-            conditional_formatting.set_sequence_of_references(sequence);
-            conditional_formatting.add_conditional_collection(rule);
-
-            // Add the conditional formatting to the worksheet
-            worksheet.add_conditional_formatting_collection(conditional_formatting);
+            // Add the rule with the next priority of the sheet
+            conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
 
             Ok(atoms::ok())
         } else {
@@ -226,7 +224,7 @@ fn add_text_rule(
     cell_range: String,
     rule_type: String,
     text: String,
-    format_style: String,
+    format_style: Term,
 ) -> NifResult<Atom> {
     let result = panic::catch_unwind(AssertUnwindSafe(|| {
         let mut spreadsheet = spreadsheet_resource.spreadsheet.lock().unwrap();
@@ -253,20 +251,12 @@ fn add_text_rule(
                 _ => return Err(format!("Invalid text rule type: {}", rule_type)),
             }
 
-            // Set style if provided
-            if let Some(fill) = style_helpers::create_fill_style(&format_style) {
-                rule.set_style(fill);
+            if let Some(style) = conditional_format_helper::rule_style(format_style)? {
+                rule.set_style(style);
             }
 
-            // Add the rule to conditional formatting
-            let mut conditional_formatting = ConditionalFormatting::default();
-            let mut sequence = umya_spreadsheet::SequenceOfReferences::default();
-            sequence.set_sqref(cell_range);
-            conditional_formatting.set_sequence_of_references(sequence);
-            conditional_formatting.add_conditional_collection(rule);
-
-            // Add the conditional formatting to the worksheet
-            worksheet.add_conditional_formatting_collection(conditional_formatting);
+            // Add the rule with the next priority of the sheet
+            conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
 
             Ok(atoms::ok())
         } else {
@@ -289,10 +279,16 @@ fn add_icon_set(
     spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
     sheet_name: String,
     cell_range: String,
-    _icon_style: String, // TODO: Use this parameter when umya-spreadsheet supports icon set styles
+    icon_style: String,
     thresholds: Vec<(String, String)>, // Vec of (type, value) tuples
+    options: Term,
 ) -> Result<Atom, (Atom, String)> {
     let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Atom, String> {
+        let icon_set = match conditional_extension_helper::parse_icon_set(&icon_style)? {
+            "NoIcons" => return Err(format!("Invalid icon style '{}'", icon_style)),
+            icon_set => icon_set,
+        };
+        let options = conditional_extension_helper::decode_icon_set_options(options, icon_set)?;
         let mut spreadsheet = spreadsheet_resource
             .spreadsheet
             .lock()
@@ -308,6 +304,25 @@ fn add_icon_set(
         if thresholds.len() > 5 {
             return Err("Icon sets support maximum 5 thresholds".to_string());
         }
+        let icon_count = conditional_extension_helper::icon_count(icon_set);
+        if !options.icons.is_empty() && options.icons.len() != icon_count {
+            return Err(format!(
+                "Option 'icons' must list {} icons for icon style '{}'",
+                icon_count, icon_set
+            ));
+        }
+        // Excel starts every icon set at its lowest value, which may be left out
+        let mut thresholds = thresholds;
+        if thresholds.len() + 1 == icon_count {
+            thresholds.insert(0, ("percent".to_string(), "0".to_string()));
+        } else if thresholds.len() != icon_count {
+            return Err(format!(
+                "Icon style '{}' takes {} or {} thresholds",
+                icon_set,
+                icon_count - 1,
+                icon_count
+            ));
+        }
 
         // Get worksheet by name
         let worksheet = spreadsheet
@@ -359,15 +374,19 @@ fn add_icon_set(
         // Set the icon set in the rule
         rule.set_icon_set(icon_set);
 
-        // Add the rule to conditional formatting
-        let mut conditional_formatting = ConditionalFormatting::default();
-        let mut sequence = umya_spreadsheet::SequenceOfReferences::default();
-        sequence.set_sqref(cell_range);
-        conditional_formatting.set_sequence_of_references(sequence);
-        conditional_formatting.add_conditional_collection(rule);
+        // Add the rule with the next priority of the sheet
+        let priority = conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
 
-        // Add the conditional formatting to the worksheet
-        worksheet.add_conditional_formatting_collection(conditional_formatting);
+        // The icon set itself is written from the options
+        let mut extras = spreadsheet_resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.set_conditional_extension(
+            &sheet_name,
+            priority,
+            Some(RuleExtension::IconSet(options)),
+        );
 
         Ok(atoms::ok())
     }));
@@ -389,7 +408,7 @@ fn add_above_below_average_rule(
     cell_range: String,
     rule_type: String,    // "above", "below", "above_equal", "below_equal"
     std_dev: Option<i32>, // Standard deviation for more advanced rules
-    format_style: String,
+    format_style: Term,
 ) -> Result<Atom, (Atom, String)> {
     let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Atom, String> {
         let mut spreadsheet = spreadsheet_resource
@@ -444,22 +463,12 @@ fn add_above_below_average_rule(
             rule.set_std_dev(std_dev_value);
         }
 
-        // Set style if provided
-        if !format_style.trim().is_empty() {
-            if let Some(fill) = style_helpers::create_fill_style(&format_style) {
-                rule.set_style(fill);
-            }
+        if let Some(style) = conditional_format_helper::rule_style(format_style)? {
+            rule.set_style(style);
         }
 
-        // Add the rule to conditional formatting
-        let mut conditional_formatting = ConditionalFormatting::default();
-        let mut sequence = umya_spreadsheet::SequenceOfReferences::default();
-        sequence.set_sqref(cell_range);
-        conditional_formatting.set_sequence_of_references(sequence);
-        conditional_formatting.add_conditional_collection(rule);
-
-        // Add the conditional formatting to the worksheet
-        worksheet.add_conditional_formatting_collection(conditional_formatting);
+        // Add the rule with the next priority of the sheet
+        conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
 
         Ok(atoms::ok())
     }));
@@ -474,5 +483,247 @@ fn add_above_below_average_rule(
     }
 }
 
+/// Adds a rule that formats the cells for which a formula is true, the rule Excel
+/// calls "Use a formula to determine which cells to format".
+///
+/// The formula is written for the top-left cell of the range; relative references
+/// move with the other cells. `style` is a style map with font, fill, borders and
+/// number format.
+#[rustler::nif]
+fn add_expression_rule(
+    spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    formula: String,
+    style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let patch = StylePatch::decode_differential(style)?;
+        let formula = formula.trim();
+        let formula = formula.strip_prefix('=').unwrap_or(formula);
+        if formula.is_empty() {
+            return Err("Formula cannot be empty".to_string());
+        }
+
+        let mut spreadsheet = spreadsheet_resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let worksheet = spreadsheet
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+
+        let mut rule = ConditionalFormattingRule::default();
+        rule.set_type(ConditionalFormatValues::Expression);
+        let mut rule_formula = Formula::default();
+        rule_formula.set_string_value(formula);
+        rule.set_formula(rule_formula);
+        rule.set_style(conditional_format_helper::differential_style(&patch));
+
+        conditional_format_helper::add_rule(worksheet, &cell_range, rule)?;
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_expression_rule operation".to_string(),
+        )))),
+    }
+}
+
+/// Adds a rule that fills the cells whose value occurs more than once in the range,
+/// or with `unique` the cells whose value occurs only once.
+#[rustler::nif]
+fn add_duplicate_values_rule(
+    spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    unique: bool,
+    format_style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut rule = ConditionalFormattingRule::default();
+        rule.set_type(if unique {
+            ConditionalFormatValues::UniqueValues
+        } else {
+            ConditionalFormatValues::DuplicateValues
+        });
+        add_fill_rule(
+            &spreadsheet_resource,
+            &sheet_name,
+            &cell_range,
+            rule,
+            format_style,
+        )
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_duplicate_values_rule operation".to_string(),
+        )))),
+    }
+}
+
+/// Adds a rule that fills blank cells, or with `blanks` false the cells with content.
+///
+/// Cells holding only spaces count as blank, as in Excel.
+#[rustler::nif]
+fn add_blanks_rule(
+    spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    blanks: bool,
+    format_style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let rule_type = if blanks {
+            ConditionalFormatValues::ContainsBlanks
+        } else {
+            ConditionalFormatValues::NotContainsBlanks
+        };
+        let rule = cell_check_rule(rule_type, &cell_range)?;
+        add_fill_rule(
+            &spreadsheet_resource,
+            &sheet_name,
+            &cell_range,
+            rule,
+            format_style,
+        )
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_blanks_rule operation".to_string(),
+        )))),
+    }
+}
+
+/// Adds a rule that fills cells holding an error value, or with `errors` false the
+/// cells without one.
+#[rustler::nif]
+fn add_errors_rule(
+    spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    errors: bool,
+    format_style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let rule_type = if errors {
+            ConditionalFormatValues::ContainsErrors
+        } else {
+            ConditionalFormatValues::NotContainsErrors
+        };
+        let rule = cell_check_rule(rule_type, &cell_range)?;
+        add_fill_rule(
+            &spreadsheet_resource,
+            &sheet_name,
+            &cell_range,
+            rule,
+            format_style,
+        )
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_errors_rule operation".to_string(),
+        )))),
+    }
+}
+
+/// Adds a rule that fills the cells whose date falls in a time period relative to
+/// today, such as "yesterday", "last7Days" or "thisMonth".
+#[rustler::nif]
+fn add_time_period_rule(
+    spreadsheet_resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    time_period: String,
+    format_style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let time_period = conditional_format_helper::parse_time_period(&time_period)?;
+        let cell = conditional_format_helper::top_left_cell(&cell_range)?;
+
+        let mut rule = ConditionalFormattingRule::default();
+        rule.set_type(ConditionalFormatValues::TimePeriod);
+        let mut formula = Formula::default();
+        formula.set_string_value(conditional_format_helper::time_period_formula(
+            &time_period,
+            &cell,
+        ));
+        rule.set_formula(formula);
+        rule.set_time_period(time_period);
+        add_fill_rule(
+            &spreadsheet_resource,
+            &sheet_name,
+            &cell_range,
+            rule,
+            format_style,
+        )
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_time_period_rule operation".to_string(),
+        )))),
+    }
+}
+
+/// Builds a blank or error rule with the formula Excel writes for it.
+fn cell_check_rule(
+    rule_type: ConditionalFormatValues,
+    cell_range: &str,
+) -> Result<ConditionalFormattingRule, String> {
+    let cell = conditional_format_helper::top_left_cell(cell_range)?;
+    let mut rule = ConditionalFormattingRule::default();
+    if let Some(value) = conditional_format_helper::cell_check_formula(&rule_type, &cell) {
+        let mut formula = Formula::default();
+        formula.set_string_value(value);
+        rule.set_formula(formula);
+    }
+    rule.set_type(rule_type);
+    Ok(rule)
+}
+
+/// Formats the cells matching `rule` with `format_style` and adds the rule to a sheet.
+fn add_fill_rule(
+    spreadsheet_resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    cell_range: &str,
+    mut rule: ConditionalFormattingRule,
+    format_style: Term,
+) -> Result<(), String> {
+    let style = conditional_format_helper::rule_style(format_style)?;
+    let mut spreadsheet = spreadsheet_resource
+        .spreadsheet
+        .lock()
+        .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+    let worksheet = spreadsheet
+        .get_sheet_by_name_mut(sheet_name)
+        .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+
+    if let Some(style) = style {
+        rule.set_style(style);
+    }
+    conditional_format_helper::add_rule(worksheet, cell_range, rule)?;
+    Ok(())
+}
+
 // Note: add_color_scale is now implemented in conditional_formatting.rs
 // This implementation was removed to prevent duplicate function errors
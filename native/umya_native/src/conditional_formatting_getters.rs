@@ -1,12 +1,17 @@
 use rustler::{Atom, Encoder, Error as NifError, NifResult, ResourceArc, Term};
 use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::drawing::Theme;
 use umya_spreadsheet::{
     ConditionalFormatValueObjectValues, ConditionalFormatValues,
-    ConditionalFormattingOperatorValues, ConditionalFormattingRule,
+    ConditionalFormattingOperatorValues, ConditionalFormattingRule, Style, TimePeriodValues,
 };
 
 use crate::atoms;
 use crate::custom_structs::CustomColor;
+use crate::helpers::conditional_extension_helper::{
+    self, DataBarOptions, IconSetOptions, RuleExtension,
+};
+use crate::helpers::style_map_helper;
 use crate::UmyaSpreadsheet;
 
 // Helper function to convert ConditionalFormatValueObjectValues to string
@@ -53,6 +58,14 @@ fn format_type_to_string(format_type: &ConditionalFormatValues) -> String {
         ConditionalFormatValues::BeginsWith => "begins_with".to_string(),
         ConditionalFormatValues::ContainsText => "contains_text".to_string(),
         ConditionalFormatValues::EndsWith => "ends_with".to_string(),
+        ConditionalFormatValues::Expression => "expression".to_string(),
+        ConditionalFormatValues::DuplicateValues => "duplicate_values".to_string(),
+        ConditionalFormatValues::UniqueValues => "unique_values".to_string(),
+        ConditionalFormatValues::ContainsBlanks => "contains_blanks".to_string(),
+        ConditionalFormatValues::NotContainsBlanks => "not_contains_blanks".to_string(),
+        ConditionalFormatValues::ContainsErrors => "contains_errors".to_string(),
+        ConditionalFormatValues::NotContainsErrors => "not_contains_errors".to_string(),
+        ConditionalFormatValues::TimePeriod => "time_period".to_string(),
         _ => "unknown".to_string(),
     }
 }
@@ -68,6 +81,7 @@ fn color_to_custom_color(color: &umya_spreadsheet::Color) -> CustomColor {
 fn extract_cell_value_rule<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    theme: &Theme,
 ) -> rustler::Term<'a> {
     let operator = operator_to_string(rule.get_operator());
 
@@ -114,6 +128,10 @@ fn extract_cell_value_rule<'a>(
         .map_put(atoms::format_style(), format_style)
         .ok()
         .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
 
     map
 }
@@ -205,6 +223,7 @@ fn extract_color_scale<'a>(
 fn extract_data_bar<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    extension: Option<&RuleExtension>,
 ) -> rustler::Term<'a> {
     match rule.get_data_bar() {
         Some(data_bar) => {
@@ -265,6 +284,18 @@ fn extract_data_bar<'a>(
                 map = map.map_put(atoms::color(), color_value).ok().unwrap();
             }
 
+            let options = match extension {
+                Some(RuleExtension::DataBar(options)) => options.clone(),
+                _ => DataBarOptions::default(),
+            };
+            map = map
+                .map_put(
+                    atoms::options(),
+                    conditional_extension_helper::encode_data_bar_options(env, &options),
+                )
+                .ok()
+                .unwrap();
+
             map
         }
         None => rustler::types::map::map_new(env),
@@ -275,18 +306,18 @@ fn extract_data_bar<'a>(
 fn extract_icon_set<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    extension: Option<&RuleExtension>,
 ) -> rustler::Term<'a> {
     match rule.get_icon_set() {
         Some(icon_set) => {
             let cfvos = icon_set.get_cfvo_collection();
-            // Since IconSetValues is not available, determine the type by the number of items
-            let icon_set_type = match cfvos.len() {
-                3 => "3arrows", // Default to 3 arrows when there are 3 items
-                4 => "4arrows", // Default to 4 arrows when there are 4 items
-                5 => "5arrows", // Default to 5 arrows when there are 5 items
-                _ => "unknown",
-            }
-            .to_string();
+            // umya-spreadsheet does not keep the icon set, which is written from the
+            // options stored with the rule
+            let options = match extension {
+                Some(RuleExtension::IconSet(options)) => options.clone(),
+                _ => IconSetOptions::for_icon_count(cfvos.len()),
+            };
+            let icon_set_type = options.icon_set.clone();
 
             let mut map = rustler::types::map::map_new(env);
             map = map
@@ -308,6 +339,13 @@ fn extract_icon_set<'a>(
             }
 
             map = map.map_put(atoms::thresholds(), thresholds).ok().unwrap();
+            map = map
+                .map_put(
+                    atoms::options(),
+                    conditional_extension_helper::encode_icon_set_options(env, &options),
+                )
+                .ok()
+                .unwrap();
 
             map
         }
@@ -319,6 +357,7 @@ fn extract_icon_set<'a>(
 fn extract_top_bottom_rule<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    theme: &Theme,
 ) -> rustler::Term<'a> {
     let mut map = rustler::types::map::map_new(env);
     map = map
@@ -368,6 +407,10 @@ fn extract_top_bottom_rule<'a>(
         .map_put(atoms::format_style(), format_style)
         .ok()
         .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
 
     map
 }
@@ -376,6 +419,7 @@ fn extract_top_bottom_rule<'a>(
 fn extract_above_below_average_rule<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    theme: &Theme,
 ) -> rustler::Term<'a> {
     let mut map = rustler::types::map::map_new(env);
     map = map
@@ -433,6 +477,10 @@ fn extract_above_below_average_rule<'a>(
         .map_put(atoms::format_style(), format_style)
         .ok()
         .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
 
     map
 }
@@ -441,6 +489,7 @@ fn extract_above_below_average_rule<'a>(
 fn extract_text_rule<'a>(
     env: rustler::Env<'a>,
     rule: &ConditionalFormattingRule,
+    theme: &Theme,
 ) -> rustler::Term<'a> {
     let mut map = rustler::types::map::map_new(env);
 
@@ -496,10 +545,153 @@ fn extract_text_rule<'a>(
         .map_put(atoms::format_style(), format_style)
         .ok()
         .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
+
+    map
+}
+
+// Helper function to extract expression rule details
+fn extract_expression_rule<'a>(
+    env: rustler::Env<'a>,
+    rule: &ConditionalFormattingRule,
+    theme: &Theme,
+) -> rustler::Term<'a> {
+    let formula = rule
+        .get_formula()
+        .map(|formula| formula.get_address_str())
+        .unwrap_or_default();
+
+    let format_style = rule_fill_color(rule);
+
+    let mut map = rustler::types::map::map_new(env);
+    map = map
+        .map_put(atoms::rule_type(), atoms::expression())
+        .ok()
+        .unwrap();
+    map = map.map_put(atoms::formula(), formula).ok().unwrap();
+    map = map
+        .map_put(atoms::format_style(), format_style)
+        .ok()
+        .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
 
     map
 }
 
+// Helper function to extract duplicate/unique, blank and error rules
+fn extract_cell_check_rule<'a>(
+    env: rustler::Env<'a>,
+    rule: &ConditionalFormattingRule,
+    theme: &Theme,
+) -> rustler::Term<'a> {
+    let rule_type = format_type_to_string(rule.get_type());
+
+    let mut map = rustler::types::map::map_new(env);
+    map = map
+        .map_put(
+            atoms::rule_type(),
+            rustler::types::atom::Atom::from_str(env, &rule_type).unwrap(),
+        )
+        .ok()
+        .unwrap();
+    if let Some(formula) = rule.get_formula() {
+        map = map
+            .map_put(atoms::formula(), formula.get_address_str())
+            .ok()
+            .unwrap();
+    }
+    map = map
+        .map_put(atoms::format_style(), rule_fill_color(rule))
+        .ok()
+        .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
+
+    map
+}
+
+// Helper function to extract time period (date occurring) rules
+fn extract_time_period_rule<'a>(
+    env: rustler::Env<'a>,
+    rule: &ConditionalFormattingRule,
+    theme: &Theme,
+) -> rustler::Term<'a> {
+    let time_period = match rule.get_time_period() {
+        TimePeriodValues::Today => "today",
+        TimePeriodValues::Yesterday => "yesterday",
+        TimePeriodValues::Tomorrow => "tomorrow",
+        TimePeriodValues::Last7Days => "last_7_days",
+        TimePeriodValues::ThisWeek => "this_week",
+        TimePeriodValues::LastWeek => "last_week",
+        TimePeriodValues::NextWeek => "next_week",
+        TimePeriodValues::ThisMonth => "this_month",
+        TimePeriodValues::LastMonth => "last_month",
+        TimePeriodValues::NextMonth => "next_month",
+    };
+    let formula = rule
+        .get_formula()
+        .map(|formula| formula.get_address_str())
+        .unwrap_or_default();
+
+    let mut map = rustler::types::map::map_new(env);
+    map = map
+        .map_put(atoms::rule_type(), atoms::time_period())
+        .ok()
+        .unwrap();
+    map = map
+        .map_put(
+            atoms::time_period(),
+            rustler::types::atom::Atom::from_str(env, time_period).unwrap(),
+        )
+        .ok()
+        .unwrap();
+    map = map.map_put(atoms::formula(), formula).ok().unwrap();
+    map = map
+        .map_put(atoms::format_style(), rule_fill_color(rule))
+        .ok()
+        .unwrap();
+    map = map
+        .map_put(atoms::style(), rule_style(env, rule, theme))
+        .ok()
+        .unwrap();
+
+    map
+}
+
+// Helper function to encode the differential format (dxf) of a rule as a style map
+fn rule_style<'a>(
+    env: rustler::Env<'a>,
+    rule: &ConditionalFormattingRule,
+    theme: &Theme,
+) -> rustler::Term<'a> {
+    match rule.get_style() {
+        Some(style) => style_map_helper::encode_differential_style(env, style, theme),
+        None => style_map_helper::encode_differential_style(env, &Style::default(), theme),
+    }
+}
+
+// Helper function to get the fill color of a rule, which Excel may keep in bgColor only
+fn rule_fill_color(rule: &ConditionalFormattingRule) -> String {
+    rule.get_style()
+        .and_then(|style| style.get_fill())
+        .and_then(|fill| fill.get_pattern_fill())
+        .and_then(|pattern_fill| {
+            pattern_fill
+                .get_foreground_color()
+                .or(pattern_fill.get_background_color())
+        })
+        .map(|color| color.get_argb().to_string())
+        .unwrap_or_default()
+}
+
 #[rustler::nif(name = "get_conditional_formatting_rules")]
 pub fn get_conditional_formatting_rules_nif<'a>(
     env: rustler::Env<'a>,
@@ -513,6 +705,7 @@ pub fn get_conditional_formatting_rules_nif<'a>(
             let sheet = spreadsheet_guard
                 .get_sheet_by_name(&sheet_name)
                 .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+            let extras = resource.package_extras.lock().unwrap();
 
             let cf_collections = sheet.get_conditional_formatting_collection();
             let mut rules = Vec::new();
@@ -528,27 +721,59 @@ pub fn get_conditional_formatting_rules_nif<'a>(
                 }
 
                 for rule in cf.get_conditional_collection() {
+                    let extension = extras.conditional_extension(&sheet_name, *rule.get_priority());
+
                     // Create a base map with common properties
                     let mut base_map = rustler::types::map::map_new(env);
                     base_map = base_map
                         .map_put(atoms::range(), sqref.clone())
                         .ok()
                         .unwrap();
+                    base_map = base_map
+                        .map_put(atoms::priority(), *rule.get_priority())
+                        .ok()
+                        .unwrap();
+                    base_map = base_map
+                        .map_put(atoms::stop_if_true(), *rule.get_stop_if_true())
+                        .ok()
+                        .unwrap();
 
                     // Create a rule-specific map based on the type
                     let rule_type = rule.get_type();
                     let rule_map = match rule_type {
-                        ConditionalFormatValues::CellIs => extract_cell_value_rule(env, rule),
+                        ConditionalFormatValues::CellIs => {
+                            extract_cell_value_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
                         ConditionalFormatValues::ColorScale => extract_color_scale(env, rule),
-                        ConditionalFormatValues::DataBar => extract_data_bar(env, rule),
-                        ConditionalFormatValues::IconSet => extract_icon_set(env, rule),
-                        ConditionalFormatValues::Top10 => extract_top_bottom_rule(env, rule),
-                        ConditionalFormatValues::AboveAverage => {
-                            extract_above_below_average_rule(env, rule)
+                        ConditionalFormatValues::DataBar => extract_data_bar(env, rule, extension),
+                        ConditionalFormatValues::IconSet => extract_icon_set(env, rule, extension),
+                        ConditionalFormatValues::Top10 => {
+                            extract_top_bottom_rule(env, rule, spreadsheet_guard.get_theme())
                         }
+                        ConditionalFormatValues::AboveAverage => extract_above_below_average_rule(
+                            env,
+                            rule,
+                            spreadsheet_guard.get_theme(),
+                        ),
                         ConditionalFormatValues::ContainsText
                         | ConditionalFormatValues::BeginsWith
-                        | ConditionalFormatValues::EndsWith => extract_text_rule(env, rule),
+                        | ConditionalFormatValues::EndsWith => {
+                            extract_text_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
+                        ConditionalFormatValues::Expression => {
+                            extract_expression_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
+                        ConditionalFormatValues::DuplicateValues
+                        | ConditionalFormatValues::UniqueValues
+                        | ConditionalFormatValues::ContainsBlanks
+                        | ConditionalFormatValues::NotContainsBlanks
+                        | ConditionalFormatValues::ContainsErrors
+                        | ConditionalFormatValues::NotContainsErrors => {
+                            extract_cell_check_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
+                        ConditionalFormatValues::TimePeriod => {
+                            extract_time_period_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
                         _ => rustler::types::map::map_new(env),
                     };
 
@@ -579,6 +804,9 @@ pub fn get_conditional_formatting_rules_nif<'a>(
                         atoms::std_dev(),
                         atoms::text(),
                         atoms::rule_type_value(),
+                        atoms::style(),
+                        atoms::time_period(),
+                        atoms::options(),
                     ];
 
                     // Try to get values for each key from rule_map
@@ -636,7 +864,8 @@ pub fn get_cell_value_rules_nif<'a>(
 
                 for rule in cf.get_conditional_collection() {
                     if *rule.get_type() == ConditionalFormatValues::CellIs {
-                        let mut rule_map = extract_cell_value_rule(env, rule);
+                        let mut rule_map =
+                            extract_cell_value_rule(env, rule, spreadsheet_guard.get_theme());
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -726,6 +955,7 @@ pub fn get_data_bars_nif<'a>(
             let sheet = spreadsheet_guard
                 .get_sheet_by_name(&sheet_name)
                 .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+            let extras = resource.package_extras.lock().unwrap();
 
             let cf_collections = sheet.get_conditional_formatting_collection();
             let mut rules = Vec::new();
@@ -742,7 +972,9 @@ pub fn get_data_bars_nif<'a>(
 
                 for rule in cf.get_conditional_collection() {
                     if *rule.get_type() == ConditionalFormatValues::DataBar {
-                        let mut rule_map = extract_data_bar(env, rule);
+                        let extension =
+                            extras.conditional_extension(&sheet_name, *rule.get_priority());
+                        let mut rule_map = extract_data_bar(env, rule, extension);
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -779,6 +1011,7 @@ pub fn get_icon_sets_nif<'a>(
             let sheet = spreadsheet_guard
                 .get_sheet_by_name(&sheet_name)
                 .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+            let extras = resource.package_extras.lock().unwrap();
 
             let cf_collections = sheet.get_conditional_formatting_collection();
             let mut rules = Vec::new();
@@ -795,7 +1028,9 @@ pub fn get_icon_sets_nif<'a>(
 
                 for rule in cf.get_conditional_collection() {
                     if *rule.get_type() == ConditionalFormatValues::IconSet {
-                        let mut rule_map = extract_icon_set(env, rule);
+                        let extension =
+                            extras.conditional_extension(&sheet_name, *rule.get_priority());
+                        let mut rule_map = extract_icon_set(env, rule, extension);
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -848,7 +1083,8 @@ pub fn get_top_bottom_rules_nif<'a>(
 
                 for rule in cf.get_conditional_collection() {
                     if *rule.get_type() == ConditionalFormatValues::Top10 {
-                        let mut rule_map = extract_top_bottom_rule(env, rule);
+                        let mut rule_map =
+                            extract_top_bottom_rule(env, rule, spreadsheet_guard.get_theme());
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -901,7 +1137,11 @@ pub fn get_above_below_average_rules_nif<'a>(
 
                 for rule in cf.get_conditional_collection() {
                     if *rule.get_type() == ConditionalFormatValues::AboveAverage {
-                        let mut rule_map = extract_above_below_average_rule(env, rule);
+                        let mut rule_map = extract_above_below_average_rule(
+                            env,
+                            rule,
+                            spreadsheet_guard.get_theme(),
+                        );
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -958,7 +1198,8 @@ pub fn get_text_rules_nif<'a>(
                         || *rule_type == ConditionalFormatValues::BeginsWith
                         || *rule_type == ConditionalFormatValues::EndsWith
                     {
-                        let mut rule_map = extract_text_rule(env, rule);
+                        let mut rule_map =
+                            extract_text_rule(env, rule, spreadsheet_guard.get_theme());
                         rule_map = rule_map
                             .map_put(atoms::range(), sqref.clone())
                             .ok()
@@ -981,3 +1222,194 @@ pub fn get_text_rules_nif<'a>(
         )),
     }
 }
+
+#[rustler::nif(name = "get_expression_rules")]
+pub fn get_expression_rules_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        || -> Result<Vec<rustler::Term<'a>>, String> {
+            let spreadsheet_guard = resource.spreadsheet.lock().unwrap();
+            let sheet = spreadsheet_guard
+                .get_sheet_by_name(&sheet_name)
+                .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+
+            let cf_collections = sheet.get_conditional_formatting_collection();
+            let mut rules = Vec::new();
+
+            for cf in cf_collections {
+                let sqref = cf.get_sequence_of_references().get_sqref();
+
+                // If range filter is specified, only include rules that match
+                if let Some(ref filter_range) = range {
+                    if sqref != *filter_range {
+                        continue;
+                    }
+                }
+
+                for rule in cf.get_conditional_collection() {
+                    if *rule.get_type() == ConditionalFormatValues::Expression {
+                        let mut rule_map =
+                            extract_expression_rule(env, rule, spreadsheet_guard.get_theme());
+                        rule_map = rule_map
+                            .map_put(atoms::range(), sqref.clone())
+                            .ok()
+                            .unwrap();
+                        rules.push(rule_map);
+                    }
+                }
+            }
+
+            Ok(rules)
+        },
+    ));
+
+    match result {
+        Ok(Ok(rules)) => Ok(rules),
+        Ok(Err(msg)) => Err((atoms::error(), msg)),
+        Err(_) => Err((
+            atoms::error(),
+            "Error occurred in get_expression_rules".to_string(),
+        )),
+    }
+}
+
+#[rustler::nif(name = "get_duplicate_values_rules")]
+pub fn get_duplicate_values_rules_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    get_rules_of_types(
+        env,
+        &resource,
+        &sheet_name,
+        range,
+        &[
+            ConditionalFormatValues::DuplicateValues,
+            ConditionalFormatValues::UniqueValues,
+        ],
+        "get_duplicate_values_rules",
+    )
+}
+
+#[rustler::nif(name = "get_blanks_rules")]
+pub fn get_blanks_rules_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    get_rules_of_types(
+        env,
+        &resource,
+        &sheet_name,
+        range,
+        &[
+            ConditionalFormatValues::ContainsBlanks,
+            ConditionalFormatValues::NotContainsBlanks,
+        ],
+        "get_blanks_rules",
+    )
+}
+
+#[rustler::nif(name = "get_errors_rules")]
+pub fn get_errors_rules_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    get_rules_of_types(
+        env,
+        &resource,
+        &sheet_name,
+        range,
+        &[
+            ConditionalFormatValues::ContainsErrors,
+            ConditionalFormatValues::NotContainsErrors,
+        ],
+        "get_errors_rules",
+    )
+}
+
+#[rustler::nif(name = "get_time_period_rules")]
+pub fn get_time_period_rules_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: Option<String>,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    get_rules_of_types(
+        env,
+        &resource,
+        &sheet_name,
+        range,
+        &[ConditionalFormatValues::TimePeriod],
+        "get_time_period_rules",
+    )
+}
+
+// Collects the duplicate/unique, blank, error and time period rules of the given types
+fn get_rules_of_types<'a>(
+    env: rustler::Env<'a>,
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    range: Option<String>,
+    rule_types: &[ConditionalFormatValues],
+    operation: &str,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        || -> Result<Vec<rustler::Term<'a>>, String> {
+            let spreadsheet_guard = resource
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let sheet = spreadsheet_guard
+                .get_sheet_by_name(sheet_name)
+                .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+
+            let mut rules = Vec::new();
+            for cf in sheet.get_conditional_formatting_collection() {
+                let sqref = cf.get_sequence_of_references().get_sqref();
+
+                // If range filter is specified, only include rules that match
+                if let Some(ref filter_range) = range {
+                    if sqref != *filter_range {
+                        continue;
+                    }
+                }
+
+                for rule in cf.get_conditional_collection() {
+                    if !rule_types.contains(rule.get_type()) {
+                        continue;
+                    }
+                    let rule_map = match rule.get_type() {
+                        ConditionalFormatValues::TimePeriod => {
+                            extract_time_period_rule(env, rule, spreadsheet_guard.get_theme())
+                        }
+                        _ => extract_cell_check_rule(env, rule, spreadsheet_guard.get_theme()),
+                    };
+                    rules.push(
+                        rule_map
+                            .map_put(atoms::range(), sqref.clone())
+                            .ok()
+                            .unwrap(),
+                    );
+                }
+            }
+
+            Ok(rules)
+        },
+    ));
+
+    match result {
+        Ok(Ok(rules)) => Ok(rules),
+        Ok(Err(msg)) => Err((atoms::error(), msg)),
+        Err(_) => Err((atoms::error(), format!("Error occurred in {}", operation))),
+    }
+}
@@ -0,0 +1,151 @@
+use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{ConditionalFormatting, Worksheet};
+
+use crate::atoms;
+use crate::helpers::conditional_format_helper;
+use crate::helpers::package_helper::PackageExtras;
+use crate::UmyaSpreadsheet;
+
+// Rules are identified by their priority, which is unique within a sheet for rules
+// added through this library and is returned by get_conditional_formatting_rules.
+
+/// Remove the conditional formatting rules applying to exactly `range`
+#[rustler::nif]
+pub fn remove_conditional_formatting(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "remove_conditional_formatting",
+        |sheet, _| conditional_format_helper::remove_range(sheet, &range),
+    )
+}
+
+/// Remove the conditional formatting rule with the given priority
+#[rustler::nif]
+pub fn remove_conditional_formatting_rule(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    priority: i32,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "remove_conditional_formatting_rule",
+        |sheet, _| conditional_format_helper::remove_rule(sheet, priority),
+    )
+}
+
+/// Move a conditional formatting rule to another priority
+///
+/// All rules of the sheet are renumbered from 1, so the priorities of other rules
+/// may change as well.
+#[rustler::nif]
+pub fn set_conditional_formatting_priority(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    priority: i32,
+    new_priority: i32,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "set_conditional_formatting_priority",
+        |sheet, extras| {
+            let renumbered =
+                conditional_format_helper::set_priority(sheet, priority, new_priority)?;
+            extras.renumber_conditional_extensions(&sheet_name, &renumbered);
+            Ok(())
+        },
+    )
+}
+
+/// Set whether the rules after a conditional formatting rule are skipped when it applies
+#[rustler::nif]
+pub fn set_conditional_formatting_stop_if_true(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    priority: i32,
+    stop_if_true: bool,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "set_conditional_formatting_stop_if_true",
+        |sheet, _| conditional_format_helper::set_stop_if_true(sheet, priority, stop_if_true),
+    )
+}
+
+/// Apply a conditional formatting rule to another range
+#[rustler::nif]
+pub fn set_conditional_formatting_range(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    priority: i32,
+    range: String,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "set_conditional_formatting_range",
+        |sheet, _| conditional_format_helper::set_range(sheet, priority, &range),
+    )
+}
+
+/// Remove all conditional formatting of a sheet
+#[rustler::nif]
+pub fn clear_conditional_formatting(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+) -> NifResult<Atom> {
+    update_rules(
+        &resource,
+        &sheet_name,
+        "clear_conditional_formatting",
+        |sheet, _| {
+            sheet.set_conditional_formatting_collection(Vec::<ConditionalFormatting>::new());
+            Ok(())
+        },
+    )
+}
+
+fn update_rules<F>(
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    operation: &str,
+    update: F,
+) -> NifResult<Atom>
+where
+    F: FnOnce(&mut Worksheet, &mut PackageExtras) -> Result<(), String>,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut spreadsheet = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = spreadsheet
+            .get_sheet_by_name_mut(sheet_name)
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        update(sheet, &mut extras)?;
+        // Options of data bars and icon sets belong to rules by priority
+        extras.retain_conditional_extensions(sheet_name, sheet);
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(msg)) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            format!("Error occurred in {} operation", operation),
+        )))),
+    }
+}
@@ -1,8 +1,10 @@
-use rustler::{Atom, ResourceArc};
+use rustler::{Atom, ResourceArc, Term};
+use std::collections::HashMap;
 use std::panic::{self, AssertUnwindSafe};
-use umya_spreadsheet::{DataValidationOperatorValues, DataValidationValues};
+use umya_spreadsheet::DataValidationValues;
 
 use crate::atoms;
+use crate::helpers::data_validation_helper;
 use crate::UmyaSpreadsheet;
 
 /// Add a simple list data validation to a cell or range
@@ -32,25 +34,20 @@ pub fn add_list_validation(
             return Err("List items cannot be empty".to_string());
         }
 
-        let sheet = guard
-            .get_sheet_by_name_mut(&sheet_name)
-            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+        if guard.get_sheet_by_name(&sheet_name).is_none() {
+            return Err(format!("Sheet '{}' not found", sheet_name));
+        }
 
         // Create the data validation
-        let mut validation = umya_spreadsheet::DataValidation::default();
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::List)?;
 
         // Set basic properties
-        validation.set_type(DataValidationValues::List);
         validation.set_allow_blank(allow_blank);
 
-        // Create and set sequence of references
-        let mut seq_refs = umya_spreadsheet::SequenceOfReferences::default();
-        seq_refs.set_sqref(&cell_range);
-        validation.set_sequence_of_references(seq_refs);
-
-        // Set the formula1 (list source)
-        let formula = list_items.join(",");
-        validation.set_formula1(format!("\"{}\"", formula));
+        // Set the formula1 (list source), spilling long lists to the hidden list sheet
+        let formula = data_validation_helper::list_formula(&mut guard, &list_items)?;
+        validation.set_formula1(formula);
 
         // Set error title and message if provided
         if let Some(title) = error_title {
@@ -72,14 +69,11 @@ pub fn add_list_validation(
             validation.set_show_input_message(true);
         }
 
-        // Add the validation to the sheet
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            validations.add_data_validation_list(validation);
-        } else {
-            let mut data_validations = umya_spreadsheet::DataValidations::default();
-            data_validations.add_data_validation_list(validation);
-            sheet.set_data_validations(data_validations);
-        }
+        // Add the validation to the sheet, taking its cells out of any other validation
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+        data_validation_helper::add_validation(sheet, validation);
 
         Ok(atoms::ok())
     }));
@@ -121,39 +115,20 @@ pub fn add_number_validation(
         }
 
         // Convert operator string to Rust enum
-        let op = match operator.as_str() {
-            "between" => DataValidationOperatorValues::Between,
-            "notBetween" | "not_between" => DataValidationOperatorValues::NotBetween,
-            "equal" => DataValidationOperatorValues::Equal,
-            "notEqual" | "not_equal" => DataValidationOperatorValues::NotEqual,
-            "greaterThan" | "greater_than" => DataValidationOperatorValues::GreaterThan,
-            "lessThan" | "less_than" => DataValidationOperatorValues::LessThan,
-            "greaterThanOrEqual" | "greater_than_or_equal" => {
-                DataValidationOperatorValues::GreaterThanOrEqual
-            }
-            "lessThanOrEqual" | "less_than_or_equal" => {
-                DataValidationOperatorValues::LessThanOrEqual
-            }
-            _ => return Err(format!("Invalid operator: {}", operator)),
-        };
+        let op = data_validation_helper::parse_operator(&operator)?;
 
         let sheet = guard
             .get_sheet_by_name_mut(&sheet_name)
             .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
 
         // Create the data validation
-        let mut validation = umya_spreadsheet::DataValidation::default();
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::Decimal)?;
 
         // Set basic properties
-        validation.set_type(DataValidationValues::Decimal);
         validation.set_operator(op);
         validation.set_allow_blank(allow_blank);
 
-        // Create and set sequence of references
-        let mut seq_refs = umya_spreadsheet::SequenceOfReferences::default();
-        seq_refs.set_sqref(&cell_range);
-        validation.set_sequence_of_references(seq_refs);
-
         // Set the formula values
         validation.set_formula1(value1);
         if let Some(val2) = value2 {
@@ -180,14 +155,8 @@ pub fn add_number_validation(
             validation.set_show_input_message(true);
         }
 
-        // Add the validation to the sheet
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            validations.add_data_validation_list(validation);
-        } else {
-            let mut data_validations = umya_spreadsheet::DataValidations::default();
-            data_validations.add_data_validation_list(validation);
-            sheet.set_data_validations(data_validations);
-        }
+        // Add the validation to the sheet, taking its cells out of any other validation
+        data_validation_helper::add_validation(sheet, validation);
 
         Ok(atoms::ok())
     }));
@@ -229,39 +198,20 @@ pub fn add_date_validation(
         }
 
         // Convert operator string to Rust enum
-        let op = match operator.as_str() {
-            "between" => DataValidationOperatorValues::Between,
-            "notBetween" | "not_between" => DataValidationOperatorValues::NotBetween,
-            "equal" => DataValidationOperatorValues::Equal,
-            "notEqual" | "not_equal" => DataValidationOperatorValues::NotEqual,
-            "greaterThan" | "greater_than" => DataValidationOperatorValues::GreaterThan,
-            "lessThan" | "less_than" => DataValidationOperatorValues::LessThan,
-            "greaterThanOrEqual" | "greater_than_or_equal" => {
-                DataValidationOperatorValues::GreaterThanOrEqual
-            }
-            "lessThanOrEqual" | "less_than_or_equal" => {
-                DataValidationOperatorValues::LessThanOrEqual
-            }
-            _ => return Err(format!("Invalid operator: {}", operator)),
-        };
+        let op = data_validation_helper::parse_operator(&operator)?;
 
         let sheet = guard
             .get_sheet_by_name_mut(&sheet_name)
             .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
 
         // Create the data validation
-        let mut validation = umya_spreadsheet::DataValidation::default();
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::Date)?;
 
         // Set basic properties
-        validation.set_type(DataValidationValues::Date);
         validation.set_operator(op);
         validation.set_allow_blank(allow_blank);
 
-        // Create and set sequence of references
-        let mut seq_refs = umya_spreadsheet::SequenceOfReferences::default();
-        seq_refs.set_sqref(&cell_range);
-        validation.set_sequence_of_references(seq_refs);
-
         // Set the formula values
         validation.set_formula1(date1);
         if let Some(date2) = date2 {
@@ -288,14 +238,8 @@ pub fn add_date_validation(
             validation.set_show_input_message(true);
         }
 
-        // Add the validation to the sheet
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            validations.add_data_validation_list(validation);
-        } else {
-            let mut data_validations = umya_spreadsheet::DataValidations::default();
-            data_validations.add_data_validation_list(validation);
-            sheet.set_data_validations(data_validations);
-        }
+        // Add the validation to the sheet, taking its cells out of any other validation
+        data_validation_helper::add_validation(sheet, validation);
 
         Ok(atoms::ok())
     }));
@@ -347,39 +291,20 @@ pub fn add_text_length_validation(
         }
 
         // Convert operator string to Rust enum
-        let op = match operator.as_str() {
-            "between" => DataValidationOperatorValues::Between,
-            "notBetween" | "not_between" => DataValidationOperatorValues::NotBetween,
-            "equal" => DataValidationOperatorValues::Equal,
-            "notEqual" | "not_equal" => DataValidationOperatorValues::NotEqual,
-            "greaterThan" | "greater_than" => DataValidationOperatorValues::GreaterThan,
-            "lessThan" | "less_than" => DataValidationOperatorValues::LessThan,
-            "greaterThanOrEqual" | "greater_than_or_equal" => {
-                DataValidationOperatorValues::GreaterThanOrEqual
-            }
-            "lessThanOrEqual" | "less_than_or_equal" => {
-                DataValidationOperatorValues::LessThanOrEqual
-            }
-            _ => return Err(format!("Invalid operator: {}", operator)),
-        };
+        let op = data_validation_helper::parse_operator(&operator)?;
 
         let sheet = guard
             .get_sheet_by_name_mut(&sheet_name)
             .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
 
         // Create the data validation
-        let mut validation = umya_spreadsheet::DataValidation::default();
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::TextLength)?;
 
         // Set basic properties
-        validation.set_type(DataValidationValues::TextLength);
         validation.set_operator(op);
         validation.set_allow_blank(allow_blank);
 
-        // Create and set sequence of references
-        let mut seq_refs = umya_spreadsheet::SequenceOfReferences::default();
-        seq_refs.set_sqref(&cell_range);
-        validation.set_sequence_of_references(seq_refs);
-
         // Set the formula values
         validation.set_formula1(length1.to_string());
         if let Some(len2) = length2 {
@@ -406,14 +331,8 @@ pub fn add_text_length_validation(
             validation.set_show_input_message(true);
         }
 
-        // Add the validation to the sheet
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            validations.add_data_validation_list(validation);
-        } else {
-            let mut data_validations = umya_spreadsheet::DataValidations::default();
-            data_validations.add_data_validation_list(validation);
-            sheet.set_data_validations(data_validations);
-        }
+        // Add the validation to the sheet, taking its cells out of any other validation
+        data_validation_helper::add_validation(sheet, validation);
 
         Ok(atoms::ok())
     }));
@@ -461,17 +380,12 @@ pub fn add_custom_validation(
             .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
 
         // Create the data validation
-        let mut validation = umya_spreadsheet::DataValidation::default();
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::Custom)?;
 
         // Set basic properties
-        validation.set_type(DataValidationValues::Custom);
         validation.set_allow_blank(allow_blank);
 
-        // Create and set sequence of references
-        let mut seq_refs = umya_spreadsheet::SequenceOfReferences::default();
-        seq_refs.set_sqref(&cell_range);
-        validation.set_sequence_of_references(seq_refs);
-
         // Set the formula
         validation.set_formula1(formula);
 
@@ -495,14 +409,8 @@ pub fn add_custom_validation(
             validation.set_show_input_message(true);
         }
 
-        // Add the validation to the sheet
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            validations.add_data_validation_list(validation);
-        } else {
-            let mut data_validations = umya_spreadsheet::DataValidations::default();
-            data_validations.add_data_validation_list(validation);
-            sheet.set_data_validations(data_validations);
-        }
+        // Add the validation to the sheet, taking its cells out of any other validation
+        data_validation_helper::add_validation(sheet, validation);
 
         Ok(atoms::ok())
     }));
@@ -517,7 +425,7 @@ pub fn add_custom_validation(
     }
 }
 
-/// Remove data validations from a cell range
+/// Remove data validations from a cell range, keeping their cells outside of it
 #[rustler::nif]
 pub fn remove_data_validation(
     resource: ResourceArc<UmyaSpreadsheet>,
@@ -539,27 +447,129 @@ pub fn remove_data_validation(
             .get_sheet_by_name_mut(&sheet_name)
             .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
 
-        // Get current validations
-        if let Some(validations) = sheet.get_data_validations_mut() {
-            // Filter out validations that match the cell range
-            let mut range_to_remove = umya_spreadsheet::SequenceOfReferences::default();
-            range_to_remove.set_sqref(&cell_range);
-
-            // Since we can't directly remove validations from the list, we'll collect
-            // the validations to keep and then replace the original list
-            let mut validations_to_keep = Vec::new();
-
-            for validation in validations.get_data_validation_list().iter() {
-                if validation.get_sequence_of_references().get_sqref()
-                    != range_to_remove.get_sqref()
-                {
-                    validations_to_keep.push(validation.clone());
-                }
+        // Take the range out of every validation, keeping the cells outside of it
+        let areas = data_validation_helper::parse_sqref(&cell_range)?;
+        data_validation_helper::clear_areas(sheet, &areas);
+
+        Ok(atoms::ok())
+    }));
+
+    match result {
+        Ok(Ok(atom)) => Ok(atom),
+        Ok(Err(err_msg)) => Err((atoms::error(), err_msg)),
+        Err(_) => Err((
+            atoms::error(),
+            "Error occurred in remove_data_validation operation".to_string(),
+        )),
+    }
+}
+
+/// Add a list data validation whose items come from a range, a defined name or,
+/// for dependent dropdowns, the range named by another cell through INDIRECT
+#[rustler::nif]
+pub fn add_list_source_validation(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_range: String,
+    source_type: String,
+    source: String,
+    options: HashMap<String, Term>,
+) -> Result<Atom, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Atom, String> {
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        data_validation_helper::check_option_keys(
+            &options,
+            &data_validation_helper::MESSAGE_OPTION_KEYS,
+        )?;
+        if guard.get_sheet_by_name(&sheet_name).is_none() {
+            return Err(format!("Sheet '{}' not found", sheet_name));
+        }
+
+        let mut validation =
+            data_validation_helper::new_validation(&cell_range, DataValidationValues::List)?;
+        validation.set_allow_blank(true);
+        validation.set_formula1(data_validation_helper::source_formula(
+            &guard,
+            &sheet_name,
+            &source_type,
+            &source,
+        )?);
+        data_validation_helper::apply_options(&mut validation, &options)?;
+
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+        data_validation_helper::add_validation(sheet, validation);
+
+        Ok(atoms::ok())
+    }));
+
+    match result {
+        Ok(Ok(atom)) => Ok(atom),
+        Ok(Err(err_msg)) => Err((atoms::error(), err_msg)),
+        Err(_) => Err((
+            atoms::error(),
+            "Error occurred in add_list_source_validation operation".to_string(),
+        )),
+    }
+}
+
+/// Update the validation that applies to a cell in place
+///
+/// A new `range` takes its cells out of the other validations of the sheet.
+#[rustler::nif]
+pub fn update_data_validation(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell: String,
+    options: HashMap<String, Term>,
+) -> Result<Atom, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Atom, String> {
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        let keys: Vec<&str> = data_validation_helper::MESSAGE_OPTION_KEYS
+            .iter()
+            .chain(data_validation_helper::RULE_OPTION_KEYS.iter())
+            .chain(["range"].iter())
+            .copied()
+            .collect();
+        data_validation_helper::check_option_keys(&options, &keys)?;
+        let areas = match options.get("range") {
+            Some(term) => {
+                let range: String = term
+                    .decode()
+                    .map_err(|_| "Option 'range' must be a string".to_string())?;
+                Some(data_validation_helper::parse_sqref(&range)?)
             }
+            None => None,
+        };
+        let (column, row) = data_validation_helper::parse_cell(&cell)?;
 
-            // Replace the original list with our filtered list
-            validations.set_data_validation_list(validations_to_keep);
-        }
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+        let index = data_validation_helper::position_at(sheet, column, row)
+            .ok_or_else(|| format!("No data validation applies to cell '{}'", cell))?;
+
+        let mut list = sheet
+            .get_data_validations()
+            .map(|validations| validations.get_data_validation_list().to_vec())
+            .unwrap_or_default();
+        let mut validation = list.remove(index);
+        data_validation_helper::apply_options(&mut validation, &options)?;
+        if let Some(areas) = areas {
+            data_validation_helper::set_areas(&mut validation, &areas);
+            list = data_validation_helper::remove_areas(list, &areas);
+        }
+        list.insert(index.min(list.len()), validation);
+        data_validation_helper::set_validations(sheet, list);
 
         Ok(atoms::ok())
     }));
@@ -569,7 +579,7 @@ pub fn remove_data_validation(
         Ok(Err(err_msg)) => Err((atoms::error(), err_msg)),
         Err(_) => Err((
             atoms::error(),
-            "Error occurred in remove_data_validation operation".to_string(),
+            "Error occurred in update_data_validation operation".to_string(),
         )),
     }
 }
@@ -1,8 +1,11 @@
 use rustler::{Atom, ResourceArc};
 use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
 use umya_spreadsheet::DataValidationValues;
 
 use crate::atoms;
+use crate::helpers::data_validation_check_helper;
+use crate::helpers::data_validation_helper;
 use crate::UmyaSpreadsheet;
 
 // Helper function to map DataValidationValues to string
@@ -37,6 +40,129 @@ fn operator_to_string(op: &umya_spreadsheet::DataValidationOperatorValues) -> St
     }
 }
 
+/// Builds the map `get_data_validations` returns for a validation
+fn validation_map<'a>(
+    env: rustler::Env<'a>,
+    validation: &umya_spreadsheet::DataValidation,
+) -> rustler::Term<'a> {
+    let mut map = rustler::types::map::map_new(env);
+
+    // Common properties for all validation types
+    map = map
+        .map_put(
+            atoms::range(),
+            validation
+                .get_sequence_of_references()
+                .get_sqref()
+                .to_string(),
+        )
+        .ok()
+        .unwrap();
+
+    // Type-specific properties
+    let validation_type = validation_type_to_string(validation.get_type());
+    map = map
+        .map_put(
+            atoms::rule_type(),
+            rustler::types::atom::Atom::from_str(env, &validation_type).unwrap(),
+        )
+        .ok()
+        .unwrap();
+
+    // Only include operator if applicable for this validation type
+    if *validation.get_type() != DataValidationValues::List
+        && *validation.get_type() != DataValidationValues::Custom
+    {
+        let op_str = operator_to_string(validation.get_operator());
+        map = map
+            .map_put(
+                atoms::operator(),
+                rustler::types::atom::Atom::from_str(env, &op_str).unwrap(),
+            )
+            .ok()
+            .unwrap();
+    }
+
+    // Formula 1 is present in all validation types
+    if !validation.get_formula1().is_empty() {
+        map = map
+            .map_put(atoms::formula1(), validation.get_formula1().to_string())
+            .ok()
+            .unwrap();
+    }
+
+    // Formula 2 is only present in "between" and "notBetween" operators
+    if !validation.get_formula2().is_empty() {
+        map = map
+            .map_put(atoms::formula2(), validation.get_formula2().to_string())
+            .ok()
+            .unwrap();
+    }
+
+    // Include error message details if present
+    if *validation.get_show_error_message() {
+        map = map
+            .map_put(
+                atoms::show_error_message(),
+                validation.get_show_error_message(),
+            )
+            .ok()
+            .unwrap();
+        if !validation.get_error_title().is_empty() {
+            map = map
+                .map_put(
+                    atoms::error_title(),
+                    validation.get_error_title().to_string(),
+                )
+                .ok()
+                .unwrap();
+        }
+        if !validation.get_error_message().is_empty() {
+            map = map
+                .map_put(
+                    atoms::error_message(),
+                    validation.get_error_message().to_string(),
+                )
+                .ok()
+                .unwrap();
+        }
+    }
+
+    // Include input message details if present
+    if *validation.get_show_input_message() {
+        map = map
+            .map_put(
+                atoms::show_input_message(),
+                validation.get_show_input_message(),
+            )
+            .ok()
+            .unwrap();
+        if !validation.get_prompt_title().is_empty() {
+            map = map
+                .map_put(
+                    atoms::prompt_title(),
+                    validation.get_prompt_title().to_string(),
+                )
+                .ok()
+                .unwrap();
+        }
+        if !validation.get_prompt().is_empty() {
+            map = map
+                .map_put(atoms::prompt_message(), validation.get_prompt().to_string())
+                .ok()
+                .unwrap();
+        }
+    }
+
+    // Whether the validation allows blank values
+    map = map
+        .map_put(atoms::allow_blank(), validation.get_allow_blank())
+        .ok()
+        .unwrap();
+
+    map
+}
+
 /// Get all data validation rules for a specific sheet or range
 #[rustler::nif(name = "get_data_validations")]
 pub fn get_data_validations_nif<'a>(
@@ -63,138 +189,136 @@ pub fn get_data_validations_nif<'a>(
                         }
                     }
 
-                    let mut map = rustler::types::map::map_new(env);
-
-                    // Common properties for all validation types
-                    map = map
-                        .map_put(
-                            atoms::range(),
-                            validation
-                                .get_sequence_of_references()
-                                .get_sqref()
-                                .to_string(),
-                        )
-                        .ok()
-                        .unwrap();
-
-                    // Type-specific properties
-                    let validation_type = validation_type_to_string(validation.get_type());
-                    map = map
-                        .map_put(
-                            atoms::rule_type(),
-                            rustler::types::atom::Atom::from_str(env, &validation_type).unwrap(),
-                        )
-                        .ok()
-                        .unwrap();
-
-                    // Only include operator if applicable for this validation type
-                    if *validation.get_type() != DataValidationValues::List
-                        && *validation.get_type() != DataValidationValues::Custom
-                    {
-                        let op_str = operator_to_string(validation.get_operator());
-                        map = map
-                            .map_put(
-                                atoms::operator(),
-                                rustler::types::atom::Atom::from_str(env, &op_str).unwrap(),
-                            )
-                            .ok()
-                            .unwrap();
-                    }
-
-                    // Formula 1 is present in all validation types
-                    if !validation.get_formula1().is_empty() {
-                        map = map
-                            .map_put(atoms::formula1(), validation.get_formula1().to_string())
-                            .ok()
-                            .unwrap();
-                    }
+                    validations.push(validation_map(env, validation));
+                }
+            }
 
-                    // Formula 2 is only present in "between" and "notBetween" operators
-                    if !validation.get_formula2().is_empty() {
-                        map = map
-                            .map_put(atoms::formula2(), validation.get_formula2().to_string())
-                            .ok()
-                            .unwrap();
-                    }
+            Ok(validations)
+        },
+    ));
 
-                    // Include error message details if present
-                    if *validation.get_show_error_message() {
-                        map = map
-                            .map_put(
-                                atoms::show_error_message(),
-                                validation.get_show_error_message(),
-                            )
-                            .ok()
-                            .unwrap();
-                        if !validation.get_error_title().is_empty() {
-                            map = map
-                                .map_put(
-                                    atoms::error_title(),
-                                    validation.get_error_title().to_string(),
-                                )
-                                .ok()
-                                .unwrap();
-                        }
-                        if !validation.get_error_message().is_empty() {
-                            map = map
-                                .map_put(
-                                    atoms::error_message(),
-                                    validation.get_error_message().to_string(),
-                                )
-                                .ok()
-                                .unwrap();
-                        }
-                    }
+    match result {
+        Ok(Ok(validations)) => Ok(validations),
+        Ok(Err(msg)) => Err((atoms::error(), msg)),
+        Err(_) => Err((
+            atoms::error(),
+            "Error occurred in get_data_validations".to_string(),
+        )),
+    }
+}
 
-                    // Include input message details if present
-                    if *validation.get_show_input_message() {
-                        map = map
-                            .map_put(
-                                atoms::show_input_message(),
-                                validation.get_show_input_message(),
-                            )
-                            .ok()
-                            .unwrap();
-                        if !validation.get_prompt_title().is_empty() {
-                            map = map
-                                .map_put(
-                                    atoms::prompt_title(),
-                                    validation.get_prompt_title().to_string(),
-                                )
-                                .ok()
-                                .unwrap();
-                        }
-                        if !validation.get_prompt().is_empty() {
-                            map = map
-                                .map_put(
-                                    atoms::prompt_message(),
-                                    validation.get_prompt().to_string(),
-                                )
-                                .ok()
-                                .unwrap();
-                        }
-                    }
+/// Get the data validation rule that applies to a cell, or nil when there is none
+#[rustler::nif(name = "get_data_validation_at")]
+pub fn get_data_validation_at_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell: String,
+) -> Result<Option<rustler::Term<'a>>, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        || -> Result<Option<rustler::Term<'a>>, String> {
+            let spreadsheet_guard = resource
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let sheet = spreadsheet_guard
+                .get_sheet_by_name(&sheet_name)
+                .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+            let (column, row) = data_validation_helper::parse_cell(&cell)?;
+
+            let validation =
+                data_validation_helper::position_at(sheet, column, row).and_then(|index| {
+                    sheet
+                        .get_data_validations()?
+                        .get_data_validation_list()
+                        .get(index)
+                });
+            Ok(validation.map(|validation| validation_map(env, validation)))
+        },
+    ));
 
-                    // Whether the validation allows blank values
-                    map = map
-                        .map_put(atoms::allow_blank(), validation.get_allow_blank())
-                        .ok()
-                        .unwrap();
+    match result {
+        Ok(Ok(validation)) => Ok(validation),
+        Ok(Err(msg)) => Err((atoms::error(), msg)),
+        Err(_) => Err((
+            atoms::error(),
+            "Error occurred in get_data_validation_at".to_string(),
+        )),
+    }
+}
 
-                    validations.push(map);
-                }
+/// Get the cells whose current values break their data validation rules
+#[rustler::nif(name = "get_data_validation_violations", schedule = "DirtyCpu")]
+pub fn get_data_validation_violations_nif<'a>(
+    env: rustler::Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+) -> Result<Vec<rustler::Term<'a>>, (Atom, String)> {
+    let result = panic::catch_unwind(AssertUnwindSafe(
+        || -> Result<Vec<rustler::Term<'a>>, String> {
+            let spreadsheet_guard = resource
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let sheet = spreadsheet_guard
+                .get_sheet_by_name(&sheet_name)
+                .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))?;
+            let violations =
+                data_validation_check_helper::violations(&spreadsheet_guard, &sheet_name)
+                    .unwrap_or_default();
+
+            let mut result = Vec::new();
+            for violation in violations {
+                let validation = violation.validation;
+                let cell = format!(
+                    "{}{}",
+                    string_from_column_index(&violation.column),
+                    violation.row
+                );
+                let rule_type = validation_type_to_string(validation.get_type());
+                let error_message = match validation.get_error_message() {
+                    "" => None,
+                    message => Some(message.to_string()),
+                };
+
+                let map = rustler::types::map::map_new(env)
+                    .map_put(atoms::cell(), cell)
+                    .ok()
+                    .unwrap()
+                    .map_put(
+                        atoms::range(),
+                        validation.get_sequence_of_references().get_sqref(),
+                    )
+                    .ok()
+                    .unwrap()
+                    .map_put(
+                        atoms::rule_type(),
+                        rustler::types::atom::Atom::from_str(env, &rule_type).unwrap(),
+                    )
+                    .ok()
+                    .unwrap()
+                    .map_put(
+                        atoms::value(),
+                        sheet.get_value((violation.column, violation.row)),
+                    )
+                    .ok()
+                    .unwrap()
+                    .map_put(atoms::error_message(), error_message)
+                    .ok()
+                    .unwrap();
+                result.push(map);
             }
 
-            Ok(validations)
+            Ok(result)
         },
     ));
 
     match result {
-        Ok(Ok(validations)) => Ok(validations),
+        Ok(Ok(violations)) => Ok(violations),
         Ok(Err(msg)) => Err((atoms::error(), msg)),
         Err(_) => Err((
             atoms::error(),
-            "Error occurred in get_data_validations".to_string(),
+            "Error occurred in get_data_validation_violations".to_string(),
         )),
     }
 }
@@ -245,17 +369,16 @@ pub fn get_list_validations_nif<'a>(
                         .unwrap();
                     map = map.map_put(atoms::rule_type(), atoms::list()).ok().unwrap();
 
-                    // Parse formula1 to get the list items
+                    // Parse formula1 to get the list items, inline or on the hidden list sheet
                     let formula = validation.get_formula1().to_string();
-                    if formula.starts_with('"') && formula.ends_with('"') {
-                        // Extract the comma-separated list
-                        let list_str = formula[1..formula.len() - 1].to_string();
-                        let list_items: Vec<String> =
-                            list_str.split(',').map(|s| s.to_string()).collect();
-                        map = map.map_put(atoms::list_items(), list_items).ok().unwrap();
-                    } else {
-                        // If formula doesn't start/end with quotes, it might be a range reference
-                        map = map.map_put(atoms::formula1(), formula).ok().unwrap();
+                    match data_validation_helper::list_items(&spreadsheet_guard, &formula) {
+                        Some(list_items) => {
+                            map = map.map_put(atoms::list_items(), list_items).ok().unwrap();
+                        }
+                        None => {
+                            // Other sources are ranges, defined names or INDIRECT formulas
+                            map = map.map_put(atoms::formula1(), formula).ok().unwrap();
+                        }
                     }
 
                     // Include error message details if present
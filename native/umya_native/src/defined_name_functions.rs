@@ -0,0 +1,619 @@
+use rustler::{Atom, Decoder, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{Cell, CellRawValue, DefinedName, Spreadsheet, Worksheet};
+
+use crate::atoms;
+use crate::helpers::defined_name_helper::{self, DefinedNameLocation};
+use crate::helpers::package_helper::PackageExtras;
+use crate::UmyaSpreadsheet;
+
+/// The most cells `get_defined_name_values` returns, so that a name over a huge
+/// range cannot build a list too large for the VM.
+const MAX_VALUE_CELLS: u64 = 1_000_000;
+
+/// Add a defined name with an explicit scope
+///
+/// A `sheet_name` of `None` creates a workbook-scoped name, otherwise the name is
+/// scoped to that sheet (written with its `localSheetId`).
+#[rustler::nif]
+pub fn add_defined_name(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    refers_to: String,
+    sheet_name: Option<String>,
+    hidden: bool,
+    comment: Option<String>,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut spreadsheet = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        defined_name_helper::validate_defined_name(&name)?;
+        if refers_to.trim().is_empty() {
+            return Err("Refers-to formula cannot be empty".to_string());
+        }
+
+        let local_sheet_id = defined_name_helper::resolve_scope(&spreadsheet, &sheet_name)?;
+        if defined_name_helper::find_defined_name(&spreadsheet, &name, local_sheet_id).is_some() {
+            return Err(format!(
+                "Defined name '{}' already exists in this scope",
+                name
+            ));
+        }
+
+        let mut defined_name = defined_name_helper::new_defined_name(&name, &refers_to);
+        if let Some(id) = local_sheet_id {
+            defined_name.set_local_sheet_id(id);
+        }
+        if hidden {
+            defined_name.set_hidden(true);
+        }
+        defined_name_helper::insert(&mut spreadsheet, defined_name);
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.set_defined_name_comment(&name, local_sheet_id, comment);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_defined_name operation".to_string(),
+        )))),
+    }
+}
+
+/// Update an existing defined name
+///
+/// `changes` is a map with any of the string keys "name", "refers_to", "hidden",
+/// "comment" (nil clears it) and "scope" (a sheet name, or nil for workbook scope).
+#[rustler::nif]
+pub fn update_defined_name<'a>(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+    changes: Term<'a>,
+) -> NifResult<Atom> {
+    let changes = <HashMap<String, Term<'a>>>::decode(changes).map_err(|_| {
+        NifError::Term(Box::new((
+            atoms::error(),
+            "Changes must be a map".to_string(),
+        )))
+    })?;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut spreadsheet = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        let local_sheet_id = defined_name_helper::resolve_scope(&spreadsheet, &sheet_name)?;
+        let location = defined_name_helper::find_defined_name(&spreadsheet, &name, local_sheet_id)
+            .ok_or_else(|| format!("Defined name '{}' not found", name))?;
+
+        let new_name = decode_change::<String>(&changes, "name")?;
+        let refers_to = decode_change::<String>(&changes, "refers_to")?;
+        let hidden = decode_change::<bool>(&changes, "hidden")?;
+        let comment = decode_change::<Option<String>>(&changes, "comment")?;
+        let new_scope = decode_change::<Option<String>>(&changes, "scope")?;
+
+        let target_name = new_name.clone().unwrap_or_else(|| name.clone());
+        defined_name_helper::validate_defined_name(&target_name)?;
+        if let Some(refers_to) = &refers_to {
+            if refers_to.trim().is_empty() {
+                return Err("Refers-to formula cannot be empty".to_string());
+            }
+        }
+
+        let target_scope = match &new_scope {
+            Some(scope) => defined_name_helper::resolve_scope(&spreadsheet, scope)?,
+            None => local_sheet_id,
+        };
+
+        if let Some(existing) =
+            defined_name_helper::find_defined_name(&spreadsheet, &target_name, target_scope)
+        {
+            if existing != location {
+                return Err(format!(
+                    "Defined name '{}' already exists in this scope",
+                    target_name
+                ));
+            }
+        }
+
+        let current = defined_name_helper::remove_at(&mut spreadsheet, location)
+            .ok_or_else(|| format!("Defined name '{}' not found", name))?;
+
+        let mut updated = match (&new_name, &refers_to) {
+            (None, None) => current.clone(),
+            _ => {
+                let address = refers_to.clone().unwrap_or_else(|| current.get_address());
+                let mut rebuilt = defined_name_helper::new_defined_name(&target_name, &address);
+                rebuilt.set_hidden(*current.get_hidden());
+                rebuilt
+            }
+        };
+        set_scope(&mut updated, target_scope);
+        if let Some(hidden) = hidden {
+            updated.set_hidden(hidden);
+        }
+        store_updated(&mut spreadsheet, location, &current, updated);
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.move_defined_name_comment(&name, local_sheet_id, &target_name, target_scope);
+        if let Some(comment) = comment {
+            extras.set_defined_name_comment(&target_name, target_scope, comment);
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in update_defined_name operation".to_string(),
+        )))),
+    }
+}
+
+/// Remove a defined name from the given scope
+#[rustler::nif]
+pub fn remove_defined_name(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut spreadsheet = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        let local_sheet_id = defined_name_helper::resolve_scope(&spreadsheet, &sheet_name)?;
+        let location = defined_name_helper::find_defined_name(&spreadsheet, &name, local_sheet_id)
+            .ok_or_else(|| format!("Defined name '{}' not found", name))?;
+        defined_name_helper::remove_at(&mut spreadsheet, location);
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.set_defined_name_comment(&name, local_sheet_id, None);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in remove_defined_name operation".to_string(),
+        )))),
+    }
+}
+
+/// Get a single defined name by name and scope
+#[rustler::nif]
+pub fn get_defined_name(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+) -> Term {
+    let spreadsheet = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
+
+    let local_sheet_id = match defined_name_helper::resolve_scope(&spreadsheet, &sheet_name) {
+        Ok(id) => id,
+        Err(msg) => return (atoms::error(), msg).encode(env),
+    };
+
+    match defined_name_helper::find_defined_name(&spreadsheet, &name, local_sheet_id)
+        .and_then(|location| defined_name_helper::get_at(&spreadsheet, location))
+    {
+        Some(defined_name) => (
+            atoms::ok(),
+            defined_name_to_map(env, &spreadsheet, &extras, defined_name),
+        )
+            .encode(env),
+        None => (atoms::error(), format!("Defined name '{}' not found", name)).encode(env),
+    }
+}
+
+/// List every defined name with its scope, hidden flag and comment
+#[rustler::nif]
+pub fn list_defined_names(env: Env, resource: ResourceArc<UmyaSpreadsheet>) -> Term {
+    let spreadsheet = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
+
+    let names: Vec<HashMap<String, Term>> = defined_name_helper::all_defined_names(&spreadsheet)
+        .into_iter()
+        .map(|(_, defined_name)| defined_name_to_map(env, &spreadsheet, &extras, defined_name))
+        .collect();
+
+    (atoms::ok(), names).encode(env)
+}
+
+/// Resolve a defined name to the sheet ranges it refers to
+///
+/// When `sheet_name` is given, a name scoped to that sheet takes precedence over a
+/// workbook-scoped name, as it does for formulas on that sheet.
+#[rustler::nif]
+pub fn resolve_defined_name(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+) -> Term {
+    let spreadsheet = resource.spreadsheet.lock().unwrap();
+
+    match resolve_areas(&spreadsheet, &name, &sheet_name) {
+        Ok(areas) => {
+            let areas: Vec<HashMap<String, String>> = areas
+                .into_iter()
+                .map(|(sheet, range)| {
+                    let mut area = HashMap::new();
+                    area.insert("sheet_name".to_string(), sheet);
+                    area.insert("range".to_string(), range);
+                    area
+                })
+                .collect();
+            (atoms::ok(), areas).encode(env)
+        }
+        Err(msg) => (atoms::error(), msg).encode(env),
+    }
+}
+
+/// Check a name against Excel's naming rules
+#[rustler::nif]
+pub fn validate_defined_name(name: String) -> NifResult<Atom> {
+    match defined_name_helper::validate_defined_name(&name) {
+        Ok(()) => Ok(atoms::ok()),
+        Err(err_msg) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+    }
+}
+
+/// Read the typed cell values of the range a defined name refers to
+///
+/// Returns `{:ok, %{"sheet_name" => ..., "range" => ..., "values" => rows}}`, where
+/// each row is a list of numbers, booleans, strings or nil for empty cells.
+/// Whole-column and whole-row names are clamped to the used area of the sheet,
+/// and ranges of more than `MAX_VALUE_CELLS` cells are rejected.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn get_defined_name_values(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut spreadsheet = resource.spreadsheet.lock().unwrap();
+        let (target_sheet, range) = resolve_single_area(&spreadsheet, &name, &sheet_name)?;
+        let sheet = sheet_for_area(&mut spreadsheet, &target_sheet)?;
+
+        let (max_column, max_row) = sheet.get_highest_column_and_row();
+        let (col_start, row_start, col_end, row_end) =
+            defined_name_helper::area_bounds(&range, max_column.max(1), max_row.max(1));
+        let cells = (col_end - col_start + 1) as u64 * (row_end - row_start + 1) as u64;
+        if cells > MAX_VALUE_CELLS {
+            return Err(format!(
+                "Defined name '{}' spans {} cells, more than the {} that can be read at once",
+                name, cells, MAX_VALUE_CELLS
+            ));
+        }
+
+        let rows: Vec<Vec<Term>> = (row_start..=row_end)
+            .map(|row| {
+                (col_start..=col_end)
+                    .map(|col| cell_value_to_term(env, sheet.get_cell((col, row))))
+                    .collect()
+            })
+            .collect();
+
+        let mut map = HashMap::new();
+        map.insert("sheet_name".to_string(), target_sheet.encode(env));
+        map.insert("range".to_string(), range.encode(env));
+        map.insert("values".to_string(), rows.encode(env));
+        Ok::<HashMap<String, Term>, String>(map)
+    }));
+
+    match result {
+        Ok(Ok(map)) => (atoms::ok(), map).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_defined_name_values operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+/// Fill the range a defined name refers to from a list of rows
+///
+/// Rows are written from the top-left cell of the range. Numbers, booleans and
+/// strings keep their type and nil clears the cell. Data that does not fit inside
+/// the named range is rejected rather than written past its bounds.
+#[rustler::nif]
+pub fn set_defined_name_values<'a>(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    sheet_name: Option<String>,
+    rows: Vec<Vec<Term<'a>>>,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut spreadsheet = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+
+        let (target_sheet, range) = resolve_single_area(&spreadsheet, &name, &sheet_name)?;
+        let (col_start, row_start, col_end, row_end) = defined_name_helper::area_bounds(
+            &range,
+            defined_name_helper::MAX_COLUMN,
+            defined_name_helper::MAX_ROW,
+        );
+
+        let height = (row_end - row_start + 1) as usize;
+        let width = (col_end - col_start + 1) as usize;
+        if rows.len() > height {
+            return Err(format!(
+                "{} rows do not fit into '{}', which spans {} rows",
+                rows.len(),
+                name,
+                height
+            ));
+        }
+        if let Some(row) = rows.iter().find(|row| row.len() > width) {
+            return Err(format!(
+                "{} columns do not fit into '{}', which spans {} columns",
+                row.len(),
+                name,
+                width
+            ));
+        }
+
+        // Decode everything first, so a bad value leaves the sheet untouched
+        let values = rows
+            .iter()
+            .map(|row| row.iter().map(|value| decode_cell_value(*value)).collect())
+            .collect::<Result<Vec<Vec<CellRawValue>>, String>>()?;
+
+        let sheet = sheet_for_area(&mut spreadsheet, &target_sheet)?;
+        for (row_offset, row) in values.into_iter().enumerate() {
+            for (col_offset, value) in row.into_iter().enumerate() {
+                let cell = sheet
+                    .get_cell_mut((col_start + col_offset as u32, row_start + row_offset as u32));
+                match value {
+                    CellRawValue::Bool(flag) => cell.set_value_bool(flag),
+                    CellRawValue::Numeric(number) => cell.set_value_number(number),
+                    CellRawValue::String(text) => cell.set_value_string(text.to_string()),
+                    _ => cell.set_blank(),
+                };
+            }
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in set_defined_name_values operation".to_string(),
+        )))),
+    }
+}
+
+/// Resolves a defined name to `(sheet name, range)` pairs.
+pub(crate) fn resolve_areas(
+    spreadsheet: &Spreadsheet,
+    name: &str,
+    sheet_name: &Option<String>,
+) -> Result<Vec<(String, String)>, String> {
+    let sheet_index = defined_name_helper::resolve_scope(spreadsheet, sheet_name)?;
+    let defined_name = defined_name_helper::lookup_defined_name(spreadsheet, name, sheet_index)
+        .and_then(|location| defined_name_helper::get_at(spreadsheet, location))
+        .ok_or_else(|| format!("Defined name '{}' not found", name))?;
+
+    // Unqualified references are relative to the sheet the name is scoped to
+    let default_sheet = defined_name_helper::scope_sheet_name(
+        spreadsheet,
+        defined_name_helper::scope_of(defined_name),
+    )
+    .or_else(|| sheet_name.clone());
+
+    let address = defined_name.get_address();
+    let mut areas = Vec::new();
+    for area in defined_name_helper::split_areas(&address) {
+        let (sheet, range) = defined_name_helper::split_area(&area).ok_or_else(|| {
+            format!(
+                "Defined name '{}' does not refer to a range: {}",
+                name, address
+            )
+        })?;
+        let sheet = sheet
+            .or_else(|| default_sheet.clone())
+            .ok_or_else(|| format!("Defined name '{}' has no sheet: {}", name, address))?;
+        defined_name_helper::resolve_scope(spreadsheet, &Some(sheet.clone()))?;
+        areas.push((sheet, range));
+    }
+
+    if areas.is_empty() {
+        return Err(format!("Defined name '{}' does not refer to a range", name));
+    }
+
+    Ok(areas)
+}
+
+/// Resolves a defined name that must refer to exactly one range.
+fn resolve_single_area(
+    spreadsheet: &Spreadsheet,
+    name: &str,
+    sheet_name: &Option<String>,
+) -> Result<(String, String), String> {
+    let mut areas = resolve_areas(spreadsheet, name, sheet_name)?;
+    if areas.len() > 1 {
+        return Err(format!(
+            "Defined name '{}' refers to {} ranges; only single ranges can be read or written",
+            name,
+            areas.len()
+        ));
+    }
+    Ok(areas.remove(0))
+}
+
+/// Returns the worksheet an area lives on, deserializing it first if it was lazily read.
+fn sheet_for_area<'s>(
+    spreadsheet: &'s mut Spreadsheet,
+    sheet_name: &str,
+) -> Result<&'s mut Worksheet, String> {
+    let index = defined_name_helper::resolve_scope(spreadsheet, &Some(sheet_name.to_string()))?
+        .unwrap_or_default() as usize;
+    spreadsheet.read_sheet(index);
+    spreadsheet
+        .get_sheet_mut(&index)
+        .ok_or_else(|| format!("Sheet '{}' not found", sheet_name))
+}
+
+/// Encodes a cell value with its type: number, boolean, string or nil.
+fn cell_value_to_term<'a>(env: Env<'a>, cell: Option<&Cell>) -> Term<'a> {
+    let cell = match cell {
+        Some(cell) => cell,
+        None => return rustler::types::atom::nil().encode(env),
+    };
+
+    match cell.get_raw_value() {
+        CellRawValue::Numeric(number) => {
+            if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+                (*number as i64).encode(env)
+            } else {
+                number.encode(env)
+            }
+        }
+        CellRawValue::Bool(value) => value.encode(env),
+        CellRawValue::Empty => rustler::types::atom::nil().encode(env),
+        _ => cell.get_value().to_string().encode(env),
+    }
+}
+
+/// Decodes an Elixir value into the raw value of a cell, keeping its type.
+fn decode_cell_value(value: Term) -> Result<CellRawValue, String> {
+    if let Ok(flag) = value.decode::<bool>() {
+        Ok(CellRawValue::Bool(flag))
+    } else if let Ok(number) = value.decode::<i64>() {
+        Ok(CellRawValue::Numeric(number as f64))
+    } else if let Ok(number) = value.decode::<f64>() {
+        Ok(CellRawValue::Numeric(number))
+    } else if let Ok(text) = value.decode::<String>() {
+        Ok(CellRawValue::String(text.into()))
+    } else if value.is_atom() && value == rustler::types::atom::nil().to_term(value.get_env()) {
+        Ok(CellRawValue::Empty)
+    } else {
+        Err("Values must be numbers, booleans, strings or nil".to_string())
+    }
+}
+
+/// Builds the map returned to Elixir for a defined name.
+fn defined_name_to_map<'a>(
+    env: Env<'a>,
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    defined_name: &DefinedName,
+) -> HashMap<String, Term<'a>> {
+    let local_sheet_id = defined_name_helper::scope_of(defined_name);
+    let mut map = HashMap::new();
+
+    map.insert("name".to_string(), defined_name.get_name().encode(env));
+    map.insert(
+        "refers_to".to_string(),
+        defined_name.get_address().encode(env),
+    );
+    map.insert(
+        "scope".to_string(),
+        defined_name_helper::scope_sheet_name(spreadsheet, local_sheet_id).encode(env),
+    );
+    map.insert("local_sheet_id".to_string(), local_sheet_id.encode(env));
+    map.insert("hidden".to_string(), defined_name.get_hidden().encode(env));
+    map.insert(
+        "comment".to_string(),
+        extras
+            .defined_name_comment(defined_name.get_name(), local_sheet_id)
+            .cloned()
+            .encode(env),
+    );
+
+    map
+}
+
+/// Decodes an optional entry of the `changes` map.
+fn decode_change<'a, T: Decoder<'a>>(
+    changes: &HashMap<String, Term<'a>>,
+    key: &str,
+) -> Result<Option<T>, String> {
+    match changes.get(key) {
+        Some(term) => T::decode(*term)
+            .map(Some)
+            .map_err(|_| format!("Invalid value for '{}'", key)),
+        None => Ok(None),
+    }
+}
+
+/// Applies a scope to a defined name; `None` makes it workbook-scoped.
+fn set_scope(defined_name: &mut DefinedName, local_sheet_id: Option<u32>) {
+    match local_sheet_id {
+        Some(id) => defined_name.set_local_sheet_id(id),
+        None => {
+            if defined_name.has_local_sheet_id() {
+                // There is no way to unset localSheetId, so rebuild the name without it
+                let mut rebuilt = defined_name_helper::new_defined_name(
+                    defined_name.get_name(),
+                    &defined_name.get_address(),
+                );
+                rebuilt.set_hidden(*defined_name.get_hidden());
+                *defined_name = rebuilt;
+            }
+        }
+    }
+}
+
+/// Puts an updated defined name back, keeping its slot when the scope did not change.
+fn store_updated(
+    spreadsheet: &mut Spreadsheet,
+    location: DefinedNameLocation,
+    previous: &DefinedName,
+    updated: DefinedName,
+) {
+    let same_scope =
+        defined_name_helper::scope_of(previous) == defined_name_helper::scope_of(&updated);
+    match location {
+        DefinedNameLocation::Workbook(index) if same_scope => {
+            let names = spreadsheet.get_defined_names_mut();
+            let index = index.min(names.len());
+            names.insert(index, updated);
+        }
+        DefinedNameLocation::Sheet(sheet_index, index) if same_scope => {
+            if let Some(sheet) = spreadsheet.get_sheet_mut(&sheet_index) {
+                let names = sheet.get_defined_names_mut();
+                let index = index.min(names.len());
+                names.insert(index, updated);
+            }
+        }
+        _ => defined_name_helper::insert(spreadsheet, updated),
+    }
+}
@@ -1,7 +1,9 @@
+use crate::helpers;
+use crate::helpers::crypt_helper::{self, EncryptionOptions};
+use crate::helpers::package_helper;
 use crate::{atoms, UmyaSpreadsheet};
 use rustler::{Atom, Binary, Env, Error as NifError, NifResult, OwnedBinary, ResourceArc};
 use std::path::Path;
-use umya_spreadsheet::writer::xlsx;
 
 /// Write a spreadsheet with compression level options.
 /// Compression levels range from 0 (no compression) to 9 (maximum compression).
@@ -29,6 +31,7 @@ pub fn write_with_compression(
 
     // Acquire mutex guard to access the spreadsheet data
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
     // Validate compression level (0-9)
     let _level = if compression_level > 9 {
@@ -41,7 +44,7 @@ pub fn write_with_compression(
 
     // Note: Currently all compression levels use the standard write method
     // Future enhancement: implement actual compression level control
-    let result = match xlsx::write(&guard, path_obj) {
+    let result = match package_helper::write_to_path(&guard, &extras, path_obj, false) {
         Ok(_) => Ok(atoms::ok()),
         Err(_) => Err(NifError::Term(Box::new((
             atoms::error(),
@@ -49,16 +52,22 @@ pub fn write_with_compression(
         )))),
     };
 
-    // Explicitly drop the guard to release the mutex before returning
-    // This ensures the mutex is always released, even in error cases
+    // Explicitly drop the guards to release the mutexes before returning
+    // This ensures the mutexes are always released, even in error cases
+    drop(extras);
     drop(guard);
 
     // Return the result
     result
 }
 
-/// Write a spreadsheet with enhanced encryption options
-#[rustler::nif]
+/// Write a spreadsheet encrypted with ECMA-376 agile encryption
+///
+/// `algorithm` selects the AES key size, `hash_algorithm` the hash used for key
+/// derivation and data integrity, and `salt_value` (base64, 16 bytes) and
+/// `spin_count` the password key derivation. The package is encrypted in memory
+/// and written to `path` in one step.
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn write_with_encryption_options(
     resource: ResourceArc<UmyaSpreadsheet>,
     path: String,
@@ -66,7 +75,18 @@ pub fn write_with_encryption_options(
     algorithm: String,
     salt_value: Option<String>,
     spin_count: Option<u32>,
+    hash_algorithm: Option<String>,
 ) -> NifResult<Atom> {
+    let options = match EncryptionOptions::parse(
+        &algorithm,
+        hash_algorithm.as_deref(),
+        salt_value.as_deref(),
+        spin_count,
+    ) {
+        Ok(options) => options,
+        Err(msg) => return Err(NifError::Term(Box::new((atoms::error(), msg)))),
+    };
+
     // For output paths, we use the direct path since the file might not exist yet
     let path_obj = Path::new(&path);
 
@@ -83,65 +103,65 @@ pub fn write_with_encryption_options(
     }
 
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    // First, apply any workbook protection settings with advanced options
-    // These will be included in the file before encryption
-    {
-        let mut spreadsheet_mut = guard.clone();
-
-        let workbook_protection = spreadsheet_mut.get_workbook_protection_mut();
-        workbook_protection.set_workbook_password(&password);
+    let encrypted = package_helper::write_encrypted_package(&guard, &extras, &password, &options);
 
-        // Set algorithm if specified (only for workbook protection)
-        if !algorithm.is_empty() && algorithm != "default" {
-            workbook_protection.set_revisions_algorithm_name(&algorithm);
-        }
-
-        // Set salt value if provided
-        if let Some(salt) = &salt_value {
-            workbook_protection.set_revisions_salt_value(salt);
-        }
+    // Explicitly drop the guards before touching the file system
+    drop(extras);
+    drop(guard);
 
-        // Set spin count if provided
-        if let Some(count) = spin_count {
-            workbook_protection.set_revisions_spin_count(count);
-        }
+    match encrypted.and_then(|data| std::fs::write(path_obj, data).map_err(|e| e.to_string())) {
+        Ok(_) => Ok(atoms::ok()),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Failed to write file with encryption".to_string(),
+        )))),
+    }
+}
 
-        // Write to a temporary file first
-        let temp_file = format!("{}.tmp", path);
-        let temp_path = Path::new(&temp_file);
+/// Convert the spreadsheet to an encrypted binary XLSX file instead of writing to disk
+///
+/// Takes the same encryption options as `write_with_encryption_options`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn to_encrypted_binary_xlsx<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    password: String,
+    algorithm: String,
+    salt_value: Option<String>,
+    spin_count: Option<u32>,
+    hash_algorithm: Option<String>,
+) -> NifResult<Binary<'a>> {
+    let options = match EncryptionOptions::parse(
+        &algorithm,
+        hash_algorithm.as_deref(),
+        salt_value.as_deref(),
+        spin_count,
+    ) {
+        Ok(options) => options,
+        Err(msg) => return Err(NifError::Term(Box::new((atoms::error(), msg)))),
+    };
 
-        // Write the file with protection settings
-        if xlsx::write(&spreadsheet_mut, temp_path).is_err() {
-            // Explicitly drop the guard to ensure mutex is released
-            drop(guard);
-            return Err(NifError::Term(Box::new((
-                atoms::error(),
-                "Failed to write temporary file".to_string(),
-            ))));
-        }
+    let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-        // Now encrypt the file with password
-        let result = match xlsx::set_password(temp_path, path_obj, &password) {
-            Ok(_) => {
-                // Clean up temporary file
-                let _ = std::fs::remove_file(temp_path);
-                Ok(atoms::ok())
-            }
-            Err(_) => {
-                let _ = std::fs::remove_file(temp_path);
-                Err(NifError::Term(Box::new((
-                    atoms::error(),
-                    "Failed to write file with encryption".to_string(),
-                ))))
-            }
-        };
+    let encrypted = package_helper::write_encrypted_package(&guard, &extras, &password, &options);
 
-        // Explicitly drop the guard to ensure mutex is released
-        drop(guard);
+    // Explicitly drop the guards before returning
+    drop(extras);
+    drop(guard);
 
-        // Return the result
-        result
+    match encrypted {
+        Ok(data) => {
+            let mut owned = OwnedBinary::new(data.len()).unwrap();
+            owned.copy_from_slice(&data);
+            Ok(Binary::from_owned(owned, env))
+        }
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Failed to encrypt spreadsheet".to_string(),
+        )))),
     }
 }
 
@@ -152,14 +172,11 @@ pub fn to_binary_xlsx<'a>(
     resource: ResourceArc<UmyaSpreadsheet>,
 ) -> Result<Binary<'a>, Atom> {
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    // Use a memory buffer instead of a file
-    let mut buffer = std::io::Cursor::new(Vec::new());
-
-    // Write to the memory buffer
-    let result = match xlsx::write_writer(&guard, &mut buffer) {
-        Ok(_) => {
-            let data = buffer.into_inner();
+    // Write to a memory buffer instead of a file
+    let result = match package_helper::write_package(&guard, &extras, false) {
+        Ok(data) => {
             let mut owned = OwnedBinary::new(data.len()).unwrap();
             owned.copy_from_slice(&data);
             Ok(Binary::from_owned(owned, env))
@@ -167,7 +184,8 @@ pub fn to_binary_xlsx<'a>(
         Err(_) => Err(atoms::error()),
     };
 
-    // Explicitly drop the guard before returning
+    // Explicitly drop the guards before returning
+    drop(extras);
     drop(guard);
 
     // Return the result
@@ -182,53 +200,58 @@ pub fn get_compression_level(_resource: ResourceArc<UmyaSpreadsheet>) -> NifResu
     Ok(6)
 }
 
-/// Check if a spreadsheet has encryption enabled
+/// Check if the spreadsheet was read from an encrypted file
+///
+/// Workbook protection is a separate feature and does not count as encryption.
 #[rustler::nif]
 pub fn is_encrypted(resource: ResourceArc<UmyaSpreadsheet>) -> NifResult<bool> {
-    let guard = resource.spreadsheet.lock().unwrap();
-
-    // Check if workbook protection is enabled by checking if the password is non-empty
-    let has_protection = match guard.get_workbook_protection() {
-        Some(protection) => !protection.get_workbook_password_raw().is_empty(),
-        None => false,
-    };
+    let extras = resource.package_extras.lock().unwrap();
+    let encrypted = extras.source_encryption.is_some();
 
     // Explicitly drop the guard before returning
-    drop(guard);
+    drop(extras);
 
-    // Return the result
-    Ok(has_protection)
+    Ok(encrypted)
+}
+
+/// Check if a file on disk is encrypted by inspecting its container
+///
+/// Encrypted packages are stored in an OLE compound document holding an
+/// `EncryptionInfo` stream, while plain packages are zip archives.
+#[rustler::nif]
+pub fn is_file_encrypted(path: String) -> NifResult<bool> {
+    let valid_path = match helpers::path_helper::find_valid_file_path(&path) {
+        Some(p) => p,
+        None => {
+            return Err(NifError::Term(Box::new((
+                atoms::error(),
+                "File not found".to_string(),
+            ))))
+        }
+    };
+
+    match std::fs::read(Path::new(&valid_path)) {
+        Ok(data) => Ok(crypt_helper::is_encrypted_package(&data)),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "read_error".to_string(),
+        )))),
+    }
 }
 
-/// Get the encryption algorithm used for the workbook
+/// Get the encryption algorithm of the file the spreadsheet was read from
 #[rustler::nif]
 pub fn get_encryption_algorithm(
     resource: ResourceArc<UmyaSpreadsheet>,
 ) -> NifResult<Option<String>> {
-    let guard = resource.spreadsheet.lock().unwrap();
-
-    // Check if a password is set
-    if let Some(protection) = guard.get_workbook_protection() {
-        if !protection.get_workbook_password_raw().is_empty() {
-            // Get the algorithm name or use default
-            let algorithm = protection.get_revisions_algorithm_name().to_string();
-
-            // Return default if empty
-            let result = if algorithm.is_empty() {
-                "default".to_string()
-            } else {
-                algorithm
-            };
-
-            // Explicitly drop the guard before returning
-            drop(guard);
-
-            return Ok(Some(result));
-        }
-    }
+    let extras = resource.package_extras.lock().unwrap();
+    let algorithm = extras
+        .source_encryption
+        .as_ref()
+        .map(|encryption| encryption.algorithm_name());
 
     // Explicitly drop the guard before returning
-    drop(guard);
+    drop(extras);
 
-    Ok(None)
+    Ok(algorithm)
 }
@@ -4,6 +4,8 @@ use umya_spreadsheet;
 
 use crate::atoms;
 use crate::helpers;
+use crate::helpers::crypt_helper::{self, Container};
+use crate::helpers::package_helper::{self, PackageExtras};
 use crate::UmyaSpreadsheet;
 
 /// Create a new spreadsheet file with default sheet
@@ -12,6 +14,7 @@ pub fn new_file() -> NifResult<ResourceArc<UmyaSpreadsheet>> {
     let spreadsheet = umya_spreadsheet::new_file();
     let resource = ResourceArc::new(UmyaSpreadsheet {
         spreadsheet: std::sync::Mutex::new(spreadsheet),
+        package_extras: std::sync::Mutex::new(PackageExtras::default()),
     });
     Ok(resource)
 }
@@ -22,6 +25,7 @@ pub fn new_file_empty_worksheet() -> NifResult<ResourceArc<UmyaSpreadsheet>> {
     let spreadsheet = umya_spreadsheet::new_file_empty_worksheet();
     let resource = ResourceArc::new(UmyaSpreadsheet {
         spreadsheet: std::sync::Mutex::new(spreadsheet),
+        package_extras: std::sync::Mutex::new(PackageExtras::default()),
     });
     Ok(resource)
 }
@@ -43,16 +47,21 @@ pub fn read_file(path: String) -> NifResult<ResourceArc<UmyaSpreadsheet>> {
     let path_obj = Path::new(&valid_path);
 
     // Improved error handling with specific error messages for corrupted files
-    match umya_spreadsheet::reader::xlsx::read(path_obj) {
-        Ok(spreadsheet) => {
+    match package_helper::read_spreadsheet_from_path(path_obj, false) {
+        Ok(mut spreadsheet) => {
+            let mut extras = package_helper::read_extras_from_path(path_obj);
+            extras.restore_into(&mut spreadsheet);
+
             let resource = ResourceArc::new(UmyaSpreadsheet {
                 spreadsheet: std::sync::Mutex::new(spreadsheet),
+                package_extras: std::sync::Mutex::new(extras),
             });
             Ok(resource)
         }
         Err(e) => {
             // Provide specific error messages based on the error type
             let error_msg = match e.to_string().as_str() {
+                _ if is_encrypted_container(path_obj) => "password_required",
                 s if s.contains("zip") => "corrupted_file",
                 s if s.contains("xml") => "invalid_format",
                 s if s.contains("permission") => "access_denied",
@@ -83,16 +92,21 @@ pub fn lazy_read_file(path: String) -> NifResult<ResourceArc<UmyaSpreadsheet>> {
     let path_obj = Path::new(&valid_path);
 
     // Handle both .xlsx and .xlsm files with lazy loading and improved error handling
-    match umya_spreadsheet::reader::xlsx::lazy_read(path_obj) {
-        Ok(spreadsheet) => {
+    match package_helper::read_spreadsheet_from_path(path_obj, true) {
+        Ok(mut spreadsheet) => {
+            let mut extras = package_helper::read_extras_from_path(path_obj);
+            extras.restore_into(&mut spreadsheet);
+
             let resource = ResourceArc::new(UmyaSpreadsheet {
                 spreadsheet: std::sync::Mutex::new(spreadsheet),
+                package_extras: std::sync::Mutex::new(extras),
             });
             Ok(resource)
         }
         Err(e) => {
             // Provide specific error messages based on the error type
             let error_msg = match e.to_string().as_str() {
+                _ if is_encrypted_container(path_obj) => "password_required",
                 s if s.contains("zip") => "corrupted_file",
                 s if s.contains("xml") => "invalid_format",
                 s if s.contains("permission") => "access_denied",
@@ -106,6 +120,85 @@ pub fn lazy_read_file(path: String) -> NifResult<ResourceArc<UmyaSpreadsheet>> {
     }
 }
 
+/// Read a password-encrypted spreadsheet file (ECMA-376 agile encryption)
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn read_file_with_password(
+    path: String,
+    password: String,
+) -> NifResult<ResourceArc<UmyaSpreadsheet>> {
+    let valid_path = match helpers::path_helper::find_valid_file_path(&path) {
+        Some(p) => p,
+        None => {
+            return Err(NifError::Term(Box::new((
+                atoms::error(),
+                "File not found".to_string(),
+            ))))
+        }
+    };
+
+    let data = match std::fs::read(Path::new(&valid_path)) {
+        Ok(data) => data,
+        Err(e) => {
+            let error_msg = match e.kind() {
+                std::io::ErrorKind::PermissionDenied => "access_denied",
+                _ => "read_error",
+            };
+            return Err(NifError::Term(Box::new((
+                atoms::error(),
+                error_msg.to_string(),
+            ))));
+        }
+    };
+
+    // Decrypt the package; the reason tells a wrong password from an unsupported cipher
+    let (package, encryption) = match crypt_helper::decrypt_package(&data, &password) {
+        Ok(decrypted) => decrypted,
+        Err(e) => {
+            return Err(NifError::Term(Box::new((
+                atoms::error(),
+                e.reason().to_string(),
+            ))))
+        }
+    };
+
+    let readable = package_helper::readable_package(std::io::Cursor::new(&package))
+        .ok()
+        .flatten();
+    let readable = readable.as_deref().unwrap_or(&package);
+    match umya_spreadsheet::reader::xlsx::read_reader(std::io::Cursor::new(readable), true) {
+        Ok(mut spreadsheet) => {
+            let mut extras =
+                package_helper::read_extras(std::io::Cursor::new(&package)).unwrap_or_default();
+            extras.source_encryption = Some(encryption);
+            extras.restore_into(&mut spreadsheet);
+
+            let resource = ResourceArc::new(UmyaSpreadsheet {
+                spreadsheet: std::sync::Mutex::new(spreadsheet),
+                package_extras: std::sync::Mutex::new(extras),
+            });
+            Ok(resource)
+        }
+        Err(e) => {
+            let error_msg = match e.to_string().as_str() {
+                s if s.contains("xml") => "invalid_format",
+                _ => "corrupted_file",
+            };
+            Err(NifError::Term(Box::new((
+                atoms::error(),
+                error_msg.to_string(),
+            ))))
+        }
+    }
+}
+
+/// Returns true when the file is an OLE container, i.e. an encrypted package
+fn is_encrypted_container(path: &Path) -> bool {
+    matches!(
+        crypt_helper::detect_file_container(path),
+        Ok(Container::Ole)
+    )
+}
+
 /// Write a spreadsheet to a file
 #[rustler::nif]
 pub fn write_file(resource: ResourceArc<UmyaSpreadsheet>, path: String) -> NifResult<Atom> {
@@ -126,11 +219,12 @@ pub fn write_file(resource: ResourceArc<UmyaSpreadsheet>, path: String) -> NifRe
     }
 
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    match umya_spreadsheet::writer::xlsx::write(&guard, path_obj) {
+    match package_helper::write_to_path(&guard, &extras, path_obj, false) {
         Ok(_) => Ok(atoms::ok()),
         Err(e) => {
-            let error_msg = match e.to_string().as_str() {
+            let error_msg = match e.as_str() {
                 s if s.contains("permission") => "access_denied",
                 s if s.contains("space") => "insufficient_disk_space",
                 _ => "write_error",
@@ -163,11 +257,12 @@ pub fn write_file_light(
     }
 
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    match umya_spreadsheet::writer::xlsx::write_light(&guard, path_obj) {
+    match package_helper::write_to_path(&guard, &extras, path_obj, true) {
         Ok(_) => Ok(atoms::ok()),
         Err(e) => {
-            let error_msg = match e.to_string().as_str() {
+            let error_msg = match e.as_str() {
                 s if s.contains("permission") => "access_denied",
                 s if s.contains("space") => "insufficient_disk_space",
                 _ => "write_error",
@@ -201,17 +296,20 @@ pub fn write_file_with_password(
     }
 
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    let result =
-        match umya_spreadsheet::writer::xlsx::write_with_password(&guard, path_obj, &password) {
-            Ok(_) => Ok(atoms::ok()),
-            Err(_) => Err(NifError::Term(Box::new((
-                atoms::error(),
-                "Failed to write file with password".to_string(),
-            )))),
-        };
+    let result = match package_helper::write_to_path_with_password(
+        &guard, &extras, path_obj, &password, false,
+    ) {
+        Ok(_) => Ok(atoms::ok()),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Failed to write file with password".to_string(),
+        )))),
+    };
 
-    // Explicitly drop the guard to ensure mutex is released before returning
+    // Explicitly drop the guards to ensure mutexes are released before returning
+    drop(extras);
     drop(guard);
 
     // Return the result
@@ -242,9 +340,10 @@ pub fn write_file_with_password_light(
     }
 
     let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    let result = match umya_spreadsheet::writer::xlsx::write_with_password_light(
-        &guard, path_obj, &password,
+    let result = match package_helper::write_to_path_with_password(
+        &guard, &extras, path_obj, &password, true,
     ) {
         Ok(_) => Ok(atoms::ok()),
         Err(_) => Err(NifError::Term(Box::new((
@@ -253,7 +352,8 @@ pub fn write_file_with_password_light(
         )))),
     };
 
-    // Explicitly drop the guard to ensure mutex is released before returning
+    // Explicitly drop the guards to ensure mutexes are released before returning
+    drop(extras);
     drop(guard);
 
     // Return the result
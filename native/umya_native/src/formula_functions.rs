@@ -1,10 +1,10 @@
 use crate::atoms;
+use crate::helpers::defined_name_helper;
 use crate::UmyaSpreadsheet;
 use rustler::{Atom, Error as NifError, NifResult};
 use std::panic::{self, AssertUnwindSafe};
 use umya_spreadsheet::CellFormula;
 use umya_spreadsheet::CellFormulaValues;
-use umya_spreadsheet::DefinedName;
 
 #[rustler::nif]
 pub fn set_array_formula(
@@ -79,9 +79,7 @@ pub fn create_named_range(
         if range.trim().is_empty() {
             return Err("Range cannot be empty".to_string());
         }
-        if name.trim().is_empty() {
-            return Err("Name cannot be empty".to_string());
-        }
+        defined_name_helper::validate_defined_name(&name)?;
 
         // Get the sheet and use its add_defined_name method which can call the private set_name method
         // since it's within the same crate
@@ -124,35 +122,22 @@ pub fn create_defined_name(
         if formula.trim().is_empty() {
             return Err("Formula cannot be empty".to_string());
         }
-        if name.trim().is_empty() {
-            return Err("Name cannot be empty".to_string());
-        }
-
-        // Handle sheet-scoped vs global defined names
-        if let Some(sheet_name_str) = &sheet_name {
-            // For sheet-scoped defined names, use the worksheet's add_defined_name method
-            // which can call the private set_name method since it's within the same crate
-            let sheet = spreadsheet
-                .get_sheet_by_name_mut(sheet_name_str)
-                .ok_or_else(|| format!("Sheet '{}' not found", sheet_name_str))?;
-
-            sheet
-                .add_defined_name(name, formula)
-                .map_err(|e| format!("Failed to add defined name: {}", e))?;
-        } else {
-            // For global defined names, we cannot set the name directly due to the set_name method being private.
-            // This is a limitation of the current umya-spreadsheet 2.3.0 API when used from external crates.
-            // We create a defined name with only the formula/address set.
-            // Note: The name won't be set, but the formula will still work.
-            let mut defined_name = DefinedName::default();
-            defined_name.set_address(&formula);
+        defined_name_helper::validate_defined_name(&name)?;
 
-            // Add the defined name to the spreadsheet
-            spreadsheet.add_defined_names(defined_name);
+        let local_sheet_id = defined_name_helper::resolve_scope(&spreadsheet, &sheet_name)?;
+        if defined_name_helper::find_defined_name(&spreadsheet, &name, local_sheet_id).is_some() {
+            return Err(format!(
+                "Defined name '{}' already exists in this scope",
+                name
+            ));
+        }
 
-            // Log a warning about the limitation
-            // eprintln!("Warning: Global defined name '{}' created without name due to API limitations in umya-spreadsheet 2.3.0. The formula '{}' is stored and functional.", name, formula);
+        // Sheet-scoped names carry the sheet's localSheetId; global names go to the workbook
+        let mut defined_name = defined_name_helper::new_defined_name(&name, &formula);
+        if let Some(id) = local_sheet_id {
+            defined_name.set_local_sheet_id(id);
         }
+        defined_name_helper::insert(&mut spreadsheet, defined_name);
 
         Ok(())
     }));
@@ -175,25 +160,16 @@ pub fn get_defined_names(
         // Lock the mutex to get access to the spreadsheet
         let guard = spreadsheet_resource.spreadsheet.lock().unwrap();
 
-        // Get global defined names
-        let defined_names = guard.get_defined_names();
-        let mut names = Vec::new();
-
-        // Add global defined names
-        for defined_name in defined_names {
-            let name = defined_name.get_name().to_string();
-            let address = defined_name.get_address();
-            names.push((name, address));
-        }
-
-        // Add sheet-specific defined names
-        for sheet in guard.get_sheet_collection() {
-            for defined_name in sheet.get_defined_names() {
-                let name = defined_name.get_name().to_string();
-                let address = defined_name.get_address();
-                names.push((name, address));
-            }
-        }
+        // Workbook-level names first, then the names stored with each sheet
+        let names: Vec<(String, String)> = defined_name_helper::all_defined_names(&guard)
+            .into_iter()
+            .map(|(_, defined_name)| {
+                (
+                    defined_name.get_name().to_string(),
+                    defined_name.get_address(),
+                )
+            })
+            .collect();
 
         Ok::<Vec<(String, String)>, String>(names)
     }));
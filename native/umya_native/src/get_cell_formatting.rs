@@ -1,7 +1,16 @@
+use crate::helpers::range_helper::{self, RangeTarget};
+use crate::helpers::style_map_helper;
 use crate::{atoms, UmyaSpreadsheet};
-use rustler::{Error as NifError, NifResult, ResourceArc};
+use rustler::{Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
 use umya_spreadsheet::EnumTrait;
 
+/// The most cells `get_range_styles` reads at once, so that a huge explicit range
+/// cannot build more style maps than the VM can hold.
+const MAX_STYLE_CELLS: u64 = 100_000;
+
 #[rustler::nif]
 pub fn get_font_name(
     resource: ResourceArc<UmyaSpreadsheet>,
@@ -729,3 +738,119 @@ pub fn get_cell_hidden(
         )))),
     }
 }
+
+// ============================================================================
+// STYLE MAP GETTERS
+// ============================================================================
+
+/// Read the complete style of a cell as one nested map
+///
+/// The map has the keys of the style maps taken by `set_range_style`, with every
+/// part filled in and theme colors resolved. A cell that does not exist reports the
+/// style of its row or column.
+#[rustler::nif]
+pub fn get_cell_style(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_address: String,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Term, String> {
+        let (column, row) = match range_helper::parse_range(&cell_address) {
+            Ok(RangeTarget::Cells {
+                start_column,
+                start_row,
+                end_column,
+                end_row,
+            }) if start_column == end_column && start_row == end_row => (start_column, start_row),
+            _ => return Err(format!("Invalid cell address '{}'", cell_address)),
+        };
+
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let style = style_map_helper::effective_style(sheet, column, row);
+        Ok(style_map_helper::encode_style(
+            env,
+            &style,
+            guard.get_theme(),
+        ))
+    }));
+
+    match result {
+        Ok(Ok(style)) => (atoms::ok(), style).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_cell_style operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+/// Read the complete style of every cell in a range
+///
+/// Returns a map from cell address to the style map of `get_cell_style`. Whole rows
+/// and columns are clamped to the used area of the sheet, and ranges of more than
+/// `MAX_STYLE_CELLS` cells are rejected.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn get_range_styles(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Term, String> {
+        let target = range_helper::parse_range(&range)?;
+
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let (start_column, start_row, mut end_column, mut end_row) = target.bounds();
+        if !matches!(target, RangeTarget::Cells { .. }) {
+            let (max_column, max_row) = sheet.get_highest_column_and_row();
+            end_column = end_column.min(max_column.max(1));
+            end_row = end_row.min(max_row.max(1));
+        }
+        let cells = (end_column - start_column + 1) as u64 * (end_row - start_row + 1) as u64;
+        if cells > MAX_STYLE_CELLS {
+            return Err(format!(
+                "Range '{}' spans {} cells, more than the {} that can be read at once",
+                range, cells, MAX_STYLE_CELLS
+            ));
+        }
+
+        let theme = guard.get_theme();
+        let mut styles = HashMap::new();
+        for row in start_row..=end_row {
+            for column in start_column..=end_column {
+                let style = style_map_helper::effective_style(sheet, column, row);
+                styles.insert(
+                    format!("{}{}", string_from_column_index(&column), row),
+                    style_map_helper::encode_style(env, &style, theme),
+                );
+            }
+        }
+        Ok(styles.encode(env))
+    }));
+
+    match result {
+        Ok(Ok(styles)) => (atoms::ok(), styles).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_range_styles operation".to_string(),
+        )
+            .encode(env),
+    }
+}
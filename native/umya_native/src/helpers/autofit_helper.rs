@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use crate::helpers::dimension_helper::{self, MAX_COLUMN_WIDTH, MAX_ROW_HEIGHT};
+use crate::helpers::range_helper::{self, Bounds};
+use umya_spreadsheet::{Cell, Font, Worksheet};
+
+/// Width of the digits of the default font (Calibri 11) in pixels, the unit Excel
+/// measures column widths in.
+pub const MAX_DIGIT_WIDTH: f64 = 7.0;
+
+/// Pixels Excel keeps free around the text of a cell.
+const CELL_PADDING: f64 = 5.0;
+
+/// Advance widths of the printable ASCII characters, space to tilde, in 1/1000 em.
+type Metrics = [u16; 95];
+
+#[rustfmt::skip]
+const CALIBRI: Metrics = [
+    226, 326, 401, 498, 507, 715, 682, 221, 303, 303, 498, 498, 250, 306, 252, 386,
+    507, 507, 507, 507, 507, 507, 507, 507, 507, 507, 268, 268, 498, 498, 498, 463,
+    894, 579, 544, 533, 615, 488, 459, 631, 623, 252, 319, 520, 420, 855, 646, 662,
+    517, 673, 543, 459, 487, 642, 567, 890, 519, 487, 468, 307, 386, 307, 498, 498,
+    291, 479, 525, 423, 525, 498, 305, 471, 525, 230, 239, 455, 230, 799, 525, 527,
+    525, 525, 349, 391, 335, 525, 452, 715, 433, 453, 395, 314, 460, 314, 498,
+];
+
+#[rustfmt::skip]
+const ARIAL: Metrics = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[rustfmt::skip]
+const TIMES_NEW_ROMAN: Metrics = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444,
+    921, 722, 667, 667, 722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722,
+    556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333, 278, 333, 469, 500,
+    333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+    500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+
+/// Bold text runs about this much wider than regular text.
+const BOLD_FACTOR: f64 = 1.05;
+
+/// Font properties that decide how wide and how high text is drawn.
+struct FontSpec {
+    metrics: &'static Metrics,
+    /// Row height of one line in points per point of font size
+    line_height: f64,
+    size: f64,
+    bold: bool,
+}
+
+impl FontSpec {
+    /// Picks the metrics for a font; fonts without a table are measured as Calibri.
+    fn of(font: Option<&Font>) -> FontSpec {
+        let name = font.map(|font| font.get_name().to_lowercase());
+        let (metrics, line_height) = match name.as_deref() {
+            Some(name) if name.starts_with("arial") || name.starts_with("helvetica") => {
+                (&ARIAL, 12.75 / 10.0)
+            }
+            Some(name) if name.starts_with("times") => (&TIMES_NEW_ROMAN, 15.75 / 12.0),
+            _ => (&CALIBRI, 15.0 / 11.0),
+        };
+        let size = font
+            .map(|font| *font.get_size())
+            .filter(|size| *size > 0.0)
+            .unwrap_or(11.0);
+        FontSpec {
+            metrics,
+            line_height,
+            size,
+            bold: font.is_some_and(|font| *font.get_bold()),
+        }
+    }
+
+    /// Width of `text` drawn in this font, in pixels.
+    fn text_width(&self, text: &str) -> f64 {
+        let units: f64 = text.chars().map(|c| self.char_width(c)).sum();
+        let width = units / 1000.0 * self.size * 96.0 / 72.0;
+        if self.bold {
+            width * BOLD_FACTOR
+        } else {
+            width
+        }
+    }
+
+    fn char_width(&self, c: char) -> f64 {
+        match c {
+            ' '..='~' => f64::from(self.metrics[c as usize - 0x20]),
+            _ if is_wide(c) => 1000.0,
+            // Other letters are about as wide as an average lowercase letter
+            _ => f64::from(self.metrics['n' as usize - 0x20]),
+        }
+    }
+
+    /// Height of one line of text, in points.
+    fn line_height(&self) -> f64 {
+        self.size * self.line_height
+    }
+}
+
+/// Tells whether East Asian text draws `c` at full width.
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF
+        | 0x20000..=0x3FFFD)
+}
+
+/// Converts a text width in pixels to a column width in characters, as Excel does.
+pub fn column_width_from_pixels(pixels: f64) -> f64 {
+    ((pixels.ceil() + CELL_PADDING) / MAX_DIGIT_WIDTH * 256.0).trunc() / 256.0
+}
+
+/// Converts a column width in characters to the pixels available for text.
+pub fn pixels_from_column_width(width: f64) -> f64 {
+    let pixels =
+        ((256.0 * width + (128.0 / MAX_DIGIT_WIDTH).trunc()) / 256.0 * MAX_DIGIT_WIDTH).trunc();
+    (pixels - CELL_PADDING).max(0.0)
+}
+
+/// Rounds a height in points up to whole pixels, as Excel stores row heights.
+fn round_height(points: f64) -> f64 {
+    (points * 96.0 / 72.0 - 1e-9).ceil() * 72.0 / 96.0
+}
+
+/// Returns the width each column of `bounds` needs to show its cells.
+///
+/// Cells are measured in their own font with their number format applied. Wrapped
+/// cells count with their longest word, as their lines follow the column width, and
+/// cells merged across columns do not count, as in Excel. Columns without text are
+/// left out.
+pub fn fit_columns(sheet: &Worksheet, bounds: Bounds) -> BTreeMap<u32, f64> {
+    let merged = merged_areas(sheet);
+    let mut pixels: BTreeMap<u32, f64> = BTreeMap::new();
+
+    for cell in cells_in(sheet, bounds) {
+        let (column, row) = position(cell);
+        let spans_columns = merged.iter().any(|area| {
+            area.0 != area.2 && range_helper::intersect(*area, (column, row, column, row)).is_some()
+        });
+        if spans_columns {
+            continue;
+        }
+
+        let text = cell.get_formatted_value();
+        if text.is_empty() {
+            continue;
+        }
+        let style = cell.get_style();
+        let font = FontSpec::of(style.get_font());
+        let wrap = style
+            .get_alignment()
+            .is_some_and(|alignment| *alignment.get_wrap_text());
+        let rotation = style
+            .get_alignment()
+            .map_or(0, |alignment| *alignment.get_text_rotation());
+
+        let width = text
+            .lines()
+            .flat_map(|line| {
+                if wrap {
+                    line.split_whitespace().collect::<Vec<_>>()
+                } else {
+                    vec![line]
+                }
+            })
+            .map(|part| font.text_width(part))
+            .fold(0.0, f64::max);
+        let width = rotated_width(width, &font, rotation, text.lines().count());
+
+        let entry = pixels.entry(column).or_insert(0.0);
+        *entry = entry.max(width);
+    }
+
+    pixels
+        .into_iter()
+        .map(|(column, pixels)| {
+            (
+                column,
+                column_width_from_pixels(pixels).min(MAX_COLUMN_WIDTH),
+            )
+        })
+        .collect()
+}
+
+/// Horizontal extent of text turned by an Excel text rotation.
+fn rotated_width(width: f64, font: &FontSpec, rotation: u32, lines: usize) -> f64 {
+    let line_pixels = font.line_height() * 96.0 / 72.0 * lines.max(1) as f64;
+    let degrees = match rotation {
+        0 => return width,
+        // Stacked text is one character wide
+        255 => return font.text_width("W"),
+        1..=90 => rotation as f64,
+        _ => rotation.saturating_sub(90) as f64,
+    };
+    let radians = degrees.to_radians();
+    width * radians.cos() + line_pixels * radians.sin()
+}
+
+/// Returns the height each row of `bounds` needs to show its cells.
+///
+/// Rows get the height of their largest font, times the number of lines of wrapped
+/// cells at the current column widths. Cells merged across rows do not count, as in
+/// Excel. Rows without text are left out.
+pub fn fit_rows(sheet: &Worksheet, bounds: Bounds) -> BTreeMap<u32, f64> {
+    let merged = merged_areas(sheet);
+    let mut heights: BTreeMap<u32, f64> = BTreeMap::new();
+
+    for cell in cells_in(sheet, bounds) {
+        let (column, row) = position(cell);
+        let spans_rows = merged.iter().any(|area| {
+            area.1 != area.3 && range_helper::intersect(*area, (column, row, column, row)).is_some()
+        });
+        if spans_rows {
+            continue;
+        }
+
+        let text = cell.get_formatted_value();
+        if text.is_empty() {
+            continue;
+        }
+        let style = cell.get_style();
+        let font = FontSpec::of(style.get_font());
+        let wrap = style
+            .get_alignment()
+            .is_some_and(|alignment| *alignment.get_wrap_text());
+
+        let lines = if wrap {
+            let available = pixels_from_column_width(dimension_helper::column_width(sheet, column));
+            text.lines()
+                .map(|line| wrapped_line_count(line, &font, available))
+                .sum::<usize>()
+                .max(1)
+        } else {
+            1
+        };
+        let height = font.line_height() * lines as f64;
+
+        let entry = heights.entry(row).or_insert(0.0);
+        *entry = entry.max(height);
+    }
+
+    heights
+        .into_iter()
+        .map(|(row, height)| (row, round_height(height).min(MAX_ROW_HEIGHT)))
+        .collect()
+}
+
+/// Counts the lines a paragraph takes when wrapped at `available` pixels.
+fn wrapped_line_count(line: &str, font: &FontSpec, available: f64) -> usize {
+    let space = font.text_width(" ");
+    let mut lines = 1;
+    let mut used = 0.0;
+    for word in line.split_whitespace() {
+        let width = font.text_width(word);
+        if used > 0.0 && used + space + width > available {
+            lines += 1;
+            used = 0.0;
+        }
+        used = if used > 0.0 {
+            used + space + width
+        } else {
+            width
+        };
+        // Words wider than the column break over several lines
+        while used > available && available > 0.0 {
+            lines += 1;
+            used -= available;
+        }
+    }
+    lines
+}
+
+fn cells_in(sheet: &Worksheet, bounds: Bounds) -> impl Iterator<Item = &Cell> {
+    sheet.get_cell_collection().into_iter().filter(move |cell| {
+        let (column, row) = position(cell);
+        range_helper::intersect(bounds, (column, row, column, row)).is_some()
+    })
+}
+
+fn position(cell: &Cell) -> (u32, u32) {
+    let coordinate = cell.get_coordinate();
+    (*coordinate.get_col_num(), *coordinate.get_row_num())
+}
+
+fn merged_areas(sheet: &Worksheet) -> Vec<Bounds> {
+    sheet
+        .get_merge_cells()
+        .iter()
+        .filter_map(|range| range_helper::parse_range(&range.get_range()).ok())
+        .map(|target| target.bounds())
+        .collect()
+}
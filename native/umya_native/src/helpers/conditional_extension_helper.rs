@@ -0,0 +1,965 @@
+use crate::helpers::color_helper;
+use crate::helpers::conditional_format_helper;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::name::QName;
+use quick_xml::{Reader, Writer};
+use rustler::{Encoder, Env, Term};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Cursor, Write};
+use umya_spreadsheet::{
+    ConditionalFormatValueObject, ConditionalFormatValueObjectValues, ConditionalFormatValues,
+    ConditionalFormatting, ConditionalFormattingRule, IconSet, Worksheet,
+};
+
+/// Icon sets Excel 2007 knows, named as the `iconSet` attribute names them.
+const ICON_SETS: [&str; 17] = [
+    "3Arrows",
+    "3ArrowsGray",
+    "3Flags",
+    "3TrafficLights1",
+    "3TrafficLights2",
+    "3Signs",
+    "3Symbols",
+    "3Symbols2",
+    "4Arrows",
+    "4ArrowsGray",
+    "4RedToBlack",
+    "4Rating",
+    "4TrafficLights",
+    "5Arrows",
+    "5ArrowsGray",
+    "5Rating",
+    "5Quarters",
+];
+
+/// Icon sets added by Excel 2010, which only the x14 extension of a worksheet holds.
+const X14_ICON_SETS: [&str; 3] = ["3Stars", "3Triangles", "5Boxes"];
+
+/// Icon set of a custom icon that shows no icon at all.
+const NO_ICONS: &str = "NoIcons";
+
+const DATA_BAR_OPTION_KEYS: [&str; 7] = [
+    "gradient",
+    "border_color",
+    "negative_fill_color",
+    "negative_border_color",
+    "axis_position",
+    "axis_color",
+    "direction",
+];
+const ICON_SET_OPTION_KEYS: [&str; 3] = ["show_value", "reverse", "icons"];
+
+const X14_NAMESPACE: &str = "http://schemas.microsoft.com/office/spreadsheetml/2009/9/main";
+const XM_NAMESPACE: &str = "http://schemas.microsoft.com/office/excel/2006/main";
+/// `<ext>` of a `<cfRule>` that links it to its x14 rule
+const RULE_EXTENSION_URI: &str = "{B025F937-C7B1-47D3-B67F-A62EFF666E3E}";
+/// `<ext>` of a worksheet that holds the x14 conditional formatting
+const CONDITIONAL_FORMATTINGS_URI: &str = "{78C0D931-6437-407d-A8EE-F0AAD7539E65}";
+
+/// Data bar settings added by Excel 2010, which umya-spreadsheet does not model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataBarOptions {
+    /// Bars fade out towards their end instead of having a solid fill
+    pub gradient: bool,
+    /// ARGB color of the bar border; `None` draws no border
+    pub border_color: Option<String>,
+    /// ARGB fill of bars for negative values; `None` fills them like positive ones
+    pub negative_fill_color: Option<String>,
+    /// ARGB border of bars for negative values; `None` takes the border color
+    pub negative_border_color: Option<String>,
+    /// "automatic", "middle" or "none"
+    pub axis_position: String,
+    /// ARGB color of the axis between negative and positive bars
+    pub axis_color: Option<String>,
+    /// "context", "leftToRight" or "rightToLeft"
+    pub direction: String,
+}
+
+impl Default for DataBarOptions {
+    fn default() -> Self {
+        DataBarOptions {
+            gradient: true,
+            border_color: None,
+            negative_fill_color: None,
+            negative_border_color: None,
+            axis_position: "automatic".to_string(),
+            axis_color: None,
+            direction: "context".to_string(),
+        }
+    }
+}
+
+/// Settings of an icon set, which umya-spreadsheet neither reads nor writes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IconSetOptions {
+    /// Excel name of the icon set, e.g. "3TrafficLights1"
+    pub icon_set: String,
+    /// Show the cell value next to the icon
+    pub show_value: bool,
+    /// Give the lowest values the icon of the highest ones
+    pub reverse: bool,
+    /// Icons of a custom icon set by icon set and 0-based index, from the lowest
+    /// threshold up; empty for the icons of `icon_set`
+    pub icons: Vec<(String, u32)>,
+}
+
+impl IconSetOptions {
+    /// Returns the options of a plain icon set with `count` icons.
+    pub fn for_icon_count(count: usize) -> Self {
+        let icon_set = match count {
+            4 => "4Arrows",
+            5 => "5Arrows",
+            _ => "3TrafficLights1",
+        };
+        IconSetOptions {
+            icon_set: icon_set.to_string(),
+            show_value: true,
+            reverse: false,
+            icons: Vec::new(),
+        }
+    }
+
+    /// Returns true when only the x14 extension can hold the icon set.
+    pub fn is_x14_only(&self) -> bool {
+        !self.icons.is_empty() || X14_ICON_SETS.contains(&self.icon_set.as_str())
+    }
+}
+
+/// Options of a conditional formatting rule that are kept outside of umya-spreadsheet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RuleExtension {
+    DataBar(DataBarOptions),
+    IconSet(IconSetOptions),
+}
+
+/// An icon set rule read from the x14 extension of a worksheet, which
+/// umya-spreadsheet does not see.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtensionIconSet {
+    pub sqref: String,
+    pub priority: Option<i32>,
+    /// Type and value of each threshold
+    pub thresholds: Vec<(String, String)>,
+    pub options: IconSetOptions,
+}
+
+/// Parses an icon set as Excel names it ("3TrafficLights1") or in snake case
+/// ("3_traffic_lights"), where the trailing "1" of the first variant may be left out.
+pub fn parse_icon_set(value: &str) -> Result<&'static str, String> {
+    let key: String = value
+        .chars()
+        .filter(|c| !matches!(c, '_' | ' ' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    ICON_SETS
+        .iter()
+        .chain(X14_ICON_SETS.iter())
+        .chain(std::iter::once(&NO_ICONS))
+        .find(|icon_set| {
+            let name = icon_set.to_lowercase();
+            name == key || name == format!("{}1", key)
+        })
+        .copied()
+        .ok_or_else(|| format!("Invalid icon style '{}'", value))
+}
+
+/// Returns the number of icons of an icon set from the digit its name starts with.
+pub fn icon_count(icon_set: &str) -> usize {
+    icon_set
+        .chars()
+        .next()
+        .and_then(|c| c.to_digit(10))
+        .map(|count| count as usize)
+        .unwrap_or(1)
+}
+
+/// Decodes the options map of a data bar; `nil` gives the defaults.
+pub fn decode_data_bar_options(options: Term) -> Result<DataBarOptions, String> {
+    let options = match decode_options(options, "Data bar")? {
+        Some(options) => options,
+        None => return Ok(DataBarOptions::default()),
+    };
+    check_keys(&options, &DATA_BAR_OPTION_KEYS, "data bar")?;
+
+    let mut decoded = DataBarOptions::default();
+    if let Some(gradient) = flag(&options, "gradient")? {
+        decoded.gradient = gradient;
+    }
+    decoded.border_color = color(&options, "border_color")?;
+    decoded.negative_fill_color = color(&options, "negative_fill_color")?;
+    decoded.negative_border_color = color(&options, "negative_border_color")?;
+    decoded.axis_color = color(&options, "axis_color")?;
+    if let Some(position) = string(&options, "axis_position")? {
+        decoded.axis_position = match position.to_lowercase().as_str() {
+            "automatic" | "auto" => "automatic",
+            "middle" => "middle",
+            "none" => "none",
+            _ => return Err(format!("Invalid axis position '{}'", position)),
+        }
+        .to_string();
+    }
+    if let Some(direction) = string(&options, "direction")? {
+        decoded.direction = parse_direction(&direction)?.to_string();
+    }
+    Ok(decoded)
+}
+
+/// Decodes the options map of an icon set of `icon_set`; `nil` gives the defaults.
+pub fn decode_icon_set_options(options: Term, icon_set: &str) -> Result<IconSetOptions, String> {
+    let mut decoded = IconSetOptions {
+        icon_set: icon_set.to_string(),
+        ..IconSetOptions::for_icon_count(3)
+    };
+    let options = match decode_options(options, "Icon set")? {
+        Some(options) => options,
+        None => return Ok(decoded),
+    };
+    check_keys(&options, &ICON_SET_OPTION_KEYS, "icon set")?;
+
+    if let Some(show_value) = flag(&options, "show_value")? {
+        decoded.show_value = show_value;
+    }
+    if let Some(reverse) = flag(&options, "reverse")? {
+        decoded.reverse = reverse;
+    }
+    if let Some(term) = options.get("icons") {
+        let icons: Option<Vec<(String, u32)>> = term.decode().map_err(|_| {
+            "Option 'icons' must be a list of {icon_style, index} tuples".to_string()
+        })?;
+        for (name, index) in icons.unwrap_or_default() {
+            let icon_set = parse_icon_set(&name)?;
+            let count = if icon_set == NO_ICONS {
+                1
+            } else {
+                icon_count(icon_set)
+            };
+            if index as usize >= count {
+                return Err(format!(
+                    "Icon index {} is out of range for icon style '{}'",
+                    index, icon_set
+                ));
+            }
+            decoded.icons.push((icon_set.to_string(), index));
+        }
+    }
+    Ok(decoded)
+}
+
+fn decode_options<'a>(
+    options: Term<'a>,
+    kind: &str,
+) -> Result<Option<HashMap<String, Term<'a>>>, String> {
+    if let Ok(None) = options.decode::<Option<bool>>() {
+        return Ok(None);
+    }
+    options
+        .decode()
+        .map(Some)
+        .map_err(|_| format!("{} options must be a map", kind))
+}
+
+fn check_keys(options: &HashMap<String, Term>, keys: &[&str], kind: &str) -> Result<(), String> {
+    match options.keys().find(|key| !keys.contains(&key.as_str())) {
+        Some(key) => Err(format!("Unknown {} option '{}'", kind, key)),
+        None => Ok(()),
+    }
+}
+
+fn flag(options: &HashMap<String, Term>, key: &str) -> Result<Option<bool>, String> {
+    match options.get(key) {
+        Some(term) => term
+            .decode::<Option<bool>>()
+            .map_err(|_| format!("Option '{}' must be a boolean", key)),
+        None => Ok(None),
+    }
+}
+
+fn string(options: &HashMap<String, Term>, key: &str) -> Result<Option<String>, String> {
+    match options.get(key) {
+        Some(term) => term
+            .decode::<Option<String>>()
+            .map_err(|_| format!("Option '{}' must be a string", key)),
+        None => Ok(None),
+    }
+}
+
+fn color(options: &HashMap<String, Term>, key: &str) -> Result<Option<String>, String> {
+    match string(options, key)? {
+        Some(value) => color_helper::parse_color(&value)
+            .map(Some)
+            .map_err(|_| format!("Invalid color '{}'", value)),
+        None => Ok(None),
+    }
+}
+
+/// Parses a bar direction as Excel names it ("leftToRight") or in snake case.
+fn parse_direction(value: &str) -> Result<&'static str, String> {
+    let key: String = value
+        .chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect();
+    match key.as_str() {
+        "context" => Ok("context"),
+        "lefttoright" => Ok("leftToRight"),
+        "righttoleft" => Ok("rightToLeft"),
+        _ => Err(format!("Invalid direction '{}'", value)),
+    }
+}
+
+/// Returns the name `parse_direction` takes for an Excel bar direction.
+fn direction_name(direction: &str) -> &str {
+    match direction {
+        "leftToRight" => "left_to_right",
+        "rightToLeft" => "right_to_left",
+        other => other,
+    }
+}
+
+/// Encodes data bar options as a map with the keys `decode_data_bar_options` takes.
+pub fn encode_data_bar_options<'a>(env: Env<'a>, options: &DataBarOptions) -> Term<'a> {
+    let mut map = HashMap::new();
+    map.insert("gradient", options.gradient.encode(env));
+    map.insert("border_color", options.border_color.encode(env));
+    map.insert(
+        "negative_fill_color",
+        options.negative_fill_color.encode(env),
+    );
+    map.insert(
+        "negative_border_color",
+        options.negative_border_color.encode(env),
+    );
+    map.insert("axis_position", options.axis_position.encode(env));
+    map.insert("axis_color", options.axis_color.encode(env));
+    map.insert("direction", direction_name(&options.direction).encode(env));
+    map.encode(env)
+}
+
+/// Encodes icon set options as a map with the keys `decode_icon_set_options` takes.
+pub fn encode_icon_set_options<'a>(env: Env<'a>, options: &IconSetOptions) -> Term<'a> {
+    let mut map = HashMap::new();
+    map.insert("show_value", options.show_value.encode(env));
+    map.insert("reverse", options.reverse.encode(env));
+    map.insert("icons", options.icons.encode(env));
+    map.encode(env)
+}
+
+/// Lists the priorities of the rules of a sheet.
+pub fn rule_priorities(sheet: &Worksheet) -> HashSet<i32> {
+    sheet
+        .get_conditional_formatting_collection()
+        .iter()
+        .flat_map(|formatting| formatting.get_conditional_collection())
+        .map(|rule| *rule.get_priority())
+        .collect()
+}
+
+/// Returns true when a sheet has icon set rules, which umya-spreadsheet writes
+/// with a `<dataBar>` element in place of `<iconSet>`.
+pub fn has_icon_sets(sheet: &Worksheet) -> bool {
+    sheet
+        .get_conditional_formatting_collection()
+        .iter()
+        .flat_map(|formatting| formatting.get_conditional_collection())
+        .any(|rule| *rule.get_type() == ConditionalFormatValues::IconSet)
+}
+
+/// Adds an icon set read from the x14 extension to a sheet and returns its priority.
+pub fn restore_icon_set(sheet: &mut Worksheet, rule: &ExtensionIconSet) -> i32 {
+    let priority = rule
+        .priority
+        .unwrap_or_else(|| conditional_format_helper::next_priority(sheet));
+
+    let mut icon_set = IconSet::default();
+    for (threshold_type, value) in &rule.thresholds {
+        let mut cfvo = ConditionalFormatValueObject::default();
+        let cfvo_type = match threshold_type.as_str() {
+            "min" => ConditionalFormatValueObjectValues::Min,
+            "max" => ConditionalFormatValueObjectValues::Max,
+            "num" => ConditionalFormatValueObjectValues::Number,
+            "percentile" => ConditionalFormatValueObjectValues::Percentile,
+            "formula" => ConditionalFormatValueObjectValues::Formula,
+            _ => ConditionalFormatValueObjectValues::Percent,
+        };
+        cfvo.set_type(cfvo_type);
+        if !value.is_empty() {
+            cfvo.set_val(value.as_str());
+        }
+        icon_set.add_cfvo_collection(cfvo);
+    }
+
+    let mut conditional_rule = ConditionalFormattingRule::default();
+    conditional_rule.set_type(ConditionalFormatValues::IconSet);
+    conditional_rule.set_priority(priority);
+    conditional_rule.set_icon_set(icon_set);
+    let mut formatting = ConditionalFormatting::default();
+    formatting
+        .get_sequence_of_references_mut()
+        .set_sqref(rule.sqref.as_str());
+    formatting.add_conditional_collection(conditional_rule);
+    sheet.add_conditional_formatting_collection(formatting);
+    priority
+}
+
+/// A rule written into the x14 extension of a worksheet.
+struct X14Rule {
+    id: String,
+    sqref: String,
+    priority: String,
+    thresholds: Vec<(String, String)>,
+    extension: RuleExtension,
+}
+
+/// A `<conditionalFormatting>` held back until it is known whether any of its rules
+/// stay in it.
+struct BufferedFormatting {
+    sqref: String,
+    writer: Writer<Cursor<Vec<u8>>>,
+    kept_rules: usize,
+}
+
+/// How a `<cfRule>` umya-spreadsheet wrote is patched.
+enum RulePatch {
+    Copy,
+    /// A data bar that gets a link to its x14 rule
+    DataBar(X14Rule),
+    /// An icon set whose `<dataBar>` element becomes `<iconSet>`
+    IconSet(IconSetOptions),
+    /// An icon set that moves to the x14 extension
+    X14IconSet(X14Rule),
+}
+
+/// Writes the options of the data bar and icon set rules into a worksheet
+/// umya-spreadsheet wrote.
+///
+/// `extensions` holds the options by rule priority; `sheet_index` makes the ids that
+/// link rules to their x14 rules unique within the workbook. Icon sets get a proper
+/// `<iconSet>` element, and those only Excel 2010 knows move to the x14 extension
+/// along with the x14 part of the data bars.
+pub fn patch_worksheet(
+    xml: &str,
+    extensions: &BTreeMap<i32, RuleExtension>,
+    sheet_index: usize,
+) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    // Each <conditionalFormatting> is buffered, as it is dropped when all its
+    // rules move to the extension
+    let mut formatting: Option<BufferedFormatting> = None;
+    let mut rule: Option<RulePatch> = None;
+    let mut x14_rules = Vec::new();
+    let mut extension_written = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let name = match &event {
+            Event::Start(e) | Event::Empty(e) => Some(e.name().as_ref().to_vec()),
+            Event::End(e) => Some(e.name().as_ref().to_vec()),
+            _ => None,
+        };
+
+        match (&event, name.as_deref()) {
+            (Event::Start(e), Some(b"conditionalFormatting")) => {
+                let sqref = attribute(e, b"sqref").unwrap_or_default();
+                let mut buffer = Writer::new(Cursor::new(Vec::new()));
+                write_event(&mut buffer, event)?;
+                formatting = Some(BufferedFormatting {
+                    sqref,
+                    writer: buffer,
+                    kept_rules: 0,
+                });
+                continue;
+            }
+            (Event::End(_), Some(b"conditionalFormatting")) => {
+                if let Some(mut buffered) = formatting.take() {
+                    if buffered.kept_rules > 0 {
+                        write_event(&mut buffered.writer, event)?;
+                        write_raw(&mut writer, &buffered.writer.into_inner().into_inner())?;
+                    }
+                }
+                continue;
+            }
+            (Event::Start(e), Some(b"cfRule")) => {
+                let sqref = formatting
+                    .as_ref()
+                    .map(|buffered| buffered.sqref.clone())
+                    .unwrap_or_default();
+                let patch = rule_patch(e, &sqref, extensions, sheet_index);
+                if !matches!(patch, RulePatch::X14IconSet(_)) {
+                    if let Some(buffered) = formatting.as_mut() {
+                        buffered.kept_rules += 1;
+                    }
+                }
+                rule = Some(patch);
+            }
+            (Event::Empty(_), Some(b"cfRule")) => {
+                if let Some(buffered) = formatting.as_mut() {
+                    buffered.kept_rules += 1;
+                }
+            }
+            (Event::Empty(e), Some(b"cfvo")) => {
+                let threshold = (
+                    attribute(e, b"type").unwrap_or_default(),
+                    attribute(e, b"val").unwrap_or_default(),
+                );
+                match rule.as_mut() {
+                    Some(RulePatch::DataBar(x14_rule)) => x14_rule.thresholds.push(threshold),
+                    Some(RulePatch::X14IconSet(x14_rule)) => {
+                        x14_rule.thresholds.push(threshold);
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+            (Event::Start(_), Some(b"dataBar")) => {
+                if let Some(RulePatch::IconSet(options)) = &rule {
+                    let icon_set = icon_set_start(options);
+                    write_current(&mut writer, &mut formatting, Event::Start(icon_set))?;
+                    continue;
+                }
+            }
+            (Event::End(_), Some(b"dataBar")) => {
+                if let Some(RulePatch::IconSet(_)) = &rule {
+                    let end = Event::End(BytesEnd::new("iconSet"));
+                    write_current(&mut writer, &mut formatting, end)?;
+                    continue;
+                }
+            }
+            (Event::End(_), Some(b"cfRule")) => match rule.take() {
+                Some(RulePatch::DataBar(x14_rule)) => {
+                    let link = format!(
+                        "<extLst><ext uri=\"{}\" xmlns:x14=\"{}\"><x14:id>{}</x14:id></ext></extLst>",
+                        RULE_EXTENSION_URI, X14_NAMESPACE, x14_rule.id
+                    );
+                    write_current_raw(&mut writer, &mut formatting, link.as_bytes())?;
+                    x14_rules.push(x14_rule);
+                }
+                Some(RulePatch::X14IconSet(x14_rule)) => {
+                    x14_rules.push(x14_rule);
+                    continue;
+                }
+                _ => (),
+            },
+            (Event::Start(_), Some(b"extLst")) if formatting.is_none() && rule.is_none() => {
+                write_event(&mut writer, event)?;
+                write_raw(&mut writer, x14_extension(&x14_rules).as_bytes())?;
+                extension_written = true;
+                continue;
+            }
+            (Event::End(_), Some(b"worksheet")) if !extension_written && !x14_rules.is_empty() => {
+                let extension = format!("<extLst>{}</extLst>", x14_extension(&x14_rules));
+                write_raw(&mut writer, extension.as_bytes())?;
+            }
+            (Event::Eof, _) => break,
+            _ => (),
+        }
+
+        if matches!(rule, Some(RulePatch::X14IconSet(_))) {
+            continue;
+        }
+        write_current(&mut writer, &mut formatting, event)?;
+    }
+
+    String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())
+}
+
+/// Decides how a `<cfRule>` is patched from its type and the options of its priority.
+fn rule_patch(
+    element: &BytesStart,
+    sqref: &str,
+    extensions: &BTreeMap<i32, RuleExtension>,
+    sheet_index: usize,
+) -> RulePatch {
+    let priority = attribute(element, b"priority").unwrap_or_default();
+    let extension = priority
+        .parse::<i32>()
+        .ok()
+        .and_then(|priority| extensions.get(&priority));
+    let x14_rule = |extension: RuleExtension| X14Rule {
+        id: rule_id(sheet_index, &priority),
+        sqref: sqref.to_string(),
+        priority: priority.clone(),
+        thresholds: Vec::new(),
+        extension,
+    };
+
+    match (attribute(element, b"type").as_deref(), extension) {
+        (Some("dataBar"), Some(RuleExtension::DataBar(options))) => {
+            RulePatch::DataBar(x14_rule(RuleExtension::DataBar(options.clone())))
+        }
+        (Some("iconSet"), Some(RuleExtension::IconSet(options))) if options.is_x14_only() => {
+            RulePatch::X14IconSet(x14_rule(RuleExtension::IconSet(options.clone())))
+        }
+        (Some("iconSet"), Some(RuleExtension::IconSet(options))) => {
+            RulePatch::IconSet(options.clone())
+        }
+        // The icon set is unknown, so Excel's default one is written
+        (Some("iconSet"), _) => RulePatch::IconSet(IconSetOptions::for_icon_count(3)),
+        _ => RulePatch::Copy,
+    }
+}
+
+/// Returns the id linking a rule to its x14 rule, made up of the sheet and priority.
+fn rule_id(sheet_index: usize, priority: &str) -> String {
+    format!(
+        "{{{:08X}-0000-4000-8000-{:012X}}}",
+        sheet_index + 1,
+        priority.parse::<u32>().unwrap_or(0)
+    )
+}
+
+fn icon_set_start(options: &IconSetOptions) -> BytesStart<'static> {
+    let mut icon_set = BytesStart::new("iconSet");
+    icon_set.push_attribute(("iconSet", options.icon_set.as_str()));
+    if !options.show_value {
+        icon_set.push_attribute(("showValue", "0"));
+    }
+    if options.reverse {
+        icon_set.push_attribute(("reverse", "1"));
+    }
+    icon_set
+}
+
+/// Builds the `<ext>` element holding the x14 rules of a worksheet.
+fn x14_extension(rules: &[X14Rule]) -> String {
+    if rules.is_empty() {
+        return String::new();
+    }
+
+    let mut xml = format!(
+        "<ext uri=\"{}\" xmlns:x14=\"{}\"><x14:conditionalFormattings>",
+        CONDITIONAL_FORMATTINGS_URI, X14_NAMESPACE
+    );
+    for rule in rules {
+        xml.push_str(&format!(
+            "<x14:conditionalFormatting xmlns:xm=\"{}\">",
+            XM_NAMESPACE
+        ));
+        match &rule.extension {
+            RuleExtension::DataBar(options) => {
+                xml.push_str(&format!("<x14:cfRule type=\"dataBar\" id=\"{}\">", rule.id));
+                xml.push_str(&x14_data_bar(options, &rule.thresholds));
+            }
+            RuleExtension::IconSet(options) => {
+                xml.push_str(&format!(
+                    "<x14:cfRule type=\"iconSet\" priority=\"{}\" id=\"{}\">",
+                    escape(&rule.priority),
+                    rule.id
+                ));
+                xml.push_str(&x14_icon_set(options, &rule.thresholds));
+            }
+        }
+        xml.push_str(&format!(
+            "</x14:cfRule><xm:sqref>{}</xm:sqref></x14:conditionalFormatting>",
+            escape(&rule.sqref)
+        ));
+    }
+    xml.push_str("</x14:conditionalFormattings></ext>");
+    xml
+}
+
+fn x14_data_bar(options: &DataBarOptions, thresholds: &[(String, String)]) -> String {
+    let mut attributes = String::new();
+    if options.border_color.is_some() {
+        attributes.push_str(" border=\"1\"");
+    }
+    if !options.gradient {
+        attributes.push_str(" gradient=\"0\"");
+    }
+    if options.direction != "context" {
+        attributes.push_str(&format!(" direction=\"{}\"", options.direction));
+    }
+    if options.negative_fill_color.is_none() {
+        attributes.push_str(" negativeBarColorSameAsPositive=\"1\"");
+    }
+    if options.negative_border_color.is_some() {
+        attributes.push_str(" negativeBarBorderColorSameAsPositive=\"0\"");
+    }
+    if options.axis_position != "automatic" {
+        attributes.push_str(&format!(" axisPosition=\"{}\"", options.axis_position));
+    }
+
+    let mut xml = format!("<x14:dataBar{}>", attributes);
+    xml.push_str(&x14_thresholds(thresholds));
+    let colors = [
+        ("borderColor", &options.border_color),
+        ("negativeFillColor", &options.negative_fill_color),
+        ("negativeBorderColor", &options.negative_border_color),
+        ("axisColor", &options.axis_color),
+    ];
+    for (element, color) in colors {
+        if let Some(color) = color {
+            xml.push_str(&format!("<x14:{} rgb=\"{}\"/>", element, color));
+        }
+    }
+    xml.push_str("</x14:dataBar>");
+    xml
+}
+
+fn x14_icon_set(options: &IconSetOptions, thresholds: &[(String, String)]) -> String {
+    let mut xml = format!("<x14:iconSet iconSet=\"{}\"", options.icon_set);
+    if !options.show_value {
+        xml.push_str(" showValue=\"0\"");
+    }
+    if options.reverse {
+        xml.push_str(" reverse=\"1\"");
+    }
+    if !options.icons.is_empty() {
+        xml.push_str(" custom=\"1\"");
+    }
+    xml.push('>');
+    xml.push_str(&x14_thresholds(thresholds));
+    for (icon_set, index) in &options.icons {
+        xml.push_str(&format!(
+            "<x14:cfIcon iconSet=\"{}\" iconId=\"{}\"/>",
+            icon_set, index
+        ));
+    }
+    xml.push_str("</x14:iconSet>");
+    xml
+}
+
+fn x14_thresholds(thresholds: &[(String, String)]) -> String {
+    thresholds
+        .iter()
+        .map(|(threshold_type, value)| {
+            if value.is_empty() {
+                format!("<x14:cfvo type=\"{}\"/>", escape(threshold_type))
+            } else {
+                format!(
+                    "<x14:cfvo type=\"{}\"><xm:f>{}</xm:f></x14:cfvo>",
+                    escape(threshold_type),
+                    escape(value)
+                )
+            }
+        })
+        .collect()
+}
+
+/// An x14 rule being read.
+#[derive(Default)]
+struct ReadRule {
+    rule_type: String,
+    id: String,
+    priority: Option<i32>,
+    thresholds: Vec<(String, String)>,
+    data_bar: DataBarOptions,
+    icon_set: Option<IconSetOptions>,
+}
+
+/// Reads the data bar and icon set options of a worksheet, keyed by rule priority,
+/// and the icon sets only its x14 extension holds.
+pub fn read_worksheet(
+    xml: &str,
+) -> Result<(BTreeMap<i32, RuleExtension>, Vec<ExtensionIconSet>), String> {
+    let mut extensions = BTreeMap::new();
+    let mut icon_sets = Vec::new();
+    if !xml.contains("<cfRule") && !xml.contains("<x14:cfRule") {
+        return Ok((extensions, icon_sets));
+    }
+
+    let mut reader = Reader::from_str(xml);
+    // Priorities of the rules by the id linking them to their x14 rule
+    let mut linked: HashMap<String, i32> = HashMap::new();
+    let mut priority = None;
+    let mut rules: Vec<ReadRule> = Vec::new();
+    let mut rule: Option<ReadRule> = None;
+    // x14 data bar flags that decide whether a color applies
+    let (mut border, mut negative_fill_same, mut negative_border_same) = (false, false, true);
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let (e, is_start) = match &event {
+            Event::Start(e) => (e, true),
+            Event::Empty(e) => (e, false),
+            Event::End(e) if e.name().as_ref() == b"x14:cfRule" => {
+                if let Some(rule) = rule.take() {
+                    rules.push(rule);
+                }
+                continue;
+            }
+            Event::End(e) if e.name().as_ref() == b"x14:conditionalFormatting" => {
+                rules.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match e.name().as_ref() {
+            b"cfRule" => priority = attribute(e, b"priority").and_then(|p| p.parse().ok()),
+            b"iconSet" => {
+                if let Some(priority) = priority {
+                    let options = icon_set_options(e);
+                    extensions.insert(priority, RuleExtension::IconSet(options));
+                }
+            }
+            b"x14:id" if is_start => {
+                let id = reader
+                    .read_text(QName(b"x14:id"))
+                    .map_err(|e| e.to_string())?;
+                if let Some(priority) = priority {
+                    linked.insert(id.trim().to_string(), priority);
+                }
+            }
+            b"x14:cfRule" => {
+                rule = Some(ReadRule {
+                    rule_type: attribute(e, b"type").unwrap_or_default(),
+                    id: attribute(e, b"id").unwrap_or_default(),
+                    priority: attribute(e, b"priority").and_then(|p| p.parse().ok()),
+                    ..ReadRule::default()
+                });
+            }
+            b"x14:dataBar" => {
+                border = attribute(e, b"border").is_some_and(|v| xml_bool(&v));
+                negative_fill_same =
+                    attribute(e, b"negativeBarColorSameAsPositive").is_some_and(|v| xml_bool(&v));
+                negative_border_same = attribute(e, b"negativeBarBorderColorSameAsPositive")
+                    .is_none_or(|v| xml_bool(&v));
+                if let Some(rule) = rule.as_mut() {
+                    let data_bar = &mut rule.data_bar;
+                    data_bar.gradient = attribute(e, b"gradient").is_none_or(|v| xml_bool(&v));
+                    if let Some(direction) = attribute(e, b"direction") {
+                        data_bar.direction = direction;
+                    }
+                    if let Some(position) = attribute(e, b"axisPosition") {
+                        data_bar.axis_position = position;
+                    }
+                }
+            }
+            b"x14:borderColor"
+            | b"x14:negativeFillColor"
+            | b"x14:negativeBorderColor"
+            | b"x14:axisColor" => {
+                let data_bar = match rule.as_mut() {
+                    Some(rule) => &mut rule.data_bar,
+                    None => continue,
+                };
+                let color = attribute(e, b"rgb");
+                match e.name().as_ref() {
+                    b"x14:borderColor" if border => data_bar.border_color = color,
+                    b"x14:negativeFillColor" if !negative_fill_same => {
+                        data_bar.negative_fill_color = color
+                    }
+                    b"x14:negativeBorderColor" if !negative_border_same => {
+                        data_bar.negative_border_color = color
+                    }
+                    b"x14:axisColor" => data_bar.axis_color = color,
+                    _ => (),
+                }
+            }
+            b"x14:iconSet" => {
+                if let Some(rule) = rule.as_mut() {
+                    rule.icon_set = Some(icon_set_options(e));
+                }
+            }
+            b"x14:cfIcon" => {
+                let icon = (
+                    attribute(e, b"iconSet").unwrap_or_default(),
+                    attribute(e, b"iconId")
+                        .and_then(|id| id.parse().ok())
+                        .unwrap_or(0),
+                );
+                if let Some(icon_set) = rule.as_mut().and_then(|rule| rule.icon_set.as_mut()) {
+                    icon_set.icons.push(icon);
+                }
+            }
+            b"x14:cfvo" => {
+                if let Some(rule) = rule.as_mut() {
+                    let threshold_type = attribute(e, b"type").unwrap_or_default();
+                    rule.thresholds.push((threshold_type, String::new()));
+                }
+            }
+            b"xm:f" if is_start => {
+                let formula = reader
+                    .read_text(QName(b"xm:f"))
+                    .map_err(|e| e.to_string())?;
+                let formula = unescape(&formula).map_err(|e| e.to_string())?;
+                if let Some(threshold) = rule.as_mut().and_then(|rule| rule.thresholds.last_mut()) {
+                    threshold.1 = formula.to_string();
+                }
+            }
+            b"xm:sqref" if is_start => {
+                let sqref = reader
+                    .read_text(QName(b"xm:sqref"))
+                    .map_err(|e| e.to_string())?;
+                for read in rules.drain(..) {
+                    let linked_priority = linked.get(&read.id).copied();
+                    match (read.rule_type.as_str(), linked_priority, read.icon_set) {
+                        ("dataBar", Some(priority), _) => {
+                            extensions.insert(priority, RuleExtension::DataBar(read.data_bar));
+                        }
+                        ("iconSet", Some(priority), Some(options)) => {
+                            extensions.insert(priority, RuleExtension::IconSet(options));
+                        }
+                        ("iconSet", None, Some(options)) => icon_sets.push(ExtensionIconSet {
+                            sqref: sqref.trim().to_string(),
+                            priority: read.priority,
+                            thresholds: read.thresholds,
+                            options,
+                        }),
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok((extensions, icon_sets))
+}
+
+/// Reads the attributes `<iconSet>` and `<x14:iconSet>` share.
+fn icon_set_options(element: &BytesStart) -> IconSetOptions {
+    IconSetOptions {
+        icon_set: attribute(element, b"iconSet").unwrap_or_else(|| "3TrafficLights1".to_string()),
+        show_value: attribute(element, b"showValue").is_none_or(|v| xml_bool(&v)),
+        reverse: attribute(element, b"reverse").is_some_and(|v| xml_bool(&v)),
+        icons: Vec::new(),
+    }
+}
+
+/// Writes an event to the buffered `<conditionalFormatting>`, if there is one.
+fn write_current(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    formatting: &mut Option<BufferedFormatting>,
+    event: Event,
+) -> Result<(), String> {
+    match formatting {
+        Some(buffered) => write_event(&mut buffered.writer, event),
+        None => write_event(writer, event),
+    }
+}
+
+fn write_current_raw(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    formatting: &mut Option<BufferedFormatting>,
+    xml: &[u8],
+) -> Result<(), String> {
+    match formatting {
+        Some(buffered) => write_raw(&mut buffered.writer, xml),
+        None => write_raw(writer, xml),
+    }
+}
+
+fn write_event(writer: &mut Writer<Cursor<Vec<u8>>>, event: Event) -> Result<(), String> {
+    writer.write_event(event).map_err(|e| e.to_string())
+}
+
+fn write_raw(writer: &mut Writer<Cursor<Vec<u8>>>, xml: &[u8]) -> Result<(), String> {
+    writer.get_mut().write_all(xml).map_err(|e| e.to_string())
+}
+
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == key)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+fn xml_bool(value: &str) -> bool {
+    value == "1" || value == "true"
+}
@@ -0,0 +1,709 @@
+use crate::helpers::named_style_helper::{CUSTOM_NUMBER_FORMAT_ID, FIRST_CUSTOM_NUMBER_FORMAT_ID};
+use crate::helpers::range_helper;
+use crate::helpers::style_helpers;
+use crate::helpers::style_map_helper::StylePatch;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use rustler::Term;
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use umya_spreadsheet::{
+    ConditionalFormatValues, ConditionalFormatting, ConditionalFormattingRule, Fill, Font,
+    PatternValues, Style, TimePeriodValues, Worksheet,
+};
+
+/// Builds the differential format (dxf) of a conditional formatting rule.
+pub fn differential_style(patch: &StylePatch) -> Style {
+    // umya-spreadsheet starts new fonts as Calibri 11 and new fills with a pattern, which
+    // a dxf would force on the cells, so both start empty and are dropped when unused
+    let mut style = Style::default();
+    style.set_font(Font::default());
+    style.set_fill(Fill::default());
+    patch.apply(&mut style);
+    if style.get_font() == Some(&Font::default()) {
+        style.remove_font();
+    }
+    if style.get_fill() == Some(&Fill::default()) {
+        style.remove_fill();
+    }
+    mirror_solid_fill(&mut style);
+    style
+}
+
+/// Builds the dxf of a rule that only fills cells, from a color such as "#FFC7CE".
+pub fn fill_style(format_style: &str) -> Style {
+    let mut style = style_helpers::create_fill_style(format_style).unwrap_or_default();
+    mirror_solid_fill(&mut style);
+    style
+}
+
+/// Builds the dxf of a rule from its `format_style` argument, either a color that
+/// fills the cells or a style map as taken by `differential_style`.
+///
+/// An empty color or `nil` gives a rule without formatting.
+pub fn rule_style(format_style: Term) -> Result<Option<Style>, String> {
+    if let Ok(color) = format_style.decode::<String>() {
+        return Ok((!color.trim().is_empty()).then(|| fill_style(&color)));
+    }
+    if format_style.is_map() {
+        let patch = StylePatch::decode_differential(format_style)?;
+        return Ok(Some(differential_style(&patch)));
+    }
+    match format_style.decode::<Option<String>>() {
+        Ok(None) => Ok(None),
+        _ => Err("Format style must be a color or a style map".to_string()),
+    }
+}
+
+/// Returns the top-left cell of a space-separated range, e.g. "B2" for "B2:D9 F1".
+///
+/// Formulas of rules are written for this cell; Excel moves relative references
+/// along for the other cells.
+pub fn top_left_cell(range: &str) -> Result<String, String> {
+    let first = range
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| "Range must not be empty".to_string())?;
+    let (column, row, _, _) = range_helper::parse_range(first)?.bounds();
+    Ok(range_helper::format_bounds((column, row, column, row)))
+}
+
+/// Parses a time period as Excel names it ("last7Days") or in snake case ("last_7_days").
+pub fn parse_time_period(value: &str) -> Result<TimePeriodValues, String> {
+    let key: String = value
+        .chars()
+        .filter(|c| !matches!(c, '_' | ' ' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    match key.as_str() {
+        "today" => Ok(TimePeriodValues::Today),
+        "yesterday" => Ok(TimePeriodValues::Yesterday),
+        "tomorrow" => Ok(TimePeriodValues::Tomorrow),
+        "last7days" => Ok(TimePeriodValues::Last7Days),
+        "thisweek" => Ok(TimePeriodValues::ThisWeek),
+        "lastweek" => Ok(TimePeriodValues::LastWeek),
+        "nextweek" => Ok(TimePeriodValues::NextWeek),
+        "thismonth" => Ok(TimePeriodValues::ThisMonth),
+        "lastmonth" => Ok(TimePeriodValues::LastMonth),
+        "nextmonth" => Ok(TimePeriodValues::NextMonth),
+        _ => Err(format!("Invalid time period '{}'", value)),
+    }
+}
+
+/// Returns the formula Excel writes for a time period rule on `cell`.
+///
+/// Excel evaluates time period rules by their formula, so a rule without one never
+/// applies.
+pub fn time_period_formula(period: &TimePeriodValues, cell: &str) -> String {
+    match period {
+        TimePeriodValues::Today => format!("FLOOR({0},1)=TODAY()", cell),
+        TimePeriodValues::Yesterday => format!("FLOOR({0},1)=TODAY()-1", cell),
+        TimePeriodValues::Tomorrow => format!("FLOOR({0},1)=TODAY()+1", cell),
+        TimePeriodValues::Last7Days => format!(
+            "AND(TODAY()-FLOOR({0},1)<=6,FLOOR({0},1)<=TODAY())",
+            cell
+        ),
+        TimePeriodValues::ThisWeek => format!(
+            "AND(TODAY()-ROUNDDOWN({0},0)<=WEEKDAY(TODAY())-1,ROUNDDOWN({0},0)-TODAY()<=7-WEEKDAY(TODAY()))",
+            cell
+        ),
+        TimePeriodValues::LastWeek => format!(
+            "AND(TODAY()-ROUNDDOWN({0},0)>=(WEEKDAY(TODAY())),TODAY()-ROUNDDOWN({0},0)<(WEEKDAY(TODAY())+7))",
+            cell
+        ),
+        TimePeriodValues::NextWeek => format!(
+            "AND(ROUNDDOWN({0},0)-TODAY()>(7-WEEKDAY(TODAY())),ROUNDDOWN({0},0)-TODAY()<(15-WEEKDAY(TODAY())))",
+            cell
+        ),
+        TimePeriodValues::ThisMonth => format!(
+            "AND(MONTH({0})=MONTH(TODAY()),YEAR({0})=YEAR(TODAY()))",
+            cell
+        ),
+        TimePeriodValues::LastMonth => format!(
+            "AND(MONTH({0})=MONTH(EDATE(TODAY(),0-1)),YEAR({0})=YEAR(EDATE(TODAY(),0-1)))",
+            cell
+        ),
+        TimePeriodValues::NextMonth => format!(
+            "AND(MONTH({0})=MONTH(EDATE(TODAY(),0+1)),YEAR({0})=YEAR(EDATE(TODAY(),0+1)))",
+            cell
+        ),
+    }
+}
+
+/// Returns the formula Excel writes for blank and error rules on `cell`, or nothing
+/// for other rule types.
+pub fn cell_check_formula(rule_type: &ConditionalFormatValues, cell: &str) -> Option<String> {
+    match rule_type {
+        ConditionalFormatValues::ContainsBlanks => Some(format!("LEN(TRIM({}))=0", cell)),
+        ConditionalFormatValues::NotContainsBlanks => Some(format!("LEN(TRIM({}))>0", cell)),
+        ConditionalFormatValues::ContainsErrors => Some(format!("ISERROR({})", cell)),
+        ConditionalFormatValues::NotContainsErrors => Some(format!("NOT(ISERROR({}))", cell)),
+        _ => None,
+    }
+}
+
+/// Gives a solid fill without a background color its color in both.
+///
+/// Excel takes the color of a solid dxf fill from `bgColor`.
+fn mirror_solid_fill(style: &mut Style) {
+    let solid_color = style
+        .get_fill()
+        .and_then(|fill| fill.get_pattern_fill())
+        .filter(|pattern_fill| {
+            *pattern_fill.get_pattern_type() == PatternValues::Solid
+                && pattern_fill.get_background_color().is_none()
+        })
+        .and_then(|pattern_fill| pattern_fill.get_foreground_color().cloned());
+    if let Some(color) = solid_color {
+        style
+            .get_fill_mut()
+            .get_pattern_fill_mut()
+            .set_background_color(color);
+    }
+}
+
+/// Returns the priority after the highest one among the rules of a sheet.
+pub fn next_priority(sheet: &Worksheet) -> i32 {
+    sheet
+        .get_conditional_formatting_collection()
+        .iter()
+        .flat_map(|formatting| formatting.get_conditional_collection())
+        .map(|rule| *rule.get_priority())
+        .max()
+        .unwrap_or(0)
+        .max(0)
+        + 1
+}
+
+/// Adds a rule for `range` with the next priority of the sheet and returns that
+/// priority.
+pub fn add_rule(
+    sheet: &mut Worksheet,
+    range: &str,
+    mut rule: ConditionalFormattingRule,
+) -> Result<i32, String> {
+    let sqref = parse_sqref(range)?;
+    let priority = next_priority(sheet);
+    rule.set_priority(priority);
+    let mut formatting = ConditionalFormatting::default();
+    formatting.get_sequence_of_references_mut().set_sqref(sqref);
+    formatting.add_conditional_collection(rule);
+    sheet.add_conditional_formatting_collection(formatting);
+    Ok(priority)
+}
+
+/// Removes the rules applying to exactly `range`.
+pub fn remove_range(sheet: &mut Worksheet, range: &str) -> Result<(), String> {
+    let sqref = parse_sqref(range)?;
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    collection.retain(|formatting| {
+        normalize_sqref(&formatting.get_sequence_of_references().get_sqref()) != sqref
+    });
+    sheet.set_conditional_formatting_collection(collection);
+    Ok(())
+}
+
+/// Removes the rule with the given priority.
+pub fn remove_rule(sheet: &mut Worksheet, priority: i32) -> Result<(), String> {
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let (formatting, rule) = find_rule(&collection, priority)?;
+    collection[formatting]
+        .get_conditional_collection_mut()
+        .remove(rule);
+    if collection[formatting]
+        .get_conditional_collection()
+        .is_empty()
+    {
+        collection.remove(formatting);
+    }
+    sheet.set_conditional_formatting_collection(collection);
+    Ok(())
+}
+
+/// Moves the rule with the given priority to `new_priority` and renumbers all rules
+/// of the sheet from 1, keeping the order of the others.
+///
+/// Returns the new priority of every rule by its old one.
+pub fn set_priority(
+    sheet: &mut Worksheet,
+    priority: i32,
+    new_priority: i32,
+) -> Result<HashMap<i32, i32>, String> {
+    if new_priority < 1 {
+        return Err("Priority must be at least 1".to_string());
+    }
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let target = find_rule(&collection, priority)?;
+
+    let mut order: Vec<(usize, usize)> = collection
+        .iter()
+        .enumerate()
+        .flat_map(|(formatting_index, formatting)| {
+            (0..formatting.get_conditional_collection().len())
+                .map(move |rule_index| (formatting_index, rule_index))
+        })
+        .filter(|position| *position != target)
+        .collect();
+    let priority_of = |(formatting, rule): &(usize, usize)| {
+        *collection[*formatting].get_conditional_collection()[*rule].get_priority()
+    };
+    order.sort_by_key(priority_of);
+    if order
+        .windows(2)
+        .any(|pair| priority_of(&pair[0]) == priority_of(&pair[1]))
+    {
+        return Err("Several conditional formatting rules share a priority".to_string());
+    }
+    let index = (new_priority as usize - 1).min(order.len());
+    order.insert(index, target);
+
+    let mut renumbered = HashMap::new();
+    for (number, (formatting, rule)) in order.into_iter().enumerate() {
+        let rule = &mut collection[formatting].get_conditional_collection_mut()[rule];
+        renumbered.insert(*rule.get_priority(), number as i32 + 1);
+        rule.set_priority(number as i32 + 1);
+    }
+    sheet.set_conditional_formatting_collection(collection);
+    Ok(renumbered)
+}
+
+/// Sets whether Excel skips the rules after the one with the given priority when it
+/// applies.
+pub fn set_stop_if_true(sheet: &mut Worksheet, priority: i32, stop: bool) -> Result<(), String> {
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let (formatting, rule) = find_rule(&collection, priority)?;
+    collection[formatting].get_conditional_collection_mut()[rule].set_stop_if_true(stop);
+    sheet.set_conditional_formatting_collection(collection);
+    Ok(())
+}
+
+/// Applies the rule with the given priority to `range` instead of its current range.
+///
+/// A rule sharing its range with other rules moves to a range of its own.
+pub fn set_range(sheet: &mut Worksheet, priority: i32, range: &str) -> Result<(), String> {
+    let sqref = parse_sqref(range)?;
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let (formatting, rule) = find_rule(&collection, priority)?;
+
+    if collection[formatting].get_conditional_collection().len() == 1 {
+        range_helper::replace_sqref(
+            collection[formatting].get_sequence_of_references_mut(),
+            &sqref,
+        );
+    } else {
+        let moved = collection[formatting]
+            .get_conditional_collection_mut()
+            .remove(rule);
+        let mut own = ConditionalFormatting::default();
+        own.get_sequence_of_references_mut().set_sqref(sqref);
+        own.add_conditional_collection(moved);
+        collection.insert(formatting + 1, own);
+    }
+    sheet.set_conditional_formatting_collection(collection);
+    Ok(())
+}
+
+/// Checks a space-separated range and returns it as a sqref.
+fn parse_sqref(range: &str) -> Result<String, String> {
+    let references: Vec<&str> = range.split_whitespace().collect();
+    if references.is_empty() {
+        return Err("Range must not be empty".to_string());
+    }
+    for reference in &references {
+        range_helper::parse_range(reference)?;
+    }
+    Ok(normalize_sqref(range))
+}
+
+fn normalize_sqref(sqref: &str) -> String {
+    sqref
+        .split_whitespace()
+        .map(|reference| reference.replace('$', "").to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renumbers the rules of a sheet from 1 when several of them share a priority,
+/// keeping their order, so that a priority identifies a single rule again.
+///
+/// Returns the new priority of the first rule with each old one, or nothing when
+/// the priorities were already unique.
+pub fn normalize_priorities(sheet: &mut Worksheet) -> Option<HashMap<i32, i32>> {
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let mut order: Vec<(i32, usize, usize)> = collection
+        .iter()
+        .enumerate()
+        .flat_map(|(formatting_index, formatting)| {
+            formatting
+                .get_conditional_collection()
+                .iter()
+                .enumerate()
+                .map(move |(rule_index, rule)| (*rule.get_priority(), formatting_index, rule_index))
+        })
+        .collect();
+    // Stable, so rules sharing a priority keep their document order
+    order.sort_by_key(|(priority, _, _)| *priority);
+    if order.windows(2).all(|pair| pair[0].0 != pair[1].0) {
+        return None;
+    }
+
+    let mut renumbered = HashMap::new();
+    for (number, (priority, formatting, rule)) in order.into_iter().enumerate() {
+        renumbered.entry(priority).or_insert(number as i32 + 1);
+        collection[formatting].get_conditional_collection_mut()[rule]
+            .set_priority(number as i32 + 1);
+    }
+    sheet.set_conditional_formatting_collection(collection);
+    Some(renumbered)
+}
+
+/// Finds the rule with the given priority, which identifies rules within a sheet.
+fn find_rule(
+    collection: &[ConditionalFormatting],
+    priority: i32,
+) -> Result<(usize, usize), String> {
+    let mut found = collection
+        .iter()
+        .enumerate()
+        .flat_map(|(formatting_index, formatting)| {
+            formatting
+                .get_conditional_collection()
+                .iter()
+                .enumerate()
+                .filter(|(_, rule)| *rule.get_priority() == priority)
+                .map(move |(rule_index, _)| (formatting_index, rule_index))
+        });
+    let position = found.next().ok_or_else(|| {
+        format!(
+            "Conditional formatting rule with priority {} not found",
+            priority
+        )
+    })?;
+    if found.next().is_some() {
+        return Err(format!(
+            "Several conditional formatting rules have priority {}",
+            priority
+        ));
+    }
+    Ok(position)
+}
+
+/// Returns the dxf number format of every rule of a sheet, in the order
+/// umya-spreadsheet writes the rules, or nothing when no rule has one.
+pub fn rule_number_formats(sheet: &Worksheet) -> Option<Vec<Option<String>>> {
+    let formats: Vec<Option<String>> = sheet
+        .get_conditional_formatting_collection()
+        .iter()
+        .flat_map(|formatting| formatting.get_conditional_collection())
+        .map(|rule| {
+            rule.get_style()
+                .and_then(|style| style.get_numbering_format())
+                .map(|format| format.get_format_code().to_string())
+        })
+        .collect();
+    formats.iter().any(Option::is_some).then_some(formats)
+}
+
+/// Sets the dxf number formats read from a file on the rules of a sheet.
+///
+/// umya-spreadsheet reads the font, fill and border of a dxf but not its number format.
+pub fn restore_number_formats(sheet: &mut Worksheet, formats: &[Option<String>]) {
+    // umya-spreadsheet has no mutable access to the formatting of a sheet
+    let mut collection = sheet.get_conditional_formatting_collection().to_vec();
+    let rules = collection
+        .iter_mut()
+        .flat_map(|formatting| formatting.get_conditional_collection_mut());
+    for (rule, format) in rules.zip(formats) {
+        if let Some(format_code) = format {
+            let mut style = rule.get_style().cloned().unwrap_or_default();
+            style
+                .get_number_format_mut()
+                .set_format_code(format_code.as_str());
+            rule.set_style(style);
+        }
+    }
+    sheet.set_conditional_formatting_collection(collection);
+}
+
+/// Lists the number format codes of the `<dxf>` elements of styles.xml, by dxf index.
+pub fn read_dxf_number_formats(styles_xml: &str) -> Result<Vec<Option<String>>, String> {
+    let mut formats = Vec::new();
+    let mut reader = Reader::from_str(styles_xml);
+    let mut in_dxfs = false;
+
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) if e.name().as_ref() == b"dxfs" => in_dxfs = true,
+            Event::End(e) if e.name().as_ref() == b"dxfs" => break,
+            Event::Start(e) | Event::Empty(e) if in_dxfs && e.name().as_ref() == b"dxf" => {
+                formats.push(None)
+            }
+            Event::Start(e) | Event::Empty(e) if in_dxfs && e.name().as_ref() == b"numFmt" => {
+                if let Some(last) = formats.last_mut() {
+                    *last = attribute(&e, b"formatCode");
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(formats)
+}
+
+/// Returns the dxf number format of every `<cfRule>` of a worksheet, in document
+/// order, or nothing when no rule has one.
+pub fn read_rule_number_formats(
+    worksheet_xml: &str,
+    dxf_number_formats: &[Option<String>],
+) -> Result<Option<Vec<Option<String>>>, String> {
+    if dxf_number_formats.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+
+    let mut formats = Vec::new();
+    let mut reader = Reader::from_str(worksheet_xml);
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"cfRule" => formats.push(
+                attribute(&e, b"dxfId")
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| dxf_number_formats.get(id).cloned().flatten()),
+            ),
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(formats.iter().any(Option::is_some).then_some(formats))
+}
+
+/// Returns true when several `<cfRule>` elements of a worksheet share a priority.
+pub fn has_duplicate_priorities(worksheet_xml: &str) -> Result<bool, String> {
+    let mut priorities = HashSet::new();
+    let mut reader = Reader::from_str(worksheet_xml);
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"cfRule" => {
+                let priority =
+                    attribute(&e, b"priority").and_then(|p| p.trim().parse::<i32>().ok());
+                if let Some(priority) = priority {
+                    if !priorities.insert(priority) {
+                        return Ok(true);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(false)
+}
+
+/// Adds number formats to the dxfs umya-spreadsheet wrote for conditional formatting.
+///
+/// Each rule with a number format gets a copy of its dxf that includes it, so rules
+/// sharing a dxf keep their own formats. umya-spreadsheet keeps the dxfs of the file
+/// it read and points rules to the first equal one, so copies written earlier come
+/// back as unused duplicates; copies take their place to keep the list from growing.
+pub struct DxfNumberFormats {
+    /// Raw `<dxf>` elements of styles.xml
+    dxfs: Vec<String>,
+    /// Copies already added, keyed by original dxf index and format code
+    copies: HashMap<(usize, String), usize>,
+    /// Ids of the number formats in styles.xml, keyed by format code
+    format_ids: HashMap<String, u32>,
+    next_format_id: u32,
+    changed: bool,
+}
+
+impl DxfNumberFormats {
+    pub fn read(styles_xml: &str) -> Result<Self, String> {
+        let mut dxfs = Vec::new();
+        let mut format_ids = HashMap::new();
+        let mut next_format_id = FIRST_CUSTOM_NUMBER_FORMAT_ID;
+        let mut reader = Reader::from_str(styles_xml);
+        let mut in_dxfs = false;
+        let mut dxf_start = 0;
+
+        loop {
+            let start = reader.buffer_position() as usize;
+            match reader.read_event().map_err(|e| e.to_string())? {
+                Event::Start(e) if e.name().as_ref() == b"dxfs" => in_dxfs = true,
+                Event::End(e) if e.name().as_ref() == b"dxfs" => in_dxfs = false,
+                Event::Start(e) if in_dxfs && e.name().as_ref() == b"dxf" => dxf_start = start,
+                Event::End(e) if in_dxfs && e.name().as_ref() == b"dxf" => {
+                    dxfs.push(styles_xml[dxf_start..reader.buffer_position() as usize].to_string());
+                }
+                Event::Empty(e) if in_dxfs && e.name().as_ref() == b"dxf" => {
+                    dxfs.push(styles_xml[start..reader.buffer_position() as usize].to_string());
+                }
+                Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"numFmt" => {
+                    let id = attribute(&e, b"numFmtId").and_then(|id| id.parse::<u32>().ok());
+                    if let (Some(id), Some(code)) = (id, attribute(&e, b"formatCode")) {
+                        next_format_id = next_format_id.max(id + 1);
+                        format_ids.entry(code).or_insert(id);
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+
+        Ok(DxfNumberFormats {
+            dxfs,
+            copies: HashMap::new(),
+            format_ids,
+            next_format_id,
+            changed: false,
+        })
+    }
+
+    /// Points the `<cfRule>` elements of a worksheet to dxfs with their number format.
+    ///
+    /// `formats` holds the number format of every rule in document order.
+    pub fn patch_worksheet(
+        &mut self,
+        worksheet_xml: &str,
+        formats: &[Option<String>],
+    ) -> Result<String, String> {
+        let mut reader = Reader::from_str(worksheet_xml);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let mut rule_index = 0;
+
+        loop {
+            let event = reader.read_event().map_err(|e| e.to_string())?;
+            let patched = match &event {
+                Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"cfRule" => {
+                    let format = formats.get(rule_index).cloned().flatten();
+                    rule_index += 1;
+                    let dxf_id = attribute(e, b"dxfId").and_then(|id| id.parse::<usize>().ok());
+                    match (format, dxf_id) {
+                        (Some(format), Some(dxf_id)) if dxf_id < self.dxfs.len() => {
+                            let copy = self.copy_with_format(dxf_id, &format);
+                            Some(with_dxf_id(e, copy))
+                        }
+                        _ => None,
+                    }
+                }
+                Event::Eof => break,
+                _ => None,
+            };
+            let event = match (patched, event) {
+                (Some(rule), Event::Start(_)) => Event::Start(rule),
+                (Some(rule), _) => Event::Empty(rule),
+                (None, event) => event,
+            };
+            writer.write_event(event).map_err(|e| e.to_string())?;
+        }
+
+        String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())
+    }
+
+    /// Writes the dxfs with number formats to the `<dxfs>` element of styles.xml.
+    pub fn patch_styles_xml(&self, styles_xml: &str) -> Result<String, String> {
+        if !self.changed {
+            return Ok(styles_xml.to_string());
+        }
+
+        let malformed = || "styles.xml has no valid dxfs element".to_string();
+        let start = styles_xml.find("<dxfs").ok_or_else(malformed)?;
+        let rest = &styles_xml[start..];
+        let tag_end = rest.find('>').ok_or_else(malformed)?;
+        let end = if rest[..tag_end].ends_with('/') {
+            start + tag_end + 1
+        } else {
+            start + rest.find("</dxfs>").ok_or_else(malformed)? + "</dxfs>".len()
+        };
+
+        Ok(format!(
+            "{}<dxfs count=\"{}\">{}</dxfs>{}",
+            &styles_xml[..start],
+            self.dxfs.len(),
+            self.dxfs.concat(),
+            &styles_xml[end..]
+        ))
+    }
+
+    /// Returns the index of a copy of dxf `dxf_id` with the number format `format_code`.
+    fn copy_with_format(&mut self, dxf_id: usize, format_code: &str) -> usize {
+        let key = (dxf_id, format_code.to_string());
+        if let Some(copy) = self.copies.get(&key) {
+            return *copy;
+        }
+
+        let format_id = self.format_id(format_code);
+        let number_format = format!(
+            "<numFmt numFmtId=\"{}\" formatCode=\"{}\"/>",
+            format_id,
+            escape(format_code)
+        );
+        let dxf = &self.dxfs[dxf_id];
+        // numFmt follows font and precedes fill and border
+        let copy = if dxf.trim_end().ends_with("/>") {
+            format!("<dxf>{}</dxf>", number_format)
+        } else if let Some(font_end) = dxf.find("</font>") {
+            let at = font_end + "</font>".len();
+            format!("{}{}{}", &dxf[..at], number_format, &dxf[at..])
+        } else {
+            let at = dxf.find('>').map(|end| end + 1).unwrap_or(0);
+            format!("{}{}{}", &dxf[..at], number_format, &dxf[at..])
+        };
+
+        let duplicate = (dxf_id + 1..self.dxfs.len()).find(|index| self.dxfs[*index] == *dxf);
+        let index = match duplicate {
+            Some(index) => {
+                self.dxfs[index] = copy;
+                index
+            }
+            None => {
+                self.dxfs.push(copy);
+                self.dxfs.len() - 1
+            }
+        };
+        self.changed = true;
+        self.copies.insert(key, index);
+        index
+    }
+
+    /// Returns the id of a number format: the built-in id, the id styles.xml already
+    /// uses for the code, or a new custom id.
+    fn format_id(&mut self, format_code: &str) -> u32 {
+        let mut number_format = umya_spreadsheet::NumberingFormat::default();
+        number_format.set_format_code(format_code);
+        let id = *number_format.get_number_format_id();
+        if id != CUSTOM_NUMBER_FORMAT_ID && id < FIRST_CUSTOM_NUMBER_FORMAT_ID {
+            return id;
+        }
+        if let Some(id) = self.format_ids.get(format_code) {
+            return *id;
+        }
+        let id = self.next_format_id;
+        self.next_format_id += 1;
+        self.format_ids.insert(format_code.to_string(), id);
+        id
+    }
+}
+
+fn with_dxf_id(element: &BytesStart, dxf_id: usize) -> BytesStart<'static> {
+    let mut patched = BytesStart::new("cfRule");
+    for attribute in element.attributes().flatten() {
+        if attribute.key.as_ref() == b"dxfId" {
+            patched.push_attribute(("dxfId", dxf_id.to_string().as_str()));
+        } else {
+            patched.push_attribute(attribute);
+        }
+    }
+    patched.into_owned()
+}
+
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == key)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
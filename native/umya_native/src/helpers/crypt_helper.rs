@@ -0,0 +1,1050 @@
+use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use hmac::{Hmac, Mac};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fmt;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+/// First bytes of an OLE compound document, the container of encrypted packages.
+const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+/// First bytes of a zip archive, the container of plain xlsx packages.
+const ZIP_SIGNATURE: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+
+/// Version 4.4 (agile encryption) and the reserved flags that start `EncryptionInfo`.
+const AGILE_ENCRYPTION_INFO_PREFIX: [u8; 8] = [0x04, 0x00, 0x04, 0x00, 0x40, 0x00, 0x00, 0x00];
+const ENCRYPTION_NS: &str = "http://schemas.microsoft.com/office/2006/encryption";
+const PASSWORD_NS: &str = "http://schemas.microsoft.com/office/2006/keyEncryptor/password";
+const CERTIFICATE_NS: &str = "http://schemas.microsoft.com/office/2006/keyEncryptor/certificate";
+
+/// Size of the random salts written by `encrypt_package`.
+const SALT_SIZE: usize = 16;
+/// AES block size in bytes.
+const BLOCK_SIZE: usize = 16;
+/// Spin count Excel uses when encrypting.
+pub const DEFAULT_SPIN_COUNT: u32 = 100_000;
+/// Largest spin count ECMA-376 allows.
+pub const MAX_SPIN_COUNT: u32 = 10_000_000;
+
+/// The encrypted package is processed in segments of this size.
+const SEGMENT_SIZE: usize = 4096;
+/// The encrypted package starts with the plain package size as a u64.
+const PACKAGE_SIZE_PREFIX: usize = 8;
+
+// Block keys of ECMA-376 agile encryption (MS-OFFCRYPTO 2.3.4.11 to 2.3.4.14)
+const BLOCK_KEY_VERIFIER_HASH_INPUT: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const BLOCK_KEY_VERIFIER_HASH_VALUE: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const BLOCK_KEY_ENCRYPTED_KEY: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+const BLOCK_KEY_HMAC_KEY: [u8; 8] = [0x5f, 0xb2, 0xad, 0x01, 0x0c, 0xb9, 0xe1, 0xf6];
+const BLOCK_KEY_HMAC_VALUE: [u8; 8] = [0xa0, 0x67, 0x7f, 0x02, 0xb2, 0x2c, 0x84, 0x33];
+
+/// Container format of a spreadsheet file, told apart by its signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Container {
+    /// A plain xlsx/xlsm zip package
+    Zip,
+    /// An OLE compound document, which holds encrypted packages (and legacy .xls files)
+    Ole,
+    /// Anything else
+    Unknown,
+}
+
+/// Reasons an encrypted package cannot be opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The data is a plain zip package
+    NotEncrypted,
+    /// The password does not match the password verifier
+    WrongPassword,
+    /// The package uses an encryption scheme or cipher that is not supported
+    UnsupportedCipher(String),
+    /// The container or the encryption data is damaged
+    Corrupted(String),
+}
+
+impl DecryptError {
+    /// Error reason returned to Elixir.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            DecryptError::NotEncrypted => "not_encrypted",
+            DecryptError::WrongPassword => "wrong_password",
+            DecryptError::UnsupportedCipher(_) => "unsupported_cipher",
+            DecryptError::Corrupted(_) => "corrupted_file",
+        }
+    }
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecryptError::NotEncrypted => write!(f, "The file is not encrypted"),
+            DecryptError::WrongPassword => write!(f, "The password is incorrect"),
+            DecryptError::UnsupportedCipher(detail) => {
+                write!(f, "Unsupported encryption: {}", detail)
+            }
+            DecryptError::Corrupted(detail) => write!(f, "Corrupted encrypted file: {}", detail),
+        }
+    }
+}
+
+/// Parameters of the encryption a package was opened with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionSummary {
+    pub cipher_algorithm: String,
+    pub key_bits: usize,
+    pub hash_algorithm: String,
+    pub spin_count: u32,
+}
+
+impl EncryptionSummary {
+    /// Short algorithm name such as "AES256".
+    pub fn algorithm_name(&self) -> String {
+        format!("{}{}", self.cipher_algorithm, self.key_bits)
+    }
+}
+
+/// Hash algorithms allowed by agile encryption.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Parses the `hashAlgorithm` attribute value.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_uppercase().replace('-', "").as_str() {
+            "SHA1" => Some(HashAlgorithm::Sha1),
+            "SHA256" => Some(HashAlgorithm::Sha256),
+            "SHA384" => Some(HashAlgorithm::Sha384),
+            "SHA512" => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Name used in the `hashAlgorithm` attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "SHA1",
+            HashAlgorithm::Sha256 => "SHA256",
+            HashAlgorithm::Sha384 => "SHA384",
+            HashAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    /// Size of a hash in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha384 => 48,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+
+    /// Hashes the concatenation of `parts`.
+    pub fn digest(&self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut digest = D::new();
+            for part in parts {
+                digest.update(part);
+            }
+            digest.finalize().to_vec()
+        }
+
+        match self {
+            HashAlgorithm::Sha1 => run::<Sha1>(parts),
+            HashAlgorithm::Sha256 => run::<Sha256>(parts),
+            HashAlgorithm::Sha384 => run::<Sha384>(parts),
+            HashAlgorithm::Sha512 => run::<Sha512>(parts),
+        }
+    }
+
+    /// Computes the HMAC of `data` with `key`.
+    pub fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        fn run<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key)
+                .expect("HMAC accepts keys of any length");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+
+        match self {
+            HashAlgorithm::Sha1 => run::<Hmac<Sha1>>(key, data),
+            HashAlgorithm::Sha256 => run::<Hmac<Sha256>>(key, data),
+            HashAlgorithm::Sha384 => run::<Hmac<Sha384>>(key, data),
+            HashAlgorithm::Sha512 => run::<Hmac<Sha512>>(key, data),
+        }
+    }
+}
+
+/// Parameters of the agile encryption written by `encrypt_package`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionOptions {
+    /// AES key size: 128, 192 or 256
+    pub key_bits: usize,
+    pub hash_algorithm: HashAlgorithm,
+    pub spin_count: u32,
+    /// Salt of the password key derivation; a random salt is used when `None`
+    pub salt: Option<Vec<u8>>,
+}
+
+impl Default for EncryptionOptions {
+    /// AES-256 with SHA-512 and 100000 spins, as written by Excel.
+    fn default() -> Self {
+        EncryptionOptions {
+            key_bits: 256,
+            hash_algorithm: HashAlgorithm::Sha512,
+            spin_count: DEFAULT_SPIN_COUNT,
+            salt: None,
+        }
+    }
+}
+
+impl EncryptionOptions {
+    /// Builds options from the values passed in from Elixir.
+    ///
+    /// # Arguments
+    /// * `algorithm` - "AES128", "AES192", "AES256" or "default" (AES-256)
+    /// * `hash_algorithm` - "SHA256", "SHA384", "SHA512" or `None` for SHA-512
+    /// * `salt_value` - Base64 encoded 16-byte salt, or `None` for a random salt
+    /// * `spin_count` - Key derivation iterations, or `None` for 100000
+    pub fn parse(
+        algorithm: &str,
+        hash_algorithm: Option<&str>,
+        salt_value: Option<&str>,
+        spin_count: Option<u32>,
+    ) -> Result<Self, String> {
+        let key_bits = match algorithm.to_uppercase().replace(['-', '_'], "").as_str() {
+            "" | "DEFAULT" | "AES256" => 256,
+            "AES192" => 192,
+            "AES128" => 128,
+            _ => {
+                return Err(format!(
+                    "Unsupported encryption algorithm '{}'; use AES128, AES192 or AES256",
+                    algorithm
+                ))
+            }
+        };
+        let mut options = EncryptionOptions {
+            key_bits,
+            ..EncryptionOptions::default()
+        };
+
+        if let Some(name) = hash_algorithm {
+            options.hash_algorithm = match HashAlgorithm::parse(name) {
+                Some(HashAlgorithm::Sha1) | None => {
+                    return Err(format!(
+                        "Unsupported hash algorithm '{}'; use SHA256, SHA384 or SHA512",
+                        name
+                    ))
+                }
+                Some(hash) => hash,
+            };
+        }
+
+        if let Some(salt) = salt_value {
+            match STANDARD.decode(salt.trim()) {
+                Ok(bytes) if bytes.len() == SALT_SIZE => options.salt = Some(bytes),
+                _ => return Err("Salt value must be 16 bytes encoded as base64".to_string()),
+            }
+        }
+
+        if let Some(count) = spin_count {
+            if count > MAX_SPIN_COUNT {
+                return Err(format!("Spin count must not exceed {}", MAX_SPIN_COUNT));
+            }
+            options.spin_count = count;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Detects the container format from the first bytes of a file.
+pub fn detect_container(data: &[u8]) -> Container {
+    if data.starts_with(&OLE_SIGNATURE) {
+        Container::Ole
+    } else if data.starts_with(&ZIP_SIGNATURE) {
+        Container::Zip
+    } else {
+        Container::Unknown
+    }
+}
+
+/// Detects the container format of the file at `path`.
+pub fn detect_file_container(path: &Path) -> std::io::Result<Container> {
+    let mut header = Vec::with_capacity(OLE_SIGNATURE.len());
+    std::fs::File::open(path)?
+        .take(OLE_SIGNATURE.len() as u64)
+        .read_to_end(&mut header)?;
+    Ok(detect_container(&header))
+}
+
+/// Returns true when `data` is an OLE compound document holding an encrypted package.
+pub fn is_encrypted_package(data: &[u8]) -> bool {
+    if detect_container(data) != Container::Ole {
+        return false;
+    }
+    cfb::CompoundFile::open(Cursor::new(data))
+        .map(|compound| compound.is_stream("/EncryptionInfo"))
+        .unwrap_or(false)
+}
+
+/// Decrypts an ECMA-376 agile encrypted package.
+///
+/// # Arguments
+/// * `data` - Contents of the encrypted file (an OLE compound document)
+/// * `password` - The password the file was encrypted with
+///
+/// # Returns
+/// The plain xlsx package and the parameters it was encrypted with.
+pub fn decrypt_package(
+    data: &[u8],
+    password: &str,
+) -> Result<(Vec<u8>, EncryptionSummary), DecryptError> {
+    match detect_container(data) {
+        Container::Ole => (),
+        Container::Zip => return Err(DecryptError::NotEncrypted),
+        Container::Unknown => {
+            return Err(DecryptError::Corrupted(
+                "not an OLE compound document".to_string(),
+            ))
+        }
+    }
+
+    let mut compound = cfb::CompoundFile::open(Cursor::new(data))
+        .map_err(|e| DecryptError::Corrupted(e.to_string()))?;
+    let encryption_info = read_stream(&mut compound, "/EncryptionInfo")?;
+    let encrypted_package = read_stream(&mut compound, "/EncryptedPackage")?;
+
+    let info = parse_encryption_info(&encryption_info)?;
+    let key = info.unlock(password)?;
+    info.verify_integrity(&key, &encrypted_package)?;
+    let package = decrypt_segments(&info.key_data, &key, &encrypted_package)?;
+
+    Ok((package, info.summary()))
+}
+
+/// Encrypts an xlsx package with ECMA-376 agile encryption.
+///
+/// # Arguments
+/// * `data` - The plain xlsx package
+/// * `password` - The password to encrypt with
+/// * `options` - Key size, hash algorithm, spin count and salt
+///
+/// # Returns
+/// An OLE compound document holding the `EncryptionInfo` and `EncryptedPackage` streams.
+pub fn encrypt_package(
+    data: &[u8],
+    password: &str,
+    options: &EncryptionOptions,
+) -> Result<Vec<u8>, String> {
+    let hash_algorithm = options.hash_algorithm;
+    let key_bytes = options.key_bits / 8;
+
+    let key_data = CipherParams {
+        salt: random_bytes(SALT_SIZE)?,
+        block_size: BLOCK_SIZE,
+        key_bits: options.key_bits,
+        hash_size: hash_algorithm.size(),
+        hash_algorithm,
+        cipher_algorithm: "AES".to_string(),
+    };
+    let package_key = random_bytes(key_bytes)?;
+    let encrypted_package = encrypt_segments(&key_data, &package_key, data)?;
+
+    // Data integrity: an HMAC of the encrypted package, encrypted with the package key
+    let hmac_key = random_bytes(key_data.hash_size)?;
+    let hmac_value = hash_algorithm.hmac(&hmac_key, &encrypted_package);
+    let encrypted_hmac_key = aes_cbc_encrypt(
+        &package_key,
+        &block_iv(&key_data, &BLOCK_KEY_HMAC_KEY),
+        &hmac_key,
+    )?;
+    let encrypted_hmac_value = aes_cbc_encrypt(
+        &package_key,
+        &block_iv(&key_data, &BLOCK_KEY_HMAC_VALUE),
+        &hmac_value,
+    )?;
+
+    // Password key encryptor: verifier and package key, encrypted with password-derived keys
+    let params = CipherParams {
+        salt: match &options.salt {
+            Some(salt) => salt.clone(),
+            None => random_bytes(SALT_SIZE)?,
+        },
+        ..key_data.clone()
+    };
+    let base_hash = password_hash(hash_algorithm, &params.salt, password, options.spin_count);
+    let encrypt_with_block_key = |block_key: &[u8], value: &[u8]| {
+        let key = derive_key(hash_algorithm, &base_hash, block_key, key_bytes);
+        aes_cbc_encrypt(&key, &params.salt, value)
+    };
+
+    let verifier_input = random_bytes(SALT_SIZE)?;
+    let password_encryptor = PasswordKeyEncryptor {
+        spin_count: options.spin_count,
+        encrypted_verifier_hash_input: encrypt_with_block_key(
+            &BLOCK_KEY_VERIFIER_HASH_INPUT,
+            &verifier_input,
+        )?,
+        encrypted_verifier_hash_value: encrypt_with_block_key(
+            &BLOCK_KEY_VERIFIER_HASH_VALUE,
+            &hash_algorithm.digest(&[&verifier_input]),
+        )?,
+        encrypted_key_value: encrypt_with_block_key(&BLOCK_KEY_ENCRYPTED_KEY, &package_key)?,
+        params,
+    };
+
+    let info = AgileEncryptionInfo {
+        key_data,
+        encrypted_hmac_key: Some(encrypted_hmac_key),
+        encrypted_hmac_value: Some(encrypted_hmac_value),
+        password_encryptor,
+    };
+    let mut encryption_info = AGILE_ENCRYPTION_INFO_PREFIX.to_vec();
+    encryption_info.extend(write_encryption_info(&info)?);
+
+    let mut compound =
+        cfb::CompoundFile::create(Cursor::new(Vec::new())).map_err(|e| e.to_string())?;
+    for (name, content) in [
+        ("/EncryptionInfo", &encryption_info),
+        ("/EncryptedPackage", &encrypted_package),
+    ] {
+        compound
+            .create_stream(name)
+            .and_then(|mut stream| stream.write_all(content))
+            .map_err(|e| e.to_string())?;
+    }
+    compound.flush().map_err(|e| e.to_string())?;
+    Ok(compound.into_inner().into_inner())
+}
+
+/// Cipher parameters shared by `<keyData>` and `<p:encryptedKey>`.
+#[derive(Clone)]
+struct CipherParams {
+    salt: Vec<u8>,
+    block_size: usize,
+    key_bits: usize,
+    hash_size: usize,
+    hash_algorithm: HashAlgorithm,
+    cipher_algorithm: String,
+}
+
+/// The `<p:encryptedKey>` of the password key encryptor.
+struct PasswordKeyEncryptor {
+    params: CipherParams,
+    spin_count: u32,
+    encrypted_verifier_hash_input: Vec<u8>,
+    encrypted_verifier_hash_value: Vec<u8>,
+    encrypted_key_value: Vec<u8>,
+}
+
+/// Contents of an agile `EncryptionInfo` stream.
+struct AgileEncryptionInfo {
+    key_data: CipherParams,
+    encrypted_hmac_key: Option<Vec<u8>>,
+    encrypted_hmac_value: Option<Vec<u8>>,
+    password_encryptor: PasswordKeyEncryptor,
+}
+
+impl AgileEncryptionInfo {
+    /// Checks the password and returns the package key.
+    fn unlock(&self, password: &str) -> Result<Vec<u8>, DecryptError> {
+        let encryptor = &self.password_encryptor;
+        let params = &encryptor.params;
+        let key_bytes = params.key_bits / 8;
+        let base_hash = password_hash(
+            params.hash_algorithm,
+            &params.salt,
+            password,
+            encryptor.spin_count,
+        );
+
+        let verifier_input_key = derive_key(
+            params.hash_algorithm,
+            &base_hash,
+            &BLOCK_KEY_VERIFIER_HASH_INPUT,
+            key_bytes,
+        );
+        let verifier_input = aes_cbc_decrypt(
+            &verifier_input_key,
+            &params.salt,
+            &encryptor.encrypted_verifier_hash_input,
+        )?;
+        let verifier_input = &verifier_input[..params.salt.len().min(verifier_input.len())];
+
+        let verifier_value_key = derive_key(
+            params.hash_algorithm,
+            &base_hash,
+            &BLOCK_KEY_VERIFIER_HASH_VALUE,
+            key_bytes,
+        );
+        let verifier_value = aes_cbc_decrypt(
+            &verifier_value_key,
+            &params.salt,
+            &encryptor.encrypted_verifier_hash_value,
+        )?;
+
+        let expected = params.hash_algorithm.digest(&[verifier_input]);
+        if verifier_value.len() < params.hash_size
+            || expected.len() < params.hash_size
+            || verifier_value[..params.hash_size] != expected[..params.hash_size]
+        {
+            return Err(DecryptError::WrongPassword);
+        }
+
+        let key_key = derive_key(
+            params.hash_algorithm,
+            &base_hash,
+            &BLOCK_KEY_ENCRYPTED_KEY,
+            key_bytes,
+        );
+        let mut package_key =
+            aes_cbc_decrypt(&key_key, &params.salt, &encryptor.encrypted_key_value)?;
+        package_key.truncate(self.key_data.key_bits / 8);
+        Ok(package_key)
+    }
+
+    /// Verifies the HMAC of the encrypted package when the file carries one.
+    fn verify_integrity(&self, key: &[u8], encrypted_package: &[u8]) -> Result<(), DecryptError> {
+        let (encrypted_hmac_key, encrypted_hmac_value) =
+            match (&self.encrypted_hmac_key, &self.encrypted_hmac_value) {
+                (Some(hmac_key), Some(hmac_value)) => (hmac_key, hmac_value),
+                _ => return Ok(()),
+            };
+
+        let key_data = &self.key_data;
+        let hash_size = key_data.hash_size;
+        let hmac_key_iv = block_iv(key_data, &BLOCK_KEY_HMAC_KEY);
+        let mut hmac_key = aes_cbc_decrypt(key, &hmac_key_iv, encrypted_hmac_key)?;
+        hmac_key.truncate(hash_size);
+
+        let hmac_value_iv = block_iv(key_data, &BLOCK_KEY_HMAC_VALUE);
+        let hmac_value = aes_cbc_decrypt(key, &hmac_value_iv, encrypted_hmac_value)?;
+
+        let actual = key_data.hash_algorithm.hmac(&hmac_key, encrypted_package);
+        if hmac_value.len() < hash_size || actual[..hash_size] != hmac_value[..hash_size] {
+            return Err(DecryptError::Corrupted(
+                "data integrity check failed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> EncryptionSummary {
+        EncryptionSummary {
+            cipher_algorithm: self.key_data.cipher_algorithm.clone(),
+            key_bits: self.key_data.key_bits,
+            hash_algorithm: self.key_data.hash_algorithm.name().to_string(),
+            spin_count: self.password_encryptor.spin_count,
+        }
+    }
+}
+
+/// Reads a whole stream of the compound document.
+fn read_stream(
+    compound: &mut cfb::CompoundFile<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Vec<u8>, DecryptError> {
+    let mut stream = compound.open_stream(name).map_err(|_| {
+        DecryptError::Corrupted(format!("missing {} stream", name.trim_start_matches('/')))
+    })?;
+    let mut content = Vec::new();
+    stream
+        .read_to_end(&mut content)
+        .map_err(|e| DecryptError::Corrupted(e.to_string()))?;
+    Ok(content)
+}
+
+/// Parses the `EncryptionInfo` stream; only agile encryption (version 4.4) is supported.
+fn parse_encryption_info(data: &[u8]) -> Result<AgileEncryptionInfo, DecryptError> {
+    if data.len() < 8 {
+        return Err(DecryptError::Corrupted(
+            "EncryptionInfo stream is too short".to_string(),
+        ));
+    }
+
+    let major = u16::from_le_bytes([data[0], data[1]]);
+    let minor = u16::from_le_bytes([data[2], data[3]]);
+    match (major, minor) {
+        (4, 4) => (),
+        (2..=4, 2) => {
+            return Err(DecryptError::UnsupportedCipher(
+                "standard encryption (Office 2007)".to_string(),
+            ))
+        }
+        (3 | 4, 3) => {
+            return Err(DecryptError::UnsupportedCipher(
+                "extensible encryption".to_string(),
+            ))
+        }
+        _ => {
+            return Err(DecryptError::UnsupportedCipher(format!(
+                "encryption version {}.{}",
+                major, minor
+            )))
+        }
+    }
+
+    let xml = std::str::from_utf8(&data[8..])
+        .map_err(|_| DecryptError::Corrupted("EncryptionInfo is not UTF-8".to_string()))?;
+    let mut reader = Reader::from_str(xml);
+
+    let mut key_data = None;
+    let mut encrypted_hmac_key = None;
+    let mut encrypted_hmac_value = None;
+    let mut password_encryptor = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"keyData" => key_data = Some(parse_cipher_params(e)?),
+                    b"dataIntegrity" => {
+                        encrypted_hmac_key = attribute_base64(e, b"encryptedHmacKey")?;
+                        encrypted_hmac_value = attribute_base64(e, b"encryptedHmacValue")?;
+                    }
+                    // Certificate key encryptors have no spinCount; only the password one is usable
+                    b"encryptedKey"
+                        if attribute(e, b"spinCount").is_some() && password_encryptor.is_none() =>
+                    {
+                        password_encryptor = Some(parse_password_encryptor(e)?);
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(DecryptError::Corrupted(e.to_string())),
+            _ => (),
+        }
+    }
+
+    let key_data =
+        key_data.ok_or_else(|| DecryptError::Corrupted("missing keyData".to_string()))?;
+    let password_encryptor = password_encryptor
+        .ok_or_else(|| DecryptError::UnsupportedCipher("no password key encryptor".to_string()))?;
+
+    Ok(AgileEncryptionInfo {
+        key_data,
+        encrypted_hmac_key,
+        encrypted_hmac_value,
+        password_encryptor,
+    })
+}
+
+fn parse_password_encryptor(element: &BytesStart) -> Result<PasswordKeyEncryptor, DecryptError> {
+    let required = |name: &[u8]| {
+        attribute_base64(element, name)?.ok_or_else(|| {
+            DecryptError::Corrupted(format!(
+                "missing {} attribute",
+                String::from_utf8_lossy(name)
+            ))
+        })
+    };
+
+    // The spin count comes from the file, so cap it before hashing that many rounds
+    let spin_count = attribute_number(element, b"spinCount")?;
+    if spin_count > MAX_SPIN_COUNT as usize {
+        return Err(DecryptError::UnsupportedCipher(format!(
+            "spin count {} above {}",
+            spin_count, MAX_SPIN_COUNT
+        )));
+    }
+
+    Ok(PasswordKeyEncryptor {
+        params: parse_cipher_params(element)?,
+        spin_count: spin_count as u32,
+        encrypted_verifier_hash_input: required(b"encryptedVerifierHashInput")?,
+        encrypted_verifier_hash_value: required(b"encryptedVerifierHashValue")?,
+        encrypted_key_value: required(b"encryptedKeyValue")?,
+    })
+}
+
+/// Parses and checks the cipher attributes of `<keyData>` or `<p:encryptedKey>`.
+fn parse_cipher_params(element: &BytesStart) -> Result<CipherParams, DecryptError> {
+    let cipher_algorithm = attribute(element, b"cipherAlgorithm").unwrap_or_default();
+    if cipher_algorithm != "AES" {
+        return Err(DecryptError::UnsupportedCipher(format!(
+            "cipher {}",
+            cipher_algorithm
+        )));
+    }
+
+    let cipher_chaining = attribute(element, b"cipherChaining").unwrap_or_default();
+    if cipher_chaining != "ChainingModeCBC" {
+        return Err(DecryptError::UnsupportedCipher(format!(
+            "cipher chaining {}",
+            cipher_chaining
+        )));
+    }
+
+    let hash_name = attribute(element, b"hashAlgorithm").unwrap_or_default();
+    let hash_algorithm = HashAlgorithm::parse(&hash_name)
+        .ok_or_else(|| DecryptError::UnsupportedCipher(format!("hash algorithm {}", hash_name)))?;
+
+    let key_bits = attribute_number(element, b"keyBits")?;
+    if !matches!(key_bits, 128 | 192 | 256) {
+        return Err(DecryptError::UnsupportedCipher(format!(
+            "AES key size {}",
+            key_bits
+        )));
+    }
+
+    let block_size = attribute_number(element, b"blockSize")?;
+    if block_size != 16 {
+        return Err(DecryptError::Corrupted(format!(
+            "invalid AES block size {}",
+            block_size
+        )));
+    }
+
+    // Hashes and HMACs are cut to this size, so it must not exceed the digest
+    let hash_size = attribute_number(element, b"hashSize")?;
+    if hash_size != hash_algorithm.size() {
+        return Err(DecryptError::Corrupted(format!(
+            "hash size {} does not match {}",
+            hash_size,
+            hash_algorithm.name()
+        )));
+    }
+
+    Ok(CipherParams {
+        salt: attribute_base64(element, b"saltValue")?
+            .ok_or_else(|| DecryptError::Corrupted("missing saltValue attribute".to_string()))?,
+        block_size,
+        key_bits,
+        hash_size,
+        hash_algorithm,
+        cipher_algorithm,
+    })
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+fn attribute_number(element: &BytesStart, name: &[u8]) -> Result<usize, DecryptError> {
+    attribute(element, name)
+        .and_then(|value| value.parse::<usize>().ok())
+        .ok_or_else(|| {
+            DecryptError::Corrupted(format!(
+                "missing or invalid {} attribute",
+                String::from_utf8_lossy(name)
+            ))
+        })
+}
+
+fn attribute_base64(element: &BytesStart, name: &[u8]) -> Result<Option<Vec<u8>>, DecryptError> {
+    attribute(element, name)
+        .map(|value| {
+            STANDARD.decode(value.trim()).map_err(|_| {
+                DecryptError::Corrupted(format!(
+                    "invalid base64 in {} attribute",
+                    String::from_utf8_lossy(name)
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// Serializes the agile `EncryptionInfo` XML.
+fn write_encryption_info(info: &AgileEncryptionInfo) -> Result<Vec<u8>, String> {
+    fn cipher_attributes(params: &CipherParams) -> Vec<(&'static str, String)> {
+        vec![
+            ("saltSize", params.salt.len().to_string()),
+            ("blockSize", params.block_size.to_string()),
+            ("keyBits", params.key_bits.to_string()),
+            ("hashSize", params.hash_size.to_string()),
+            ("cipherAlgorithm", params.cipher_algorithm.clone()),
+            ("cipherChaining", "ChainingModeCBC".to_string()),
+            ("hashAlgorithm", params.hash_algorithm.name().to_string()),
+            ("saltValue", STANDARD.encode(&params.salt)),
+        ]
+    }
+
+    fn element(name: &str, attributes: &[(&str, String)]) -> BytesStart<'static> {
+        let mut element = BytesStart::new(name.to_string());
+        for (key, value) in attributes {
+            element.push_attribute((*key, value.as_str()));
+        }
+        element
+    }
+
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    let mut write = |event: Event| writer.write_event(event).map_err(|e| e.to_string());
+
+    write(Event::Decl(BytesDecl::new(
+        "1.0",
+        Some("UTF-8"),
+        Some("yes"),
+    )))?;
+    write(Event::Start(element(
+        "encryption",
+        &[
+            ("xmlns", ENCRYPTION_NS.to_string()),
+            ("xmlns:p", PASSWORD_NS.to_string()),
+            ("xmlns:c", CERTIFICATE_NS.to_string()),
+        ],
+    )))?;
+    write(Event::Empty(element(
+        "keyData",
+        &cipher_attributes(&info.key_data),
+    )))?;
+
+    let mut integrity = Vec::new();
+    if let Some(hmac_key) = &info.encrypted_hmac_key {
+        integrity.push(("encryptedHmacKey", STANDARD.encode(hmac_key)));
+    }
+    if let Some(hmac_value) = &info.encrypted_hmac_value {
+        integrity.push(("encryptedHmacValue", STANDARD.encode(hmac_value)));
+    }
+    write(Event::Empty(element("dataIntegrity", &integrity)))?;
+
+    let encryptor = &info.password_encryptor;
+    let mut key_attributes = vec![("spinCount", encryptor.spin_count.to_string())];
+    key_attributes.extend(cipher_attributes(&encryptor.params));
+    key_attributes.extend([
+        (
+            "encryptedVerifierHashInput",
+            STANDARD.encode(&encryptor.encrypted_verifier_hash_input),
+        ),
+        (
+            "encryptedVerifierHashValue",
+            STANDARD.encode(&encryptor.encrypted_verifier_hash_value),
+        ),
+        (
+            "encryptedKeyValue",
+            STANDARD.encode(&encryptor.encrypted_key_value),
+        ),
+    ]);
+
+    write(Event::Start(element("keyEncryptors", &[])))?;
+    write(Event::Start(element(
+        "keyEncryptor",
+        &[("uri", PASSWORD_NS.to_string())],
+    )))?;
+    write(Event::Empty(element("p:encryptedKey", &key_attributes)))?;
+    write(Event::End(BytesEnd::new("keyEncryptor")))?;
+    write(Event::End(BytesEnd::new("keyEncryptors")))?;
+    write(Event::End(BytesEnd::new("encryption")))?;
+
+    Ok(writer.into_inner().into_inner())
+}
+
+/// Returns `size` random bytes from the operating system.
+fn random_bytes(size: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; size];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Hashes the password with the salt and iterates `spin_count` times (H0 to Hn).
+pub fn password_hash(
+    hash_algorithm: HashAlgorithm,
+    salt: &[u8],
+    password: &str,
+    spin_count: u32,
+) -> Vec<u8> {
+    let password_bytes: Vec<u8> = password
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+
+    let mut hash = hash_algorithm.digest(&[salt, &password_bytes]);
+    for iteration in 0..spin_count {
+        hash = hash_algorithm.digest(&[&iteration.to_le_bytes(), &hash]);
+    }
+    hash
+}
+
+/// Derives a key from the password hash and a block key.
+pub fn derive_key(
+    hash_algorithm: HashAlgorithm,
+    base_hash: &[u8],
+    block_key: &[u8],
+    key_bytes: usize,
+) -> Vec<u8> {
+    fit(hash_algorithm.digest(&[base_hash, block_key]), key_bytes)
+}
+
+/// Builds the IV for a block key (or segment index) from the key data salt.
+fn block_iv(key_data: &CipherParams, block_key: &[u8]) -> Vec<u8> {
+    fit(
+        key_data.hash_algorithm.digest(&[&key_data.salt, block_key]),
+        key_data.block_size,
+    )
+}
+
+/// Truncates a hash or pads it with 0x36 to `size` bytes.
+fn fit(mut value: Vec<u8>, size: usize) -> Vec<u8> {
+    value.resize(size, 0x36);
+    value
+}
+
+/// Decrypts every segment of the `EncryptedPackage` stream.
+fn decrypt_segments(
+    key_data: &CipherParams,
+    key: &[u8],
+    encrypted_package: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    if encrypted_package.len() < PACKAGE_SIZE_PREFIX {
+        return Err(DecryptError::Corrupted(
+            "EncryptedPackage stream is too short".to_string(),
+        ));
+    }
+
+    let mut size_bytes = [0u8; PACKAGE_SIZE_PREFIX];
+    size_bytes.copy_from_slice(&encrypted_package[..PACKAGE_SIZE_PREFIX]);
+    // The size comes from the file; a package can never be larger than its stream
+    let size = u64::from_le_bytes(size_bytes);
+    let available = encrypted_package.len() - PACKAGE_SIZE_PREFIX;
+    if size > available as u64 {
+        return Err(DecryptError::Corrupted(format!(
+            "EncryptedPackage claims {} bytes but holds {}",
+            size, available
+        )));
+    }
+    let size = size as usize;
+
+    let mut package = Vec::with_capacity(available);
+    for (index, segment) in encrypted_package[PACKAGE_SIZE_PREFIX..]
+        .chunks(SEGMENT_SIZE)
+        .enumerate()
+    {
+        let iv = block_iv(key_data, &(index as u32).to_le_bytes());
+        // Some writers leave trailing bytes that do not fill a whole block
+        let usable = segment.len() - segment.len() % key_data.block_size;
+        package.extend(aes_cbc_decrypt(key, &iv, &segment[..usable])?);
+    }
+
+    if package.len() < size {
+        return Err(DecryptError::Corrupted(
+            "EncryptedPackage stream is truncated".to_string(),
+        ));
+    }
+    package.truncate(size);
+    Ok(package)
+}
+
+/// Encrypts every 4096-byte segment of the package and prefixes the package size.
+fn encrypt_segments(
+    key_data: &CipherParams,
+    key: &[u8],
+    package: &[u8],
+) -> Result<Vec<u8>, String> {
+    let mut encrypted = Vec::with_capacity(PACKAGE_SIZE_PREFIX + package.len() + BLOCK_SIZE);
+    encrypted.extend((package.len() as u64).to_le_bytes());
+
+    for (index, segment) in package.chunks(SEGMENT_SIZE).enumerate() {
+        let iv = block_iv(key_data, &(index as u32).to_le_bytes());
+        encrypted.extend(aes_cbc_encrypt(key, &iv, segment)?);
+    }
+    Ok(encrypted)
+}
+
+/// Encrypts AES-CBC data, padding it with zeros to a whole number of blocks.
+fn aes_cbc_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    fn run<C>(key: &[u8], iv: &[u8], buffer: &mut [u8]) -> Result<(), String>
+    where
+        cbc::Encryptor<C>: KeyIvInit + BlockEncryptMut,
+        C: aes::cipher::BlockEncryptMut + aes::cipher::BlockCipher,
+    {
+        let length = buffer.len();
+        cbc::Encryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| "invalid key or IV length".to_string())?
+            .encrypt_padded_mut::<NoPadding>(buffer, length)
+            .map_err(|_| "invalid plain text length".to_string())?;
+        Ok(())
+    }
+
+    let mut buffer = data.to_vec();
+    buffer.resize(data.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, 0);
+    match key.len() {
+        16 => run::<aes::Aes128>(key, iv, &mut buffer)?,
+        24 => run::<aes::Aes192>(key, iv, &mut buffer)?,
+        32 => run::<aes::Aes256>(key, iv, &mut buffer)?,
+        other => return Err(format!("Unsupported AES key size {}", other * 8)),
+    }
+    Ok(buffer)
+}
+
+/// Decrypts AES-CBC data without padding, choosing AES-128/192/256 from the key length.
+fn aes_cbc_decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+    fn run<C>(key: &[u8], iv: &[u8], buffer: &mut [u8]) -> Result<(), DecryptError>
+    where
+        cbc::Decryptor<C>: KeyIvInit + BlockDecryptMut,
+        C: aes::cipher::BlockDecryptMut + aes::cipher::BlockCipher,
+    {
+        cbc::Decryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| DecryptError::Corrupted("invalid key or IV length".to_string()))?
+            .decrypt_padded_mut::<NoPadding>(buffer)
+            .map_err(|_| DecryptError::Corrupted("invalid cipher text length".to_string()))?;
+        Ok(())
+    }
+
+    let mut buffer = data.to_vec();
+    match key.len() {
+        16 => run::<aes::Aes128>(key, iv, &mut buffer)?,
+        24 => run::<aes::Aes192>(key, iv, &mut buffer)?,
+        32 => run::<aes::Aes256>(key, iv, &mut buffer)?,
+        other => {
+            return Err(DecryptError::UnsupportedCipher(format!(
+                "AES key size {}",
+                other * 8
+            )))
+        }
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the `EncryptionInfo` stream of a package encrypted with SHA-1.
+    fn sha1_encryption_info() -> Vec<u8> {
+        let options = EncryptionOptions {
+            hash_algorithm: HashAlgorithm::Sha1,
+            spin_count: 1,
+            ..EncryptionOptions::default()
+        };
+        let data = encrypt_package(b"PK", "secret", &options).unwrap();
+        let mut compound = cfb::CompoundFile::open(Cursor::new(data.as_slice())).unwrap();
+        read_stream(&mut compound, "/EncryptionInfo").unwrap()
+    }
+
+    #[test]
+    fn rejects_a_hash_size_that_does_not_match_the_hash_algorithm() {
+        let info = sha1_encryption_info();
+        let xml = String::from_utf8(info[8..].to_vec()).unwrap();
+        assert_eq!(xml.matches("hashSize=\"20\"").count(), 2);
+        assert!(parse_encryption_info(&info).is_ok());
+
+        // keyData comes first, then the password key encryptor
+        for occurrence in 0..2 {
+            let (position, _) = xml
+                .match_indices("hashSize=\"20\"")
+                .nth(occurrence)
+                .unwrap();
+            let mut crafted = info.clone();
+            crafted[8 + position..8 + position + 13].copy_from_slice(b"hashSize=\"64\"");
+
+            assert!(matches!(
+                parse_encryption_info(&crafted),
+                Err(DecryptError::Corrupted(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::helpers::data_validation_helper;
+use crate::helpers::formula_evaluation_helper::{self, CellIndex, Value};
+use crate::helpers::formula_helper;
+use umya_spreadsheet::helper::date::convert_date;
+use umya_spreadsheet::{
+    DataValidation, DataValidationOperatorValues, DataValidationValues, Spreadsheet,
+};
+
+/// A cell whose value breaks the data validation that applies to it.
+pub struct Violation<'a> {
+    pub validation: &'a DataValidation,
+    pub column: u32,
+    pub row: u32,
+}
+
+/// Checks the values already in a sheet against its data validations, as Excel's
+/// "Circle Invalid Data" does, and returns the cells that break them in row order.
+///
+/// Empty cells are not checked. Rules whose formulas cannot be evaluated, such as
+/// custom formulas calling unsupported functions, are skipped rather than reported.
+pub fn violations<'a>(
+    spreadsheet: &'a Spreadsheet,
+    sheet_name: &str,
+) -> Option<Vec<Violation<'a>>> {
+    let sheet = spreadsheet.get_sheet_by_name(sheet_name)?;
+    let Some(validations) = sheet.get_data_validations() else {
+        return Some(Vec::new());
+    };
+
+    let mut cells: Vec<(u32, u32)> = sheet
+        .get_cell_collection()
+        .iter()
+        .map(|cell| {
+            let coordinate = cell.get_coordinate();
+            (*coordinate.get_row_num(), *coordinate.get_col_num())
+        })
+        .collect();
+    cells.sort();
+
+    let index = CellIndex::default();
+    let mut violations = Vec::new();
+    for (row, column) in cells {
+        let value = formula_evaluation_helper::cell_value(sheet, column, row);
+        if value == Value::Blank {
+            continue;
+        }
+        for validation in validations.get_data_validation_list() {
+            let areas = data_validation_helper::validation_areas(validation);
+            let Some(anchor) = areas.first() else {
+                continue;
+            };
+            let inside = areas
+                .iter()
+                .any(|&(start_column, start_row, end_column, end_row)| {
+                    (start_column..=end_column).contains(&column)
+                        && (start_row..=end_row).contains(&row)
+                });
+            if !inside {
+                continue;
+            }
+            let rule = Rule {
+                spreadsheet,
+                sheet_name,
+                validation,
+                cells: &index,
+                column_offset: column as i64 - anchor.0 as i64,
+                row_offset: row as i64 - anchor.1 as i64,
+            };
+            if rule.check(&value) == Some(false) {
+                violations.push(Violation {
+                    validation,
+                    column,
+                    row,
+                });
+            }
+            // A cell has at most one validation
+            break;
+        }
+    }
+
+    Some(violations)
+}
+
+/// A validation as seen from one of its cells.
+struct Rule<'a> {
+    spreadsheet: &'a Spreadsheet,
+    sheet_name: &'a str,
+    validation: &'a DataValidation,
+    cells: &'a CellIndex,
+    column_offset: i64,
+    row_offset: i64,
+}
+
+impl Rule<'_> {
+    /// Returns whether the value passes, or `None` when the rule cannot be evaluated.
+    fn check(&self, value: &Value) -> Option<bool> {
+        match self.validation.get_type() {
+            DataValidationValues::Whole => self.check_number(value, true),
+            DataValidationValues::Decimal
+            | DataValidationValues::Date
+            | DataValidationValues::Time => self.check_number(value, false),
+            DataValidationValues::TextLength => match value.to_text() {
+                Ok(text) => self.compare(text.chars().count() as f64),
+                Err(_) => Some(false),
+            },
+            DataValidationValues::List => self.check_list(value),
+            DataValidationValues::Custom => {
+                match self.evaluate(self.validation.get_formula1())?.to_bool() {
+                    Ok(flag) => Some(flag),
+                    Err(_) => Some(false),
+                }
+            }
+            DataValidationValues::None => None,
+        }
+    }
+
+    fn check_number(&self, value: &Value, whole: bool) -> Option<bool> {
+        match value {
+            Value::Number(number) if whole && number.fract() != 0.0 => Some(false),
+            Value::Number(number) => self.compare(*number),
+            _ => Some(false),
+        }
+    }
+
+    /// Compares a number with the operands of the rule, using its operator.
+    fn compare(&self, number: f64) -> Option<bool> {
+        let first = self.operand(self.validation.get_formula1())?;
+        let second = || self.operand(self.validation.get_formula2());
+
+        // Excel leaves out the operator attribute for "between", which reads back
+        // as umya-spreadsheet's default of less than; only between has a second operand.
+        let operator = match self.validation.get_operator() {
+            DataValidationOperatorValues::LessThan
+                if !self.validation.get_formula2().is_empty() =>
+            {
+                &DataValidationOperatorValues::Between
+            }
+            operator => operator,
+        };
+
+        Some(match operator {
+            DataValidationOperatorValues::Between => {
+                let second = second()?;
+                first.min(second) <= number && number <= first.max(second)
+            }
+            DataValidationOperatorValues::NotBetween => {
+                let second = second()?;
+                number < first.min(second) || number > first.max(second)
+            }
+            DataValidationOperatorValues::Equal => number == first,
+            DataValidationOperatorValues::NotEqual => number != first,
+            DataValidationOperatorValues::GreaterThan => number > first,
+            DataValidationOperatorValues::GreaterThanOrEqual => number >= first,
+            DataValidationOperatorValues::LessThan => number < first,
+            DataValidationOperatorValues::LessThanOrEqual => number <= first,
+        })
+    }
+
+    /// Evaluates an operand of a numeric rule. Dates and times may also be written
+    /// as "2024-01-31", "2024-01-31 09:30" or "09:30:15".
+    fn operand(&self, formula: &str) -> Option<f64> {
+        if let Some(serial) = parse_date_time(formula.trim().trim_matches('"')) {
+            return Some(serial);
+        }
+        match self.evaluate(formula)? {
+            Value::Text(text) => parse_date_time(&text).or_else(|| text.trim().parse().ok()),
+            value => value.to_number().ok(),
+        }
+    }
+
+    fn check_list(&self, value: &Value) -> Option<bool> {
+        let formula = self.validation.get_formula1();
+        let items = match data_validation_helper::list_items(self.spreadsheet, formula) {
+            Some(items) => items
+                .into_iter()
+                .map(|item| Value::Text(item.trim().to_string()))
+                .collect(),
+            None => match self.evaluate(formula)? {
+                Value::Error(_) => Vec::new(),
+                value => value.into_values(),
+            },
+        };
+        Some(items.iter().any(|item| list_match(value, item)))
+    }
+
+    /// Evaluates a formula of the rule as written for the current cell.
+    fn evaluate(&self, formula: &str) -> Option<Value> {
+        if formula.trim().is_empty() {
+            return None;
+        }
+        let formula = formula_helper::shift_formula(formula, self.column_offset, self.row_offset);
+        formula_evaluation_helper::evaluate(self.spreadsheet, self.sheet_name, &formula, self.cells)
+    }
+}
+
+/// Whether a value matches a list item, ignoring case; text that holds a number
+/// matches that number.
+fn list_match(value: &Value, item: &Value) -> bool {
+    if formula_evaluation_helper::compare("=", value, item) == Value::Bool(true) {
+        return true;
+    }
+    match (value, item) {
+        (Value::Number(_), Value::Text(_)) | (Value::Text(_), Value::Number(_)) => {
+            matches!((value.to_number(), item.to_number()), (Ok(a), Ok(b)) if a == b)
+        }
+        _ => false,
+    }
+}
+
+/// Parses "YYYY-MM-DD", "YYYY-MM-DD hh:mm[:ss]" (or with a "T") and "hh:mm[:ss]"
+/// into an Excel serial number.
+fn parse_date_time(text: &str) -> Option<f64> {
+    let (date, time) = match text.split_once([' ', 'T']) {
+        Some((date, time)) => (Some(date), Some(time)),
+        None if text.contains(':') => (None, Some(text)),
+        None => (Some(text), None),
+    };
+
+    let date = match date {
+        Some(date) => {
+            let parts: Vec<&str> = date.split('-').collect();
+            let [year, month, day] = parts.as_slice() else {
+                return None;
+            };
+            let (year, month, day): (i32, i32, i32) =
+                (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+            if !(1900..=9999).contains(&year)
+                || !(1..=12).contains(&month)
+                || !(1..=31).contains(&day)
+            {
+                return None;
+            }
+            convert_date(year, month, day, 0, 0, 0)
+        }
+        None => 0.0,
+    };
+
+    let time = match time {
+        Some(time) => {
+            let parts: Vec<u32> = time
+                .split(':')
+                .map(|part| part.parse().ok())
+                .collect::<Option<_>>()?;
+            let (hours, minutes, seconds) = match parts.as_slice() {
+                [hours, minutes] => (*hours, *minutes, 0),
+                [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+                _ => return None,
+            };
+            if hours > 23 || minutes > 59 || seconds > 59 {
+                return None;
+            }
+            (hours * 3600 + minutes * 60 + seconds) as f64 / 86400.0
+        }
+        None => 0.0,
+    };
+
+    Some(date + time)
+}
@@ -0,0 +1,456 @@
+use crate::helpers::defined_name_helper;
+use crate::helpers::formula_helper;
+use crate::helpers::range_helper::{self, Bounds, RangeTarget};
+use rustler::Term;
+use std::collections::HashMap;
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
+use umya_spreadsheet::{
+    DataValidation, DataValidationOperatorValues, DataValidationValues, DataValidations,
+    SheetStateValues, Spreadsheet, Worksheet,
+};
+
+/// Hidden sheet holding the items of lists that cannot be written inline.
+pub const LIST_SHEET_NAME: &str = "_ValidationLists";
+
+/// Longest inline list formula Excel accepts, quotes included.
+const MAX_INLINE_LIST_LENGTH: usize = 255;
+
+/// Options that set the messages and blank handling of a validation.
+pub const MESSAGE_OPTION_KEYS: [&str; 7] = [
+    "allow_blank",
+    "error_title",
+    "error_message",
+    "show_error_message",
+    "prompt_title",
+    "prompt_message",
+    "show_input_message",
+];
+
+/// Options that change the rule of a validation.
+pub const RULE_OPTION_KEYS: [&str; 3] = ["operator", "formula1", "formula2"];
+
+/// Parses a validation operator as Excel names it ("greaterThan") or in snake case.
+pub fn parse_operator(operator: &str) -> Result<DataValidationOperatorValues, String> {
+    match operator {
+        "between" => Ok(DataValidationOperatorValues::Between),
+        "notBetween" | "not_between" => Ok(DataValidationOperatorValues::NotBetween),
+        "equal" => Ok(DataValidationOperatorValues::Equal),
+        "notEqual" | "not_equal" => Ok(DataValidationOperatorValues::NotEqual),
+        "greaterThan" | "greater_than" => Ok(DataValidationOperatorValues::GreaterThan),
+        "lessThan" | "less_than" => Ok(DataValidationOperatorValues::LessThan),
+        "greaterThanOrEqual" | "greater_than_or_equal" => {
+            Ok(DataValidationOperatorValues::GreaterThanOrEqual)
+        }
+        "lessThanOrEqual" | "less_than_or_equal" => {
+            Ok(DataValidationOperatorValues::LessThanOrEqual)
+        }
+        _ => Err(format!("Invalid operator: {}", operator)),
+    }
+}
+
+/// Parses a space-separated range such as "A1:A10 C1" into the bounds of its areas.
+///
+/// Whole rows and columns are expanded to the size of the sheet.
+pub fn parse_sqref(range: &str) -> Result<Vec<Bounds>, String> {
+    let areas = range
+        .split_whitespace()
+        .map(|area| range_helper::parse_range(area).map(|target| target.bounds()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if areas.is_empty() {
+        return Err("Cell range cannot be empty".to_string());
+    }
+    Ok(areas)
+}
+
+/// Parses a single cell such as "B2" into its column and row.
+pub fn parse_cell(cell: &str) -> Result<(u32, u32), String> {
+    match range_helper::parse_range(cell) {
+        Ok(RangeTarget::Cells {
+            start_column,
+            start_row,
+            end_column,
+            end_row,
+        }) if start_column == end_column && start_row == end_row => Ok((start_column, start_row)),
+        _ => Err(format!("Invalid cell '{}'", cell)),
+    }
+}
+
+/// Creates a validation of the given type for a space-separated range.
+pub fn new_validation(range: &str, kind: DataValidationValues) -> Result<DataValidation, String> {
+    let areas = parse_sqref(range)?;
+    let mut validation = DataValidation::default();
+    validation.set_type(kind);
+    set_areas(&mut validation, &areas);
+    Ok(validation)
+}
+
+/// Returns the areas a validation applies to, skipping any that do not parse.
+pub fn validation_areas(validation: &DataValidation) -> Vec<Bounds> {
+    validation
+        .get_sequence_of_references()
+        .get_sqref()
+        .split_whitespace()
+        .filter_map(|area| range_helper::parse_range(area).ok())
+        .map(|target| target.bounds())
+        .collect()
+}
+
+/// Adds a validation to a sheet, first taking its cells out of the validations
+/// already there, as Excel does: a cell can only have one validation.
+pub fn add_validation(sheet: &mut Worksheet, validation: DataValidation) {
+    clear_areas(sheet, &validation_areas(&validation));
+    match sheet.get_data_validations_mut() {
+        Some(validations) => {
+            validations.add_data_validation_list(validation);
+        }
+        None => {
+            let mut validations = DataValidations::default();
+            validations.add_data_validation_list(validation);
+            sheet.set_data_validations(validations);
+        }
+    }
+}
+
+/// Takes `areas` out of every validation of a sheet, dropping the validations
+/// left without cells.
+pub fn clear_areas(sheet: &mut Worksheet, areas: &[Bounds]) {
+    let Some(validations) = sheet.get_data_validations_mut() else {
+        return;
+    };
+    let list = remove_areas(validations.get_data_validation_list().to_vec(), areas);
+    set_validations(sheet, list);
+}
+
+/// Takes `areas` out of each validation of `list`, dropping the validations left
+/// without cells.
+///
+/// Relative references in the formulas of a validation point from its first cell,
+/// so they move along when that cell is taken out.
+pub fn remove_areas(list: Vec<DataValidation>, areas: &[Bounds]) -> Vec<DataValidation> {
+    list.into_iter()
+        .filter_map(|mut validation| {
+            let current = validation_areas(&validation);
+            let Some(anchor) = current.first().copied() else {
+                return Some(validation);
+            };
+            let mut rest = current.clone();
+            for area in areas {
+                rest = rest
+                    .into_iter()
+                    .flat_map(|bounds| range_helper::subtract(bounds, *area))
+                    .collect();
+            }
+            if rest == current {
+                return Some(validation);
+            }
+            rest.sort_by_key(|&(column, row, _, _)| (row, column));
+            let first = *rest.first()?;
+
+            set_areas(&mut validation, &rest);
+            shift_formulas(
+                &mut validation,
+                first.0 as i64 - anchor.0 as i64,
+                first.1 as i64 - anchor.1 as i64,
+            );
+            Some(validation)
+        })
+        .collect()
+}
+
+/// Replaces the validations of a sheet, removing the element when none are left,
+/// which Excel would not accept.
+pub fn set_validations(sheet: &mut Worksheet, list: Vec<DataValidation>) {
+    if list.is_empty() {
+        sheet.remove_data_validations();
+        return;
+    }
+    let mut validations = DataValidations::default();
+    validations.set_data_validation_list(list);
+    sheet.set_data_validations(validations);
+}
+
+/// Returns the position of the validation that applies to a cell.
+pub fn position_at(sheet: &Worksheet, column: u32, row: u32) -> Option<usize> {
+    sheet
+        .get_data_validations()?
+        .get_data_validation_list()
+        .iter()
+        .position(|validation| {
+            validation_areas(validation)
+                .into_iter()
+                .any(|area| range_helper::intersect(area, (column, row, column, row)).is_some())
+        })
+}
+
+/// Points a validation at `areas`.
+pub fn set_areas(validation: &mut DataValidation, areas: &[Bounds]) {
+    let sqref: Vec<String> = areas
+        .iter()
+        .map(|area| range_helper::format_bounds(*area))
+        .collect();
+    range_helper::replace_sqref(
+        validation.get_sequence_of_references_mut(),
+        &sqref.join(" "),
+    );
+}
+
+fn shift_formulas(validation: &mut DataValidation, column_offset: i64, row_offset: i64) {
+    let formula1 =
+        formula_helper::shift_formula(validation.get_formula1(), column_offset, row_offset);
+    let formula2 =
+        formula_helper::shift_formula(validation.get_formula2(), column_offset, row_offset);
+    validation.set_formula1(formula1).set_formula2(formula2);
+}
+
+/// Applies the message, blank handling and rule options of `options` to a validation.
+///
+/// Setting an error or prompt message also shows it, unless `show_error_message`
+/// or `show_input_message` say otherwise; `nil` clears a message.
+pub fn apply_options(
+    validation: &mut DataValidation,
+    options: &HashMap<String, Term>,
+) -> Result<(), String> {
+    if let Some(allow_blank) = flag(options, "allow_blank")? {
+        validation.set_allow_blank(allow_blank);
+    }
+    if let Some(title) = string(options, "error_title")? {
+        validation.set_error_title(title.unwrap_or_default());
+    }
+    if let Some(message) = string(options, "error_message")? {
+        validation.set_show_error_message(message.is_some());
+        validation.set_error_message(message.unwrap_or_default());
+    }
+    if let Some(show) = flag(options, "show_error_message")? {
+        validation.set_show_error_message(show);
+    }
+    if let Some(title) = string(options, "prompt_title")? {
+        validation.set_prompt_title(title.unwrap_or_default());
+    }
+    if let Some(message) = string(options, "prompt_message")? {
+        validation.set_show_input_message(message.is_some());
+        validation.set_prompt(message.unwrap_or_default());
+    }
+    if let Some(show) = flag(options, "show_input_message")? {
+        validation.set_show_input_message(show);
+    }
+
+    if let Some(operator) = string(options, "operator")? {
+        let operator = operator.ok_or_else(|| "Option 'operator' must be a string".to_string())?;
+        validation.set_operator(parse_operator(&operator)?);
+    }
+    if let Some(formula) = string(options, "formula1")? {
+        let formula = formula.ok_or_else(|| "Option 'formula1' must be a string".to_string())?;
+        validation.set_formula1(formula);
+    }
+    if let Some(formula) = string(options, "formula2")? {
+        validation.set_formula2(formula.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Rejects option keys outside of `keys`.
+pub fn check_option_keys(options: &HashMap<String, Term>, keys: &[&str]) -> Result<(), String> {
+    match options.keys().find(|key| !keys.contains(&key.as_str())) {
+        Some(key) => Err(format!("Unknown data validation option '{}'", key)),
+        None => Ok(()),
+    }
+}
+
+fn flag(options: &HashMap<String, Term>, key: &str) -> Result<Option<bool>, String> {
+    match options.get(key) {
+        Some(term) => term
+            .decode::<bool>()
+            .map(Some)
+            .map_err(|_| format!("Option '{}' must be a boolean", key)),
+        None => Ok(None),
+    }
+}
+
+/// Decodes a string option: `None` when absent, `Some(None)` when `nil`.
+fn string(options: &HashMap<String, Term>, key: &str) -> Result<Option<Option<String>>, String> {
+    match options.get(key) {
+        Some(term) => term
+            .decode::<Option<String>>()
+            .map(Some)
+            .map_err(|_| format!("Option '{}' must be a string", key)),
+        None => Ok(None),
+    }
+}
+
+/// Builds the formula of a list validation from its items.
+///
+/// Items are written inline, with quotes doubled, when they fit. An item with a
+/// comma cannot be written inline, and Excel rejects inline lists longer than 255
+/// characters, so those lists go to a hidden sheet the formula points to instead.
+pub fn list_formula(spreadsheet: &mut Spreadsheet, items: &[String]) -> Result<String, String> {
+    let inline = items.iter().all(|item| !item.contains(','));
+    let formula = format!(
+        "\"{}\"",
+        items
+            .iter()
+            .map(|item| item.replace('"', "\"\""))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    if inline && formula.chars().count() <= MAX_INLINE_LIST_LENGTH {
+        return Ok(formula);
+    }
+    spill_list(spreadsheet, items)
+}
+
+/// Writes list items to a column of the hidden list sheet, reusing a column that
+/// already holds the same items, and returns the reference to them.
+fn spill_list(spreadsheet: &mut Spreadsheet, items: &[String]) -> Result<String, String> {
+    if spreadsheet.get_sheet_by_name(LIST_SHEET_NAME).is_none() {
+        spreadsheet
+            .new_sheet(LIST_SHEET_NAME)
+            .map_err(|err| err.to_string())?
+            .set_state(SheetStateValues::Hidden);
+    }
+    let sheet = spreadsheet
+        .get_sheet_by_name_mut(LIST_SHEET_NAME)
+        .ok_or_else(|| format!("Sheet '{}' not found", LIST_SHEET_NAME))?;
+
+    let highest_column = sheet.get_highest_column();
+    let column = (1..=highest_column)
+        .find(|column| column_values(sheet, *column) == items)
+        .unwrap_or(highest_column + 1);
+    if column > highest_column {
+        for (row, item) in items.iter().enumerate() {
+            sheet
+                .get_cell_mut((column, row as u32 + 1))
+                .set_value_string(item);
+        }
+    }
+
+    let letters = string_from_column_index(&column);
+    Ok(format!(
+        "{}!${}$1:${}${}",
+        formula_helper::quote_sheet_name(LIST_SHEET_NAME),
+        letters,
+        letters,
+        items.len()
+    ))
+}
+
+/// Returns the values of a column down to its last non-empty cell.
+fn column_values(sheet: &Worksheet, column: u32) -> Vec<String> {
+    let last_row = sheet
+        .get_cell_collection()
+        .iter()
+        .filter(|cell| *cell.get_coordinate().get_col_num() == column)
+        .filter(|cell| !cell.get_cell_value().is_empty())
+        .map(|cell| *cell.get_coordinate().get_row_num())
+        .max()
+        .unwrap_or(0);
+    (1..=last_row)
+        .map(|row| sheet.get_value((column, row)))
+        .collect()
+}
+
+/// Returns the items of a list validation formula, whether written inline or
+/// to the hidden list sheet; `None` for other list sources.
+pub fn list_items(spreadsheet: &Spreadsheet, formula: &str) -> Option<Vec<String>> {
+    if formula.len() >= 2 && formula.starts_with('"') && formula.ends_with('"') {
+        let items = formula[1..formula.len() - 1]
+            .split(',')
+            .map(|item| item.replace("\"\"", "\""))
+            .collect();
+        return Some(items);
+    }
+
+    let (sheet_name, range) = defined_name_helper::split_area(formula)?;
+    if sheet_name.as_deref() != Some(LIST_SHEET_NAME) {
+        return None;
+    }
+    let sheet = spreadsheet.get_sheet_by_name(LIST_SHEET_NAME)?;
+    let (start_column, start_row, end_column, end_row) =
+        range_helper::parse_range(&range).ok()?.bounds();
+    let mut items = Vec::new();
+    for row in start_row..=end_row {
+        for column in start_column..=end_column {
+            items.push(sheet.get_value((column, row)));
+        }
+    }
+    Some(items)
+}
+
+/// Builds the formula of a list validation whose items come from elsewhere in the
+/// workbook.
+///
+/// * `"range"` - a single row or column, on `sheet_name` or another sheet ("Lists!A1:A10")
+/// * `"defined_name"` - a defined name visible from `sheet_name`
+/// * `"indirect"` - a cell whose value names the range, for dependent dropdowns;
+///   relative references move with each cell of the validation
+pub fn source_formula(
+    spreadsheet: &Spreadsheet,
+    sheet_name: &str,
+    source_type: &str,
+    source: &str,
+) -> Result<String, String> {
+    let source = source.trim();
+    match source_type {
+        "range" => {
+            let invalid = || format!("Invalid list source range '{}'", source);
+            let (source_sheet, range) =
+                defined_name_helper::split_area(source).ok_or_else(invalid)?;
+            if let Some(name) = &source_sheet {
+                spreadsheet
+                    .get_sheet_by_name(name)
+                    .ok_or_else(|| format!("Sheet '{}' not found", name))?;
+            }
+            let target = range_helper::parse_range(&range).map_err(|_| invalid())?;
+            let (start_column, start_row, end_column, end_row) = target.bounds();
+            if start_column != end_column && start_row != end_row {
+                return Err(format!(
+                    "List source range '{}' must be a single row or column",
+                    source
+                ));
+            }
+            let area = absolute_range(target);
+            Ok(match source_sheet {
+                Some(name) => format!("{}!{}", formula_helper::quote_sheet_name(&name), area),
+                None => area,
+            })
+        }
+        "defined_name" => {
+            let sheet_index = spreadsheet
+                .get_sheet_collection_no_check()
+                .iter()
+                .position(|sheet| sheet.get_name() == sheet_name)
+                .map(|index| index as u32);
+            defined_name_helper::lookup_defined_name(spreadsheet, source, sheet_index)
+                .ok_or_else(|| format!("Defined name '{}' not found", source))?;
+            Ok(source.to_string())
+        }
+        "indirect" => {
+            parse_cell(source)?;
+            Ok(format!("INDIRECT({})", source.to_uppercase()))
+        }
+        _ => Err(format!("Invalid list source type '{}'", source_type)),
+    }
+}
+
+fn absolute_range(target: RangeTarget) -> String {
+    let column = |index: u32| format!("${}", string_from_column_index(&index));
+    match target {
+        RangeTarget::Cells {
+            start_column,
+            start_row,
+            end_column,
+            end_row,
+        } => {
+            let start = format!("{}${}", column(start_column), start_row);
+            if start_column == end_column && start_row == end_row {
+                start
+            } else {
+                format!("{}:{}${}", start, column(end_column), end_row)
+            }
+        }
+        RangeTarget::Rows { start_row, end_row } => format!("${}:${}", start_row, end_row),
+        RangeTarget::Columns {
+            start_column,
+            end_column,
+        } => format!("{}:{}", column(start_column), column(end_column)),
+    }
+}
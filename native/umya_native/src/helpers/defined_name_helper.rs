@@ -0,0 +1,407 @@
+use umya_spreadsheet::helper::coordinate::column_index_from_string;
+use umya_spreadsheet::{DefinedName, Spreadsheet, Worksheet};
+
+/// Maximum length Excel accepts for a defined name.
+const MAX_DEFINED_NAME_LENGTH: usize = 255;
+
+/// Last column (XFD) and row of an Excel 2007+ worksheet.
+pub const MAX_COLUMN: u32 = 16_384;
+pub const MAX_ROW: u32 = 1_048_576;
+
+/// Location of a defined name inside the umya-spreadsheet object model.
+///
+/// umya-spreadsheet keeps defined names in two places: the workbook-level list
+/// and the per-worksheet lists. The reader files workbook-scoped names that point
+/// at a sheet under that sheet, so the storage location alone does not tell the
+/// scope; only `localSheetId` does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefinedNameLocation {
+    /// Index into `Spreadsheet::get_defined_names`
+    Workbook(usize),
+    /// Sheet index and index into that sheet's `get_defined_names`
+    Sheet(usize, usize),
+}
+
+/// Creates a `DefinedName` with its name set.
+///
+/// `DefinedName::set_name` is crate-private in umya-spreadsheet 2.3.0, but
+/// `Worksheet::add_defined_name` sets it, so a scratch worksheet is used to build
+/// the object before it is moved to its real location.
+///
+/// # Arguments
+/// * `name` - The defined name (e.g., "TaxRate")
+/// * `refers_to` - The range, constant or formula the name refers to, without a leading `=`
+pub fn new_defined_name(name: &str, refers_to: &str) -> DefinedName {
+    let mut scratch = Worksheet::default();
+    let _ = scratch.add_defined_name(name.to_string(), normalize_refers_to(refers_to));
+    scratch.get_defined_names_mut().pop().unwrap_or_default()
+}
+
+/// Strips the leading `=` Excel users tend to type in front of references.
+pub fn normalize_refers_to(refers_to: &str) -> String {
+    refers_to.trim().trim_start_matches('=').to_string()
+}
+
+/// Returns the `localSheetId` of a defined name, or `None` when it is workbook-scoped.
+pub fn scope_of(defined_name: &DefinedName) -> Option<u32> {
+    if defined_name.has_local_sheet_id() {
+        Some(*defined_name.get_local_sheet_id())
+    } else {
+        None
+    }
+}
+
+/// Converts an optional sheet name into the `localSheetId` used for sheet scoping.
+///
+/// # Returns
+/// `Ok(None)` for workbook scope, `Ok(Some(index))` for sheet scope, or an error
+/// when the sheet does not exist.
+pub fn resolve_scope(
+    spreadsheet: &Spreadsheet,
+    scope: &Option<String>,
+) -> Result<Option<u32>, String> {
+    match scope {
+        None => Ok(None),
+        Some(sheet_name) => spreadsheet
+            .get_sheet_collection_no_check()
+            .iter()
+            .position(|sheet| sheet.get_name() == sheet_name)
+            .map(|index| Some(index as u32))
+            .ok_or_else(|| format!("Sheet '{}' not found", sheet_name)),
+    }
+}
+
+/// Returns the sheet name for a `localSheetId`, if the sheet exists.
+pub fn scope_sheet_name(spreadsheet: &Spreadsheet, local_sheet_id: Option<u32>) -> Option<String> {
+    local_sheet_id.and_then(|id| {
+        spreadsheet
+            .get_sheet_collection_no_check()
+            .get(id as usize)
+            .map(|sheet| sheet.get_name().to_string())
+    })
+}
+
+/// Lists every defined name in the workbook together with its location.
+pub fn all_defined_names(spreadsheet: &Spreadsheet) -> Vec<(DefinedNameLocation, &DefinedName)> {
+    let mut result: Vec<(DefinedNameLocation, &DefinedName)> = spreadsheet
+        .get_defined_names()
+        .iter()
+        .enumerate()
+        .map(|(index, defined_name)| (DefinedNameLocation::Workbook(index), defined_name))
+        .collect();
+
+    for (sheet_index, sheet) in spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .enumerate()
+    {
+        for (index, defined_name) in sheet.get_defined_names().iter().enumerate() {
+            result.push((DefinedNameLocation::Sheet(sheet_index, index), defined_name));
+        }
+    }
+
+    result
+}
+
+/// Finds a defined name by name (case-insensitive, as in Excel) and exact scope.
+pub fn find_defined_name(
+    spreadsheet: &Spreadsheet,
+    name: &str,
+    local_sheet_id: Option<u32>,
+) -> Option<DefinedNameLocation> {
+    all_defined_names(spreadsheet)
+        .into_iter()
+        .find(|(_, defined_name)| {
+            defined_name.get_name().eq_ignore_ascii_case(name)
+                && scope_of(defined_name) == local_sheet_id
+        })
+        .map(|(location, _)| location)
+}
+
+/// Finds a defined name the way a formula on `sheet_index` would see it: a name
+/// scoped to that sheet wins over a workbook-scoped name with the same name.
+pub fn lookup_defined_name(
+    spreadsheet: &Spreadsheet,
+    name: &str,
+    sheet_index: Option<u32>,
+) -> Option<DefinedNameLocation> {
+    sheet_index
+        .and_then(|index| find_defined_name(spreadsheet, name, Some(index)))
+        .or_else(|| find_defined_name(spreadsheet, name, None))
+}
+
+/// Returns the defined name stored at `location`.
+pub fn get_at(spreadsheet: &Spreadsheet, location: DefinedNameLocation) -> Option<&DefinedName> {
+    match location {
+        DefinedNameLocation::Workbook(index) => spreadsheet.get_defined_names().get(index),
+        DefinedNameLocation::Sheet(sheet_index, index) => spreadsheet
+            .get_sheet_collection_no_check()
+            .get(sheet_index)
+            .and_then(|sheet| sheet.get_defined_names().get(index)),
+    }
+}
+
+/// Removes and returns the defined name stored at `location`.
+pub fn remove_at(
+    spreadsheet: &mut Spreadsheet,
+    location: DefinedNameLocation,
+) -> Option<DefinedName> {
+    match location {
+        DefinedNameLocation::Workbook(index) => {
+            let names = spreadsheet.get_defined_names_mut();
+            (index < names.len()).then(|| names.remove(index))
+        }
+        DefinedNameLocation::Sheet(sheet_index, index) => {
+            spreadsheet.get_sheet_mut(&sheet_index).and_then(|sheet| {
+                let names = sheet.get_defined_names_mut();
+                (index < names.len()).then(|| names.remove(index))
+            })
+        }
+    }
+}
+
+/// Stores a defined name where umya-spreadsheet's writer expects it for its scope.
+///
+/// Sheet-scoped names go to the sheet they are scoped to; workbook-scoped names go
+/// to the workbook-level list.
+pub fn insert(spreadsheet: &mut Spreadsheet, defined_name: DefinedName) {
+    match scope_of(&defined_name) {
+        Some(local_sheet_id) => {
+            if let Some(sheet) = spreadsheet.get_sheet_mut(&(local_sheet_id as usize)) {
+                sheet.add_defined_names(defined_name);
+            }
+        }
+        None => spreadsheet.add_defined_names(defined_name),
+    }
+}
+
+/// Points the `localSheetId` of every sheet-scoped name at the index `map` returns
+/// for it, after sheets were reordered.
+pub fn remap_local_sheet_ids<F: Fn(u32) -> u32>(spreadsheet: &mut Spreadsheet, map: F) {
+    let remap = |defined_name: &mut DefinedName| {
+        if let Some(local_sheet_id) = scope_of(defined_name) {
+            defined_name.set_local_sheet_id(map(local_sheet_id));
+        }
+    };
+
+    spreadsheet
+        .get_defined_names_mut()
+        .iter_mut()
+        .for_each(remap);
+    for sheet in spreadsheet.get_sheet_collection_mut().iter_mut() {
+        sheet.get_defined_names_mut().iter_mut().for_each(remap);
+    }
+}
+
+/// Removes the names scoped to the sheet at `local_sheet_id`, before that sheet
+/// is removed.
+pub fn remove_local_names(spreadsheet: &mut Spreadsheet, local_sheet_id: u32) {
+    let outside = |defined_name: &DefinedName| scope_of(defined_name) != Some(local_sheet_id);
+
+    spreadsheet.get_defined_names_mut().retain(outside);
+    for sheet in spreadsheet.get_sheet_collection_mut().iter_mut() {
+        sheet.get_defined_names_mut().retain(outside);
+    }
+}
+
+/// Checks a name against Excel's naming rules.
+///
+/// A valid name:
+/// * is 1 to 255 characters long
+/// * starts with a letter, an underscore or a backslash
+/// * contains only letters, digits, periods, underscores, backslashes and question marks
+/// * is not "C", "c", "R" or "r"
+/// * does not look like an A1 (e.g., "AB12") or R1C1 (e.g., "R2C3") cell reference
+pub fn validate_defined_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+
+    if name.chars().count() > MAX_DEFINED_NAME_LENGTH {
+        return Err(format!(
+            "Name '{}' is longer than {} characters",
+            name, MAX_DEFINED_NAME_LENGTH
+        ));
+    }
+
+    let mut chars = name.chars();
+    let first = chars.next().unwrap_or_default();
+    if !(first.is_alphabetic() || first == '_' || first == '\\') {
+        return Err(format!(
+            "Name '{}' must start with a letter, an underscore or a backslash",
+            name
+        ));
+    }
+
+    if let Some(invalid) =
+        chars.find(|c| !(c.is_alphanumeric() || *c == '_' || *c == '.' || *c == '\\' || *c == '?'))
+    {
+        return Err(format!(
+            "Name '{}' contains the invalid character '{}'",
+            name, invalid
+        ));
+    }
+
+    if matches!(name, "C" | "c" | "R" | "r") {
+        return Err(format!("Name '{}' is reserved by Excel", name));
+    }
+
+    if is_a1_reference(name) || is_r1c1_reference(name) {
+        return Err(format!("Name '{}' conflicts with a cell reference", name));
+    }
+
+    Ok(())
+}
+
+/// Returns true for names such as "A1" or "XFD1048576" that Excel reads as a cell.
+fn is_a1_reference(name: &str) -> bool {
+    let letters: String = name
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    let digits = &name[letters.len()..];
+
+    if letters.is_empty()
+        || letters.len() > 3
+        || digits.is_empty()
+        || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+
+    let column = column_index_from_string(letters.to_uppercase());
+    let row = digits.parse::<u64>().unwrap_or(u64::MAX);
+
+    column <= MAX_COLUMN && row >= 1 && row <= u64::from(MAX_ROW)
+}
+
+/// Returns true for names such as "R1C1", "R2", "C3" or "RC" that Excel reads as R1C1 references.
+fn is_r1c1_reference(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    let rest = match upper.strip_prefix('R') {
+        Some(rest) => rest,
+        None => return is_prefixed_number(&upper, 'C'),
+    };
+
+    let digits_end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let after_row = &rest[digits_end..];
+
+    after_row.is_empty() || is_prefixed_number(after_row, 'C')
+}
+
+/// Returns true when `value` is `prefix` optionally followed by digits only.
+fn is_prefixed_number(value: &str, prefix: char) -> bool {
+    value
+        .strip_prefix(prefix)
+        .map(|digits| digits.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
+/// Splits a defined name address into its areas, honouring quoted sheet names.
+///
+/// `"'My Sheet'!$A$1:$B$2,Data!C3"` becomes `["'My Sheet'!$A$1:$B$2", "Data!C3"]`.
+pub fn split_areas(address: &str) -> Vec<String> {
+    let mut areas = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in address.chars() {
+        match c {
+            '\'' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ',' if !in_quotes => areas.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        areas.push(current);
+    }
+
+    areas
+        .into_iter()
+        .map(|area| area.trim().to_string())
+        .filter(|area| !area.is_empty())
+        .collect()
+}
+
+/// Splits a single area such as `'My Sheet'!$A$1:$B$2` into its sheet name and
+/// range without absolute markers (`("My Sheet", "A1:B2")`).
+///
+/// # Returns
+/// `None` when the area has no valid cell range part.
+pub fn split_area(area: &str) -> Option<(Option<String>, String)> {
+    let (sheet_part, range_part) = match area.rfind('!') {
+        Some(position) => (Some(&area[..position]), &area[position + 1..]),
+        None => (None, area),
+    };
+
+    let range = range_part.replace('$', "").to_uppercase();
+    if !is_cell_range(&range) {
+        return None;
+    }
+
+    let sheet_name = sheet_part.map(unquote_sheet_name);
+    Some((sheet_name, range))
+}
+
+/// Returns the `(start column, start row, end column, end row)` of a range produced
+/// by `split_area`.
+///
+/// Whole-column ranges ("A:C") end at `max_row` and whole-row ranges ("1:3") end
+/// at `max_column`, so callers can clamp them to the used area of a sheet.
+pub fn area_bounds(range: &str, max_column: u32, max_row: u32) -> (u32, u32, u32, u32) {
+    let (row_start, row_end, col_start, col_end) =
+        umya_spreadsheet::helper::range::get_start_and_end_point(range);
+
+    let (row_start, row_end) = if row_start == 0 {
+        (1, max_row)
+    } else {
+        (row_start, row_end)
+    };
+    let (col_start, col_end) = if col_start == 0 {
+        (1, max_column)
+    } else {
+        (col_start, col_end)
+    };
+
+    (
+        col_start.min(col_end),
+        row_start.min(row_end),
+        col_start.max(col_end),
+        row_start.max(row_end),
+    )
+}
+
+/// Removes the quotes around a sheet name and unescapes doubled apostrophes.
+pub fn unquote_sheet_name(sheet_name: &str) -> String {
+    let trimmed = sheet_name.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('\'') && trimmed.ends_with('\'') {
+        trimmed[1..trimmed.len() - 1].replace("''", "'")
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Returns true for "A1", "A1:B2", "A:C" and "1:3" style ranges.
+fn is_cell_range(range: &str) -> bool {
+    let parts: Vec<&str> = range.split(':').collect();
+    if parts.is_empty() || parts.len() > 2 {
+        return false;
+    }
+
+    let is_cell = |part: &str| {
+        let letters = part.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+        let rest = &part[letters..];
+        letters <= 3
+            && !part.is_empty()
+            && rest.chars().all(|c| c.is_ascii_digit())
+            && (letters > 0 || !rest.is_empty())
+    };
+
+    parts.iter().all(|part| is_cell(part))
+}
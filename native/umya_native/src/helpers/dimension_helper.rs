@@ -0,0 +1,95 @@
+use umya_spreadsheet::{Column, Worksheet};
+
+/// Height Excel gives rows of the default font (Calibri 11), in points.
+pub const DEFAULT_ROW_HEIGHT: f64 = 15.0;
+
+/// Width Excel gives columns without a width of their own.
+pub const DEFAULT_COLUMN_WIDTH: f64 = 8.43;
+
+/// Widest column Excel accepts.
+pub const MAX_COLUMN_WIDTH: f64 = 255.0;
+
+/// Highest row Excel accepts, in points.
+pub const MAX_ROW_HEIGHT: f64 = 409.0;
+
+/// Returns the height of rows without a height of their own.
+///
+/// Excel only keeps the sheet's `defaultRowHeight` when it is marked custom and
+/// otherwise sizes rows to the default font.
+pub fn default_row_height(sheet: &Worksheet) -> f64 {
+    let format = sheet.get_sheet_format_properties();
+    let height = *format.get_default_row_height();
+    if *format.get_custom_height() && height > 0.0 {
+        height
+    } else {
+        DEFAULT_ROW_HEIGHT
+    }
+}
+
+/// Returns the width of columns without a width of their own.
+pub fn default_column_width(sheet: &Worksheet) -> f64 {
+    let width = *sheet
+        .get_sheet_format_properties()
+        .get_default_column_width();
+    if width > 0.0 {
+        width
+    } else {
+        DEFAULT_COLUMN_WIDTH
+    }
+}
+
+/// Returns the height of a row, falling back to the sheet's default.
+pub fn row_height(sheet: &Worksheet, row: u32) -> f64 {
+    sheet
+        .get_row_dimension(&row)
+        .map(|dimension| *dimension.get_height())
+        .filter(|height| *height > 0.0)
+        .unwrap_or_else(|| default_row_height(sheet))
+}
+
+/// Returns the width of a column, falling back to the sheet's default.
+pub fn column_width(sheet: &Worksheet, column: u32) -> f64 {
+    sheet
+        .get_column_dimension_by_number(&column)
+        .map(|dimension| *dimension.get_width())
+        .filter(|width| *width > 0.0)
+        .unwrap_or_else(|| default_column_width(sheet))
+}
+
+/// Returns the dimension of a column, creating it with the sheet's default width.
+///
+/// umya-spreadsheet always writes the width of a column dimension, so a new one
+/// would otherwise change the column's width to 8.38.
+pub fn column_dimension_mut(sheet: &mut Worksheet, column: u32) -> &mut Column {
+    if sheet.get_column_dimension_by_number(&column).is_none() {
+        let width = default_column_width(sheet);
+        sheet
+            .get_column_dimension_by_number_mut(&column)
+            .set_width(width);
+    }
+    sheet.get_column_dimension_by_number_mut(&column)
+}
+
+/// Checks a row height against the range Excel accepts.
+pub fn check_row_height(height: f64) -> Result<(), String> {
+    if (0.0..=MAX_ROW_HEIGHT).contains(&height) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Row height must be between 0 and {} points",
+            MAX_ROW_HEIGHT
+        ))
+    }
+}
+
+/// Checks a column width against the range Excel accepts.
+pub fn check_column_width(width: f64) -> Result<(), String> {
+    if (0.0..=MAX_COLUMN_WIDTH).contains(&width) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Column width must be between 0 and {} characters",
+            MAX_COLUMN_WIDTH
+        ))
+    }
+}
@@ -0,0 +1,790 @@
+use crate::helpers::defined_name_helper::{self, MAX_COLUMN, MAX_ROW};
+use crate::helpers::formula_helper::{self, Reference, ReferenceArea};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+use umya_spreadsheet::helper::date::convert_date;
+use umya_spreadsheet::{CellRawValue, Spreadsheet, Worksheet};
+
+/// Defined names and INDIRECT targets deeper than this are not followed, which
+/// also stops names that refer to themselves.
+const MAX_DEPTH: usize = 8;
+
+/// Excel's serial number of 1970-01-01, the Unix epoch.
+const UNIX_EPOCH_SERIAL: f64 = 25569.0;
+
+/// A value produced while evaluating a formula.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Blank,
+    /// An Excel error such as "#DIV/0!"
+    Error(String),
+    /// The non-empty cells of a range of more than one cell, row by row
+    Range(Vec<Value>),
+}
+
+impl Value {
+    /// Converts the value to a number as Excel does in arithmetic.
+    pub fn to_number(&self) -> Result<f64, Value> {
+        match self {
+            Value::Number(number) => Ok(*number),
+            Value::Bool(flag) => Ok(if *flag { 1.0 } else { 0.0 }),
+            Value::Blank => Ok(0.0),
+            Value::Text(text) => text.trim().parse().map_err(|_| value_error()),
+            Value::Error(_) => Err(self.clone()),
+            Value::Range(_) => Err(value_error()),
+        }
+    }
+
+    /// Converts the value to text as Excel does in concatenation.
+    pub fn to_text(&self) -> Result<String, Value> {
+        match self {
+            Value::Number(number) => Ok(number_text(*number)),
+            Value::Text(text) => Ok(text.clone()),
+            Value::Bool(flag) => Ok(if *flag { "TRUE" } else { "FALSE" }.to_string()),
+            Value::Blank => Ok(String::new()),
+            Value::Error(_) => Err(self.clone()),
+            Value::Range(_) => Err(value_error()),
+        }
+    }
+
+    /// Converts the value to a logical value as Excel does in conditions.
+    pub fn to_bool(&self) -> Result<bool, Value> {
+        match self {
+            Value::Bool(flag) => Ok(*flag),
+            Value::Number(number) => Ok(*number != 0.0),
+            Value::Blank => Ok(false),
+            Value::Text(text) if text.eq_ignore_ascii_case("TRUE") => Ok(true),
+            Value::Text(text) if text.eq_ignore_ascii_case("FALSE") => Ok(false),
+            Value::Error(_) => Err(self.clone()),
+            _ => Err(value_error()),
+        }
+    }
+
+    /// Returns the values of a range, or the value itself.
+    pub fn into_values(self) -> Vec<Value> {
+        match self {
+            Value::Range(values) => values,
+            value => vec![value],
+        }
+    }
+}
+
+fn value_error() -> Value {
+    Value::Error("#VALUE!".to_string())
+}
+
+/// Writes a number the way Excel's General format shows it, without a trailing ".0".
+pub fn number_text(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        format!("{}", number)
+    }
+}
+
+/// Returns the value of a cell, `Value::Blank` when it is empty or missing.
+///
+/// Formula cells give the result last calculated, as stored in the file.
+pub fn cell_value(sheet: &Worksheet, column: u32, row: u32) -> Value {
+    let Some(cell) = sheet.get_cell((column, row)) else {
+        return Value::Blank;
+    };
+    match cell.get_cell_value().get_raw_value() {
+        CellRawValue::Numeric(number) => Value::Number(*number),
+        CellRawValue::Bool(flag) => Value::Bool(*flag),
+        CellRawValue::Error(_) => Value::Error(cell.get_value().to_string()),
+        CellRawValue::Empty => Value::Blank,
+        _ => match cell.get_value().to_string() {
+            text if text.is_empty() => Value::Blank,
+            text => Value::Text(text),
+        },
+    }
+}
+
+/// The positions of the cells of each sheet, sorted by row and column, for reading
+/// ranges larger than the sheet's cell count without scanning every cell.
+///
+/// A sheet is indexed the first time such a range of it is read, so one index can
+/// serve many formulas evaluated against the same unchanged spreadsheet.
+#[derive(Default)]
+pub struct CellIndex {
+    sheets: RefCell<HashMap<String, BTreeSet<(u32, u32)>>>,
+}
+
+impl CellIndex {
+    /// Returns the positions of the cells of `sheet` within the bounds, as
+    /// (row, column) in row order.
+    fn positions(&self, sheet: &Worksheet, bounds: (u32, u32, u32, u32)) -> Vec<(u32, u32)> {
+        let (start_column, start_row, end_column, end_row) = bounds;
+        let mut sheets = self.sheets.borrow_mut();
+        let cells = sheets
+            .entry(sheet.get_name().to_string())
+            .or_insert_with(|| sheet.get_collection_to_hashmap().keys().copied().collect());
+        cells
+            .range((start_row, start_column)..=(end_row, end_column))
+            .filter(|(_, column)| (start_column..=end_column).contains(column))
+            .copied()
+            .collect()
+    }
+}
+
+/// Evaluates a formula written for a cell of `sheet_name`.
+///
+/// Supports numbers, text, logical values, references to cells and ranges on any
+/// sheet, defined names, the arithmetic, text and comparison operators, and the
+/// functions listed in `call`. Returns `None` for anything else, so that callers
+/// can tell a formula they cannot evaluate from one that gives an error.
+///
+/// `cells` must have been used with this spreadsheet only, and without changes to it.
+pub fn evaluate(
+    spreadsheet: &Spreadsheet,
+    sheet_name: &str,
+    formula: &str,
+    cells: &CellIndex,
+) -> Option<Value> {
+    let sheet = spreadsheet.get_sheet_by_name(sheet_name)?;
+    let sheet_index = spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| sheet.get_name() == sheet_name)
+        .map(|index| index as u32);
+    let context = Context {
+        spreadsheet,
+        sheet,
+        sheet_index,
+        cells,
+    };
+    context.evaluate(formula, 0)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Reference(Reference),
+    Name(String),
+    Operator(&'static str),
+    Open,
+    Close,
+    Separator,
+}
+
+const OPERATORS: [&str; 13] = [
+    "<=", ">=", "<>", "<", ">", "=", "+", "-", "*", "/", "^", "&", "%",
+];
+
+fn tokenize(formula: &str) -> Option<Vec<Token>> {
+    let chars: Vec<char> = formula.trim().trim_start_matches('=').chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') if chars.get(i + 1) == Some(&'"') => {
+                        text.push('"');
+                        i += 2;
+                    }
+                    Some('"') => break,
+                    Some(c) => {
+                        text.push(*c);
+                        i += 1;
+                    }
+                    None => return None,
+                }
+            }
+            tokens.push(Token::Text(text));
+            i += 1;
+        } else if let Some((reference, end)) = formula_helper::reference_at(&chars, i) {
+            tokens.push(Token::Reference(reference));
+            i = end;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1)?.is_ascii_digit()) {
+            let mut end = i;
+            while end < chars.len() && (chars[end].is_ascii_digit() || chars[end] == '.') {
+                end += 1;
+            }
+            if matches!(chars.get(end), Some('E' | 'e')) {
+                let sign = matches!(chars.get(end + 1), Some('+' | '-')) as usize;
+                let digits = end + 1 + sign;
+                if chars.get(digits).is_some_and(|c| c.is_ascii_digit()) {
+                    end = digits;
+                    while end < chars.len() && chars[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+            }
+            let number: String = chars[i..end].iter().collect();
+            tokens.push(Token::Number(number.parse().ok()?));
+            i = end;
+        } else if c.is_alphabetic() || c == '_' || c == '\\' {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_alphanumeric() || matches!(chars[j], '_' | '.' | '\\')))
+                .unwrap_or(chars.len());
+            let name: String = chars[i..end].iter().collect();
+            let call = chars[end..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+            tokens.push(match name.to_uppercase().as_str() {
+                "TRUE" if !call => Token::Bool(true),
+                "FALSE" if !call => Token::Bool(false),
+                _ => Token::Name(name),
+            });
+            i = end;
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Separator);
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let operator = OPERATORS.iter().find(|op| rest.starts_with(*op))?;
+            tokens.push(Token::Operator(operator));
+            i += operator.len();
+        }
+    }
+
+    Some(tokens)
+}
+
+struct Context<'a> {
+    spreadsheet: &'a Spreadsheet,
+    sheet: &'a Worksheet,
+    sheet_index: Option<u32>,
+    cells: &'a CellIndex,
+}
+
+impl Context<'_> {
+    fn evaluate(&self, formula: &str, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let tokens = tokenize(formula)?;
+        let mut parser = Parser {
+            context: self,
+            tokens,
+            position: 0,
+            depth,
+        };
+        let value = parser.comparison()?;
+        (parser.position == parser.tokens.len()).then_some(value)
+    }
+
+    fn reference(&self, reference: &Reference) -> Value {
+        let sheet = match &reference.sheet {
+            Some(name) => match self.spreadsheet.get_sheet_by_name(name) {
+                Some(sheet) => sheet,
+                None => return Value::Error("#REF!".to_string()),
+            },
+            None => self.sheet,
+        };
+        let (start_column, start_row, end_column, end_row) = reference_bounds(reference);
+        if start_column == end_column && start_row == end_row {
+            return cell_value(sheet, start_column, start_row);
+        }
+
+        // Looking up every position of a small range is cheaper than the index
+        let area = (end_column - start_column + 1) as u64 * (end_row - start_row + 1) as u64;
+        let positions = if area <= sheet.get_collection_to_hashmap().len() as u64 {
+            (start_row..=end_row)
+                .flat_map(|row| (start_column..=end_column).map(move |column| (row, column)))
+                .collect()
+        } else {
+            self.cells
+                .positions(sheet, (start_column, start_row, end_column, end_row))
+        };
+        let values = positions
+            .into_iter()
+            .map(|(row, column)| cell_value(sheet, column, row))
+            .filter(|value| *value != Value::Blank)
+            .collect();
+        Value::Range(values)
+    }
+
+    /// Follows a defined name, or `None` when there is no such name.
+    fn name(&self, name: &str, depth: usize) -> Option<Value> {
+        let location =
+            defined_name_helper::lookup_defined_name(self.spreadsheet, name, self.sheet_index)?;
+        let defined_name = defined_name_helper::get_at(self.spreadsheet, location)?;
+        self.evaluate(&defined_name.get_address(), depth + 1)
+    }
+
+    /// Evaluates the text of an INDIRECT call, which must be a reference or a name.
+    fn indirect(&self, text: &str, depth: usize) -> Option<Value> {
+        match tokenize(text).as_deref() {
+            Some([Token::Reference(reference)]) => Some(self.reference(reference)),
+            Some([Token::Name(name)]) => Some(self.name(name, depth).unwrap_or_else(ref_error)),
+            _ => Some(ref_error()),
+        }
+    }
+}
+
+fn ref_error() -> Value {
+    Value::Error("#REF!".to_string())
+}
+
+fn reference_bounds(reference: &Reference) -> (u32, u32, u32, u32) {
+    let (start_column, start_row, end_column, end_row) = match reference.area {
+        ReferenceArea::Cells { start, end } => {
+            let end = end.unwrap_or(start);
+            (start.0.index, start.1.index, end.0.index, end.1.index)
+        }
+        ReferenceArea::Columns { start, end } => (start.index, 1, end.index, MAX_ROW),
+        ReferenceArea::Rows { start, end } => (1, start.index, MAX_COLUMN, end.index),
+    };
+    (
+        start_column.min(end_column),
+        start_row.min(end_row),
+        start_column.max(end_column),
+        start_row.max(end_row),
+    )
+}
+
+/// Evaluates tokens by recursive descent, from the operators binding loosest.
+struct Parser<'a> {
+    context: &'a Context<'a>,
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn operator(&mut self, operators: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Operator(operator)) if operators.contains(operator) => {
+                let operator = *operator;
+                self.position += 1;
+                Some(operator)
+            }
+            _ => None,
+        }
+    }
+
+    fn comparison(&mut self) -> Option<Value> {
+        let mut left = self.concatenation()?;
+        while let Some(operator) = self.operator(&["=", "<>", "<", ">", "<=", ">="]) {
+            let right = self.concatenation()?;
+            left = compare(operator, &scalar(left)?, &scalar(right)?);
+        }
+        Some(left)
+    }
+
+    fn concatenation(&mut self) -> Option<Value> {
+        let mut left = self.additive()?;
+        while self.operator(&["&"]).is_some() {
+            let right = self.additive()?;
+            left = match (scalar(left)?.to_text(), scalar(right)?.to_text()) {
+                (Ok(left), Ok(right)) => Value::Text(left + &right),
+                (Err(error), _) | (_, Err(error)) => error,
+            };
+        }
+        Some(left)
+    }
+
+    fn additive(&mut self) -> Option<Value> {
+        let mut left = self.multiplicative()?;
+        while let Some(operator) = self.operator(&["+", "-"]) {
+            let right = self.multiplicative()?;
+            left = arithmetic(operator, &scalar(left)?, &scalar(right)?);
+        }
+        Some(left)
+    }
+
+    fn multiplicative(&mut self) -> Option<Value> {
+        let mut left = self.power()?;
+        while let Some(operator) = self.operator(&["*", "/"]) {
+            let right = self.power()?;
+            left = arithmetic(operator, &scalar(left)?, &scalar(right)?);
+        }
+        Some(left)
+    }
+
+    fn power(&mut self) -> Option<Value> {
+        let mut left = self.unary()?;
+        while self.operator(&["^"]).is_some() {
+            let right = self.unary()?;
+            left = arithmetic("^", &scalar(left)?, &scalar(right)?);
+        }
+        Some(left)
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        match self.operator(&["-", "+"]) {
+            Some("-") => Some(arithmetic(
+                "-",
+                &Value::Number(0.0),
+                &scalar(self.unary()?)?,
+            )),
+            Some(_) => self.unary(),
+            None => {
+                let value = self.primary()?;
+                match self.operator(&["%"]) {
+                    Some(_) => Some(arithmetic("/", &scalar(value)?, &Value::Number(100.0))),
+                    None => Some(value),
+                }
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Option<Value> {
+        match self.next()? {
+            Token::Number(number) => Some(Value::Number(number)),
+            Token::Text(text) => Some(Value::Text(text)),
+            Token::Bool(flag) => Some(Value::Bool(flag)),
+            Token::Reference(reference) => Some(self.context.reference(&reference)),
+            Token::Open => {
+                let value = self.comparison()?;
+                (self.next()? == Token::Close).then_some(value)
+            }
+            Token::Name(name) if self.peek() == Some(&Token::Open) => {
+                self.position += 1;
+                let mut arguments = Vec::new();
+                if self.peek() == Some(&Token::Close) {
+                    self.position += 1;
+                } else {
+                    loop {
+                        arguments.push(self.comparison()?);
+                        match self.next()? {
+                            Token::Separator => continue,
+                            Token::Close => break,
+                            _ => return None,
+                        }
+                    }
+                }
+                self.call(&name.to_uppercase(), arguments)
+            }
+            Token::Name(name) => self.context.name(&name, self.depth),
+            _ => None,
+        }
+    }
+
+    /// Calls a function, or returns `None` for functions that are not supported.
+    fn call(&self, name: &str, arguments: Vec<Value>) -> Option<Value> {
+        let count = arguments.len();
+        let argument = |index: usize| arguments.get(index).cloned().map(scalar);
+        let number =
+            |index: usize| -> Option<Result<f64, Value>> { Some(argument(index)??.to_number()) };
+        let text =
+            |index: usize| -> Option<Result<String, Value>> { Some(argument(index)??.to_text()) };
+
+        let value = match (name, count) {
+            ("AND" | "OR", 1..) => {
+                let mut flags = Vec::new();
+                for value in arguments.into_iter().flat_map(Value::into_values) {
+                    match value {
+                        Value::Text(_) | Value::Blank => (),
+                        value => match value.to_bool() {
+                            Ok(flag) => flags.push(flag),
+                            Err(error) => return Some(error),
+                        },
+                    }
+                }
+                if flags.is_empty() {
+                    value_error()
+                } else if name == "AND" {
+                    Value::Bool(flags.into_iter().all(|flag| flag))
+                } else {
+                    Value::Bool(flags.into_iter().any(|flag| flag))
+                }
+            }
+            ("NOT", 1) => result(argument(0)??.to_bool().map(|flag| Value::Bool(!flag))),
+            ("IF", 2 | 3) => match argument(0)??.to_bool() {
+                Ok(true) => argument(1)??,
+                Ok(false) => argument(2).unwrap_or(Some(Value::Bool(false)))?,
+                Err(error) => error,
+            },
+            ("ISNUMBER", 1) => Value::Bool(matches!(argument(0)?, Some(Value::Number(_)))),
+            ("ISTEXT", 1) => Value::Bool(matches!(argument(0)?, Some(Value::Text(_)))),
+            ("ISBLANK", 1) => Value::Bool(matches!(argument(0)?, Some(Value::Blank))),
+            ("ISLOGICAL", 1) => Value::Bool(matches!(argument(0)?, Some(Value::Bool(_)))),
+            ("ISERROR", 1) => Value::Bool(matches!(argument(0)?, Some(Value::Error(_)))),
+            ("LEN", 1) => result(text(0)?.map(|text| Value::Number(text.chars().count() as f64))),
+            ("UPPER", 1) => result(text(0)?.map(|text| Value::Text(text.to_uppercase()))),
+            ("LOWER", 1) => result(text(0)?.map(|text| Value::Text(text.to_lowercase()))),
+            ("TRIM", 1) => result(text(0)?.map(|text| {
+                Value::Text(
+                    text.split(' ')
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            })),
+            ("EXACT", 2) => match (text(0)?, text(1)?) {
+                (Ok(left), Ok(right)) => Value::Bool(left == right),
+                (Err(error), _) | (_, Err(error)) => error,
+            },
+            ("LEFT" | "RIGHT", 1 | 2) => {
+                let length = if count == 2 { number(1)? } else { Ok(1.0) };
+                match (text(0)?, length) {
+                    (Ok(_), Ok(length)) if length < 0.0 => value_error(),
+                    (Ok(text), Ok(length)) => {
+                        let chars: Vec<char> = text.chars().collect();
+                        let length = (length as usize).min(chars.len());
+                        let part = if name == "LEFT" {
+                            &chars[..length]
+                        } else {
+                            &chars[chars.len() - length..]
+                        };
+                        Value::Text(part.iter().collect())
+                    }
+                    (Err(error), _) | (_, Err(error)) => error,
+                }
+            }
+            ("MID", 3) => match (text(0)?, number(1)?, number(2)?) {
+                (Ok(_), Ok(start), Ok(length)) if start < 1.0 || length < 0.0 => value_error(),
+                (Ok(text), Ok(start), Ok(length)) => Value::Text(
+                    text.chars()
+                        .skip(start as usize - 1)
+                        .take(length as usize)
+                        .collect(),
+                ),
+                (Err(error), _, _) | (_, Err(error), _) | (_, _, Err(error)) => error,
+            },
+            ("VALUE", 1) => result(number(0)?.map(Value::Number)),
+            ("ABS", 1) => result(number(0)?.map(|number| Value::Number(number.abs()))),
+            ("INT", 1) => result(number(0)?.map(|number| Value::Number(number.floor()))),
+            ("ROUND", 2) => match (number(0)?, number(1)?) {
+                (Ok(number), Ok(digits)) => {
+                    let factor = 10f64.powi(digits as i32);
+                    Value::Number((number * factor).round() / factor)
+                }
+                (Err(error), _) | (_, Err(error)) => error,
+            },
+            ("MOD", 2) => match (number(0)?, number(1)?) {
+                (Ok(_), Ok(0.0)) => Value::Error("#DIV/0!".to_string()),
+                (Ok(number), Ok(divisor)) => {
+                    Value::Number(number - divisor * (number / divisor).floor())
+                }
+                (Err(error), _) | (_, Err(error)) => error,
+            },
+            ("SUM" | "MIN" | "MAX" | "COUNT", 1..) => {
+                let mut numbers = Vec::new();
+                for value in arguments.into_iter().flat_map(Value::into_values) {
+                    match value {
+                        Value::Number(number) => numbers.push(number),
+                        Value::Error(_) if name != "COUNT" => return Some(value),
+                        _ => (),
+                    }
+                }
+                Value::Number(match name {
+                    "SUM" => numbers.iter().sum(),
+                    "COUNT" => numbers.len() as f64,
+                    _ if numbers.is_empty() => 0.0,
+                    "MIN" => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+                    _ => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                })
+            }
+            ("COUNTA", 1..) => Value::Number(
+                arguments
+                    .into_iter()
+                    .flat_map(Value::into_values)
+                    .filter(|value| *value != Value::Blank)
+                    .count() as f64,
+            ),
+            ("COUNTIF", 2) => {
+                let values = arguments[0].clone().into_values();
+                let criteria = scalar(arguments[1].clone())?;
+                let (operator, operand) = parse_criteria(&criteria)?;
+                let count = values
+                    .iter()
+                    .filter(|value| criteria_match(value, operator, &operand))
+                    .count();
+                Value::Number(count as f64)
+            }
+            ("DATE", 3) => match (number(0)?, number(1)?, number(2)?) {
+                (Ok(year), Ok(month), Ok(day)) => date_serial(year, month, day),
+                (Err(error), _, _) | (_, Err(error), _) | (_, _, Err(error)) => error,
+            },
+            ("TIME", 3) => match (number(0)?, number(1)?, number(2)?) {
+                (Ok(hours), Ok(minutes), Ok(seconds)) => {
+                    let seconds = hours.trunc() * 3600.0 + minutes.trunc() * 60.0 + seconds.trunc();
+                    if seconds < 0.0 {
+                        Value::Error("#NUM!".to_string())
+                    } else {
+                        Value::Number((seconds / 86400.0).fract())
+                    }
+                }
+                (Err(error), _, _) | (_, Err(error), _) | (_, _, Err(error)) => error,
+            },
+            ("TODAY" | "NOW", 0) => {
+                let seconds = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()?
+                    .as_secs_f64();
+                let now = UNIX_EPOCH_SERIAL + seconds / 86400.0;
+                Value::Number(if name == "TODAY" { now.floor() } else { now })
+            }
+            ("INDIRECT", 1) => match text(0)? {
+                Ok(text) => self.context.indirect(&text, self.depth + 1)?,
+                Err(error) => error,
+            },
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+fn result(value: Result<Value, Value>) -> Value {
+    value.unwrap_or_else(|error| error)
+}
+
+/// Takes the single value of a one-cell expression; ranges of several cells are
+/// not supported where a single value is expected.
+fn scalar(value: Value) -> Option<Value> {
+    match value {
+        Value::Range(_) => None,
+        value => Some(value),
+    }
+}
+
+fn arithmetic(operator: &str, left: &Value, right: &Value) -> Value {
+    let (left, right) = match (left.to_number(), right.to_number()) {
+        (Ok(left), Ok(right)) => (left, right),
+        (Err(error), _) | (_, Err(error)) => return error,
+    };
+    let number = match operator {
+        "+" => left + right,
+        "-" => left - right,
+        "*" => left * right,
+        "/" if right == 0.0 => return Value::Error("#DIV/0!".to_string()),
+        "/" => left / right,
+        _ => left.powf(right),
+    };
+    if number.is_finite() {
+        Value::Number(number)
+    } else {
+        Value::Error("#NUM!".to_string())
+    }
+}
+
+/// Compares two values as Excel does: text ignores case, and numbers sort before
+/// text, which sorts before logical values. Blank cells match 0, "" and FALSE.
+pub fn compare(operator: &str, left: &Value, right: &Value) -> Value {
+    use std::cmp::Ordering;
+
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) => 0,
+            Value::Text(_) => 1,
+            _ => 2,
+        }
+    }
+    fn blank_as(value: &Value, other: &Value) -> Value {
+        match (value, other) {
+            (Value::Blank, Value::Text(_)) => Value::Text(String::new()),
+            (Value::Blank, Value::Bool(_)) => Value::Bool(false),
+            (Value::Blank, _) => Value::Number(0.0),
+            (value, _) => value.clone(),
+        }
+    }
+
+    if let Value::Error(_) = left {
+        return left.clone();
+    }
+    if let Value::Error(_) = right {
+        return right.clone();
+    }
+    let (left, right) = (blank_as(left, right), blank_as(right, left));
+    let ordering = match (&left, &right) {
+        (Value::Number(left), Value::Number(right)) => left.partial_cmp(right),
+        (Value::Text(left), Value::Text(right)) => {
+            Some(left.to_lowercase().cmp(&right.to_lowercase()))
+        }
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        _ => Some(rank(&left).cmp(&rank(&right))),
+    }
+    .unwrap_or(Ordering::Equal);
+
+    Value::Bool(match operator {
+        "=" => ordering == Ordering::Equal,
+        "<>" => ordering != Ordering::Equal,
+        "<" => ordering == Ordering::Less,
+        ">" => ordering == Ordering::Greater,
+        "<=" => ordering != Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    })
+}
+
+/// Whether a value meets a COUNTIF criteria. Only values of the operand's type
+/// are compared, except that "<>" counts every value not equal to the operand.
+fn criteria_match(value: &Value, operator: &str, operand: &Value) -> bool {
+    if operator == "<>" {
+        return !criteria_match(value, "=", operand);
+    }
+    let value = match (value, operand) {
+        (Value::Text(text), Value::Number(_)) => match text.trim().parse() {
+            Ok(number) => Value::Number(number),
+            Err(_) => return false,
+        },
+        (Value::Number(_), Value::Number(_))
+        | (Value::Text(_), Value::Text(_))
+        | (Value::Bool(_), Value::Bool(_)) => value.clone(),
+        _ => return false,
+    };
+    compare(operator, &value, operand) == Value::Bool(true)
+}
+
+/// Splits a COUNTIF criteria such as ">=10" or "apple" into its operator and value.
+///
+/// Wildcards and criteria matching blank cells are not supported.
+fn parse_criteria(criteria: &Value) -> Option<(&'static str, Value)> {
+    let Value::Text(text) = criteria else {
+        return Some(("=", criteria.clone()));
+    };
+    let operator = ["<=", ">=", "<>", "<", ">", "="]
+        .into_iter()
+        .find(|operator| text.starts_with(operator));
+    let operand = &text[operator.map_or(0, str::len)..];
+    if operand.is_empty() || operand.contains(['*', '?', '~']) {
+        return None;
+    }
+    let operand = match operand.parse::<f64>() {
+        Ok(number) => Value::Number(number),
+        Err(_) => Value::Text(operand.to_string()),
+    };
+    Some((operator.unwrap_or("="), operand))
+}
+
+/// Returns the serial number of a date, rolling months over into years and days
+/// over into months as Excel's DATE does.
+pub fn date_serial(year: f64, month: f64, day: f64) -> Value {
+    let (year, month, day) = (
+        year.trunc() as i64,
+        month.trunc() as i64,
+        day.trunc() as i64,
+    );
+    let year = if (0..1900).contains(&year) {
+        year + 1900
+    } else {
+        year
+    };
+    let year = year + (month - 1).div_euclid(12);
+    let month = (month - 1).rem_euclid(12) + 1;
+    if !(1900..=9999).contains(&year) {
+        return Value::Error("#NUM!".to_string());
+    }
+    let first = convert_date(year as i32, month as i32, 1, 0, 0, 0);
+    Value::Number(first + (day - 1) as f64)
+}
@@ -0,0 +1,464 @@
+use crate::helpers::defined_name_helper::{MAX_COLUMN, MAX_ROW};
+use umya_spreadsheet::helper::coordinate::{column_index_from_string, string_from_column_index};
+
+/// One column or row coordinate of a reference, e.g. the `$B` of `$B7`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReferencePart {
+    pub index: u32,
+    pub absolute: bool,
+}
+
+/// The area of a cell reference found in a formula.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceArea {
+    /// "A1" or "A1:B2"; the end is `None` for a single cell
+    Cells {
+        start: (ReferencePart, ReferencePart),
+        end: Option<(ReferencePart, ReferencePart)>,
+    },
+    /// Whole columns, e.g. "A:C"
+    Columns {
+        start: ReferencePart,
+        end: ReferencePart,
+    },
+    /// Whole rows, e.g. "3:5"
+    Rows {
+        start: ReferencePart,
+        end: ReferencePart,
+    },
+}
+
+/// A cell reference found in a formula, with its sheet name when it has one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// The unquoted sheet name
+    pub sheet: Option<String>,
+    pub area: ReferenceArea,
+}
+
+impl Reference {
+    /// Moves the relative parts of the reference by the given offsets.
+    ///
+    /// Returns `None` when the reference would leave the sheet, where Excel writes `#REF!`.
+    pub fn shifted(&self, column_offset: i64, row_offset: i64) -> Option<Reference> {
+        let column = |part: ReferencePart| shift_part(part, column_offset, MAX_COLUMN);
+        let row = |part: ReferencePart| shift_part(part, row_offset, MAX_ROW);
+
+        let area = match self.area {
+            ReferenceArea::Cells { start, end } => ReferenceArea::Cells {
+                start: (column(start.0)?, row(start.1)?),
+                end: match end {
+                    Some(end) => Some((column(end.0)?, row(end.1)?)),
+                    None => None,
+                },
+            },
+            ReferenceArea::Columns { start, end } => ReferenceArea::Columns {
+                start: column(start)?,
+                end: column(end)?,
+            },
+            ReferenceArea::Rows { start, end } => ReferenceArea::Rows {
+                start: row(start)?,
+                end: row(end)?,
+            },
+        };
+
+        Some(Reference {
+            sheet: self.sheet.clone(),
+            area,
+        })
+    }
+
+    /// Writes the reference in A1 notation, quoting the sheet name when needed.
+    pub fn render(&self) -> String {
+        let column = |part: ReferencePart| {
+            format!(
+                "{}{}",
+                if part.absolute { "$" } else { "" },
+                string_from_column_index(&part.index)
+            )
+        };
+        let row =
+            |part: ReferencePart| format!("{}{}", if part.absolute { "$" } else { "" }, part.index);
+
+        let area = match self.area {
+            ReferenceArea::Cells { start, end } => {
+                let start = format!("{}{}", column(start.0), row(start.1));
+                match end {
+                    Some(end) => format!("{}:{}{}", start, column(end.0), row(end.1)),
+                    None => start,
+                }
+            }
+            ReferenceArea::Columns { start, end } => format!("{}:{}", column(start), column(end)),
+            ReferenceArea::Rows { start, end } => format!("{}:{}", row(start), row(end)),
+        };
+
+        match &self.sheet {
+            Some(sheet) => format!("{}!{}", quote_sheet_name(sheet), area),
+            None => area,
+        }
+    }
+}
+
+fn shift_part(part: ReferencePart, offset: i64, max: u32) -> Option<ReferencePart> {
+    if part.absolute {
+        return Some(part);
+    }
+    let index = part.index as i64 + offset;
+    if index < 1 || index > max as i64 {
+        return None;
+    }
+    Some(ReferencePart {
+        index: index as u32,
+        absolute: false,
+    })
+}
+
+/// Quotes a sheet name for use in a formula when Excel requires it.
+///
+/// Names made of letters, digits, `_` and `.` that do not start with a digit and
+/// do not look like a cell reference are written as they are.
+pub fn quote_sheet_name(sheet_name: &str) -> String {
+    let plain = !sheet_name.is_empty()
+        && sheet_name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        && !sheet_name.starts_with(|c: char| c.is_ascii_digit())
+        && parse_area(&sheet_name.chars().collect::<Vec<_>>(), 0)
+            .is_none_or(|(_, end)| end != sheet_name.chars().count());
+
+    if plain {
+        sheet_name.to_string()
+    } else {
+        format!("'{}'", sheet_name.replace('\'', "''"))
+    }
+}
+
+/// Rewrites every cell reference of a formula.
+///
+/// `map` receives each reference and returns its replacement, or `None` to write
+/// `#REF!`. String literals, structured table references, function names and
+/// defined names are left alone.
+pub fn map_references<F>(formula: &str, mut map: F) -> String
+where
+    F: FnMut(&Reference) -> Option<Reference>,
+{
+    let chars: Vec<char> = formula.chars().collect();
+    let mut result = String::with_capacity(formula.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let at_boundary = i == 0 || !is_name_char(chars[i - 1]);
+
+        if c == '"' {
+            let end = string_end(&chars, i);
+            result.extend(&chars[i..end]);
+            i = end;
+        } else if c == '[' {
+            let end = bracket_end(&chars, i);
+            result.extend(&chars[i..end]);
+            i = end;
+        } else if c == '\'' && at_boundary {
+            let end = string_end(&chars, i);
+            let sheet: String = chars[i + 1..end.saturating_sub(1).max(i + 1)]
+                .iter()
+                .collect::<String>()
+                .replace("''", "'");
+            match chars.get(end) {
+                Some('!') => match parse_area(&chars, end + 1) {
+                    Some((area, area_end)) => {
+                        result.push_str(&render_mapped(Some(sheet), area, &mut map));
+                        i = area_end;
+                    }
+                    None => {
+                        result.extend(&chars[i..end + 1]);
+                        i = end + 1;
+                    }
+                },
+                _ => {
+                    result.extend(&chars[i..end]);
+                    i = end;
+                }
+            }
+        } else if at_boundary && (is_name_char(c) || c == '$') {
+            let name_end = (i..chars.len())
+                .find(|&j| !(is_name_char(chars[j]) || chars[j] == '$'))
+                .unwrap_or(chars.len());
+
+            if chars.get(name_end) == Some(&'!') && !chars[i..name_end].contains(&'$') {
+                let sheet: String = chars[i..name_end].iter().collect();
+                if let Some((area, area_end)) = parse_area(&chars, name_end + 1) {
+                    result.push_str(&render_mapped(Some(sheet), area, &mut map));
+                    i = area_end;
+                    continue;
+                }
+            } else if let Some((area, area_end)) = parse_area(&chars, i) {
+                result.push_str(&render_mapped(None, area, &mut map));
+                i = area_end;
+                continue;
+            }
+            result.extend(&chars[i..name_end]);
+            i = name_end;
+        } else {
+            result.push(c);
+            i += 1;
+        }
+    }
+
+    result
+}
+
+/// Moves the relative references of a formula, as Excel does when a formula is
+/// copied `column_offset` columns and `row_offset` rows away.
+pub fn shift_formula(formula: &str, column_offset: i64, row_offset: i64) -> String {
+    if column_offset == 0 && row_offset == 0 {
+        return formula.to_string();
+    }
+    map_references(formula, |reference| {
+        reference.shifted(column_offset, row_offset)
+    })
+}
+
+/// Points the references of a formula on sheet `old_name` at `new_name`.
+///
+/// Sheet names are matched case-insensitively, as Excel does, and the new name is
+/// quoted when it needs to be.
+pub fn rename_sheet_references(formula: &str, old_name: &str, new_name: &str) -> String {
+    let old_name = old_name.to_lowercase();
+    map_references(formula, |reference| {
+        let mut reference = reference.clone();
+        if reference
+            .sheet
+            .as_ref()
+            .is_some_and(|sheet| sheet.to_lowercase() == old_name)
+        {
+            reference.sheet = Some(new_name.to_string());
+        }
+        Some(reference)
+    })
+}
+
+/// Renames the table of structured references such as `Sales[Qty]` or `Sales[#All]`.
+///
+/// Table names are matched case-insensitively, as Excel does; string literals and
+/// quoted sheet names are left alone.
+pub fn rename_table_references(formula: &str, old_name: &str, new_name: &str) -> String {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut result = String::with_capacity(formula.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let at_boundary = i == 0 || !is_name_char(chars[i - 1]);
+
+        if c == '"' || (c == '\'' && at_boundary) {
+            let end = string_end(&chars, i);
+            result.extend(&chars[i..end]);
+            i = end;
+        } else if c == '[' {
+            let end = bracket_end(&chars, i);
+            result.extend(&chars[i..end]);
+            i = end;
+        } else if at_boundary && is_name_char(c) {
+            let name_end = (i..chars.len())
+                .find(|&j| !is_name_char(chars[j]))
+                .unwrap_or(chars.len());
+            let name: String = chars[i..name_end].iter().collect();
+            if chars.get(name_end) == Some(&'[') && name.eq_ignore_ascii_case(old_name) {
+                result.push_str(new_name);
+            } else {
+                result.push_str(&name);
+            }
+            i = name_end;
+        } else {
+            result.push(c);
+            i += 1;
+        }
+    }
+
+    result
+}
+
+/// Parses the reference starting at `start`, with its sheet name when it has one
+/// (`A1`, `$A$1:$B$2`, `Data!C:C` or `'My Sheet'!3:5`), returning it with the
+/// index after it.
+pub fn reference_at(chars: &[char], start: usize) -> Option<(Reference, usize)> {
+    if chars.get(start) == Some(&'\'') {
+        let end = string_end(chars, start);
+        if chars.get(end) != Some(&'!') || end < start + 2 {
+            return None;
+        }
+        let sheet: String = chars[start + 1..end - 1]
+            .iter()
+            .collect::<String>()
+            .replace("''", "'");
+        let (area, area_end) = parse_area(chars, end + 1)?;
+        let sheet = Some(sheet);
+        return Some((Reference { sheet, area }, area_end));
+    }
+
+    let name_end = (start..chars.len())
+        .find(|&i| !(is_name_char(chars[i]) || chars[i] == '$'))
+        .unwrap_or(chars.len());
+    if chars.get(name_end) == Some(&'!') && !chars[start..name_end].contains(&'$') {
+        let sheet = Some(chars[start..name_end].iter().collect());
+        let (area, area_end) = parse_area(chars, name_end + 1)?;
+        return Some((Reference { sheet, area }, area_end));
+    }
+    let (area, area_end) = parse_area(chars, start)?;
+    Some((Reference { sheet: None, area }, area_end))
+}
+
+fn render_mapped<F>(sheet: Option<String>, area: ReferenceArea, map: &mut F) -> String
+where
+    F: FnMut(&Reference) -> Option<Reference>,
+{
+    let reference = Reference { sheet, area };
+    match map(&reference) {
+        Some(mapped) => mapped.render(),
+        None => match &reference.sheet {
+            Some(sheet) => format!("{}!#REF!", quote_sheet_name(sheet)),
+            None => "#REF!".to_string(),
+        },
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '\\' || c == '?'
+}
+
+/// Returns the index after the quoted text starting at `start`, with doubled
+/// quotes as escapes.
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Returns the index after the bracketed block starting at `start`, e.g. `[[#This Row],[Qty]]`.
+fn bracket_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => (),
+        }
+    }
+    chars.len()
+}
+
+/// Parses the area of a reference at `start`, returning it with the index after it.
+///
+/// The area must not run into more name characters or an opening parenthesis, so
+/// that names such as `LOG10(` or `TAX2023A` are not taken for references.
+fn parse_area(chars: &[char], start: usize) -> Option<(ReferenceArea, usize)> {
+    let ends_cleanly = |end: usize| {
+        chars
+            .get(end)
+            .is_none_or(|&c| !(is_name_char(c) || c == '$' || c == '(' || c == '!'))
+    };
+
+    if let Some((start_cell, cell_end)) = parse_cell(chars, start) {
+        if chars.get(cell_end) == Some(&':') {
+            if let Some((end_cell, end)) = parse_cell(chars, cell_end + 1) {
+                if ends_cleanly(end) {
+                    let area = ReferenceArea::Cells {
+                        start: start_cell,
+                        end: Some(end_cell),
+                    };
+                    return Some((area, end));
+                }
+            }
+        }
+        return ends_cleanly(cell_end).then_some((
+            ReferenceArea::Cells {
+                start: start_cell,
+                end: None,
+            },
+            cell_end,
+        ));
+    }
+
+    if let Some((start_column, column_end)) = parse_column(chars, start) {
+        if chars.get(column_end) == Some(&':') {
+            if let Some((end_column, end)) = parse_column(chars, column_end + 1) {
+                if ends_cleanly(end) {
+                    let area = ReferenceArea::Columns {
+                        start: start_column,
+                        end: end_column,
+                    };
+                    return Some((area, end));
+                }
+            }
+        }
+        return None;
+    }
+
+    let (start_row, row_end) = parse_row(chars, start)?;
+    if chars.get(row_end) != Some(&':') {
+        return None;
+    }
+    let (end_row, end) = parse_row(chars, row_end + 1)?;
+    ends_cleanly(end).then_some((
+        ReferenceArea::Rows {
+            start: start_row,
+            end: end_row,
+        },
+        end,
+    ))
+}
+
+fn parse_cell(chars: &[char], start: usize) -> Option<((ReferencePart, ReferencePart), usize)> {
+    let (column, column_end) = parse_column(chars, start)?;
+    let (row, end) = parse_row(chars, column_end)?;
+    Some(((column, row), end))
+}
+
+fn parse_column(chars: &[char], start: usize) -> Option<(ReferencePart, usize)> {
+    let absolute = chars.get(start) == Some(&'$');
+    let letters_start = start + absolute as usize;
+    let letters_end = (letters_start..chars.len())
+        .find(|&i| !chars[i].is_ascii_alphabetic())
+        .unwrap_or(chars.len());
+    let letters = letters_end - letters_start;
+    if letters == 0 || letters > 3 {
+        return None;
+    }
+
+    let name: String = chars[letters_start..letters_end].iter().collect();
+    let index = column_index_from_string(name.to_uppercase());
+    (index <= MAX_COLUMN).then_some((ReferencePart { index, absolute }, letters_end))
+}
+
+fn parse_row(chars: &[char], start: usize) -> Option<(ReferencePart, usize)> {
+    let absolute = chars.get(start) == Some(&'$');
+    let digits_start = start + absolute as usize;
+    let digits_end = (digits_start..chars.len())
+        .find(|&i| !chars[i].is_ascii_digit())
+        .unwrap_or(chars.len());
+    if digits_end == digits_start || digits_end - digits_start > 7 {
+        return None;
+    }
+
+    let digits: String = chars[digits_start..digits_end].iter().collect();
+    let index: u32 = digits.parse().ok()?;
+    (1..=MAX_ROW)
+        .contains(&index)
+        .then_some((ReferencePart { index, absolute }, digits_end))
+}
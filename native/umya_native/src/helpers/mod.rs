@@ -1,7 +1,26 @@
 pub mod alignment_helper;
+pub mod autofit_helper;
 pub mod cell_helpers;
 pub mod color_helper;
+pub mod conditional_extension_helper;
+pub mod conditional_format_helper;
+pub mod crypt_helper;
+pub mod data_validation_check_helper;
+pub mod data_validation_helper;
+pub mod defined_name_helper;
+pub mod dimension_helper;
 pub mod error_helper;
 pub mod format_helper;
+pub mod formula_evaluation_helper;
+pub mod formula_helper;
+pub mod named_style_helper;
+pub mod outline_helper;
+pub mod package_helper;
 pub mod path_helper;
+pub mod protection_helper;
+pub mod range_helper;
+pub mod row_column_helper;
+pub mod sheet_property_helper;
+pub mod sheet_reference_helper;
 pub mod style_helpers;
+pub mod style_map_helper;
@@ -0,0 +1,1346 @@
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::str::FromStr;
+use umya_spreadsheet::{
+    Alignment, Border, BorderStyleValues, Color, EnumTrait, Fill, Font, FontCharSet,
+    FontFamilyNumbering, FontScheme, FontSize, HorizontalAlignmentValues, NumberingFormat,
+    PatternValues, Protection, Style, Underline, VerticalAlignmentValues,
+};
+
+/// Number format id umya-spreadsheet gives format codes that are not built in.
+pub const CUSTOM_NUMBER_FORMAT_ID: u32 = 999999;
+/// First number format id available to custom formats.
+pub const FIRST_CUSTOM_NUMBER_FORMAT_ID: u32 = 164;
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Built-in number formats (ECMA-376 Part 1, 18.8.30) that need no `<numFmt>`.
+///
+/// Dates use the codes umya-spreadsheet assigns to the same ids.
+const BUILTIN_NUMBER_FORMATS: [(u32, &str); 30] = [
+    (0, "General"),
+    (1, "0"),
+    (2, "0.00"),
+    (3, "#,##0"),
+    (4, "#,##0.00"),
+    (9, "0%"),
+    (10, "0.00%"),
+    (11, "0.00E+00"),
+    (12, "# ?/?"),
+    (13, "# ??/??"),
+    (14, "m/d/yyyy"),
+    (15, "d-mmm-yy"),
+    (16, "d-mmm"),
+    (17, "mmm-yy"),
+    (18, "h:mm AM/PM"),
+    (19, "h:mm:ss AM/PM"),
+    (20, "h:mm"),
+    (21, "h:mm:ss"),
+    (22, "m/d/yyyy h:mm"),
+    (37, "#,##0_);(#,##0)"),
+    (38, "#,##0_);[Red](#,##0)"),
+    (39, "#,##0.00_);(#,##0.00)"),
+    (40, "#,##0.00_);[Red](#,##0.00)"),
+    (41, r#"_(* #,##0_);_(* \(#,##0\);_(* "-"_);_(@_)"#),
+    (42, r#"_("$"* #,##0_);_("$"* \(#,##0\);_("$"* "-"_);_(@_)"#),
+    (43, r#"_(* #,##0.00_);_(* \(#,##0.00\);_(* "-"??_);_(@_)"#),
+    (
+        44,
+        r#"_("$"* #,##0.00_);_("$"* \(#,##0.00\);_("$"* "-"??_);_(@_)"#,
+    ),
+    (45, "mm:ss"),
+    (46, "[h]:mm:ss"),
+    (49, "@"),
+];
+
+/// A workbook-level named cell style, such as "Normal", "Heading 1" or "Currency".
+#[derive(Clone, Debug)]
+pub struct NamedStyle {
+    pub name: String,
+    /// Id of a built-in style, `None` for custom styles
+    pub builtin_id: Option<u32>,
+    /// Index of the style's `<xf>` in `cellStyleXfs`, the `xfId` of cells using it
+    pub format_id: u32,
+    /// Formatting the style defines; parts it does not define are `None`
+    pub style: Style,
+    /// Whether the style's `<xf>` has to be written into the package
+    modified: bool,
+}
+
+impl NamedStyle {
+    /// Writes the parts the named style defines into `target` and links it to the style.
+    ///
+    /// As in Excel, parts the style does not define keep the cell's own formatting.
+    pub fn apply_to(&self, target: &mut Style) {
+        if let Some(font) = self.style.get_font() {
+            target.set_font(font.clone());
+        }
+        if let Some(fill) = self.style.get_fill() {
+            target.set_fill(fill.clone());
+        }
+        if let Some(borders) = self.style.get_borders() {
+            target.set_borders(borders.clone());
+        }
+        if let Some(alignment) = self.style.get_alignment() {
+            target.set_alignment(alignment.clone());
+        }
+        if let Some(number_format) = self.style.get_numbering_format() {
+            target.set_numbering_format(number_format.clone());
+        }
+        if let Some(protection) = self.style.get_protection() {
+            target.set_protection(protection.clone());
+        }
+        target.set_format_id(self.format_id);
+    }
+}
+
+/// The named cell styles of a workbook.
+///
+/// umya-spreadsheet 2.3.0 keeps `cellStyleXfs` and `cellStyles` private and writes
+/// every new cell format with `xfId="0"`, so named styles are read from and
+/// patched into styles.xml.
+#[derive(Clone, Debug, Default)]
+pub struct NamedStyles {
+    styles: Vec<NamedStyle>,
+    /// Number of `<xf>` elements in `cellStyleXfs`
+    format_count: u32,
+    /// Whether styles.xml has to be patched when the workbook is written
+    changed: bool,
+}
+
+impl NamedStyles {
+    /// Reads the named styles of a styles.xml part.
+    pub fn read(styles_xml: &str) -> Result<Self, String> {
+        let parts = StylesheetParts::read(styles_xml)?;
+
+        let styles = parts
+            .cell_styles
+            .iter()
+            .map(|(name, format_id, builtin_id)| {
+                let mut style = parts
+                    .style_formats
+                    .get(*format_id as usize)
+                    .map(|format| format.to_style(&parts))
+                    .unwrap_or_default();
+                style.set_format_id(*format_id);
+                NamedStyle {
+                    name: name.clone(),
+                    builtin_id: *builtin_id,
+                    format_id: *format_id,
+                    style,
+                    modified: false,
+                }
+            })
+            .collect();
+
+        Ok(NamedStyles {
+            styles,
+            format_count: parts.style_formats.len() as u32,
+            changed: false,
+        })
+    }
+
+    /// Returns true when styles.xml has to be patched on write.
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Records that cells were linked to named styles since the workbook was read.
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    pub fn styles(&self) -> &[NamedStyle] {
+        &self.styles
+    }
+
+    /// Finds a named style, ignoring case as Excel does.
+    pub fn find(&self, name: &str) -> Option<&NamedStyle> {
+        self.styles
+            .iter()
+            .find(|style| style.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Formatting new styles start from: that of "Normal", or Excel's defaults.
+    pub fn base_style(&self) -> Style {
+        let mut style = self
+            .styles
+            .iter()
+            .find(|style| style.builtin_id == Some(0))
+            .map(|normal| normal.style.clone())
+            .unwrap_or_else(normal_style);
+        style.set_format_id(0);
+        style
+    }
+
+    /// Adds a custom named style and returns it.
+    ///
+    /// A workbook without named styles first gets "Normal", which Excel expects at
+    /// `xfId` 0.
+    pub fn add(&mut self, name: &str, mut style: Style) -> Result<&NamedStyle, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Named style name cannot be empty".to_string());
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!(
+                "Named style name cannot be longer than {} characters",
+                MAX_NAME_LENGTH
+            ));
+        }
+        if self.find(name).is_some() {
+            return Err(format!("Named style '{}' already exists", name));
+        }
+
+        if self.format_count == 0 {
+            self.styles.push(NamedStyle {
+                name: "Normal".to_string(),
+                builtin_id: Some(0),
+                format_id: 0,
+                style: normal_style(),
+                modified: true,
+            });
+            self.format_count = 1;
+        }
+
+        let format_id = self.format_count;
+        style.set_format_id(format_id);
+        self.styles.push(NamedStyle {
+            name: name.to_string(),
+            builtin_id: None,
+            format_id,
+            style,
+            modified: true,
+        });
+        self.format_count += 1;
+        self.changed = true;
+
+        Ok(self.styles.last().unwrap())
+    }
+
+    /// Changes the formatting of a named style and returns its `xfId`.
+    pub fn update<F: Fn(&mut Style)>(&mut self, name: &str, update: F) -> Result<u32, String> {
+        let named_style = self
+            .styles
+            .iter_mut()
+            .find(|style| style.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Named style '{}' not found", name))?;
+
+        update(&mut named_style.style);
+        named_style.style.set_format_id(named_style.format_id);
+        named_style.modified = true;
+        self.changed = true;
+
+        Ok(named_style.format_id)
+    }
+
+    /// Writes new and modified named styles into a styles.xml part written by
+    /// umya-spreadsheet.
+    ///
+    /// `cell_format_ids` maps `cellXfs` indexes to the `xfId` their cells link to.
+    pub fn patch_styles_xml(
+        &self,
+        xml: &str,
+        cell_format_ids: &HashMap<usize, u32>,
+    ) -> Result<String, String> {
+        let counts = ElementCounts::read(xml)?;
+        let additions = Additions::new(&self.styles, &counts);
+
+        let mut reader = Reader::from_str(xml);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let mut section = Section::Other;
+        let mut xf_index = 0usize;
+        let mut skip_until: Option<&'static str> = None;
+
+        loop {
+            let event = reader.read_event().map_err(|e| e.to_string())?;
+
+            if let Some(end) = skip_until {
+                if matches!(&event, Event::End(e) if e.name().as_ref() == end.as_bytes()) {
+                    skip_until = None;
+                }
+                continue;
+            }
+
+            match &event {
+                Event::Start(e) | Event::Empty(e) => {
+                    let is_empty = matches!(event, Event::Empty(_));
+                    match e.name().as_ref() {
+                        b"fonts" if !counts.has_num_fmts => {
+                            additions.write_num_fmts(&mut writer)?;
+                        }
+                        b"cellXfs" if !counts.has_style_formats => {
+                            self.write_style_formats(&mut writer, &additions)?;
+                        }
+                        _ => (),
+                    }
+
+                    match e.name().as_ref() {
+                        b"numFmts" if !is_empty => {
+                            let count = counts.num_fmts + additions.num_fmts.len();
+                            write(&mut writer, Event::Start(with_count(e, count)))?;
+                            continue;
+                        }
+                        b"fonts" | b"fills" | b"borders" if !is_empty => {
+                            let count = match e.name().as_ref() {
+                                b"fonts" => counts.fonts + additions.fonts.len(),
+                                b"fills" => counts.fills + additions.fills.len(),
+                                _ => counts.borders + additions.borders.len(),
+                            };
+                            write(&mut writer, Event::Start(with_count(e, count)))?;
+                            continue;
+                        }
+                        b"cellStyleXfs" if !is_empty => {
+                            section = Section::StyleFormats;
+                            xf_index = 0;
+                            let count = counts.style_formats.max(self.format_count as usize);
+                            write(&mut writer, Event::Start(with_count(e, count)))?;
+                            continue;
+                        }
+                        b"cellStyleXfs" => {
+                            self.write_style_formats(&mut writer, &additions)?;
+                            continue;
+                        }
+                        b"cellXfs" if !is_empty => {
+                            section = Section::CellFormats;
+                            xf_index = 0;
+                        }
+                        b"cellStyles" => {
+                            self.write_cell_styles(&mut writer)?;
+                            if !is_empty {
+                                skip_until = Some("cellStyles");
+                            }
+                            continue;
+                        }
+                        b"xf" if section == Section::StyleFormats => {
+                            let index = xf_index;
+                            xf_index += 1;
+                            if let Some(named_style) = self.modified_style(index as u32) {
+                                write_style_format(&mut writer, named_style, &additions)?;
+                                if !is_empty {
+                                    skip_until = Some("xf");
+                                }
+                                continue;
+                            }
+                        }
+                        b"xf" if section == Section::CellFormats => {
+                            let index = xf_index;
+                            xf_index += 1;
+                            if let Some(format_id) = cell_format_ids.get(&index) {
+                                let patched = with_format_id(e, *format_id);
+                                let patched = if is_empty {
+                                    Event::Empty(patched)
+                                } else {
+                                    Event::Start(patched)
+                                };
+                                write(&mut writer, patched)?;
+                                continue;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                Event::End(e) => match e.name().as_ref() {
+                    b"numFmts" => {
+                        additions.write_num_fmt_elements(&mut writer)?;
+                    }
+                    b"fonts" => {
+                        for font in &additions.fonts {
+                            write_font(&mut writer, font)?;
+                        }
+                    }
+                    b"fills" => {
+                        for fill in &additions.fills {
+                            write_fill(&mut writer, fill)?;
+                        }
+                    }
+                    b"borders" => {
+                        for borders in &additions.borders {
+                            write_borders(&mut writer, borders)?;
+                        }
+                    }
+                    b"cellStyleXfs" => {
+                        section = Section::Other;
+                        for named_style in self.new_styles(counts.style_formats as u32) {
+                            write_style_format(&mut writer, named_style, &additions)?;
+                        }
+                    }
+                    b"cellXfs" => {
+                        section = Section::Other;
+                        write(&mut writer, event.clone())?;
+                        if !counts.has_cell_styles {
+                            self.write_cell_styles(&mut writer)?;
+                        }
+                        continue;
+                    }
+                    _ => (),
+                },
+                Event::Eof => break,
+                _ => (),
+            }
+
+            write(&mut writer, event)?;
+        }
+
+        String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())
+    }
+
+    fn modified_style(&self, format_id: u32) -> Option<&NamedStyle> {
+        self.styles
+            .iter()
+            .find(|style| style.modified && style.format_id == format_id)
+    }
+
+    /// Styles whose `<xf>` is not in styles.xml yet, in `xfId` order.
+    fn new_styles(&self, existing: u32) -> Vec<&NamedStyle> {
+        let mut styles: Vec<&NamedStyle> = self
+            .styles
+            .iter()
+            .filter(|style| style.format_id >= existing)
+            .collect();
+        styles.sort_by_key(|style| style.format_id);
+        styles
+    }
+
+    /// Writes a whole `cellStyleXfs` element for a part that has none.
+    fn write_style_formats(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        additions: &Additions,
+    ) -> Result<(), String> {
+        let styles = self.new_styles(0);
+        if styles.is_empty() {
+            return Ok(());
+        }
+        let count = styles.len().to_string();
+        write(
+            writer,
+            Event::Start(
+                BytesStart::new("cellStyleXfs").with_attributes([("count", count.as_str())]),
+            ),
+        )?;
+        for named_style in styles {
+            write_style_format(writer, named_style, additions)?;
+        }
+        write(writer, Event::End(BytesEnd::new("cellStyleXfs")))
+    }
+
+    fn write_cell_styles(&self, writer: &mut Writer<Cursor<Vec<u8>>>) -> Result<(), String> {
+        if self.styles.is_empty() {
+            return Ok(());
+        }
+        let count = self.styles.len().to_string();
+        write(
+            writer,
+            Event::Start(
+                BytesStart::new("cellStyles").with_attributes([("count", count.as_str())]),
+            ),
+        )?;
+        for named_style in &self.styles {
+            let format_id = named_style.format_id.to_string();
+            let builtin_id = named_style.builtin_id.map(|id| id.to_string());
+            let mut element = BytesStart::new("cellStyle");
+            element.push_attribute(("name", named_style.name.as_str()));
+            element.push_attribute(("xfId", format_id.as_str()));
+            if let Some(builtin_id) = &builtin_id {
+                element.push_attribute(("builtinId", builtin_id.as_str()));
+            }
+            write(writer, Event::Empty(element))?;
+        }
+        write(writer, Event::End(BytesEnd::new("cellStyles")))
+    }
+}
+
+/// The formatting of Excel's "Normal" style in a new workbook.
+fn normal_style() -> Style {
+    let mut style = Style::get_default_value();
+    style.get_number_format_mut().set_number_format_id(0);
+    style
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Other,
+    NumFmts,
+    Fonts,
+    Fills,
+    Borders,
+    StyleFormats,
+    CellFormats,
+    CellStyles,
+}
+
+/// An `<xf>` of `cellStyleXfs`.
+#[derive(Clone, Debug, Default)]
+struct StyleFormat {
+    num_fmt_id: u32,
+    font_id: usize,
+    fill_id: usize,
+    border_id: usize,
+    apply_number_format: bool,
+    apply_font: bool,
+    apply_fill: bool,
+    apply_border: bool,
+    alignment: Option<Alignment>,
+    protection: Option<Protection>,
+}
+
+impl StyleFormat {
+    fn to_style(&self, parts: &StylesheetParts) -> Style {
+        let mut style = Style::default();
+        if self.apply_number_format {
+            if let Some(number_format) = parts.number_format(self.num_fmt_id) {
+                style.set_numbering_format(number_format);
+            }
+        }
+        if self.apply_font {
+            if let Some(font) = parts.fonts.get(self.font_id) {
+                style.set_font(font.clone());
+            }
+        }
+        if self.apply_fill {
+            if let Some(fill) = parts.fills.get(self.fill_id) {
+                style.set_fill(fill.clone());
+            }
+        }
+        if self.apply_border {
+            if let Some(borders) = parts
+                .borders
+                .get(self.border_id)
+                .and_then(|holder| holder.get_borders())
+            {
+                style.set_borders(borders.clone());
+            }
+        }
+        if let Some(alignment) = &self.alignment {
+            style.set_alignment(alignment.clone());
+        }
+        if let Some(protection) = &self.protection {
+            style.set_protection(protection.clone());
+        }
+        style
+    }
+}
+
+/// The parts of styles.xml that named styles are built from.
+#[derive(Default)]
+struct StylesheetParts {
+    num_fmts: HashMap<u32, String>,
+    fonts: Vec<Font>,
+    fills: Vec<Fill>,
+    /// Each `<border>` in a style of its own, as umya-spreadsheet does not export `Borders`
+    borders: Vec<Style>,
+    style_formats: Vec<StyleFormat>,
+    /// `(name, xfId, builtinId)` of each `<cellStyle>`
+    cell_styles: Vec<(String, u32, Option<u32>)>,
+}
+
+impl StylesheetParts {
+    fn read(xml: &str) -> Result<Self, String> {
+        let mut parts = StylesheetParts::default();
+        let mut reader = Reader::from_str(xml);
+        let mut section = Section::Other;
+        let mut font: Option<Font> = None;
+        let mut fill: Option<Fill> = None;
+        let mut borders: Option<Style> = None;
+        let mut border_side: Option<String> = None;
+        let mut style_format: Option<StyleFormat> = None;
+
+        loop {
+            let event = reader.read_event().map_err(|e| e.to_string())?;
+            let (e, is_empty) = match &event {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    match e.name().as_ref() {
+                        b"numFmts" | b"fonts" | b"fills" | b"borders" | b"cellStyleXfs"
+                        | b"cellStyles" => section = Section::Other,
+                        b"font" => parts.fonts.extend(font.take()),
+                        b"fill" => parts.fills.extend(fill.take()),
+                        b"border" => parts.borders.extend(borders.take()),
+                        b"xf" => parts.style_formats.extend(style_format.take()),
+                        b"left" | b"start" | b"right" | b"end" | b"top" | b"bottom"
+                        | b"diagonal" => border_side = None,
+                        _ => (),
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            match (section, e.name().as_ref()) {
+                (_, b"numFmts") if !is_empty => section = Section::NumFmts,
+                (_, b"fonts") if !is_empty => section = Section::Fonts,
+                (_, b"fills") if !is_empty => section = Section::Fills,
+                (_, b"borders") if !is_empty => section = Section::Borders,
+                (_, b"cellStyleXfs") if !is_empty => section = Section::StyleFormats,
+                (_, b"cellStyles") if !is_empty => section = Section::CellStyles,
+                (_, b"cellXfs") | (_, b"dxfs") if !is_empty => section = Section::Other,
+                (Section::NumFmts, b"numFmt") => {
+                    if let (Some(id), Some(code)) = (
+                        attribute(e, "numFmtId").and_then(|id| id.parse().ok()),
+                        attribute(e, "formatCode"),
+                    ) {
+                        parts.num_fmts.insert(id, code);
+                    }
+                }
+                (Section::Fonts, b"font") => {
+                    font = Some(Font::default());
+                    if is_empty {
+                        parts.fonts.extend(font.take());
+                    }
+                }
+                (Section::Fonts, name) => {
+                    if let Some(font) = font.as_mut() {
+                        read_font_property(font, name, e);
+                    }
+                }
+                (Section::Fills, b"fill") => {
+                    fill = Some(Fill::default());
+                    if is_empty {
+                        parts.fills.extend(fill.take());
+                    }
+                }
+                (Section::Fills, name) => {
+                    if let Some(fill) = fill.as_mut() {
+                        read_fill_property(fill, name, e);
+                    }
+                }
+                (Section::Borders, b"border") => {
+                    let mut holder = Style::default();
+                    let new_borders = holder.get_borders_mut();
+                    if bool_attribute(e, "diagonalUp") == Some(true) {
+                        new_borders.set_diagonal_up(true);
+                    }
+                    if bool_attribute(e, "diagonalDown") == Some(true) {
+                        new_borders.set_diagonal_down(true);
+                    }
+                    borders = Some(holder);
+                    if is_empty {
+                        parts.borders.extend(borders.take());
+                    }
+                }
+                (Section::Borders, b"color") => {
+                    if let (Some(borders), Some(side)) = (borders.as_mut(), &border_side) {
+                        border_mut(borders, side).set_color(read_color(e));
+                    }
+                }
+                (Section::Borders, name) => {
+                    if let Some(borders) = borders.as_mut() {
+                        let side = String::from_utf8_lossy(name).to_string();
+                        if let Some(style) = attribute(e, "style")
+                            .and_then(|style| BorderStyleValues::from_str(&style).ok())
+                        {
+                            border_mut(borders, &side).set_style(style);
+                        }
+                        if !is_empty {
+                            border_side = Some(side);
+                        }
+                    }
+                }
+                (Section::StyleFormats, b"xf") => {
+                    style_format = Some(read_style_format(e));
+                    if is_empty {
+                        parts.style_formats.extend(style_format.take());
+                    }
+                }
+                (Section::StyleFormats, b"alignment") => {
+                    if let Some(format) = style_format.as_mut() {
+                        format.alignment = Some(read_alignment(e));
+                    }
+                }
+                (Section::StyleFormats, b"protection") => {
+                    if let Some(format) = style_format.as_mut() {
+                        format.protection = Some(read_protection(e));
+                    }
+                }
+                (Section::CellStyles, b"cellStyle") => {
+                    let name = attribute(e, "name");
+                    let format_id = attribute(e, "xfId").and_then(|id| id.parse().ok());
+                    if let (Some(name), Some(format_id)) = (name, format_id) {
+                        let builtin_id = attribute(e, "builtinId").and_then(|id| id.parse().ok());
+                        parts.cell_styles.push((name, format_id, builtin_id));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(parts)
+    }
+
+    /// Resolves a number format id to a custom `<numFmt>` or a built-in format.
+    fn number_format(&self, id: u32) -> Option<NumberingFormat> {
+        let code = match self.num_fmts.get(&id) {
+            Some(code) => code.as_str(),
+            None => {
+                BUILTIN_NUMBER_FORMATS
+                    .iter()
+                    .find(|(builtin_id, _)| *builtin_id == id)?
+                    .1
+            }
+        };
+        let mut number_format = NumberingFormat::default();
+        number_format.set_format_code(code);
+        Some(number_format)
+    }
+}
+
+fn read_font_property(font: &mut Font, name: &[u8], e: &BytesStart) {
+    let val = attribute(e, "val");
+    match name {
+        b"b" => {
+            font.set_bold(bool_attribute(e, "val").unwrap_or(true));
+        }
+        b"i" => {
+            font.set_italic(bool_attribute(e, "val").unwrap_or(true));
+        }
+        b"strike" => {
+            font.set_strikethrough(bool_attribute(e, "val").unwrap_or(true));
+        }
+        b"u" => {
+            let underline = val.unwrap_or_else(|| "single".to_string());
+            if [
+                "single",
+                "double",
+                "singleAccounting",
+                "doubleAccounting",
+                "none",
+            ]
+            .contains(&underline.as_str())
+            {
+                font.set_underline(underline);
+            }
+        }
+        b"sz" => {
+            if let Some(size) = val.and_then(|size| size.parse().ok()) {
+                font.set_size(size);
+            }
+        }
+        b"color" => {
+            font.set_color(read_color(e));
+        }
+        b"name" => {
+            if let Some(name) = val {
+                font.set_name(name);
+            }
+        }
+        b"family" => {
+            if let Some(family) = val.and_then(|family| family.parse().ok()) {
+                font.set_family(family);
+            }
+        }
+        b"charset" => {
+            if let Some(charset) = val.and_then(|charset| charset.parse().ok()) {
+                font.set_charset(charset);
+            }
+        }
+        b"scheme" => {
+            if let Some(scheme) =
+                val.filter(|scheme| ["major", "minor", "none"].contains(&scheme.as_str()))
+            {
+                font.set_scheme(scheme);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn read_fill_property(fill: &mut Fill, name: &[u8], e: &BytesStart) {
+    match name {
+        b"patternFill" => {
+            let pattern = attribute(e, "patternType")
+                .and_then(|pattern| PatternValues::from_str(&pattern).ok())
+                .unwrap_or(PatternValues::None);
+            fill.get_pattern_fill_mut().set_pattern_type(pattern);
+        }
+        b"fgColor" => {
+            fill.get_pattern_fill_mut()
+                .set_foreground_color(read_color(e));
+        }
+        b"bgColor" => {
+            fill.get_pattern_fill_mut()
+                .set_background_color(read_color(e));
+        }
+        _ => (),
+    }
+}
+
+fn read_style_format(e: &BytesStart) -> StyleFormat {
+    let id = |key: &str| {
+        attribute(e, key)
+            .and_then(|id| id.parse().ok())
+            .unwrap_or(0)
+    };
+    let apply = |key: &str| bool_attribute(e, key).unwrap_or(true);
+    StyleFormat {
+        num_fmt_id: id("numFmtId") as u32,
+        font_id: id("fontId"),
+        fill_id: id("fillId"),
+        border_id: id("borderId"),
+        apply_number_format: apply("applyNumberFormat"),
+        apply_font: apply("applyFont"),
+        apply_fill: apply("applyFill"),
+        apply_border: apply("applyBorder"),
+        alignment: None,
+        protection: None,
+    }
+}
+
+fn read_alignment(e: &BytesStart) -> Alignment {
+    let mut alignment = Alignment::default();
+    if let Some(horizontal) = attribute(e, "horizontal")
+        .and_then(|value| HorizontalAlignmentValues::from_str(&value).ok())
+    {
+        alignment.set_horizontal(horizontal);
+    }
+    if let Some(vertical) =
+        attribute(e, "vertical").and_then(|value| VerticalAlignmentValues::from_str(&value).ok())
+    {
+        alignment.set_vertical(vertical);
+    }
+    if let Some(wrap_text) = bool_attribute(e, "wrapText") {
+        alignment.set_wrap_text(wrap_text);
+    }
+    if let Some(rotation) = attribute(e, "textRotation").and_then(|value| value.parse().ok()) {
+        alignment.set_text_rotation(rotation);
+    }
+    alignment
+}
+
+fn read_protection(e: &BytesStart) -> Protection {
+    let mut protection = Protection::default();
+    if let Some(locked) = bool_attribute(e, "locked") {
+        protection.set_locked(locked);
+    }
+    if let Some(hidden) = bool_attribute(e, "hidden") {
+        protection.set_hidden(hidden);
+    }
+    protection
+}
+
+fn read_color(e: &BytesStart) -> Color {
+    let mut color = Color::default();
+    if let Some(theme) = attribute(e, "theme").and_then(|value| value.parse().ok()) {
+        color.set_theme_index(theme);
+    } else if let Some(indexed) = attribute(e, "indexed").and_then(|value| value.parse().ok()) {
+        color.set_indexed(indexed);
+    } else if let Some(argb) = attribute(e, "rgb") {
+        color.set_argb(argb.to_uppercase());
+    }
+    if let Some(tint) = attribute(e, "tint").and_then(|value| value.parse().ok()) {
+        color.set_tint(tint);
+    }
+    color
+}
+
+fn border_mut<'a>(holder: &'a mut Style, side: &str) -> &'a mut Border {
+    let borders = holder.get_borders_mut();
+    match side {
+        "left" | "start" => borders.get_left_mut(),
+        "right" | "end" => borders.get_right_mut(),
+        "top" => borders.get_top_mut(),
+        "bottom" => borders.get_bottom_mut(),
+        _ => borders.get_diagonal_mut(),
+    }
+}
+
+fn attribute(e: &BytesStart, key: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == key.as_bytes())
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+fn bool_attribute(e: &BytesStart, key: &str) -> Option<bool> {
+    match attribute(e, key)?.as_str() {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Element counts of a written styles.xml, and the custom number formats in it.
+#[derive(Default)]
+struct ElementCounts {
+    has_num_fmts: bool,
+    has_style_formats: bool,
+    has_cell_styles: bool,
+    num_fmts: usize,
+    custom_num_fmts: HashMap<String, u32>,
+    fonts: usize,
+    fills: usize,
+    borders: usize,
+    style_formats: usize,
+}
+
+impl ElementCounts {
+    fn read(xml: &str) -> Result<Self, String> {
+        let mut counts = ElementCounts::default();
+        let mut reader = Reader::from_str(xml);
+        let mut section = Section::Other;
+
+        loop {
+            let event = reader.read_event().map_err(|e| e.to_string())?;
+            let (e, is_empty) = match &event {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    if matches!(
+                        e.name().as_ref(),
+                        b"numFmts" | b"fonts" | b"fills" | b"borders" | b"cellStyleXfs"
+                    ) {
+                        section = Section::Other;
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            match (section, e.name().as_ref()) {
+                (_, b"numFmts") if !is_empty => {
+                    counts.has_num_fmts = true;
+                    section = Section::NumFmts;
+                }
+                (_, b"fonts") if !is_empty => section = Section::Fonts,
+                (_, b"fills") if !is_empty => section = Section::Fills,
+                (_, b"borders") if !is_empty => section = Section::Borders,
+                (_, b"cellStyleXfs") if !is_empty => {
+                    counts.has_style_formats = true;
+                    section = Section::StyleFormats;
+                }
+                (_, b"cellStyles") => counts.has_cell_styles = true,
+                (Section::NumFmts, b"numFmt") => {
+                    counts.num_fmts += 1;
+                    if let (Some(id), Some(code)) = (
+                        attribute(e, "numFmtId").and_then(|id| id.parse().ok()),
+                        attribute(e, "formatCode"),
+                    ) {
+                        counts.custom_num_fmts.insert(code, id);
+                    }
+                }
+                (Section::Fonts, b"font") => counts.fonts += 1,
+                (Section::Fills, b"fill") => counts.fills += 1,
+                (Section::Borders, b"border") => counts.borders += 1,
+                (Section::StyleFormats, b"xf") => counts.style_formats += 1,
+                _ => (),
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+/// Fonts, fills, borders and number formats appended for modified named styles.
+struct Additions {
+    fonts: Vec<Font>,
+    fills: Vec<Fill>,
+    borders: Vec<Style>,
+    num_fmts: Vec<(u32, String)>,
+    /// `(numFmtId, fontId, fillId, borderId)` keyed by `xfId`
+    ids: HashMap<u32, (u32, usize, usize, usize)>,
+}
+
+impl Additions {
+    fn new(styles: &[NamedStyle], counts: &ElementCounts) -> Self {
+        let mut additions = Additions {
+            fonts: Vec::new(),
+            fills: Vec::new(),
+            borders: Vec::new(),
+            num_fmts: Vec::new(),
+            ids: HashMap::new(),
+        };
+        let mut next_num_fmt_id = counts
+            .custom_num_fmts
+            .values()
+            .copied()
+            .max()
+            .map_or(FIRST_CUSTOM_NUMBER_FORMAT_ID, |id| {
+                (id + 1).max(FIRST_CUSTOM_NUMBER_FORMAT_ID)
+            });
+
+        for named_style in styles.iter().filter(|style| style.modified) {
+            let style = &named_style.style;
+            let num_fmt_id = match style.get_numbering_format() {
+                Some(number_format) => {
+                    let code = number_format.get_format_code();
+                    let id = *number_format.get_number_format_id();
+                    if id != CUSTOM_NUMBER_FORMAT_ID && id < FIRST_CUSTOM_NUMBER_FORMAT_ID {
+                        id
+                    } else if let Some((id, _)) = BUILTIN_NUMBER_FORMATS
+                        .iter()
+                        .find(|(_, builtin)| *builtin == code)
+                    {
+                        *id
+                    } else if let Some(id) = counts.custom_num_fmts.get(code) {
+                        *id
+                    } else if let Some((id, _)) =
+                        additions.num_fmts.iter().find(|(_, added)| added == code)
+                    {
+                        *id
+                    } else {
+                        additions.num_fmts.push((next_num_fmt_id, code.to_string()));
+                        next_num_fmt_id += 1;
+                        next_num_fmt_id - 1
+                    }
+                }
+                None => 0,
+            };
+            let font_id = match style.get_font() {
+                Some(font) => {
+                    additions.fonts.push(font.clone());
+                    counts.fonts + additions.fonts.len() - 1
+                }
+                None => 0,
+            };
+            let fill_id = match style.get_fill() {
+                Some(fill) => {
+                    additions.fills.push(fill.clone());
+                    counts.fills + additions.fills.len() - 1
+                }
+                None => 0,
+            };
+            let border_id = match style.get_borders() {
+                Some(borders) => {
+                    let mut holder = Style::default();
+                    holder.set_borders(borders.clone());
+                    additions.borders.push(holder);
+                    counts.borders + additions.borders.len() - 1
+                }
+                None => 0,
+            };
+            additions.ids.insert(
+                named_style.format_id,
+                (num_fmt_id, font_id, fill_id, border_id),
+            );
+        }
+
+        additions
+    }
+
+    /// Writes a whole `numFmts` element for a part that has none.
+    fn write_num_fmts(&self, writer: &mut Writer<Cursor<Vec<u8>>>) -> Result<(), String> {
+        if self.num_fmts.is_empty() {
+            return Ok(());
+        }
+        let count = self.num_fmts.len().to_string();
+        write(
+            writer,
+            Event::Start(BytesStart::new("numFmts").with_attributes([("count", count.as_str())])),
+        )?;
+        self.write_num_fmt_elements(writer)?;
+        write(writer, Event::End(BytesEnd::new("numFmts")))
+    }
+
+    fn write_num_fmt_elements(&self, writer: &mut Writer<Cursor<Vec<u8>>>) -> Result<(), String> {
+        for (id, code) in &self.num_fmts {
+            let id = id.to_string();
+            write(
+                writer,
+                Event::Empty(
+                    BytesStart::new("numFmt").with_attributes([
+                        ("numFmtId", id.as_str()),
+                        ("formatCode", code.as_str()),
+                    ]),
+                ),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the `cellStyleXfs` `<xf>` of a named style.
+///
+/// Parts the style does not define are written with `apply*="0"`.
+fn write_style_format(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    named_style: &NamedStyle,
+    additions: &Additions,
+) -> Result<(), String> {
+    let style = &named_style.style;
+    let (num_fmt_id, font_id, fill_id, border_id) = additions
+        .ids
+        .get(&named_style.format_id)
+        .copied()
+        .unwrap_or_default();
+    let ids = [
+        num_fmt_id.to_string(),
+        font_id.to_string(),
+        fill_id.to_string(),
+        border_id.to_string(),
+    ];
+
+    let mut element = BytesStart::new("xf");
+    element.push_attribute(("numFmtId", ids[0].as_str()));
+    element.push_attribute(("fontId", ids[1].as_str()));
+    element.push_attribute(("fillId", ids[2].as_str()));
+    element.push_attribute(("borderId", ids[3].as_str()));
+    for (key, defined) in [
+        ("applyNumberFormat", style.get_numbering_format().is_some()),
+        ("applyFont", style.get_font().is_some()),
+        ("applyFill", style.get_fill().is_some()),
+        ("applyBorder", style.get_borders().is_some()),
+    ] {
+        if !defined {
+            element.push_attribute((key, "0"));
+        }
+    }
+
+    if style.get_alignment().is_none() && style.get_protection().is_none() {
+        return write(writer, Event::Empty(element));
+    }
+
+    write(writer, Event::Start(element))?;
+    if let Some(alignment) = style.get_alignment() {
+        write_alignment(writer, alignment)?;
+    }
+    if let Some(protection) = style.get_protection() {
+        write_protection(writer, protection)?;
+    }
+    write(writer, Event::End(BytesEnd::new("xf")))
+}
+
+fn write_font(writer: &mut Writer<Cursor<Vec<u8>>>, font: &Font) -> Result<(), String> {
+    write(writer, Event::Start(BytesStart::new("font")))?;
+    if *font.get_bold() {
+        write(writer, Event::Empty(BytesStart::new("b")))?;
+    }
+    if *font.get_italic() {
+        write(writer, Event::Empty(BytesStart::new("i")))?;
+    }
+    if font.get_font_underline() != &Underline::default() {
+        let mut element = BytesStart::new("u");
+        if font.get_underline() != "single" {
+            element.push_attribute(("val", font.get_underline()));
+        }
+        write(writer, Event::Empty(element))?;
+    }
+    if *font.get_strikethrough() {
+        write(writer, Event::Empty(BytesStart::new("strike")))?;
+    }
+    if font.get_font_size() != &FontSize::default() {
+        write_val(writer, "sz", &font.get_size().to_string())?;
+    }
+    write_color(writer, "color", font.get_color())?;
+    if !font.get_name().is_empty() {
+        write_val(writer, "name", font.get_name())?;
+    }
+    if font.get_font_family_numbering() != &FontFamilyNumbering::default() {
+        write_val(writer, "family", &font.get_family().to_string())?;
+    }
+    if font.get_font_char_set() != &FontCharSet::default() {
+        write_val(writer, "charset", &font.get_charset().to_string())?;
+    }
+    if font.get_font_scheme() != &FontScheme::default() {
+        write_val(writer, "scheme", font.get_scheme())?;
+    }
+    write(writer, Event::End(BytesEnd::new("font")))
+}
+
+fn write_fill(writer: &mut Writer<Cursor<Vec<u8>>>, fill: &Fill) -> Result<(), String> {
+    write(writer, Event::Start(BytesStart::new("fill")))?;
+    match fill.get_pattern_fill() {
+        Some(pattern_fill) => {
+            let element = BytesStart::new("patternFill").with_attributes([(
+                "patternType",
+                pattern_fill.get_pattern_type().get_value_string(),
+            )]);
+            let foreground = pattern_fill.get_foreground_color();
+            let background = pattern_fill.get_background_color();
+            if foreground.is_none() && background.is_none() {
+                write(writer, Event::Empty(element))?;
+            } else {
+                write(writer, Event::Start(element))?;
+                if let Some(color) = foreground {
+                    write_color(writer, "fgColor", color)?;
+                }
+                if let Some(color) = background {
+                    write_color(writer, "bgColor", color)?;
+                }
+                write(writer, Event::End(BytesEnd::new("patternFill")))?;
+            }
+        }
+        None => write(
+            writer,
+            Event::Empty(BytesStart::new("patternFill").with_attributes([("patternType", "none")])),
+        )?,
+    }
+    write(writer, Event::End(BytesEnd::new("fill")))
+}
+
+fn write_borders(writer: &mut Writer<Cursor<Vec<u8>>>, holder: &Style) -> Result<(), String> {
+    let borders = match holder.get_borders() {
+        Some(borders) => borders,
+        None => return Ok(()),
+    };
+    let mut element = BytesStart::new("border");
+    if *borders.get_diagonal_up() {
+        element.push_attribute(("diagonalUp", "1"));
+    }
+    if *borders.get_diagonal_down() {
+        element.push_attribute(("diagonalDown", "1"));
+    }
+    write(writer, Event::Start(element))?;
+    for (name, border) in [
+        ("left", borders.get_left()),
+        ("right", borders.get_right()),
+        ("top", borders.get_top()),
+        ("bottom", borders.get_bottom()),
+        ("diagonal", borders.get_diagonal()),
+    ] {
+        let style = border.get_border_style();
+        let mut element = BytesStart::new(name);
+        if style != "none" {
+            element.push_attribute(("style", style));
+        }
+        if border.get_color() == &Color::default() {
+            write(writer, Event::Empty(element))?;
+        } else {
+            write(writer, Event::Start(element))?;
+            write_color(writer, "color", border.get_color())?;
+            write(writer, Event::End(BytesEnd::new(name)))?;
+        }
+    }
+    write(writer, Event::End(BytesEnd::new("border")))
+}
+
+fn write_alignment(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    alignment: &Alignment,
+) -> Result<(), String> {
+    let mut element = BytesStart::new("alignment");
+    let mut probe = alignment.clone();
+    probe.set_horizontal(alignment.get_horizontal().clone());
+    if probe == *alignment {
+        element.push_attribute(("horizontal", alignment.get_horizontal().get_value_string()));
+    }
+    let mut probe = alignment.clone();
+    probe.set_vertical(alignment.get_vertical().clone());
+    if probe == *alignment {
+        element.push_attribute(("vertical", alignment.get_vertical().get_value_string()));
+    }
+    let mut probe = alignment.clone();
+    probe.set_wrap_text(*alignment.get_wrap_text());
+    if probe == *alignment {
+        element.push_attribute(("wrapText", bool_value(*alignment.get_wrap_text())));
+    }
+    let mut probe = alignment.clone();
+    probe.set_text_rotation(*alignment.get_text_rotation());
+    if probe == *alignment {
+        let rotation = alignment.get_text_rotation().to_string();
+        element.push_attribute(("textRotation", rotation.as_str()));
+    }
+    write(writer, Event::Empty(element))
+}
+
+fn write_protection(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    protection: &Protection,
+) -> Result<(), String> {
+    let mut element = BytesStart::new("protection");
+    let locked = *protection.get_locked();
+    let hidden = *protection.clone().get_hidden();
+    let mut probe = protection.clone();
+    probe.set_locked(locked);
+    if probe == *protection {
+        element.push_attribute(("locked", bool_value(locked)));
+    }
+    let mut probe = protection.clone();
+    probe.set_hidden(hidden);
+    if probe == *protection {
+        element.push_attribute(("hidden", bool_value(hidden)));
+    }
+    write(writer, Event::Empty(element))
+}
+
+/// Writes a color element, or nothing for a color without any value.
+///
+/// umya-spreadsheet does not expose which color attributes are set, so each one is
+/// detected by setting it to its current value and checking that nothing changed.
+fn write_color(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    tag: &str,
+    color: &Color,
+) -> Result<(), String> {
+    let mut attributes: Vec<(&str, String)> = Vec::new();
+
+    let mut probe = color.clone();
+    probe.set_theme_index(*color.get_theme_index());
+    if probe == *color {
+        attributes.push(("theme", color.get_theme_index().to_string()));
+    } else {
+        let mut probe = color.clone();
+        probe.set_indexed(*color.get_indexed());
+        if probe == *color {
+            attributes.push(("indexed", color.get_indexed().to_string()));
+        } else if !color.get_argb().is_empty() {
+            attributes.push(("rgb", color.get_argb().to_string()));
+        }
+    }
+
+    let mut probe = color.clone();
+    probe.set_tint(*color.get_tint());
+    if probe == *color {
+        attributes.push(("tint", color.get_tint().to_string()));
+    }
+
+    if attributes.is_empty() {
+        return Ok(());
+    }
+    let element = BytesStart::new(tag)
+        .with_attributes(attributes.iter().map(|(key, value)| (*key, value.as_str())));
+    write(writer, Event::Empty(element))
+}
+
+fn write_val(writer: &mut Writer<Cursor<Vec<u8>>>, tag: &str, value: &str) -> Result<(), String> {
+    write(
+        writer,
+        Event::Empty(BytesStart::new(tag).with_attributes([("val", value)])),
+    )
+}
+
+fn write(writer: &mut Writer<Cursor<Vec<u8>>>, event: Event) -> Result<(), String> {
+    writer.write_event(event).map_err(|e| e.to_string())
+}
+
+fn bool_value(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+/// Copies a start tag, replacing its `count` attribute.
+fn with_count(element: &BytesStart, count: usize) -> BytesStart<'static> {
+    replace_attribute(element, "count", &count.to_string())
+}
+
+/// Copies a `cellXfs` `<xf>` start tag, replacing its `xfId`.
+fn with_format_id(element: &BytesStart, format_id: u32) -> BytesStart<'static> {
+    replace_attribute(element, "xfId", &format_id.to_string())
+}
+
+fn replace_attribute(element: &BytesStart, key: &str, value: &str) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+    let mut patched = BytesStart::new(name);
+    let mut replaced = false;
+
+    for attribute in element.attributes().flatten() {
+        if attribute.key.as_ref() == key.as_bytes() {
+            patched.push_attribute((key, value));
+            replaced = true;
+        } else {
+            patched.push_attribute(attribute);
+        }
+    }
+    if !replaced {
+        patched.push_attribute((key, value));
+    }
+
+    patched.into_owned()
+}
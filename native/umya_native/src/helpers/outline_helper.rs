@@ -0,0 +1,288 @@
+use crate::helpers::defined_name_helper::{MAX_COLUMN, MAX_ROW};
+use crate::helpers::dimension_helper;
+use crate::helpers::package_helper::{OutlineAxis, OutlineEntry, SheetOutline};
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
+use umya_spreadsheet::Worksheet;
+
+/// Deepest outline level Excel supports.
+pub const MAX_OUTLINE_LEVEL: u8 = 7;
+
+/// A group of rows or columns at one outline level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutlineGroup {
+    pub start: u32,
+    pub end: u32,
+    pub level: u8,
+    /// The summary row or column of the group is marked collapsed
+    pub collapsed: bool,
+}
+
+/// Describes rows "2:5" or columns "B:D" for error messages.
+pub fn describe(axis: OutlineAxis, start: u32, end: u32) -> String {
+    match axis {
+        OutlineAxis::Rows => format!("Rows {}:{}", start, end),
+        OutlineAxis::Columns => format!(
+            "Columns {}:{}",
+            string_from_column_index(&start),
+            string_from_column_index(&end)
+        ),
+    }
+}
+
+/// Sets the outline level of rows or columns `start..=end`.
+pub fn set_level(
+    sheet: &mut Worksheet,
+    outline: &mut SheetOutline,
+    axis: OutlineAxis,
+    start: u32,
+    end: u32,
+    level: u8,
+) -> Result<(), String> {
+    if level > MAX_OUTLINE_LEVEL {
+        return Err(format!(
+            "Outline level must be between 0 and {}",
+            MAX_OUTLINE_LEVEL
+        ));
+    }
+    for index in start..=end {
+        let entry = OutlineEntry {
+            level,
+            ..outline.entry(axis, index)
+        };
+        outline.set_entry(axis, index, entry);
+    }
+    sync_dimensions(sheet, outline, axis, start, end);
+    Ok(())
+}
+
+/// Raises (`delta` 1) or lowers (`delta` -1) the outline level of rows or columns,
+/// as Excel's Group and Ungroup do.
+pub fn change_level(
+    sheet: &mut Worksheet,
+    outline: &mut SheetOutline,
+    axis: OutlineAxis,
+    start: u32,
+    end: u32,
+    delta: i8,
+) -> Result<(), String> {
+    let levels: Vec<u8> = (start..=end)
+        .map(|index| outline.entry(axis, index).level)
+        .collect();
+    if delta > 0 && levels.contains(&MAX_OUTLINE_LEVEL) {
+        return Err(format!(
+            "{} would exceed the deepest outline level {}",
+            describe(axis, start, end),
+            MAX_OUTLINE_LEVEL
+        ));
+    }
+    if delta < 0 && levels.iter().all(|level| *level == 0) {
+        return Err(format!("{} are not grouped", describe(axis, start, end)));
+    }
+
+    for (index, level) in (start..=end).zip(levels) {
+        let entry = OutlineEntry {
+            level: level.saturating_add_signed(delta),
+            ..outline.entry(axis, index)
+        };
+        outline.set_entry(axis, index, entry);
+    }
+    sync_dimensions(sheet, outline, axis, start, end);
+    Ok(())
+}
+
+/// Collapses or expands the group that rows or columns `start..=end` belong to.
+///
+/// The innermost level the rows or columns share decides the group. Collapsing hides
+/// the whole group and marks its summary row or column collapsed; expanding shows it
+/// again, except for nested groups that stay collapsed.
+pub fn set_collapsed(
+    sheet: &mut Worksheet,
+    outline: &mut SheetOutline,
+    axis: OutlineAxis,
+    start: u32,
+    end: u32,
+    summary_after: bool,
+    collapsed: bool,
+) -> Result<(), String> {
+    let level = (start..=end)
+        .map(|index| outline.entry(axis, index).level)
+        .min()
+        .unwrap_or(0);
+    if level == 0 {
+        return Err(format!("{} are not grouped", describe(axis, start, end)));
+    }
+
+    let (group_start, group_end) = group_bounds(outline, axis, start, level);
+    if let Some(summary) = summary_index(axis, group_start, group_end, summary_after) {
+        let entry = OutlineEntry {
+            collapsed,
+            ..outline.entry(axis, summary)
+        };
+        outline.set_entry(axis, summary, entry);
+        ensure_dimension(sheet, axis, summary);
+    }
+
+    if collapsed {
+        for index in group_start..=group_end {
+            set_hidden(sheet, axis, index, true);
+        }
+    } else {
+        show_group(
+            sheet,
+            outline,
+            axis,
+            group_start,
+            group_end,
+            level,
+            summary_after,
+        );
+    }
+    Ok(())
+}
+
+/// Lists the groups of every level, outermost first.
+pub fn groups(outline: &SheetOutline, axis: OutlineAxis, summary_after: bool) -> Vec<OutlineGroup> {
+    let mut groups = Vec::new();
+    for level in 1..=outline.max_level(axis) {
+        let mut current: Option<(u32, u32)> = None;
+        let close = |bounds: Option<(u32, u32)>, groups: &mut Vec<OutlineGroup>| {
+            if let Some((start, end)) = bounds {
+                let collapsed = summary_index(axis, start, end, summary_after)
+                    .is_some_and(|summary| outline.entry(axis, summary).collapsed);
+                groups.push(OutlineGroup {
+                    start,
+                    end,
+                    level,
+                    collapsed,
+                });
+            }
+        };
+        for (index, entry) in outline.entries(axis) {
+            if entry.level < level {
+                continue;
+            }
+            current = match current {
+                Some((start, end)) if end + 1 == *index => Some((start, *index)),
+                other => {
+                    close(other, &mut groups);
+                    Some((*index, *index))
+                }
+            };
+        }
+        close(current, &mut groups);
+    }
+    groups
+}
+
+/// Returns the run of rows or columns around `index` at `level` or deeper.
+fn group_bounds(outline: &SheetOutline, axis: OutlineAxis, index: u32, level: u8) -> (u32, u32) {
+    let in_group = |index: u32| outline.entry(axis, index).level >= level;
+    let mut start = index;
+    while start > 1 && in_group(start - 1) {
+        start -= 1;
+    }
+    let mut end = index;
+    while end < axis_limit(axis) && in_group(end + 1) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Returns the summary row or column of a group, if the sheet has room for it.
+fn summary_index(axis: OutlineAxis, start: u32, end: u32, summary_after: bool) -> Option<u32> {
+    if summary_after {
+        (end < axis_limit(axis)).then_some(end + 1)
+    } else {
+        (start > 1).then(|| start - 1)
+    }
+}
+
+/// Shows the rows or columns of a group at `level`, keeping collapsed nested groups hidden.
+fn show_group(
+    sheet: &mut Worksheet,
+    outline: &SheetOutline,
+    axis: OutlineAxis,
+    start: u32,
+    end: u32,
+    level: u8,
+    summary_after: bool,
+) {
+    let mut index = start;
+    while index <= end {
+        if outline.entry(axis, index).level <= level {
+            set_hidden(sheet, axis, index, false);
+            index += 1;
+            continue;
+        }
+
+        let (nested_start, nested_end) = group_bounds(outline, axis, index, level + 1);
+        let nested_end = nested_end.min(end);
+        let nested_collapsed = summary_index(axis, nested_start, nested_end, summary_after)
+            .is_some_and(|summary| outline.entry(axis, summary).collapsed);
+        if nested_collapsed {
+            for hidden in nested_start..=nested_end {
+                set_hidden(sheet, axis, hidden, true);
+            }
+        } else {
+            show_group(
+                sheet,
+                outline,
+                axis,
+                nested_start,
+                nested_end,
+                level + 1,
+                summary_after,
+            );
+        }
+        index = nested_end + 1;
+    }
+}
+
+fn axis_limit(axis: OutlineAxis) -> u32 {
+    match axis {
+        OutlineAxis::Rows => MAX_ROW,
+        OutlineAxis::Columns => MAX_COLUMN,
+    }
+}
+
+/// Gives grouped rows and columns a dimension, so that umya-spreadsheet writes the
+/// elements the outline is stored on, and updates the sheet's outline depth.
+fn sync_dimensions(
+    sheet: &mut Worksheet,
+    outline: &SheetOutline,
+    axis: OutlineAxis,
+    start: u32,
+    end: u32,
+) {
+    for index in start..=end {
+        ensure_dimension(sheet, axis, index);
+    }
+    let depth = outline.max_level(axis);
+    let format = sheet.get_sheet_format_properties_mut();
+    match axis {
+        OutlineAxis::Rows => format.set_outline_level_row(depth),
+        OutlineAxis::Columns => format.set_outline_level_column(depth),
+    };
+}
+
+fn ensure_dimension(sheet: &mut Worksheet, axis: OutlineAxis, index: u32) {
+    match axis {
+        OutlineAxis::Rows => {
+            sheet.get_row_dimension_mut(&index);
+        }
+        OutlineAxis::Columns => {
+            dimension_helper::column_dimension_mut(sheet, index);
+        }
+    }
+}
+
+fn set_hidden(sheet: &mut Worksheet, axis: OutlineAxis, index: u32, hidden: bool) {
+    match axis {
+        OutlineAxis::Rows => {
+            sheet.get_row_dimension_mut(&index).set_hidden(hidden);
+        }
+        OutlineAxis::Columns => {
+            dimension_helper::column_dimension_mut(sheet, index).set_hidden(hidden);
+        }
+    }
+}
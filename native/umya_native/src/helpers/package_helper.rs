@@ -0,0 +1,1563 @@
+use crate::helpers::conditional_extension_helper::{self, ExtensionIconSet, RuleExtension};
+use crate::helpers::conditional_format_helper::{self, DxfNumberFormats};
+use crate::helpers::crypt_helper::{self, EncryptionOptions, EncryptionSummary};
+use crate::helpers::named_style_helper::NamedStyles;
+use crate::helpers::protection_helper::SHEET_PROTECTION_FLAGS;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::{Reader, Writer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+use umya_spreadsheet::reader;
+use umya_spreadsheet::writer::xlsx;
+use umya_spreadsheet::{Spreadsheet, Worksheet, XlsxError};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const WORKBOOK_PART: &str = "xl/workbook.xml";
+const WORKBOOK_RELS_PART: &str = "xl/_rels/workbook.xml.rels";
+const STYLES_PART: &str = "xl/styles.xml";
+
+/// Key of a defined name: lowercase name and `localSheetId` (`None` for workbook scope).
+pub type DefinedNameKey = (String, Option<u32>);
+
+/// Workbook data that umya-spreadsheet 2.3.0 does not model.
+///
+/// It is read from the package next to the umya-spreadsheet reader and merged
+/// back into the package after the umya-spreadsheet writer has produced it.
+#[derive(Clone, Debug, Default)]
+pub struct PackageExtras {
+    /// `comment` attributes of `<definedName>` elements
+    pub defined_name_comments: HashMap<DefinedNameKey, String>,
+    /// Encryption of the file the spreadsheet was read from, if it was encrypted
+    pub source_encryption: Option<EncryptionSummary>,
+    /// Sheet protection flags whose attribute is absent, keyed by sheet name.
+    ///
+    /// umya-spreadsheet reads an absent flag as `false`, although ECMA-376
+    /// defaults several of them to `true`, and writes it back absent.
+    pub implicit_sheet_protection_flags: HashMap<String, HashSet<String>>,
+    /// Named cell styles (`cellStyles`) and their formatting
+    pub named_styles: NamedStyles,
+    /// Worksheet properties that differ from the defaults, keyed by sheet name
+    pub sheet_properties: HashMap<String, SheetProperties>,
+    /// Outline levels and collapsed flags of rows and columns, keyed by sheet name
+    pub sheet_outlines: HashMap<String, SheetOutline>,
+    /// Number formats of the conditional formatting dxfs read from the file, keyed by
+    /// sheet name, in rule order; `restore_into` moves them into the rules
+    pub conditional_number_formats: HashMap<String, Vec<Option<String>>>,
+    /// Data bar and icon set options of the conditional formatting rules, keyed by
+    /// sheet name and rule priority
+    pub conditional_extensions: HashMap<String, BTreeMap<i32, RuleExtension>>,
+    /// Icon sets read from the x14 extension of a worksheet, keyed by sheet name;
+    /// `restore_into` adds them to the rules
+    pub extension_icon_sets: HashMap<String, Vec<ExtensionIconSet>>,
+    /// Sheets with conditional formatting rules sharing a priority; `restore_into`
+    /// renumbers their rules
+    pub duplicate_rule_priorities: HashSet<String>,
+}
+
+/// `<sheetPr>` settings of a worksheet that umya-spreadsheet does not write.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetProperties {
+    /// `codeName`, which umya-spreadsheet only writes for workbooks with macros
+    pub code_name: Option<String>,
+    /// `filterMode`: a filter hides rows of the sheet
+    pub filter_mode: bool,
+    /// `summaryBelow` of `<outlinePr>`: summary rows follow their detail rows
+    pub summary_below: bool,
+    /// `summaryRight` of `<outlinePr>`: summary columns follow their detail columns
+    pub summary_right: bool,
+}
+
+impl Default for SheetProperties {
+    fn default() -> Self {
+        SheetProperties {
+            code_name: None,
+            filter_mode: false,
+            summary_below: true,
+            summary_right: true,
+        }
+    }
+}
+
+/// Rows or columns of a worksheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlineAxis {
+    Rows,
+    Columns,
+}
+
+/// `outlineLevel` and `collapsed` attributes of a row or column, which
+/// umya-spreadsheet neither reads nor writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Outline level, 0 for rows and columns outside of any group
+    pub level: u8,
+    /// Set on the summary row or column of a collapsed group
+    pub collapsed: bool,
+}
+
+/// Outline entries of the rows and columns of a worksheet, by 1-based index.
+///
+/// Rows and columns without an entry have level 0 and are not collapsed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SheetOutline {
+    pub rows: BTreeMap<u32, OutlineEntry>,
+    pub columns: BTreeMap<u32, OutlineEntry>,
+}
+
+impl SheetOutline {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.columns.is_empty()
+    }
+
+    pub fn entries(&self, axis: OutlineAxis) -> &BTreeMap<u32, OutlineEntry> {
+        match axis {
+            OutlineAxis::Rows => &self.rows,
+            OutlineAxis::Columns => &self.columns,
+        }
+    }
+
+    pub fn entries_mut(&mut self, axis: OutlineAxis) -> &mut BTreeMap<u32, OutlineEntry> {
+        match axis {
+            OutlineAxis::Rows => &mut self.rows,
+            OutlineAxis::Columns => &mut self.columns,
+        }
+    }
+
+    pub fn entry(&self, axis: OutlineAxis, index: u32) -> OutlineEntry {
+        self.entries(axis).get(&index).copied().unwrap_or_default()
+    }
+
+    /// Stores the entry of a row or column, forgetting it when it is the default.
+    pub fn set_entry(&mut self, axis: OutlineAxis, index: u32, entry: OutlineEntry) {
+        if entry == OutlineEntry::default() {
+            self.entries_mut(axis).remove(&index);
+        } else {
+            self.entries_mut(axis).insert(index, entry);
+        }
+    }
+
+    /// Returns the deepest outline level of the rows or columns.
+    pub fn max_level(&self, axis: OutlineAxis) -> u8 {
+        self.entries(axis)
+            .values()
+            .map(|entry| entry.level)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Moves the entries at and after `start` by `delta` after rows or columns were
+    /// inserted (positive) or removed (negative); entries of removed ones are dropped.
+    pub fn shift(&mut self, axis: OutlineAxis, start: u32, delta: i64) {
+        let removed_end = i64::from(start) - delta;
+        let entries = std::mem::take(self.entries_mut(axis));
+        *self.entries_mut(axis) = entries
+            .into_iter()
+            .filter_map(|(index, entry)| {
+                let index = i64::from(index);
+                if index < i64::from(start) {
+                    Some((index as u32, entry))
+                } else if delta < 0 && index < removed_end {
+                    None
+                } else {
+                    u32::try_from(index + delta)
+                        .ok()
+                        .filter(|index| *index > 0)
+                        .map(|index| (index, entry))
+                }
+            })
+            .collect();
+    }
+}
+
+impl PackageExtras {
+    /// Returns true when nothing needs to be merged into a written package.
+    pub fn is_empty(&self) -> bool {
+        self.defined_name_comments.is_empty()
+            && !self.named_styles.is_changed()
+            && self.sheet_properties.is_empty()
+            && self.sheet_outlines.is_empty()
+            && self.conditional_extensions.is_empty()
+    }
+
+    /// Moves the data umya-spreadsheet holds but does not read into a spreadsheet
+    /// just read from the same package.
+    pub fn restore_into(&mut self, spreadsheet: &mut Spreadsheet) {
+        for (sheet_name, formats) in self.conditional_number_formats.drain() {
+            if let Some(sheet) = spreadsheet.get_sheet_by_name_mut(&sheet_name) {
+                conditional_format_helper::restore_number_formats(sheet, &formats);
+            }
+        }
+        // Added after the number formats, which follow the order of the other rules
+        for (sheet_name, icon_sets) in std::mem::take(&mut self.extension_icon_sets) {
+            let sheet = match spreadsheet.get_sheet_by_name_mut(&sheet_name) {
+                Some(sheet) => sheet,
+                None => continue,
+            };
+            for icon_set in icon_sets {
+                let priority = conditional_extension_helper::restore_icon_set(sheet, &icon_set);
+                self.set_conditional_extension(
+                    &sheet_name,
+                    priority,
+                    Some(RuleExtension::IconSet(icon_set.options)),
+                );
+            }
+            // The priority of a restored icon set may already be taken
+            self.duplicate_rule_priorities.insert(sheet_name);
+        }
+        // Rules are found by priority, so they must not share one
+        for sheet_name in std::mem::take(&mut self.duplicate_rule_priorities) {
+            let sheet = match spreadsheet.get_sheet_by_name_mut(&sheet_name) {
+                Some(sheet) => sheet,
+                None => continue,
+            };
+            if let Some(renumbered) = conditional_format_helper::normalize_priorities(sheet) {
+                self.renumber_conditional_extensions(&sheet_name, &renumbered);
+            }
+        }
+    }
+
+    pub fn defined_name_comment(&self, name: &str, local_sheet_id: Option<u32>) -> Option<&String> {
+        self.defined_name_comments
+            .get(&(name.to_lowercase(), local_sheet_id))
+    }
+
+    /// Sets or, with `None`, clears the comment of a defined name.
+    pub fn set_defined_name_comment(
+        &mut self,
+        name: &str,
+        local_sheet_id: Option<u32>,
+        comment: Option<String>,
+    ) {
+        let key = (name.to_lowercase(), local_sheet_id);
+        match comment.filter(|comment| !comment.is_empty()) {
+            Some(comment) => {
+                self.defined_name_comments.insert(key, comment);
+            }
+            None => {
+                self.defined_name_comments.remove(&key);
+            }
+        }
+    }
+
+    /// Returns true when the protection of `sheet_name` has no attribute for `flag`.
+    pub fn is_implicit_sheet_protection_flag(&self, sheet_name: &str, flag: &str) -> bool {
+        self.implicit_sheet_protection_flags
+            .get(sheet_name)
+            .map(|flags| flags.contains(flag))
+            .unwrap_or(false)
+    }
+
+    /// Records that the protection of `sheet_name` has no attribute for any flag.
+    pub fn set_all_sheet_protection_flags_implicit(&mut self, sheet_name: &str) {
+        let flags = SHEET_PROTECTION_FLAGS
+            .iter()
+            .map(|(key, _, _)| key.to_string())
+            .collect();
+        self.implicit_sheet_protection_flags
+            .insert(sheet_name.to_string(), flags);
+    }
+
+    /// Forgets the implicit flags of `sheet_name` once they were written explicitly.
+    pub fn clear_implicit_sheet_protection_flags(&mut self, sheet_name: &str) {
+        self.implicit_sheet_protection_flags.remove(sheet_name);
+    }
+
+    /// Gives a cloned sheet the implicit flags of its source.
+    pub fn copy_implicit_sheet_protection_flags(&mut self, source_name: &str, target_name: &str) {
+        match self
+            .implicit_sheet_protection_flags
+            .get(source_name)
+            .cloned()
+        {
+            Some(flags) => {
+                self.implicit_sheet_protection_flags
+                    .insert(target_name.to_string(), flags);
+            }
+            None => self.clear_implicit_sheet_protection_flags(target_name),
+        }
+    }
+
+    /// Moves the implicit flags after a sheet was renamed.
+    pub fn move_implicit_sheet_protection_flags(&mut self, old_name: &str, new_name: &str) {
+        if let Some(flags) = self.implicit_sheet_protection_flags.remove(old_name) {
+            self.implicit_sheet_protection_flags
+                .insert(new_name.to_string(), flags);
+        }
+    }
+
+    pub fn sheet_properties(&self, sheet_name: &str) -> SheetProperties {
+        self.sheet_properties
+            .get(sheet_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Stores the properties of `sheet_name`, forgetting them when they are the defaults.
+    pub fn set_sheet_properties(&mut self, sheet_name: &str, properties: SheetProperties) {
+        if properties == SheetProperties::default() {
+            self.sheet_properties.remove(sheet_name);
+        } else {
+            self.sheet_properties
+                .insert(sheet_name.to_string(), properties);
+        }
+    }
+
+    pub fn clear_sheet_properties(&mut self, sheet_name: &str) {
+        self.sheet_properties.remove(sheet_name);
+    }
+
+    /// Gives a cloned sheet the properties of its source.
+    ///
+    /// Code names identify a sheet to macros and must be unique, so the copy has none.
+    pub fn copy_sheet_properties(&mut self, source_name: &str, target_name: &str) {
+        let properties = SheetProperties {
+            code_name: None,
+            ..self.sheet_properties(source_name)
+        };
+        self.set_sheet_properties(target_name, properties);
+    }
+
+    /// Moves the properties after a sheet was renamed.
+    pub fn move_sheet_properties(&mut self, old_name: &str, new_name: &str) {
+        if let Some(properties) = self.sheet_properties.remove(old_name) {
+            self.sheet_properties
+                .insert(new_name.to_string(), properties);
+        }
+    }
+
+    pub fn sheet_outline(&self, sheet_name: &str) -> SheetOutline {
+        self.sheet_outlines
+            .get(sheet_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Stores the outline of `sheet_name`, forgetting it when it is empty.
+    pub fn set_sheet_outline(&mut self, sheet_name: &str, outline: SheetOutline) {
+        if outline.is_empty() {
+            self.sheet_outlines.remove(sheet_name);
+        } else {
+            self.sheet_outlines.insert(sheet_name.to_string(), outline);
+        }
+    }
+
+    pub fn clear_sheet_outline(&mut self, sheet_name: &str) {
+        self.sheet_outlines.remove(sheet_name);
+    }
+
+    pub fn copy_sheet_outline(&mut self, source_name: &str, target_name: &str) {
+        self.set_sheet_outline(target_name, self.sheet_outline(source_name));
+    }
+
+    /// Moves the outline after a sheet was renamed.
+    pub fn move_sheet_outline(&mut self, old_name: &str, new_name: &str) {
+        if let Some(outline) = self.sheet_outlines.remove(old_name) {
+            self.sheet_outlines.insert(new_name.to_string(), outline);
+        }
+    }
+
+    pub fn conditional_extensions(&self, sheet_name: &str) -> BTreeMap<i32, RuleExtension> {
+        self.conditional_extensions
+            .get(sheet_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn conditional_extension(&self, sheet_name: &str, priority: i32) -> Option<&RuleExtension> {
+        self.conditional_extensions
+            .get(sheet_name)
+            .and_then(|extensions| extensions.get(&priority))
+    }
+
+    /// Sets or, with `None`, clears the options of the rule with the given priority.
+    pub fn set_conditional_extension(
+        &mut self,
+        sheet_name: &str,
+        priority: i32,
+        extension: Option<RuleExtension>,
+    ) {
+        let mut extensions = self
+            .conditional_extensions
+            .remove(sheet_name)
+            .unwrap_or_default();
+        match extension {
+            Some(extension) => {
+                extensions.insert(priority, extension);
+            }
+            None => {
+                extensions.remove(&priority);
+            }
+        }
+        self.set_conditional_extensions(sheet_name, extensions);
+    }
+
+    /// Stores the rule options of `sheet_name`, forgetting them when there are none.
+    pub fn set_conditional_extensions(
+        &mut self,
+        sheet_name: &str,
+        extensions: BTreeMap<i32, RuleExtension>,
+    ) {
+        if extensions.is_empty() {
+            self.conditional_extensions.remove(sheet_name);
+        } else {
+            self.conditional_extensions
+                .insert(sheet_name.to_string(), extensions);
+        }
+    }
+
+    /// Forgets the options of rules that no longer exist on the sheet.
+    pub fn retain_conditional_extensions(&mut self, sheet_name: &str, sheet: &Worksheet) {
+        let priorities = conditional_extension_helper::rule_priorities(sheet);
+        let mut extensions = self
+            .conditional_extensions
+            .remove(sheet_name)
+            .unwrap_or_default();
+        extensions.retain(|priority, _| priorities.contains(priority));
+        self.set_conditional_extensions(sheet_name, extensions);
+    }
+
+    /// Moves the rule options along after the rules were renumbered.
+    pub fn renumber_conditional_extensions(
+        &mut self,
+        sheet_name: &str,
+        renumbered: &HashMap<i32, i32>,
+    ) {
+        let extensions = self
+            .conditional_extensions
+            .remove(sheet_name)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(priority, extension)| {
+                let priority = renumbered.get(&priority).copied().unwrap_or(priority);
+                (priority, extension)
+            })
+            .collect();
+        self.set_conditional_extensions(sheet_name, extensions);
+    }
+
+    pub fn clear_conditional_extensions(&mut self, sheet_name: &str) {
+        self.conditional_extensions.remove(sheet_name);
+    }
+
+    pub fn copy_conditional_extensions(&mut self, source_name: &str, target_name: &str) {
+        self.set_conditional_extensions(target_name, self.conditional_extensions(source_name));
+    }
+
+    /// Moves the rule options after a sheet was renamed.
+    pub fn move_conditional_extensions(&mut self, old_name: &str, new_name: &str) {
+        if let Some(extensions) = self.conditional_extensions.remove(old_name) {
+            self.conditional_extensions
+                .insert(new_name.to_string(), extensions);
+        }
+    }
+
+    /// Keeps the outline in step with rows or columns inserted or removed at `start`.
+    pub fn shift_sheet_outline(
+        &mut self,
+        sheet_name: &str,
+        axis: OutlineAxis,
+        start: u32,
+        delta: i64,
+    ) {
+        let mut outline = self.sheet_outline(sheet_name);
+        outline.shift(axis, start, delta);
+        self.set_sheet_outline(sheet_name, outline);
+    }
+
+    /// Re-keys the comments of sheet-scoped names after sheets were reordered.
+    pub fn remap_defined_name_comment_sheets<F: Fn(u32) -> u32>(&mut self, map: F) {
+        self.defined_name_comments = self
+            .defined_name_comments
+            .drain()
+            .map(|((name, local_sheet_id), comment)| ((name, local_sheet_id.map(&map)), comment))
+            .collect();
+    }
+
+    /// Forgets the comments of the names scoped to a removed sheet.
+    pub fn remove_defined_name_comment_sheet(&mut self, local_sheet_id: u32) {
+        self.defined_name_comments
+            .retain(|(_, scope), _| *scope != Some(local_sheet_id));
+    }
+
+    /// Moves a comment after a defined name was renamed or re-scoped.
+    pub fn move_defined_name_comment(
+        &mut self,
+        old_name: &str,
+        old_local_sheet_id: Option<u32>,
+        new_name: &str,
+        new_local_sheet_id: Option<u32>,
+    ) {
+        if let Some(comment) = self
+            .defined_name_comments
+            .remove(&(old_name.to_lowercase(), old_local_sheet_id))
+        {
+            self.defined_name_comments
+                .insert((new_name.to_lowercase(), new_local_sheet_id), comment);
+        }
+    }
+}
+
+/// Reads the extras of the package at `path`.
+///
+/// Files that cannot be opened as a zip package simply have no extras.
+pub fn read_extras_from_path(path: &Path) -> PackageExtras {
+    std::fs::File::open(path)
+        .ok()
+        .and_then(|file| read_extras(file).ok())
+        .unwrap_or_default()
+}
+
+/// Reads the extras of a package.
+pub fn read_extras<R: Read + Seek>(reader: R) -> Result<PackageExtras, String> {
+    let mut archive = ZipArchive::new(reader).map_err(|e| e.to_string())?;
+    let mut extras = PackageExtras::default();
+
+    let workbook_xml = match read_part(&mut archive, WORKBOOK_PART)? {
+        Some(xml) => xml,
+        None => return Ok(extras),
+    };
+
+    let mut reader = Reader::from_str(&workbook_xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if e.name().as_ref() == b"definedName" =>
+            {
+                let (name, local_sheet_id, comment) = defined_name_attributes(e);
+                if let (Some(name), Some(comment)) = (name, comment) {
+                    extras.set_defined_name_comment(&name, local_sheet_id, Some(comment));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.to_string()),
+            _ => (),
+        }
+    }
+
+    let styles_xml = read_part(&mut archive, STYLES_PART)?;
+    let dxf_number_formats = match &styles_xml {
+        Some(styles_xml) => conditional_format_helper::read_dxf_number_formats(styles_xml)?,
+        None => Vec::new(),
+    };
+    read_worksheet_extras(
+        &mut archive,
+        &workbook_xml,
+        &dxf_number_formats,
+        &mut extras,
+    )?;
+
+    if let Some(styles_xml) = styles_xml {
+        extras.named_styles = NamedStyles::read(&styles_xml)?;
+    }
+
+    Ok(extras)
+}
+
+/// Reads the spreadsheet at `path` with umya-spreadsheet, loading the worksheets
+/// right away unless `lazy` is set.
+pub fn read_spreadsheet_from_path(path: &Path, lazy: bool) -> Result<Spreadsheet, XlsxError> {
+    let package = std::fs::File::open(path)
+        .ok()
+        .and_then(|file| readable_package(file).ok().flatten());
+    match package {
+        Some(package) => reader::xlsx::read_reader(Cursor::new(package), !lazy),
+        None if lazy => reader::xlsx::lazy_read(path),
+        None => reader::xlsx::read(path),
+    }
+}
+
+/// Returns a copy of the package umya-spreadsheet can read, or `None` when it can
+/// read the package as it is.
+///
+/// umya-spreadsheet 2.3.0 reads the children of a `<cfRule>`'s `<iconSet>` up to a
+/// `</dataBar>` end tag and panics with "Could not find dataBar end element" when
+/// there is none, so the copy gets an empty `<dataBar>` before the end of each such
+/// icon set. The icon set options are read from the original package.
+pub fn readable_package<R: Read + Seek>(reader: R) -> Result<Option<Vec<u8>>, String> {
+    let mut archive = ZipArchive::new(reader).map_err(|e| e.to_string())?;
+    let mut patched = HashMap::new();
+    let workbook_xml = read_part(&mut archive, WORKBOOK_PART)?.unwrap_or_default();
+    for (_, part_name) in worksheet_parts(&mut archive, &workbook_xml)? {
+        if let Some(xml) = read_part(&mut archive, &part_name)? {
+            if let Some(xml) = close_icon_sets(&xml)? {
+                patched.insert(part_name, xml);
+            }
+        }
+    }
+    if patched.is_empty() {
+        return Ok(None);
+    }
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for index in 0..archive.len() {
+        let file = archive.by_index(index).map_err(|e| e.to_string())?;
+        match patched.get(file.name()) {
+            Some(xml) => {
+                writer
+                    .start_file(file.name(), options)
+                    .map_err(|e| e.to_string())?;
+                writer
+                    .write_all(xml.as_bytes())
+                    .map_err(|e| e.to_string())?;
+            }
+            None => writer.raw_copy_file(file).map_err(|e| e.to_string())?,
+        }
+    }
+    writer
+        .finish()
+        .map(|cursor| Some(cursor.into_inner()))
+        .map_err(|e| e.to_string())
+}
+
+/// Adds an empty `<dataBar>` before the end tag of every `<iconSet>` of a `<cfRule>`
+/// in the main namespace, or returns `None` when a worksheet has none. The x14
+/// icon sets of the extension are left alone; umya-spreadsheet does not read them.
+fn close_icon_sets(worksheet_xml: &str) -> Result<Option<String>, String> {
+    if !worksheet_xml.contains("<iconSet") {
+        return Ok(None);
+    }
+
+    let mut reader = Reader::from_str(worksheet_xml);
+    let mut open: Vec<Vec<u8>> = Vec::new();
+    let mut positions = Vec::new();
+    loop {
+        let position = reader.buffer_position() as usize;
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) => open.push(e.name().as_ref().to_vec()),
+            Event::End(e) => {
+                let in_rule = open.len() >= 2 && open[open.len() - 2] == b"cfRule";
+                if e.name().as_ref() == b"iconSet" && in_rule {
+                    positions.push(position);
+                }
+                open.pop();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    if positions.is_empty() {
+        return Ok(None);
+    }
+
+    let mut xml = String::with_capacity(worksheet_xml.len() + positions.len() * 19);
+    let mut start = 0;
+    for position in positions {
+        xml.push_str(&worksheet_xml[start..position]);
+        xml.push_str("<dataBar></dataBar>");
+        start = position;
+    }
+    xml.push_str(&worksheet_xml[start..]);
+    Ok(Some(xml))
+}
+
+/// Collects, per sheet, the `<sheetProtection>` flags without an attribute, the
+/// `<sheetPr>` settings, the outline of rows and columns and the number formats,
+/// data bar and icon set options of the conditional formatting rules.
+fn read_worksheet_extras<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    workbook_xml: &str,
+    dxf_number_formats: &[Option<String>],
+    extras: &mut PackageExtras,
+) -> Result<(), String> {
+    for (sheet_name, part_name) in worksheet_parts(archive, workbook_xml)? {
+        let worksheet_xml = match read_part(archive, &part_name)? {
+            Some(xml) => xml,
+            None => continue,
+        };
+        if let Some(flags) = absent_sheet_protection_flags(&worksheet_xml)? {
+            extras
+                .implicit_sheet_protection_flags
+                .insert(sheet_name.clone(), flags);
+        }
+        extras.set_sheet_properties(&sheet_name, read_sheet_properties(&worksheet_xml)?);
+        extras.set_sheet_outline(&sheet_name, read_sheet_outline(&worksheet_xml)?);
+        let (extensions, icon_sets) = conditional_extension_helper::read_worksheet(&worksheet_xml)?;
+        extras.set_conditional_extensions(&sheet_name, extensions);
+        if !icon_sets.is_empty() {
+            extras
+                .extension_icon_sets
+                .insert(sheet_name.clone(), icon_sets);
+        }
+        if conditional_format_helper::has_duplicate_priorities(&worksheet_xml)? {
+            extras.duplicate_rule_priorities.insert(sheet_name.clone());
+        }
+        if let Some(formats) =
+            conditional_format_helper::read_rule_number_formats(&worksheet_xml, dxf_number_formats)?
+        {
+            extras
+                .conditional_number_formats
+                .insert(sheet_name, formats);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the sheet names of workbook.xml with the part name of their worksheet.
+fn worksheet_parts<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    workbook_xml: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let targets = match read_part(archive, WORKBOOK_RELS_PART)? {
+        Some(rels_xml) => relationship_targets(&rels_xml)?,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(workbook_sheets(workbook_xml)?
+        .into_iter()
+        .filter_map(|(sheet_name, relationship_id)| {
+            targets
+                .get(&relationship_id)
+                .map(|target| (sheet_name, worksheet_part_name(target)))
+        })
+        .collect())
+}
+
+/// Lists `name` and `r:id` of the `<sheet>` elements of workbook.xml.
+fn workbook_sheets(workbook_xml: &str) -> Result<Vec<(String, String)>, String> {
+    let mut sheets = Vec::new();
+    let mut reader = Reader::from_str(workbook_xml);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name().as_ref() == b"sheet" => {
+                let mut name = None;
+                let mut relationship_id = None;
+                for attribute in e.attributes().flatten() {
+                    let value = match attribute.unescape_value() {
+                        Ok(value) => value.to_string(),
+                        Err(_) => continue,
+                    };
+                    match attribute.key.as_ref() {
+                        b"name" => name = Some(value),
+                        b"r:id" => relationship_id = Some(value),
+                        _ => (),
+                    }
+                }
+                if let (Some(name), Some(relationship_id)) = (name, relationship_id) {
+                    sheets.push((name, relationship_id));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.to_string()),
+            _ => (),
+        }
+    }
+
+    Ok(sheets)
+}
+
+/// Maps relationship ids to their targets.
+fn relationship_targets(rels_xml: &str) -> Result<HashMap<String, String>, String> {
+    let mut targets = HashMap::new();
+    let mut reader = Reader::from_str(rels_xml);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+                if e.name().as_ref() == b"Relationship" =>
+            {
+                let mut id = None;
+                let mut target = None;
+                for attribute in e.attributes().flatten() {
+                    let value = match attribute.unescape_value() {
+                        Ok(value) => value.to_string(),
+                        Err(_) => continue,
+                    };
+                    match attribute.key.as_ref() {
+                        b"Id" => id = Some(value),
+                        b"Target" => target = Some(value),
+                        _ => (),
+                    }
+                }
+                if let (Some(id), Some(target)) = (id, target) {
+                    targets.insert(id, target);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.to_string()),
+            _ => (),
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Resolves a workbook relationship target to a part name, e.g. "xl/worksheets/sheet1.xml".
+fn worksheet_part_name(target: &str) -> String {
+    match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    }
+}
+
+/// Returns the flags missing from the `<sheetProtection>` element, if the sheet has one.
+fn absent_sheet_protection_flags(worksheet_xml: &str) -> Result<Option<HashSet<String>>, String> {
+    // Skip the cell data; the protection element follows it.
+    let start = match worksheet_xml.find("<sheetProtection") {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let mut reader = Reader::from_str(&worksheet_xml[start..]);
+
+    match reader.read_event() {
+        Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e))
+            if e.name().as_ref() == b"sheetProtection" =>
+        {
+            let present: HashSet<Vec<u8>> = e
+                .attributes()
+                .flatten()
+                .map(|attribute| attribute.key.as_ref().to_vec())
+                .collect();
+            Ok(Some(
+                SHEET_PROTECTION_FLAGS
+                    .iter()
+                    .filter(|(_, attribute, _)| !present.contains(attribute.as_bytes()))
+                    .map(|(key, _, _)| key.to_string())
+                    .collect(),
+            ))
+        }
+        Ok(_) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Reads the `<sheetPr>` element, the first child of a worksheet.
+fn read_sheet_properties(worksheet_xml: &str) -> Result<SheetProperties, String> {
+    let mut properties = SheetProperties::default();
+    let mut reader = Reader::from_str(worksheet_xml);
+
+    loop {
+        let e = match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+        match e.name().as_ref() {
+            b"worksheet" | b"tabColor" | b"pageSetUpPr" => (),
+            b"sheetPr" => {
+                for attribute in e.attributes().flatten() {
+                    let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+                    match attribute.key.as_ref() {
+                        b"codeName" => properties.code_name = Some(value.to_string()),
+                        b"filterMode" => properties.filter_mode = xml_bool(&value),
+                        _ => (),
+                    }
+                }
+            }
+            b"outlinePr" => {
+                for attribute in e.attributes().flatten() {
+                    let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+                    match attribute.key.as_ref() {
+                        b"summaryBelow" => properties.summary_below = xml_bool(&value),
+                        b"summaryRight" => properties.summary_right = xml_bool(&value),
+                        _ => (),
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+
+    Ok(properties)
+}
+
+/// Reads the `outlineLevel` and `collapsed` attributes of `<col>` and `<row>` elements.
+fn read_sheet_outline(worksheet_xml: &str) -> Result<SheetOutline, String> {
+    let mut outline = SheetOutline::default();
+    let mut reader = Reader::from_str(worksheet_xml);
+
+    loop {
+        let e = match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let axis = match e.name().as_ref() {
+            b"col" => OutlineAxis::Columns,
+            b"row" => OutlineAxis::Rows,
+            _ => continue,
+        };
+
+        let mut entry = OutlineEntry::default();
+        let (mut first, mut last) = (0, 0);
+        for attribute in e.attributes().flatten() {
+            let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+            match attribute.key.as_ref() {
+                b"outlineLevel" => entry.level = value.parse().unwrap_or(0),
+                b"collapsed" => entry.collapsed = xml_bool(&value),
+                b"r" | b"min" => first = value.parse().unwrap_or(0),
+                b"max" => last = value.parse().unwrap_or(0),
+                _ => (),
+            }
+        }
+        if entry == OutlineEntry::default() || first == 0 {
+            continue;
+        }
+        for index in first..=last.max(first) {
+            outline.set_entry(axis, index, entry);
+        }
+    }
+
+    Ok(outline)
+}
+
+fn xml_bool(value: &str) -> bool {
+    value == "1" || value == "true"
+}
+
+/// Writes the spreadsheet to an in-memory xlsx package including the extras.
+///
+/// # Arguments
+/// * `spreadsheet` - The spreadsheet to serialize
+/// * `extras` - Data to merge into the package
+/// * `light` - Use umya-spreadsheet's light writer
+pub fn write_package(
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    light: bool,
+) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    let result = if light {
+        xlsx::write_writer_light(spreadsheet, &mut buffer)
+    } else {
+        xlsx::write_writer(spreadsheet, &mut buffer)
+    };
+    result.map_err(|e| e.to_string())?;
+
+    apply_extras(buffer.into_inner(), spreadsheet, extras)
+}
+
+/// Writes the spreadsheet to `path`, merging the extras when there are any.
+pub fn write_to_path(
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    path: &Path,
+    light: bool,
+) -> Result<(), String> {
+    if !needs_patching(spreadsheet, extras) {
+        let result = if light {
+            xlsx::write_light(spreadsheet, path)
+        } else {
+            xlsx::write(spreadsheet, path)
+        };
+        return result.map_err(|e| e.to_string());
+    }
+
+    let package = write_package(spreadsheet, extras, light)?;
+    std::fs::write(path, package).map_err(|e| e.to_string())
+}
+
+/// Writes the spreadsheet to `path` encrypted with `password`, merging the extras
+/// when there are any.
+pub fn write_to_path_with_password(
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    path: &Path,
+    password: &str,
+    light: bool,
+) -> Result<(), String> {
+    if !needs_patching(spreadsheet, extras) {
+        let result = if light {
+            xlsx::write_with_password_light(spreadsheet, path, password)
+        } else {
+            xlsx::write_with_password(spreadsheet, path, password)
+        };
+        return result.map_err(|e| e.to_string());
+    }
+
+    let package = write_package(spreadsheet, extras, light)?;
+    umya_spreadsheet::helper::crypt::encrypt(&path, &package, password);
+    Ok(())
+}
+
+/// Writes the spreadsheet to an in-memory package encrypted with `password`.
+///
+/// Unlike umya-spreadsheet's password writers, the cipher, hash, spin count and
+/// salt come from `options`, and nothing is written to disk.
+pub fn write_encrypted_package(
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    password: &str,
+    options: &EncryptionOptions,
+) -> Result<Vec<u8>, String> {
+    let package = write_package(spreadsheet, extras, false)?;
+    crypt_helper::encrypt_package(&package, password, options)
+}
+
+/// Merges the extras into an xlsx package that umya-spreadsheet produced from `spreadsheet`.
+pub fn apply_extras(
+    package: Vec<u8>,
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+) -> Result<Vec<u8>, String> {
+    if !needs_patching(spreadsheet, extras) {
+        return Ok(package);
+    }
+
+    let mut archive = ZipArchive::new(Cursor::new(package)).map_err(|e| e.to_string())?;
+    let patched_styles = match read_part(&mut archive, STYLES_PART)? {
+        Some(styles_xml) if extras.named_styles.is_changed() => {
+            let cell_format_ids = cell_format_ids(&mut archive, spreadsheet)?;
+            Some(
+                extras
+                    .named_styles
+                    .patch_styles_xml(&styles_xml, &cell_format_ids)?,
+            )
+        }
+        _ => None,
+    };
+    let mut patched_worksheets = patch_worksheets(&mut archive, extras)?;
+    let rule_number_formats = conditional_number_formats(spreadsheet);
+    let patched_styles = if rule_number_formats.is_empty() {
+        patched_styles
+    } else {
+        match patched_styles {
+            Some(styles_xml) => Some(styles_xml),
+            None => read_part(&mut archive, STYLES_PART)?,
+        }
+        .map(|styles_xml| {
+            patch_conditional_number_formats(
+                &mut archive,
+                &styles_xml,
+                &rule_number_formats,
+                &mut patched_worksheets,
+            )
+        })
+        .transpose()?
+    };
+    patch_conditional_extensions(&mut archive, spreadsheet, extras, &mut patched_worksheets)?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| e.to_string())?;
+        if file.name() == WORKBOOK_PART {
+            let mut xml = String::new();
+            file.read_to_string(&mut xml).map_err(|e| e.to_string())?;
+            let patched = patch_workbook_xml(&xml, extras)?;
+            writer
+                .start_file(WORKBOOK_PART, options)
+                .map_err(|e| e.to_string())?;
+            writer
+                .write_all(patched.as_bytes())
+                .map_err(|e| e.to_string())?;
+        } else if let (STYLES_PART, Some(patched)) = (file.name(), &patched_styles) {
+            writer
+                .start_file(STYLES_PART, options)
+                .map_err(|e| e.to_string())?;
+            writer
+                .write_all(patched.as_bytes())
+                .map_err(|e| e.to_string())?;
+        } else if let Some(patched) = patched_worksheets.get(file.name()) {
+            writer
+                .start_file(file.name(), options)
+                .map_err(|e| e.to_string())?;
+            writer
+                .write_all(patched.as_bytes())
+                .map_err(|e| e.to_string())?;
+        } else {
+            writer.raw_copy_file(file).map_err(|e| e.to_string())?;
+        }
+    }
+
+    writer
+        .finish()
+        .map(|cursor| cursor.into_inner())
+        .map_err(|e| e.to_string())
+}
+
+/// Returns true when the package umya-spreadsheet writes needs changes.
+fn needs_patching(spreadsheet: &Spreadsheet, extras: &PackageExtras) -> bool {
+    !extras.is_empty()
+        || !conditional_number_formats(spreadsheet).is_empty()
+        || spreadsheet
+            .get_sheet_collection_no_check()
+            .iter()
+            .any(conditional_extension_helper::has_icon_sets)
+}
+
+/// Collects the dxf number formats of the conditional formatting rules, keyed by
+/// sheet name, for sheets that have any.
+fn conditional_number_formats(spreadsheet: &Spreadsheet) -> HashMap<String, Vec<Option<String>>> {
+    spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .filter_map(|sheet| {
+            conditional_format_helper::rule_number_formats(sheet)
+                .map(|formats| (sheet.get_name().to_string(), formats))
+        })
+        .collect()
+}
+
+/// Adds the number formats umya-spreadsheet leaves out to the dxfs of the
+/// conditional formatting rules, returning the patched styles.xml.
+fn patch_conditional_number_formats<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    styles_xml: &str,
+    rule_number_formats: &HashMap<String, Vec<Option<String>>>,
+    patched_worksheets: &mut HashMap<String, String>,
+) -> Result<String, String> {
+    let mut dxfs = DxfNumberFormats::read(styles_xml)?;
+    let workbook_xml = read_part(archive, WORKBOOK_PART)?.unwrap_or_default();
+
+    for (sheet_name, part_name) in worksheet_parts(archive, &workbook_xml)? {
+        let formats = match rule_number_formats.get(&sheet_name) {
+            Some(formats) => formats,
+            None => continue,
+        };
+        let xml = match patched_worksheets.remove(&part_name) {
+            Some(xml) => xml,
+            None => match read_part(archive, &part_name)? {
+                Some(xml) => xml,
+                None => continue,
+            },
+        };
+        patched_worksheets.insert(part_name, dxfs.patch_worksheet(&xml, formats)?);
+    }
+
+    dxfs.patch_styles_xml(styles_xml)
+}
+
+/// Writes the data bar and icon set options into the worksheets and gives icon sets
+/// the `<iconSet>` element umya-spreadsheet writes as `<dataBar>`.
+fn patch_conditional_extensions<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    patched_worksheets: &mut HashMap<String, String>,
+) -> Result<(), String> {
+    let no_extensions = BTreeMap::new();
+    let workbook_xml = read_part(archive, WORKBOOK_PART)?.unwrap_or_default();
+
+    for (sheet_index, (sheet_name, part_name)) in worksheet_parts(archive, &workbook_xml)?
+        .into_iter()
+        .enumerate()
+    {
+        let extensions = extras.conditional_extensions.get(&sheet_name);
+        let has_icon_sets = spreadsheet
+            .get_sheet_by_name(&sheet_name)
+            .is_some_and(conditional_extension_helper::has_icon_sets);
+        if extensions.is_none() && !has_icon_sets {
+            continue;
+        }
+        let xml = match patched_worksheets.remove(&part_name) {
+            Some(xml) => xml,
+            None => match read_part(archive, &part_name)? {
+                Some(xml) => xml,
+                None => continue,
+            },
+        };
+        let patched = conditional_extension_helper::patch_worksheet(
+            &xml,
+            extensions.unwrap_or(&no_extensions),
+            sheet_index,
+        )?;
+        patched_worksheets.insert(part_name, patched);
+    }
+
+    Ok(())
+}
+
+/// Maps the `cellXfs` indexes used in the worksheets to the named style (`xfId`) of
+/// the cells, rows and columns using them.
+///
+/// umya-spreadsheet writes every cell format it adds with `xfId="0"`.
+fn cell_format_ids<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    spreadsheet: &Spreadsheet,
+) -> Result<HashMap<usize, u32>, String> {
+    let mut format_ids = HashMap::new();
+    let workbook_xml = read_part(archive, WORKBOOK_PART)?.unwrap_or_default();
+
+    for (sheet_name, part_name) in worksheet_parts(archive, &workbook_xml)? {
+        let (sheet, worksheet_xml) = match (
+            spreadsheet.get_sheet_by_name(&sheet_name),
+            read_part(archive, &part_name)?,
+        ) {
+            (Some(sheet), Some(xml)) => (sheet, xml),
+            _ => continue,
+        };
+
+        let mut reader = Reader::from_str(&worksheet_xml);
+        loop {
+            let e = match reader.read_event().map_err(|e| e.to_string())? {
+                Event::Start(e) | Event::Empty(e) => e,
+                Event::Eof => break,
+                _ => continue,
+            };
+            let (position_key, format_key) = match e.name().as_ref() {
+                b"c" => ("r", "s"),
+                b"row" => ("r", "s"),
+                b"col" => ("min", "style"),
+                _ => continue,
+            };
+            let mut position = None;
+            let mut format_index = None;
+            for attribute in e.attributes().flatten() {
+                let value = String::from_utf8_lossy(&attribute.value).to_string();
+                if attribute.key.as_ref() == position_key.as_bytes() {
+                    position = Some(value);
+                } else if attribute.key.as_ref() == format_key.as_bytes() {
+                    format_index = value.parse::<usize>().ok();
+                }
+            }
+            let (position, format_index) = match (position, format_index) {
+                (Some(position), Some(format_index)) => (position, format_index),
+                _ => continue,
+            };
+
+            let style = match e.name().as_ref() {
+                b"c" => sheet.get_cell(position.as_str()).map(|cell| cell.get_style()),
+                b"row" => position
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|row| sheet.get_row_dimension(&row))
+                    .map(|row| row.get_style()),
+                _ => position
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|column| sheet.get_column_dimension_by_number(&column))
+                    .map(|column| column.get_style()),
+            };
+            if let Some(format_id) = style.map(|style| *style.get_format_id()) {
+                if format_id != 0 {
+                    format_ids.entry(format_index).or_insert(format_id);
+                }
+            }
+        }
+    }
+
+    Ok(format_ids)
+}
+
+/// Writes the stored sheet properties and outlines into the worksheets, keyed by
+/// part name.
+fn patch_worksheets<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    extras: &PackageExtras,
+) -> Result<HashMap<String, String>, String> {
+    let mut patched = HashMap::new();
+    if extras.sheet_properties.is_empty() && extras.sheet_outlines.is_empty() {
+        return Ok(patched);
+    }
+
+    let workbook_xml = read_part(archive, WORKBOOK_PART)?.unwrap_or_default();
+    for (sheet_name, part_name) in worksheet_parts(archive, &workbook_xml)? {
+        let properties = extras.sheet_properties.get(&sheet_name);
+        let outline = extras.sheet_outlines.get(&sheet_name);
+        if properties.is_none() && outline.is_none() {
+            continue;
+        }
+        let mut xml = match read_part(archive, &part_name)? {
+            Some(xml) => xml,
+            None => continue,
+        };
+        if let Some(properties) = properties {
+            xml = patch_sheet_properties(&xml, properties)?;
+        }
+        if let Some(outline) = outline {
+            xml = patch_sheet_outline(&xml, outline)?;
+        }
+        patched.insert(part_name, xml);
+    }
+
+    Ok(patched)
+}
+
+/// Sets the `<sheetPr>` attributes and `<outlinePr>` of a worksheet, adding the
+/// element when umya-spreadsheet wrote none.
+fn patch_sheet_properties(xml: &str, properties: &SheetProperties) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    let mut in_worksheet = false;
+    let mut in_sheet_pr = false;
+    let mut outline_written = false;
+
+    // Only the head of the worksheet is parsed; the rest is copied as it is
+    let rest = loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let name = match &event {
+            Event::Start(e) | Event::Empty(e) => Some(e.name().as_ref().to_vec()),
+            Event::End(e) => Some(e.name().as_ref().to_vec()),
+            _ => None,
+        };
+        match (&event, name.as_deref()) {
+            (Event::Start(_), Some(b"worksheet")) => {
+                in_worksheet = true;
+                write_xml_event(&mut writer, event)?;
+            }
+            (Event::Start(e), Some(b"sheetPr")) => {
+                in_sheet_pr = true;
+                write_xml_event(&mut writer, Event::Start(sheet_pr_start(e, properties)))?;
+            }
+            (Event::Empty(e), Some(b"sheetPr")) => {
+                let start = sheet_pr_start(e, properties);
+                write_sheet_pr(&mut writer, start, properties)?;
+                break reader.buffer_position();
+            }
+            (Event::Start(_) | Event::Empty(_), Some(b"outlinePr")) if in_sheet_pr => {
+                // Replaced by the stored settings
+                if matches!(event, Event::Start(_)) {
+                    reader
+                        .read_to_end(quick_xml::name::QName(b"outlinePr"))
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            (Event::Start(_) | Event::Empty(_), Some(b"pageSetUpPr")) if in_sheet_pr => {
+                if !outline_written {
+                    write_outline_pr(&mut writer, properties)?;
+                    outline_written = true;
+                }
+                write_xml_event(&mut writer, event)?;
+            }
+            (Event::End(_), Some(b"sheetPr")) => {
+                if !outline_written {
+                    write_outline_pr(&mut writer, properties)?;
+                }
+                write_xml_event(&mut writer, event)?;
+                break reader.buffer_position();
+            }
+            (Event::Start(_) | Event::Empty(_), Some(_)) if in_worksheet && !in_sheet_pr => {
+                let start = sheet_pr_start(&BytesStart::new("sheetPr"), properties);
+                write_sheet_pr(&mut writer, start, properties)?;
+                write_xml_event(&mut writer, event)?;
+                break reader.buffer_position();
+            }
+            (Event::Eof, _) => break reader.buffer_position(),
+            _ => write_xml_event(&mut writer, event)?,
+        }
+    };
+
+    let mut patched =
+        String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())?;
+    patched.push_str(&xml[rest as usize..]);
+    Ok(patched)
+}
+
+/// Copies a `<sheetPr>` start tag with the stored `codeName` and `filterMode`.
+fn sheet_pr_start(element: &BytesStart, properties: &SheetProperties) -> BytesStart<'static> {
+    let mut patched = BytesStart::new("sheetPr");
+
+    for attribute in element.attributes().flatten() {
+        let replaced = match attribute.key.as_ref() {
+            b"codeName" => properties.code_name.is_some(),
+            b"filterMode" => true,
+            _ => false,
+        };
+        if !replaced {
+            patched.push_attribute(attribute);
+        }
+    }
+
+    if let Some(code_name) = &properties.code_name {
+        patched.push_attribute(("codeName", code_name.as_str()));
+    }
+    if properties.filter_mode {
+        patched.push_attribute(("filterMode", "1"));
+    }
+
+    patched.into_owned()
+}
+
+/// Writes a whole `<sheetPr>` element with no children but `<outlinePr>`.
+fn write_sheet_pr(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    start: BytesStart<'static>,
+    properties: &SheetProperties,
+) -> Result<(), String> {
+    if properties.summary_below && properties.summary_right {
+        return write_xml_event(writer, Event::Empty(start));
+    }
+    write_xml_event(writer, Event::Start(start))?;
+    write_outline_pr(writer, properties)?;
+    write_xml_event(writer, Event::End(BytesEnd::new("sheetPr")))
+}
+
+/// Writes `<outlinePr>` when the summary placement is not Excel's default.
+fn write_outline_pr(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    properties: &SheetProperties,
+) -> Result<(), String> {
+    let mut outline = BytesStart::new("outlinePr");
+    if !properties.summary_below {
+        outline.push_attribute(("summaryBelow", "0"));
+    }
+    if !properties.summary_right {
+        outline.push_attribute(("summaryRight", "0"));
+    }
+    if outline.attributes().next().is_none() {
+        return Ok(());
+    }
+    write_xml_event(writer, Event::Empty(outline))
+}
+
+/// Sets the `outlineLevel` and `collapsed` attributes of the `<col>` and `<row>`
+/// elements, splitting `<col>` ranges whose columns have different entries.
+fn patch_sheet_outline(xml: &str, outline: &SheetOutline) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        match &event {
+            Event::Empty(e) if e.name().as_ref() == b"col" => {
+                let (first, last) = (
+                    attribute_u32(e, b"min").unwrap_or(0),
+                    attribute_u32(e, b"max").unwrap_or(0),
+                );
+                let mut start = first;
+                while start <= last && start > 0 {
+                    let entry = outline.entry(OutlineAxis::Columns, start);
+                    let mut end = start;
+                    while end < last && outline.entry(OutlineAxis::Columns, end + 1) == entry {
+                        end += 1;
+                    }
+                    let col = with_outline_entry(e, entry, Some((start, end)));
+                    write_xml_event(&mut writer, Event::Empty(col))?;
+                    start = end + 1;
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if e.name().as_ref() == b"row" => {
+                let entry = attribute_u32(e, b"r")
+                    .map(|row| outline.entry(OutlineAxis::Rows, row))
+                    .unwrap_or_default();
+                let row = with_outline_entry(e, entry, None);
+                match event {
+                    Event::Start(_) => write_xml_event(&mut writer, Event::Start(row))?,
+                    _ => write_xml_event(&mut writer, Event::Empty(row))?,
+                }
+            }
+            Event::Eof => break,
+            _ => write_xml_event(&mut writer, event)?,
+        }
+    }
+
+    String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())
+}
+
+/// Copies a `<col>` or `<row>` element with the given outline entry and, for columns,
+/// the given `min` and `max`.
+fn with_outline_entry(
+    element: &BytesStart,
+    entry: OutlineEntry,
+    columns: Option<(u32, u32)>,
+) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+    let mut patched = BytesStart::new(name);
+
+    for attribute in element.attributes().flatten() {
+        match (attribute.key.as_ref(), columns) {
+            (b"outlineLevel" | b"collapsed", _) => (),
+            (b"min", Some((first, _))) => {
+                patched.push_attribute(("min", first.to_string().as_str()))
+            }
+            (b"max", Some((_, last))) => patched.push_attribute(("max", last.to_string().as_str())),
+            _ => patched.push_attribute(attribute),
+        }
+    }
+
+    if entry.level > 0 {
+        patched.push_attribute(("outlineLevel", entry.level.to_string().as_str()));
+    }
+    if entry.collapsed {
+        patched.push_attribute(("collapsed", "1"));
+    }
+    patched
+}
+
+fn attribute_u32(element: &BytesStart, key: &[u8]) -> Option<u32> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == key)
+        .and_then(|attribute| std::str::from_utf8(&attribute.value).ok()?.parse().ok())
+}
+
+fn write_xml_event(writer: &mut Writer<Cursor<Vec<u8>>>, event: Event) -> Result<(), String> {
+    writer.write_event(event).map_err(|e| e.to_string())
+}
+
+/// Adds the `comment` attribute to the `<definedName>` elements of workbook.xml.
+fn patch_workbook_xml(xml: &str, extras: &PackageExtras) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let result = match event {
+            Event::Start(ref e) if e.name().as_ref() == b"definedName" => {
+                writer.write_event(Event::Start(with_comment(e, extras)))
+            }
+            Event::Empty(ref e) if e.name().as_ref() == b"definedName" => {
+                writer.write_event(Event::Empty(with_comment(e, extras)))
+            }
+            Event::Eof => break,
+            other => writer.write_event(other),
+        };
+        result.map_err(|e| e.to_string())?;
+    }
+
+    String::from_utf8(writer.into_inner().into_inner()).map_err(|e| e.to_string())
+}
+
+/// Copies a `<definedName>` start tag, replacing its comment with the stored one.
+fn with_comment(element: &BytesStart, extras: &PackageExtras) -> BytesStart<'static> {
+    let (name, local_sheet_id, _) = defined_name_attributes(element);
+    let mut patched = BytesStart::new("definedName");
+
+    for attribute in element.attributes().flatten() {
+        if attribute.key.as_ref() != b"comment" {
+            patched.push_attribute(attribute);
+        }
+    }
+
+    if let Some(comment) = name.and_then(|name| extras.defined_name_comment(&name, local_sheet_id))
+    {
+        patched.push_attribute(("comment", comment.as_str()));
+    }
+
+    patched.into_owned()
+}
+
+/// Extracts `name`, `localSheetId` and `comment` from a `<definedName>` tag.
+fn defined_name_attributes(element: &BytesStart) -> (Option<String>, Option<u32>, Option<String>) {
+    let mut name = None;
+    let mut local_sheet_id = None;
+    let mut comment = None;
+
+    for attribute in element.attributes().flatten() {
+        let value = match attribute.unescape_value() {
+            Ok(value) => value.to_string(),
+            Err(_) => continue,
+        };
+        match attribute.key.as_ref() {
+            b"name" => name = Some(value),
+            b"localSheetId" => local_sheet_id = value.parse::<u32>().ok(),
+            b"comment" => comment = Some(value),
+            _ => (),
+        }
+    }
+
+    (name, local_sheet_id, comment)
+}
+
+/// Reads a part of the package as UTF-8 text, if it exists.
+fn read_part<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part_name: &str,
+) -> Result<Option<String>, String> {
+    let mut file = match archive.by_name(part_name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    Ok(Some(content))
+}
@@ -0,0 +1,349 @@
+use crate::helpers::crypt_helper::HashAlgorithm;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rustler::Term;
+use std::collections::HashMap;
+use umya_spreadsheet::SheetProtection;
+
+/// Hash algorithm Excel uses for protection passwords.
+const DEFAULT_ALGORITHM_NAME: &str = "SHA-512";
+/// Spin count Excel uses for protection passwords.
+const DEFAULT_SPIN_COUNT: u32 = 100_000;
+/// Largest spin count ECMA-376 allows.
+const MAX_SPIN_COUNT: u32 = 10_000_000;
+const SALT_SIZE: usize = 16;
+
+/// Option keys that describe the password hash rather than a lock flag.
+pub const PASSWORD_OPTION_KEYS: [&str; 5] = [
+    "password",
+    "algorithm_name",
+    "hash_value",
+    "salt_value",
+    "spin_count",
+];
+
+/// Sheet protection flags: option key, `<sheetProtection>` attribute and the value
+/// ECMA-376 assumes when the attribute is absent.
+///
+/// A flag that is `true` blocks the action while the sheet is protected.
+pub const SHEET_PROTECTION_FLAGS: [(&str, &str, bool); 16] = [
+    ("sheet", "sheet", false),
+    ("objects", "objects", false),
+    ("scenarios", "scenarios", false),
+    ("format_cells", "formatCells", true),
+    ("format_columns", "formatColumns", true),
+    ("format_rows", "formatRows", true),
+    ("insert_columns", "insertColumns", true),
+    ("insert_rows", "insertRows", true),
+    ("insert_hyperlinks", "insertHyperlinks", true),
+    ("delete_columns", "deleteColumns", true),
+    ("delete_rows", "deleteRows", true),
+    ("select_locked_cells", "selectLockedCells", false),
+    ("select_unlocked_cells", "selectUnlockedCells", false),
+    ("sort", "sort", true),
+    ("auto_filter", "autoFilter", true),
+    ("pivot_tables", "pivotTables", true),
+];
+
+/// A protection password stored as an iterated, salted hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PasswordHash {
+    pub algorithm_name: String,
+    pub hash_value: String,
+    pub salt_value: String,
+    pub spin_count: u32,
+}
+
+impl PasswordHash {
+    /// Builds a hash from stored attributes; `None` when no hash is stored.
+    pub fn from_parts(
+        algorithm_name: &str,
+        hash_value: &str,
+        salt_value: &str,
+        spin_count: u32,
+    ) -> Option<Self> {
+        if hash_value.is_empty() {
+            return None;
+        }
+        Some(PasswordHash {
+            algorithm_name: algorithm_name.to_string(),
+            hash_value: hash_value.to_string(),
+            salt_value: salt_value.to_string(),
+            spin_count,
+        })
+    }
+
+    /// Hashes `password` the way Excel does for sheet and workbook protection.
+    ///
+    /// # Arguments
+    /// * `password` - The plain password
+    /// * `algorithm_name` - "SHA-256", "SHA-384" or "SHA-512", or `None` for SHA-512
+    /// * `salt_value` - Base64 encoded salt, or `None` for a random 16-byte salt
+    /// * `spin_count` - Hash iterations, or `None` for 100000
+    pub fn generate(
+        password: &str,
+        algorithm_name: Option<&str>,
+        salt_value: Option<&str>,
+        spin_count: Option<u32>,
+    ) -> Result<Self, String> {
+        let algorithm_name = algorithm_name.unwrap_or(DEFAULT_ALGORITHM_NAME);
+        let hash_algorithm = parse_algorithm(algorithm_name)?;
+
+        let salt = match salt_value {
+            Some(value) => match STANDARD.decode(value.trim()) {
+                Ok(salt) if !salt.is_empty() => salt,
+                _ => return Err("Salt value must be base64 encoded".to_string()),
+            },
+            None => {
+                let mut salt = vec![0u8; SALT_SIZE];
+                getrandom::getrandom(&mut salt).map_err(|e| e.to_string())?;
+                salt
+            }
+        };
+
+        let spin_count = spin_count.unwrap_or(DEFAULT_SPIN_COUNT);
+        if spin_count > MAX_SPIN_COUNT {
+            return Err(format!("Spin count must not exceed {}", MAX_SPIN_COUNT));
+        }
+
+        Ok(PasswordHash {
+            algorithm_name: protection_algorithm_name(hash_algorithm).to_string(),
+            hash_value: STANDARD.encode(iterated_hash(hash_algorithm, &salt, password, spin_count)),
+            salt_value: STANDARD.encode(&salt),
+            spin_count,
+        })
+    }
+
+    /// Checks a candidate password against the stored hash.
+    ///
+    /// The spin count may come from a loaded file, so it is capped like a new one.
+    pub fn verify(&self, password: &str) -> Result<bool, String> {
+        if self.spin_count > MAX_SPIN_COUNT {
+            return Err(format!(
+                "Stored spin count {} exceeds {}",
+                self.spin_count, MAX_SPIN_COUNT
+            ));
+        }
+        let hash_algorithm = parse_algorithm(&self.algorithm_name)?;
+        let salt = STANDARD
+            .decode(self.salt_value.trim())
+            .map_err(|_| "Stored salt value is not valid base64".to_string())?;
+        let expected = STANDARD
+            .decode(self.hash_value.trim())
+            .map_err(|_| "Stored hash value is not valid base64".to_string())?;
+
+        Ok(iterated_hash(hash_algorithm, &salt, password, self.spin_count) == expected)
+    }
+}
+
+/// Checks a candidate password against stored protection.
+///
+/// The modern hash is used when present, otherwise the legacy 16-bit `password`
+/// attribute. Without any stored password only the empty password matches.
+pub fn verify_password(
+    hash: Option<&PasswordHash>,
+    legacy_hash: &str,
+    password: &str,
+) -> Result<bool, String> {
+    match hash {
+        Some(hash) => hash.verify(password),
+        None if !legacy_hash.is_empty() => {
+            Ok(legacy_hash.eq_ignore_ascii_case(&legacy_password_hash(password)))
+        }
+        None => Ok(password.is_empty()),
+    }
+}
+
+/// Excel's legacy 16-bit password hash, as stored in the `password` attribute.
+pub fn legacy_password_hash(password: &str) -> String {
+    let bytes: Vec<u8> = password.chars().take(15).map(|c| c as u8).collect();
+    let mut hash: u16 = 0;
+
+    for (index, &byte) in bytes.iter().enumerate() {
+        let value = (byte as u32) << (index + 1);
+        let rotated = ((value >> 15) | value) & 0x7fff;
+        hash ^= rotated as u16;
+    }
+    hash ^= bytes.len() as u16;
+    hash ^= 0xce4b;
+
+    format!("{:X}", hash)
+}
+
+/// Resolves the password hash after applying `options` to the `current` one.
+///
+/// `password` (a string, or `nil` to remove the password) is hashed with the
+/// optional `algorithm_name`, `salt_value` and `spin_count`. A `hash_value` is
+/// stored as given together with those parameters, e.g. when copying protection
+/// read with a getter. Without either key the current hash is kept.
+pub fn apply_password_options(
+    options: &HashMap<String, Term>,
+    current: Option<PasswordHash>,
+) -> Result<Option<PasswordHash>, String> {
+    let algorithm_name = optional_string(options, "algorithm_name")?;
+    let salt_value = optional_string(options, "salt_value")?;
+    let spin_count = match options.get("spin_count") {
+        Some(term) => term
+            .decode::<Option<u32>>()
+            .map_err(|_| "Option 'spin_count' must be a non-negative integer".to_string())?,
+        None => None,
+    };
+
+    match (options.get("password"), options.get("hash_value")) {
+        (Some(_), Some(_)) => {
+            Err("Options 'password' and 'hash_value' cannot be combined".to_string())
+        }
+        (Some(term), None) => match optional_string_term(term, "password")? {
+            Some(password) => PasswordHash::generate(
+                &password,
+                algorithm_name.as_deref(),
+                salt_value.as_deref(),
+                spin_count,
+            )
+            .map(Some),
+            None => Ok(None),
+        },
+        (None, Some(term)) => match optional_string_term(term, "hash_value")? {
+            Some(hash_value) => {
+                let algorithm_name = algorithm_name
+                    .ok_or_else(|| "Option 'hash_value' requires 'algorithm_name'".to_string())?;
+                parse_algorithm(&algorithm_name)?;
+                Ok(Some(PasswordHash {
+                    algorithm_name,
+                    hash_value,
+                    salt_value: salt_value.unwrap_or_default(),
+                    spin_count: spin_count.unwrap_or(0),
+                }))
+            }
+            None => Ok(None),
+        },
+        (None, None) => {
+            if algorithm_name.is_some() || salt_value.is_some() || spin_count.is_some() {
+                return Err(
+                    "Options 'algorithm_name', 'salt_value' and 'spin_count' require 'password' or 'hash_value'"
+                        .to_string(),
+                );
+            }
+            Ok(current)
+        }
+    }
+}
+
+/// Decodes a boolean flag option; `None` when the key is absent.
+pub fn flag_option(options: &HashMap<String, Term>, key: &str) -> Result<Option<bool>, String> {
+    match options.get(key) {
+        Some(term) => term
+            .decode::<bool>()
+            .map(Some)
+            .map_err(|_| format!("Option '{}' must be a boolean", key)),
+        None => Ok(None),
+    }
+}
+
+/// Rejects option keys that are neither flags nor password options.
+pub fn check_option_keys(options: &HashMap<String, Term>, flags: &[&str]) -> Result<(), String> {
+    match options
+        .keys()
+        .find(|key| !flags.contains(&key.as_str()) && !PASSWORD_OPTION_KEYS.contains(&key.as_str()))
+    {
+        Some(key) => Err(format!("Unknown protection option '{}'", key)),
+        None => Ok(()),
+    }
+}
+
+fn optional_string(options: &HashMap<String, Term>, key: &str) -> Result<Option<String>, String> {
+    match options.get(key) {
+        Some(term) => optional_string_term(term, key),
+        None => Ok(None),
+    }
+}
+
+fn optional_string_term(term: &Term, key: &str) -> Result<Option<String>, String> {
+    term.decode::<Option<String>>()
+        .map_err(|_| format!("Option '{}' must be a string", key))
+}
+
+fn parse_algorithm(name: &str) -> Result<HashAlgorithm, String> {
+    HashAlgorithm::parse(name).ok_or_else(|| {
+        format!(
+            "Unsupported hash algorithm '{}'; use SHA-256, SHA-384 or SHA-512",
+            name
+        )
+    })
+}
+
+/// Name of a hash algorithm in protection elements, e.g. "SHA-512".
+fn protection_algorithm_name(hash_algorithm: HashAlgorithm) -> &'static str {
+    match hash_algorithm {
+        HashAlgorithm::Sha1 => "SHA-1",
+        HashAlgorithm::Sha256 => "SHA-256",
+        HashAlgorithm::Sha384 => "SHA-384",
+        HashAlgorithm::Sha512 => "SHA-512",
+    }
+}
+
+/// H0 = H(salt + password), Hn = H(Hn-1 + n) as little-endian u32.
+///
+/// The iterator follows the hash here, unlike the key derivation of file encryption.
+fn iterated_hash(
+    hash_algorithm: HashAlgorithm,
+    salt: &[u8],
+    password: &str,
+    spin_count: u32,
+) -> Vec<u8> {
+    let password_bytes: Vec<u8> = password
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+
+    let mut hash = hash_algorithm.digest(&[salt, &password_bytes]);
+    for iteration in 0..spin_count {
+        hash = hash_algorithm.digest(&[&hash, &iteration.to_le_bytes()]);
+    }
+    hash
+}
+
+/// Reads a sheet protection flag by its option key.
+pub fn sheet_protection_flag(protection: &SheetProtection, key: &str) -> bool {
+    match key {
+        "sheet" => *protection.get_sheet(),
+        "objects" => *protection.get_objects(),
+        "scenarios" => *protection.get_scenarios(),
+        "format_cells" => *protection.get_format_cells(),
+        "format_columns" => *protection.get_format_columns(),
+        "format_rows" => *protection.get_format_rows(),
+        "insert_columns" => *protection.get_insert_columns(),
+        "insert_rows" => *protection.get_insert_rows(),
+        "insert_hyperlinks" => *protection.get_insert_hyperlinks(),
+        "delete_columns" => *protection.get_delete_columns(),
+        "delete_rows" => *protection.get_delete_rows(),
+        "select_locked_cells" => *protection.get_select_locked_cells(),
+        "select_unlocked_cells" => *protection.get_select_unlocked_cells(),
+        "sort" => *protection.get_sort(),
+        "auto_filter" => *protection.get_auto_filter(),
+        "pivot_tables" => *protection.get_pivot_tables(),
+        _ => false,
+    }
+}
+
+/// Sets a sheet protection flag by its option key, writing it explicitly.
+pub fn set_sheet_protection_flag(protection: &mut SheetProtection, key: &str, value: bool) {
+    match key {
+        "sheet" => protection.set_sheet(value),
+        "objects" => protection.set_objects(value),
+        "scenarios" => protection.set_scenarios(value),
+        "format_cells" => protection.set_format_cells(value),
+        "format_columns" => protection.set_format_columns(value),
+        "format_rows" => protection.set_format_rows(value),
+        "insert_columns" => protection.set_insert_columns(value),
+        "insert_rows" => protection.set_insert_rows(value),
+        "insert_hyperlinks" => protection.set_insert_hyperlinks(value),
+        "delete_columns" => protection.set_delete_columns(value),
+        "delete_rows" => protection.set_delete_rows(value),
+        "select_locked_cells" => protection.set_select_locked_cells(value),
+        "select_unlocked_cells" => protection.set_select_unlocked_cells(value),
+        "sort" => protection.set_sort(value),
+        "auto_filter" => protection.set_auto_filter(value),
+        "pivot_tables" => protection.set_pivot_tables(value),
+        _ => protection,
+    };
+}
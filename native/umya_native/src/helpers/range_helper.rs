@@ -0,0 +1,210 @@
+use crate::helpers::defined_name_helper::{MAX_COLUMN, MAX_ROW};
+use umya_spreadsheet::helper::coordinate::{column_index_from_string, string_from_column_index};
+use umya_spreadsheet::{SequenceOfReferences, Style, Worksheet};
+
+/// Area addressed by an A1-style range.
+///
+/// Bounds are 1-based and inclusive, with the start never after the end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeTarget {
+    /// A rectangle of cells, e.g. "B2" or "A1:Z500"
+    Cells {
+        start_column: u32,
+        start_row: u32,
+        end_column: u32,
+        end_row: u32,
+    },
+    /// Whole rows, e.g. "3:5"
+    Rows { start_row: u32, end_row: u32 },
+    /// Whole columns, e.g. "A:C"
+    Columns { start_column: u32, end_column: u32 },
+}
+
+impl RangeTarget {
+    /// Returns the column and row bounds, expanding whole rows and columns to the sheet size.
+    pub fn bounds(&self) -> (u32, u32, u32, u32) {
+        match *self {
+            RangeTarget::Cells {
+                start_column,
+                start_row,
+                end_column,
+                end_row,
+            } => (start_column, start_row, end_column, end_row),
+            RangeTarget::Rows { start_row, end_row } => (1, start_row, MAX_COLUMN, end_row),
+            RangeTarget::Columns {
+                start_column,
+                end_column,
+            } => (start_column, 1, end_column, MAX_ROW),
+        }
+    }
+
+    /// Returns true when the cell at `column`/`row` lies inside the range.
+    pub fn contains(&self, column: u32, row: u32) -> bool {
+        let (start_column, start_row, end_column, end_row) = self.bounds();
+        (start_column..=end_column).contains(&column) && (start_row..=end_row).contains(&row)
+    }
+}
+
+/// Parses "A1", "A1:B2", "A:C" or "1:3", with optional `$` markers.
+///
+/// Unlike umya-spreadsheet's range helpers this does not panic on malformed input.
+pub fn parse_range(range: &str) -> Result<RangeTarget, String> {
+    let invalid = || format!("Invalid range '{}'", range);
+    let parts: Vec<&str> = range.trim().split(':').collect();
+    if parts.len() > 2 {
+        return Err(invalid());
+    }
+
+    let start = parse_reference(parts[0]).ok_or_else(invalid)?;
+    let end = match parts.get(1) {
+        Some(part) => parse_reference(part).ok_or_else(invalid)?,
+        None => start,
+    };
+
+    match (start, end) {
+        ((Some(c1), Some(r1)), (Some(c2), Some(r2))) => Ok(RangeTarget::Cells {
+            start_column: c1.min(c2),
+            start_row: r1.min(r2),
+            end_column: c1.max(c2),
+            end_row: r1.max(r2),
+        }),
+        ((None, Some(r1)), (None, Some(r2))) if parts.len() == 2 => Ok(RangeTarget::Rows {
+            start_row: r1.min(r2),
+            end_row: r1.max(r2),
+        }),
+        ((Some(c1), None), (Some(c2), None)) if parts.len() == 2 => Ok(RangeTarget::Columns {
+            start_column: c1.min(c2),
+            end_column: c1.max(c2),
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+/// Replaces the ranges of a sqref, as umya-spreadsheet's `set_sqref` adds to them.
+pub fn replace_sqref(sequence: &mut SequenceOfReferences, sqref: &str) {
+    sequence.remove_range_collection();
+    sequence.set_sqref(sqref);
+}
+
+/// Column and row bounds of a cell rectangle: start column, start row, end column, end row.
+pub type Bounds = (u32, u32, u32, u32);
+
+/// Writes bounds as "A1" or "A1:B2".
+pub fn format_bounds((start_column, start_row, end_column, end_row): Bounds) -> String {
+    let start = format!("{}{}", string_from_column_index(&start_column), start_row);
+    if start_column == end_column && start_row == end_row {
+        start
+    } else {
+        format!(
+            "{}:{}{}",
+            start,
+            string_from_column_index(&end_column),
+            end_row
+        )
+    }
+}
+
+/// Returns the cells two rectangles have in common.
+pub fn intersect(a: Bounds, b: Bounds) -> Option<Bounds> {
+    let bounds = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    (bounds.0 <= bounds.2 && bounds.1 <= bounds.3).then_some(bounds)
+}
+
+/// Returns the cells of `a` outside of `b`, as at most four rectangles.
+pub fn subtract(a: Bounds, b: Bounds) -> Vec<Bounds> {
+    let Some(overlap) = intersect(a, b) else {
+        return vec![a];
+    };
+
+    let mut rest = Vec::new();
+    if a.1 < overlap.1 {
+        rest.push((a.0, a.1, a.2, overlap.1 - 1));
+    }
+    if overlap.3 < a.3 {
+        rest.push((a.0, overlap.3 + 1, a.2, a.3));
+    }
+    if a.0 < overlap.0 {
+        rest.push((a.0, overlap.1, overlap.0 - 1, overlap.3));
+    }
+    if overlap.2 < a.2 {
+        rest.push((overlap.2 + 1, overlap.1, a.2, overlap.3));
+    }
+    rest
+}
+
+/// Splits "$A$1", "A" or "1" into column and row numbers.
+fn parse_reference(reference: &str) -> Option<(Option<u32>, Option<u32>)> {
+    let reference = reference.trim().replace('$', "");
+    let letters = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .count();
+    let (column, row) = reference.split_at(letters);
+    if (column.is_empty() && row.is_empty()) || letters > 3 {
+        return None;
+    }
+
+    let column = match column {
+        "" => None,
+        letters => Some(column_index_from_string(letters.to_uppercase())),
+    };
+    let row = match row {
+        "" => None,
+        digits if digits.chars().all(|c| c.is_ascii_digit()) => Some(digits.parse::<u32>().ok()?),
+        _ => return None,
+    };
+
+    let column_valid = column.is_none_or(|column| column <= MAX_COLUMN);
+    let row_valid = row.is_none_or(|row| (1..=MAX_ROW).contains(&row));
+    match (column_valid, row_valid) {
+        (true, true) => Some((column, row)),
+        _ => None,
+    }
+}
+
+/// Calls `update` for the style of every cell, row or column the range covers.
+///
+/// Cells of a rectangle are created when missing; whole rows and columns only
+/// touch the cells that exist, next to the row or column style.
+pub fn update_styles<F: Fn(&mut Style)>(sheet: &mut Worksheet, target: RangeTarget, update: F) {
+    match target {
+        RangeTarget::Cells {
+            start_column,
+            start_row,
+            end_column,
+            end_row,
+        } => {
+            for row in start_row..=end_row {
+                for column in start_column..=end_column {
+                    update(sheet.get_style_mut((column, row)));
+                }
+            }
+        }
+        RangeTarget::Rows { start_row, end_row } => {
+            for row in start_row..=end_row {
+                update(sheet.get_row_dimension_mut(&row).get_style_mut());
+            }
+        }
+        RangeTarget::Columns {
+            start_column,
+            end_column,
+        } => {
+            for column in start_column..=end_column {
+                update(
+                    sheet
+                        .get_column_dimension_by_number_mut(&column)
+                        .get_style_mut(),
+                );
+            }
+        }
+    }
+
+    if !matches!(target, RangeTarget::Cells { .. }) {
+        for cell in sheet.get_cell_collection_mut() {
+            let coordinate = cell.get_coordinate();
+            if target.contains(*coordinate.get_col_num(), *coordinate.get_row_num()) {
+                update(cell.get_style_mut());
+            }
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::helpers::formula_helper;
+use crate::helpers::package_helper::OutlineAxis;
+use crate::helpers::range_helper::{self, Bounds, RangeTarget};
+use umya_spreadsheet::helper::coordinate::CellCoordinates;
+use umya_spreadsheet::{Table, TableColumn, Worksheet};
+
+/// Which neighbour inserted rows or columns take their formatting from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inherit {
+    /// The row above or the column to the left
+    Before,
+    /// The row below or the column to the right
+    After,
+}
+
+impl Inherit {
+    /// Parses "above" or "below" for rows and "left" or "right" for columns.
+    pub fn parse(axis: OutlineAxis, value: &str) -> Result<Inherit, String> {
+        match (axis, value) {
+            (OutlineAxis::Rows, "above") | (OutlineAxis::Columns, "left") => Ok(Inherit::Before),
+            (OutlineAxis::Rows, "below") | (OutlineAxis::Columns, "right") => Ok(Inherit::After),
+            (OutlineAxis::Rows, _) => Err(format!(
+                "Invalid inherit option '{}', expected above or below",
+                value
+            )),
+            (OutlineAxis::Columns, _) => Err(format!(
+                "Invalid inherit option '{}', expected left or right",
+                value
+            )),
+        }
+    }
+}
+
+/// Inserts `amount` rows or columns before `index`.
+///
+/// Tables and data validations grow when the rows or columns are inserted inside
+/// them and move when they are inserted before them, as in Excel. With `inherit`,
+/// the new rows or columns take the size, styles, formulas and data validations of
+/// their neighbour.
+pub fn insert(
+    sheet: &mut Worksheet,
+    axis: OutlineAxis,
+    index: u32,
+    amount: u32,
+    inherit: Option<Inherit>,
+) {
+    if amount == 0 {
+        return;
+    }
+    match axis {
+        OutlineAxis::Rows => sheet.insert_new_row(&index, &amount),
+        OutlineAxis::Columns => sheet.insert_new_column_by_index(&index, &amount),
+    }
+    let delta = i64::from(amount);
+    adjust_tables(sheet, axis, index, delta);
+    adjust_data_validations(sheet, axis, index, delta);
+
+    let source = match inherit {
+        Some(Inherit::Before) if index > 1 => Some(index - 1),
+        Some(Inherit::After) => Some(index + amount),
+        _ => None,
+    };
+    if let Some(source) = source {
+        for target in index..index + amount {
+            copy_line(sheet, axis, source, target);
+        }
+        extend_data_validations(sheet, axis, source, (index, index + amount - 1));
+    }
+}
+
+/// Removes `amount` rows or columns starting at `index`.
+///
+/// Tables and data validations shrink with them and are dropped when all of
+/// their rows or columns are removed.
+pub fn remove(sheet: &mut Worksheet, axis: OutlineAxis, index: u32, amount: u32) {
+    if amount == 0 {
+        return;
+    }
+    match axis {
+        OutlineAxis::Rows => sheet.remove_row(&index, &amount),
+        OutlineAxis::Columns => sheet.remove_column_by_index(&index, &amount),
+    }
+    let delta = -i64::from(amount);
+    adjust_tables(sheet, axis, index, delta);
+    adjust_data_validations(sheet, axis, index, delta);
+}
+
+/// Moves a span `start..=end` for `delta` rows or columns inserted (positive) or
+/// removed (negative) at `index`. Returns `None` when the whole span is removed.
+fn adjust_span(start: u32, end: u32, index: u32, delta: i64) -> Option<(u32, u32)> {
+    if delta >= 0 {
+        let amount = delta as u32;
+        return Some(if index <= start {
+            (start + amount, end + amount)
+        } else if index <= end {
+            (start, end + amount)
+        } else {
+            (start, end)
+        });
+    }
+
+    let amount = delta.unsigned_abs() as u32;
+    let last_removed = index + amount - 1;
+    let moved = |value: u32, inside: u32| {
+        if value < index {
+            value
+        } else if value > last_removed {
+            value - amount
+        } else {
+            inside
+        }
+    };
+    let start = moved(start, index);
+    let end = moved(end, index.saturating_sub(1));
+    (start <= end).then_some((start, end))
+}
+
+/// Applies `adjust_span` to the rows or columns of a cell rectangle.
+fn adjust_bounds(bounds: Bounds, axis: OutlineAxis, index: u32, delta: i64) -> Option<Bounds> {
+    let (start_column, start_row, end_column, end_row) = bounds;
+    match axis {
+        OutlineAxis::Rows => adjust_span(start_row, end_row, index, delta)
+            .map(|(start, end)| (start_column, start, end_column, end)),
+        OutlineAxis::Columns => adjust_span(start_column, end_column, index, delta)
+            .map(|(start, end)| (start, start_row, end, end_row)),
+    }
+}
+
+fn adjust_tables(sheet: &mut Worksheet, axis: OutlineAxis, index: u32, delta: i64) {
+    let tables: Vec<Table> = sheet.get_tables_mut().drain(..).collect();
+    for table in tables {
+        let (start, end) = table.get_area();
+        let bounds = (
+            *start.get_col_num(),
+            *start.get_row_num(),
+            *end.get_col_num(),
+            *end.get_row_num(),
+        );
+        let Some(new_bounds) = adjust_bounds(bounds, axis, index, delta) else {
+            continue;
+        };
+
+        let columns = match axis {
+            OutlineAxis::Rows => table.get_columns().to_vec(),
+            OutlineAxis::Columns => {
+                adjust_table_columns(sheet, &table, bounds, new_bounds, index, delta)
+            }
+        };
+        // Removing the totals row turns it off, as in Excel
+        let totals_row_removed = axis == OutlineAxis::Rows
+            && delta < 0
+            && (index..index + delta.unsigned_abs() as u32).contains(&bounds.3);
+        let totals_row = *table.get_totals_row_shown() && !totals_row_removed;
+        sheet
+            .get_tables_mut()
+            .push(rebuild_table(&table, new_bounds, columns, totals_row));
+    }
+}
+
+/// Adds columns to a table for columns inserted inside it, or drops the columns
+/// that were removed. New columns get unique names, written to the header row.
+fn adjust_table_columns(
+    sheet: &mut Worksheet,
+    table: &Table,
+    bounds: Bounds,
+    new_bounds: Bounds,
+    index: u32,
+    delta: i64,
+) -> Vec<TableColumn> {
+    let mut columns = table.get_columns().to_vec();
+    let grows = new_bounds.2 - new_bounds.0 > bounds.2 - bounds.0;
+    if delta > 0 && grows {
+        let position = ((index - bounds.0) as usize).min(columns.len());
+        for offset in 0..delta as u32 {
+            let name = unique_column_name(&columns);
+            let header = sheet.get_cell_mut((index + offset, new_bounds.1));
+            if header.get_value().is_empty() {
+                header.set_value_string(name.clone());
+            }
+            columns.insert(position + offset as usize, TableColumn::new(&name));
+        }
+    } else if delta < 0 {
+        let last_removed = index + delta.unsigned_abs() as u32 - 1;
+        let mut column = bounds.0;
+        columns.retain(|_| {
+            let keep = column < index || column > last_removed;
+            column += 1;
+            keep
+        });
+    }
+    columns
+}
+
+fn unique_column_name(columns: &[TableColumn]) -> String {
+    (1..)
+        .map(|number| format!("Column{}", number))
+        .find(|name| {
+            !columns
+                .iter()
+                .any(|column| column.get_name().eq_ignore_ascii_case(name))
+        })
+        .unwrap_or_default()
+}
+
+/// Builds a copy of `table` with a new area and columns, as umya-spreadsheet does
+/// not give access to the columns of a table.
+fn rebuild_table(
+    table: &Table,
+    bounds: Bounds,
+    columns: Vec<TableColumn>,
+    totals_row: bool,
+) -> Table {
+    let mut rebuilt = Table::default();
+    rebuilt.set_name(table.get_name());
+    rebuilt.set_display_name(table.get_display_name());
+    rebuilt.set_area((
+        CellCoordinates::from((bounds.0, bounds.1)),
+        CellCoordinates::from((bounds.2, bounds.3)),
+    ));
+    rebuilt.set_totals_row_shown(totals_row);
+    if totals_row && *table.get_totals_row_count() > 0 {
+        rebuilt.set_totals_row_count(*table.get_totals_row_count());
+    }
+    rebuilt.set_style_info(table.get_style_info().cloned());
+    for column in columns {
+        rebuilt.add_column(column);
+    }
+    rebuilt
+}
+
+fn adjust_data_validations(sheet: &mut Worksheet, axis: OutlineAxis, index: u32, delta: i64) {
+    let Some(validations) = sheet.get_data_validations_mut() else {
+        return;
+    };
+    validations
+        .get_data_validation_list_mut()
+        .retain_mut(|validation| {
+            let sqref = validation.get_sequence_of_references().get_sqref();
+            let ranges: Vec<String> = sqref_bounds(&sqref)
+                .into_iter()
+                .filter_map(|bounds| adjust_bounds(bounds, axis, index, delta))
+                .map(range_helper::format_bounds)
+                .collect();
+            if ranges.is_empty() {
+                return false;
+            }
+            range_helper::replace_sqref(
+                validation.get_sequence_of_references_mut(),
+                &ranges.join(" "),
+            );
+            true
+        });
+    if validations.get_data_validation_list().is_empty() {
+        // Excel does not accept an empty dataValidations element
+        sheet.remove_data_validations();
+    }
+}
+
+/// Gives the inserted rows or columns `targets` the data validations of the
+/// `source` row or column.
+fn extend_data_validations(
+    sheet: &mut Worksheet,
+    axis: OutlineAxis,
+    source: u32,
+    targets: (u32, u32),
+) {
+    let Some(validations) = sheet.get_data_validations_mut() else {
+        return;
+    };
+    for validation in validations.get_data_validation_list_mut().iter_mut() {
+        let sqref = validation.get_sequence_of_references().get_sqref();
+        let mut ranges = sqref_bounds(&sqref);
+        let mut added = Vec::new();
+        for bounds in &ranges {
+            let (start_column, start_row, end_column, end_row) = *bounds;
+            let new_area = match axis {
+                OutlineAxis::Rows if (start_row..=end_row).contains(&source) => {
+                    (start_column, targets.0, end_column, targets.1)
+                }
+                OutlineAxis::Columns if (start_column..=end_column).contains(&source) => {
+                    (targets.0, start_row, targets.1, end_row)
+                }
+                _ => continue,
+            };
+            let covered = ranges
+                .iter()
+                .any(|range| range_helper::intersect(*range, new_area) == Some(new_area));
+            if !covered {
+                added.push(new_area);
+            }
+        }
+        if added.is_empty() {
+            continue;
+        }
+        ranges.extend(added);
+        let ranges: Vec<String> = ranges
+            .into_iter()
+            .map(range_helper::format_bounds)
+            .collect();
+        range_helper::replace_sqref(
+            validation.get_sequence_of_references_mut(),
+            &ranges.join(" "),
+        );
+    }
+}
+
+fn sqref_bounds(sqref: &str) -> Vec<Bounds> {
+    sqref
+        .split_whitespace()
+        .filter_map(|range| range_helper::parse_range(range).ok())
+        .filter(|target| matches!(target, RangeTarget::Cells { .. }))
+        .map(|target| target.bounds())
+        .collect()
+}
+
+/// Copies the size, styles and formulas of row or column `source` to `target`.
+///
+/// Values are not copied, and formulas move their relative references as they do
+/// when Excel fills them into the new row or column.
+fn copy_line(sheet: &mut Worksheet, axis: OutlineAxis, source: u32, target: u32) {
+    let offset = i64::from(target) - i64::from(source);
+    let cells: Vec<(u32, umya_spreadsheet::Style, String)> = match axis {
+        OutlineAxis::Rows => sheet.get_collection_by_row(&source),
+        OutlineAxis::Columns => sheet.get_collection_by_column(&source),
+    }
+    .into_iter()
+    .map(|cell| {
+        let coordinate = cell.get_coordinate();
+        let position = match axis {
+            OutlineAxis::Rows => *coordinate.get_col_num(),
+            OutlineAxis::Columns => *coordinate.get_row_num(),
+        };
+        (
+            position,
+            cell.get_style().clone(),
+            cell.get_formula().to_string(),
+        )
+    })
+    .collect();
+
+    for (position, style, formula) in cells {
+        let (column, row, column_offset, row_offset) = match axis {
+            OutlineAxis::Rows => (position, target, 0, offset),
+            OutlineAxis::Columns => (target, position, offset, 0),
+        };
+        let cell = sheet.get_cell_mut((column, row));
+        cell.set_style(style);
+        if !formula.is_empty() {
+            cell.set_formula(formula_helper::shift_formula(
+                &formula,
+                column_offset,
+                row_offset,
+            ));
+        }
+    }
+
+    match axis {
+        OutlineAxis::Rows => {
+            let Some(dimension) = sheet.get_row_dimension(&source) else {
+                return;
+            };
+            let height = *dimension.get_height();
+            let custom_height = *dimension.get_custom_height();
+            let style = dimension.get_style().clone();
+            let row = sheet.get_row_dimension_mut(&target);
+            row.set_style(style);
+            if height > 0.0 {
+                row.set_height(height);
+            }
+            row.set_custom_height(custom_height);
+        }
+        OutlineAxis::Columns => {
+            let Some(dimension) = sheet.get_column_dimension_by_number(&source) else {
+                return;
+            };
+            let width = *dimension.get_width();
+            let style = dimension.get_style().clone();
+            let column = sheet.get_column_dimension_by_number_mut(&target);
+            column.set_width(width);
+            column.set_style(style);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use umya_spreadsheet::{SheetStateValues, Spreadsheet, Worksheet};
+
+/// Parses a sheet state as Excel writes it or as this library documents it:
+/// "visible", "hidden" and "veryHidden", also spelled "veryhidden" or "very_hidden".
+pub fn parse_sheet_state(state: &str) -> Result<SheetStateValues, String> {
+    match state.to_lowercase().replace('_', "").as_str() {
+        "visible" => Ok(SheetStateValues::Visible),
+        "hidden" => Ok(SheetStateValues::Hidden),
+        "veryhidden" => Ok(SheetStateValues::VeryHidden),
+        _ => Err(format!(
+            "Invalid sheet state '{}'; use visible, hidden or veryhidden",
+            state
+        )),
+    }
+}
+
+pub fn sheet_state_name(state: &SheetStateValues) -> &'static str {
+    match state {
+        SheetStateValues::Visible => "visible",
+        SheetStateValues::Hidden => "hidden",
+        SheetStateValues::VeryHidden => "veryhidden",
+    }
+}
+
+pub fn is_visible(sheet: &Worksheet) -> bool {
+    matches!(sheet.get_state(), SheetStateValues::Visible)
+}
+
+/// Shows or hides the sheet at `sheet_index`.
+///
+/// Excel needs one visible sheet to open a workbook, so hiding the last one fails.
+/// A hidden sheet cannot be active either: when the active sheet is hidden, the
+/// first visible sheet becomes active instead, as in Excel.
+pub fn set_sheet_state(
+    spreadsheet: &mut Spreadsheet,
+    sheet_index: usize,
+    state: SheetStateValues,
+) -> Result<(), String> {
+    let hiding = !matches!(state, SheetStateValues::Visible);
+    let first_other_visible = spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .enumerate()
+        .find(|(index, sheet)| *index != sheet_index && is_visible(sheet))
+        .map(|(index, _)| index);
+    if hiding && first_other_visible.is_none() {
+        return Err("A workbook must keep at least one visible sheet".to_string());
+    }
+
+    let sheet = spreadsheet
+        .get_sheet_mut(&sheet_index)
+        .ok_or_else(|| "Sheet not found".to_string())?;
+    sheet.set_state(state);
+    if !hiding {
+        return Ok(());
+    }
+    for sheet_view in sheet.get_sheet_views_mut().get_sheet_view_list_mut() {
+        sheet_view.set_tab_selected(false);
+    }
+
+    let active_tab = *spreadsheet.get_workbook_view().get_active_tab() as usize;
+    if let Some(first_visible) = first_other_visible.filter(|_| active_tab == sheet_index) {
+        spreadsheet.set_active_sheet(first_visible as u32);
+        if let Some(sheet) = spreadsheet.get_sheet_mut(&first_visible) {
+            for sheet_view in sheet.get_sheet_views_mut().get_sheet_view_list_mut() {
+                sheet_view.set_tab_selected(true);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::helpers::defined_name_helper;
+use umya_spreadsheet::{CellFormulaValues, DefinedName, Spreadsheet, Worksheet};
+
+/// Applies `rewrite` to the formulas of cells, conditional formats and data
+/// validations, and to the targets of hyperlinks that point into the workbook.
+pub fn rewrite_formulas<F: Fn(&str) -> String>(sheet: &mut Worksheet, rewrite: &F) {
+    for cell in sheet.get_cell_collection_mut() {
+        if let Some(formula) = cell.get_formula_obj() {
+            let text = formula.get_text().to_string();
+            let rewritten = rewrite(&text);
+            if formula.get_formula_type() == &CellFormulaValues::Shared {
+                // Every cell of a shared formula holds its own text after reading, so
+                // the cells are written as plain formulas rather than mixing both
+                cell.get_cell_value_mut().set_formula(rewritten);
+            } else if rewritten != text {
+                let mut formula = formula.clone();
+                formula.set_text(rewritten);
+                cell.get_cell_value_mut().set_formula_obj(formula);
+            }
+        }
+
+        if let Some(hyperlink) = cell.get_hyperlink() {
+            if *hyperlink.get_location() {
+                let target = rewrite(hyperlink.get_url());
+                if target != hyperlink.get_url() {
+                    cell.get_hyperlink_mut().set_url(target);
+                }
+            }
+        }
+    }
+
+    let mut conditional_formats = sheet.get_conditional_formatting_collection().to_vec();
+    for conditional_format in &mut conditional_formats {
+        for rule in conditional_format.get_conditional_collection_mut() {
+            if let Some(formula) = rule.get_formula() {
+                let mut formula = formula.clone();
+                formula.set_string_value(rewrite(&formula.get_address_str()));
+                rule.set_formula(formula);
+            }
+        }
+    }
+    sheet.set_conditional_formatting_collection(conditional_formats);
+
+    if let Some(validations) = sheet.get_data_validations_mut() {
+        for validation in validations.get_data_validation_list_mut().iter_mut() {
+            let formula1 = rewrite(validation.get_formula1());
+            let formula2 = rewrite(validation.get_formula2());
+            validation.set_formula1(formula1).set_formula2(formula2);
+        }
+    }
+}
+
+/// Points chart series and pivot table sources on `old_name` at `new_name`.
+///
+/// Both keep the sheet name apart from the range, so no formula needs parsing.
+pub fn rename_sheet_sources(sheet: &mut Worksheet, old_name: &str, new_name: &str) {
+    let matches = |sheet_name: &str| sheet_name.to_lowercase() == old_name.to_lowercase();
+
+    for chart in sheet.get_chart_collection_mut().iter_mut() {
+        for formula in chart.get_plot_area_mut().get_formula_mut() {
+            let address = formula.get_address_mut();
+            if matches(address.get_sheet_name()) {
+                address.set_sheet_name(new_name);
+            }
+        }
+    }
+
+    for pivot_table in sheet.get_pivot_tables_mut().iter_mut() {
+        let source = pivot_table
+            .get_pivot_cache_definition_mut()
+            .get_cache_source_mut()
+            .get_worksheet_source_mut();
+        if let Some(source) = source {
+            let address = source.get_address_mut();
+            if matches(address.get_sheet_name()) {
+                address.set_sheet_name(new_name);
+            }
+        }
+    }
+}
+
+/// Applies `rewrite` to what every defined name of the workbook refers to.
+pub fn rewrite_defined_names<F: Fn(&str) -> String>(spreadsheet: &mut Spreadsheet, rewrite: &F) {
+    let update = |defined_name: &mut DefinedName| {
+        let refers_to = defined_name.get_address();
+        let rewritten = rewrite(&refers_to);
+        if rewritten == refers_to {
+            return;
+        }
+        // `set_address` appends to the existing areas, so the name is rebuilt
+        let mut rebuilt =
+            defined_name_helper::new_defined_name(defined_name.get_name(), &rewritten);
+        if let Some(local_sheet_id) = defined_name_helper::scope_of(defined_name) {
+            rebuilt.set_local_sheet_id(local_sheet_id);
+        }
+        rebuilt.set_hidden(*defined_name.get_hidden());
+        *defined_name = rebuilt;
+    };
+
+    spreadsheet
+        .get_defined_names_mut()
+        .iter_mut()
+        .for_each(update);
+    for sheet in spreadsheet.get_sheet_collection_mut().iter_mut() {
+        sheet.get_defined_names_mut().iter_mut().for_each(update);
+    }
+}
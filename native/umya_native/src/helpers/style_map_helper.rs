@@ -0,0 +1,710 @@
+use crate::helpers::color_helper;
+use rustler::{Encoder, Env, Term};
+use std::collections::HashMap;
+use std::str::FromStr;
+use umya_spreadsheet::drawing::Theme;
+use umya_spreadsheet::{
+    Border, BorderStyleValues, Color, EnumTrait, HorizontalAlignmentValues, PatternValues, Style,
+    VerticalAlignmentValues, Worksheet,
+};
+
+/// Top-level keys of a style map.
+const STYLE_KEYS: [&str; 6] = [
+    "font",
+    "fill",
+    "borders",
+    "alignment",
+    "number_format",
+    "protection",
+];
+/// Top-level keys of a style map for a conditional format (dxf).
+const DIFFERENTIAL_STYLE_KEYS: [&str; 4] = ["font", "fill", "borders", "number_format"];
+const FONT_KEYS: [&str; 7] = [
+    "name",
+    "size",
+    "bold",
+    "italic",
+    "underline",
+    "strikethrough",
+    "color",
+];
+const FILL_KEYS: [&str; 3] = ["pattern", "color", "background_color"];
+const BORDER_SIDES: [&str; 5] = ["left", "right", "top", "bottom", "diagonal"];
+const BORDER_KEYS: [&str; 2] = ["style", "color"];
+const ALIGNMENT_KEYS: [&str; 4] = ["horizontal", "vertical", "wrap_text", "text_rotation"];
+const PROTECTION_KEYS: [&str; 2] = ["locked", "hidden"];
+
+/// A decoded style map, applied to many cells without decoding it again.
+///
+/// Every field is optional: `None` leaves that part of a style untouched.
+#[derive(Clone, Debug, Default)]
+pub struct StylePatch {
+    font: FontPatch,
+    fill: Option<FillPatch>,
+    borders: BordersPatch,
+    alignment: AlignmentPatch,
+    number_format: Option<String>,
+    protection: ProtectionPatch,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FontPatch {
+    name: Option<String>,
+    size: Option<f64>,
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underline: Option<String>,
+    strikethrough: Option<bool>,
+    color: Option<Color>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FillPatch {
+    pattern: Option<PatternValues>,
+    color: Option<Color>,
+    background_color: Option<Color>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct BorderPatch {
+    style: Option<String>,
+    color: Option<Color>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct BordersPatch {
+    sides: Vec<(&'static str, BorderPatch)>,
+    diagonal_up: Option<bool>,
+    diagonal_down: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct AlignmentPatch {
+    horizontal: Option<HorizontalAlignmentValues>,
+    vertical: Option<VerticalAlignmentValues>,
+    wrap_text: Option<bool>,
+    text_rotation: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ProtectionPatch {
+    locked: Option<bool>,
+    hidden: Option<bool>,
+}
+
+impl StylePatch {
+    /// Decodes a style map with string keys.
+    ///
+    /// ```text
+    /// %{"font" => %{"bold" => true, "color" => "#FF0000"},
+    ///   "fill" => %{"color" => "#FFFF00"},
+    ///   "borders" => %{"all" => %{"style" => "thin"}, "bottom" => "double"},
+    ///   "alignment" => %{"horizontal" => "center", "wrap_text" => true},
+    ///   "number_format" => "0.00",
+    ///   "protection" => %{"locked" => false}}
+    /// ```
+    ///
+    /// `nil` values count as absent. Unknown keys and invalid values are errors.
+    pub fn decode(term: Term) -> Result<Self, String> {
+        let map = decode_map(term, "style")?;
+        check_keys(&map, &STYLE_KEYS, "")?;
+
+        let mut patch = StylePatch {
+            number_format: optional(&map, "number_format", "")?,
+            ..StylePatch::default()
+        };
+
+        if let Some(font) = optional_map(&map, "font")? {
+            check_keys(&font, &FONT_KEYS, "font.")?;
+            patch.font = FontPatch {
+                name: optional(&font, "name", "font.")?,
+                size: optional_number(&font, "size", "font.")?,
+                bold: optional(&font, "bold", "font.")?,
+                italic: optional(&font, "italic", "font.")?,
+                underline: optional::<String>(&font, "underline", "font.")?
+                    .map(|value| parse_underline(&value))
+                    .transpose()?,
+                strikethrough: optional(&font, "strikethrough", "font.")?,
+                color: optional_color(&font, "color", "font.")?,
+            };
+        }
+
+        if let Some(fill) = optional_map(&map, "fill")? {
+            check_keys(&fill, &FILL_KEYS, "fill.")?;
+            patch.fill = Some(FillPatch {
+                pattern: optional::<String>(&fill, "pattern", "fill.")?
+                    .map(|value| {
+                        PatternValues::from_str(&value)
+                            .map_err(|_| format!("Invalid fill pattern '{}'", value))
+                    })
+                    .transpose()?,
+                color: optional_color(&fill, "color", "fill.")?,
+                background_color: optional_color(&fill, "background_color", "fill.")?,
+            });
+        }
+
+        if let Some(borders) = optional_map(&map, "borders")? {
+            patch.borders = decode_borders(&borders)?;
+        }
+
+        if let Some(alignment) = optional_map(&map, "alignment")? {
+            check_keys(&alignment, &ALIGNMENT_KEYS, "alignment.")?;
+            patch.alignment = AlignmentPatch {
+                horizontal: optional::<String>(&alignment, "horizontal", "alignment.")?
+                    .map(|value| {
+                        HorizontalAlignmentValues::from_str(&value)
+                            .map_err(|_| format!("Invalid horizontal alignment '{}'", value))
+                    })
+                    .transpose()?,
+                vertical: optional::<String>(&alignment, "vertical", "alignment.")?
+                    .map(|value| {
+                        VerticalAlignmentValues::from_str(&value)
+                            .map_err(|_| format!("Invalid vertical alignment '{}'", value))
+                    })
+                    .transpose()?,
+                wrap_text: optional(&alignment, "wrap_text", "alignment.")?,
+                text_rotation: optional(&alignment, "text_rotation", "alignment.")?,
+            };
+        }
+
+        if let Some(protection) = optional_map(&map, "protection")? {
+            check_keys(&protection, &PROTECTION_KEYS, "protection.")?;
+            patch.protection = ProtectionPatch {
+                locked: optional(&protection, "locked", "protection.")?,
+                hidden: optional(&protection, "hidden", "protection.")?,
+            };
+        }
+
+        Ok(patch)
+    }
+
+    /// Decodes a style map for a conditional format, which has no alignment or protection.
+    pub fn decode_differential(term: Term) -> Result<Self, String> {
+        let map = decode_map(term, "style")?;
+        check_keys(&map, &DIFFERENTIAL_STYLE_KEYS, "")?;
+        Self::decode(term)
+    }
+
+    /// Writes the given parts of the patch into `style`, keeping everything else.
+    pub fn apply(&self, style: &mut Style) {
+        self.apply_font(style);
+
+        if let Some(fill) = &self.fill {
+            let pattern_fill = style.get_fill_mut().get_pattern_fill_mut();
+            match &fill.pattern {
+                Some(pattern) => {
+                    pattern_fill.set_pattern_type(pattern.clone());
+                }
+                // A color alone means a solid fill, as with set_background_color
+                None if fill.color.is_some()
+                    && *pattern_fill.get_pattern_type() == PatternValues::None =>
+                {
+                    pattern_fill.set_pattern_type(PatternValues::Solid);
+                }
+                None => (),
+            }
+            if let Some(color) = &fill.color {
+                pattern_fill.set_foreground_color(color.clone());
+            }
+            if let Some(color) = &fill.background_color {
+                pattern_fill.set_background_color(color.clone());
+            }
+        }
+
+        self.apply_borders(style);
+
+        let alignment = &self.alignment;
+        if let Some(horizontal) = &alignment.horizontal {
+            style.get_alignment_mut().set_horizontal(horizontal.clone());
+        }
+        if let Some(vertical) = &alignment.vertical {
+            style.get_alignment_mut().set_vertical(vertical.clone());
+        }
+        if let Some(wrap_text) = alignment.wrap_text {
+            style.get_alignment_mut().set_wrap_text(wrap_text);
+        }
+        if let Some(text_rotation) = alignment.text_rotation {
+            style.get_alignment_mut().set_text_rotation(text_rotation);
+        }
+
+        if let Some(format_code) = &self.number_format {
+            style
+                .get_number_format_mut()
+                .set_format_code(format_code.as_str());
+        }
+
+        if let Some(locked) = self.protection.locked {
+            style.get_protection_mut().set_locked(locked);
+        }
+        if let Some(hidden) = self.protection.hidden {
+            style.get_protection_mut().set_hidden(hidden);
+        }
+    }
+
+    fn apply_font(&self, style: &mut Style) {
+        let font = &self.font;
+        if let Some(name) = &font.name {
+            style.get_font_mut().set_name(name.as_str());
+        }
+        if let Some(size) = font.size {
+            style.get_font_mut().set_size(size);
+        }
+        if let Some(bold) = font.bold {
+            style.get_font_mut().set_bold(bold);
+        }
+        if let Some(italic) = font.italic {
+            style.get_font_mut().set_italic(italic);
+        }
+        if let Some(underline) = &font.underline {
+            style.get_font_mut().set_underline(underline.as_str());
+        }
+        if let Some(strikethrough) = font.strikethrough {
+            style.get_font_mut().set_strikethrough(strikethrough);
+        }
+        if let Some(color) = &font.color {
+            style.get_font_mut().set_color(color.clone());
+        }
+    }
+
+    fn apply_borders(&self, style: &mut Style) {
+        for (side, patch) in &self.borders.sides {
+            let borders = style.get_borders_mut();
+            let border: &mut Border = match *side {
+                "left" => borders.get_left_mut(),
+                "right" => borders.get_right_mut(),
+                "top" => borders.get_top_mut(),
+                "bottom" => borders.get_bottom_mut(),
+                _ => borders.get_diagonal_mut(),
+            };
+            if let Some(border_style) = &patch.style {
+                border.set_border_style(border_style.as_str());
+            }
+            if let Some(color) = &patch.color {
+                border.set_color(color.clone());
+            }
+        }
+        if let Some(diagonal_up) = self.borders.diagonal_up {
+            style.get_borders_mut().set_diagonal_up(diagonal_up);
+        }
+        if let Some(diagonal_down) = self.borders.diagonal_down {
+            style.get_borders_mut().set_diagonal_down(diagonal_down);
+        }
+    }
+}
+
+/// Returns the style that applies to a cell.
+///
+/// A cell that does not exist takes the style of its row when the row is formatted,
+/// and otherwise the style of its column, as Excel does.
+pub fn effective_style(sheet: &Worksheet, column: u32, row: u32) -> Style {
+    if let Some(cell) = sheet.get_cell((column, row)) {
+        return cell.get_style().clone();
+    }
+    if let Some(row_dimension) = sheet.get_row_dimension(&row) {
+        if *row_dimension.get_style() != Style::default() {
+            return row_dimension.get_style().clone();
+        }
+    }
+    match sheet.get_column_dimension_by_number(&column) {
+        Some(column_dimension) => column_dimension.get_style().clone(),
+        None => Style::default(),
+    }
+}
+
+/// Encodes the complete style as a nested map with the keys of a style map.
+///
+/// Parts the style does not set are filled in with Excel's defaults and theme colors
+/// are resolved to ARGB, so two maps compare equal when the cells look the same.
+/// Colors that are not set are nil. Besides the keys `decode` accepts, `fill` has a
+/// `gradient` entry, which is nil for pattern fills.
+pub fn encode_style<'a>(env: Env<'a>, style: &Style, theme: &Theme) -> Term<'a> {
+    let color = |color: Option<&Color>| -> Term<'a> {
+        color
+            .map(|color| color.get_argb_with_theme(theme))
+            .filter(|argb| !argb.is_empty())
+            // Theme colors are stored as RGB
+            .map(|argb| match argb.len() {
+                6 => format!("FF{}", argb),
+                _ => argb.to_string(),
+            })
+            .encode(env)
+    };
+    let default_style = Style::get_default_value();
+
+    let font = style.get_font().or(default_style.get_font()).unwrap();
+    let mut font_map = HashMap::new();
+    font_map.insert("name", font.get_name().encode(env));
+    font_map.insert("size", font.get_size().encode(env));
+    font_map.insert("bold", font.get_bold().encode(env));
+    font_map.insert("italic", font.get_italic().encode(env));
+    // get_underline reports "single" when no underline is set
+    font_map.insert(
+        "underline",
+        font.get_font_underline()
+            .get_val()
+            .get_value_string()
+            .encode(env),
+    );
+    font_map.insert("strikethrough", font.get_strikethrough().encode(env));
+    font_map.insert("color", color(Some(font.get_color())));
+
+    let mut fill_map = HashMap::new();
+    let pattern_fill = style.get_fill().and_then(|fill| fill.get_pattern_fill());
+    let pattern = pattern_fill
+        .map(|pattern_fill| pattern_fill.get_pattern_type().get_value_string())
+        .unwrap_or("none");
+    fill_map.insert("pattern", pattern.encode(env));
+    fill_map.insert(
+        "color",
+        color(pattern_fill.and_then(|pattern_fill| pattern_fill.get_foreground_color())),
+    );
+    fill_map.insert(
+        "background_color",
+        color(pattern_fill.and_then(|pattern_fill| pattern_fill.get_background_color())),
+    );
+    let gradient = style
+        .get_fill()
+        .and_then(|fill| fill.get_gradient_fill())
+        .map(|gradient_fill| {
+            let stops: Vec<HashMap<&str, Term>> = gradient_fill
+                .get_gradient_stop()
+                .iter()
+                .map(|stop| {
+                    let mut stop_map = HashMap::new();
+                    stop_map.insert("position", stop.get_position().encode(env));
+                    stop_map.insert("color", color(Some(stop.get_color())));
+                    stop_map
+                })
+                .collect();
+            let mut gradient_map = HashMap::new();
+            gradient_map.insert("degree", gradient_fill.get_degree().encode(env));
+            gradient_map.insert("stops", stops.encode(env));
+            gradient_map
+        });
+    fill_map.insert("gradient", gradient.encode(env));
+
+    let mut borders_map = HashMap::new();
+    for side in BORDER_SIDES {
+        let border = style.get_borders().map(|borders| match side {
+            "left" => borders.get_left(),
+            "right" => borders.get_right(),
+            "top" => borders.get_top(),
+            "bottom" => borders.get_bottom(),
+            _ => borders.get_diagonal(),
+        });
+        let mut border_map = HashMap::new();
+        border_map.insert(
+            "style",
+            border
+                .map(|border| border.get_border_style())
+                .unwrap_or("none")
+                .encode(env),
+        );
+        border_map.insert("color", color(border.map(|border| border.get_color())));
+        borders_map.insert(side, border_map.encode(env));
+    }
+    let borders = style.get_borders();
+    borders_map.insert(
+        "diagonal_up",
+        borders
+            .map(|borders| *borders.get_diagonal_up())
+            .unwrap_or(false)
+            .encode(env),
+    );
+    borders_map.insert(
+        "diagonal_down",
+        borders
+            .map(|borders| *borders.get_diagonal_down())
+            .unwrap_or(false)
+            .encode(env),
+    );
+
+    let mut alignment_map = HashMap::new();
+    let alignment = style.get_alignment();
+    alignment_map.insert(
+        "horizontal",
+        alignment
+            .map(|alignment| alignment.get_horizontal().get_value_string())
+            .unwrap_or("general")
+            .encode(env),
+    );
+    alignment_map.insert(
+        "vertical",
+        alignment
+            .map(|alignment| alignment.get_vertical().get_value_string())
+            .unwrap_or("bottom")
+            .encode(env),
+    );
+    alignment_map.insert(
+        "wrap_text",
+        alignment
+            .map(|alignment| *alignment.get_wrap_text())
+            .unwrap_or(false)
+            .encode(env),
+    );
+    alignment_map.insert(
+        "text_rotation",
+        alignment
+            .map(|alignment| *alignment.get_text_rotation())
+            .unwrap_or(0)
+            .encode(env),
+    );
+
+    let number_format = style
+        .get_numbering_format()
+        .map(|number_format| number_format.get_format_code())
+        .unwrap_or("General");
+
+    let mut protection_map = HashMap::new();
+    let mut protection = style.get_protection().cloned();
+    protection_map.insert(
+        "locked",
+        protection
+            .as_ref()
+            .map(|protection| *protection.get_locked())
+            .unwrap_or(true)
+            .encode(env),
+    );
+    protection_map.insert(
+        "hidden",
+        protection
+            .as_mut()
+            .map(|protection| *protection.get_hidden())
+            .unwrap_or(false)
+            .encode(env),
+    );
+
+    let mut map = HashMap::new();
+    map.insert("font", font_map.encode(env));
+    map.insert("fill", fill_map.encode(env));
+    map.insert("borders", borders_map.encode(env));
+    map.insert("alignment", alignment_map.encode(env));
+    map.insert("number_format", number_format.encode(env));
+    map.insert("protection", protection_map.encode(env));
+    map.encode(env)
+}
+
+/// Encodes the differential format (dxf) of a conditional format as a style map.
+///
+/// Unlike `encode_style`, parts the dxf does not set are nil, as the cell's own
+/// formatting shows through them.
+pub fn encode_differential_style<'a>(env: Env<'a>, style: &Style, theme: &Theme) -> Term<'a> {
+    let color = |color: Option<&Color>| -> Option<String> {
+        color
+            .map(|color| color.get_argb_with_theme(theme))
+            .filter(|argb| !argb.is_empty())
+            .map(|argb| match argb.len() {
+                6 => format!("FF{}", argb),
+                _ => argb.to_string(),
+            })
+    };
+
+    let font = style.get_font().map(|font| {
+        let mut font_map = HashMap::new();
+        let name = Some(font.get_name()).filter(|name| !name.is_empty());
+        font_map.insert("name", name.encode(env));
+        let size = Some(*font.get_size()).filter(|size| *size > 0.0);
+        font_map.insert("size", size.encode(env));
+        font_map.insert("bold", font.get_bold().encode(env));
+        font_map.insert("italic", font.get_italic().encode(env));
+        font_map.insert(
+            "underline",
+            font.get_font_underline()
+                .get_val()
+                .get_value_string()
+                .encode(env),
+        );
+        font_map.insert("strikethrough", font.get_strikethrough().encode(env));
+        font_map.insert("color", color(Some(font.get_color())).encode(env));
+        font_map
+    });
+
+    let fill = style
+        .get_fill()
+        .and_then(|fill| fill.get_pattern_fill())
+        .map(|pattern_fill| {
+            let mut fill_map = HashMap::new();
+            fill_map.insert(
+                "pattern",
+                pattern_fill
+                    .get_pattern_type()
+                    .get_value_string()
+                    .encode(env),
+            );
+            // Excel keeps the color of a solid dxf fill in bgColor only
+            let foreground = color(pattern_fill.get_foreground_color());
+            let background = color(pattern_fill.get_background_color());
+            fill_map.insert("color", foreground.or(background.clone()).encode(env));
+            fill_map.insert("background_color", background.encode(env));
+            fill_map
+        });
+
+    let borders = style.get_borders().map(|borders| {
+        let mut borders_map = HashMap::new();
+        for side in BORDER_SIDES {
+            let border = match side {
+                "left" => borders.get_left(),
+                "right" => borders.get_right(),
+                "top" => borders.get_top(),
+                "bottom" => borders.get_bottom(),
+                _ => borders.get_diagonal(),
+            };
+            let mut border_map = HashMap::new();
+            border_map.insert("style", border.get_border_style().encode(env));
+            border_map.insert("color", color(Some(border.get_color())).encode(env));
+            borders_map.insert(side, border_map.encode(env));
+        }
+        borders_map
+    });
+
+    let number_format = style
+        .get_numbering_format()
+        .map(|number_format| number_format.get_format_code());
+
+    let mut map = HashMap::new();
+    map.insert("font", font.encode(env));
+    map.insert("fill", fill.encode(env));
+    map.insert("borders", borders.encode(env));
+    map.insert("number_format", number_format.encode(env));
+    map.encode(env)
+}
+
+/// Decodes the `borders` map: one entry per side, plus `all` for the four outer sides.
+///
+/// A side is a map with `style` and `color`, or just the border style string.
+fn decode_borders(borders: &HashMap<String, Term>) -> Result<BordersPatch, String> {
+    let mut patch = BordersPatch {
+        diagonal_up: optional(borders, "diagonal_up", "borders.")?,
+        diagonal_down: optional(borders, "diagonal_down", "borders.")?,
+        ..BordersPatch::default()
+    };
+
+    if let Some(term) = borders.get("all").filter(|term| !is_nil(term)) {
+        let all = decode_border(*term, "all")?;
+        for side in &BORDER_SIDES[..4] {
+            patch.sides.push((side, all.clone()));
+        }
+    }
+
+    for (key, term) in borders {
+        if key == "all" || key == "diagonal_up" || key == "diagonal_down" || is_nil(term) {
+            continue;
+        }
+        let side = BORDER_SIDES
+            .iter()
+            .find(|side| **side == key.as_str())
+            .ok_or_else(|| format!("Unknown style key 'borders.{}'", key))?;
+        patch.sides.push((side, decode_border(*term, side)?));
+    }
+
+    Ok(patch)
+}
+
+fn decode_border(term: Term, side: &str) -> Result<BorderPatch, String> {
+    let prefix = format!("borders.{}.", side);
+    let patch = match term.decode::<String>() {
+        Ok(border_style) => BorderPatch {
+            style: Some(border_style),
+            color: None,
+        },
+        Err(_) => {
+            let map = decode_map(term, &format!("borders.{}", side))?;
+            check_keys(&map, &BORDER_KEYS, &prefix)?;
+            BorderPatch {
+                style: optional(&map, "style", &prefix)?,
+                color: optional_color(&map, "color", &prefix)?,
+            }
+        }
+    };
+
+    if let Some(border_style) = &patch.style {
+        BorderStyleValues::from_str(border_style)
+            .map_err(|_| format!("Invalid border style '{}'", border_style))?;
+    }
+    Ok(patch)
+}
+
+/// Maps the underline names of `set_font_underline` to their OOXML values.
+fn parse_underline(value: &str) -> Result<String, String> {
+    let underline = match value {
+        "none" => "none",
+        "single" => "single",
+        "double" => "double",
+        "single_accounting" | "singleAccounting" => "singleAccounting",
+        "double_accounting" | "doubleAccounting" => "doubleAccounting",
+        _ => return Err(format!("Invalid underline '{}'", value)),
+    };
+    Ok(underline.to_string())
+}
+
+fn decode_map<'a>(term: Term<'a>, key: &str) -> Result<HashMap<String, Term<'a>>, String> {
+    term.decode()
+        .map_err(|_| format!("Style key '{}' must be a map", key))
+}
+
+fn check_keys(map: &HashMap<String, Term>, keys: &[&str], prefix: &str) -> Result<(), String> {
+    match map.keys().find(|key| !keys.contains(&key.as_str())) {
+        Some(key) => Err(format!("Unknown style key '{}{}'", prefix, key)),
+        None => Ok(()),
+    }
+}
+
+fn is_nil(term: &Term) -> bool {
+    term.decode::<Option<bool>>()
+        .map(|value| value.is_none())
+        .unwrap_or(false)
+}
+
+fn optional<'a, T: rustler::Decoder<'a>>(
+    map: &HashMap<String, Term<'a>>,
+    key: &str,
+    prefix: &str,
+) -> Result<Option<T>, String> {
+    match map.get(key) {
+        Some(term) => term
+            .decode::<Option<T>>()
+            .map_err(|_| format!("Invalid value for style key '{}{}'", prefix, key)),
+        None => Ok(None),
+    }
+}
+
+/// Decodes a number that may be passed as an integer, e.g. a font size of 12.
+fn optional_number(
+    map: &HashMap<String, Term>,
+    key: &str,
+    prefix: &str,
+) -> Result<Option<f64>, String> {
+    match map.get(key) {
+        Some(term) => match term.decode::<Option<f64>>() {
+            Ok(value) => Ok(value),
+            Err(_) => term
+                .decode::<i64>()
+                .map(|value| Some(value as f64))
+                .map_err(|_| format!("Invalid value for style key '{}{}'", prefix, key)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn optional_color(
+    map: &HashMap<String, Term>,
+    key: &str,
+    prefix: &str,
+) -> Result<Option<Color>, String> {
+    match optional::<String>(map, key, prefix)? {
+        Some(value) => color_helper::create_color_object(&value)
+            .map(Some)
+            .map_err(|_| format!("Invalid color '{}'", value)),
+        None => Ok(None),
+    }
+}
+
+fn optional_map<'a>(
+    map: &HashMap<String, Term<'a>>,
+    key: &str,
+) -> Result<Option<HashMap<String, Term<'a>>>, String> {
+    match map.get(key) {
+        Some(term) if !is_nil(term) => decode_map(*term, key).map(Some),
+        _ => Ok(None),
+    }
+}
@@ -4,6 +4,7 @@ use umya_spreadsheet;
 // Import modules
 mod advanced_fills;
 mod auto_filter_functions;
+mod autofit_functions;
 mod cell_formatting;
 mod cell_functions;
 mod cell_operations;
@@ -12,9 +13,11 @@ mod comment_functions;
 pub mod conditional_formatting;
 pub mod conditional_formatting_additional;
 pub mod conditional_formatting_getters;
+mod conditional_formatting_management;
 pub mod custom_structs;
 mod data_validation;
 mod data_validation_getters;
+mod defined_name_functions;
 mod document_properties;
 mod drawing_functions;
 mod drawing_getters;
@@ -25,16 +28,22 @@ mod get_cell_formatting;
 mod helpers;
 mod hyperlink;
 mod image_functions;
+mod merge_cell_functions;
+mod named_style_functions;
 mod ole_object_functions;
+mod outline_functions;
 mod page_breaks;
 mod password_functions;
 mod pivot_table;
 mod print_settings;
+mod range_copy_functions;
 mod rich_text_functions;
 mod row_column_operations;
 mod set_background_color;
 mod set_cell_alignment;
+mod sheet_copy_functions;
 mod sheet_operations;
+mod sheet_property_functions;
 mod sheet_view_functions;
 mod styling_operations;
 mod table;
@@ -74,6 +83,12 @@ mod atoms {
         top_bottom,
         above_below_average,
         text_rule,
+        expression,
+        style,
+        time_period,
+        options,
+        priority,
+        stop_if_true,
         rule_type,
         rule_type_value,
         operator,
@@ -151,12 +166,19 @@ mod atoms {
         prompt_title,
         prompt_message,
         allow_blank,
+        value,
+
+        // Sheet position atoms
+        before,
+        after,
     }
 }
 
 // Primary data structure representing a spreadsheet
 pub struct UmyaSpreadsheet {
     spreadsheet: Mutex<umya_spreadsheet::Spreadsheet>,
+    // Workbook data umya-spreadsheet does not model; always lock after `spreadsheet`
+    package_extras: Mutex<helpers::package_helper::PackageExtras>,
 }
 
 // Register the NIF module
@@ -181,6 +203,7 @@ rustler::init!(
         file_operations::new_file_empty_worksheet,
         file_operations::read_file,
         file_operations::lazy_read_file,
+        file_operations::read_file_with_password,
         file_operations::write_file,
         file_operations::write_file_light,
         file_operations::write_file_with_password,
@@ -210,14 +233,24 @@ rustler::init!(
         sheet_operations::get_merge_cells,
         sheet_operations::add_sheet,
         sheet_operations::clone_sheet,
+        sheet_copy_functions::copy_sheet_to_workbook,
         sheet_operations::remove_sheet,
+        sheet_operations::move_sheet,
         sheet_operations::rename_sheet,
         sheet_operations::insert_new_row,
         sheet_operations::insert_new_column,
+        sheet_operations::insert_new_column_by_index,
         sheet_operations::set_sheet_protection,
-        sheet_operations::add_merge_cells,
+        sheet_operations::set_sheet_protection_options,
+        merge_cell_functions::add_merge_cells,
+        merge_cell_functions::merge_cells_across,
+        merge_cell_functions::unmerge_cells,
+        merge_cell_functions::get_merged_area,
         sheet_operations::set_sheet_state,
+        sheet_property_functions::get_sheet_properties,
+        sheet_property_functions::set_sheet_properties,
         sheet_operations::move_range,
+        range_copy_functions::copy_range,
         // Styling operations
         styling_operations::set_font_color,
         styling_operations::set_font_size,
@@ -225,6 +258,11 @@ rustler::init!(
         styling_operations::set_font_name,
         styling_operations::copy_row_styling,
         styling_operations::copy_column_styling,
+        styling_operations::set_range_style,
+        named_style_functions::add_named_style,
+        named_style_functions::list_named_styles,
+        named_style_functions::apply_named_style,
+        named_style_functions::update_named_style,
         // Advanced fills
         advanced_fills::set_gradient_fill,
         advanced_fills::set_linear_gradient_fill,
@@ -248,6 +286,23 @@ rustler::init!(
         row_column_operations::remove_row,
         row_column_operations::remove_column,
         row_column_operations::remove_column_by_index,
+        row_column_operations::set_rows_hidden,
+        row_column_operations::set_columns_hidden,
+        row_column_operations::set_rows_height,
+        row_column_operations::set_columns_width,
+        row_column_operations::reset_rows_height,
+        row_column_operations::get_row_custom_height,
+        row_column_operations::set_default_row_height,
+        row_column_operations::get_default_row_height,
+        row_column_operations::set_default_column_width,
+        row_column_operations::get_default_column_width,
+        autofit_functions::autofit_columns,
+        autofit_functions::autofit_rows,
+        outline_functions::set_outline_level,
+        outline_functions::group_outline,
+        outline_functions::ungroup_outline,
+        outline_functions::set_outline_collapsed,
+        outline_functions::get_outline,
         // Pivot table operations
         pivot_table::has_pivot_tables,
         pivot_table::add_pivot_table,
@@ -317,6 +372,8 @@ rustler::init!(
         get_cell_formatting::get_cell_format_code,
         get_cell_formatting::get_cell_locked,
         get_cell_formatting::get_cell_hidden,
+        get_cell_formatting::get_cell_style,
+        get_cell_formatting::get_range_styles,
         // Print settings functions
         print_settings::set_page_orientation,
         print_settings::set_paper_size,
@@ -348,6 +405,17 @@ rustler::init!(
         conditional_formatting_additional::add_text_rule,
         conditional_formatting_additional::add_icon_set,
         conditional_formatting_additional::add_above_below_average_rule,
+        conditional_formatting_additional::add_expression_rule,
+        conditional_formatting_additional::add_duplicate_values_rule,
+        conditional_formatting_additional::add_blanks_rule,
+        conditional_formatting_additional::add_errors_rule,
+        conditional_formatting_additional::add_time_period_rule,
+        conditional_formatting_management::remove_conditional_formatting,
+        conditional_formatting_management::remove_conditional_formatting_rule,
+        conditional_formatting_management::set_conditional_formatting_priority,
+        conditional_formatting_management::set_conditional_formatting_stop_if_true,
+        conditional_formatting_management::set_conditional_formatting_range,
+        conditional_formatting_management::clear_conditional_formatting,
         // Conditional formatting getter functions
         conditional_formatting_getters::get_conditional_formatting_rules_nif,
         conditional_formatting_getters::get_cell_value_rules_nif,
@@ -357,6 +425,11 @@ rustler::init!(
         conditional_formatting_getters::get_top_bottom_rules_nif,
         conditional_formatting_getters::get_above_below_average_rules_nif,
         conditional_formatting_getters::get_text_rules_nif,
+        conditional_formatting_getters::get_expression_rules_nif,
+        conditional_formatting_getters::get_duplicate_values_rules_nif,
+        conditional_formatting_getters::get_blanks_rules_nif,
+        conditional_formatting_getters::get_errors_rules_nif,
+        conditional_formatting_getters::get_time_period_rules_nif,
         // CSV functions
         write_csv_with_options::write_csv,
         write_csv_with_options::write_csv_with_options,
@@ -370,9 +443,13 @@ rustler::init!(
         data_validation::add_text_length_validation,
         data_validation::add_list_validation,
         data_validation::add_custom_validation,
+        data_validation::add_list_source_validation,
+        data_validation::update_data_validation,
         data_validation::remove_data_validation,
         // Data validation getter functions
         data_validation_getters::get_data_validations_nif,
+        data_validation_getters::get_data_validation_at_nif,
+        data_validation_getters::get_data_validation_violations_nif,
         data_validation_getters::get_list_validations_nif,
         data_validation_getters::get_number_validations_nif,
         data_validation_getters::get_date_validations_nif,
@@ -406,6 +483,9 @@ rustler::init!(
         workbook_protection_functions::is_workbook_protected,
         workbook_protection_functions::get_workbook_protection_details,
         workbook_protection_functions::set_workbook_protection,
+        workbook_protection_functions::set_workbook_protection_options,
+        workbook_protection_functions::remove_workbook_protection,
+        workbook_protection_functions::verify_workbook_password,
         // Comment functions
         comment_functions::add_comment,
         comment_functions::get_comment,
@@ -425,6 +505,16 @@ rustler::init!(
         formula_functions::create_named_range,
         formula_functions::create_defined_name,
         formula_functions::get_defined_names,
+        // Defined name functions
+        defined_name_functions::add_defined_name,
+        defined_name_functions::update_defined_name,
+        defined_name_functions::remove_defined_name,
+        defined_name_functions::get_defined_name,
+        defined_name_functions::list_defined_names,
+        defined_name_functions::resolve_defined_name,
+        defined_name_functions::validate_defined_name,
+        defined_name_functions::get_defined_name_values,
+        defined_name_functions::set_defined_name_values,
         // Formula getter functions
         formula_functions::is_formula,
         formula_functions::get_formula,
@@ -577,8 +667,10 @@ rustler::init!(
         file_format_options::write_with_compression,
         file_format_options::write_with_encryption_options,
         file_format_options::to_binary_xlsx,
+        file_format_options::to_encrypted_binary_xlsx,
         file_format_options::get_compression_level,
         file_format_options::is_encrypted,
+        file_format_options::is_file_encrypted,
         file_format_options::get_encryption_algorithm,
         // Advanced fills functions
         advanced_fills::set_gradient_fill,
@@ -0,0 +1,239 @@
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{HorizontalAlignmentValues, Range, Worksheet};
+
+use crate::atoms;
+use crate::helpers::range_helper::{self, Bounds, RangeTarget};
+use crate::UmyaSpreadsheet;
+
+/// Merge the cells of a range
+///
+/// The range must span more than one cell and must not overlap merged cells, which
+/// Excel would have to repair. With `center` the top-left cell is centered
+/// horizontally, and with `clear_non_anchor` the values and formulas of the other
+/// cells are removed, as Excel's "Merge & Center" does.
+#[rustler::nif]
+pub fn add_merge_cells(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    center: bool,
+    clear_non_anchor: bool,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let bounds = parse_merge_range(&range)?;
+        if bounds.0 == bounds.2 && bounds.1 == bounds.3 {
+            return Err(format!("Range '{}' must span more than one cell", range));
+        }
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        check_overlap(sheet, &[bounds])?;
+        merge(sheet, bounds, center, clear_non_anchor);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_merge_cells operation".to_string(),
+        )))),
+    }
+}
+
+/// Merge the cells of each row of a range separately, like Excel's "Merge Across"
+///
+/// Accepts the same options as `add_merge_cells`.
+#[rustler::nif]
+pub fn merge_cells_across(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    center: bool,
+    clear_non_anchor: bool,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let (start_column, start_row, end_column, end_row) = parse_merge_range(&range)?;
+        if start_column == end_column {
+            return Err(format!("Range '{}' must span more than one column", range));
+        }
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let rows: Vec<Bounds> = (start_row..=end_row)
+            .map(|row| (start_column, row, end_column, row))
+            .collect();
+        check_overlap(sheet, &rows)?;
+        for bounds in rows {
+            merge(sheet, bounds, center, clear_non_anchor);
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in merge_cells_across operation".to_string(),
+        )))),
+    }
+}
+
+/// Unmerge every merged area that overlaps a range
+///
+/// As in Excel, selecting any cell of a merged area unmerges all of it. Ranges
+/// without merged cells are left as they are.
+#[rustler::nif]
+pub fn unmerge_cells(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let bounds = parse_merge_range(&range)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        sheet.get_merge_cells_mut().retain(|merged| {
+            merged_bounds(merged)
+                .is_none_or(|merged| range_helper::intersect(merged, bounds).is_none())
+        });
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in unmerge_cells operation".to_string(),
+        )))),
+    }
+}
+
+/// Get the merged area that covers a cell
+///
+/// Returns the range of the merged area, or nil when the cell is not merged.
+#[rustler::nif]
+pub fn get_merged_area(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    cell_address: String,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Option<String>, String> {
+        let (column, row) = match range_helper::parse_range(&cell_address) {
+            Ok(RangeTarget::Cells {
+                start_column,
+                start_row,
+                end_column,
+                end_row,
+            }) if start_column == end_column && start_row == end_row => (start_column, start_row),
+            _ => return Err(format!("Invalid cell address '{}'", cell_address)),
+        };
+
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        Ok(sheet
+            .get_merge_cells()
+            .iter()
+            .filter_map(merged_bounds)
+            .find(|merged| range_helper::intersect(*merged, (column, row, column, row)).is_some())
+            .map(range_helper::format_bounds))
+    }));
+
+    match result {
+        Ok(Ok(area)) => (atoms::ok(), area).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_merged_area operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+fn parse_merge_range(range: &str) -> Result<Bounds, String> {
+    Ok(range_helper::parse_range(range)?.bounds())
+}
+
+fn merged_bounds(range: &Range) -> Option<Bounds> {
+    range_helper::parse_range(&range.get_range())
+        .ok()
+        .map(|target| target.bounds())
+}
+
+/// Fails when one of `areas` overlaps a merged area of the sheet.
+fn check_overlap(sheet: &Worksheet, areas: &[Bounds]) -> Result<(), String> {
+    for area in areas {
+        let existing = sheet
+            .get_merge_cells()
+            .iter()
+            .filter_map(merged_bounds)
+            .find(|merged| range_helper::intersect(*merged, *area).is_some());
+        if let Some(merged) = existing {
+            return Err(format!(
+                "Range '{}' overlaps merged cells '{}'",
+                range_helper::format_bounds(*area),
+                range_helper::format_bounds(merged)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn merge(sheet: &mut Worksheet, bounds: Bounds, center: bool, clear_non_anchor: bool) {
+    let (start_column, start_row, ..) = bounds;
+
+    if clear_non_anchor {
+        for cell in sheet.get_cell_collection_mut() {
+            let coordinate = cell.get_coordinate();
+            let (column, row) = (*coordinate.get_col_num(), *coordinate.get_row_num());
+            let inside = range_helper::intersect((column, row, column, row), bounds).is_some();
+            if inside && (column, row) != (start_column, start_row) {
+                cell.get_cell_value_mut().set_blank();
+            }
+        }
+    }
+
+    if center {
+        sheet
+            .get_style_mut((start_column, start_row))
+            .get_alignment_mut()
+            .set_horizontal(HorizontalAlignmentValues::Center);
+    }
+
+    let mut merged = Range::default();
+    merged.set_range(range_helper::format_bounds(bounds));
+    sheet.get_merge_cells_mut().push(merged);
+}
@@ -0,0 +1,171 @@
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::atoms;
+use crate::helpers::range_helper;
+use crate::helpers::style_map_helper::StylePatch;
+use crate::UmyaSpreadsheet;
+
+/// Define a custom named cell style
+///
+/// The style starts from the formatting of "Normal" and `style` is a style map as
+/// accepted by `set_range_style`.
+#[rustler::nif]
+pub fn add_named_style(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let patch = StylePatch::decode(style)?;
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let mut new_style = extras.named_styles.base_style();
+        patch.apply(&mut new_style);
+        extras.named_styles.add(&name, new_style)?;
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in add_named_style operation".to_string(),
+        )))),
+    }
+}
+
+/// List the named cell styles of the workbook
+#[rustler::nif]
+pub fn list_named_styles(env: Env, resource: ResourceArc<UmyaSpreadsheet>) -> Term {
+    let extras = resource.package_extras.lock().unwrap();
+
+    let styles: Vec<HashMap<String, Term>> = extras
+        .named_styles
+        .styles()
+        .iter()
+        .map(|named_style| {
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), named_style.name.encode(env));
+            map.insert("builtin_id".to_string(), named_style.builtin_id.encode(env));
+            map.insert(
+                "builtin".to_string(),
+                named_style.builtin_id.is_some().encode(env),
+            );
+            map
+        })
+        .collect();
+
+    (atoms::ok(), styles).encode(env)
+}
+
+/// Apply a named cell style to a range
+///
+/// The parts the named style defines replace those of each cell, and the cells
+/// are linked to the style so that later changes to it reach them.
+#[rustler::nif]
+pub fn apply_named_style(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    name: String,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let target = range_helper::parse_range(&range)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+
+        let named_style = extras
+            .named_styles
+            .find(&name)
+            .cloned()
+            .ok_or_else(|| format!("Named style '{}' not found", name))?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        range_helper::update_styles(sheet, target, |style| named_style.apply_to(style));
+        extras.named_styles.mark_changed();
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in apply_named_style operation".to_string(),
+        )))),
+    }
+}
+
+/// Change a named cell style and every cell, row and column that uses it
+///
+/// Only the parts given in the style map change, both in the named style and in
+/// the cells using it, so formatting applied on top of the style is kept.
+#[rustler::nif]
+pub fn update_named_style(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    name: String,
+    style: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let patch = StylePatch::decode(style)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+
+        let format_id = extras
+            .named_styles
+            .update(&name, |style| patch.apply(style))?;
+
+        for sheet in guard.get_sheet_collection_mut().iter_mut() {
+            for cell in sheet.get_cell_collection_mut() {
+                if *cell.get_style().get_format_id() == format_id {
+                    patch.apply(cell.get_style_mut());
+                }
+            }
+            for row in sheet.get_row_dimensions_mut() {
+                if *row.get_style().get_format_id() == format_id {
+                    patch.apply(row.get_style_mut());
+                }
+            }
+            for column in sheet.get_column_dimensions_mut().iter_mut() {
+                if *column.get_style().get_format_id() == format_id {
+                    patch.apply(column.get_style_mut());
+                }
+            }
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in update_named_style operation".to_string(),
+        )))),
+    }
+}
@@ -0,0 +1,238 @@
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::helper::coordinate::string_from_column_index;
+
+use crate::atoms;
+use crate::helpers::outline_helper;
+use crate::helpers::package_helper::{OutlineAxis, PackageExtras, SheetOutline};
+use crate::helpers::range_helper::{self, RangeTarget};
+use crate::UmyaSpreadsheet;
+
+/// Set the outline level of rows or columns
+///
+/// `range` is whole rows such as "2:5" or whole columns such as "B:D"; `level` is
+/// between 0 (not grouped) and 7.
+#[rustler::nif]
+pub fn set_outline_level(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    level: u8,
+) -> NifResult<Atom> {
+    update_outline(
+        &resource,
+        &sheet_name,
+        &range,
+        "set_outline_level",
+        |sheet, outline, _, axis, (start, end)| {
+            outline_helper::set_level(sheet, outline, axis, start, end, level)
+        },
+    )
+}
+
+/// Group rows or columns one level deeper, as Excel's Group does
+#[rustler::nif]
+pub fn group_outline(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+) -> NifResult<Atom> {
+    update_outline(
+        &resource,
+        &sheet_name,
+        &range,
+        "group_outline",
+        |sheet, outline, _, axis, (start, end)| {
+            outline_helper::change_level(sheet, outline, axis, start, end, 1)
+        },
+    )
+}
+
+/// Ungroup rows or columns by one level, as Excel's Ungroup does
+#[rustler::nif]
+pub fn ungroup_outline(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+) -> NifResult<Atom> {
+    update_outline(
+        &resource,
+        &sheet_name,
+        &range,
+        "ungroup_outline",
+        |sheet, outline, _, axis, (start, end)| {
+            outline_helper::change_level(sheet, outline, axis, start, end, -1)
+        },
+    )
+}
+
+/// Collapse or expand the group that rows or columns belong to
+///
+/// Collapsing hides the group and marks its summary row or column, which follows the
+/// group unless the sheet puts summaries above or left of their details.
+#[rustler::nif]
+pub fn set_outline_collapsed(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    collapsed: bool,
+) -> NifResult<Atom> {
+    update_outline(
+        &resource,
+        &sheet_name,
+        &range,
+        "set_outline_collapsed",
+        |sheet, outline, summary_after, axis, (start, end)| {
+            outline_helper::set_collapsed(
+                sheet,
+                outline,
+                axis,
+                start,
+                end,
+                summary_after,
+                collapsed,
+            )
+        },
+    )
+}
+
+/// Get the outline of a sheet
+///
+/// Returns a map with the row and column groups of every level, each a map with
+/// `start`, `end`, `level` and `collapsed`, and the summary placement.
+#[rustler::nif]
+pub fn get_outline(env: Env, resource: ResourceArc<UmyaSpreadsheet>, sheet_name: String) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Term, String> {
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        guard
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let outline = extras.sheet_outline(&sheet_name);
+        let properties = extras.sheet_properties(&sheet_name);
+
+        let encode_groups = |axis: OutlineAxis, summary_after: bool| -> Vec<Term> {
+            outline_helper::groups(&outline, axis, summary_after)
+                .into_iter()
+                .map(|group| {
+                    let bound = |index: u32| match axis {
+                        OutlineAxis::Rows => index.encode(env),
+                        OutlineAxis::Columns => string_from_column_index(&index).encode(env),
+                    };
+                    let mut map = HashMap::new();
+                    map.insert("start", bound(group.start));
+                    map.insert("end", bound(group.end));
+                    map.insert("level", group.level.encode(env));
+                    map.insert("collapsed", group.collapsed.encode(env));
+                    map.encode(env)
+                })
+                .collect()
+        };
+
+        let mut map = HashMap::new();
+        map.insert(
+            "rows",
+            encode_groups(OutlineAxis::Rows, properties.summary_below).encode(env),
+        );
+        map.insert(
+            "columns",
+            encode_groups(OutlineAxis::Columns, properties.summary_right).encode(env),
+        );
+        map.insert("summary_below", properties.summary_below.encode(env));
+        map.insert("summary_right", properties.summary_right.encode(env));
+        Ok(map.encode(env))
+    }));
+
+    match result {
+        Ok(Ok(map)) => (atoms::ok(), map).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_outline operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+/// Runs `update` on the outline of a sheet and stores the result.
+///
+/// `update` receives the worksheet, its outline, whether summaries follow their
+/// details on the range's axis, the axis and the range's bounds on it.
+fn update_outline<F>(
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    range: &str,
+    operation: &str,
+    update: F,
+) -> NifResult<Atom>
+where
+    F: FnOnce(
+        &mut umya_spreadsheet::Worksheet,
+        &mut SheetOutline,
+        bool,
+        OutlineAxis,
+        (u32, u32),
+    ) -> Result<(), String>,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let (axis, bounds) = outline_range(range)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let mut outline = extras.sheet_outline(sheet_name);
+        let summary_after = summary_after(&extras, sheet_name, axis);
+        update(sheet, &mut outline, summary_after, axis, bounds)?;
+        extras.set_sheet_outline(sheet_name, outline);
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            format!("Error occurred in {} operation", operation),
+        )))),
+    }
+}
+
+fn summary_after(extras: &PackageExtras, sheet_name: &str, axis: OutlineAxis) -> bool {
+    let properties = extras.sheet_properties(sheet_name);
+    match axis {
+        OutlineAxis::Rows => properties.summary_below,
+        OutlineAxis::Columns => properties.summary_right,
+    }
+}
+
+/// Parses whole rows ("2:5") or whole columns ("B:D") into an axis and bounds.
+fn outline_range(range: &str) -> Result<(OutlineAxis, (u32, u32)), String> {
+    match range_helper::parse_range(range)? {
+        RangeTarget::Rows { start_row, end_row } => Ok((OutlineAxis::Rows, (start_row, end_row))),
+        RangeTarget::Columns {
+            start_column,
+            end_column,
+        } => Ok((OutlineAxis::Columns, (start_column, end_column))),
+        RangeTarget::Cells { .. } => Err(format!(
+            "Outline range '{}' must be whole rows such as \"2:5\" or whole columns such as \"B:D\"",
+            range
+        )),
+    }
+}
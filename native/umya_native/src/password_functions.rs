@@ -1,8 +1,8 @@
 use rustler::{Atom, Error as NifError, NifResult};
 use std::path::Path;
-use umya_spreadsheet;
 
 use crate::atoms;
+use crate::helpers::package_helper;
 
 /// Set password protection on an Excel file
 #[rustler::nif]
@@ -17,9 +17,14 @@ pub fn set_password(input_path: String, output_path: String, password: String) -
         ))));
     }
 
-    match umya_spreadsheet::reader::xlsx::read(input) {
-        Ok(book) => {
-            match umya_spreadsheet::writer::xlsx::write_with_password(&book, output, &password) {
+    match package_helper::read_spreadsheet_from_path(input, false) {
+        Ok(mut book) => {
+            // Keep what umya-spreadsheet does not model, such as x14 rules and named styles
+            let mut extras = package_helper::read_extras_from_path(input);
+            extras.restore_into(&mut book);
+            match package_helper::write_to_path_with_password(
+                &book, &extras, output, &password, false,
+            ) {
                 Ok(_) => Ok(atoms::ok()),
                 Err(_) => Err(NifError::Term(Box::new((
                     atoms::error(),
@@ -0,0 +1,476 @@
+use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{
+    Cell, Comment, ConditionalFormatting, DataValidation, DataValidations, Style, Worksheet,
+};
+
+use crate::atoms;
+use crate::helpers::conditional_extension_helper::RuleExtension;
+use crate::helpers::conditional_format_helper;
+use crate::helpers::defined_name_helper::{MAX_COLUMN, MAX_ROW};
+use crate::helpers::formula_helper;
+use crate::helpers::range_helper::{self, Bounds, RangeTarget};
+use crate::UmyaSpreadsheet;
+
+/// What a paste takes from the copied range, like Excel's Paste Special.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PasteMode {
+    /// Values, formulas, styles, merges, comments, hyperlinks, validations and conditional formats
+    All,
+    /// Values only; formulas are replaced by their last calculated result
+    Values,
+    /// Values and formulas, with relative references moved
+    Formulas,
+    /// Styles, merges and conditional formats
+    Formats,
+}
+
+impl PasteMode {
+    fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "all" => Ok(PasteMode::All),
+            "values" => Ok(PasteMode::Values),
+            "formulas" => Ok(PasteMode::Formulas),
+            "formats" => Ok(PasteMode::Formats),
+            _ => Err(format!(
+                "Invalid paste mode '{}', expected all, values, formulas or formats",
+                mode
+            )),
+        }
+    }
+
+    fn pastes_formats(self) -> bool {
+        matches!(self, PasteMode::All | PasteMode::Formats)
+    }
+}
+
+/// The copied range, already moved to its target position.
+///
+/// It is taken while only the source workbook is locked, so a range can be pasted
+/// into the same workbook or sheet it was copied from.
+struct Clipboard {
+    target: Bounds,
+    /// One entry per target cell; `None` where the source cell does not exist
+    cells: Vec<((u32, u32), Option<Cell>)>,
+    merges: Vec<Bounds>,
+    comments: Vec<Comment>,
+    data_validations: Vec<DataValidation>,
+    conditional_formats: Vec<ConditionalFormatting>,
+    /// Data bar and icon set options of the source sheet's rules, by priority
+    rule_extensions: BTreeMap<i32, RuleExtension>,
+}
+
+/// Copy a range within a sheet, to another sheet or to another workbook
+///
+/// `mode` selects what is pasted: "all", "values", "formulas" or "formats".
+/// Relative references in formulas, validations and conditional formats move with
+/// the range, as in Excel.
+#[rustler::nif]
+pub fn copy_range(
+    source: ResourceArc<UmyaSpreadsheet>,
+    source_sheet_name: String,
+    source_range: String,
+    target: ResourceArc<UmyaSpreadsheet>,
+    target_sheet_name: String,
+    target_cell: String,
+    mode: String,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mode = PasteMode::parse(&mode)?;
+        let source_target = range_helper::parse_range(&source_range)?;
+        let (target_column, target_row) = match range_helper::parse_range(&target_cell) {
+            Ok(RangeTarget::Cells {
+                start_column,
+                start_row,
+                ..
+            }) => (start_column, start_row),
+            _ => return Err(format!("Invalid cell address '{}'", target_cell)),
+        };
+
+        let mut clipboard = {
+            let guard = source
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let sheet = guard
+                .get_sheet_by_name(&source_sheet_name)
+                .ok_or_else(|| "Source sheet not found".to_string())?;
+
+            let mut bounds = source_target.bounds();
+            if !matches!(source_target, RangeTarget::Cells { .. }) {
+                let (max_column, max_row) = sheet.get_highest_column_and_row();
+                bounds.2 = bounds.2.min(max_column.max(1));
+                bounds.3 = bounds.3.min(max_row.max(1));
+            }
+            let column_offset = target_column as i64 - bounds.0 as i64;
+            let row_offset = target_row as i64 - bounds.1 as i64;
+            if bounds.2 as i64 + column_offset > MAX_COLUMN as i64
+                || bounds.3 as i64 + row_offset > MAX_ROW as i64
+            {
+                return Err("Target range does not fit on the sheet".to_string());
+            }
+
+            let mut clipboard = copy(sheet, bounds, column_offset, row_offset, mode);
+            if !clipboard.conditional_formats.is_empty() {
+                let extras = source
+                    .package_extras
+                    .lock()
+                    .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+                clipboard.rule_extensions = extras.conditional_extensions(&source_sheet_name);
+            }
+            clipboard
+        };
+
+        let mut guard = target
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&target_sheet_name)
+            .ok_or_else(|| "Target sheet not found".to_string())?;
+        let rule_extensions = std::mem::take(&mut clipboard.rule_extensions);
+        let pasted_rules = paste(sheet, clipboard, mode);
+
+        if mode.pastes_formats() {
+            let mut extras = target
+                .package_extras
+                .lock()
+                .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+            extras.retain_conditional_extensions(&target_sheet_name, sheet);
+            for (source_priority, priority) in pasted_rules {
+                let extension = rule_extensions.get(&source_priority).cloned();
+                extras.set_conditional_extension(&target_sheet_name, priority, extension);
+            }
+        }
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in copy_range operation".to_string(),
+        )))),
+    }
+}
+
+fn copy(
+    sheet: &Worksheet,
+    bounds: Bounds,
+    column_offset: i64,
+    row_offset: i64,
+    mode: PasteMode,
+) -> Clipboard {
+    let moved = |(start_column, start_row, end_column, end_row): Bounds| -> Bounds {
+        (
+            (start_column as i64 + column_offset) as u32,
+            (start_row as i64 + row_offset) as u32,
+            (end_column as i64 + column_offset) as u32,
+            (end_row as i64 + row_offset) as u32,
+        )
+    };
+    let target = moved(bounds);
+
+    let mut cells = Vec::new();
+    for row in bounds.1..=bounds.3 {
+        for column in bounds.0..=bounds.2 {
+            let position = (
+                (column as i64 + column_offset) as u32,
+                (row as i64 + row_offset) as u32,
+            );
+            let cell = sheet.get_cell((column, row)).map(|cell| {
+                let mut cell = cell.clone();
+                cell.set_coordinate(position);
+                if cell.get_cell_value().is_formula() {
+                    if mode == PasteMode::Values {
+                        cell.get_cell_value_mut().remove_formula();
+                    } else {
+                        let formula = formula_helper::shift_formula(
+                            cell.get_formula(),
+                            column_offset,
+                            row_offset,
+                        );
+                        // The cached result belongs to the source references
+                        cell.get_cell_value_mut().set_blank().set_formula(formula);
+                    }
+                }
+                cell
+            });
+            cells.push((position, cell));
+        }
+    }
+
+    let mut clipboard = Clipboard {
+        target,
+        cells,
+        merges: Vec::new(),
+        comments: Vec::new(),
+        data_validations: Vec::new(),
+        conditional_formats: Vec::new(),
+        rule_extensions: BTreeMap::new(),
+    };
+
+    if mode.pastes_formats() {
+        clipboard.merges = sheet
+            .get_merge_cells()
+            .iter()
+            .filter_map(|range| bounds_of(&range.get_range()))
+            .filter(|merge| range_helper::intersect(*merge, bounds) == Some(*merge))
+            .map(moved)
+            .collect();
+
+        for conditional_format in sheet.get_conditional_formatting_collection() {
+            let sqref = conditional_format.get_sequence_of_references().get_sqref();
+            let Some((new_sqref, formula_offset)) =
+                clip_sqref(&sqref, bounds, column_offset, row_offset)
+            else {
+                continue;
+            };
+            let mut conditional_format = conditional_format.clone();
+            range_helper::replace_sqref(
+                conditional_format.get_sequence_of_references_mut(),
+                &new_sqref,
+            );
+            shift_rule_formulas(&mut conditional_format, formula_offset);
+            clipboard.conditional_formats.push(conditional_format);
+        }
+    }
+
+    if mode == PasteMode::All {
+        for comment in sheet.get_comments() {
+            let coordinate = comment.get_coordinate();
+            let (column, row) = (*coordinate.get_col_num(), *coordinate.get_row_num());
+            if range_helper::intersect((column, row, column, row), bounds).is_none() {
+                continue;
+            }
+            let mut comment = comment.clone();
+            comment
+                .get_coordinate_mut()
+                .set_col_num((column as i64 + column_offset) as u32)
+                .set_row_num((row as i64 + row_offset) as u32);
+            let anchor = comment.get_anchor_mut();
+            anchor.set_left_column(shift_anchor(*anchor.get_left_column(), column_offset));
+            anchor.set_right_column(shift_anchor(*anchor.get_right_column(), column_offset));
+            anchor.set_top_row(shift_anchor(*anchor.get_top_row(), row_offset));
+            anchor.set_bottom_row(shift_anchor(*anchor.get_bottom_row(), row_offset));
+            clipboard.comments.push(comment);
+        }
+
+        let validations = sheet
+            .get_data_validations()
+            .map(|validations| validations.get_data_validation_list())
+            .unwrap_or_default();
+        for validation in validations {
+            let sqref = validation.get_sequence_of_references().get_sqref();
+            let Some((new_sqref, formula_offset)) =
+                clip_sqref(&sqref, bounds, column_offset, row_offset)
+            else {
+                continue;
+            };
+            let mut validation = validation.clone();
+            range_helper::replace_sqref(validation.get_sequence_of_references_mut(), &new_sqref);
+            shift_validation_formulas(&mut validation, formula_offset);
+            clipboard.data_validations.push(validation);
+        }
+    }
+
+    clipboard
+}
+
+/// Pastes a clipboard into a sheet and returns the source and new priority of every
+/// pasted conditional formatting rule.
+fn paste(sheet: &mut Worksheet, clipboard: Clipboard, mode: PasteMode) -> Vec<(i32, i32)> {
+    let target = clipboard.target;
+    let mut pasted_rules = Vec::new();
+
+    for (position, cell) in clipboard.cells {
+        match (mode, cell) {
+            (PasteMode::All, Some(cell)) => {
+                sheet.set_cell(cell);
+            }
+            (PasteMode::All, None) => {
+                sheet.remove_cell(position);
+            }
+            (PasteMode::Formats, cell) => {
+                let style = cell
+                    .map(|cell| cell.get_style().clone())
+                    .unwrap_or_else(Style::default);
+                sheet.get_cell_mut(position).set_style(style);
+            }
+            (_, Some(cell)) => {
+                sheet
+                    .get_cell_mut(position)
+                    .set_cell_value(cell.get_cell_value().clone());
+            }
+            (_, None) => {
+                if sheet.get_cell(position).is_some() {
+                    sheet
+                        .get_cell_mut(position)
+                        .get_cell_value_mut()
+                        .set_blank();
+                }
+            }
+        }
+    }
+
+    if mode.pastes_formats() {
+        sheet.get_merge_cells_mut().retain(|range| {
+            bounds_of(&range.get_range())
+                .is_none_or(|merge| range_helper::intersect(merge, target).is_none())
+        });
+        for merge in clipboard.merges {
+            sheet.add_merge_cells(range_helper::format_bounds(merge));
+        }
+
+        let mut conditional_formats: Vec<ConditionalFormatting> = sheet
+            .get_conditional_formatting_collection()
+            .iter()
+            .filter_map(|conditional_format| {
+                let sqref = conditional_format.get_sequence_of_references().get_sqref();
+                let (rest, formula_offset) = remove_area(&sqref, target)?;
+                let mut conditional_format = conditional_format.clone();
+                range_helper::replace_sqref(
+                    conditional_format.get_sequence_of_references_mut(),
+                    &rest,
+                );
+                shift_rule_formulas(&mut conditional_format, formula_offset);
+                Some(conditional_format)
+            })
+            .collect();
+        // Pasted rules come after the rules of the sheet, as new rules do
+        let mut priority = conditional_format_helper::next_priority(sheet);
+        for mut conditional_format in clipboard.conditional_formats {
+            for rule in conditional_format.get_conditional_collection_mut() {
+                pasted_rules.push((*rule.get_priority(), priority));
+                rule.set_priority(priority);
+                priority += 1;
+            }
+            conditional_formats.push(conditional_format);
+        }
+        sheet.set_conditional_formatting_collection(conditional_formats);
+    }
+
+    if mode == PasteMode::All {
+        sheet.get_comments_mut().retain(|comment| {
+            let coordinate = comment.get_coordinate();
+            let (column, row) = (*coordinate.get_col_num(), *coordinate.get_row_num());
+            range_helper::intersect((column, row, column, row), target).is_none()
+        });
+        for comment in clipboard.comments {
+            sheet.add_comments(comment);
+        }
+
+        let mut validations: Vec<DataValidation> = sheet
+            .get_data_validations()
+            .map(|validations| validations.get_data_validation_list().to_vec())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|mut validation| {
+                let sqref = validation.get_sequence_of_references().get_sqref();
+                let (rest, formula_offset) = remove_area(&sqref, target)?;
+                range_helper::replace_sqref(validation.get_sequence_of_references_mut(), &rest);
+                shift_validation_formulas(&mut validation, formula_offset);
+                Some(validation)
+            })
+            .collect();
+        validations.extend(clipboard.data_validations);
+        if !validations.is_empty() || sheet.get_data_validations().is_some() {
+            let mut data_validations = DataValidations::default();
+            data_validations.set_data_validation_list(validations);
+            sheet.set_data_validations(data_validations);
+        }
+    }
+
+    pasted_rules
+}
+
+/// Returns the part of an sqref inside `bounds`, moved by the offsets, with the
+/// offset its relative formulas must move by.
+///
+/// Formulas of validations and conditional formats are relative to the first cell
+/// of the sqref, which changes when the sqref is clipped.
+fn clip_sqref(
+    sqref: &str,
+    bounds: Bounds,
+    column_offset: i64,
+    row_offset: i64,
+) -> Option<(String, (i64, i64))> {
+    let areas: Vec<Bounds> = sqref.split_whitespace().filter_map(bounds_of).collect();
+    let anchor = areas.first()?;
+    let clipped: Vec<Bounds> = areas
+        .iter()
+        .filter_map(|area| range_helper::intersect(*area, bounds))
+        .collect();
+    let first = clipped.first()?;
+
+    let formula_offset = (
+        first.0 as i64 + column_offset - anchor.0 as i64,
+        first.1 as i64 + row_offset - anchor.1 as i64,
+    );
+    let moved: Vec<String> = clipped
+        .iter()
+        .map(|area| {
+            range_helper::format_bounds((
+                (area.0 as i64 + column_offset) as u32,
+                (area.1 as i64 + row_offset) as u32,
+                (area.2 as i64 + column_offset) as u32,
+                (area.3 as i64 + row_offset) as u32,
+            ))
+        })
+        .collect();
+    Some((moved.join(" "), formula_offset))
+}
+
+/// Removes `area` from an sqref, returning `None` when nothing is left.
+///
+/// Also returns the offset relative formulas must move by when the first cell of
+/// the sqref is pasted over.
+fn remove_area(sqref: &str, area: Bounds) -> Option<(String, (i64, i64))> {
+    let areas: Vec<Bounds> = sqref.split_whitespace().filter_map(bounds_of).collect();
+    let anchor = areas.first()?;
+    let rest: Vec<Bounds> = areas
+        .iter()
+        .flat_map(|bounds| range_helper::subtract(*bounds, area))
+        .collect();
+    let first = rest.first()?;
+
+    let formula_offset = (
+        first.0 as i64 - anchor.0 as i64,
+        first.1 as i64 - anchor.1 as i64,
+    );
+    let rest: Vec<String> = rest.into_iter().map(range_helper::format_bounds).collect();
+    Some((rest.join(" "), formula_offset))
+}
+
+fn shift_rule_formulas(conditional_format: &mut ConditionalFormatting, offset: (i64, i64)) {
+    for rule in conditional_format.get_conditional_collection_mut() {
+        if let Some(formula) = rule.get_formula() {
+            let shifted =
+                formula_helper::shift_formula(&formula.get_address_str(), offset.0, offset.1);
+            let mut formula = formula.clone();
+            formula.set_string_value(shifted);
+            rule.set_formula(formula);
+        }
+    }
+}
+
+fn shift_validation_formulas(validation: &mut DataValidation, offset: (i64, i64)) {
+    let formula1 = formula_helper::shift_formula(validation.get_formula1(), offset.0, offset.1);
+    let formula2 = formula_helper::shift_formula(validation.get_formula2(), offset.0, offset.1);
+    validation.set_formula1(formula1).set_formula2(formula2);
+}
+
+fn bounds_of(range: &str) -> Option<Bounds> {
+    match range_helper::parse_range(range).ok()? {
+        target @ RangeTarget::Cells { .. } => Some(target.bounds()),
+        _ => None,
+    }
+}
+
+/// Moves a zero-based comment anchor column or row, stopping at the sheet edge.
+fn shift_anchor(value: u32, offset: i64) -> u32 {
+    (value as i64 + offset).max(0) as u32
+}
@@ -1,6 +1,16 @@
-use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::helper::coordinate::column_index_from_string;
+use umya_spreadsheet::Worksheet;
 
 use crate::atoms;
+use crate::helpers::autofit_helper;
+use crate::helpers::defined_name_helper::MAX_ROW;
+use crate::helpers::dimension_helper;
+use crate::helpers::package_helper::OutlineAxis;
+use crate::helpers::range_helper::{self, RangeTarget};
+use crate::helpers::row_column_helper;
 use crate::UmyaSpreadsheet;
 
 /// Set the height of a row
@@ -91,6 +101,9 @@ pub fn set_column_width(
 }
 
 /// Set auto width for a column
+///
+/// Excel does not resize `bestFit` columns when it opens a file, so enabling auto
+/// width also sets the width the column's cells need.
 #[rustler::nif]
 pub fn set_column_auto_width(
     resource: ResourceArc<UmyaSpreadsheet>,
@@ -102,9 +115,20 @@ pub fn set_column_auto_width(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
+            let column_index = column_index_from_string(&column);
+            let fitted = if is_auto_width {
+                autofit_helper::fit_columns(sheet, (column_index, 1, column_index, MAX_ROW))
+                    .remove(&column_index)
+            } else {
+                None
+            };
+
             // get_column_dimension_mut returns &mut Column directly, not Option
             let column_dimension = sheet.get_column_dimension_mut(&column);
             column_dimension.set_best_fit(is_auto_width);
+            if let Some(width) = fitted {
+                column_dimension.set_width(width);
+            }
             Ok(atoms::ok())
         }
         None => Err(NifError::Term(Box::new("Sheet not found".to_string()))),
@@ -121,15 +145,10 @@ pub fn get_column_width(
     let mut guard = resource.spreadsheet.lock().unwrap();
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            match sheet.get_column_dimension(&column) {
-                Some(column_dim) => {
-                    let width = column_dim.get_width();
-                    Ok(*width)
-                }
-                None => Ok(8.43), // Default column width
-            }
-        }
+        Some(sheet) => Ok(dimension_helper::column_width(
+            sheet,
+            column_index_from_string(&column),
+        )),
         None => Err(NifError::Term(Box::new("Sheet not found".to_string()))),
     }
 }
@@ -191,15 +210,8 @@ pub fn get_row_height(
     let mut guard = resource.spreadsheet.lock().unwrap();
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            match sheet.get_row_dimension(&row_number) {
-                Some(row_dim) => {
-                    let height = row_dim.get_height();
-                    Ok(*height)
-                }
-                None => Ok(15.0), // Default row height
-            }
-        }
+        // Rows without a height of their own have the sheet's default height
+        Some(sheet) => Ok(dimension_helper::row_height(sheet, row_number)),
         None => Err(NifError::Term(Box::new("Sheet not found".to_string()))),
     }
 }
@@ -239,7 +251,14 @@ pub fn remove_row(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
-            sheet.remove_row(&row_index, &amount);
+            row_column_helper::remove(sheet, OutlineAxis::Rows, row_index, amount);
+            let mut extras = resource.package_extras.lock().unwrap();
+            extras.shift_sheet_outline(
+                &sheet_name,
+                OutlineAxis::Rows,
+                row_index,
+                -i64::from(amount),
+            );
             Ok(atoms::ok())
         }
         None => Err(NifError::Term(Box::new((
@@ -261,7 +280,15 @@ pub fn remove_column(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
-            sheet.remove_column(column_letter.as_str(), &amount);
+            let column_index = column_index_from_string(&column_letter);
+            row_column_helper::remove(sheet, OutlineAxis::Columns, column_index, amount);
+            let mut extras = resource.package_extras.lock().unwrap();
+            extras.shift_sheet_outline(
+                &sheet_name,
+                OutlineAxis::Columns,
+                column_index,
+                -i64::from(amount),
+            );
             Ok(atoms::ok())
         }
         None => Err(NifError::Term(Box::new((
@@ -283,7 +310,14 @@ pub fn remove_column_by_index(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
-            sheet.remove_column_by_index(&column_index, &amount);
+            row_column_helper::remove(sheet, OutlineAxis::Columns, column_index, amount);
+            let mut extras = resource.package_extras.lock().unwrap();
+            extras.shift_sheet_outline(
+                &sheet_name,
+                OutlineAxis::Columns,
+                column_index,
+                -i64::from(amount),
+            );
             Ok(atoms::ok())
         }
         None => Err(NifError::Term(Box::new((
@@ -292,3 +326,270 @@ pub fn remove_column_by_index(
         )))),
     }
 }
+
+/// Hide or unhide rows `start_row..=end_row`
+#[rustler::nif]
+pub fn set_rows_hidden(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    start_row: u32,
+    end_row: u32,
+    hidden: bool,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "set_rows_hidden", |sheet| {
+        for row in row_span(start_row, end_row)? {
+            sheet.get_row_dimension_mut(&row).set_hidden(hidden);
+        }
+        Ok(())
+    })
+}
+
+/// Hide or unhide columns `start_column..=end_column`, e.g. "B" to "D"
+#[rustler::nif]
+pub fn set_columns_hidden(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    start_column: String,
+    end_column: String,
+    hidden: bool,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "set_columns_hidden", |sheet| {
+        for column in column_span(&start_column, &end_column)? {
+            dimension_helper::column_dimension_mut(sheet, column).set_hidden(hidden);
+        }
+        Ok(())
+    })
+}
+
+/// Set the height of rows `start_row..=end_row`, marking it custom
+#[rustler::nif]
+pub fn set_rows_height(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    start_row: u32,
+    end_row: u32,
+    height: f64,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "set_rows_height", |sheet| {
+        dimension_helper::check_row_height(height)?;
+        for row in row_span(start_row, end_row)? {
+            sheet.get_row_dimension_mut(&row).set_height(height);
+        }
+        Ok(())
+    })
+}
+
+/// Set the width of columns `start_column..=end_column`
+#[rustler::nif]
+pub fn set_columns_width(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    start_column: String,
+    end_column: String,
+    width: f64,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "set_columns_width", |sheet| {
+        dimension_helper::check_column_width(width)?;
+        for column in column_span(&start_column, &end_column)? {
+            let dimension = sheet.get_column_dimension_by_number_mut(&column);
+            dimension.set_width(width);
+            dimension.set_best_fit(false);
+        }
+        Ok(())
+    })
+}
+
+/// Clear the custom height of rows `start_row..=end_row`
+///
+/// Excel sizes rows without a custom height to their contents when it opens the file.
+#[rustler::nif]
+pub fn reset_rows_height(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    start_row: u32,
+    end_row: u32,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "reset_rows_height", |sheet| {
+        for row in row_span(start_row, end_row)? {
+            if sheet.get_row_dimension(&row).is_some() {
+                let dimension = sheet.get_row_dimension_mut(&row);
+                dimension.set_height(0.0);
+                dimension.set_custom_height(false);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Get whether a row has a custom height
+#[rustler::nif]
+pub fn get_row_custom_height(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    row_number: u32,
+) -> Term {
+    read_sheet(
+        env,
+        &resource,
+        &sheet_name,
+        "get_row_custom_height",
+        |sheet| {
+            Ok(sheet
+                .get_row_dimension(&row_number)
+                .is_some_and(|dimension| *dimension.get_custom_height())
+                .encode(env))
+        },
+    )
+}
+
+/// Set the height of rows without a height of their own
+#[rustler::nif]
+pub fn set_default_row_height(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    height: f64,
+) -> NifResult<Atom> {
+    update_sheet(&resource, &sheet_name, "set_default_row_height", |sheet| {
+        dimension_helper::check_row_height(height)?;
+        let format = sheet.get_sheet_format_properties_mut();
+        format.set_default_row_height(height);
+        // Excel ignores the default height unless it is marked custom
+        format.set_custom_height(true);
+        Ok(())
+    })
+}
+
+/// Get the height of rows without a height of their own
+#[rustler::nif]
+pub fn get_default_row_height(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+) -> Term {
+    read_sheet(
+        env,
+        &resource,
+        &sheet_name,
+        "get_default_row_height",
+        |sheet| Ok(dimension_helper::default_row_height(sheet).encode(env)),
+    )
+}
+
+/// Set the width of columns without a width of their own
+#[rustler::nif]
+pub fn set_default_column_width(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    width: f64,
+) -> NifResult<Atom> {
+    update_sheet(
+        &resource,
+        &sheet_name,
+        "set_default_column_width",
+        |sheet| {
+            dimension_helper::check_column_width(width)?;
+            sheet
+                .get_sheet_format_properties_mut()
+                .set_default_column_width(width);
+            Ok(())
+        },
+    )
+}
+
+/// Get the width of columns without a width of their own
+#[rustler::nif]
+pub fn get_default_column_width(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+) -> Term {
+    read_sheet(
+        env,
+        &resource,
+        &sheet_name,
+        "get_default_column_width",
+        |sheet| Ok(dimension_helper::default_column_width(sheet).encode(env)),
+    )
+}
+
+fn update_sheet<F>(
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    operation: &str,
+    update: F,
+) -> NifResult<Atom>
+where
+    F: FnOnce(&mut Worksheet) -> Result<(), String>,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        update(sheet)
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            format!("Error occurred in {} operation", operation),
+        )))),
+    }
+}
+
+fn read_sheet<'a, F>(
+    env: Env<'a>,
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    operation: &str,
+    read: F,
+) -> Term<'a>
+where
+    F: FnOnce(&Worksheet) -> Result<Term<'a>, String>,
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Term<'a>, String> {
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name(sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        read(sheet)
+    }));
+
+    match result {
+        Ok(Ok(value)) => (atoms::ok(), value).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            format!("Error occurred in {} operation", operation),
+        )
+            .encode(env),
+    }
+}
+
+/// Parses a span of rows, in either order.
+fn row_span(start_row: u32, end_row: u32) -> Result<RangeInclusive<u32>, String> {
+    match range_helper::parse_range(&format!("{}:{}", start_row, end_row))? {
+        RangeTarget::Rows { start_row, end_row } => Ok(start_row..=end_row),
+        _ => Err(format!("Invalid range '{}:{}'", start_row, end_row)),
+    }
+}
+
+/// Parses a span of columns given by letters, in either order.
+fn column_span(start_column: &str, end_column: &str) -> Result<RangeInclusive<u32>, String> {
+    match range_helper::parse_range(&format!("{}:{}", start_column, end_column))? {
+        RangeTarget::Columns {
+            start_column,
+            end_column,
+        } => Ok(start_column..=end_column),
+        _ => Err(format!("Invalid range '{}:{}'", start_column, end_column)),
+    }
+}
@@ -0,0 +1,333 @@
+use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{DefinedName, Spreadsheet, Style, Worksheet};
+
+use crate::atoms;
+use crate::helpers::conditional_extension_helper::RuleExtension;
+use crate::helpers::defined_name_helper;
+use crate::helpers::formula_helper;
+use crate::helpers::named_style_helper::{NamedStyle, NamedStyles};
+use crate::helpers::package_helper::{PackageExtras, SheetOutline, SheetProperties};
+use crate::helpers::sheet_reference_helper;
+use crate::UmyaSpreadsheet;
+
+/// A worksheet taken out of its workbook, with the workbook data it depends on.
+///
+/// It is taken while only the source workbook is locked, so a sheet can be copied
+/// into the workbook it comes from.
+struct SheetCopy {
+    sheet: Worksheet,
+    /// Names scoped to the sheet, with their comments
+    defined_names: Vec<(DefinedName, Option<String>)>,
+    /// Named styles the sheet's cells, rows and columns link to, by `xfId`
+    named_styles: HashMap<u32, NamedStyle>,
+    implicit_protection_flags: Option<HashSet<String>>,
+    /// Filter mode and outline settings, without the code name
+    properties: SheetProperties,
+    /// Outline levels and collapsed flags of rows and columns
+    outline: SheetOutline,
+    /// Data bar and icon set options, by rule priority
+    conditional_extensions: BTreeMap<i32, RuleExtension>,
+}
+
+/// Copy a worksheet into another workbook, or into the same one
+///
+/// Cell formatting, images, charts, comments, conditional formats and data
+/// validations travel with the sheet. Tables are renamed when their name is taken
+/// in the target workbook, names scoped to the sheet are scoped to the copy, and
+/// named styles the sheet uses are added to the target workbook when missing.
+#[rustler::nif]
+pub fn copy_sheet_to_workbook(
+    source: ResourceArc<UmyaSpreadsheet>,
+    source_sheet_name: String,
+    target: ResourceArc<UmyaSpreadsheet>,
+    new_sheet_name: String,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        if new_sheet_name.trim().is_empty() {
+            return Err("Sheet name cannot be empty".to_string());
+        }
+
+        let copy = {
+            let guard = source
+                .spreadsheet
+                .lock()
+                .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+            let extras = source
+                .package_extras
+                .lock()
+                .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+            take_sheet(&guard, &extras, &source_sheet_name)?
+        };
+
+        let mut guard = target
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        if guard.get_sheet_by_name(&new_sheet_name).is_some() {
+            return Err("Target sheet already exists".to_string());
+        }
+        let mut extras = target
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+
+        let SheetCopy {
+            mut sheet,
+            defined_names,
+            named_styles,
+            implicit_protection_flags,
+            properties,
+            outline,
+            conditional_extensions,
+        } = copy;
+        let local_sheet_id = guard.get_sheet_collection_no_check().len() as u32;
+
+        let table_renames = rename_tables(&guard, &mut sheet);
+        let rewrite = |formula: &str| -> String {
+            let mut formula = if source_sheet_name == new_sheet_name {
+                formula.to_string()
+            } else {
+                formula_helper::rename_sheet_references(
+                    formula,
+                    &source_sheet_name,
+                    &new_sheet_name,
+                )
+            };
+            for (old_name, new_name) in &table_renames {
+                formula = formula_helper::rename_table_references(&formula, old_name, new_name);
+            }
+            formula
+        };
+        if source_sheet_name != new_sheet_name || !table_renames.is_empty() {
+            sheet_reference_helper::rewrite_formulas(&mut sheet, &rewrite);
+            sheet_reference_helper::rename_sheet_sources(
+                &mut sheet,
+                &source_sheet_name,
+                &new_sheet_name,
+            );
+        }
+
+        let format_ids = link_named_styles(&mut extras.named_styles, named_styles);
+        if !format_ids.is_empty() {
+            relink_styles(&mut sheet, &format_ids);
+            extras.named_styles.mark_changed();
+        }
+
+        sheet.set_name(&new_sheet_name);
+        guard
+            .add_sheet(sheet)
+            .map_err(|_| "Target sheet already exists".to_string())?;
+
+        for (defined_name, comment) in defined_names {
+            let mut copied = defined_name_helper::new_defined_name(
+                defined_name.get_name(),
+                &rewrite(&defined_name.get_address()),
+            );
+            copied.set_local_sheet_id(local_sheet_id);
+            copied.set_hidden(*defined_name.get_hidden());
+            extras.set_defined_name_comment(defined_name.get_name(), Some(local_sheet_id), comment);
+            defined_name_helper::insert(&mut guard, copied);
+        }
+
+        match implicit_protection_flags {
+            Some(flags) => {
+                extras
+                    .implicit_sheet_protection_flags
+                    .insert(new_sheet_name.clone(), flags);
+            }
+            None => extras.clear_implicit_sheet_protection_flags(&new_sheet_name),
+        }
+        extras.set_sheet_properties(&new_sheet_name, properties);
+        extras.set_sheet_outline(&new_sheet_name, outline);
+        extras.set_conditional_extensions(&new_sheet_name, conditional_extensions);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in copy_sheet_to_workbook operation".to_string(),
+        )))),
+    }
+}
+
+fn take_sheet(
+    spreadsheet: &Spreadsheet,
+    extras: &PackageExtras,
+    sheet_name: &str,
+) -> Result<SheetCopy, String> {
+    let sheet_index = spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| sheet.get_name() == sheet_name)
+        .ok_or_else(|| "Source sheet not found".to_string())?;
+    let mut sheet = spreadsheet.get_sheet_collection_no_check()[sheet_index].clone();
+
+    // The sheet's own list also holds workbook-scoped names that point at it,
+    // which stay behind with the workbook
+    sheet.get_defined_names_mut().clear();
+    let defined_names = defined_name_helper::all_defined_names(spreadsheet)
+        .into_iter()
+        .filter(|(_, defined_name)| {
+            defined_name_helper::scope_of(defined_name) == Some(sheet_index as u32)
+        })
+        .map(|(_, defined_name)| {
+            let comment = extras
+                .defined_name_comment(defined_name.get_name(), Some(sheet_index as u32))
+                .cloned();
+            (defined_name.clone(), comment)
+        })
+        .collect();
+
+    let named_styles = used_format_ids(&sheet)
+        .into_iter()
+        .filter_map(|format_id| {
+            extras
+                .named_styles
+                .styles()
+                .iter()
+                .find(|named_style| named_style.format_id == format_id)
+                .map(|named_style| (format_id, named_style.clone()))
+        })
+        .collect();
+
+    Ok(SheetCopy {
+        sheet,
+        defined_names,
+        named_styles,
+        implicit_protection_flags: extras
+            .implicit_sheet_protection_flags
+            .get(sheet_name)
+            .cloned(),
+        // Code names must be unique within a workbook
+        properties: SheetProperties {
+            code_name: None,
+            ..extras.sheet_properties(sheet_name)
+        },
+        outline: extras.sheet_outline(sheet_name),
+        conditional_extensions: extras.conditional_extensions(sheet_name),
+    })
+}
+
+/// Returns the `xfId`s other than "Normal" that cells, rows and columns link to.
+fn used_format_ids(sheet: &Worksheet) -> HashSet<u32> {
+    let cells = sheet
+        .get_cell_collection()
+        .into_iter()
+        .map(|cell| cell.get_style());
+    let rows = sheet
+        .get_row_dimensions()
+        .into_iter()
+        .map(|row| row.get_style());
+    let columns = sheet
+        .get_column_dimensions()
+        .iter()
+        .map(|column| column.get_style());
+
+    cells
+        .chain(rows)
+        .chain(columns)
+        .map(|style| *style.get_format_id())
+        .filter(|format_id| *format_id != 0)
+        .collect()
+}
+
+/// Finds or adds the named styles of the copied sheet in the target workbook.
+///
+/// Returns the source `xfId`s that link to a different `xfId` in the target.
+fn link_named_styles(
+    target: &mut NamedStyles,
+    named_styles: HashMap<u32, NamedStyle>,
+) -> HashMap<u32, u32> {
+    let mut format_ids = HashMap::new();
+    for (source_id, named_style) in named_styles {
+        let target_id = match target.find(&named_style.name) {
+            Some(existing) => existing.format_id,
+            None => match target.add(&named_style.name, named_style.style) {
+                Ok(added) => added.format_id,
+                Err(_) => 0,
+            },
+        };
+        if target_id != source_id {
+            format_ids.insert(source_id, target_id);
+        }
+    }
+    format_ids
+}
+
+fn relink_styles(sheet: &mut Worksheet, format_ids: &HashMap<u32, u32>) {
+    let relink = |style: &mut Style| {
+        if let Some(format_id) = format_ids.get(style.get_format_id()) {
+            style.set_format_id(*format_id);
+        }
+    };
+
+    for cell in sheet.get_cell_collection_mut() {
+        relink(cell.get_style_mut());
+    }
+    for row in sheet.get_row_dimensions_mut() {
+        relink(row.get_style_mut());
+    }
+    for column in sheet.get_column_dimensions_mut().iter_mut() {
+        relink(column.get_style_mut());
+    }
+}
+
+/// Gives tables whose name is taken in the target workbook a free name.
+///
+/// Table names share one namespace with defined names, so both are checked.
+/// Returns the old and new name of every renamed table.
+fn rename_tables(target: &Spreadsheet, sheet: &mut Worksheet) -> Vec<(String, String)> {
+    let mut taken: HashSet<String> = target
+        .get_sheet_collection_no_check()
+        .iter()
+        .flat_map(|sheet| {
+            sheet
+                .get_tables()
+                .iter()
+                .flat_map(|table| [table.get_name(), table.get_display_name()])
+        })
+        .chain(
+            defined_name_helper::all_defined_names(target)
+                .into_iter()
+                .map(|(_, defined_name)| defined_name.get_name()),
+        )
+        .map(|name| name.to_lowercase())
+        .collect();
+
+    let mut renames = Vec::new();
+    for table in sheet.get_tables_mut().iter_mut() {
+        let name = table.get_name().to_string();
+        let display_name = match table.get_display_name() {
+            "" => name.clone(),
+            display_name => display_name.to_string(),
+        };
+        if !taken.contains(&name.to_lowercase()) && !taken.contains(&display_name.to_lowercase()) {
+            taken.insert(name.to_lowercase());
+            taken.insert(display_name.to_lowercase());
+            continue;
+        }
+
+        // Formulas refer to a table by its display name
+        let mut suffix = 2;
+        let mut new_name = format!("{}_{}", display_name, suffix);
+        while taken.contains(&new_name.to_lowercase()) {
+            suffix += 1;
+            new_name = format!("{}_{}", display_name, suffix);
+        }
+        taken.insert(new_name.to_lowercase());
+
+        table.set_name(&new_name);
+        table.set_display_name(&new_name);
+        if !name.eq_ignore_ascii_case(&display_name) {
+            renames.push((name, new_name.clone()));
+        }
+        renames.push((display_name, new_name));
+    }
+    renames
+}
@@ -1,7 +1,17 @@
-use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
-use umya_spreadsheet;
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::{self, SheetProtection};
 
 use crate::atoms;
+use crate::helpers::defined_name_helper::{self, MAX_COLUMN, MAX_ROW};
+use crate::helpers::formula_helper;
+use crate::helpers::package_helper::OutlineAxis;
+use crate::helpers::protection_helper::{self, PasswordHash, SHEET_PROTECTION_FLAGS};
+use crate::helpers::range_helper::{self, RangeTarget};
+use crate::helpers::row_column_helper;
+use crate::helpers::sheet_property_helper;
+use crate::helpers::sheet_reference_helper;
 use crate::UmyaSpreadsheet;
 
 /// Helper function to ensure a worksheet is deserialized
@@ -66,11 +76,8 @@ pub fn get_sheet_state(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
-            let state = sheet.get_sheet_state();
-            // get_sheet_state() returns &str directly now
-            // If state is empty, default to "visible"
-            let state_str = if state.is_empty() { "visible" } else { state };
-            Ok((atoms::ok(), state_str.to_string()))
+            let state = sheet_property_helper::sheet_state_name(sheet.get_state());
+            Ok((atoms::ok(), state.to_string()))
         }
         None => Err(NifError::Term(Box::new((
             atoms::error(),
@@ -79,73 +86,68 @@ pub fn get_sheet_state(
     }
 }
 
-/// Get sheet protection status
+/// Get sheet protection settings
+///
+/// Returns every protection flag plus the stored password hash parameters, in
+/// the shape accepted by `set_sheet_protection_options`. Flags without an
+/// attribute report their ECMA-376 default.
 #[rustler::nif]
-pub fn get_sheet_protection(
+pub fn get_sheet_protection<'a>(
+    env: Env<'a>,
     resource: ResourceArc<UmyaSpreadsheet>,
     sheet_name: String,
-) -> NifResult<(Atom, std::collections::HashMap<String, bool>)> {
-    let mut guard = resource.spreadsheet.lock().unwrap();
+) -> NifResult<(Atom, HashMap<String, Term<'a>>)> {
+    let guard = resource.spreadsheet.lock().unwrap();
+    let extras = resource.package_extras.lock().unwrap();
 
-    match guard.get_sheet_by_name_mut(&sheet_name) {
+    match guard.get_sheet_by_name(&sheet_name) {
         Some(sheet) => {
-            let mut protection_map = std::collections::HashMap::new();
-
-            if let Some(protection) = sheet.get_sheet_protection() {
-                protection_map.insert("sheet".to_string(), *protection.get_sheet());
-                protection_map.insert("objects".to_string(), *protection.get_objects());
-                protection_map.insert("scenarios".to_string(), *protection.get_scenarios());
-                protection_map.insert("format_cells".to_string(), *protection.get_format_cells());
-                protection_map.insert(
-                    "format_columns".to_string(),
-                    *protection.get_format_columns(),
-                );
-                protection_map.insert("format_rows".to_string(), *protection.get_format_rows());
-                protection_map.insert(
-                    "insert_columns".to_string(),
-                    *protection.get_insert_columns(),
-                );
-                protection_map.insert("insert_rows".to_string(), *protection.get_insert_rows());
-                protection_map.insert(
-                    "insert_hyperlinks".to_string(),
-                    *protection.get_insert_hyperlinks(),
-                );
-                protection_map.insert(
-                    "delete_columns".to_string(),
-                    *protection.get_delete_columns(),
-                );
-                protection_map.insert("delete_rows".to_string(), *protection.get_delete_rows());
-                protection_map.insert(
-                    "select_locked_cells".to_string(),
-                    *protection.get_select_locked_cells(),
-                );
-                protection_map.insert("sort".to_string(), *protection.get_sort());
-                protection_map.insert("auto_filter".to_string(), *protection.get_auto_filter());
-                protection_map.insert("pivot_tables".to_string(), *protection.get_pivot_tables());
-                protection_map.insert(
-                    "select_unlocked_cells".to_string(),
-                    *protection.get_select_unlocked_cells(),
-                );
-            } else {
-                // Return default protection settings if none exist
-                protection_map.insert("sheet".to_string(), false);
-                protection_map.insert("objects".to_string(), false);
-                protection_map.insert("scenarios".to_string(), false);
-                protection_map.insert("format_cells".to_string(), true);
-                protection_map.insert("format_columns".to_string(), true);
-                protection_map.insert("format_rows".to_string(), true);
-                protection_map.insert("insert_columns".to_string(), true);
-                protection_map.insert("insert_rows".to_string(), true);
-                protection_map.insert("insert_hyperlinks".to_string(), true);
-                protection_map.insert("delete_columns".to_string(), true);
-                protection_map.insert("delete_rows".to_string(), true);
-                protection_map.insert("select_locked_cells".to_string(), true);
-                protection_map.insert("sort".to_string(), true);
-                protection_map.insert("auto_filter".to_string(), true);
-                protection_map.insert("pivot_tables".to_string(), true);
-                protection_map.insert("select_unlocked_cells".to_string(), true);
+            let protection = sheet.get_sheet_protection();
+            let mut protection_map = HashMap::new();
+
+            for (key, _, default) in SHEET_PROTECTION_FLAGS {
+                let value = match protection {
+                    Some(protection)
+                        if !extras.is_implicit_sheet_protection_flag(&sheet_name, key) =>
+                    {
+                        protection_helper::sheet_protection_flag(protection, key)
+                    }
+                    _ => default,
+                };
+                protection_map.insert(key.to_string(), value.encode(env));
             }
 
+            let hash = protection.and_then(|protection| {
+                PasswordHash::from_parts(
+                    protection.get_algorithm_name(),
+                    protection.get_hash_value(),
+                    protection.get_salt_value(),
+                    *protection.get_spin_count(),
+                )
+            });
+            protection_map.insert(
+                "algorithm_name".to_string(),
+                hash.as_ref()
+                    .map(|hash| hash.algorithm_name.as_str())
+                    .encode(env),
+            );
+            protection_map.insert(
+                "hash_value".to_string(),
+                hash.as_ref()
+                    .map(|hash| hash.hash_value.as_str())
+                    .encode(env),
+            );
+            protection_map.insert(
+                "salt_value".to_string(),
+                hash.as_ref()
+                    .map(|hash| hash.salt_value.as_str())
+                    .encode(env),
+            );
+            protection_map.insert(
+                "spin_count".to_string(),
+                hash.as_ref().map(|hash| hash.spin_count).encode(env),
+            );
+
             Ok((atoms::ok(), protection_map))
         }
         None => Err(NifError::Term(Box::new((
@@ -197,11 +199,16 @@ pub fn add_sheet(resource: ResourceArc<UmyaSpreadsheet>, sheet_name: String) ->
 }
 
 /// Rename an existing sheet
+///
+/// With `rewrite_references`, the formulas, defined names, conditional formats,
+/// data validations, hyperlinks, chart series and pivot table sources that refer to
+/// the sheet are pointed at the new name, as Excel does.
 #[rustler::nif]
 pub fn rename_sheet(
     resource: ResourceArc<UmyaSpreadsheet>,
     old_name: String,
     new_name: String,
+    rewrite_references: bool,
 ) -> NifResult<Atom> {
     let mut guard = resource.spreadsheet.lock().unwrap();
 
@@ -222,12 +229,41 @@ pub fn rename_sheet(
     }
 
     // Rename sheet
-    if let Some(sheet) = guard.get_sheet_by_name_mut(&old_name) {
+    if rewrite_references {
+        rename_sheet_with_references(&mut guard, &old_name, &new_name);
+    } else if let Some(sheet) = guard.get_sheet_by_name_mut(&old_name) {
         sheet.set_name(&new_name);
     }
+    let mut extras = resource.package_extras.lock().unwrap();
+    extras.move_implicit_sheet_protection_flags(&old_name, &new_name);
+    extras.move_sheet_properties(&old_name, &new_name);
+    extras.move_sheet_outline(&old_name, &new_name);
+    extras.move_conditional_extensions(&old_name, &new_name);
     Ok(atoms::ok())
 }
 
+fn rename_sheet_with_references(
+    spreadsheet: &mut umya_spreadsheet::Spreadsheet,
+    old_name: &str,
+    new_name: &str,
+) {
+    if let Some(sheet) = spreadsheet.get_sheet_by_name_mut(old_name) {
+        // `set_name` points every name kept on the sheet at it, even names for other
+        // sheets, so the names are set aside and rewritten with the rest
+        let defined_names = std::mem::take(sheet.get_defined_names_mut());
+        sheet.set_name(new_name);
+        *sheet.get_defined_names_mut() = defined_names;
+    }
+
+    let rewrite =
+        |formula: &str| formula_helper::rename_sheet_references(formula, old_name, new_name);
+    for sheet in spreadsheet.get_sheet_collection_mut().iter_mut() {
+        sheet_reference_helper::rewrite_formulas(sheet, &rewrite);
+        sheet_reference_helper::rename_sheet_sources(sheet, old_name, new_name);
+    }
+    sheet_reference_helper::rewrite_defined_names(spreadsheet, &rewrite);
+}
+
 /// Move a range of cells to a new location
 #[rustler::nif]
 pub fn move_range(
@@ -265,7 +301,13 @@ pub fn clone_sheet(
         Some(source_sheet) => {
             let mut cloned_sheet = source_sheet.clone();
             cloned_sheet.set_name(&new_sheet_name);
-            let _ = guard.add_sheet(cloned_sheet);
+            if guard.add_sheet(cloned_sheet).is_ok() {
+                let mut extras = resource.package_extras.lock().unwrap();
+                extras.copy_implicit_sheet_protection_flags(&source_sheet_name, &new_sheet_name);
+                extras.copy_sheet_properties(&source_sheet_name, &new_sheet_name);
+                extras.copy_sheet_outline(&source_sheet_name, &new_sheet_name);
+                extras.copy_conditional_extensions(&source_sheet_name, &new_sheet_name);
+            }
             Ok(atoms::ok())
         }
         None => Err(NifError::Term(Box::new((
@@ -276,12 +318,34 @@ pub fn clone_sheet(
 }
 
 /// Remove a sheet from the spreadsheet
+///
+/// Names scoped to the sheet are removed with it, and names scoped to later sheets
+/// stay scoped to them.
 #[rustler::nif]
 pub fn remove_sheet(resource: ResourceArc<UmyaSpreadsheet>, sheet_name: String) -> NifResult<Atom> {
     let mut guard = resource.spreadsheet.lock().unwrap();
+    let index = guard
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| sheet.get_name() == sheet_name)
+        .map(|index| index as u32);
 
     match guard.remove_sheet_by_name(&sheet_name) {
-        Ok(_) => Ok(atoms::ok()),
+        Ok(_) => {
+            let mut extras = resource.package_extras.lock().unwrap();
+            if let Some(index) = index {
+                let new_index = |id: u32| if id > index { id - 1 } else { id };
+                defined_name_helper::remove_local_names(&mut guard, index);
+                defined_name_helper::remap_local_sheet_ids(&mut guard, new_index);
+                extras.remove_defined_name_comment_sheet(index);
+                extras.remap_defined_name_comment_sheets(new_index);
+            }
+            extras.clear_implicit_sheet_protection_flags(&sheet_name);
+            extras.clear_sheet_properties(&sheet_name);
+            extras.clear_sheet_outline(&sheet_name);
+            extras.clear_conditional_extensions(&sheet_name);
+            Ok(atoms::ok())
+        }
         Err(_) => Err(NifError::Term(Box::new((
             atoms::error(),
             "Sheet not found or cannot be removed".to_string(),
@@ -289,47 +353,219 @@ pub fn remove_sheet(resource: ResourceArc<UmyaSpreadsheet>, sheet_name: String)
     }
 }
 
-/// Insert new rows into a sheet
+/// Move a sheet to another position in the workbook
+///
+/// `position` is a zero-based index, `{:before, sheet_name}` or `{:after, sheet_name}`.
+/// The active sheet stays active, and names scoped to a sheet stay scoped to it.
 #[rustler::nif]
-pub fn insert_new_row(
+pub fn move_sheet(
     resource: ResourceArc<UmyaSpreadsheet>,
     sheet_name: String,
-    row_index: u32,
-    amount: u32,
+    position: Term,
 ) -> NifResult<Atom> {
-    let mut guard = resource.spreadsheet.lock().unwrap();
-
-    match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            sheet.insert_new_row(&row_index, &amount);
-            Ok(atoms::ok())
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let names: Vec<String> = guard
+            .get_sheet_collection_no_check()
+            .iter()
+            .map(|sheet| sheet.get_name().to_string())
+            .collect();
+        let index_of = |name: &str| names.iter().position(|sheet| sheet == name);
+
+        let from = index_of(&sheet_name).ok_or_else(|| "Sheet not found".to_string())?;
+        let invalid_position =
+            || "Position must be an index, {:before, name} or {:after, name}".to_string();
+        let to = if let Ok(index) = position.decode::<usize>() {
+            if index >= names.len() {
+                return Err(format!("Sheet index {} is out of range", index));
+            }
+            index
+        } else {
+            let (side, other) = position
+                .decode::<(Atom, String)>()
+                .map_err(|_| invalid_position())?;
+            let other_index =
+                index_of(&other).ok_or_else(|| format!("Sheet '{}' not found", other))?;
+            // Count positions as if the moved sheet had been taken out already
+            let other_index = if from < other_index {
+                other_index - 1
+            } else {
+                other_index
+            };
+            match side {
+                side if side == atoms::before() => other_index,
+                side if side == atoms::after() && other == sheet_name => from,
+                side if side == atoms::after() => other_index + 1,
+                _ => return Err(invalid_position()),
+            }
+        };
+        if from == to {
+            return Ok(());
         }
-        None => Err(NifError::Term(Box::new((
+
+        let mut order: Vec<u32> = (0..names.len() as u32).collect();
+        let moved = order.remove(from);
+        order.insert(to, moved);
+        let new_index = |old: u32| {
+            order
+                .iter()
+                .position(|index| *index == old)
+                .map(|index| index as u32)
+                .unwrap_or(old)
+        };
+
+        let sheets = guard.get_sheet_collection_mut();
+        let sheet = sheets.remove(from);
+        sheets.insert(to, sheet);
+
+        let active_tab = *guard.get_workbook_view().get_active_tab();
+        guard.set_active_sheet(new_index(active_tab));
+        defined_name_helper::remap_local_sheet_ids(&mut guard, new_index);
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.remap_defined_name_comment_sheets(new_index);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
             atoms::error(),
-            "Sheet not found".to_string(),
+            "Error occurred in move_sheet operation".to_string(),
         )))),
     }
 }
 
+/// Insert new rows into a sheet
+///
+/// Tables and data validations grow with rows inserted inside them. `inherit` is
+/// "above" or "below" to give the new rows the height, styles, formulas and data
+/// validations of that neighbour.
+#[rustler::nif]
+pub fn insert_new_row(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    row_index: u32,
+    amount: u32,
+    inherit: Option<String>,
+) -> NifResult<Atom> {
+    insert_lines(
+        &resource,
+        &sheet_name,
+        OutlineAxis::Rows,
+        if (1..=MAX_ROW).contains(&row_index) {
+            Ok(row_index)
+        } else {
+            Err(format!("Invalid row index {}", row_index))
+        },
+        amount,
+        inherit,
+        "insert_new_row",
+    )
+}
+
 /// Insert new columns into a sheet
+///
+/// `inherit` is "left" or "right" to give the new columns the width, styles,
+/// formulas and data validations of that neighbour.
 #[rustler::nif]
 pub fn insert_new_column(
     resource: ResourceArc<UmyaSpreadsheet>,
     sheet_name: String,
     column_letter: String,
     amount: u32,
+    inherit: Option<String>,
 ) -> NifResult<Atom> {
-    let mut guard = resource.spreadsheet.lock().unwrap();
+    insert_lines(
+        &resource,
+        &sheet_name,
+        OutlineAxis::Columns,
+        match range_helper::parse_range(&format!("{0}:{0}", column_letter)) {
+            Ok(RangeTarget::Columns { start_column, .. }) => Ok(start_column),
+            _ => Err(format!("Invalid column '{}'", column_letter)),
+        },
+        amount,
+        inherit,
+        "insert_new_column",
+    )
+}
 
-    match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            // Use the column letter directly
-            sheet.insert_new_column(column_letter.as_str(), &amount);
-            Ok(atoms::ok())
+/// Insert new columns into a sheet by column index (1-based)
+#[rustler::nif]
+pub fn insert_new_column_by_index(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    column_index: u32,
+    amount: u32,
+    inherit: Option<String>,
+) -> NifResult<Atom> {
+    insert_lines(
+        &resource,
+        &sheet_name,
+        OutlineAxis::Columns,
+        if (1..=MAX_COLUMN).contains(&column_index) {
+            Ok(column_index)
+        } else {
+            Err(format!("Invalid column index {}", column_index))
+        },
+        amount,
+        inherit,
+        "insert_new_column_by_index",
+    )
+}
+
+/// Inserts `amount` rows or columns at `index`, as parsed by the calling NIF; an
+/// `Err` index is reported as the error of the operation.
+fn insert_lines(
+    resource: &ResourceArc<UmyaSpreadsheet>,
+    sheet_name: &str,
+    axis: OutlineAxis,
+    index: Result<u32, String>,
+    amount: u32,
+    inherit: Option<String>,
+    operation: &str,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let index = index?;
+        if amount == 0 {
+            return Err("Amount must be at least 1".to_string());
         }
-        None => Err(NifError::Term(Box::new((
+        let inherit = inherit
+            .as_deref()
+            .map(|value| row_column_helper::Inherit::parse(axis, value))
+            .transpose()?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        row_column_helper::insert(sheet, axis, index, amount, inherit);
+
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        extras.shift_sheet_outline(sheet_name, axis, index, amount.into());
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
             atoms::error(),
-            "Sheet not found".to_string(),
+            format!("Error occurred in {} operation", operation),
         )))),
     }
 }
@@ -346,6 +582,12 @@ pub fn set_sheet_protection(
 
     match guard.get_sheet_by_name_mut(&sheet_name) {
         Some(sheet) => {
+            if sheet.get_sheet_protection().is_none() {
+                // Only `sheet` is written; the other flags keep their defaults
+                let mut extras = resource.package_extras.lock().unwrap();
+                extras.set_all_sheet_protection_flags_implicit(&sheet_name);
+            }
+
             let protection = sheet.get_sheet_protection_mut();
             protection.set_sheet(protected);
 
@@ -362,31 +604,103 @@ pub fn set_sheet_protection(
     }
 }
 
-/// Add merge cells to a sheet
-#[rustler::nif]
-pub fn add_merge_cells(
+/// Set sheet protection flags and password from an options map
+///
+/// Keys are the flags returned by `get_sheet_protection` (`true` blocks the
+/// action) and the password options `password`, `algorithm_name`,
+/// `hash_value`, `salt_value` and `spin_count`. Flags that are not given keep
+/// their current value; `sheet` defaults to `true` on an unprotected sheet.
+/// All flags are written explicitly.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn set_sheet_protection_options(
     resource: ResourceArc<UmyaSpreadsheet>,
     sheet_name: String,
-    range: String,
+    options: Term,
 ) -> NifResult<Atom> {
-    let mut guard = resource.spreadsheet.lock().unwrap();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let options: HashMap<String, Term> = options
+            .decode()
+            .map_err(|_| "Protection options must be a map".to_string())?;
+        let flag_keys: Vec<&str> = SHEET_PROTECTION_FLAGS
+            .iter()
+            .map(|(key, ..)| *key)
+            .collect();
+        protection_helper::check_option_keys(&options, &flag_keys)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let current = sheet.get_sheet_protection().cloned();
+        let mut protection = SheetProtection::default();
+
+        for (key, _, default) in SHEET_PROTECTION_FLAGS {
+            let value = match protection_helper::flag_option(&options, key)? {
+                Some(value) => value,
+                None => match &current {
+                    Some(current)
+                        if !extras.is_implicit_sheet_protection_flag(&sheet_name, key) =>
+                    {
+                        protection_helper::sheet_protection_flag(current, key)
+                    }
+                    Some(_) => default,
+                    None => key == "sheet" || default,
+                },
+            };
+            protection_helper::set_sheet_protection_flag(&mut protection, key, value);
+        }
 
-    match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            // Create a new Range and add it to merge cells
-            let mut new_range = umya_spreadsheet::Range::default();
-            new_range.set_range(range.as_str());
-            sheet.get_merge_cells_mut().push(new_range);
-            Ok(atoms::ok())
+        let current_hash = current.as_ref().and_then(|current| {
+            PasswordHash::from_parts(
+                current.get_algorithm_name(),
+                current.get_hash_value(),
+                current.get_salt_value(),
+                *current.get_spin_count(),
+            )
+        });
+        let password_changed =
+            options.contains_key("password") || options.contains_key("hash_value");
+
+        if let Some(hash) = protection_helper::apply_password_options(&options, current_hash)? {
+            protection.set_algorithm_name(hash.algorithm_name);
+            protection.set_hash_value(hash.hash_value);
+            protection.set_salt_value(hash.salt_value);
+            protection.set_spin_count(hash.spin_count);
         }
-        None => Err(NifError::Term(Box::new((
+        if let Some(current) = current.filter(|_| !password_changed) {
+            if !current.get_password_raw().is_empty() {
+                protection.set_password_raw(current.get_password_raw());
+            }
+        }
+
+        sheet.set_sheet_protection(protection);
+        extras.clear_implicit_sheet_protection_flags(&sheet_name);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
             atoms::error(),
-            "Sheet not found".to_string(),
+            "Error occurred in set_sheet_protection_options operation".to_string(),
         )))),
     }
 }
 
 /// Set sheet state (visible, hidden, very hidden)
+///
+/// The last visible sheet cannot be hidden, and hiding the active sheet activates
+/// the first visible one.
 #[rustler::nif]
 pub fn set_sheet_state(
     resource: ResourceArc<UmyaSpreadsheet>,
@@ -395,14 +709,17 @@ pub fn set_sheet_state(
 ) -> NifResult<Atom> {
     let mut guard = resource.spreadsheet.lock().unwrap();
 
-    match guard.get_sheet_by_name_mut(&sheet_name) {
-        Some(sheet) => {
-            sheet.set_sheet_state(state);
-            Ok(atoms::ok())
-        }
-        None => Err(NifError::Term(Box::new((
-            atoms::error(),
-            "Sheet not found".to_string(),
-        )))),
+    let result = sheet_property_helper::parse_sheet_state(&state).and_then(|state| {
+        let sheet_index = guard
+            .get_sheet_collection_no_check()
+            .iter()
+            .position(|sheet| sheet.get_name() == sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        sheet_property_helper::set_sheet_state(&mut guard, sheet_index, state)
+    });
+
+    match result {
+        Ok(()) => Ok(atoms::ok()),
+        Err(msg) => Err(NifError::Term(Box::new((atoms::error(), msg)))),
     }
 }
@@ -0,0 +1,283 @@
+use rustler::{Atom, Encoder, Env, Error as NifError, NifResult, ResourceArc, Term};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::drawing::Theme;
+use umya_spreadsheet::{Color, Spreadsheet};
+
+use crate::atoms;
+use crate::helpers::color_helper;
+use crate::helpers::sheet_property_helper;
+use crate::UmyaSpreadsheet;
+
+const SHEET_PROPERTY_KEYS: [&str; 6] = [
+    "state",
+    "tab_color",
+    "code_name",
+    "filter_mode",
+    "summary_below",
+    "summary_right",
+];
+
+/// Get the properties of a sheet
+///
+/// Returns a map with `state` ("visible", "hidden" or "veryhidden"), `tab_color`,
+/// `code_name`, `filter_mode`, `summary_below` and `summary_right`. The tab color
+/// is nil or a map with its `rgb` value, resolved from the theme or the indexed
+/// palette when it is defined that way, and its `theme`, `indexed` and `tint`.
+#[rustler::nif]
+pub fn get_sheet_properties(
+    env: Env,
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<Term, String> {
+        let guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        let properties = extras.sheet_properties(&sheet_name);
+
+        let mut map = HashMap::new();
+        map.insert(
+            "state",
+            sheet_property_helper::sheet_state_name(sheet.get_state()).encode(env),
+        );
+        map.insert(
+            "tab_color",
+            match sheet.get_tab_color() {
+                Some(color) => encode_tab_color(env, color, guard.get_theme()),
+                None => None::<String>.encode(env),
+            },
+        );
+        map.insert("code_name", properties.code_name.encode(env));
+        map.insert("filter_mode", properties.filter_mode.encode(env));
+        map.insert("summary_below", properties.summary_below.encode(env));
+        map.insert("summary_right", properties.summary_right.encode(env));
+
+        Ok(map.encode(env))
+    }));
+
+    match result {
+        Ok(Ok(map)) => (atoms::ok(), map).encode(env),
+        Ok(Err(msg)) => (atoms::error(), msg).encode(env),
+        Err(_) => (
+            atoms::error(),
+            "Error occurred in get_sheet_properties operation".to_string(),
+        )
+            .encode(env),
+    }
+}
+
+/// Set properties of a sheet from a map
+///
+/// Accepts the keys returned by `get_sheet_properties`; keys that are not given
+/// keep their value. `tab_color` is a color string, nil to remove the color, or a
+/// map with `theme`, `indexed` or `rgb`, taken in that order, and an optional `tint`.
+/// `code_name` nil removes the code name.
+#[rustler::nif]
+pub fn set_sheet_properties(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    options: Term,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let options: HashMap<String, Term> = options
+            .decode()
+            .map_err(|_| "Sheet properties must be a map".to_string())?;
+        if let Some(key) = options
+            .keys()
+            .find(|key| !SHEET_PROPERTY_KEYS.contains(&key.as_str()))
+        {
+            return Err(format!("Unknown sheet property '{}'", key));
+        }
+
+        let state = match options.get("state") {
+            Some(term) => {
+                let state: String = term
+                    .decode()
+                    .map_err(|_| "Property 'state' must be a string".to_string())?;
+                Some(sheet_property_helper::parse_sheet_state(&state)?)
+            }
+            None => None,
+        };
+        let tab_color = match options.get("tab_color") {
+            Some(term) => Some(decode_tab_color(*term)?),
+            None => None,
+        };
+        let code_name = match options.get("code_name") {
+            Some(term) => Some(
+                term.decode::<Option<String>>()
+                    .map_err(|_| "Property 'code_name' must be a string".to_string())?
+                    .filter(|code_name| !code_name.is_empty()),
+            ),
+            None => None,
+        };
+        let flag = |key: &str| -> Result<Option<bool>, String> {
+            match options.get(key) {
+                Some(term) => term
+                    .decode::<bool>()
+                    .map(Some)
+                    .map_err(|_| format!("Property '{}' must be a boolean", key)),
+                None => Ok(None),
+            }
+        };
+        let filter_mode = flag("filter_mode")?;
+        let summary_below = flag("summary_below")?;
+        let summary_right = flag("summary_right")?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let mut extras = resource
+            .package_extras
+            .lock()
+            .map_err(|_| "Failed to acquire package extras lock".to_string())?;
+        let sheet_index = sheet_index(&guard, &sheet_name)?;
+
+        if let Some(state) = state {
+            sheet_property_helper::set_sheet_state(&mut guard, sheet_index, state)?;
+        }
+
+        let sheet = guard
+            .get_sheet_mut(&sheet_index)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+        match tab_color {
+            Some(Some(color)) => {
+                sheet.set_tab_color(color);
+            }
+            Some(None) => {
+                sheet.remove_tab_color();
+            }
+            None => (),
+        }
+
+        let mut properties = extras.sheet_properties(&sheet_name);
+        if let Some(code_name) = code_name {
+            if let Some(code_name) = &code_name {
+                sheet.set_code_name(code_name);
+            }
+            properties.code_name = code_name;
+        }
+        properties.filter_mode = filter_mode.unwrap_or(properties.filter_mode);
+        properties.summary_below = summary_below.unwrap_or(properties.summary_below);
+        properties.summary_right = summary_right.unwrap_or(properties.summary_right);
+        extras.set_sheet_properties(&sheet_name, properties);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in set_sheet_properties operation".to_string(),
+        )))),
+    }
+}
+
+fn sheet_index(spreadsheet: &Spreadsheet, sheet_name: &str) -> Result<usize, String> {
+    spreadsheet
+        .get_sheet_collection_no_check()
+        .iter()
+        .position(|sheet| sheet.get_name() == sheet_name)
+        .ok_or_else(|| "Sheet not found".to_string())
+}
+
+/// Encodes a tab color as a map of its resolved `rgb` value and its definition.
+fn encode_tab_color<'a>(env: Env<'a>, color: &Color, theme: &Theme) -> Term<'a> {
+    let resolved = color.get_argb_with_theme(theme);
+    let rgb = match resolved.trim_start_matches('#') {
+        argb if argb.len() == 8 => Some(format!("#{}", &argb[2..])),
+        rgb if rgb.len() == 6 => Some(format!("#{}", rgb)),
+        _ => None,
+    };
+    let tint = Some(*color.get_tint()).filter(|tint| *tint != 0.0);
+
+    // Color does not tell how it is defined, so compare it with one defined each way
+    let defined_as = |define: &dyn Fn(&mut Color)| {
+        let mut probe = Color::default();
+        define(&mut probe);
+        if let Some(tint) = tint {
+            probe.set_tint(tint);
+        }
+        probe == *color
+    };
+    let theme_index = Some(*color.get_theme_index()).filter(|index| {
+        defined_as(&|probe: &mut Color| {
+            probe.set_theme_index(*index);
+        })
+    });
+    let indexed = Some(*color.get_indexed()).filter(|index| {
+        defined_as(&|probe: &mut Color| {
+            probe.set_indexed(*index);
+        })
+    });
+
+    let mut map = HashMap::new();
+    map.insert("rgb", rgb.encode(env));
+    map.insert("theme", theme_index.encode(env));
+    map.insert("indexed", indexed.encode(env));
+    map.insert("tint", tint.encode(env));
+    map.encode(env)
+}
+
+/// Decodes a tab color option; nil removes the color.
+fn decode_tab_color(term: Term) -> Result<Option<Color>, String> {
+    if let Ok(value) = term.decode::<Option<String>>() {
+        return match value {
+            Some(value) => color_helper::create_color_object(&value)
+                .map(Some)
+                .map_err(|_| format!("Invalid color '{}'", value)),
+            None => Ok(None),
+        };
+    }
+
+    let invalid = || {
+        "Property 'tab_color' must be a color string or a map with rgb, theme or indexed"
+            .to_string()
+    };
+    let map: HashMap<String, Term> = term.decode().map_err(|_| invalid())?;
+    // Nil entries are ignored, so a map from `get_sheet_properties` can be passed back
+    let entry = |key: &str| {
+        map.get(key).copied().filter(|term| {
+            term.decode::<Option<bool>>()
+                .map(|value| value.is_some())
+                .unwrap_or(true)
+        })
+    };
+
+    // A theme or indexed color also reports its resolved rgb value, which is ignored
+    let mut color = Color::default();
+    if let Some(theme) = entry("theme") {
+        color.set_theme_index(theme.decode().map_err(|_| invalid())?);
+    } else if let Some(indexed) = entry("indexed") {
+        color.set_indexed(indexed.decode().map_err(|_| invalid())?);
+    } else if let Some(rgb) = entry("rgb") {
+        let rgb: String = rgb.decode().map_err(|_| invalid())?;
+        color = color_helper::create_color_object(&rgb)
+            .map_err(|_| format!("Invalid color '{}'", rgb))?;
+    } else {
+        return Err(invalid());
+    }
+
+    if let Some(tint) = entry("tint") {
+        let tint: f64 = tint
+            .decode()
+            .or_else(|_| tint.decode::<i64>().map(|tint| tint as f64))
+            .map_err(|_| "Tab color tint must be a number between -1 and 1".to_string())?;
+        if !(-1.0..=1.0).contains(&tint) {
+            return Err("Tab color tint must be a number between -1 and 1".to_string());
+        }
+        color.set_tint(tint);
+    }
+    Ok(Some(color))
+}
@@ -1,4 +1,5 @@
 use crate::atoms;
+use crate::helpers::color_helper;
 use crate::UmyaSpreadsheet;
 use rustler::{Atom, Encoder, Error as NifError, NifResult};
 use std::panic::{self, AssertUnwindSafe};
@@ -159,8 +160,8 @@ pub fn set_tab_color(
 
         // Get sheet by name
         if let Some(sheet) = spreadsheet.get_sheet_by_name_mut(&sheet_name) {
-            let mut tab_color = umya_spreadsheet::Color::default();
-            tab_color.set_argb(color);
+            let tab_color = color_helper::create_color_object(&color)
+                .map_err(|_| format!("Invalid color '{}'", color))?;
             sheet.set_tab_color(tab_color);
             Ok(atoms::ok())
         } else {
@@ -591,21 +592,19 @@ pub fn get_tab_color<'a>(
             // Check if tab color is set
             match sheet.get_tab_color() {
                 Some(color) => {
-                    // Get the ARGB color string and remove the alpha channel (first 2 characters)
-                    let argb = color.get_argb();
+                    // Theme and indexed colors are resolved to their RGB value
+                    let argb = color.get_argb_with_theme(spreadsheet.get_theme());
+                    let argb = argb.trim_start_matches('#');
                     // Ensure we have the full 6-digit hex color
                     let color_hex = if argb.len() >= 8 {
                         // Take characters 2-8 to get the RGB part (skip alpha FF prefix)
                         format!("#{}", &argb[2..8])
-                    } else if argb.len() >= 6 && !argb.starts_with('#') {
-                        // If it's 6 chars and doesn't start with #, assume it's RGB
-                        format!("#{}", &argb)
-                    } else if argb.starts_with('#') {
-                        // Already has #, return as-is
-                        argb.to_string()
+                    } else if argb.len() >= 6 {
+                        // Theme colors are stored as RGB
+                        format!("#{}", &argb[..6])
                     } else {
-                        // Pad with zeros if needed
-                        format!("#{:0>6}", &argb[2..])
+                        // A tab color element without a color
+                        String::new()
                     };
                     (atoms::ok(), color_hex).encode(env)
                 }
@@ -1,7 +1,11 @@
-use rustler::{Atom, Error as NifError, NifResult, ResourceArc};
+use rustler::{Atom, Error as NifError, NifResult, ResourceArc, Term};
+use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::Style;
 
 use crate::atoms;
+use crate::helpers::range_helper;
 use crate::helpers::style_helpers;
+use crate::helpers::style_map_helper::StylePatch;
 use crate::UmyaSpreadsheet;
 
 /// Set the font color for a cell
@@ -183,3 +187,60 @@ pub fn copy_column_styling(
         )))),
     }
 }
+
+/// Apply a style map to every cell of a range in one call
+///
+/// The range may be a cell rectangle ("A1:Z500"), whole rows ("3:5") or whole
+/// columns ("A:C"); for rows and columns the row/column style is set as well as
+/// the style of their existing cells. With `mode` "merge" only the parts given in
+/// the map change, with "replace" each cell's style is rebuilt from the map alone.
+#[rustler::nif]
+pub fn set_range_style(
+    resource: ResourceArc<UmyaSpreadsheet>,
+    sheet_name: String,
+    range: String,
+    style: Term,
+    mode: String,
+) -> NifResult<Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let patch = StylePatch::decode(style)?;
+        let replace = match mode.as_str() {
+            "merge" => false,
+            "replace" => true,
+            _ => {
+                return Err(format!(
+                    "Invalid style mode '{}'; use \"merge\" or \"replace\"",
+                    mode
+                ))
+            }
+        };
+        let target = range_helper::parse_range(&range)?;
+
+        let mut guard = resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let sheet = guard
+            .get_sheet_by_name_mut(&sheet_name)
+            .ok_or_else(|| "Sheet not found".to_string())?;
+
+        let update = |style: &mut Style| {
+            if replace {
+                *style = Style::default();
+            }
+            patch.apply(style);
+        };
+        range_helper::update_styles(sheet, target, update);
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(NifError::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(NifError::Term(Box::new((
+            atoms::error(),
+            "Error occurred in set_range_style operation".to_string(),
+        )))),
+    }
+}
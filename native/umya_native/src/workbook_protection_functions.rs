@@ -1,8 +1,13 @@
 use crate::atoms;
+use crate::helpers::protection_helper::{self, PasswordHash};
 use crate::UmyaSpreadsheet;
 use rustler::{Encoder, Env, Term};
 use std::collections::HashMap;
 use std::panic::{self, AssertUnwindSafe};
+use umya_spreadsheet::WorkbookProtection;
+
+/// Workbook lock flags accepted by `set_workbook_protection_options`.
+const WORKBOOK_LOCK_FLAGS: [&str; 3] = ["lock_structure", "lock_windows", "lock_revision"];
 
 #[rustler::nif]
 pub fn is_workbook_protected(
@@ -69,7 +74,7 @@ pub fn get_workbook_protection_details(
     }
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 pub fn set_workbook_protection(
     spreadsheet_resource: rustler::ResourceArc<UmyaSpreadsheet>,
     password: String,
@@ -93,3 +98,150 @@ pub fn set_workbook_protection(
         )))),
     }
 }
+
+/// Set workbook protection from an options map
+///
+/// Keys are `lock_structure`, `lock_windows` and `lock_revision` plus the password
+/// options `password`, `algorithm_name`, `hash_value`, `salt_value` and
+/// `spin_count`. Options that are not given keep their current value;
+/// `lock_structure` defaults to `true` on an unprotected workbook.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn set_workbook_protection_options(
+    spreadsheet_resource: rustler::ResourceArc<UmyaSpreadsheet>,
+    options: Term,
+) -> rustler::NifResult<rustler::Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), String> {
+        let options: HashMap<String, Term> = options
+            .decode()
+            .map_err(|_| "Protection options must be a map".to_string())?;
+        protection_helper::check_option_keys(&options, &WORKBOOK_LOCK_FLAGS)?;
+
+        let lock_structure = protection_helper::flag_option(&options, "lock_structure")?;
+        let lock_windows = protection_helper::flag_option(&options, "lock_windows")?;
+        let lock_revision = protection_helper::flag_option(&options, "lock_revision")?;
+
+        let mut spreadsheet = spreadsheet_resource
+            .spreadsheet
+            .lock()
+            .map_err(|_| "Failed to acquire spreadsheet lock".to_string())?;
+        let current = spreadsheet.get_workbook_protection().cloned();
+        let current_hash = current.as_ref().and_then(workbook_password_hash);
+        let hash = protection_helper::apply_password_options(&options, current_hash)?;
+        let password_changed =
+            options.contains_key("password") || options.contains_key("hash_value");
+
+        let mut protection = WorkbookProtection::default();
+        match &current {
+            Some(current) => {
+                protection
+                    .set_lock_structure(lock_structure.unwrap_or(*current.get_lock_structure()));
+                protection.set_lock_windows(lock_windows.unwrap_or(*current.get_lock_windows()));
+                protection.set_lock_revision(lock_revision.unwrap_or(*current.get_lock_revision()));
+                copy_revisions_password(current, &mut protection);
+                if !password_changed && !current.get_workbook_password_raw().is_empty() {
+                    protection.set_workbook_password_raw(current.get_workbook_password_raw());
+                }
+            }
+            None => {
+                protection.set_lock_structure(lock_structure.unwrap_or(true));
+                protection.set_lock_windows(lock_windows.unwrap_or(false));
+                protection.set_lock_revision(lock_revision.unwrap_or(false));
+            }
+        }
+
+        if let Some(hash) = hash {
+            protection.set_workbook_algorithm_name(hash.algorithm_name);
+            protection.set_workbook_hash_value(hash.hash_value);
+            protection.set_workbook_salt_value(hash.salt_value);
+            protection.set_workbook_spin_count(hash.spin_count);
+        }
+
+        spreadsheet.set_workbook_protection(protection);
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => Ok(atoms::ok()),
+        Ok(Err(err_msg)) => Err(rustler::Error::Term(Box::new((atoms::error(), err_msg)))),
+        Err(_) => Err(rustler::Error::Term(Box::new((
+            atoms::error(),
+            "Error occurred in set_workbook_protection_options".to_string(),
+        )))),
+    }
+}
+
+#[rustler::nif]
+pub fn remove_workbook_protection(
+    spreadsheet_resource: rustler::ResourceArc<UmyaSpreadsheet>,
+) -> rustler::NifResult<rustler::Atom> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut spreadsheet = spreadsheet_resource.spreadsheet.lock().unwrap();
+        spreadsheet.remove_workbook_protection();
+
+        atoms::ok()
+    }));
+
+    match result {
+        Ok(atom) => Ok(atom),
+        Err(_) => Err(rustler::Error::Term(Box::new((
+            atoms::error(),
+            "Error occurred in remove_workbook_protection".to_string(),
+        )))),
+    }
+}
+
+/// Checks a candidate password against the stored workbook password
+///
+/// Uses the hashed password when present, otherwise the legacy `workbookPassword`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn verify_workbook_password(
+    env: Env,
+    spreadsheet_resource: rustler::ResourceArc<UmyaSpreadsheet>,
+    password: String,
+) -> Term {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let spreadsheet = spreadsheet_resource.spreadsheet.lock().unwrap();
+
+        match spreadsheet.get_workbook_protection() {
+            Some(protection) => {
+                let hash = workbook_password_hash(protection);
+                match protection_helper::verify_password(
+                    hash.as_ref(),
+                    protection.get_workbook_password_raw(),
+                    &password,
+                ) {
+                    Ok(matches) => (atoms::ok(), matches).encode(env),
+                    Err(err_msg) => (atoms::error(), err_msg).encode(env),
+                }
+            }
+            None => (atoms::error(), "Workbook is not protected").encode(env),
+        }
+    }));
+
+    match result {
+        Ok(term) => term,
+        Err(_) => (atoms::error(), "Error occurred in verify_workbook_password").encode(env),
+    }
+}
+
+fn workbook_password_hash(protection: &WorkbookProtection) -> Option<PasswordHash> {
+    PasswordHash::from_parts(
+        protection.get_workbook_algorithm_name(),
+        protection.get_workbook_hash_value(),
+        protection.get_workbook_salt_value(),
+        *protection.get_workbook_spin_count(),
+    )
+}
+
+/// Keeps the revisions password, which the options do not touch.
+fn copy_revisions_password(source: &WorkbookProtection, target: &mut WorkbookProtection) {
+    if !source.get_revisions_hash_value().is_empty() {
+        target.set_revisions_algorithm_name(source.get_revisions_algorithm_name());
+        target.set_revisions_hash_value(source.get_revisions_hash_value());
+        target.set_revisions_salt_value(source.get_revisions_salt_value());
+        target.set_revisions_spin_count(*source.get_revisions_spin_count());
+    }
+    if !source.get_revisions_password_raw().is_empty() {
+        target.set_revisions_password_raw(source.get_revisions_password_raw());
+    }
+}
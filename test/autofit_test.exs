@@ -0,0 +1,97 @@
+defmodule UmyaSpreadsheet.AutofitTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/autofit_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "Name")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A2", "A considerably longer name")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "B1", "1234567.891")
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "fits a column to its longest text", %{spreadsheet: spreadsheet} do
+    assert {:ok, %{"A" => width}} = UmyaSpreadsheet.autofit_column(spreadsheet, "Sheet1", "A")
+
+    assert width > 20
+    assert {:ok, ^width} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", "A")
+    assert {:ok, true} = UmyaSpreadsheet.get_column_auto_width(spreadsheet, "Sheet1", "A")
+  end
+
+  test "only measures cells inside the range", %{spreadsheet: spreadsheet} do
+    {:ok, %{"A" => full}} = UmyaSpreadsheet.autofit_columns(spreadsheet, "Sheet1", "A:A")
+    {:ok, %{"A" => header}} = UmyaSpreadsheet.autofit_columns(spreadsheet, "Sheet1", "A1:B1")
+
+    assert header < full
+  end
+
+  test "applies number formats and fonts", %{spreadsheet: spreadsheet} do
+    {:ok, %{"B" => plain}} = UmyaSpreadsheet.autofit_column(spreadsheet, "Sheet1", "B")
+
+    :ok = UmyaSpreadsheet.set_number_format(spreadsheet, "Sheet1", "B1", "#,##0.000 \"EUR\"")
+    {:ok, %{"B" => formatted}} = UmyaSpreadsheet.autofit_column(spreadsheet, "Sheet1", "B")
+    assert formatted > plain
+
+    :ok = UmyaSpreadsheet.set_font_size(spreadsheet, "Sheet1", "B1", 20.0)
+    {:ok, %{"B" => large}} = UmyaSpreadsheet.autofit_column(spreadsheet, "Sheet1", "B")
+    assert large > formatted
+  end
+
+  test "fits every column of a sheet within limits", %{spreadsheet: spreadsheet} do
+    assert {:ok, %{"A" => 12.0, "B" => width}} =
+             UmyaSpreadsheet.autofit_sheet(spreadsheet, "Sheet1", max_width: 12)
+
+    assert width <= 12.0
+  end
+
+  test "skips cells merged across columns", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "D1", "A title spanning columns")
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "D1:F1")
+
+    assert {:ok, widths} = UmyaSpreadsheet.autofit_sheet(spreadsheet, "Sheet1")
+    refute Map.has_key?(widths, "D")
+  end
+
+  test "fits rows to wrapped text", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_column_width(spreadsheet, "Sheet1", "C", 10.0)
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "C1", "Short")
+
+    :ok =
+      UmyaSpreadsheet.set_cell_value(
+        spreadsheet,
+        "Sheet1",
+        "C2",
+        "A long paragraph that needs several lines in a narrow column"
+      )
+
+    :ok = UmyaSpreadsheet.set_wrap_text(spreadsheet, "Sheet1", "C2", true)
+
+    assert {:ok, %{1 => single, 2 => wrapped}} =
+             UmyaSpreadsheet.autofit_rows(spreadsheet, "Sheet1", "C1:C2")
+
+    assert single == 15.0
+    assert wrapped >= 3 * single
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+    assert {:ok, ^wrapped} = UmyaSpreadsheet.get_row_height(reloaded, "Sheet1", 2)
+  end
+
+  test "set_column_auto_width sets a real width", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_column_auto_width(spreadsheet, "Sheet1", "A", true)
+
+    {:ok, width} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", "A")
+    assert width > 20
+  end
+
+  test "rejects invalid ranges and limits", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid range 'A1:'"} =
+             UmyaSpreadsheet.autofit_columns(spreadsheet, "Sheet1", "A1:")
+
+    assert {:error, "Minimum size 20 is larger than maximum size 10"} =
+             UmyaSpreadsheet.autofit_sheet(spreadsheet, "Sheet1", min_width: 20, max_width: 10)
+
+    assert {:error, "Sheet not found"} = UmyaSpreadsheet.autofit_rows(spreadsheet, "Missing", nil)
+  end
+end
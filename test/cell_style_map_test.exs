@@ -0,0 +1,79 @@
+defmodule UmyaSpreadsheet.CellStyleMapTest do
+  use ExUnit.Case, async: true
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "returns the complete style of a cell", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "B2", %{
+        font: %{bold: true, size: 14, color: "#FF0000"},
+        fill: %{color: "#FFFF00"},
+        borders: %{bottom: %{style: "thin", color: "#0000FF"}},
+        alignment: %{horizontal: "center", wrap_text: true},
+        number_format: "0.00",
+        protection: %{locked: false}
+      })
+
+    assert {:ok, style} = UmyaSpreadsheet.get_cell_style(spreadsheet, "Sheet1", "B2")
+
+    assert %{"bold" => true, "size" => 14.0, "color" => "FFFF0000", "underline" => "none"} =
+             style["font"]
+
+    assert %{"pattern" => "solid", "color" => "FFFFFF00", "gradient" => nil} = style["fill"]
+    assert %{"style" => "thin", "color" => "FF0000FF"} = style["borders"]["bottom"]
+    assert %{"style" => "none"} = style["borders"]["top"]
+    assert %{"horizontal" => "center", "wrap_text" => true} = style["alignment"]
+    assert "0.00" = style["number_format"]
+    assert %{"locked" => false, "hidden" => false} = style["protection"]
+  end
+
+  test "fills in defaults for unstyled cells", %{spreadsheet: spreadsheet} do
+    assert {:ok, style} = UmyaSpreadsheet.get_cell_style(spreadsheet, "Sheet1", "Z99")
+
+    assert %{"bold" => false, "italic" => false} = style["font"]
+    assert %{"pattern" => "none", "color" => nil} = style["fill"]
+    assert "General" = style["number_format"]
+    assert %{"locked" => true} = style["protection"]
+  end
+
+  test "style maps can be fed back into set_range_style", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "A1", %{
+        font: %{italic: true},
+        borders: %{all: "medium"}
+      })
+
+    {:ok, style} = UmyaSpreadsheet.get_cell_style(spreadsheet, "Sheet1", "A1")
+
+    # The gradient entry is informational only
+    patch = Map.update!(style, "fill", &Map.delete(&1, "gradient"))
+
+    assert :ok = UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "C3", patch, :replace)
+    assert {:ok, ^style} = UmyaSpreadsheet.get_cell_style(spreadsheet, "Sheet1", "C3")
+  end
+
+  test "returns the styles of a range by address", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_font_bold(spreadsheet, "Sheet1", "B1", true)
+
+    assert {:ok, styles} = UmyaSpreadsheet.get_range_styles(spreadsheet, "Sheet1", "A1:B2")
+    assert Enum.sort(Map.keys(styles)) == ["A1", "A2", "B1", "B2"]
+    assert styles["B1"]["font"]["bold"]
+    refute styles["A1"]["font"]["bold"]
+  end
+
+  test "reports invalid addresses and sheets", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid cell address 'A1:B2'"} =
+             UmyaSpreadsheet.get_cell_style(spreadsheet, "Sheet1", "A1:B2")
+
+    assert {:error, "Sheet not found"} = UmyaSpreadsheet.get_cell_style(spreadsheet, "Nope", "A1")
+
+    assert {:error, "Invalid range 'A1:'"} =
+             UmyaSpreadsheet.get_range_styles(spreadsheet, "Sheet1", "A1:")
+
+    assert {:error, "Range 'A1:XFD1048576' spans 17179869184 cells" <> _} =
+             UmyaSpreadsheet.get_range_styles(spreadsheet, "Sheet1", "A1:XFD1048576")
+  end
+end
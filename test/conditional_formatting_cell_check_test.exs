@@ -0,0 +1,137 @@
+defmodule UmyaSpreadsheet.ConditionalFormattingCellCheckTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/conditional_formatting_cell_check.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "adds duplicate and unique values rules", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "A1:A10", "#FFC7CE")
+
+    assert :ok =
+             UmyaSpreadsheet.add_unique_values_rule(spreadsheet, "Sheet1", "B1:B10", "#C6EFCE")
+
+    assert [duplicates, unique] =
+             ConditionalFormatting.get_duplicate_values_rules(spreadsheet, "Sheet1")
+
+    assert %{range: "A1:A10", rule_type: :duplicate_values, format_style: "FFFFC7CE"} =
+             duplicates
+
+    assert %{range: "B1:B10", rule_type: :unique_values, format_style: "FFC6EFCE"} = unique
+  end
+
+  test "adds blank and error rules with the formulas Excel writes", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B2:B10", "#FFEB9C")
+    :ok = UmyaSpreadsheet.add_no_blanks_rule(spreadsheet, "Sheet1", "C2:C10", "#FFEB9C")
+    :ok = UmyaSpreadsheet.add_errors_rule(spreadsheet, "Sheet1", "D2:D10 F2:F10", "#FF0000")
+    :ok = UmyaSpreadsheet.add_no_errors_rule(spreadsheet, "Sheet1", "E2:E10", "#FF0000")
+
+    assert [
+             %{rule_type: :contains_blanks, formula: "LEN(TRIM(B2))=0"},
+             %{rule_type: :not_contains_blanks, formula: "LEN(TRIM(C2))>0"}
+           ] = ConditionalFormatting.get_blanks_rules(spreadsheet, "Sheet1")
+
+    assert [
+             %{rule_type: :contains_errors, formula: "ISERROR(D2)", range: "D2:D10 F2:F10"},
+             %{rule_type: :not_contains_errors, formula: "NOT(ISERROR(E2))"}
+           ] = ConditionalFormatting.get_errors_rules(spreadsheet, "Sheet1")
+  end
+
+  test "adds time period rules", %{spreadsheet: spreadsheet} do
+    periods = [{"D2:D20", :yesterday}, {"E2:E20", "last7Days"}, {"F2:F20", :this_month}]
+
+    for {range, period} <- periods do
+      :ok = UmyaSpreadsheet.add_time_period_rule(spreadsheet, "Sheet1", range, period, "#C6EFCE")
+    end
+
+    assert [yesterday, last_7_days, this_month] =
+             ConditionalFormatting.get_time_period_rules(spreadsheet, "Sheet1")
+
+    assert %{rule_type: :time_period, time_period: :yesterday, formula: "FLOOR(D2,1)=TODAY()-1"} =
+             yesterday
+
+    assert last_7_days.time_period == :last_7_days
+    assert this_month.formula == "AND(MONTH(F2)=MONTH(TODAY()),YEAR(F2)=YEAR(TODAY()))"
+
+    assert {:error, "Invalid time period 'last_year'"} =
+             UmyaSpreadsheet.add_time_period_rule(
+               spreadsheet,
+               "Sheet1",
+               "D2:D20",
+               :last_year,
+               "#C6EFCE"
+             )
+  end
+
+  test "are returned with the other rules of the sheet", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "A1:A10", "#FFC7CE")
+    :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B1:B10", "#FFEB9C")
+    :ok = UmyaSpreadsheet.add_errors_rule(spreadsheet, "Sheet1", "C1:C10", "#FF0000")
+    :ok = UmyaSpreadsheet.add_time_period_rule(spreadsheet, "Sheet1", "D1:D10", :today, "#C6EFCE")
+
+    rules = ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1")
+
+    assert Enum.map(rules, & &1.rule_type) == [
+             :duplicate_values,
+             :contains_blanks,
+             :contains_errors,
+             :time_period
+           ]
+
+    assert %{time_period: :today} = List.last(rules)
+
+    assert [%{rule_type: :contains_blanks}] =
+             ConditionalFormatting.get_conditional_formatting_rules(
+               spreadsheet,
+               "Sheet1",
+               "B1:B10"
+             )
+  end
+
+  test "keeps the rules when written and read back", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_unique_values_rule(spreadsheet, "Sheet1", "A1:A10", "#C6EFCE")
+    :ok = UmyaSpreadsheet.add_no_blanks_rule(spreadsheet, "Sheet1", "B1:B10", "#FFEB9C")
+    :ok = UmyaSpreadsheet.add_no_errors_rule(spreadsheet, "Sheet1", "C1:C10", "#FF0000")
+
+    :ok =
+      UmyaSpreadsheet.add_time_period_rule(spreadsheet, "Sheet1", "D1:D10", :next_week, "#C6EFCE")
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert [%{rule_type: :unique_values, format_style: "FFC6EFCE"}] =
+             ConditionalFormatting.get_duplicate_values_rules(read_back, "Sheet1")
+
+    assert [%{rule_type: :not_contains_blanks, formula: "LEN(TRIM(B1))>0"}] =
+             ConditionalFormatting.get_blanks_rules(read_back, "Sheet1")
+
+    assert [%{rule_type: :not_contains_errors}] =
+             ConditionalFormatting.get_errors_rules(read_back, "Sheet1")
+
+    assert [%{time_period: :next_week}] =
+             ConditionalFormatting.get_time_period_rules(read_back, "Sheet1")
+  end
+
+  test "rejects invalid ranges and sheets", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid range 'B2:'"} =
+             UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B2:", "#FFEB9C")
+
+    assert {:error, "Sheet 'Missing' not found"} =
+             UmyaSpreadsheet.add_duplicate_values_rule(
+               spreadsheet,
+               "Missing",
+               "A1:A10",
+               "#FFC7CE"
+             )
+
+    assert {:error, "Sheet 'Missing' not found"} =
+             ConditionalFormatting.get_time_period_rules(spreadsheet, "Missing")
+  end
+end
@@ -0,0 +1,123 @@
+defmodule UmyaSpreadsheet.ConditionalFormattingExpressionTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/conditional_formatting_expression.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "adds an expression rule with font, fill, borders and number format", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok =
+             UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A2:F20", "=$C2>100", %{
+               font: %{bold: true, color: "#9C0006"},
+               fill: %{color: "#FFC7CE"},
+               borders: %{bottom: %{style: :thin, color: "#9C0006"}},
+               number_format: "#,##0.00"
+             })
+
+    assert [rule] = ConditionalFormatting.get_expression_rules(spreadsheet, "Sheet1")
+    assert rule.range == "A2:F20"
+    assert rule.rule_type == :expression
+    assert rule.formula == "$C2>100"
+
+    style = rule.style
+    assert style["font"]["bold"]
+    assert style["font"]["color"] == "FF9C0006"
+    assert style["font"]["name"] == nil
+    assert style["font"]["size"] == nil
+    assert style["fill"]["pattern"] == "solid"
+    assert style["fill"]["color"] == "FFFFC7CE"
+    assert style["borders"]["bottom"] == %{"style" => "thin", "color" => "FF9C0006"}
+    assert style["number_format"] == "#,##0.00"
+  end
+
+  test "leaves out the parts a rule does not set", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_expression_rule(
+               spreadsheet,
+               "Sheet1",
+               "A1:A10",
+               "ISBLANK(A1)",
+               %{fill: %{color: "#FFEB9C"}}
+             )
+
+    assert [%{style: style}] = ConditionalFormatting.get_expression_rules(spreadsheet, "Sheet1")
+    assert style["font"] == nil
+    assert style["borders"] == nil
+    assert style["number_format"] == nil
+  end
+
+  test "is returned with the other rules of the sheet", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_text_rule(spreadsheet, "Sheet1", "B1:B10", "contains", "x", "#FF0000")
+
+    :ok =
+      UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A1:A10 C1:C10", "A1>5", %{
+        font: %{italic: true}
+      })
+
+    rules = ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1")
+    assert Enum.any?(rules, &(&1.rule_type == :text_rule))
+
+    assert %{formula: "A1>5", range: "A1:A10 C1:C10", style: style} =
+             Enum.find(rules, &(&1.rule_type == :expression))
+
+    assert style["font"]["italic"]
+
+    assert [_] =
+             ConditionalFormatting.get_expression_rules(spreadsheet, "Sheet1", "A1:A10 C1:C10")
+
+    assert [] = ConditionalFormatting.get_expression_rules(spreadsheet, "Sheet1", "B1:B10")
+  end
+
+  test "keeps the formatting when written and read back", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A1:A10", "A1>0.5", %{
+        font: %{bold: true},
+        fill: %{color: "#C6EFCE"},
+        number_format: "0.0%"
+      })
+
+    :ok =
+      UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "B1:B10", "B1<0", %{
+        font: %{bold: true},
+        fill: %{color: "#C6EFCE"},
+        number_format: "#,##0.0"
+      })
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert [first, second] = ConditionalFormatting.get_expression_rules(read_back, "Sheet1")
+    assert first.formula == "A1>0.5"
+    assert first.style["number_format"] == "0.0%"
+    assert first.style["font"]["bold"]
+    assert first.style["fill"]["color"] == "FFC6EFCE"
+    assert second.style["number_format"] == "#,##0.0"
+  end
+
+  test "rejects invalid rules", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unknown style key 'alignment'"} =
+             UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A1:A10", "A1>1", %{
+               alignment: %{horizontal: :center}
+             })
+
+    assert {:error, "Formula cannot be empty"} =
+             UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A1:A10", "=", %{})
+
+    assert {:error, "Invalid range 'A1:'"} =
+             UmyaSpreadsheet.add_expression_rule(spreadsheet, "Sheet1", "A1:", "A1>1", %{})
+
+    assert {:error, "Sheet 'Missing' not found"} =
+             UmyaSpreadsheet.add_expression_rule(spreadsheet, "Missing", "A1:A10", "A1>1", %{})
+
+    assert [] = ConditionalFormatting.get_expression_rules(spreadsheet, "Sheet1")
+  end
+end
@@ -0,0 +1,219 @@
+defmodule UmyaSpreadsheet.ConditionalFormattingExtensionTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/conditional_formatting_extension.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+
+    for row <- 1..10 do
+      UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A#{row}", "#{row - 5}")
+    end
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "data bars default to Excel's gradient bars", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A10", nil, nil, "#638EC6")
+
+    assert {:ok, [bar]} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+
+    assert bar.options == %{
+             "gradient" => true,
+             "border_color" => nil,
+             "negative_fill_color" => nil,
+             "negative_border_color" => nil,
+             "axis_position" => "automatic",
+             "axis_color" => nil,
+             "direction" => "context"
+           }
+  end
+
+  test "keeps data bar options when written and read back", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_data_bar(
+               spreadsheet,
+               "Sheet1",
+               "A1:A10",
+               nil,
+               nil,
+               "#638EC6",
+               gradient: false,
+               border_color: "#1F4E79",
+               negative_fill_color: "#FF0000",
+               negative_border_color: "#C00000",
+               axis_position: :middle,
+               axis_color: "#000000",
+               direction: :right_to_left
+             )
+
+    expected = %{
+      "gradient" => false,
+      "border_color" => "FF1F4E79",
+      "negative_fill_color" => "FFFF0000",
+      "negative_border_color" => "FFC00000",
+      "axis_position" => "middle",
+      "axis_color" => "FF000000",
+      "direction" => "right_to_left"
+    }
+
+    assert {:ok, [bar]} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+    assert bar.options == expected
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    assert {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert {:ok, [bar]} = ConditionalFormatting.get_data_bars(read_back, "Sheet1")
+    assert bar.color == "FF638EC6"
+    assert bar.options == expected
+  end
+
+  test "keeps icon styles and options when written and read back", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok =
+             UmyaSpreadsheet.add_icon_set(
+               spreadsheet,
+               "Sheet1",
+               "A1:A10",
+               "4_rating",
+               [{"percent", "25"}, {"percent", "50"}, {"percent", "75"}],
+               show_value: false,
+               reverse: true
+             )
+
+    assert :ok =
+             UmyaSpreadsheet.add_icon_set(
+               spreadsheet,
+               "Sheet1",
+               "B1:B10",
+               "3Stars",
+               [{"percent", "0"}, {"percent", "33"}, {"percent", "67"}]
+             )
+
+    assert :ok =
+             UmyaSpreadsheet.add_icon_set(
+               spreadsheet,
+               "Sheet1",
+               "C1:C10",
+               "3_traffic_lights",
+               [{"number", "0"}, {"number", "5"}],
+               icons: [{"3Flags", 0}, {:NoIcons, 0}, {"3_symbols", 2}]
+             )
+
+    path = String.replace(@output_path, ".xlsx", "_icons.xlsx")
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, path)
+    assert {:ok, read_back} = UmyaSpreadsheet.read_file(path)
+
+    assert {:ok, icon_sets} = ConditionalFormatting.get_icon_sets(read_back, "Sheet1")
+    icon_sets = Map.new(icon_sets, &{&1.range, &1})
+
+    rating = icon_sets["A1:A10"]
+    assert rating.icon_style == "4Rating"
+    assert length(rating.thresholds) == 4
+    assert rating.options == %{"show_value" => false, "reverse" => true, "icons" => []}
+
+    assert icon_sets["B1:B10"].icon_style == "3Stars"
+
+    custom = icon_sets["C1:C10"]
+    assert custom.icon_style == "3TrafficLights1"
+
+    assert custom.thresholds == [
+             {"percent", "0"},
+             {"number", "0"},
+             {"number", "5"}
+           ]
+
+    assert custom.options["icons"] == [{"3Flags", 0}, {"NoIcons", 0}, {"3Symbols", 2}]
+  end
+
+  test "keeps the options of a rule whose priority changes", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "A1:A10", "#FFEB9C")
+
+    :ok =
+      UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A10", nil, nil, "#638EC6",
+        gradient: false
+      )
+
+    assert :ok = UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 2, 1)
+
+    assert {:ok, [bar]} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+    assert bar.options["gradient"] == false
+
+    [data_bar_rule | _] =
+      spreadsheet
+      |> ConditionalFormatting.get_conditional_formatting_rules("Sheet1")
+      |> Enum.sort_by(& &1.priority)
+
+    assert data_bar_rule.rule_type == :data_bar
+    assert data_bar_rule.options["gradient"] == false
+
+    # A removed rule takes its options along
+    assert :ok = UmyaSpreadsheet.remove_conditional_formatting_rule(spreadsheet, "Sheet1", 1)
+
+    :ok = UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "B1:B10", nil, nil, "#638EC6")
+    assert {:ok, [bar]} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+    assert bar.options["gradient"] == true
+  end
+
+  test "copies the options along with a sheet", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_icon_set(
+        spreadsheet,
+        "Sheet1",
+        "A1:A10",
+        "5Boxes",
+        [{"percent", "20"}, {"percent", "40"}, {"percent", "60"}, {"percent", "80"}]
+      )
+
+    assert :ok = UmyaSpreadsheet.clone_sheet(spreadsheet, "Sheet1", "Copy")
+    assert {:ok, [icon_set]} = ConditionalFormatting.get_icon_sets(spreadsheet, "Copy")
+    assert icon_set.icon_style == "5Boxes"
+  end
+
+  test "rejects invalid options", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unknown data bar option 'shadow'"} =
+             UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A10", nil, nil, "#638EC6",
+               shadow: true
+             )
+
+    assert {:error, "Invalid axis position 'left'"} =
+             UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A10", nil, nil, "#638EC6",
+               axis_position: :left
+             )
+
+    assert {:error, "Invalid color 'bluish'"} =
+             UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A10", nil, nil, "#638EC6",
+               border_color: "bluish"
+             )
+
+    assert {:error, "Invalid icon style '3Hearts'"} =
+             UmyaSpreadsheet.add_icon_set(spreadsheet, "Sheet1", "A1:A10", "3Hearts", [
+               {"percent", "33"},
+               {"percent", "67"}
+             ])
+
+    assert {:error, "Icon style '5Arrows' takes 4 or 5 thresholds"} =
+             UmyaSpreadsheet.add_icon_set(spreadsheet, "Sheet1", "A1:A10", "5_arrows", [
+               {"percent", "33"},
+               {"percent", "67"}
+             ])
+
+    assert {:error, "Icon index 3 is out of range for icon style '3Flags'"} =
+             UmyaSpreadsheet.add_icon_set(
+               spreadsheet,
+               "Sheet1",
+               "A1:A10",
+               "3_flags",
+               [{"percent", "33"}, {"percent", "67"}],
+               icons: [{"3Flags", 0}, {"3Flags", 1}, {"3Flags", 3}]
+             )
+
+    assert {:ok, []} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+    assert {:ok, []} = ConditionalFormatting.get_icon_sets(spreadsheet, "Sheet1")
+  end
+end
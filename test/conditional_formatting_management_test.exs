@@ -0,0 +1,134 @@
+defmodule UmyaSpreadsheet.ConditionalFormattingManagementTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/conditional_formatting_management.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+
+    :ok = UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "A1:A10", "#FFC7CE")
+    :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B1:B10", "#FFEB9C")
+
+    :ok =
+      UmyaSpreadsheet.add_text_rule(spreadsheet, "Sheet1", "C1:C10", "contains", "x", "#FF0000")
+
+    :ok = UmyaSpreadsheet.add_errors_rule(spreadsheet, "Sheet1", "B1:B10", "#FF0000")
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  defp rules(spreadsheet) do
+    spreadsheet
+    |> ConditionalFormatting.get_conditional_formatting_rules("Sheet1")
+    |> Enum.sort_by(& &1.priority)
+    |> Enum.map(&{&1.priority, &1.rule_type, &1.range})
+  end
+
+  test "gives every added rule its own priority", %{spreadsheet: spreadsheet} do
+    assert rules(spreadsheet) == [
+             {1, :duplicate_values, "A1:A10"},
+             {2, :contains_blanks, "B1:B10"},
+             {3, :text_rule, "C1:C10"},
+             {4, :contains_errors, "B1:B10"}
+           ]
+
+    assert Enum.all?(
+             ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1"),
+             &(&1.stop_if_true == false)
+           )
+  end
+
+  test "removes rules by range and by priority", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.remove_conditional_formatting(spreadsheet, "Sheet1", "B1:B10")
+    assert [{1, :duplicate_values, _}, {3, :text_rule, _}] = rules(spreadsheet)
+
+    assert :ok = UmyaSpreadsheet.remove_conditional_formatting_rule(spreadsheet, "Sheet1", 3)
+    assert [{1, :duplicate_values, "A1:A10"}] = rules(spreadsheet)
+
+    assert :ok = UmyaSpreadsheet.remove_conditional_formatting(spreadsheet, "Sheet1", "D1:D10")
+    assert [_] = rules(spreadsheet)
+  end
+
+  test "moves a rule and renumbers the others", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 3, 1)
+
+    assert Enum.map(rules(spreadsheet), &elem(&1, 1)) == [
+             :text_rule,
+             :duplicate_values,
+             :contains_blanks,
+             :contains_errors
+           ]
+
+    assert :ok = UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 1, 10)
+    assert {4, :text_rule, "C1:C10"} = List.last(rules(spreadsheet))
+  end
+
+  test "sets stop if true and keeps it when written and read back", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok =
+             UmyaSpreadsheet.set_conditional_formatting_stop_if_true(
+               spreadsheet,
+               "Sheet1",
+               2,
+               true
+             )
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    stops =
+      read_back
+      |> ConditionalFormatting.get_conditional_formatting_rules("Sheet1")
+      |> Enum.filter(& &1.stop_if_true)
+
+    assert [%{priority: 2, rule_type: :contains_blanks}] = stops
+    assert length(rules(read_back)) == 4
+  end
+
+  test "applies a rule to another range", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.set_conditional_formatting_range(
+               spreadsheet,
+               "Sheet1",
+               1,
+               "$A$1:$A$200 E1:E5"
+             )
+
+    assert {1, :duplicate_values, "A1:A200 E1:E5"} = hd(rules(spreadsheet))
+
+    assert [%{range: "A1:A200 E1:E5"}] =
+             ConditionalFormatting.get_duplicate_values_rules(
+               spreadsheet,
+               "Sheet1",
+               "A1:A200 E1:E5"
+             )
+  end
+
+  test "clears all rules of a sheet", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.clear_conditional_formatting(spreadsheet, "Sheet1")
+    assert [] = rules(spreadsheet)
+
+    :ok = UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B1:B10", "#FFEB9C")
+    assert [{1, :contains_blanks, "B1:B10"}] = rules(spreadsheet)
+  end
+
+  test "rejects unknown rules and invalid arguments", %{spreadsheet: spreadsheet} do
+    assert {:error, "Conditional formatting rule with priority 9 not found"} =
+             UmyaSpreadsheet.remove_conditional_formatting_rule(spreadsheet, "Sheet1", 9)
+
+    assert {:error, "Priority must be at least 1"} =
+             UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 1, 0)
+
+    assert {:error, "Invalid range 'A1:'"} =
+             UmyaSpreadsheet.set_conditional_formatting_range(spreadsheet, "Sheet1", 1, "A1:")
+
+    assert {:error, "Sheet 'Missing' not found"} =
+             UmyaSpreadsheet.clear_conditional_formatting(spreadsheet, "Missing")
+
+    assert length(rules(spreadsheet)) == 4
+  end
+end
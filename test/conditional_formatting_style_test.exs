@@ -0,0 +1,148 @@
+defmodule UmyaSpreadsheet.ConditionalFormattingStyleTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/conditional_formatting_style.xlsx"
+
+  @style %{
+    font: %{bold: true, italic: true, color: "#9C0006"},
+    fill: %{pattern: "gray125", color: "#FFC7CE"},
+    borders: %{all: %{style: :thin, color: "#9C0006"}},
+    number_format: "#,##0.00"
+  }
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "takes a style map for cell value rules", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_cell_value_rule(
+               spreadsheet,
+               "Sheet1",
+               "A1:A10",
+               "lessThan",
+               "0",
+               nil,
+               @style
+             )
+
+    assert [rule] = ConditionalFormatting.get_cell_value_rules(spreadsheet, "Sheet1")
+    assert rule.format_style == "FFFFC7CE"
+
+    style = rule.style
+    assert style["font"]["bold"]
+    assert style["font"]["italic"]
+    assert style["font"]["color"] == "FF9C0006"
+    assert style["fill"]["pattern"] == "gray125"
+    assert style["borders"]["left"] == %{"style" => "thin", "color" => "FF9C0006"}
+    assert style["borders"]["bottom"] == %{"style" => "thin", "color" => "FF9C0006"}
+    assert style["number_format"] == "#,##0.00"
+  end
+
+  test "takes a style map for every rule type with a format", %{spreadsheet: spreadsheet} do
+    font_only = %{font: %{bold: true, color: "#006100"}}
+
+    :ok =
+      UmyaSpreadsheet.add_top_bottom_rule(
+        spreadsheet,
+        "Sheet1",
+        "A1:A10",
+        "top",
+        3,
+        false,
+        font_only
+      )
+
+    :ok =
+      UmyaSpreadsheet.add_text_rule(spreadsheet, "Sheet1", "B1:B10", "contains", "x", font_only)
+
+    :ok =
+      UmyaSpreadsheet.add_above_below_average_rule(
+        spreadsheet,
+        "Sheet1",
+        "C1:C10",
+        "above",
+        nil,
+        font_only
+      )
+
+    :ok = UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "D1:D10", font_only)
+    :ok = UmyaSpreadsheet.add_no_blanks_rule(spreadsheet, "Sheet1", "E1:E10", font_only)
+    :ok = UmyaSpreadsheet.add_errors_rule(spreadsheet, "Sheet1", "F1:F10", font_only)
+    :ok = UmyaSpreadsheet.add_time_period_rule(spreadsheet, "Sheet1", "G1:G10", :today, font_only)
+
+    rules = ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1")
+    assert length(rules) == 7
+
+    for rule <- rules do
+      assert rule.style["font"]["bold"]
+      assert rule.style["font"]["color"] == "FF006100"
+      assert rule.style["fill"] == nil
+      assert rule.format_style == ""
+    end
+
+    assert [%{style: %{"font" => %{"bold" => true}}}] =
+             ConditionalFormatting.get_top_bottom_rules(spreadsheet, "Sheet1")
+
+    assert [%{style: %{"font" => %{"bold" => true}}}] =
+             ConditionalFormatting.get_text_rules(spreadsheet, "Sheet1")
+  end
+
+  test "still takes a color that fills the cells", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_text_rule(spreadsheet, "Sheet1", "A1:A10", "contains", "x", "#FF0000")
+
+    assert [rule] = ConditionalFormatting.get_text_rules(spreadsheet, "Sheet1")
+    assert rule.format_style == "FFFF0000"
+    assert rule.style["fill"]["pattern"] == "solid"
+    assert rule.style["fill"]["color"] == "FFFF0000"
+    assert rule.style["font"] == nil
+  end
+
+  test "keeps the formatting when written and read back", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_cell_value_rule(
+        spreadsheet,
+        "Sheet1",
+        "A1:A10",
+        "greaterThan",
+        "100",
+        nil,
+        @style
+      )
+
+    :ok =
+      UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "B1:B10", %{
+        fill: %{color: "#FFEB9C"},
+        number_format: "0.0%"
+      })
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert [cell_value] = ConditionalFormatting.get_cell_value_rules(read_back, "Sheet1")
+    assert cell_value.style["font"]["italic"]
+    assert cell_value.style["borders"]["top"]["style"] == "thin"
+    assert cell_value.style["number_format"] == "#,##0.00"
+
+    assert [blanks] = ConditionalFormatting.get_blanks_rules(read_back, "Sheet1")
+    assert blanks.format_style == "FFFFEB9C"
+    assert blanks.style["number_format"] == "0.0%"
+  end
+
+  test "rejects invalid styles", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unknown style key 'alignment'"} =
+             UmyaSpreadsheet.add_duplicate_values_rule(spreadsheet, "Sheet1", "A1:A10", %{
+               alignment: %{horizontal: :center}
+             })
+
+    assert {:error, "Format style must be a color or a style map"} =
+             UmyaSpreadsheet.add_blanks_rule(spreadsheet, "Sheet1", "A1:A10", 42)
+
+    assert [] = ConditionalFormatting.get_conditional_formatting_rules(spreadsheet, "Sheet1")
+  end
+end
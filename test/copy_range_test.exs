@@ -0,0 +1,118 @@
+defmodule UmyaSpreadsheet.CopyRangeTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.ConditionalFormatting
+
+  @output_path "test/result_files/copy_range_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "10")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A2", "20")
+    :ok = UmyaSpreadsheet.set_formula(spreadsheet, "Sheet1", "B1", "A1*2+$A$2")
+    :ok = UmyaSpreadsheet.set_font_bold(spreadsheet, "Sheet1", "A1", true)
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "copies everything within a sheet and moves relative references",
+       %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A3:B3")
+
+    assert :ok =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1:B3", spreadsheet, "Sheet1", "D5")
+
+    assert {:ok, "10"} = UmyaSpreadsheet.get_cell_value(spreadsheet, "Sheet1", "D5")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Sheet1", "D5")
+    assert "D5*2+$A$2" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "E5")
+    assert {:ok, merges} = UmyaSpreadsheet.get_merge_cells(spreadsheet, "Sheet1")
+    assert "D7:E7" in merges
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+  end
+
+  test "pastes values only", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Archive")
+
+    assert :ok =
+             UmyaSpreadsheet.copy_range(
+               spreadsheet,
+               "Sheet1",
+               "A1:A2",
+               spreadsheet,
+               "Archive",
+               "C1",
+               :values
+             )
+
+    assert {:ok, "20"} = UmyaSpreadsheet.get_cell_value(spreadsheet, "Archive", "C2")
+    assert {:ok, false} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Archive", "C1")
+  end
+
+  test "pastes formats into another spreadsheet", %{spreadsheet: spreadsheet} do
+    {:ok, other} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.set_cell_value(other, "Sheet1", "A1", "kept")
+
+    assert :ok =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1", other, "Sheet1", "A1", :formats)
+
+    assert {:ok, "kept"} = UmyaSpreadsheet.get_cell_value(other, "Sheet1", "A1")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(other, "Sheet1", "A1")
+  end
+
+  test "pasted formulas keep the target styles", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_font_italic(spreadsheet, "Sheet1", "B2", true)
+
+    assert :ok =
+             UmyaSpreadsheet.copy_range(
+               spreadsheet,
+               "Sheet1",
+               "B1",
+               spreadsheet,
+               "Sheet1",
+               "B2",
+               :formulas
+             )
+
+    assert "A2*2+$A$2" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "B2")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "B2")
+  end
+
+  test "pasted conditional formats get their own priorities and keep their options",
+       %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_data_bar(spreadsheet, "Sheet1", "A1:A2", nil, nil, "#638EC6",
+        gradient: false
+      )
+
+    assert :ok =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1:A2", spreadsheet, "Sheet1", "D1")
+
+    assert {:ok, bars} = ConditionalFormatting.get_data_bars(spreadsheet, "Sheet1")
+
+    assert Enum.map(bars, &{&1.range, &1.options["gradient"]}) |> Enum.sort() ==
+             [{"A1:A2", false}, {"D1:D2", false}]
+
+    priorities =
+      spreadsheet
+      |> ConditionalFormatting.get_conditional_formatting_rules("Sheet1")
+      |> Enum.map(& &1.priority)
+      |> Enum.sort()
+
+    assert priorities == [1, 2]
+    assert :ok = UmyaSpreadsheet.set_conditional_formatting_priority(spreadsheet, "Sheet1", 2, 1)
+  end
+
+  test "reports invalid arguments", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid paste mode 'links', expected all, values, formulas or formats"} =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1", spreadsheet, "Sheet1", "C1", :links)
+
+    assert {:error, "Source sheet not found"} =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Nope", "A1", spreadsheet, "Sheet1", "C1")
+
+    assert {:error, "Target sheet not found"} =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1", spreadsheet, "Nope", "C1")
+
+    assert {:error, "Target range does not fit on the sheet"} =
+             UmyaSpreadsheet.copy_range(spreadsheet, "Sheet1", "A1:B2", spreadsheet, "Sheet1", "XFD1")
+  end
+end
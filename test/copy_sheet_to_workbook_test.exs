@@ -0,0 +1,103 @@
+defmodule UmyaSpreadsheet.CopySheetToWorkbookTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/copy_sheet_to_workbook_output.xlsx"
+
+  setup do
+    {:ok, source} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(source, "Data")
+    :ok = UmyaSpreadsheet.set_cell_value(source, "Data", "A1", "Qty")
+    :ok = UmyaSpreadsheet.set_cell_value(source, "Data", "A2", "5")
+    :ok = UmyaSpreadsheet.set_cell_value(source, "Data", "A3", "7")
+    :ok = UmyaSpreadsheet.set_formula(source, "Data", "C1", "SUM(Data!A2:A3)")
+    :ok = UmyaSpreadsheet.set_font_bold(source, "Data", "A1", true)
+    :ok = UmyaSpreadsheet.add_merge_cells(source, "Data", "D1:E1")
+    :ok = UmyaSpreadsheet.add_comment(source, "Data", "A1", "Units sold", "Reporting")
+
+    {:ok, target} = UmyaSpreadsheet.new()
+    %{source: source, target: target}
+  end
+
+  test "copies cells, styles, merges and comments into another spreadsheet", %{
+    source: source,
+    target: target
+  } do
+    assert :ok = UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", target, "Report")
+
+    assert ["Sheet1", "Report"] = UmyaSpreadsheet.get_sheet_names(target)
+    assert {:ok, "5"} = UmyaSpreadsheet.get_cell_value(target, "Report", "A2")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(target, "Report", "A1")
+    assert "SUM(Report!A2:A3)" = UmyaSpreadsheet.get_formula(target, "Report", "C1")
+    assert {:ok, merges} = UmyaSpreadsheet.get_merge_cells(target, "Report")
+    assert "D1:E1" in merges
+    assert {:ok, "Units sold", "Reporting"} = UmyaSpreadsheet.get_comment(target, "Report", "A1")
+
+    # The source sheet is left as it was
+    assert "SUM(Data!A2:A3)" = UmyaSpreadsheet.get_formula(source, "Data", "C1")
+
+    assert :ok = UmyaSpreadsheet.write(target, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+    assert {:ok, "7"} = UmyaSpreadsheet.get_cell_value(loaded, "Report", "A3")
+    assert {:ok, "Units sold", "Reporting"} = UmyaSpreadsheet.get_comment(loaded, "Report", "A1")
+  end
+
+  test "renames tables whose name is taken in the target", %{source: source, target: target} do
+    {:ok, :ok} =
+      UmyaSpreadsheet.add_table(source, "Data", "Sales", "Sales", "A1", "A3", ["Qty"])
+
+    {:ok, :ok} =
+      UmyaSpreadsheet.add_table(target, "Sheet1", "Sales", "Sales", "A1", "B5", ["Item", "Qty"])
+
+    :ok = UmyaSpreadsheet.set_formula(source, "Data", "C2", "SUM(Sales[Qty])")
+
+    assert :ok = UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", target, "Data")
+
+    assert {:ok, [table]} = UmyaSpreadsheet.get_tables(target, "Data")
+    assert table["name"] == "Sales_2"
+    assert "SUM(Sales_2[Qty])" = UmyaSpreadsheet.get_formula(target, "Data", "C2")
+    assert {:ok, [%{"name" => "Sales"}]} = UmyaSpreadsheet.get_tables(target, "Sheet1")
+  end
+
+  test "carries over names scoped to the sheet", %{source: source, target: target} do
+    :ok = UmyaSpreadsheet.add_defined_name(source, "TaxRate", "0.15")
+
+    :ok =
+      UmyaSpreadsheet.add_defined_name(source, "Units", "Data!$A$2:$A$3", "Data", false, "Sold")
+
+    assert :ok = UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", target, "North")
+
+    {:ok, units} = UmyaSpreadsheet.get_defined_name(target, "Units", "North")
+    assert units["refers_to"] == "North!$A$2:$A$3"
+    assert units["local_sheet_id"] == 1
+    assert units["comment"] == "Sold"
+
+    # Workbook-scoped names stay with the source
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name(target, "TaxRate")
+  end
+
+  test "adds the named styles the sheet uses", %{source: source, target: target} do
+    :ok = UmyaSpreadsheet.add_named_style(source, "Total", %{font: %{italic: true}})
+    :ok = UmyaSpreadsheet.apply_named_style(source, "Data", "C1", "Total")
+
+    assert :ok = UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", target, "Data")
+
+    assert {:ok, styles} = UmyaSpreadsheet.list_named_styles(target)
+    assert "Total" in Enum.map(styles, & &1["name"])
+    assert {:ok, true} = UmyaSpreadsheet.get_font_italic(target, "Data", "C1")
+  end
+
+  test "copies a sheet within the same spreadsheet", %{source: source} do
+    assert :ok = UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", source, "Data Copy")
+
+    assert {:ok, "7"} = UmyaSpreadsheet.get_cell_value(source, "Data Copy", "A3")
+    assert "SUM('Data Copy'!A2:A3)" = UmyaSpreadsheet.get_formula(source, "Data Copy", "C1")
+  end
+
+  test "rejects missing sheets and taken names", %{source: source, target: target} do
+    assert {:error, "Source sheet not found"} =
+             UmyaSpreadsheet.copy_sheet_to_workbook(source, "Missing", target, "Copy")
+
+    assert {:error, "Target sheet already exists"} =
+             UmyaSpreadsheet.copy_sheet_to_workbook(source, "Data", target, "Sheet1")
+  end
+end
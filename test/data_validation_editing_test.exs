@@ -0,0 +1,205 @@
+defmodule UmyaSpreadsheet.DataValidationEditingTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.DataValidation
+
+  @output_path "test/result_files/data_validation_editing.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  defp ranges(spreadsheet) do
+    {:ok, validations} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    Map.new(validations, &{&1.rule_type, &1.range})
+  end
+
+  test "adding over an existing validation splits its range", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "A1:C5", ["Yes", "No"])
+    :ok = UmyaSpreadsheet.add_number_validation(spreadsheet, "Sheet1", "B2", "greater_than", 0.0)
+
+    assert ranges(spreadsheet) == %{list: "A1:C1 A2 C2 A3:C5", decimal: "B2"}
+
+    # The same range replaces the validation
+    :ok = UmyaSpreadsheet.add_number_validation(spreadsheet, "Sheet1", "B2", "less_than", 9.0)
+    assert {:ok, 2} = DataValidation.count_data_validations(spreadsheet, "Sheet1")
+  end
+
+  test "relative formulas follow the first cell", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_custom_validation(spreadsheet, "Sheet1", "E1:E5", "MOD(E1,2)=0")
+    assert :ok = UmyaSpreadsheet.remove_data_validation(spreadsheet, "Sheet1", "E1")
+
+    assert {:ok, [rule]} = DataValidation.get_custom_validations(spreadsheet, "Sheet1")
+    assert rule.range == "E2:E5"
+    assert rule.formula == "MOD(E2,2)=0"
+  end
+
+  test "removes only the part inside the range", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "A1:A10", ["Yes", "No"])
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "C1:C3", ["Yes", "No"])
+
+    assert :ok = UmyaSpreadsheet.remove_data_validation(spreadsheet, "Sheet1", "A4:C6")
+    assert {:ok, validations} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    assert Enum.map(validations, & &1.range) == ["A1:A3 A7:A10", "C1:C3"]
+
+    assert :ok = UmyaSpreadsheet.remove_data_validation(spreadsheet, "Sheet1", "A:C")
+    assert {:ok, false} = DataValidation.has_data_validations(spreadsheet, "Sheet1")
+  end
+
+  test "finds the validation of a cell", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "A1:A5 C1", ["Yes", "No"])
+
+    assert {:ok, %{rule_type: :list, range: "A1:A5 C1"}} =
+             DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "C1")
+
+    assert {:ok, nil} = DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "B1")
+
+    assert {:error, "Invalid cell 'A1:A2'"} =
+             DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "A1:A2")
+  end
+
+  test "updates a validation in place", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.add_number_validation(
+        spreadsheet,
+        "Sheet1",
+        "B1:B5",
+        "between",
+        1.0,
+        10.0,
+        true,
+        "Invalid",
+        "Enter 1 to 10",
+        nil,
+        nil
+      )
+
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "B6:B10", ["Yes", "No"])
+
+    assert :ok =
+             UmyaSpreadsheet.update_data_validation(spreadsheet, "Sheet1", "B3",
+               range: "B1:B8",
+               formula2: "100",
+               error_message: "Enter 1 to 100",
+               allow_blank: false
+             )
+
+    assert {:ok, rule} = DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "B7")
+    assert rule.rule_type == :decimal
+    assert rule.range == "B1:B8"
+    assert rule.formula2 == "100"
+    assert rule.error_title == "Invalid"
+    assert rule.error_message == "Enter 1 to 100"
+    assert rule.allow_blank == false
+
+    assert {:ok, %{range: "B9:B10"}} =
+             DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "B9")
+
+    assert :ok =
+             UmyaSpreadsheet.update_data_validation(spreadsheet, "Sheet1", "B1",
+               error_message: nil
+             )
+
+    assert {:ok, rule} = DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "B1")
+    refute Map.has_key?(rule, :error_message)
+
+    assert {:error, "No data validation applies to cell 'D1'"} =
+             UmyaSpreadsheet.update_data_validation(spreadsheet, "Sheet1", "D1", formula1: "1")
+
+    assert {:error, "Unknown data validation option 'color'"} =
+             UmyaSpreadsheet.update_data_validation(spreadsheet, "Sheet1", "B1", color: "red")
+  end
+
+  test "writes items with commas, quotes and long lists", %{spreadsheet: spreadsheet} do
+    quoted = ["Say \"hi\"", "Bye"]
+    with_commas = ["Smith, John", "Doe, Jane"]
+    long = Enum.map(1..60, &"Option #{&1}")
+
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "A1:A5", quoted)
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "B1:B5", with_commas)
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "C1:C5", long)
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    assert {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert {:ok, rules} = DataValidation.get_list_validations(read_back, "Sheet1")
+    items = Map.new(rules, &{&1.range, &1.list_items})
+    assert items["A1:A5"] == quoted
+    assert items["B1:B5"] == with_commas
+    assert items["C1:C5"] == long
+
+    assert "_ValidationLists" in UmyaSpreadsheet.get_sheet_names(read_back)
+    assert {:ok, "hidden"} = UmyaSpreadsheet.get_sheet_state(read_back, "_ValidationLists")
+  end
+
+  test "takes list items from ranges and defined names", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "My Lists")
+    :ok = UmyaSpreadsheet.create_defined_name(spreadsheet, "Fruit", "'My Lists'!$A$1:$A$3")
+
+    assert :ok =
+             UmyaSpreadsheet.add_list_validation_from_range(
+               spreadsheet,
+               "Sheet1",
+               "A2:A20",
+               "'My Lists'!b1:b3",
+               error_message: "Pick a category"
+             )
+
+    assert :ok =
+             UmyaSpreadsheet.add_list_validation_from_range(spreadsheet, "Sheet1", "C2", "D:D")
+
+    assert :ok =
+             UmyaSpreadsheet.add_list_validation_from_defined_name(
+               spreadsheet,
+               "Sheet1",
+               "E2:E20",
+               "Fruit"
+             )
+
+    assert :ok =
+             UmyaSpreadsheet.add_dependent_list_validation(spreadsheet, "Sheet1", "B2:B20", "A2")
+
+    assert {:ok, rules} = DataValidation.get_list_validations(spreadsheet, "Sheet1")
+    formulas = Map.new(rules, &{&1.range, &1.formula1})
+
+    assert formulas == %{
+             "A2:A20" => "'My Lists'!$B$1:$B$3",
+             "C2" => "$D:$D",
+             "E2:E20" => "Fruit",
+             "B2:B20" => "INDIRECT(A2)"
+           }
+
+    assert {:ok, %{error_message: "Pick a category", show_error_message: true}} =
+             DataValidation.get_data_validation_at(spreadsheet, "Sheet1", "A2")
+  end
+
+  test "rejects invalid list sources", %{spreadsheet: spreadsheet} do
+    assert {:error, "List source range 'A1:B5' must be a single row or column"} =
+             UmyaSpreadsheet.add_list_validation_from_range(spreadsheet, "Sheet1", "C1", "A1:B5")
+
+    assert {:error, "Sheet 'Lists' not found"} =
+             UmyaSpreadsheet.add_list_validation_from_range(
+               spreadsheet,
+               "Sheet1",
+               "C1",
+               "Lists!A1:A5"
+             )
+
+    assert {:error, "Defined name 'Missing' not found"} =
+             UmyaSpreadsheet.add_list_validation_from_defined_name(
+               spreadsheet,
+               "Sheet1",
+               "C1",
+               "Missing"
+             )
+
+    assert {:error, "Unknown data validation option 'formula1'"} =
+             UmyaSpreadsheet.add_dependent_list_validation(spreadsheet, "Sheet1", "C1", "A1",
+               formula1: "1"
+             )
+
+    assert {:ok, false} = DataValidation.has_data_validations(spreadsheet, "Sheet1")
+  end
+end
@@ -0,0 +1,154 @@
+defmodule UmyaSpreadsheet.DataValidationViolationsTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.DataValidation
+
+  @output_path "test/result_files/data_validation_violations.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  defp set_values(spreadsheet, values) do
+    for {cell, value} <- values do
+      :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", cell, value)
+    end
+  end
+
+  defp violating_cells(spreadsheet) do
+    {:ok, violations} = DataValidation.get_data_validation_violations(spreadsheet, "Sheet1")
+    Enum.map(violations, & &1.cell)
+  end
+
+  test "reports the cells that break number rules", %{spreadsheet: spreadsheet} do
+    set_values(spreadsheet, [{"A1", "5"}, {"A2", "42"}, {"A3", "abc"}, {"A4", "7.5"}])
+
+    :ok =
+      UmyaSpreadsheet.add_number_validation(
+        spreadsheet,
+        "Sheet1",
+        "A1:A10",
+        "between",
+        1.0,
+        10.0,
+        true,
+        "Invalid",
+        "Enter 1 to 10",
+        nil,
+        nil
+      )
+
+    assert {:ok, [too_big, text]} =
+             DataValidation.get_data_validation_violations(spreadsheet, "Sheet1")
+
+    assert too_big == %{
+             cell: "A2",
+             range: "A1:A10",
+             rule_type: :decimal,
+             value: "42",
+             error_message: "Enter 1 to 10"
+           }
+
+    assert %{cell: "A3", value: "abc"} = text
+  end
+
+  test "checks text length, list and date rules", %{spreadsheet: spreadsheet} do
+    set_values(spreadsheet, [
+      {"B1", "short"},
+      {"B2", "far too long"},
+      {"C1", "yes"},
+      {"C2", "Maybe"},
+      {"D1", "45300"},
+      {"D2", "40000"}
+    ])
+
+    :ok =
+      UmyaSpreadsheet.add_text_length_validation(
+        spreadsheet,
+        "Sheet1",
+        "B1:B5",
+        "less_than_or_equal",
+        5
+      )
+
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "C1:C5", ["Yes", "No"])
+
+    :ok =
+      UmyaSpreadsheet.add_date_validation(
+        spreadsheet,
+        "Sheet1",
+        "D1:D5",
+        "greater_than_or_equal",
+        "2024-01-01"
+      )
+
+    assert {:ok, violations} =
+             DataValidation.get_data_validation_violations(spreadsheet, "Sheet1")
+
+    assert Enum.map(violations, &{&1.cell, &1.rule_type, &1.error_message}) == [
+             {"B2", :textLength, nil},
+             {"C2", :list, nil},
+             {"D2", :date, nil}
+           ]
+  end
+
+  test "evaluates custom formulas and list sources per cell", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Lists")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Lists", "A1", "Apple")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Lists", "A2", "Pear")
+
+    set_values(spreadsheet, [
+      {"E1", "4"},
+      {"E2", "7"},
+      {"F1", "Pear"},
+      {"F2", "Kiwi"}
+    ])
+
+    :ok = UmyaSpreadsheet.add_custom_validation(spreadsheet, "Sheet1", "E1:E5", "MOD(E1,2)=0")
+
+    :ok =
+      UmyaSpreadsheet.add_list_validation_from_range(
+        spreadsheet,
+        "Sheet1",
+        "F1:F5",
+        "Lists!A1:A2"
+      )
+
+    assert violating_cells(spreadsheet) == ["E2", "F2"]
+  end
+
+  test "skips empty cells and rules it cannot evaluate", %{spreadsheet: spreadsheet} do
+    set_values(spreadsheet, [{"A1", "x"}])
+
+    :ok =
+      UmyaSpreadsheet.add_custom_validation(
+        spreadsheet,
+        "Sheet1",
+        "A1:A5",
+        "XLOOKUP(A1,B1:B5,C1:C5)=1"
+      )
+
+    :ok = UmyaSpreadsheet.add_list_validation(spreadsheet, "Sheet1", "G1:G5", ["Yes", "No"])
+
+    assert violating_cells(spreadsheet) == []
+  end
+
+  test "checks rules read back from a file", %{spreadsheet: spreadsheet} do
+    set_values(spreadsheet, [{"A1", "3"}, {"A2", "30"}])
+
+    :ok =
+      UmyaSpreadsheet.add_number_validation(spreadsheet, "Sheet1", "A1:A5", "between", 1.0, 10.0)
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    assert {:ok, read_back} = UmyaSpreadsheet.read_file(@output_path)
+
+    assert violating_cells(read_back) == ["A2"]
+  end
+
+  test "returns an error for a missing sheet", %{spreadsheet: spreadsheet} do
+    assert {:error, "Sheet 'Missing' not found"} =
+             DataValidation.get_data_validation_violations(spreadsheet, "Missing")
+  end
+end
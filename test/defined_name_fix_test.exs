@@ -88,12 +88,13 @@ defmodule UmyaSpreadsheet.DefinedNameFixTest do
     assert Enum.any?(defined_names, fn {name, _} -> name == "LOCAL_CONSTANT" end)
     assert Enum.any?(defined_names, fn {name, _} -> name == "LOCAL_FORMULA" end)
 
-    # Verify that global defined names exist (note: may have empty names due to API limitation)
-    # Check by formula content since global names might have empty name strings
-    assert Enum.any?(defined_names, fn {_, formula} -> String.contains?(formula, "10*5+2") end)
+    # Verify that global defined names exist with their names and formulas
+    assert Enum.any?(defined_names, fn {name, formula} ->
+             name == "GLOBAL_FORMULA" && String.contains?(formula, "10*5+2")
+           end)
 
-    assert Enum.any?(defined_names, fn {_, formula} ->
-             String.contains?(formula, "'TestSheet'!A1:C3")
+    assert Enum.any?(defined_names, fn {name, formula} ->
+             name == "GLOBAL_RANGE" && String.contains?(formula, "'TestSheet'!A1:C3")
            end)
 
     # Find and verify specific defined names
@@ -0,0 +1,260 @@
+defmodule UmyaSpreadsheet.DefinedNameFunctionsTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/defined_name_functions_test.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Data")
+    File.rm(@output_path)
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "add_defined_name creates workbook and sheet-scoped names", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+
+    assert :ok =
+             UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.2", "Data", true, "Local rate")
+
+    {:ok, global} = UmyaSpreadsheet.get_defined_name(spreadsheet, "TaxRate")
+    assert global["name"] == "TaxRate"
+    assert global["refers_to"] == "0.15"
+    assert global["scope"] == nil
+    assert global["local_sheet_id"] == nil
+    assert global["hidden"] == false
+    assert global["comment"] == nil
+
+    {:ok, local} = UmyaSpreadsheet.get_defined_name(spreadsheet, "taxrate", "Data")
+    assert local["refers_to"] == "0.2"
+    assert local["scope"] == "Data"
+    assert local["local_sheet_id"] == 1
+    assert local["hidden"] == true
+    assert local["comment"] == "Local rate"
+  end
+
+  test "add_defined_name rejects duplicates and invalid names", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+    assert {:error, _} = UmyaSpreadsheet.add_defined_name(spreadsheet, "TAXRATE", "0.2")
+    assert {:error, _} = UmyaSpreadsheet.add_defined_name(spreadsheet, "A1", "0.2")
+    assert {:error, _} = UmyaSpreadsheet.add_defined_name(spreadsheet, "Tax Rate", "0.2")
+    assert {:error, _} = UmyaSpreadsheet.add_defined_name(spreadsheet, "Rate", "0.2", "Missing")
+  end
+
+  test "update_defined_name changes name, target, hidden flag and comment", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15", nil, false, "Old")
+
+    assert :ok =
+             UmyaSpreadsheet.update_defined_name(spreadsheet, "TaxRate", nil, %{
+               name: "VatRate",
+               refers_to: "=0.21",
+               hidden: true
+             })
+
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name(spreadsheet, "TaxRate")
+    {:ok, updated} = UmyaSpreadsheet.get_defined_name(spreadsheet, "VatRate")
+    assert updated["refers_to"] == "0.21"
+    assert updated["hidden"] == true
+    assert updated["comment"] == "Old"
+
+    assert :ok =
+             UmyaSpreadsheet.update_defined_name(spreadsheet, "VatRate", nil, %{comment: nil})
+
+    {:ok, updated} = UmyaSpreadsheet.get_defined_name(spreadsheet, "VatRate")
+    assert updated["comment"] == nil
+  end
+
+  test "update_defined_name moves a name between scopes", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Region", "Data!$A$1")
+
+    assert :ok =
+             UmyaSpreadsheet.update_defined_name(spreadsheet, "Region", nil, %{scope: "Data"})
+
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Region")
+    {:ok, local} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Region", "Data")
+    assert local["scope"] == "Data"
+
+    assert :ok =
+             UmyaSpreadsheet.update_defined_name(spreadsheet, "Region", "Data", %{scope: nil})
+
+    {:ok, global} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Region")
+    assert global["scope"] == nil
+  end
+
+  test "remove_defined_name only removes the given scope", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Total", "100")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Total", "200", "Data")
+
+    assert :ok = UmyaSpreadsheet.remove_defined_name(spreadsheet, "Total", "Data")
+    assert {:error, _} = UmyaSpreadsheet.remove_defined_name(spreadsheet, "Total", "Data")
+
+    {:ok, names} = UmyaSpreadsheet.list_defined_names(spreadsheet)
+    assert [%{"name" => "Total", "scope" => nil}] = names
+  end
+
+  test "list_defined_names filters by scope", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Region", "Data!$A$1", "Data")
+
+    {:ok, all} = UmyaSpreadsheet.list_defined_names(spreadsheet)
+    assert length(all) == 2
+
+    {:ok, workbook} = UmyaSpreadsheet.list_defined_names(spreadsheet, :workbook)
+    assert Enum.map(workbook, & &1["name"]) == ["TaxRate"]
+
+    {:ok, sheet} = UmyaSpreadsheet.list_defined_names(spreadsheet, "Data")
+    assert Enum.map(sheet, & &1["name"]) == ["Region"]
+  end
+
+  test "resolve_defined_name returns target ranges", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_defined_name(spreadsheet, "Sales", "Data!$A$1:$B$3,Sheet1!C5")
+
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Sales", "Data!D1:D2", "Data")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+
+    assert {:ok,
+            [
+              %{"sheet_name" => "Data", "range" => "A1:B3"},
+              %{"sheet_name" => "Sheet1", "range" => "C5"}
+            ]} = UmyaSpreadsheet.resolve_defined_name(spreadsheet, "Sales")
+
+    # The sheet-scoped name wins when resolving from that sheet
+    assert {:ok, [%{"sheet_name" => "Data", "range" => "D1:D2"}]} =
+             UmyaSpreadsheet.resolve_defined_name(spreadsheet, "Sales", "Data")
+
+    assert {:error, _} = UmyaSpreadsheet.resolve_defined_name(spreadsheet, "TaxRate")
+    assert {:error, _} = UmyaSpreadsheet.resolve_defined_name(spreadsheet, "Missing")
+  end
+
+  test "get_defined_name_values reads typed values", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Data", "A2", "Widget")
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Data", "B2", "2")
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Data", "C2", "9.5")
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Data", "A3", "true")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "InvoiceLines", "Data!$A$2:$C$3")
+
+    assert {:ok, result} = UmyaSpreadsheet.get_defined_name_values(spreadsheet, "InvoiceLines")
+    assert result["sheet_name"] == "Data"
+    assert result["range"] == "A2:C3"
+    assert result["values"] == [["Widget", 2, 9.5], [true, nil, nil]]
+  end
+
+  test "get_defined_name_values prefers the sheet-scoped name", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "global")
+    assert :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Data", "A1", "local")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Input", "Sheet1!$A$1")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Input", "Data!$A$1", "Data")
+
+    assert {:ok, %{"values" => [["global"]]}} =
+             UmyaSpreadsheet.get_defined_name_values(spreadsheet, "Input")
+
+    assert {:ok, %{"values" => [["local"]]}} =
+             UmyaSpreadsheet.get_defined_name_values(spreadsheet, "Input", "Data")
+  end
+
+  test "set_defined_name_values fills the named range within its bounds", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "InvoiceLines", "Data!$B$2:$D$3")
+
+    assert :ok =
+             UmyaSpreadsheet.set_defined_name_values(spreadsheet, "InvoiceLines", [
+               ["Widget", 2, 9.5],
+               ["Gadget", false]
+             ])
+
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Data", "B2") == {:ok, "Widget"}
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Data", "D2") == {:ok, "9.5"}
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Data", "C3") == {:ok, "FALSE"}
+
+    assert {:ok, %{"values" => [["Widget", 2, 9.5], ["Gadget", false, nil]]}} =
+             UmyaSpreadsheet.get_defined_name_values(spreadsheet, "InvoiceLines")
+
+    # Too many rows or columns are rejected without writing anything
+    assert {:error, _} =
+             UmyaSpreadsheet.set_defined_name_values(spreadsheet, "InvoiceLines", [
+               ["a"],
+               ["b"],
+               ["c"]
+             ])
+
+    assert {:error, _} =
+             UmyaSpreadsheet.set_defined_name_values(spreadsheet, "InvoiceLines", [
+               [1, 2, 3, 4]
+             ])
+
+    # A value that cannot be written leaves the rows before it untouched too
+    assert {:error, _} =
+             UmyaSpreadsheet.set_defined_name_values(spreadsheet, "InvoiceLines", [
+               ["Changed"],
+               [%{}]
+             ])
+
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Data", "B2") == {:ok, "Widget"}
+  end
+
+  test "get_defined_name_values rejects huge ranges", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_defined_name(spreadsheet, "Everything", "Data!$A$1:$XFD$1048576")
+
+    assert {:error, "Defined name 'Everything' spans " <> _} =
+             UmyaSpreadsheet.get_defined_name_values(spreadsheet, "Everything")
+  end
+
+  test "named range values reject names that are not a single range", %{
+    spreadsheet: spreadsheet
+  } do
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15")
+    assert :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Areas", "Data!A1,Data!C1")
+
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name_values(spreadsheet, "TaxRate")
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name_values(spreadsheet, "Areas")
+    assert {:error, _} = UmyaSpreadsheet.set_defined_name_values(spreadsheet, "Areas", [[1]])
+  end
+
+  test "validate_defined_name follows Excel naming rules" do
+    assert :ok = UmyaSpreadsheet.validate_defined_name("TaxRate")
+    assert :ok = UmyaSpreadsheet.validate_defined_name("_total.2024")
+    assert :ok = UmyaSpreadsheet.validate_defined_name("\\Path")
+    assert :ok = UmyaSpreadsheet.validate_defined_name("XFE1")
+
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("1Rate")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("Tax-Rate")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("AB12")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("R1C1")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("RC")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name("c")
+    assert {:error, _} = UmyaSpreadsheet.validate_defined_name(String.duplicate("a", 256))
+  end
+
+  test "scope, hidden flag and comments survive a write and read", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_defined_name(spreadsheet, "TaxRate", "0.15", nil, false, "Global rate")
+
+    assert :ok =
+             UmyaSpreadsheet.add_defined_name(
+               spreadsheet,
+               "Region",
+               "Data!$A$1",
+               "Data",
+               true,
+               "Local & <escaped>"
+             )
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+
+    {:ok, global} = UmyaSpreadsheet.get_defined_name(loaded, "TaxRate")
+    assert global["comment"] == "Global rate"
+    assert global["scope"] == nil
+
+    {:ok, local} = UmyaSpreadsheet.get_defined_name(loaded, "Region", "Data")
+    assert local["comment"] == "Local & <escaped>"
+    assert local["hidden"] == true
+    assert local["local_sheet_id"] == 1
+  end
+end
@@ -0,0 +1,145 @@
+defmodule UmyaSpreadsheetTest.FileEncryptionTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet
+  alias UmyaSpreadsheet.FileFormatOptions
+
+  @encrypted_path "test/result_files/encryption_read_test.xlsx"
+  @plain_path "test/result_files/encryption_plain_test.xlsx"
+  @options_path "test/result_files/encryption_options_test.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "Confidential")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "B2", "42")
+
+    on_exit(fn ->
+      File.rm(@encrypted_path)
+      File.rm(@plain_path)
+      File.rm(@options_path)
+    end)
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "reads an encrypted file with the correct password", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.write_with_password(spreadsheet, @encrypted_path, "s3cret")
+
+    assert {:ok, decrypted} = UmyaSpreadsheet.read_with_password(@encrypted_path, "s3cret")
+    assert {:ok, "Confidential"} = UmyaSpreadsheet.get_cell_value(decrypted, "Sheet1", "A1")
+    assert {:ok, "42"} = UmyaSpreadsheet.get_cell_value(decrypted, "Sheet1", "B2")
+
+    assert FileFormatOptions.is_encrypted(decrypted) == true
+    assert FileFormatOptions.get_encryption_algorithm(decrypted) == "AES256"
+  end
+
+  test "distinguishes a wrong password", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.write_with_password(spreadsheet, @encrypted_path, "s3cret")
+
+    assert {:error, "wrong_password"} =
+             UmyaSpreadsheet.read_with_password(@encrypted_path, "guess")
+  end
+
+  test "read without a password reports that one is required", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.write_with_password(spreadsheet, @encrypted_path, "s3cret")
+
+    assert {:error, "password_required"} = UmyaSpreadsheet.read(@encrypted_path)
+    assert {:error, "password_required"} = UmyaSpreadsheet.lazy_read(@encrypted_path)
+  end
+
+  test "read_with_password rejects a plain file", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.write(spreadsheet, @plain_path)
+
+    assert {:error, "not_encrypted"} = UmyaSpreadsheet.read_with_password(@plain_path, "s3cret")
+  end
+
+  test "is_file_encrypted inspects the container", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.write_with_password(spreadsheet, @encrypted_path, "s3cret")
+    :ok = UmyaSpreadsheet.write(spreadsheet, @plain_path)
+
+    assert UmyaSpreadsheet.is_file_encrypted(@encrypted_path) == true
+    assert UmyaSpreadsheet.is_file_encrypted(@plain_path) == false
+    assert {:error, "File not found"} = UmyaSpreadsheet.is_file_encrypted("missing.xlsx")
+  end
+
+  test "workbook protection does not count as encryption", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_workbook_protection(spreadsheet, "protect")
+    assert FileFormatOptions.is_encrypted(spreadsheet) == false
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @plain_path)
+    {:ok, plain} = UmyaSpreadsheet.read(@plain_path)
+
+    assert FileFormatOptions.is_encrypted(plain) == false
+    assert FileFormatOptions.get_encryption_algorithm(plain) == nil
+  end
+
+  test "write_with_encryption_options controls the cipher and key derivation",
+       %{spreadsheet: spreadsheet} do
+    salt = Base.encode64(:binary.copy(<<7>>, 16))
+
+    assert :ok =
+             UmyaSpreadsheet.write_with_encryption_options(
+               spreadsheet,
+               @options_path,
+               "s3cret",
+               "AES128",
+               salt,
+               1000,
+               "SHA256"
+             )
+
+    refute File.exists?(@options_path <> ".tmp")
+    assert UmyaSpreadsheet.is_file_encrypted(@options_path)
+
+    assert {:ok, decrypted} = UmyaSpreadsheet.read_with_password(@options_path, "s3cret")
+    assert {:ok, "Confidential"} = UmyaSpreadsheet.get_cell_value(decrypted, "Sheet1", "A1")
+    assert FileFormatOptions.get_encryption_algorithm(decrypted) == "AES128"
+
+    assert {:error, "wrong_password"} =
+             UmyaSpreadsheet.read_with_password(@options_path, "S3cret")
+  end
+
+  test "write_with_encryption_options rejects invalid options", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unsupported encryption algorithm 'RC4'; use AES128, AES192 or AES256"} =
+             UmyaSpreadsheet.write_with_encryption_options(
+               spreadsheet,
+               @options_path,
+               "s3cret",
+               "RC4"
+             )
+
+    assert {:error, "Salt value must be 16 bytes encoded as base64"} =
+             UmyaSpreadsheet.write_with_encryption_options(
+               spreadsheet,
+               @options_path,
+               "s3cret",
+               "AES256",
+               "customSalt"
+             )
+
+    assert {:error, "Unsupported hash algorithm 'MD5'; use SHA256, SHA384 or SHA512"} =
+             UmyaSpreadsheet.write_with_encryption_options(
+               spreadsheet,
+               @options_path,
+               "s3cret",
+               "AES256",
+               nil,
+               nil,
+               "MD5"
+             )
+
+    refute File.exists?(@options_path)
+  end
+
+  test "to_encrypted_binary_xlsx returns an encrypted package", %{spreadsheet: spreadsheet} do
+    binary = UmyaSpreadsheet.to_encrypted_binary_xlsx(spreadsheet, "s3cret", "AES256", nil, 2000)
+
+    assert <<0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, _rest::binary>> = binary
+
+    :ok = File.write(@options_path, binary)
+    assert {:ok, decrypted} = UmyaSpreadsheet.read_with_password(@options_path, "s3cret")
+    assert {:ok, "42"} = UmyaSpreadsheet.get_cell_value(decrypted, "Sheet1", "B2")
+  end
+end
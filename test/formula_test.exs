@@ -173,17 +173,13 @@ defmodule UmyaSpreadsheetTest.FormulaTest do
     assert Enum.any?(defined_names, fn {name, _} -> name == "DataRange" end)
     assert Enum.any?(defined_names, fn {name, _} -> name == "Subtotal" end)
 
-    # Check that global defined name exists (but note: has empty name due to API limitation)
-    assert Enum.any?(defined_names, fn {_, formula} -> String.contains?(formula, "0.15") end)
+    # Check that global defined name exists
+    assert Enum.any?(defined_names, fn {name, _} -> name == "TaxRate" end)
 
     # Find the specific items and check their formulas
     data_range = Enum.find(defined_names, fn {name, _} -> name == "DataRange" end)
     subtotal = Enum.find(defined_names, fn {name, _} -> name == "Subtotal" end)
-    # Global defined names have empty names due to umya-spreadsheet 2.3.0 API limitation
-    tax_rate =
-      Enum.find(defined_names, fn {name, formula} ->
-        name == "" && String.contains?(formula, "0.15")
-      end)
+    tax_rate = Enum.find(defined_names, fn {name, _} -> name == "TaxRate" end)
 
     assert data_range != nil
     assert tax_rate != nil
@@ -0,0 +1,129 @@
+defmodule UmyaSpreadsheet.InsertInheritTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.DataValidation
+
+  @output_path "test/result_files/insert_inherit_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+
+    for {value, column} <- Enum.zip(["Item", "Qty", "Price", "Total"], ["A", "B", "C", "D"]) do
+      :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "#{column}1", value)
+    end
+
+    for row <- 2..4 do
+      :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A#{row}", "Item #{row}")
+      :ok = UmyaSpreadsheet.set_formula(spreadsheet, "Sheet1", "D#{row}", "B#{row}*C#{row}")
+      :ok = UmyaSpreadsheet.set_number_format(spreadsheet, "Sheet1", "D#{row}", "0.00")
+    end
+
+    :ok = UmyaSpreadsheet.set_row_height(spreadsheet, "Sheet1", 4, 24.0)
+
+    :ok =
+      UmyaSpreadsheet.add_table(
+        spreadsheet,
+        "Sheet1",
+        "Items",
+        "Items",
+        "A1",
+        "D5",
+        ["Item", "Qty", "Price", "Total"],
+        true
+      )
+
+    :ok = DataValidation.add_number_validation(spreadsheet, "Sheet1", "B2:B4", "greaterThan", 0.0)
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "grows tables and data validations with inserted rows", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 3, 2)
+
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["start_cell"] == "A1"
+    assert table["end_cell"] == "D7"
+    assert table["has_totals_row"] == true
+
+    {:ok, [validation]} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    assert validation.range == "B2:B6"
+  end
+
+  test "inserted rows inherit from the row above", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 5, 2, inherit: :above)
+
+    for row <- [5, 6] do
+      assert UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "D#{row}") =~ "B#{row}*C#{row}"
+      assert {:ok, 24.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", row)
+
+      assert UmyaSpreadsheet.get_cell_number_format_id(spreadsheet, "Sheet1", "D#{row}") ==
+               UmyaSpreadsheet.get_cell_number_format_id(spreadsheet, "Sheet1", "D4")
+    end
+
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Sheet1", "A5") == ""
+
+    {:ok, [validation]} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    assert validation.range == "B2:B6"
+
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["end_cell"] == "D7"
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+  end
+
+  test "inserted columns join the table", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.insert_new_column(spreadsheet, "Sheet1", "C", 1, inherit: :left)
+
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["end_cell"] == "E5"
+    assert table["columns"] == ["Item", "Qty", "Column1", "Price", "Total"]
+    assert UmyaSpreadsheet.get_cell_value(spreadsheet, "Sheet1", "C1") == "Column1"
+
+    {:ok, ranges} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    assert ranges |> Enum.map(& &1.range) |> Enum.sort() == ["B2:B4", "C2:C4"]
+
+    :ok = UmyaSpreadsheet.insert_new_column_by_index(spreadsheet, "Sheet1", 1, 1)
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["start_cell"] == "B1"
+    assert table["end_cell"] == "F5"
+  end
+
+  test "removing rows and columns shrinks tables and data validations", %{
+    spreadsheet: spreadsheet
+  } do
+    :ok = UmyaSpreadsheet.remove_row(spreadsheet, "Sheet1", 3, 1)
+    :ok = UmyaSpreadsheet.remove_column(spreadsheet, "Sheet1", "C", 1)
+
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["end_cell"] == "C4"
+    assert table["columns"] == ["Item", "Qty", "Total"]
+
+    {:ok, [validation]} = DataValidation.get_data_validations(spreadsheet, "Sheet1")
+    assert validation.range == "B2:B3"
+
+    :ok = UmyaSpreadsheet.remove_row(spreadsheet, "Sheet1", 4, 1)
+    {:ok, table} = UmyaSpreadsheet.get_table(spreadsheet, "Sheet1", "Items")
+    assert table["has_totals_row"] == false
+  end
+
+  test "rejects an inherit option for the other axis", %{spreadsheet: spreadsheet} do
+    assert {:error, message} =
+             UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 3, 1, inherit: :left)
+
+    assert message =~ "expected above or below"
+  end
+
+  test "rejects invalid indexes and amounts", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid row index 0"} =
+             UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 0, 1)
+
+    assert {:error, "Invalid row index 1048577"} =
+             UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 1_048_577, 1)
+
+    assert {:error, "Amount must be at least 1"} =
+             UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 3, 0)
+
+    assert {:error, "Amount must be at least 1"} =
+             UmyaSpreadsheet.insert_new_column_by_index(spreadsheet, "Sheet1", 3, 0)
+  end
+end
@@ -0,0 +1,80 @@
+defmodule UmyaSpreadsheet.MergeCellsTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/merge_cells_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "Title")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "B1", "Hidden")
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "finds the merged area covering a cell", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A1:C3")
+
+    assert {:ok, "A1:C3"} = UmyaSpreadsheet.get_merged_area(spreadsheet, "Sheet1", "B2")
+    assert {:ok, "A1:C3"} = UmyaSpreadsheet.get_merged_area(spreadsheet, "Sheet1", "A1")
+    assert {:ok, nil} = UmyaSpreadsheet.get_merged_area(spreadsheet, "Sheet1", "D4")
+
+    assert {:error, "Invalid cell address 'A1:B2'"} =
+             UmyaSpreadsheet.get_merged_area(spreadsheet, "Sheet1", "A1:B2")
+  end
+
+  test "rejects overlapping and single-cell merges", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A1:C3")
+
+    assert {:error, "Range 'B2:D2' overlaps merged cells 'A1:C3'"} =
+             UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "B2:D2")
+
+    assert {:error, "Range 'E5' must span more than one cell"} =
+             UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "E5")
+
+    assert {:error, "Sheet not found"} =
+             UmyaSpreadsheet.add_merge_cells(spreadsheet, "Missing", "A1:B1")
+
+    assert {:ok, ["A1:C3"]} = UmyaSpreadsheet.get_merge_cells(spreadsheet, "Sheet1")
+  end
+
+  test "merges and centers, clearing the other cells", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A1:D1",
+               center: true,
+               clear_non_anchor: true
+             )
+
+    assert {:ok, "Title"} = UmyaSpreadsheet.get_cell_value(spreadsheet, "Sheet1", "A1")
+    assert {:ok, ""} = UmyaSpreadsheet.get_cell_value(spreadsheet, "Sheet1", "B1")
+
+    assert {:ok, "center"} =
+             UmyaSpreadsheet.get_cell_horizontal_alignment(spreadsheet, "Sheet1", "A1")
+  end
+
+  test "merges across each row", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.merge_cells_across(spreadsheet, "Sheet1", "B3:D5")
+
+    assert {:ok, merges} = UmyaSpreadsheet.get_merge_cells(spreadsheet, "Sheet1")
+    assert Enum.sort(merges) == ["B3:D3", "B4:D4", "B5:D5"]
+
+    assert {:error, "Range 'F1:F4' must span more than one column"} =
+             UmyaSpreadsheet.merge_cells_across(spreadsheet, "Sheet1", "F1:F4")
+
+    assert {:error, "Range 'B5:D5' overlaps merged cells 'B5:D5'"} =
+             UmyaSpreadsheet.merge_cells_across(spreadsheet, "Sheet1", "B5:D6")
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+  end
+
+  test "unmerges every area touching a range", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A1:B2")
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "D1:E2")
+    :ok = UmyaSpreadsheet.add_merge_cells(spreadsheet, "Sheet1", "A5:B5")
+
+    assert :ok = UmyaSpreadsheet.unmerge_cells(spreadsheet, "Sheet1", "B2:D2")
+    assert {:ok, ["A5:B5"]} = UmyaSpreadsheet.get_merge_cells(spreadsheet, "Sheet1")
+
+    # Ranges without merged cells are fine
+    assert :ok = UmyaSpreadsheet.unmerge_cells(spreadsheet, "Sheet1", "H8")
+    assert {:ok, nil} = UmyaSpreadsheet.get_merged_area(spreadsheet, "Sheet1", "A1")
+  end
+end
@@ -0,0 +1,93 @@
+defmodule UmyaSpreadsheet.MoveSheetTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/move_sheet_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Data")
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Notes")
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Summary")
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "moves a sheet to an index", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Summary", 0)
+    assert ["Summary", "Sheet1", "Data", "Notes"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Summary", 3)
+    assert ["Sheet1", "Data", "Notes", "Summary"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+  end
+
+  test "moves a sheet before or after another sheet", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Sheet1", after: "Notes")
+    assert ["Data", "Notes", "Sheet1", "Summary"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Summary", {:before, "Data"})
+    assert ["Summary", "Data", "Notes", "Sheet1"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Data", after: "Data")
+    assert ["Summary", "Data", "Notes", "Sheet1"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+  end
+
+  test "keeps the active sheet and sheet-scoped names with their sheets", %{
+    spreadsheet: spreadsheet
+  } do
+    :ok = UmyaSpreadsheet.set_active_tab(spreadsheet, 1)
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Rows", "Data!$A$1:$A$9", "Data", false, "Input")
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Total", "Summary!$B$2", "Summary")
+
+    assert :ok = UmyaSpreadsheet.move_sheet(spreadsheet, "Summary", 0)
+
+    # "Data" was the active sheet and still is
+    assert {:ok, 2} = UmyaSpreadsheet.get_active_tab(spreadsheet)
+
+    {:ok, rows} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Rows", "Data")
+    assert rows["local_sheet_id"] == 2
+    assert rows["comment"] == "Input"
+
+    {:ok, total} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Total", "Summary")
+    assert total["local_sheet_id"] == 0
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+    assert ["Summary", "Sheet1", "Data", "Notes"] = UmyaSpreadsheet.get_sheet_names(loaded)
+    assert {:ok, %{"scope" => "Data"}} = UmyaSpreadsheet.get_defined_name(loaded, "Rows", "Data")
+  end
+
+  test "removing a sheet keeps the names of later sheets with their sheets", %{
+    spreadsheet: spreadsheet
+  } do
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Rows", "Data!$A$1:$A$9", "Data", false, "Input")
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Total", "Summary!$B$2", "Summary")
+
+    assert :ok = UmyaSpreadsheet.remove_sheet(spreadsheet, "Data")
+
+    # Names scoped to the removed sheet go with it, comments included
+    assert {:error, _} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Rows", "Data")
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Rows", "Notes!$A$1", "Notes")
+    {:ok, rows} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Rows", "Notes")
+    assert rows["comment"] == nil
+
+    {:ok, total} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Total", "Summary")
+    assert total["local_sheet_id"] == 2
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, %{"scope" => "Summary"}} =
+             UmyaSpreadsheet.get_defined_name(loaded, "Total", "Summary")
+  end
+
+  test "rejects unknown sheets and positions", %{spreadsheet: spreadsheet} do
+    assert {:error, "Sheet not found"} = UmyaSpreadsheet.move_sheet(spreadsheet, "Missing", 0)
+
+    assert {:error, "Sheet index 4 is out of range"} =
+             UmyaSpreadsheet.move_sheet(spreadsheet, "Data", 4)
+
+    assert {:error, "Sheet 'Missing' not found"} =
+             UmyaSpreadsheet.move_sheet(spreadsheet, "Data", before: "Missing")
+
+    assert {:error, _} = UmyaSpreadsheet.move_sheet(spreadsheet, "Data", "first")
+  end
+end
@@ -0,0 +1,75 @@
+defmodule UmyaSpreadsheet.NamedStyleTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/named_style_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "defines a named style that survives a round trip", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.add_named_style(spreadsheet, "Brand Heading", %{
+               font: %{bold: true, size: 16, color: "#1F4E79"},
+               borders: %{bottom: %{style: "medium", color: "#1F4E79"}}
+             })
+
+    assert {:ok, styles} = UmyaSpreadsheet.list_named_styles(spreadsheet)
+    names = Enum.map(styles, & &1["name"])
+    assert "Normal" in names
+    assert "Brand Heading" in names
+
+    assert %{"builtin" => false, "builtin_id" => nil} =
+             Enum.find(styles, &(&1["name"] == "Brand Heading"))
+
+    assert :ok = UmyaSpreadsheet.apply_named_style(spreadsheet, "Sheet1", "A1:B1", "Brand Heading")
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+    assert {:ok, loaded_styles} = UmyaSpreadsheet.list_named_styles(loaded)
+    assert "Brand Heading" in Enum.map(loaded_styles, & &1["name"])
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(loaded, "Sheet1", "B1")
+  end
+
+  test "applying a named style formats the range", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_named_style(spreadsheet, "Highlight", %{font: %{italic: true}})
+
+    assert :ok = UmyaSpreadsheet.apply_named_style(spreadsheet, "Sheet1", "A1:C2", "highlight")
+
+    assert {:ok, true} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "C2")
+    assert {:ok, false} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "D3")
+  end
+
+  test "updating a named style reaches the cells using it", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_named_style(spreadsheet, "Accent", %{font: %{color: "#0000FF"}})
+    :ok = UmyaSpreadsheet.apply_named_style(spreadsheet, "Sheet1", "A1:A3", "Accent")
+    :ok = UmyaSpreadsheet.set_font_bold(spreadsheet, "Sheet1", "A2", true)
+
+    assert :ok = UmyaSpreadsheet.update_named_style(spreadsheet, "Accent", %{font: %{color: "#FF0000"}})
+
+    assert {:ok, "FFFF0000"} = UmyaSpreadsheet.get_font_color(spreadsheet, "Sheet1", "A1")
+    assert {:ok, "FFFF0000"} = UmyaSpreadsheet.get_font_color(spreadsheet, "Sheet1", "A3")
+    # Formatting applied on top of the style is kept
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Sheet1", "A2")
+  end
+
+  test "reports invalid names and sheets", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_named_style(spreadsheet, "Accent", %{font: %{bold: true}})
+
+    assert {:error, "Named style 'ACCENT' already exists"} =
+             UmyaSpreadsheet.add_named_style(spreadsheet, "ACCENT", %{})
+
+    assert {:error, "Named style name cannot be empty"} =
+             UmyaSpreadsheet.add_named_style(spreadsheet, "", %{})
+
+    assert {:error, "Named style 'Missing' not found"} =
+             UmyaSpreadsheet.apply_named_style(spreadsheet, "Sheet1", "A1", "Missing")
+
+    assert {:error, "Named style 'Missing' not found"} =
+             UmyaSpreadsheet.update_named_style(spreadsheet, "Missing", %{font: %{bold: false}})
+
+    assert {:error, "Sheet not found"} =
+             UmyaSpreadsheet.apply_named_style(spreadsheet, "Nope", "A1", "Accent")
+  end
+end
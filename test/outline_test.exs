@@ -0,0 +1,118 @@
+defmodule UmyaSpreadsheet.OutlineTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/outline_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+
+    for row <- 1..10 do
+      :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A#{row}", "Line #{row}")
+    end
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "groups rows into nested levels", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.group_rows(spreadsheet, "Sheet1", 2, 8)
+    :ok = UmyaSpreadsheet.group_rows(spreadsheet, "Sheet1", 3, 5)
+
+    assert {:ok, %{"rows" => rows, "columns" => [], "summary_below" => true}} =
+             UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+
+    assert rows == [
+             %{"start" => 2, "end" => 8, "level" => 1, "collapsed" => false},
+             %{"start" => 3, "end" => 5, "level" => 2, "collapsed" => false}
+           ]
+
+    :ok = UmyaSpreadsheet.ungroup_rows(spreadsheet, "Sheet1", 3, 5)
+    {:ok, %{"rows" => rows}} = UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+    assert rows == [%{"start" => 2, "end" => 8, "level" => 1, "collapsed" => false}]
+  end
+
+  test "collapsing hides a group and expanding keeps nested groups collapsed", %{
+    spreadsheet: spreadsheet
+  } do
+    :ok = UmyaSpreadsheet.group_rows(spreadsheet, "Sheet1", 2, 8)
+    :ok = UmyaSpreadsheet.set_row_outline_level(spreadsheet, "Sheet1", 3, 5, 2)
+
+    :ok = UmyaSpreadsheet.collapse_rows(spreadsheet, "Sheet1", 3, 5)
+    :ok = UmyaSpreadsheet.collapse_rows(spreadsheet, "Sheet1", 2, 8)
+    assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 7)
+
+    :ok = UmyaSpreadsheet.expand_rows(spreadsheet, "Sheet1", 2, 8)
+    assert {:ok, false} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 7)
+    assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 4)
+
+    {:ok, %{"rows" => rows}} = UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+    assert %{"start" => 3, "end" => 5, "level" => 2, "collapsed" => true} in rows
+  end
+
+  test "places summaries above and left of their groups", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_outline_summary(spreadsheet, "Sheet1",
+        summary_below: false,
+        summary_right: false
+      )
+
+    :ok = UmyaSpreadsheet.group_columns(spreadsheet, "Sheet1", "C", "E")
+    :ok = UmyaSpreadsheet.collapse_columns(spreadsheet, "Sheet1", "C", "E")
+
+    assert {:ok,
+            %{
+              "columns" => [%{"start" => "C", "end" => "E", "level" => 1, "collapsed" => true}],
+              "summary_below" => false,
+              "summary_right" => false
+            }} = UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+
+    assert {:ok, true} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "D")
+    assert {:ok, false} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "B")
+
+    :ok = UmyaSpreadsheet.expand_columns(spreadsheet, "Sheet1", "C", "E")
+    assert {:ok, false} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "D")
+  end
+
+  test "keeps the outline when written and read again", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.group_rows(spreadsheet, "Sheet1", 2, 5)
+    :ok = UmyaSpreadsheet.collapse_rows(spreadsheet, "Sheet1", 2, 5)
+    :ok = UmyaSpreadsheet.group_columns(spreadsheet, "Sheet1", "B", "D")
+    {:ok, outline} = UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, ^outline} = UmyaSpreadsheet.get_outline(reloaded, "Sheet1")
+    assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(reloaded, "Sheet1", 3)
+  end
+
+  test "moves groups with inserted and removed rows", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.group_rows(spreadsheet, "Sheet1", 4, 6)
+    :ok = UmyaSpreadsheet.insert_new_row(spreadsheet, "Sheet1", 2, 2)
+
+    {:ok, %{"rows" => [%{"start" => 6, "end" => 8}]}} =
+      UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+
+    :ok = UmyaSpreadsheet.remove_row(spreadsheet, "Sheet1", 1, 3)
+
+    {:ok, %{"rows" => [%{"start" => 3, "end" => 5}]}} =
+      UmyaSpreadsheet.get_outline(spreadsheet, "Sheet1")
+  end
+
+  test "rejects invalid outlines", %{spreadsheet: spreadsheet} do
+    assert {:error, "Rows 2:5 are not grouped"} =
+             UmyaSpreadsheet.ungroup_rows(spreadsheet, "Sheet1", 2, 5)
+
+    assert {:error, "Rows 2:5 are not grouped"} =
+             UmyaSpreadsheet.collapse_rows(spreadsheet, "Sheet1", 2, 5)
+
+    assert {:error, "Outline level must be between 0 and 7"} =
+             UmyaSpreadsheet.set_row_outline_level(spreadsheet, "Sheet1", 2, 5, 8)
+
+    :ok = UmyaSpreadsheet.set_column_outline_level(spreadsheet, "Sheet1", "B", "C", 7)
+
+    assert {:error, "Columns B:C would exceed the deepest outline level 7"} =
+             UmyaSpreadsheet.group_columns(spreadsheet, "Sheet1", "B", "C")
+
+    assert {:error, "Sheet not found"} = UmyaSpreadsheet.get_outline(spreadsheet, "Missing")
+  end
+end
@@ -0,0 +1,103 @@
+defmodule UmyaSpreadsheet.RangeStyleTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/range_style_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "merge mode adds to existing styling", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_font_bold(spreadsheet, "Sheet1", "B2", true)
+
+    assert :ok =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "A1:C3", %{
+               font: %{italic: true, color: "#FF0000"},
+               fill: %{pattern: :solid, color: "FFFF00"},
+               borders: %{all: %{style: "thin", color: "000000"}},
+               alignment: %{horizontal: :center, wrap_text: true}
+             })
+
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Sheet1", "B2")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "B2")
+    assert {:ok, "FFFF0000"} = UmyaSpreadsheet.get_font_color(spreadsheet, "Sheet1", "C3")
+    assert {:ok, "solid"} = UmyaSpreadsheet.get_cell_pattern_type(spreadsheet, "Sheet1", "A1")
+    assert {:ok, "thin"} = UmyaSpreadsheet.get_border_style(spreadsheet, "Sheet1", "A3", "left")
+
+    assert {:ok, "center"} =
+             UmyaSpreadsheet.get_cell_horizontal_alignment(spreadsheet, "Sheet1", "C1")
+
+    assert {:ok, true} = UmyaSpreadsheet.get_cell_wrap_text(spreadsheet, "Sheet1", "C1")
+
+    # Cells outside the range are untouched
+    assert {:ok, false} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "D4")
+  end
+
+  test "replace mode drops previous styling", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_font_bold(spreadsheet, "Sheet1", "A1", true)
+
+    assert :ok =
+             UmyaSpreadsheet.set_range_style(
+               spreadsheet,
+               "Sheet1",
+               "A1",
+               %{font: %{italic: true}},
+               :replace
+             )
+
+    assert {:ok, false} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Sheet1", "A1")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_italic(spreadsheet, "Sheet1", "A1")
+  end
+
+  test "whole columns and rows style existing cells", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "B10", "1234.5")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "E5", "Total")
+
+    assert :ok =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "B:C", %{
+               number_format: "#,##0.00"
+             })
+
+    assert :ok =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "5:5", %{
+               font: %{bold: true},
+               protection: %{locked: false}
+             })
+
+    assert {:ok, "#,##0.00"} = UmyaSpreadsheet.get_cell_format_code(spreadsheet, "Sheet1", "B10")
+    assert {:ok, true} = UmyaSpreadsheet.get_font_bold(spreadsheet, "Sheet1", "E5")
+    assert {:ok, false} = UmyaSpreadsheet.get_cell_locked(spreadsheet, "Sheet1", "E5")
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+    assert {:ok, "#,##0.00"} = UmyaSpreadsheet.get_cell_format_code(loaded, "Sheet1", "B10")
+  end
+
+  test "rejects invalid styles and ranges", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unknown style key 'font.weight'"} =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "A1", %{
+               font: %{weight: "bold"}
+             })
+
+    assert {:error, "Invalid color 'notacolor'"} =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "A1", %{
+               font: %{color: "notacolor"}
+             })
+
+    assert {:error, "Invalid range 'A1:'"} =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Sheet1", "A1:", %{font: %{bold: true}})
+
+    assert {:error, _} =
+             UmyaSpreadsheet.set_range_style(
+               spreadsheet,
+               "Sheet1",
+               "A1",
+               %{font: %{bold: true}},
+               :overwrite
+             )
+
+    assert {:error, _} =
+             UmyaSpreadsheet.set_range_style(spreadsheet, "Missing", "A1", %{font: %{bold: true}})
+  end
+end
@@ -0,0 +1,95 @@
+defmodule UmyaSpreadsheet.RenameSheetReferencesTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/rename_sheet_references_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Old Name")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Old Name", "A1", "Red")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Old Name", "B2", "21")
+    :ok = UmyaSpreadsheet.set_formula(spreadsheet, "Sheet1", "A1", "'Old Name'!B2*2")
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "only renames the sheet by default", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.rename_sheet(spreadsheet, "Old Name", "Inputs")
+
+    assert ["Sheet1", "Inputs"] = UmyaSpreadsheet.get_sheet_names(spreadsheet)
+    assert "'Old Name'!B2*2" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "A1")
+  end
+
+  test "rewrites formulas on every sheet", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_formula(spreadsheet, "Old Name", "C1", "SUM('old name'!B1:B3)")
+    :ok = UmyaSpreadsheet.set_formula(spreadsheet, "Sheet1", "A2", "\"'Old Name'!B2\"")
+
+    assert :ok =
+             UmyaSpreadsheet.rename_sheet(spreadsheet, "Old Name", "Inputs",
+               rewrite_references: true
+             )
+
+    assert "Inputs!B2*2" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "A1")
+    assert "SUM(Inputs!B1:B3)" = UmyaSpreadsheet.get_formula(spreadsheet, "Inputs", "C1")
+
+    # Text in string literals is left alone
+    assert "\"'Old Name'!B2\"" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "A2")
+  end
+
+  test "quotes names with spaces and apostrophes", %{spreadsheet: spreadsheet} do
+    assert :ok =
+             UmyaSpreadsheet.rename_sheet(spreadsheet, "Old Name", "Q1 '24",
+               rewrite_references: true
+             )
+
+    assert "'Q1 ''24'!B2*2" = UmyaSpreadsheet.get_formula(spreadsheet, "Sheet1", "A1")
+  end
+
+  test "rewrites defined names and data validations", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Colors", "'Old Name'!$A$1:$A$3")
+    :ok = UmyaSpreadsheet.add_defined_name(spreadsheet, "Total", "Sheet1!$A$1", "Old Name")
+
+    :ok =
+      UmyaSpreadsheet.add_custom_validation(
+        spreadsheet,
+        "Sheet1",
+        "B1",
+        "COUNTIF('Old Name'!$A$1:$A$3,B1)>0"
+      )
+
+    assert :ok =
+             UmyaSpreadsheet.rename_sheet(spreadsheet, "Old Name", "Lists",
+               rewrite_references: true
+             )
+
+    {:ok, colors} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Colors")
+    assert colors["refers_to"] == "Lists!$A$1:$A$3"
+
+    # Names scoped to the renamed sheet keep pointing where they did
+    {:ok, total} = UmyaSpreadsheet.get_defined_name(spreadsheet, "Total", "Lists")
+    assert total["refers_to"] == "Sheet1!$A$1"
+
+    {:ok, [validation]} =
+      UmyaSpreadsheet.DataValidation.get_custom_validations(spreadsheet, "Sheet1")
+
+    assert validation.formula == "COUNTIF(Lists!$A$1:$A$3,B1)>0"
+
+    assert :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, loaded} = UmyaSpreadsheet.read(@output_path)
+    assert "Lists!B2*2" = UmyaSpreadsheet.get_formula(loaded, "Sheet1", "A1")
+
+    assert {:ok, %{"refers_to" => "Lists!$A$1:$A$3"}} =
+             UmyaSpreadsheet.get_defined_name(loaded, "Colors")
+  end
+
+  test "rejects missing sheets and taken names", %{spreadsheet: spreadsheet} do
+    assert {:error, "Source sheet not found"} =
+             UmyaSpreadsheet.rename_sheet(spreadsheet, "Missing", "Other",
+               rewrite_references: true
+             )
+
+    assert {:error, "Target sheet already exists"} =
+             UmyaSpreadsheet.rename_sheet(spreadsheet, "Old Name", "Sheet1",
+               rewrite_references: true
+             )
+  end
+end
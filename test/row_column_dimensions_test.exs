@@ -0,0 +1,108 @@
+defmodule UmyaSpreadsheet.RowColumnDimensionsTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/row_column_dimensions_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "hides and unhides rows", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.hide_row(spreadsheet, "Sheet1", 2)
+    :ok = UmyaSpreadsheet.hide_rows(spreadsheet, "Sheet1", 5, 7)
+
+    for row <- [2, 5, 6, 7] do
+      assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", row)
+    end
+
+    assert {:ok, false} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 4)
+    assert {:ok, 15.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", 5)
+
+    :ok = UmyaSpreadsheet.unhide_rows(spreadsheet, "Sheet1", 6, 7)
+    :ok = UmyaSpreadsheet.unhide_row(spreadsheet, "Sheet1", 2)
+    assert {:ok, false} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 2)
+    assert {:ok, false} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 7)
+    assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(spreadsheet, "Sheet1", 5)
+  end
+
+  test "hides columns without changing their width", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_column_width(spreadsheet, "Sheet1", "C", 20.0)
+    :ok = UmyaSpreadsheet.hide_columns(spreadsheet, "Sheet1", "B", "C")
+    :ok = UmyaSpreadsheet.hide_column(spreadsheet, "Sheet1", "F")
+
+    assert {:ok, true} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "B")
+    assert {:ok, true} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "F")
+    assert {:ok, 8.43} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", "B")
+    assert {:ok, 20.0} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", "C")
+
+    :ok = UmyaSpreadsheet.unhide_column(spreadsheet, "Sheet1", "C")
+    assert {:ok, false} = UmyaSpreadsheet.get_column_hidden(spreadsheet, "Sheet1", "C")
+  end
+
+  test "sets heights and widths of spans", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_rows_height(spreadsheet, "Sheet1", 2, 4, 30)
+    :ok = UmyaSpreadsheet.set_columns_width(spreadsheet, "Sheet1", "D", "B", 12.5)
+
+    for row <- 2..4 do
+      assert {:ok, 30.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", row)
+      assert {:ok, true} = UmyaSpreadsheet.get_row_custom_height(spreadsheet, "Sheet1", row)
+    end
+
+    for column <- ["B", "C", "D"] do
+      assert {:ok, 12.5} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", column)
+    end
+
+    :ok = UmyaSpreadsheet.reset_rows_height(spreadsheet, "Sheet1", 3, 4)
+    assert {:ok, false} = UmyaSpreadsheet.get_row_custom_height(spreadsheet, "Sheet1", 3)
+    assert {:ok, 15.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", 3)
+    assert {:ok, 30.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", 2)
+  end
+
+  test "sets the default row height and column width", %{spreadsheet: spreadsheet} do
+    assert {:ok, 15.0} = UmyaSpreadsheet.get_default_row_height(spreadsheet, "Sheet1")
+    assert {:ok, 8.43} = UmyaSpreadsheet.get_default_column_width(spreadsheet, "Sheet1")
+
+    :ok = UmyaSpreadsheet.set_default_row_height(spreadsheet, "Sheet1", 20)
+    :ok = UmyaSpreadsheet.set_default_column_width(spreadsheet, "Sheet1", 12.0)
+    :ok = UmyaSpreadsheet.hide_column(spreadsheet, "Sheet1", "E")
+
+    assert {:ok, 20.0} = UmyaSpreadsheet.get_row_height(spreadsheet, "Sheet1", 50)
+    assert {:ok, 12.0} = UmyaSpreadsheet.get_column_width(spreadsheet, "Sheet1", "E")
+  end
+
+  test "keeps dimensions when written and read again", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Sheet1", "A1", "Visible")
+    :ok = UmyaSpreadsheet.hide_rows(spreadsheet, "Sheet1", 3, 4)
+    :ok = UmyaSpreadsheet.hide_columns(spreadsheet, "Sheet1", "C", "D")
+    :ok = UmyaSpreadsheet.set_rows_height(spreadsheet, "Sheet1", 6, 8, 24.0)
+    :ok = UmyaSpreadsheet.set_default_row_height(spreadsheet, "Sheet1", 18.0)
+    :ok = UmyaSpreadsheet.set_default_column_width(spreadsheet, "Sheet1", 10.0)
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, true} = UmyaSpreadsheet.get_row_hidden(reloaded, "Sheet1", 4)
+    assert {:ok, true} = UmyaSpreadsheet.get_column_hidden(reloaded, "Sheet1", "D")
+    assert {:ok, 24.0} = UmyaSpreadsheet.get_row_height(reloaded, "Sheet1", 7)
+    assert {:ok, true} = UmyaSpreadsheet.get_row_custom_height(reloaded, "Sheet1", 7)
+    assert {:ok, 18.0} = UmyaSpreadsheet.get_default_row_height(reloaded, "Sheet1")
+    assert {:ok, 10.0} = UmyaSpreadsheet.get_default_column_width(reloaded, "Sheet1")
+  end
+
+  test "rejects invalid spans and sizes", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid range '0:3'"} = UmyaSpreadsheet.hide_rows(spreadsheet, "Sheet1", 0, 3)
+
+    assert {:error, "Invalid range 'B:1'"} =
+             UmyaSpreadsheet.hide_columns(spreadsheet, "Sheet1", "B", "1")
+
+    assert {:error, "Row height must be between 0 and 409 points"} =
+             UmyaSpreadsheet.set_rows_height(spreadsheet, "Sheet1", 1, 2, 500.0)
+
+    assert {:error, "Column width must be between 0 and 255 characters"} =
+             UmyaSpreadsheet.set_default_column_width(spreadsheet, "Sheet1", -1.0)
+
+    assert {:error, "Sheet not found"} =
+             UmyaSpreadsheet.get_default_row_height(spreadsheet, "Missing")
+  end
+end
@@ -0,0 +1,120 @@
+defmodule UmyaSpreadsheet.SheetPropertiesTest do
+  use ExUnit.Case, async: true
+
+  @output_path "test/result_files/sheet_properties_output.xlsx"
+
+  setup do
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Lookup")
+    :ok = UmyaSpreadsheet.set_cell_value(spreadsheet, "Lookup", "A1", "Code")
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "a new sheet has default properties", %{spreadsheet: spreadsheet} do
+    assert {:ok,
+            %{
+              "state" => "visible",
+              "tab_color" => nil,
+              "code_name" => nil,
+              "filter_mode" => false,
+              "summary_below" => true,
+              "summary_right" => true
+            }} = UmyaSpreadsheet.get_sheet_properties(spreadsheet, "Lookup")
+  end
+
+  test "very hidden sheets are written and read back", %{spreadsheet: spreadsheet} do
+    assert :ok = UmyaSpreadsheet.set_sheet_state(spreadsheet, "Lookup", "veryHidden")
+    assert {:ok, "veryhidden"} = UmyaSpreadsheet.get_sheet_state(spreadsheet, "Lookup")
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, "veryhidden"} = UmyaSpreadsheet.get_sheet_state(reloaded, "Lookup")
+    assert {:ok, "visible"} = UmyaSpreadsheet.get_sheet_state(reloaded, "Sheet1")
+  end
+
+  test "the last visible sheet cannot be hidden", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_sheet_state(spreadsheet, "Lookup", "hidden")
+
+    assert {:error, "A workbook must keep at least one visible sheet"} =
+             UmyaSpreadsheet.set_sheet_state(spreadsheet, "Sheet1", "very_hidden")
+
+    assert {:ok, "visible"} = UmyaSpreadsheet.get_sheet_state(spreadsheet, "Sheet1")
+  end
+
+  test "hiding the active sheet activates the first visible sheet", %{spreadsheet: spreadsheet} do
+    :ok = UmyaSpreadsheet.set_active_tab(spreadsheet, 1)
+    :ok = UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Lookup", %{state: "veryhidden"})
+
+    assert {:ok, 0} = UmyaSpreadsheet.get_active_tab(spreadsheet)
+  end
+
+  test "rejects unknown states and properties", %{spreadsheet: spreadsheet} do
+    assert {:error, "Invalid sheet state 'gone'; use visible, hidden or veryhidden"} =
+             UmyaSpreadsheet.set_sheet_state(spreadsheet, "Lookup", "gone")
+
+    assert {:error, "Unknown sheet property 'colour'"} =
+             UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Lookup", %{colour: "#FF0000"})
+
+    assert {:error, "Property 'filter_mode' must be a boolean"} =
+             UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Lookup", %{filter_mode: "yes"})
+
+    assert {:error, "Sheet not found"} =
+             UmyaSpreadsheet.get_sheet_properties(spreadsheet, "Missing")
+  end
+
+  test "theme tab colors are resolved and kept", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Lookup", %{
+        tab_color: %{theme: 4, tint: 0.4}
+      })
+
+    {:ok, %{"tab_color" => tab_color}} =
+      UmyaSpreadsheet.get_sheet_properties(spreadsheet, "Lookup")
+
+    assert %{"theme" => 4, "indexed" => nil, "tint" => 0.4, "rgb" => "#" <> _} = tab_color
+    assert {:ok, "#" <> _} = UmyaSpreadsheet.get_tab_color(spreadsheet, "Lookup")
+
+    # The map returned by the getter can be passed back unchanged
+    :ok = UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Sheet1", %{tab_color: tab_color})
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, %{"tab_color" => ^tab_color}} =
+             UmyaSpreadsheet.get_sheet_properties(reloaded, "Lookup")
+
+    assert {:ok, %{"tab_color" => ^tab_color}} =
+             UmyaSpreadsheet.get_sheet_properties(reloaded, "Sheet1")
+
+    :ok = UmyaSpreadsheet.set_sheet_properties(reloaded, "Lookup", %{tab_color: nil})
+    assert {:ok, %{"tab_color" => nil}} = UmyaSpreadsheet.get_sheet_properties(reloaded, "Lookup")
+  end
+
+  test "code name, filter mode and outline placement round trip", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_properties(spreadsheet, "Lookup", %{
+        code_name: "shLookup",
+        filter_mode: true,
+        summary_below: false,
+        summary_right: false
+      })
+
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok,
+            %{
+              "code_name" => "shLookup",
+              "filter_mode" => true,
+              "summary_below" => false,
+              "summary_right" => false
+            }} = UmyaSpreadsheet.get_sheet_properties(reloaded, "Lookup")
+
+    assert {:ok, %{"code_name" => nil, "summary_below" => true}} =
+             UmyaSpreadsheet.get_sheet_properties(reloaded, "Sheet1")
+
+    :ok = UmyaSpreadsheet.set_sheet_properties(reloaded, "Lookup", %{code_name: nil})
+    assert {:ok, %{"code_name" => nil}} = UmyaSpreadsheet.get_sheet_properties(reloaded, "Lookup")
+  end
+end
@@ -0,0 +1,150 @@
+defmodule UmyaSpreadsheetTest.SheetProtectionTest do
+  use ExUnit.Case, async: true
+
+  alias UmyaSpreadsheet.SheetFunctions
+
+  @output_path "test/result_files/sheet_protection_test.xlsx"
+
+  setup do
+    File.mkdir_p!("test/result_files")
+
+    {:ok, spreadsheet} = UmyaSpreadsheet.new()
+    :ok = UmyaSpreadsheet.add_sheet(spreadsheet, "Sheet2")
+
+    on_exit(fn -> File.rm(@output_path) end)
+
+    %{spreadsheet: spreadsheet}
+  end
+
+  test "unprotected sheets report the Excel defaults", %{spreadsheet: spreadsheet} do
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+
+    assert protection["sheet"] == false
+    assert protection["format_cells"] == true
+    assert protection["select_locked_cells"] == false
+    assert protection["hash_value"] == nil
+    assert map_size(protection) == 20
+  end
+
+  test "sets flags and a hashed password from a map", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        password: "secret",
+        format_cells: false,
+        sort: false,
+        select_locked_cells: true
+      })
+
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+
+    assert protection["sheet"] == true
+    assert protection["format_cells"] == false
+    assert protection["sort"] == false
+    assert protection["select_locked_cells"] == true
+    assert protection["delete_rows"] == true
+    assert protection["algorithm_name"] == "SHA-512"
+    assert protection["spin_count"] == 100_000
+    assert byte_size(Base.decode64!(protection["salt_value"])) == 16
+    assert byte_size(Base.decode64!(protection["hash_value"])) == 64
+  end
+
+  test "merges options into the current protection", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        password: "secret",
+        format_rows: false
+      })
+
+    {:ok, before} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+    :ok = UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{objects: true})
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+
+    assert protection["objects"] == true
+    assert protection["format_rows"] == false
+    assert protection["hash_value"] == before["hash_value"]
+
+    :ok = UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{password: nil})
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+
+    assert protection["sheet"] == true
+    assert protection["hash_value"] == nil
+  end
+
+  test "uses explicit hash parameters", %{spreadsheet: spreadsheet} do
+    salt = Base.encode64(:binary.copy(<<1>>, 16))
+
+    :ok =
+      UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        "password" => "secret",
+        "algorithm_name" => "SHA-256",
+        "salt_value" => salt,
+        "spin_count" => 1000
+      })
+
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+
+    assert protection["algorithm_name"] == "SHA-256"
+    assert protection["salt_value"] == salt
+    assert protection["spin_count"] == 1000
+    assert byte_size(Base.decode64!(protection["hash_value"])) == 32
+  end
+
+  test "the getter map copies protection to another sheet", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        password: "secret",
+        insert_rows: false,
+        auto_filter: false
+      })
+
+    {:ok, protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+    :ok = UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet2", protection)
+
+    assert {:ok, ^protection} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet2")
+  end
+
+  test "protection survives a round trip through a file", %{spreadsheet: spreadsheet} do
+    :ok =
+      UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+        password: "secret",
+        format_cells: false,
+        pivot_tables: false
+      })
+
+    :ok = UmyaSpreadsheet.set_sheet_protection(spreadsheet, "Sheet2", nil, true)
+
+    {:ok, expected} = SheetFunctions.get_sheet_protection(spreadsheet, "Sheet1")
+    :ok = UmyaSpreadsheet.write(spreadsheet, @output_path)
+    {:ok, reloaded} = UmyaSpreadsheet.read(@output_path)
+
+    assert {:ok, ^expected} = SheetFunctions.get_sheet_protection(reloaded, "Sheet1")
+
+    # Only the sheet attribute was written; the other flags keep Excel's defaults
+    {:ok, implicit} = SheetFunctions.get_sheet_protection(reloaded, "Sheet2")
+    assert implicit["sheet"] == true
+    assert implicit["format_cells"] == true
+    assert implicit["select_locked_cells"] == false
+  end
+
+  test "rejects invalid options", %{spreadsheet: spreadsheet} do
+    assert {:error, "Unknown protection option 'format'"} =
+             UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{format: true})
+
+    assert {:error, "Option 'sort' must be a boolean"} =
+             UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{sort: "yes"})
+
+    assert {:error, "Unsupported hash algorithm 'MD5'; use SHA-256, SHA-384 or SHA-512"} =
+             UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+               password: "secret",
+               algorithm_name: "MD5"
+             })
+
+    assert {:error, "Options 'algorithm_name', 'salt_value' and 'spin_count' require 'password' or 'hash_value'"} =
+             UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Sheet1", %{
+               spin_count: 1000
+             })
+
+    assert {:error, "Sheet not found"} =
+             UmyaSpreadsheet.set_sheet_protection_options(spreadsheet, "Missing", %{sort: true})
+  end
+end
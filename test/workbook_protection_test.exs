@@ -81,5 +81,84 @@ defmodule UmyaSpreadsheetTest.WorkbookProtectionTest do
       # The API is correctly implemented, we verify that the is_workbook_protected functions works too
       assert UmyaSpreadsheet.is_workbook_protected(spreadsheet) == {:ok, false}
     end
+
+    test "set_workbook_protection_options sets lock flags and a hashed password", %{
+      spreadsheet: spreadsheet
+    } do
+      :ok =
+        UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{
+          lock_windows: true,
+          password: "secret"
+        })
+
+      assert {:ok,
+              %{
+                "lock_structure" => "true",
+                "lock_windows" => "true",
+                "lock_revision" => "false"
+              }} = UmyaSpreadsheet.get_workbook_protection_details(spreadsheet)
+
+      assert {:ok, true} = UmyaSpreadsheet.verify_workbook_password(spreadsheet, "secret")
+      assert {:ok, false} = UmyaSpreadsheet.verify_workbook_password(spreadsheet, "Secret")
+
+      # Options that are not given keep their current value
+      :ok = UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{lock_structure: false})
+
+      assert {:ok, %{"lock_structure" => "false", "lock_windows" => "true"}} =
+               UmyaSpreadsheet.get_workbook_protection_details(spreadsheet)
+
+      assert {:ok, true} = UmyaSpreadsheet.verify_workbook_password(spreadsheet, "secret")
+    end
+
+    test "workbook password hash parameters survive a round trip", %{spreadsheet: spreadsheet} do
+      :ok =
+        UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{
+          password: "secret",
+          algorithm_name: "SHA-384",
+          salt_value: Base.encode64(:binary.copy(<<9>>, 16)),
+          spin_count: 500
+        })
+
+      path = "test/result_files/workbook_protection_options.xlsx"
+      :ok = UmyaSpreadsheet.write(spreadsheet, path)
+      {:ok, reloaded} = UmyaSpreadsheet.read(path)
+      File.rm(path)
+
+      assert {:ok, true} = UmyaSpreadsheet.verify_workbook_password(reloaded, "secret")
+      assert {:ok, false} = UmyaSpreadsheet.verify_workbook_password(reloaded, "other")
+    end
+
+    test "verify_workbook_password checks the legacy password set by set_workbook_protection",
+         %{spreadsheet: spreadsheet} do
+      :ok = UmyaSpreadsheet.set_workbook_protection(spreadsheet, "test123")
+
+      assert {:ok, true} = UmyaSpreadsheet.verify_workbook_password(spreadsheet, "test123")
+      assert {:ok, false} = UmyaSpreadsheet.verify_workbook_password(spreadsheet, "test124")
+    end
+
+    test "remove_workbook_protection removes protection and password", %{
+      spreadsheet: spreadsheet
+    } do
+      :ok = UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{password: "secret"})
+      :ok = UmyaSpreadsheet.remove_workbook_protection(spreadsheet)
+
+      assert UmyaSpreadsheet.is_workbook_protected(spreadsheet) == {:ok, false}
+
+      assert {:error, "Workbook is not protected"} =
+               UmyaSpreadsheet.verify_workbook_password(spreadsheet, "secret")
+    end
+
+    test "set_workbook_protection_options rejects invalid options", %{spreadsheet: spreadsheet} do
+      assert {:error, "Unknown protection option 'lock_sheets'"} =
+               UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{lock_sheets: true})
+
+      assert {:error, "Options 'password' and 'hash_value' cannot be combined"} =
+               UmyaSpreadsheet.set_workbook_protection_options(spreadsheet, %{
+                 password: "secret",
+                 hash_value: "abc="
+               })
+
+      assert UmyaSpreadsheet.is_workbook_protected(spreadsheet) == {:ok, false}
+    end
   end
 end